@@ -0,0 +1,112 @@
+//! clap [Args](clap::Args) for overriding fields of the chain specification
+
+use clap::Args;
+use reth_primitives::{ChainSpec, ForkCondition, Hardfork, U256};
+use std::{str::FromStr, sync::Arc};
+
+/// Parameters overriding fields of the chain specification of `--chain`, e.g. to run a devnet off
+/// a base chain.
+#[derive(Debug, Args, PartialEq, Eq, Default, Clone)]
+#[command(next_help_heading = "Chain overrides")]
+pub struct ChainOverrideArgs {
+    /// Overrides the chain id.
+    #[arg(long = "chain.override.chain-id", value_name = "CHAIN_ID")]
+    pub chain_id: Option<u64>,
+
+    /// Overrides the activation of a fork, as `<FORK>=<ACTIVATION>`. Can be repeated.
+    ///
+    /// The activation of shanghai and cancun is a timestamp, of paris the terminal total
+    /// difficulty, and of the earlier forks a block number.
+    #[arg(
+        long = "chain.override.fork",
+        value_name = "FORK=ACTIVATION",
+        value_parser = parse_fork_override
+    )]
+    pub forks: Vec<(Hardfork, ForkCondition)>,
+}
+
+impl ChainOverrideArgs {
+    /// Applies the overrides on top of `chain`, which is returned as is if there are none.
+    pub fn apply(&self, chain: Arc<ChainSpec>) -> Arc<ChainSpec> {
+        if self.chain_id.is_none() && self.forks.is_empty() {
+            return chain
+        }
+
+        let mut chain = (*chain).clone();
+        if let Some(chain_id) = self.chain_id {
+            chain.chain = chain_id.into();
+        }
+        for (fork, condition) in &self.forks {
+            chain.set_fork(*fork, *condition);
+        }
+        Arc::new(chain)
+    }
+}
+
+/// Parses a fork override of `--chain.override.fork`.
+fn parse_fork_override(value: &str) -> eyre::Result<(Hardfork, ForkCondition)> {
+    let (fork, activation) = value
+        .split_once('=')
+        .ok_or_else(|| eyre::eyre!("Expected <FORK>=<ACTIVATION>, got {value}"))?;
+    let fork = Hardfork::from_str(fork).map_err(|err| eyre::eyre!(err))?;
+
+    let condition = match fork {
+        Hardfork::Shanghai | Hardfork::Cancun => ForkCondition::Timestamp(activation.parse()?),
+        Hardfork::Paris => {
+            ForkCondition::TTD { fork_block: None, total_difficulty: U256::from_str(activation)? }
+        }
+        _ => ForkCondition::Block(activation.parse()?),
+    };
+    Ok((fork, condition))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use reth_primitives::{Chain, MAINNET};
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn parse_chain_overrides() {
+        let args = CommandParser::<ChainOverrideArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, ChainOverrideArgs::default());
+        assert!(Arc::ptr_eq(&args.apply(MAINNET.clone()), &MAINNET));
+
+        let args = CommandParser::<ChainOverrideArgs>::parse_from([
+            "reth",
+            "--chain.override.chain-id",
+            "1337",
+            "--chain.override.fork",
+            "cancun=1800000000",
+            "--chain.override.fork",
+            "London=100",
+        ])
+        .args;
+        assert_eq!(
+            args.forks,
+            [
+                (Hardfork::Cancun, ForkCondition::Timestamp(1_800_000_000)),
+                (Hardfork::London, ForkCondition::Block(100)),
+            ]
+        );
+
+        let chain = args.apply(MAINNET.clone());
+        assert_eq!(chain.chain, Chain::from(1337));
+        assert_eq!(chain.fork(Hardfork::London), ForkCondition::Block(100));
+        assert!(chain.is_cancun_active_at_timestamp(1_800_000_000));
+
+        assert!(CommandParser::<ChainOverrideArgs>::try_parse_from([
+            "reth",
+            "--chain.override.fork",
+            "prague=1"
+        ])
+        .is_err());
+    }
+}
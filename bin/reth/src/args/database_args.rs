@@ -1,7 +1,10 @@
 //! clap [Args](clap::Args) for database configuration
 
 use clap::Args;
+use eyre::WrapErr;
+use reth_db::{init_db, mdbx::EnvKind, DatabaseEnv};
 use reth_interfaces::db::LogLevel;
+use std::path::Path;
 
 /// Parameters for database configuration
 #[derive(Debug, Args, PartialEq, Default, Clone, Copy)]
@@ -10,4 +13,77 @@ pub struct DatabaseArgs {
     /// Database logging level. Levels higher than "notice" require a debug build.
     #[arg(long = "db.log-level", value_enum)]
     pub log_level: Option<LogLevel>,
+
+    /// Opens the database read-only, so it can be inspected while a node uses it.
+    ///
+    /// The database isn't created if it's missing, and commands which write to it fail.
+    #[arg(long = "db.read-only", visible_alias = "read-only")]
+    pub read_only: bool,
+}
+
+impl DatabaseArgs {
+    /// Opens the database at `path`, read-only with `--db.read-only`, or with [`init_db`]
+    /// otherwise.
+    pub fn init_db(&self, path: impl AsRef<Path>) -> eyre::Result<DatabaseEnv> {
+        if !self.read_only {
+            return init_db(path, self.log_level)
+        }
+
+        // MDBX ignores the write map of read-only environments, so this can't write to the
+        // database even though it's of the read/write type.
+        let path = path.as_ref();
+        DatabaseEnv::open(path, EnvKind::RO, self.log_level)
+            .with_context(|| format!("Could not open database at path: {}", path.display()))
+    }
+
+    /// Fails if the database is opened read-only, for the commands which write to it.
+    pub fn ensure_writable(&self, command: &str) -> eyre::Result<()> {
+        if self.read_only {
+            eyre::bail!("{command} writes to the database, which --db.read-only doesn't allow.")
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use reth_db::{database::Database, tables, transaction::DbTxMut};
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn parse_read_only() {
+        let args = CommandParser::<DatabaseArgs>::parse_from(["reth"]).args;
+        assert!(!args.read_only);
+
+        let args = CommandParser::<DatabaseArgs>::parse_from(["reth", "--db.read-only"]).args;
+        assert!(args.read_only);
+        assert!(args.ensure_writable("db drop").is_err());
+
+        let args = CommandParser::<DatabaseArgs>::parse_from(["reth", "--read-only"]).args;
+        assert!(args.read_only);
+    }
+
+    #[test]
+    fn open_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let read_only = DatabaseArgs { read_only: true, ..Default::default() };
+        assert!(read_only.init_db(dir.path().join("missing")).is_err());
+
+        let db = DatabaseArgs::default().init_db(dir.path()).unwrap();
+        let reader = read_only.init_db(dir.path()).unwrap();
+        assert!(db
+            .tx_mut()
+            .unwrap()
+            .put::<tables::CanonicalHeaders>(0, Default::default())
+            .is_ok());
+        assert!(reader.tx_mut().is_err());
+    }
 }
@@ -1,24 +1,60 @@
 use clap::Args;
+use reth_primitives::U256;
+use reth_rpc::eth::gas_oracle::GasPriceOracleConfig;
 
 /// Parameters to configure Gas Price Oracle
+///
+/// The arguments which aren't passed default to the `[gas_price_oracle]` section of the config
+/// file.
 #[derive(Debug, Clone, Args, PartialEq, Eq, Default)]
 #[command(next_help_heading = "Gas Price Oracle")]
 pub struct GasPriceOracleArgs {
-    /// Number of recent blocks to check for gas price
-    #[arg(long = "gpo.blocks", default_value = "20")]
+    /// Number of recent blocks to check for gas price [default: 20]
+    #[arg(long = "gpo.blocks")]
     pub blocks: Option<u32>,
 
-    /// Gas Price below which gpo will ignore transactions
-    #[arg(long = "gpo.ignoreprice", default_value = "2")]
+    /// Gas Price below which gpo will ignore transactions [default: 2]
+    #[arg(long = "gpo.ignoreprice")]
     pub ignore_price: Option<u64>,
 
     /// Maximum transaction priority fee(or gasprice before London Fork) to be recommended by gpo
-    #[arg(long = "gpo.maxprice", default_value = "500000000000")]
+    /// [default: 500000000000]
+    #[arg(long = "gpo.maxprice")]
     pub max_price: Option<u64>,
 
-    /// The percentile of gas prices to use for the estimate
-    #[arg(long = "gpo.percentile", default_value = "60")]
+    /// The percentile of gas prices to use for the estimate [default: 60]
+    #[arg(long = "gpo.percentile")]
     pub percentile: Option<u32>,
+
+    /// The config the arguments are applied on top of.
+    #[arg(skip)]
+    pub config: GasPriceOracleConfig,
+}
+
+impl GasPriceOracleArgs {
+    /// Sets the config the arguments are applied on top of, e.g. the one of the config file.
+    pub fn with_config(mut self, config: GasPriceOracleConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Returns the config with the passed arguments applied.
+    pub fn gas_price_oracle_config(&self) -> GasPriceOracleConfig {
+        let mut config = self.config.clone();
+        if let Some(blocks) = self.blocks {
+            config.blocks = blocks;
+        }
+        if let Some(ignore_price) = self.ignore_price {
+            config.ignore_price = Some(U256::from(ignore_price));
+        }
+        if let Some(max_price) = self.max_price {
+            config.max_price = Some(U256::from(max_price));
+        }
+        if let Some(percentile) = self.percentile {
+            config.percentile = percentile;
+        }
+        config
+    }
 }
 
 #[cfg(test)]
@@ -36,13 +72,28 @@ mod tests {
     #[test]
     fn test_parse_gpo_args() {
         let args = CommandParser::<GasPriceOracleArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, GasPriceOracleArgs::default());
+        assert_eq!(args.gas_price_oracle_config(), GasPriceOracleConfig::default());
+    }
+
+    #[test]
+    fn gpo_args_override_config() {
+        let args =
+            CommandParser::<GasPriceOracleArgs>::parse_from(["reth", "--gpo.percentile", "50"])
+                .args
+                .with_config(GasPriceOracleConfig {
+                    blocks: 10,
+                    percentile: 70,
+                    default_reward_percentiles: vec![50],
+                    ..Default::default()
+                });
         assert_eq!(
-            args,
-            GasPriceOracleArgs {
-                blocks: Some(20),
-                ignore_price: Some(2),
-                max_price: Some(500000000000),
-                percentile: Some(60),
+            args.gas_price_oracle_config(),
+            GasPriceOracleConfig {
+                blocks: 10,
+                percentile: 50,
+                default_reward_percentiles: vec![50],
+                ..Default::default()
             }
         );
     }
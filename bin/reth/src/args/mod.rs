@@ -12,6 +12,10 @@ pub use rpc_server_args::RpcServerArgs;
 mod debug_args;
 pub use debug_args::DebugArgs;
 
+/// ChainOverrideArgs struct for overriding fields of the chain specification
+mod chain_override_args;
+pub use chain_override_args::ChainOverrideArgs;
+
 /// DatabaseArgs struct for configuring the database
 mod database_args;
 pub use database_args::DatabaseArgs;
@@ -6,15 +6,20 @@ use clap::{
     builder::{RangedU64ValueParser, TypedValueParser},
     Arg, Args, Command,
 };
-use reth_primitives::constants::MAXIMUM_EXTRA_DATA_SIZE;
+use reth_basic_payload_builder::PayloadOrdering;
+use reth_config::config::BuilderConfig;
+use reth_primitives::{constants::MAXIMUM_EXTRA_DATA_SIZE, Address};
 use std::{borrow::Cow, ffi::OsStr, time::Duration};
 
 /// Parameters for configuring the Payload Builder
+///
+/// The extra data, fee recipient, minimum tip and ordering default to the `[builder]` section of
+/// the config file if not passed.
 #[derive(Debug, Args, PartialEq, Default)]
 pub struct PayloadBuilderArgs {
-    /// Block extra data set by the payload builder.
-    #[arg(long = "builder.extradata", help_heading = "Builder", value_parser=ExtradataValueParser::default(),  default_value_t = default_extradata())]
-    pub extradata: String,
+    /// Block extra data set by the payload builder [default: the client version]
+    #[arg(long = "builder.extradata", help_heading = "Builder", value_parser=ExtradataValueParser::default())]
+    pub extradata: Option<String>,
 
     /// Target gas ceiling for built blocks.
     #[arg(
@@ -36,11 +41,41 @@ pub struct PayloadBuilderArgs {
     /// Maximum number of tasks to spawn for building a payload.
     #[arg(long = "builder.max-tasks", help_heading = "Builder", default_value = "3", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
     pub max_payload_tasks: usize,
+
+    /// The fee recipient of the payloads requested with the zero address as their suggested fee
+    /// recipient.
+    #[arg(long = "builder.fee-recipient", help_heading = "Builder", value_name = "ADDRESS")]
+    pub fee_recipient: Option<Address>,
+
+    /// The minimum effective tip per gas, in wei, of the transactions included in built blocks
+    /// [default: 0]
+    #[arg(long = "builder.min-tip", help_heading = "Builder", value_name = "WEI")]
+    pub min_tip: Option<u64>,
+
+    /// The order in which built blocks include the transactions of the pool: `greedy` by tip per
+    /// gas, or `profit` by the total fees of the transactions [default: greedy]
+    #[arg(long = "builder.ordering", help_heading = "Builder", value_name = "ORDERING")]
+    pub ordering: Option<PayloadOrdering>,
+
+    /// The config the arguments are applied on top of.
+    #[arg(skip)]
+    pub config: BuilderConfig,
+}
+
+impl PayloadBuilderArgs {
+    /// Sets the config the arguments are applied on top of, e.g. the one of the config file.
+    pub fn with_config(mut self, config: BuilderConfig) -> Self {
+        self.config = config;
+        self
+    }
 }
 
 impl PayloadBuilderConfig for PayloadBuilderArgs {
     fn extradata(&self) -> Cow<'_, str> {
-        self.extradata.as_str().into()
+        match self.extradata.as_deref().or(self.config.extradata.as_deref()) {
+            Some(extradata) => extradata.into(),
+            None => default_extradata().into(),
+        }
     }
 
     fn interval(&self) -> Duration {
@@ -58,6 +93,18 @@ impl PayloadBuilderConfig for PayloadBuilderArgs {
     fn max_payload_tasks(&self) -> usize {
         self.max_payload_tasks
     }
+
+    fn fee_recipient(&self) -> Option<Address> {
+        self.fee_recipient.or(self.config.fee_recipient)
+    }
+
+    fn min_tip(&self) -> u128 {
+        self.min_tip.unwrap_or(self.config.min_tip) as u128
+    }
+
+    fn ordering(&self) -> PayloadOrdering {
+        self.ordering.unwrap_or(self.config.ordering)
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -126,7 +173,9 @@ mod tests {
             extradata.as_str(),
         ])
         .args;
-        assert_eq!(args.extradata, extradata);
+        assert_eq!(args.extradata, Some(extradata.clone()));
+        assert_eq!(args.extradata(), extradata);
+        assert_eq!(PayloadBuilderArgs::default().extradata(), extradata);
     }
 
     #[test]
@@ -139,4 +188,41 @@ mod tests {
         ]);
         assert!(args.is_err());
     }
+
+    #[test]
+    fn test_args_override_config() {
+        let config_recipient: Address =
+            "0x0000000000000000000000000000000000000001".parse().unwrap();
+        let args_recipient: Address = "0x0000000000000000000000000000000000000002".parse().unwrap();
+        let config = BuilderConfig {
+            extradata: Some("config".to_string()),
+            fee_recipient: Some(config_recipient),
+            min_tip: 1,
+            ordering: PayloadOrdering::Profit,
+        };
+
+        let args = PayloadBuilderArgs::default().with_config(config.clone());
+        assert_eq!(args.extradata(), "config");
+        assert_eq!(args.fee_recipient(), Some(config_recipient));
+        assert_eq!(args.min_tip(), 1);
+        assert_eq!(args.ordering(), PayloadOrdering::Profit);
+
+        let args = CommandParser::<PayloadBuilderArgs>::parse_from([
+            "reth",
+            "--builder.extradata",
+            "args",
+            "--builder.fee-recipient",
+            "0x0000000000000000000000000000000000000002",
+            "--builder.min-tip",
+            "2",
+            "--builder.ordering",
+            "greedy",
+        ])
+        .args
+        .with_config(config);
+        assert_eq!(args.extradata(), "args");
+        assert_eq!(args.fee_recipient(), Some(args_recipient));
+        assert_eq!(args.min_tip(), 2);
+        assert_eq!(args.ordering(), PayloadOrdering::Greedy);
+    }
 }
@@ -15,12 +15,65 @@ pub struct PruningArgs {
     /// This flag takes priority over pruning configuration in reth.toml.
     #[arg(long, default_value_t = false)]
     pub full: bool,
+
+    /// Minimum pruning interval measured in blocks.
+    #[arg(long = "prune.interval", value_name = "BLOCKS")]
+    pub block_interval: Option<usize>,
+
+    /// Pruning of the senders of transactions: `full`, the number of most recent blocks to keep,
+    /// or `before:<BLOCK>` to prune the blocks before the given one.
+    #[arg(long = "prune.senderrecovery", value_name = "MODE", value_parser = parse_prune_mode)]
+    pub sender_recovery: Option<PruneMode>,
+
+    /// Pruning of the transaction lookup: `full`, the number of most recent blocks to keep, or
+    /// `before:<BLOCK>` to prune the blocks before the given one.
+    #[arg(long = "prune.txlookup", value_name = "MODE", value_parser = parse_prune_mode)]
+    pub transaction_lookup: Option<PruneMode>,
+
+    /// Pruning of the receipts: the number of most recent blocks to keep, or `before:<BLOCK>` to
+    /// prune the blocks before the given one. At least [`MINIMUM_PRUNING_DISTANCE`] blocks are
+    /// kept.
+    #[arg(long = "prune.receipts", value_name = "MODE", value_parser = parse_min_distance_prune_mode)]
+    pub receipts: Option<PruneMode>,
+
+    /// Pruning of the account history: the number of most recent blocks to keep, or
+    /// `before:<BLOCK>` to prune the blocks before the given one. At least
+    /// [`MINIMUM_PRUNING_DISTANCE`] blocks are kept.
+    #[arg(long = "prune.accounthistory", value_name = "MODE", value_parser = parse_min_distance_prune_mode)]
+    pub account_history: Option<PruneMode>,
+
+    /// Pruning of the storage history: the number of most recent blocks to keep, or
+    /// `before:<BLOCK>` to prune the blocks before the given one. At least
+    /// [`MINIMUM_PRUNING_DISTANCE`] blocks are kept.
+    #[arg(long = "prune.storagehistory", value_name = "MODE", value_parser = parse_min_distance_prune_mode)]
+    pub storage_history: Option<PruneMode>,
+
+    /// The number of most recent blocks whose historical state is kept, at least
+    /// [`MINIMUM_PRUNING_DISTANCE`]. Requests for the state at older blocks fail with the earliest
+    /// block whose state is available.
+    ///
+    /// Sets the pruning of both the account and the storage history.
+    #[arg(
+        long = "history.state",
+        value_name = "BLOCKS",
+        value_parser = clap::value_parser!(u64).range(MINIMUM_PRUNING_DISTANCE..),
+        conflicts_with_all = ["account_history", "storage_history"],
+        verbatim_doc_comment
+    )]
+    pub history_state: Option<u64>,
 }
 
 impl PruningArgs {
-    /// Returns pruning configuration.
-    pub fn prune_config(&self, chain_spec: Arc<ChainSpec>) -> eyre::Result<Option<PruneConfig>> {
-        Ok(if self.full {
+    /// Returns pruning configuration, given the one of reth.toml.
+    ///
+    /// `--full` replaces the configuration of reth.toml, while the flags of single segments
+    /// override its segments. `--history.state` overrides the history segments of both.
+    pub fn prune_config(
+        &self,
+        chain_spec: Arc<ChainSpec>,
+        config: Option<&PruneConfig>,
+    ) -> eyre::Result<Option<PruneConfig>> {
+        let mut config = if self.full {
             Some(PruneConfig {
                 block_interval: 5,
                 segments: PruneModes {
@@ -42,8 +95,178 @@ impl PruningArgs {
                     ),
                 },
             })
+        } else if self.overrides_segments() {
+            let mut config = config.cloned().unwrap_or_default();
+            if let Some(block_interval) = self.block_interval {
+                config.block_interval = block_interval;
+            }
+            let segments = &mut config.segments;
+            for (segment, mode) in [
+                (&mut segments.sender_recovery, self.sender_recovery),
+                (&mut segments.transaction_lookup, self.transaction_lookup),
+                (&mut segments.receipts, self.receipts),
+                (&mut segments.account_history, self.account_history),
+                (&mut segments.storage_history, self.storage_history),
+            ] {
+                if mode.is_some() {
+                    *segment = mode;
+                }
+            }
+            Some(config)
         } else {
-            None
-        })
+            config.cloned()
+        };
+
+        if let Some(blocks) = self.history_state {
+            let segments = &mut config.get_or_insert_with(Default::default).segments;
+            segments.account_history = Some(PruneMode::Distance(blocks));
+            segments.storage_history = Some(PruneMode::Distance(blocks));
+        }
+
+        Ok(config)
+    }
+
+    /// Returns `true` if any of the pruning configuration of reth.toml is overridden.
+    fn overrides_segments(&self) -> bool {
+        self.block_interval.is_some() ||
+            [
+                self.sender_recovery,
+                self.transaction_lookup,
+                self.receipts,
+                self.account_history,
+                self.storage_history,
+            ]
+            .iter()
+            .any(Option::is_some)
+    }
+}
+
+/// Parses a [PruneMode]: `full`, a distance in blocks from the tip, or `before:<BLOCK>`.
+fn parse_prune_mode(value: &str) -> eyre::Result<PruneMode> {
+    Ok(match value {
+        "full" => PruneMode::Full,
+        _ => match value.strip_prefix("before:") {
+            Some(block) => PruneMode::Before(block.parse()?),
+            None => PruneMode::Distance(value.parse()?),
+        },
+    })
+}
+
+/// Parses a [PruneMode] which leaves at least [`MINIMUM_PRUNING_DISTANCE`] blocks in the
+/// database, for the segments which the state of the recent blocks is read from.
+fn parse_min_distance_prune_mode(value: &str) -> eyre::Result<PruneMode> {
+    match parse_prune_mode(value)? {
+        PruneMode::Full => {
+            eyre::bail!("Expected a prune mode that leaves at least {MINIMUM_PRUNING_DISTANCE} blocks in the database, got `full`.")
+        }
+        PruneMode::Distance(distance) if distance < MINIMUM_PRUNING_DISTANCE => {
+            eyre::bail!("Expected a prune mode that leaves at least {MINIMUM_PRUNING_DISTANCE} blocks in the database, got a distance of {distance}.")
+        }
+        mode => Ok(mode),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use reth_primitives::MAINNET;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn prune_segment_args() {
+        let args = CommandParser::<PruningArgs>::parse_from([
+            "reth",
+            "--prune.txlookup",
+            "full",
+            "--prune.accounthistory",
+            "100000",
+            "--prune.receipts",
+            "before:11052984",
+        ])
+        .args;
+        let config = PruneConfig {
+            block_interval: 10,
+            segments: PruneModes {
+                sender_recovery: Some(PruneMode::Full),
+                account_history: Some(PruneMode::Distance(MINIMUM_PRUNING_DISTANCE)),
+                ..PruneModes::none()
+            },
+        };
+
+        let config = args.prune_config(MAINNET.clone(), Some(&config)).unwrap().unwrap();
+        assert_eq!(config.block_interval, 10);
+        assert_eq!(
+            config.segments,
+            PruneModes {
+                sender_recovery: Some(PruneMode::Full),
+                transaction_lookup: Some(PruneMode::Full),
+                receipts: Some(PruneMode::Before(11052984)),
+                account_history: Some(PruneMode::Distance(100000)),
+                ..PruneModes::none()
+            }
+        );
+
+        for history in ["full", "10"] {
+            assert!(CommandParser::<PruningArgs>::try_parse_from([
+                "reth",
+                "--prune.storagehistory",
+                history
+            ])
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn history_state_args() {
+        let args = CommandParser::<PruningArgs>::parse_from([
+            "reth",
+            "--full",
+            "--history.state",
+            "90000",
+        ])
+        .args;
+        let config = args.prune_config(MAINNET.clone(), None).unwrap().unwrap();
+        assert_eq!(config.segments.account_history, Some(PruneMode::Distance(90000)));
+        assert_eq!(config.segments.storage_history, Some(PruneMode::Distance(90000)));
+        assert_eq!(config.segments.sender_recovery, Some(PruneMode::Full));
+
+        let args =
+            CommandParser::<PruningArgs>::parse_from(["reth", "--history.state", "90000"]).args;
+        let config = args.prune_config(MAINNET.clone(), None).unwrap().unwrap();
+        assert_eq!(
+            config.segments,
+            PruneModes {
+                account_history: Some(PruneMode::Distance(90000)),
+                storage_history: Some(PruneMode::Distance(90000)),
+                ..PruneModes::none()
+            }
+        );
+
+        assert!(CommandParser::<PruningArgs>::try_parse_from(["reth", "--history.state", "10"])
+            .is_err());
+        assert!(CommandParser::<PruningArgs>::try_parse_from([
+            "reth",
+            "--history.state",
+            "90000",
+            "--prune.accounthistory",
+            "90000"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn prune_config_without_args() {
+        let args = CommandParser::<PruningArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.prune_config(MAINNET.clone(), None).unwrap(), None);
+
+        let config = PruneConfig::default();
+        assert_eq!(args.prune_config(MAINNET.clone(), Some(&config)).unwrap(), Some(config));
     }
 }
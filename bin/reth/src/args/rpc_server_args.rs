@@ -16,7 +16,7 @@ use futures::TryFutureExt;
 use reth_network_api::{NetworkInfo, Peers};
 use reth_provider::{
     AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader,
-    EvmEnvProvider, HeaderProvider, StateProviderFactory,
+    EvmEnvProvider, HeaderProvider, StageCheckpointReader, StateProviderFactory,
 };
 use reth_rpc::{
     eth::{
@@ -108,6 +108,10 @@ pub struct RpcServerArgs {
     #[arg(long, default_value_t = constants::DEFAULT_IPC_ENDPOINT.to_string())]
     pub ipcpath: String,
 
+    /// Rpc Modules to be configured for the IPC server, all of them if not passed
+    #[arg(long = "ipc.api", value_parser = RpcModuleSelectionValueParser::default())]
+    pub ipc_api: Option<RpcModuleSelection>,
+
     /// Auth server address to listen on
     #[arg(long = "authrpc.addr", default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
     pub auth_addr: IpAddr,
@@ -154,6 +158,13 @@ pub struct RpcServerArgs {
     )]
     pub rpc_gas_cap: u64,
 
+    /// Allow `newPendingTransactions` subscriptions to stream full transaction objects.
+    ///
+    /// Every such subscriber receives every transaction entering the pool, so it's disabled by
+    /// default.
+    #[arg(long)]
+    pub rpc_full_pending_txs: bool,
+
     /// Gas price oracle configuration.
     #[clap(flatten)]
     pub gas_price_oracle: GasPriceOracleArgs,
@@ -255,6 +266,7 @@ impl RpcServerArgs {
             + EvmEnvProvider
             + ChainSpecProvider
             + ChangeSetReader
+            + StageCheckpointReader
             + Clone
             + Unpin
             + 'static,
@@ -328,6 +340,7 @@ impl RethRpcConfig for RpcServerArgs {
             .max_tracing_requests(self.rpc_max_tracing_requests)
             .max_logs_per_response(self.rpc_max_logs_per_response)
             .rpc_gas_cap(self.rpc_gas_cap)
+            .full_pending_transactions(self.rpc_full_pending_txs)
             .gpo_config(self.gas_price_oracle_config())
     }
 
@@ -340,12 +353,7 @@ impl RethRpcConfig for RpcServerArgs {
     }
 
     fn gas_price_oracle_config(&self) -> GasPriceOracleConfig {
-        GasPriceOracleConfig::new(
-            self.gas_price_oracle.blocks,
-            self.gas_price_oracle.ignore_price,
-            self.gas_price_oracle.max_price,
-            self.gas_price_oracle.percentile,
-        )
+        self.gas_price_oracle.gas_price_oracle_config()
     }
 
     fn transport_rpc_module_config(&self) -> TransportRpcModuleConfig {
@@ -369,7 +377,11 @@ impl RethRpcConfig for RpcServerArgs {
         }
 
         if self.is_ipc_enabled() {
-            config = config.with_ipc(RpcModuleSelection::default_ipc_modules());
+            config = config.with_ipc(
+                self.ipc_api
+                    .clone()
+                    .unwrap_or_else(|| RpcModuleSelection::default_ipc_modules().into()),
+            );
         }
 
         config
@@ -501,6 +513,16 @@ mod tests {
         assert!(args.is_err());
     }
 
+    #[test]
+    fn test_rpc_full_pending_txs() {
+        let args = CommandParser::<RpcServerArgs>::parse_from(["reth"]).args;
+        assert!(!args.eth_config().full_pending_transactions);
+
+        let args =
+            CommandParser::<RpcServerArgs>::parse_from(["reth", "--rpc-full-pending-txs"]).args;
+        assert!(args.eth_config().full_pending_transactions);
+    }
+
     #[test]
     fn test_rpc_server_args_parser() {
         let args =
@@ -551,6 +573,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ipc_rpc_modules() {
+        let args = CommandParser::<RpcServerArgs>::parse_from(["reth"]).args;
+        let config = args.transport_rpc_module_config();
+        assert_eq!(
+            config.ipc().cloned().unwrap().into_selection(),
+            RpcModuleSelection::default_ipc_modules()
+        );
+
+        let args =
+            CommandParser::<RpcServerArgs>::parse_from(["reth", "--ipc.api", "eth,net"]).args;
+        let config = args.transport_rpc_module_config();
+        let expected = vec![RethRpcModule::Eth, RethRpcModule::Net];
+        assert_eq!(config.ipc().cloned().unwrap().into_selection(), expected);
+
+        let args = CommandParser::<RpcServerArgs>::parse_from([
+            "reth",
+            "--ipcdisable",
+            "--ipc.api",
+            "eth",
+        ])
+        .args;
+        assert!(args.transport_rpc_module_config().ipc().is_none());
+    }
+
     #[test]
     fn test_unique_rpc_modules() {
         let args = CommandParser::<RpcServerArgs>::parse_from([
@@ -1,10 +1,12 @@
 //! Clap parser utilities
 
 use reth_primitives::{
-    fs, AllGenesisFormats, BlockHashOrNumber, ChainSpec, B256, DEV, GOERLI, HOLESKY, MAINNET,
-    SEPOLIA,
+    AllGenesisFormats, BlockHashOrNumber, ChainSpec, B256, DEV, GOERLI, HOLESKY, MAINNET, SEPOLIA,
 };
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
 use std::{
+    io,
     net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs},
     path::PathBuf,
     str::FromStr,
@@ -18,8 +20,28 @@ pub fn parse_duration_from_secs(arg: &str) -> eyre::Result<Duration, std::num::P
     Ok(Duration::from_secs(seconds))
 }
 
-/// Clap value parser for [ChainSpec]s that takes either a built-in chainspec or the path
-/// to a custom one.
+/// Name of the file holding the [ChainSpec] of a database folder, written by `reth stage dump`.
+///
+/// Both chain value parsers accept such a folder, so that it's loaded with the chain it was
+/// created for.
+pub const EMBEDDED_CHAIN_SPEC_FILE: &str = "chainspec.json";
+
+/// Prefix of the chain specifications which both chain value parsers download instead of reading
+/// them from a file.
+const CHAIN_SPEC_URL_PREFIX: &str = "https://";
+
+/// Prefix of the fragment of a chain specification URL which pins the SHA-256 checksum of the
+/// file, e.g. `https://example.com/genesis.json#sha256=<CHECKSUM>`.
+const CHAIN_SPEC_CHECKSUM_PREFIX: &str = "sha256=";
+
+/// Timeout of the download of a chain specification.
+const CHAIN_SPEC_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The most redirects followed while downloading a chain specification.
+const CHAIN_SPEC_MAX_REDIRECTS: usize = 5;
+
+/// Clap value parser for [ChainSpec]s that takes either a built-in chainspec, the path
+/// to a custom one, or an `https://` URL of a custom one.
 pub fn chain_spec_value_parser(s: &str) -> eyre::Result<Arc<ChainSpec>, eyre::Error> {
     Ok(match s {
         "mainnet" => MAINNET.clone(),
@@ -27,15 +49,20 @@ pub fn chain_spec_value_parser(s: &str) -> eyre::Result<Arc<ChainSpec>, eyre::Er
         "sepolia" => SEPOLIA.clone(),
         "holesky" => HOLESKY.clone(),
         "dev" => DEV.clone(),
+        _ if s.starts_with(CHAIN_SPEC_URL_PREFIX) => download_chain_spec_file(s)?,
         _ => {
-            let raw = fs::read_to_string(PathBuf::from(shellexpand::full(s)?.into_owned()))?;
-            serde_json::from_str(&raw)?
+            let path = PathBuf::from(shellexpand::full(s)?.into_owned());
+            if path.is_dir() {
+                read_chain_spec_file(path.join(EMBEDDED_CHAIN_SPEC_FILE))?
+            } else {
+                read_chain_spec_file(path)?
+            }
         }
     })
 }
 
-/// Clap value parser for [ChainSpec]s that takes either a built-in genesis format or the path
-/// to a custom one.
+/// Clap value parser for [ChainSpec]s that takes either a built-in genesis format, the path
+/// to a custom one, or an `https://` URL of a custom one.
 pub fn genesis_value_parser(s: &str) -> eyre::Result<Arc<ChainSpec>, eyre::Error> {
     Ok(match s {
         "mainnet" => MAINNET.clone(),
@@ -43,14 +70,219 @@ pub fn genesis_value_parser(s: &str) -> eyre::Result<Arc<ChainSpec>, eyre::Error
         "sepolia" => SEPOLIA.clone(),
         "holesky" => HOLESKY.clone(),
         "dev" => DEV.clone(),
+        _ if s.starts_with(CHAIN_SPEC_URL_PREFIX) => {
+            let genesis: AllGenesisFormats = download_chain_spec_file(s)?;
+            Arc::new(genesis.into())
+        }
         _ => {
-            let raw = fs::read_to_string(PathBuf::from(shellexpand::full(s)?.into_owned()))?;
-            let genesis: AllGenesisFormats = serde_json::from_str(&raw)?;
+            let path = PathBuf::from(shellexpand::full(s)?.into_owned());
+            // The embedded file is always in the reth format, which the untagged
+            // `AllGenesisFormats` would misread as an empty geth genesis.
+            if path.is_dir() {
+                return Ok(Arc::new(read_chain_spec_file(path.join(EMBEDDED_CHAIN_SPEC_FILE))?))
+            }
+            let genesis: AllGenesisFormats = read_chain_spec_file(path)?;
             Arc::new(genesis.into())
         }
     })
 }
 
+/// Error thrown while reading a chain specification file.
+///
+/// Every variant holds the absolute path of the file, or the URL of a downloaded one.
+#[derive(thiserror::Error, Debug)]
+pub enum ChainSpecFileError {
+    /// The file does not exist
+    #[error("Chain specification file not found: {0}")]
+    NotFound(PathBuf),
+    /// The file can't be read by the current user
+    #[error("Permission denied reading chain specification file: {0}")]
+    PermissionDenied(PathBuf),
+    /// Failed to read the file
+    #[error("Could not read chain specification file {path}: {source}")]
+    Io {
+        /// The path of the file
+        path: PathBuf,
+        /// The underlying error
+        source: io::Error,
+    },
+    /// The file is not valid JSON
+    #[error(
+        "Invalid JSON in chain specification file {path} at line {line}, column {column}: {source}"
+    )]
+    InvalidJson {
+        /// The path of the file
+        path: PathBuf,
+        /// The line of the error
+        line: usize,
+        /// The column of the error
+        column: usize,
+        /// The underlying error
+        source: serde_json::Error,
+    },
+    /// The file is valid JSON, but not a chain specification
+    #[error("Invalid chain specification in {path} at line {line}, column {column}: {source}")]
+    Schema {
+        /// The path of the file
+        path: PathBuf,
+        /// The line of the error
+        line: usize,
+        /// The column of the error
+        column: usize,
+        /// The underlying error
+        source: serde_json::Error,
+    },
+    /// The fragment of the URL is not a `sha256=<CHECKSUM>` pin
+    #[error("Invalid checksum pin of chain specification URL {0}, expected #sha256=<CHECKSUM>")]
+    InvalidChecksumPin(String),
+    /// Failed to download the file
+    #[error("Could not download chain specification file {url}: {reason}")]
+    Download {
+        /// The URL of the file
+        url: String,
+        /// Why the download failed
+        reason: String,
+    },
+    /// The downloaded file doesn't match the pinned checksum
+    #[error(
+        "Checksum mismatch of chain specification file {url}: expected {expected}, got {actual}"
+    )]
+    ChecksumMismatch {
+        /// The URL of the file
+        url: String,
+        /// The pinned checksum
+        expected: B256,
+        /// The checksum of the downloaded file
+        actual: B256,
+    },
+}
+
+/// Reads and deserializes a chain specification file, classifying the failure.
+fn read_chain_spec_file<T: DeserializeOwned>(path: PathBuf) -> Result<T, ChainSpecFileError> {
+    let path = absolute_path(path);
+
+    let raw = std::fs::read_to_string(&path).map_err(|source| match source.kind() {
+        io::ErrorKind::NotFound => ChainSpecFileError::NotFound(path.clone()),
+        io::ErrorKind::PermissionDenied => ChainSpecFileError::PermissionDenied(path.clone()),
+        _ => ChainSpecFileError::Io { path: path.clone(), source },
+    })?;
+
+    parse_chain_spec_file(&raw, path)
+}
+
+/// Downloads and deserializes a chain specification file, checking it against the checksum pinned
+/// by the fragment of the URL, if any.
+fn download_chain_spec_file<T: DeserializeOwned>(url: &str) -> Result<T, ChainSpecFileError> {
+    let (url, checksum) = match url.split_once('#') {
+        Some((location, pin)) => {
+            let checksum = pin
+                .strip_prefix(CHAIN_SPEC_CHECKSUM_PREFIX)
+                .and_then(|checksum| B256::from_str(checksum).ok())
+                .ok_or_else(|| ChainSpecFileError::InvalidChecksumPin(url.to_string()))?;
+            (location, Some(checksum))
+        }
+        None => (url, None),
+    };
+
+    let raw = download(url).map_err(|err| ChainSpecFileError::Download {
+        url: url.to_string(),
+        reason: format!("{err:#}"),
+    })?;
+    if let Some(expected) = checksum {
+        let actual = B256::from_slice(&Sha256::digest(&raw));
+        if actual != expected {
+            return Err(ChainSpecFileError::ChecksumMismatch {
+                url: url.to_string(),
+                expected,
+                actual,
+            })
+        }
+    }
+
+    let raw = String::from_utf8(raw).map_err(|err| ChainSpecFileError::Download {
+        url: url.to_string(),
+        reason: err.to_string(),
+    })?;
+    parse_chain_spec_file(&raw, PathBuf::from(url))
+}
+
+/// Downloads the body of `url` over HTTPS, following redirects.
+fn download(url: &str) -> eyre::Result<Vec<u8>> {
+    let mut uri: hyper::Uri = url.parse()?;
+
+    // Value parsers run before the runtime of the command exists, or within it in tests, so the
+    // download gets a runtime of its own.
+    let download = std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        runtime.block_on(async move {
+            let connector = hyper_rustls::HttpsConnectorBuilder::new()
+                .with_native_roots()
+                .https_only()
+                .enable_http1()
+                .build();
+            let client = hyper::Client::builder().build::<_, hyper::Body>(connector);
+
+            tokio::time::timeout(CHAIN_SPEC_DOWNLOAD_TIMEOUT, async move {
+                for _ in 0..=CHAIN_SPEC_MAX_REDIRECTS {
+                    let response = client.get(uri.clone()).await?;
+                    let status = response.status();
+                    if status.is_redirection() {
+                        let location = response
+                            .headers()
+                            .get(hyper::header::LOCATION)
+                            .ok_or_else(|| eyre::eyre!("redirect without a location"))?;
+                        let location: hyper::Uri = location.to_str()?.parse()?;
+                        uri = if location.authority().is_some() {
+                            location
+                        } else {
+                            let mut parts = uri.into_parts();
+                            parts.path_and_query = location.path_and_query().cloned();
+                            hyper::Uri::from_parts(parts)?
+                        };
+                        continue
+                    }
+                    if !status.is_success() {
+                        eyre::bail!("server responded with {status}")
+                    }
+                    return Ok(hyper::body::to_bytes(response.into_body()).await?.to_vec())
+                }
+                eyre::bail!("more than {CHAIN_SPEC_MAX_REDIRECTS} redirects")
+            })
+            .await
+            .map_err(|_| eyre::eyre!("timed out after {CHAIN_SPEC_DOWNLOAD_TIMEOUT:?}"))?
+        })
+    });
+    download.join().map_err(|_| eyre::eyre!("download thread panicked"))?
+}
+
+/// Deserializes the contents of a chain specification file, classifying the failure.
+fn parse_chain_spec_file<T: DeserializeOwned>(
+    raw: &str,
+    path: PathBuf,
+) -> Result<T, ChainSpecFileError> {
+    serde_json::from_str(raw).map_err(|source| {
+        let (line, column) = (source.line(), source.column());
+        match source.classify() {
+            serde_json::error::Category::Data => {
+                ChainSpecFileError::Schema { path, line, column, source }
+            }
+            serde_json::error::Category::Io |
+            serde_json::error::Category::Syntax |
+            serde_json::error::Category::Eof => {
+                ChainSpecFileError::InvalidJson { path, line, column, source }
+            }
+        }
+    })
+}
+
+/// Resolves a relative path against the current directory, without requiring it to exist.
+fn absolute_path(path: PathBuf) -> PathBuf {
+    if path.is_absolute() {
+        return path
+    }
+    std::env::current_dir().map(|dir| dir.join(&path)).unwrap_or(path)
+}
+
 /// Parse [BlockHashOrNumber]
 pub fn hash_or_num_value_parser(value: &str) -> eyre::Result<BlockHashOrNumber, eyre::Error> {
     match B256::from_str(value) {
@@ -118,6 +350,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_chain_spec_file_errors() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let missing = dir.path().join("missing.json");
+        assert!(matches!(
+            read_chain_spec_file::<AllGenesisFormats>(missing.clone()),
+            Err(ChainSpecFileError::NotFound(path)) if path == missing
+        ));
+
+        let invalid = dir.path().join("invalid.json");
+        std::fs::write(&invalid, "{\n  \"nonce\": \"0x0\",\n  oops\n}").unwrap();
+        assert!(matches!(
+            read_chain_spec_file::<AllGenesisFormats>(invalid),
+            Err(ChainSpecFileError::InvalidJson { line: 3, .. })
+        ));
+
+        let schema = dir.path().join("schema.json");
+        std::fs::write(&schema, "\"mainnet\"").unwrap();
+        assert!(matches!(
+            read_chain_spec_file::<ChainSpec>(schema),
+            Err(ChainSpecFileError::Schema { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn parse_chain_spec_url_pins() {
+        for url in [
+            "https://example.com/genesis.json#md5=00",
+            "https://example.com/genesis.json#sha256=nothex",
+        ] {
+            assert!(matches!(
+                download_chain_spec_file::<ChainSpec>(url),
+                Err(ChainSpecFileError::InvalidChecksumPin(pinned)) if pinned == url
+            ));
+        }
+        assert!(genesis_value_parser("https://example.com/genesis.json#sha256=0").is_err());
+    }
+
     #[test]
     fn parse_socket_addresses() {
         for value in ["localhost:9000", ":9000", "9000"] {
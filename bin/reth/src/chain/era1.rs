@@ -0,0 +1,353 @@
+//! Reading and writing of era1 archives, the format clients distribute pre-merge history in.
+//!
+//! An era1 archive is an e2store file, i.e. a sequence of `type || length || reserved || data`
+//! entries, holding the blocks of one epoch of [`EPOCH_SIZE`] blocks:
+//!
+//! `Version || (CompressedHeader || CompressedBody || CompressedReceipts || TotalDifficulty)* ||
+//! Accumulator || BlockIndex`
+//!
+//! Headers, bodies and receipts are snappy framed rlp. The accumulator is the SSZ root of the
+//! hashes and total difficulties of the blocks, which archives are checked against, and the block
+//! index holds the offsets of the blocks in the file.
+use alloy_rlp::{Decodable, Encodable};
+use eyre::{eyre, WrapErr};
+use reth_primitives::{
+    hex, proofs::calculate_receipt_root, Block, BlockBody, Header, ReceiptWithBloom, B256, U256,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    io::{Read, Write},
+    path::Path,
+};
+
+/// The number of blocks of an epoch, i.e. the most an era1 archive holds.
+pub(crate) const EPOCH_SIZE: u64 = 8192;
+
+/// The extension of era1 archives.
+pub(crate) const ERA1_EXTENSION: &str = "era1";
+
+const VERSION: u16 = 0x3265;
+const COMPRESSED_HEADER: u16 = 0x03;
+const COMPRESSED_BODY: u16 = 0x04;
+const COMPRESSED_RECEIPTS: u16 = 0x05;
+const TOTAL_DIFFICULTY: u16 = 0x06;
+const ACCUMULATOR: u16 = 0x07;
+const BLOCK_INDEX: u16 = 0x3266;
+
+/// Size of the `type || length || reserved` header of an entry.
+const ENTRY_HEADER_SIZE: usize = 8;
+
+/// A block of an era1 archive, with its receipts and the total difficulty of the chain up to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Era1Block {
+    pub(crate) block: Block,
+    pub(crate) receipts: Vec<ReceiptWithBloom>,
+    pub(crate) total_difficulty: U256,
+}
+
+/// The name of the era1 archive of `epoch` on `network` with the accumulator `root`, as used by
+/// all clients, e.g. `mainnet-00000-5ec1ffb8.era1`.
+pub(crate) fn file_name(network: &str, epoch: u64, root: B256) -> String {
+    format!("{network}-{epoch:05}-{}.{ERA1_EXTENSION}", hex::encode(&root[..4]))
+}
+
+/// Writes `blocks` to a new era1 archive at `path` and returns its accumulator root.
+///
+/// The blocks have to be consecutive and at most an epoch.
+pub(crate) fn write_era1(path: &Path, blocks: &[Era1Block]) -> eyre::Result<B256> {
+    let Some(first) = blocks.first() else { eyre::bail!("An era1 archive can't be empty.") };
+    if blocks.len() as u64 > EPOCH_SIZE {
+        eyre::bail!("An era1 archive holds at most {EPOCH_SIZE} blocks, got {}.", blocks.len())
+    }
+    let start = first.block.number;
+    check_consecutive(blocks, start)?;
+
+    let mut out = vec![];
+    write_entry(&mut out, VERSION, &[]);
+
+    let mut offsets = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        offsets.push(out.len() as i64);
+        let body = BlockBody {
+            transactions: block.block.body.clone(),
+            ommers: block.block.ommers.clone(),
+            withdrawals: block.block.withdrawals.clone(),
+        };
+        write_entry(&mut out, COMPRESSED_HEADER, &compress(&block.block.header)?);
+        write_entry(&mut out, COMPRESSED_BODY, &compress(&body)?);
+        write_entry(&mut out, COMPRESSED_RECEIPTS, &compress(&block.receipts)?);
+        write_entry(&mut out, TOTAL_DIFFICULTY, &block.total_difficulty.to_le_bytes::<32>());
+    }
+
+    let root = accumulator_root(blocks);
+    write_entry(&mut out, ACCUMULATOR, root.as_slice());
+
+    // The offsets of the block index are relative to the start of its own entry.
+    let index_offset = out.len() as i64;
+    let mut index = Vec::with_capacity(16 + 8 * offsets.len());
+    index.extend_from_slice(&start.to_le_bytes());
+    for offset in offsets {
+        index.extend_from_slice(&(offset - index_offset).to_le_bytes());
+    }
+    index.extend_from_slice(&(blocks.len() as u64).to_le_bytes());
+    write_entry(&mut out, BLOCK_INDEX, &index);
+
+    std::fs::write(path, out)?;
+
+    Ok(root)
+}
+
+/// Reads the blocks of the era1 archive at `path`.
+///
+/// The archive is checked against its own accumulator and block index, and the receipts and
+/// total difficulties against the headers, so a corrupted archive is rejected before anything is
+/// imported from it.
+pub(crate) fn read_era1(path: &Path) -> eyre::Result<Vec<Era1Block>> {
+    let data = std::fs::read(path)?;
+    parse_era1(&data).wrap_err_with(|| format!("Invalid era1 archive {path:?}"))
+}
+
+fn parse_era1(data: &[u8]) -> eyre::Result<Vec<Era1Block>> {
+    let mut entries = EntryReader { data, offset: 0 };
+    entries.expect(VERSION)?;
+
+    let mut blocks = vec![];
+    let mut positions = vec![];
+    let root = loop {
+        let (offset, kind, payload) = entries.next()?;
+        match kind {
+            COMPRESSED_HEADER => {
+                let header: Header = decompress(payload)?;
+                let body: BlockBody = decompress(entries.expect(COMPRESSED_BODY)?)?;
+                let receipts = decompress(entries.expect(COMPRESSED_RECEIPTS)?)?;
+                let total_difficulty = entries.expect(TOTAL_DIFFICULTY)?;
+                if total_difficulty.len() != 32 {
+                    eyre::bail!("Total difficulty of block {} isn't 32 bytes.", header.number)
+                }
+
+                positions.push(offset as i64);
+                blocks.push(Era1Block {
+                    block: body.create_block(header),
+                    receipts,
+                    total_difficulty: U256::from_le_slice(total_difficulty),
+                });
+            }
+            ACCUMULATOR => break B256::try_from(payload)?,
+            other => eyre::bail!("Unexpected entry of type {other:#06x} at byte {offset}."),
+        }
+    };
+    if blocks.is_empty() {
+        eyre::bail!("An era1 archive can't be empty.")
+    }
+
+    let (index_offset, kind, index) = entries.next()?;
+    if kind != BLOCK_INDEX {
+        eyre::bail!("Expected the block index at byte {index_offset}, got type {kind:#06x}.")
+    }
+    if index.len() != 16 + 8 * blocks.len() {
+        eyre::bail!("Block index doesn't match the {} blocks of the archive.", blocks.len())
+    }
+    let word = |at: usize| i64::from_le_bytes(index[at..at + 8].try_into().expect("8 bytes"));
+    let start = word(0) as u64;
+    let count = word(index.len() - 8) as u64;
+    if count != blocks.len() as u64 {
+        eyre::bail!("Block index lists {count} blocks, the archive holds {}.", blocks.len())
+    }
+    for (i, position) in positions.iter().enumerate() {
+        if word(8 + 8 * i) != position - index_offset as i64 {
+            eyre::bail!("Block index has the wrong offset for block {}.", start + i as u64)
+        }
+    }
+
+    check_consecutive(&blocks, start)?;
+    let expected = accumulator_root(&blocks);
+    if expected != root {
+        eyre::bail!("Accumulator root {root} doesn't match the blocks, expected {expected}.")
+    }
+
+    let mut parent_difficulty = None;
+    for block in &blocks {
+        let header = &block.block.header;
+        let receipts_root = calculate_receipt_root(&block.receipts);
+        if receipts_root != header.receipts_root {
+            eyre::bail!(
+                "Receipts of block {} don't match its receipts root {}, got {receipts_root}.",
+                header.number,
+                header.receipts_root
+            )
+        }
+        if parent_difficulty
+            .is_some_and(|parent: U256| parent + header.difficulty != block.total_difficulty)
+        {
+            eyre::bail!("Total difficulty of block {} doesn't add up.", header.number)
+        }
+        parent_difficulty = Some(block.total_difficulty);
+    }
+
+    Ok(blocks)
+}
+
+/// Checks that `blocks` are numbered consecutively from `start` on.
+fn check_consecutive(blocks: &[Era1Block], start: u64) -> eyre::Result<()> {
+    for (expected, block) in (start..).zip(blocks) {
+        if block.block.number != expected {
+            eyre::bail!("Expected block {expected}, got block {}.", block.block.number)
+        }
+    }
+    Ok(())
+}
+
+/// The accumulator root of `blocks`: the SSZ root of the list of their hashes and total
+/// difficulties, with the capacity of an epoch.
+fn accumulator_root(blocks: &[Era1Block]) -> B256 {
+    let mut layer = blocks
+        .iter()
+        .map(|block| {
+            sha256(&[
+                block.block.header.hash_slow().as_slice(),
+                &block.total_difficulty.to_le_bytes::<32>(),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    // Merkleize up to the capacity of an epoch, padding every layer with the root of an empty
+    // subtree of its depth.
+    let mut empty = B256::ZERO;
+    for _ in 0..EPOCH_SIZE.trailing_zeros() {
+        if layer.len() % 2 == 1 {
+            layer.push(empty);
+        }
+        layer =
+            layer.chunks(2).map(|pair| sha256(&[pair[0].as_slice(), pair[1].as_slice()])).collect();
+        empty = sha256(&[empty.as_slice(), empty.as_slice()]);
+    }
+    let root = layer.first().copied().unwrap_or(empty);
+
+    // Mix in the length of the list.
+    sha256(&[root.as_slice(), &U256::from(blocks.len()).to_le_bytes::<32>()])
+}
+
+fn sha256(parts: &[&[u8]]) -> B256 {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    B256::from_slice(&hasher.finalize())
+}
+
+fn write_entry(out: &mut Vec<u8>, kind: u16, data: &[u8]) {
+    out.extend_from_slice(&kind.to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(&[0; 2]);
+    out.extend_from_slice(data);
+}
+
+/// The snappy framed rlp encoding of `value`.
+fn compress<T: Encodable>(value: &T) -> eyre::Result<Vec<u8>> {
+    let mut encoder = snap::write::FrameEncoder::new(vec![]);
+    encoder.write_all(&alloy_rlp::encode(value))?;
+    encoder.into_inner().map_err(|err| eyre!("Failed to compress entry: {err}"))
+}
+
+fn decompress<T: Decodable>(data: &[u8]) -> eyre::Result<T> {
+    let mut rlp = vec![];
+    snap::read::FrameDecoder::new(data).read_to_end(&mut rlp)?;
+    Ok(T::decode(&mut rlp.as_slice())?)
+}
+
+/// Reads the entries of an e2store file one after another.
+struct EntryReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> EntryReader<'a> {
+    /// Returns the offset, type and data of the next entry.
+    fn next(&mut self) -> eyre::Result<(usize, u16, &'a [u8])> {
+        let offset = self.offset;
+        let header = self
+            .data
+            .get(offset..offset + ENTRY_HEADER_SIZE)
+            .ok_or_else(|| eyre!("Truncated at byte {offset}."))?;
+        let kind = u16::from_le_bytes([header[0], header[1]]);
+        let length = u32::from_le_bytes(header[2..6].try_into()?) as usize;
+        if header[6..] != [0; 2] {
+            eyre::bail!("Entry at byte {offset} has a non-zero reserved field.")
+        }
+
+        let start = offset + ENTRY_HEADER_SIZE;
+        let data = self
+            .data
+            .get(start..start + length)
+            .ok_or_else(|| eyre!("Entry at byte {offset} is truncated."))?;
+        self.offset = start + length;
+
+        Ok((offset, kind, data))
+    }
+
+    /// Returns the data of the next entry, which has to be of type `kind`.
+    fn expect(&mut self, kind: u16) -> eyre::Result<&'a [u8]> {
+        let (offset, got, data) = self.next()?;
+        if got != kind {
+            eyre::bail!("Expected an entry of type {kind:#06x} at byte {offset}, got {got:#06x}.")
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Receipt, TxType};
+
+    fn blocks(start: u64, count: u64) -> Vec<Era1Block> {
+        (start..start + count)
+            .map(|number| {
+                let receipts = (0..number % 3)
+                    .map(|i| {
+                        Receipt {
+                            tx_type: TxType::Legacy,
+                            success: i % 2 == 0,
+                            cumulative_gas_used: 21_000 * (i + 1),
+                            logs: vec![],
+                        }
+                        .with_bloom()
+                    })
+                    .collect::<Vec<_>>();
+                let header = Header {
+                    number,
+                    difficulty: U256::from(1_000 + number),
+                    receipts_root: calculate_receipt_root(&receipts),
+                    ..Default::default()
+                };
+                let total_difficulty =
+                    (0..=number).fold(U256::ZERO, |td, number| td + U256::from(1_000 + number));
+                Era1Block {
+                    block: Block { header, ..Default::default() },
+                    receipts,
+                    total_difficulty,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mainnet-00002-00000000.era1");
+        let blocks = blocks(2 * EPOCH_SIZE, 100);
+
+        let root = write_era1(&path, &blocks).unwrap();
+        assert_eq!(root, accumulator_root(&blocks));
+        assert_eq!(read_era1(&path).unwrap(), blocks);
+
+        // An archive holding other blocks than its accumulator commits to is rejected.
+        let mut data = std::fs::read(&path).unwrap();
+        let at = data.len() - 16 - 8 * blocks.len() - ENTRY_HEADER_SIZE - 32;
+        data[at] ^= 1;
+        assert!(parse_era1(&data).is_err());
+
+        assert!(write_era1(&path, &blocks(0, EPOCH_SIZE + 1)).is_err());
+        assert!(write_era1(&path, &[]).is_err());
+    }
+}
@@ -0,0 +1,376 @@
+use super::{
+    era1::{self, write_era1, Era1Block, EPOCH_SIZE},
+    import::GZIP_EXTENSION,
+};
+use crate::{
+    args::{utils::genesis_value_parser, ChainOverrideArgs, DatabaseArgs},
+    dirs::{DataDirPath, MaybePlatformPath},
+};
+use alloy_rlp::Encodable;
+use clap::{Parser, Subcommand};
+use eyre::eyre;
+use flate2::{write::GzEncoder, Compression};
+use reth_db::{database::Database, open_db_read_only};
+use reth_primitives::{BlockNumber, ChainSpec, Hardfork, Receipt, ReceiptWithBloom};
+use reth_provider::{
+    BlockNumReader, BlockReader, HeaderProvider, ProviderFactory, ReceiptProvider,
+};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::info;
+
+/// Exports the blocks of the database to files of other clients.
+#[derive(Debug, Parser)]
+pub struct ExportCommand {
+    /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t, global = true)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
+    ///
+    /// Built-in chains:
+    /// - mainnet
+    /// - goerli
+    /// - sepolia
+    /// - holesky
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        verbatim_doc_comment,
+        default_value = "mainnet",
+        value_parser = genesis_value_parser,
+        global = true,
+    )]
+    chain: Arc<ChainSpec>,
+
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    chain_overrides: ChainOverrideArgs,
+
+    #[clap(flatten)]
+    db: DatabaseArgs,
+
+    #[clap(subcommand)]
+    command: Subcommands,
+}
+
+/// `reth export` subcommands
+#[derive(Subcommand, Debug)]
+pub enum Subcommands {
+    /// Exports the pre-merge blocks with their receipts to era1 archives, one per epoch of 8192
+    /// blocks
+    Era1(Era1Command),
+    /// Exports a range of canonical blocks as RLP, one after another like `geth export`, e.g. to
+    /// import them into another client with `import`
+    Blocks(BlocksCommand),
+}
+
+/// `reth export era1` command
+#[derive(Debug, Parser)]
+pub struct Era1Command {
+    /// The directory the archives are written to.
+    #[arg(long, value_name = "PATH")]
+    output_dir: PathBuf,
+
+    /// The first epoch to export.
+    #[arg(long, value_name = "EPOCH", default_value_t = 0)]
+    from_epoch: u64,
+
+    /// The last epoch to export.
+    ///
+    /// Defaults to the epoch of the merge, or the last epoch the database holds entirely.
+    #[arg(long, value_name = "EPOCH", verbatim_doc_comment)]
+    to_epoch: Option<u64>,
+}
+
+/// `reth export blocks` command
+#[derive(Debug, Parser)]
+pub struct BlocksCommand {
+    /// The file the blocks are written to. It's gzipped if its extension is `.gz`.
+    #[arg(long, value_name = "PATH")]
+    output: PathBuf,
+
+    /// The first block to export.
+    #[arg(long, value_name = "BLOCK_NUMBER", default_value_t = 0)]
+    from: BlockNumber,
+
+    /// The last block to export. Defaults to the tip of the database.
+    #[arg(long, value_name = "BLOCK_NUMBER")]
+    to: Option<BlockNumber>,
+
+    /// Also writes the receipts of every block to this file, as an RLP list per block in the
+    /// order of the blocks. It's gzipped if its extension is `.gz`.
+    #[arg(long, value_name = "PATH")]
+    receipts: Option<PathBuf>,
+}
+
+impl ExportCommand {
+    /// Execute `export` command
+    pub async fn execute(mut self) -> eyre::Result<()> {
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
+        // add network name to data dir
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let db_path = data_dir.db_path();
+
+        let db = open_db_read_only(&db_path, self.db.log_level)?;
+        let factory = ProviderFactory::new(&db, self.chain.clone());
+
+        match self.command {
+            Subcommands::Era1(command) => command.execute(&factory, &self.chain),
+            Subcommands::Blocks(command) => command.execute(&factory),
+        }
+    }
+}
+
+impl Era1Command {
+    /// Execute `export era1` command
+    fn execute<DB: Database>(
+        self,
+        factory: &ProviderFactory<DB>,
+        chain: &ChainSpec,
+    ) -> eyre::Result<()> {
+        std::fs::create_dir_all(&self.output_dir)?;
+
+        let provider = factory.provider()?;
+        let tip = provider.best_block_number()?;
+        let paris = chain.fork(Hardfork::Paris);
+        let network = chain.chain.to_string();
+
+        let mut epoch = self.from_epoch;
+        while self.to_epoch.map_or(true, |to_epoch| epoch <= to_epoch) {
+            let start = epoch * EPOCH_SIZE;
+            let end = (start + EPOCH_SIZE - 1).min(tip);
+
+            let mut blocks = Vec::with_capacity(EPOCH_SIZE as usize);
+            let mut merged = false;
+            for number in start..=end {
+                let total_difficulty = provider
+                    .header_td_by_number(number)?
+                    .ok_or_else(|| eyre!("Total difficulty of block {number} is missing."))?;
+                let block = provider
+                    .block(number.into())?
+                    .ok_or_else(|| eyre!("Block {number} is missing."))?;
+                if paris.active_at_ttd(total_difficulty, block.difficulty) {
+                    merged = true;
+                    break
+                }
+                let receipts = provider.receipts_by_block(number.into())?.ok_or_else(|| {
+                    eyre!("Receipts of block {number} are missing, e.g. because they were pruned.")
+                })?;
+
+                blocks.push(Era1Block {
+                    block,
+                    receipts: receipts.into_iter().map(Receipt::with_bloom).collect(),
+                    total_difficulty,
+                });
+            }
+
+            // Epochs are only exported whole, except for the last one before the merge.
+            if blocks.is_empty() || (!merged && blocks.len() as u64 != EPOCH_SIZE) {
+                info!(target: "reth::cli", epoch, tip, merged, "No further complete epoch to export");
+                break
+            }
+
+            // The name of an archive holds its accumulator root, so it's only known once it's
+            // written.
+            let partial = self.output_dir.join(format!("{network}-{epoch:05}.era1.partial"));
+            let root = write_era1(&partial, &blocks)?;
+            let path = self.output_dir.join(era1::file_name(&network, epoch, root));
+            std::fs::rename(&partial, &path)?;
+            info!(target: "reth::cli", epoch, ?path, blocks = blocks.len(), %root, "Exported epoch");
+
+            if merged {
+                break
+            }
+            epoch += 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl BlocksCommand {
+    /// Execute `export blocks` command
+    fn execute<DB: Database>(self, factory: &ProviderFactory<DB>) -> eyre::Result<()> {
+        let provider = factory.provider()?;
+        let tip = provider.best_block_number()?;
+        let to = self.to.unwrap_or(tip);
+        if self.from > to {
+            eyre::bail!("--from {} is after --to {to}.", self.from)
+        }
+        if to > tip {
+            eyre::bail!("--to {to} is after the tip {tip} of the database.")
+        }
+
+        let mut blocks_file = create_output(&self.output)?;
+        let mut receipts_file = self.receipts.as_deref().map(create_output).transpose()?;
+
+        let mut buf = Vec::new();
+        for number in self.from..=to {
+            let block = provider
+                .block(number.into())?
+                .ok_or_else(|| eyre!("Block {number} is missing."))?;
+            buf.clear();
+            block.encode(&mut buf);
+            blocks_file.write_all(&buf)?;
+
+            if let Some(receipts_file) = &mut receipts_file {
+                let receipts = provider.receipts_by_block(number.into())?.ok_or_else(|| {
+                    eyre!("Receipts of block {number} are missing, e.g. because they were pruned.")
+                })?;
+                let receipts = receipts
+                    .into_iter()
+                    .map(Receipt::with_bloom)
+                    .collect::<Vec<ReceiptWithBloom>>();
+                buf.clear();
+                receipts.encode(&mut buf);
+                receipts_file.write_all(&buf)?;
+            }
+
+            if number % 100_000 == 0 {
+                info!(target: "reth::cli", number, to, "Exporting blocks");
+            }
+        }
+
+        blocks_file.finish()?;
+        if let Some(receipts_file) = receipts_file {
+            receipts_file.finish()?;
+        }
+        info!(target: "reth::cli", from = self.from, to, path = ?self.output, "Exported blocks");
+
+        Ok(())
+    }
+}
+
+/// Creates the file at `path` to write to, gzipping what's written if its extension is `.gz`.
+fn create_output(path: &Path) -> eyre::Result<Output> {
+    let file = BufWriter::new(File::create(path)?);
+    Ok(if path.extension().is_some_and(|ext| ext == GZIP_EXTENSION) {
+        Output::Gzip(GzEncoder::new(file, Compression::default()))
+    } else {
+        Output::Plain(file)
+    })
+}
+
+/// A file the blocks or receipts are exported to.
+enum Output {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl Output {
+    /// Writes out what's buffered, along with the trailer of a gzipped file, which is only
+    /// complete afterwards.
+    fn finish(self) -> eyre::Result<()> {
+        let mut file = match self {
+            Self::Plain(file) => file,
+            Self::Gzip(encoder) => encoder.finish()?,
+        };
+        file.flush()?;
+        Ok(())
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Plain(file) => file.write(buf),
+            Self::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Plain(file) => file.flush(),
+            Self::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::ImportCommand;
+    use reth_db::test_utils::create_test_rw_db;
+    use reth_interfaces::test_utils::generators::{self, random_block_range};
+    use reth_primitives::{
+        stage::{StageCheckpoint, StageId},
+        B256, MAINNET,
+    };
+    use reth_provider::{BlockWriter, StageCheckpointWriter};
+
+    #[test]
+    fn parse_export_era1_command() {
+        let args = ExportCommand::parse_from([
+            "reth",
+            "era1",
+            "--output-dir",
+            "era",
+            "--from-epoch",
+            "2",
+            "--chain",
+            "sepolia",
+        ]);
+        assert_eq!(args.chain.chain, "sepolia".parse().unwrap());
+        let Subcommands::Era1(command) = args.command else { panic!("not an era1 command") };
+        assert_eq!((command.from_epoch, command.to_epoch), (2, None));
+    }
+
+    #[test]
+    fn parse_export_blocks_command() {
+        let args = ExportCommand::parse_from([
+            "reth",
+            "blocks",
+            "--output",
+            "blocks.rlp.gz",
+            "--from",
+            "10",
+            "--receipts",
+            "receipts.rlp",
+        ]);
+        let Subcommands::Blocks(command) = args.command else { panic!("not a blocks command") };
+        assert_eq!((command.from, command.to), (10, None));
+        assert_eq!(command.receipts, Some(PathBuf::from("receipts.rlp")));
+    }
+
+    #[tokio::test]
+    async fn export_import_roundtrip() {
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(&db, MAINNET.clone());
+        let blocks = random_block_range(&mut generators::rng(), 0..=4, B256::ZERO, 0..3);
+        let provider = factory.provider_rw().unwrap();
+        for block in blocks.clone() {
+            provider.insert_block(block, None, None).unwrap();
+        }
+        provider.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(4)).unwrap();
+        provider.commit().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        for file in ["blocks.rlp", "blocks.rlp.gz"] {
+            let output = dir.path().join(file);
+            BlocksCommand { output: output.clone(), from: 0, to: None, receipts: None }
+                .execute(&factory)
+                .unwrap();
+
+            let import = ImportCommand::parse_from(["reth", output.to_str().unwrap()]);
+            assert_eq!(
+                import.rlp_blocks().await.unwrap(),
+                blocks.iter().cloned().map(|block| block.unseal()).collect::<Vec<_>>()
+            );
+        }
+    }
+}
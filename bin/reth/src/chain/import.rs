@@ -1,3 +1,4 @@
+use super::era1::{read_era1, ERA1_EXTENSION};
 use crate::{
     dirs::{DataDirPath, MaybePlatformPath},
     init::init_genesis,
@@ -6,11 +7,12 @@ use crate::{
 };
 use clap::Parser;
 use eyre::Context;
+use flate2::read::GzDecoder;
 use futures::{Stream, StreamExt};
 use reth_beacon_consensus::BeaconConsensus;
 use reth_provider::{ProviderFactory, StageCheckpointReader};
 
-use crate::args::{utils::genesis_value_parser, DatabaseArgs};
+use crate::args::{utils::genesis_value_parser, ChainOverrideArgs, DatabaseArgs};
 use reth_config::Config;
 use reth_db::{database::Database, init_db};
 use reth_downloaders::{
@@ -18,7 +20,7 @@ use reth_downloaders::{
     headers::reverse_headers::ReverseHeadersDownloaderBuilder, test_utils::FileClient,
 };
 use reth_interfaces::consensus::Consensus;
-use reth_primitives::{stage::StageId, ChainSpec, B256};
+use reth_primitives::{stage::StageId, Block, BlockNumber, ChainSpec, B256};
 use reth_stages::{
     prelude::*,
     stages::{
@@ -26,11 +28,18 @@ use reth_stages::{
         TotalDifficultyStage,
     },
 };
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use tokio::sync::watch;
 use tracing::{debug, info};
 
-/// Syncs RLP encoded blocks from a file.
+/// The extension of gzipped block files.
+pub(crate) const GZIP_EXTENSION: &str = "gz";
+
+/// Syncs RLP encoded blocks from a file, or the blocks of era1 archives.
 #[derive(Debug, Parser)]
 pub struct ImportCommand {
     /// The path to the configuration file to use.
@@ -49,7 +58,8 @@ pub struct ImportCommand {
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -65,22 +75,33 @@ pub struct ImportCommand {
     )]
     chain: Arc<ChainSpec>,
 
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    chain_overrides: ChainOverrideArgs,
+
     #[clap(flatten)]
     db: DatabaseArgs,
 
-    /// The path to a block file for import.
+    /// The path to a block file for import, or to an era1 archive or a directory of them.
+    ///
+    /// Block files hold RLP encoded blocks one after another, like the ones of `geth export`, and
+    /// are decompressed first if their extension is `.gz`.
     ///
     /// The online stages (headers and bodies) are replaced by a file import, after which the
-    /// remaining stages are executed.
+    /// remaining stages are executed. The blocks of era1 archives have to continue the chain in
+    /// the database, e.g. by importing the epochs in order. All the blocks are held in memory
+    /// during the import, so large ranges are best imported a few epochs at a time.
     #[arg(value_name = "IMPORT_PATH", verbatim_doc_comment)]
     path: PathBuf,
 }
 
 impl ImportCommand {
     /// Execute `import` command
-    pub async fn execute(self) -> eyre::Result<()> {
+    pub async fn execute(mut self) -> eyre::Result<()> {
         info!(target: "reth::cli", "reth {} starting", SHORT_VERSION);
 
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
         // add network name to data dir
         let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
         let config_path = self.config.clone().unwrap_or(data_dir.config_path());
@@ -91,6 +112,7 @@ impl ImportCommand {
         let db_path = data_dir.db_path();
 
         info!(target: "reth::cli", path = ?db_path, "Opening database");
+        self.db.ensure_writable("import")?;
         let db = Arc::new(init_db(db_path, self.db.log_level)?);
         info!(target: "reth::cli", "Database opened");
 
@@ -101,9 +123,18 @@ impl ImportCommand {
         let consensus = Arc::new(BeaconConsensus::new(self.chain.clone()));
         info!(target: "reth::cli", "Consensus engine initialized");
 
+        let factory = ProviderFactory::new(&db, self.chain.clone());
+        let provider = factory.provider().map_err(PipelineError::Interface)?;
+        let local_head =
+            provider.get_stage_checkpoint(StageId::Headers)?.unwrap_or_default().block_number;
+
         // create a new FileClient
         info!(target: "reth::cli", "Importing chain file");
-        let file_client = Arc::new(FileClient::new(&self.path).await?);
+        let blocks = match self.era1_archives()? {
+            Some(archives) => self.era1_blocks(&archives)?,
+            None => self.rlp_blocks().await?,
+        };
+        let file_client = Arc::new(self.file_client(blocks, local_head)?);
 
         // override the tip
         let tip = file_client.tip().expect("file client has no tip");
@@ -116,9 +147,6 @@ impl ImportCommand {
         pipeline.set_tip(tip);
         debug!(target: "reth::cli", ?tip, "Tip manually set");
 
-        let factory = ProviderFactory::new(&db, self.chain.clone());
-        let provider = factory.provider().map_err(PipelineError::Interface)?;
-
         let latest_block_number =
             provider.get_stage_checkpoint(StageId::Finish)?.map(|ch| ch.block_number);
         tokio::spawn(handle_events(None, latest_block_number, events));
@@ -177,9 +205,10 @@ impl ImportCommand {
                     TotalDifficultyStage::new(consensus.clone())
                         .with_commit_threshold(config.stages.total_difficulty.commit_threshold),
                 )
-                .set(SenderRecoveryStage {
-                    commit_threshold: config.stages.sender_recovery.commit_threshold,
-                })
+                .set(
+                    SenderRecoveryStage::new(config.stages.sender_recovery.commit_threshold)
+                        .with_workers(config.stages.sender_recovery.workers),
+                )
                 .set(ExecutionStage::new(
                     factory,
                     ExecutionStageThresholds {
@@ -203,6 +232,84 @@ impl ImportCommand {
         Ok((pipeline, events))
     }
 
+    /// Returns the era1 archives to import if the import path is one, or a directory of them.
+    fn era1_archives(&self) -> eyre::Result<Option<Vec<PathBuf>>> {
+        let is_era1 = |path: &Path| path.extension().is_some_and(|ext| ext == ERA1_EXTENSION);
+
+        if self.path.is_dir() {
+            let mut archives = std::fs::read_dir(&self.path)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            archives.retain(|path| is_era1(path));
+            // The names of the archives start with their zero padded epoch.
+            archives.sort();
+            if archives.is_empty() {
+                eyre::bail!("No era1 archives in {:?}.", self.path)
+            }
+            Ok(Some(archives))
+        } else if is_era1(&self.path) {
+            Ok(Some(vec![self.path.clone()]))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Reads the blocks of the era1 `archives`.
+    ///
+    /// The receipts of the archives are only checked against the receipts roots of their headers,
+    /// since the execution stage derives them again.
+    fn era1_blocks(&self, archives: &[PathBuf]) -> eyre::Result<Vec<Block>> {
+        let mut blocks = vec![];
+        for archive in archives {
+            let era = read_era1(archive)?;
+            info!(target: "reth::cli", ?archive, from = era[0].block.number, blocks = era.len(), "Read era1 archive");
+            blocks.extend(era.into_iter().map(|block| block.block));
+        }
+        Ok(blocks)
+    }
+
+    /// Reads the RLP encoded blocks of the import file, decompressing it first if it's gzipped,
+    /// like the `.gz` exports of `geth export`.
+    pub(super) async fn rlp_blocks(&self) -> eyre::Result<Vec<Block>> {
+        let mut bytes = std::fs::read(&self.path)
+            .wrap_err_with(|| format!("Could not read block file {:?}", self.path))?;
+        if self.path.extension().is_some_and(|ext| ext == GZIP_EXTENSION) {
+            let mut decompressed = vec![];
+            GzDecoder::new(&bytes[..]).read_to_end(&mut decompressed)?;
+            bytes = decompressed;
+        }
+        Ok(FileClient::decode_blocks(&bytes).await?)
+    }
+
+    /// Puts the blocks to import into a [`FileClient`], once they're checked to continue the chain
+    /// of the database, whose highest header is `local_head`.
+    ///
+    /// The genesis block, which e.g. geth exports along with the others, is initialized from the
+    /// chain specification instead, so it's only compared to it.
+    fn file_client(
+        &self,
+        mut blocks: Vec<Block>,
+        local_head: BlockNumber,
+    ) -> eyre::Result<FileClient> {
+        if blocks.first().is_some_and(|block| block.number == 0) {
+            let genesis = blocks.remove(0);
+            if genesis.hash_slow() != self.chain.genesis_hash() {
+                eyre::bail!("The blocks to import aren't of the {} chain.", self.chain.chain)
+            }
+        }
+
+        let Some(first) = blocks.first().map(|block| block.number) else {
+            eyre::bail!("No blocks to import in {:?}.", self.path)
+        };
+        if first > local_head + 1 {
+            eyre::bail!(
+                "The blocks to import start at block {first}, after the last header {local_head}."
+            )
+        }
+
+        Ok(FileClient::from_blocks(blocks))
+    }
+
     /// Loads the reth config
     fn load_config(&self, config_path: PathBuf) -> eyre::Result<Config> {
         confy::load_path::<Config>(config_path.clone())
@@ -221,4 +328,23 @@ mod tests {
             assert_eq!(args.chain.chain, chain.parse().unwrap());
         }
     }
+
+    #[test]
+    fn file_client_continues_local_chain() {
+        let args = ImportCommand::parse_from(["reth", "--chain", "sepolia", "blocks.rlp"]);
+        let block = |number| Block {
+            header: reth_primitives::Header { number, ..Default::default() },
+            ..Default::default()
+        };
+
+        assert!(args.file_client(vec![block(5), block(6)], 0).is_err());
+        let client = args.file_client(vec![block(5), block(6)], 4).unwrap();
+        assert_eq!(client.max_block(), Some(6));
+
+        // A genesis block of another chain is rejected, and the one of the chain is skipped.
+        assert!(args.file_client(vec![block(0), block(1)], 0).is_err());
+        let genesis = Block { header: args.chain.genesis_header(), ..Default::default() };
+        assert!(args.file_client(vec![genesis.clone()], 0).is_err());
+        assert!(args.file_client(vec![genesis, block(1)], 0).is_ok());
+    }
 }
@@ -1,5 +1,5 @@
 use crate::{
-    args::{utils::genesis_value_parser, DatabaseArgs},
+    args::{utils::genesis_value_parser, ChainOverrideArgs, DatabaseArgs},
     dirs::{DataDirPath, MaybePlatformPath},
     init::init_genesis,
 };
@@ -24,7 +24,8 @@ pub struct InitCommand {
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -40,19 +41,26 @@ pub struct InitCommand {
     )]
     chain: Arc<ChainSpec>,
 
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    chain_overrides: ChainOverrideArgs,
+
     #[clap(flatten)]
     db: DatabaseArgs,
 }
 
 impl InitCommand {
     /// Execute the `init` command
-    pub async fn execute(self) -> eyre::Result<()> {
+    pub async fn execute(mut self) -> eyre::Result<()> {
         info!(target: "reth::cli", "reth init starting");
 
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
         // add network name to data dir
         let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
         let db_path = data_dir.db_path();
         info!(target: "reth::cli", path = ?db_path, "Opening database");
+        self.db.ensure_writable("init")?;
         let db = Arc::new(init_db(&db_path, self.db.log_level)?);
         info!(target: "reth::cli", "Database opened");
 
@@ -1,7 +1,10 @@
-//! Command line utilities for initializing a chain.
+//! Command line utilities for initializing, importing and exporting a chain.
 
+mod era1;
+mod export;
 mod import;
 mod init;
 
+pub use export::ExportCommand;
 pub use import::ImportCommand;
 pub use init::InitCommand;
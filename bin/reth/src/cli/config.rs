@@ -1,7 +1,8 @@
 //! Config traits for various node components.
 
 use alloy_rlp::Encodable;
-use reth_primitives::{Bytes, BytesMut};
+use reth_basic_payload_builder::PayloadOrdering;
+use reth_primitives::{Address, Bytes, BytesMut};
 use reth_rpc::{eth::gas_oracle::GasPriceOracleConfig, JwtError, JwtSecret};
 use reth_rpc_builder::{
     auth::AuthServerConfig, error::RpcError, EthConfig, IpcServerBuilder, RpcServerConfig,
@@ -92,4 +93,14 @@ pub trait PayloadBuilderConfig {
 
     /// Maximum number of tasks to spawn for building a payload.
     fn max_payload_tasks(&self) -> usize;
+
+    /// The fee recipient of the payloads requested with the zero address as their suggested fee
+    /// recipient.
+    fn fee_recipient(&self) -> Option<Address>;
+
+    /// The minimum effective tip per gas, in wei, of the included transactions.
+    fn min_tip(&self) -> u128;
+
+    /// The order in which the transactions of the pool are included.
+    fn ordering(&self) -> PayloadOrdering;
 }
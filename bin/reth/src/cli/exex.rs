@@ -0,0 +1,354 @@
+//! Support for execution extensions (ExExes): tasks installed into the node which are driven by the
+//! chains it commits, e.g. to build indexes without polling the RPC.
+//!
+//! An ExEx is installed with
+//! [RethNodeCommandConfig::install_exexs](crate::cli::ext::RethNodeCommandConfig::install_exexs)
+//! and launched with an [ExExContext] once the components of the node are initialized.
+use crate::cli::components::RethNodeComponents;
+use futures::{future::BoxFuture, FutureExt};
+use reth_db::database::Database;
+use reth_primitives::{
+    stage::{StageCheckpoint, StageId},
+    BlockNumber,
+};
+use reth_provider::{
+    CanonStateNotification, CanonStateNotifications, CanonStateSubscriptions, ProviderFactory,
+    StageCheckpointReader, StageCheckpointWriter,
+};
+use reth_tasks::TaskSpawner;
+use std::{collections::VecDeque, fmt, future::Future, sync::Arc};
+use tokio::sync::{broadcast::error::RecvError, mpsc, watch};
+use tracing::*;
+
+/// The default number of notifications queued for an ExEx, and held back on top of them.
+pub const DEFAULT_EXEX_CAPACITY: usize = 64;
+
+/// An event an ExEx sends to the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExExEvent {
+    /// The ExEx processed the canonical chain up to and including this block.
+    ///
+    /// The height is persisted as the checkpoint of the ExEx, and passed back to it as
+    /// [ExExContext::head] when the node restarts.
+    FinishedHeight(BlockNumber),
+}
+
+/// The context an ExEx runs with.
+#[derive(Debug)]
+pub struct ExExContext<Reth> {
+    /// The components of the node.
+    pub components: Reth,
+    /// The last block the ExEx finished in an earlier run of the node, if any.
+    ///
+    /// The blocks committed while the ExEx wasn't running, or by the pipeline rather than the
+    /// blockchain tree, aren't notified: the ExEx can read the ones after its head from the
+    /// provider of the components.
+    pub head: Option<BlockNumber>,
+    /// The notifications of the chains committed and reorged by the node, in order.
+    ///
+    /// Up to the capacity of the ExEx is queued in the channel, and as many are held back until
+    /// the ExEx catches up. Past that, the notifications wait in the canonical state subscription,
+    /// whose buffer is bounded too: an ExEx falling further behind misses the oldest ones, which
+    /// is logged, and can read their blocks from the provider of the components.
+    pub notifications: mpsc::Receiver<CanonStateNotification>,
+    /// The channel to send [ExExEvent]s to the node.
+    pub events: mpsc::UnboundedSender<ExExEvent>,
+}
+
+/// Launches an installed ExEx with its context.
+type LaunchExEx<Reth> =
+    Box<dyn FnOnce(ExExContext<Reth>) -> BoxFuture<'static, eyre::Result<()>> + Send>;
+
+/// An ExEx installed into an [ExExRegistry].
+struct InstalledExEx<Reth> {
+    id: &'static str,
+    capacity: usize,
+    launch: LaunchExEx<Reth>,
+}
+
+/// The ExExes installed into the node.
+pub struct ExExRegistry<Reth> {
+    exexs: Vec<InstalledExEx<Reth>>,
+}
+
+impl<Reth> Default for ExExRegistry<Reth> {
+    fn default() -> Self {
+        Self { exexs: Vec::new() }
+    }
+}
+
+impl<Reth> fmt::Debug for ExExRegistry<Reth> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExExRegistry").field("exexs", &self.ids().collect::<Vec<_>>()).finish()
+    }
+}
+
+impl<Reth> ExExRegistry<Reth> {
+    /// Installs an ExEx, which is launched with its [ExExContext] and runs until its future
+    /// resolves.
+    ///
+    /// The `id` must be unique and stay the same across restarts, since the checkpoint of the ExEx
+    /// is stored under it.
+    pub fn install<F, Fut>(&mut self, id: &'static str, launch: F) -> &mut Self
+    where
+        F: FnOnce(ExExContext<Reth>) -> Fut + Send + 'static,
+        Fut: Future<Output = eyre::Result<()>> + Send + 'static,
+    {
+        self.install_with_capacity(id, DEFAULT_EXEX_CAPACITY, launch)
+    }
+
+    /// Installs an ExEx queueing up to `capacity` notifications, see [Self::install].
+    pub fn install_with_capacity<F, Fut>(
+        &mut self,
+        id: &'static str,
+        capacity: usize,
+        launch: F,
+    ) -> &mut Self
+    where
+        F: FnOnce(ExExContext<Reth>) -> Fut + Send + 'static,
+        Fut: Future<Output = eyre::Result<()>> + Send + 'static,
+    {
+        self.exexs.push(InstalledExEx {
+            id,
+            capacity: capacity.max(1),
+            launch: Box::new(move |ctx| launch(ctx).boxed()),
+        });
+        self
+    }
+
+    /// Returns the ids of the installed ExExes.
+    pub fn ids(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.exexs.iter().map(|exex| exex.id)
+    }
+
+    /// Returns whether no ExEx is installed.
+    pub fn is_empty(&self) -> bool {
+        self.exexs.is_empty()
+    }
+}
+
+impl<Reth> ExExRegistry<Reth>
+where
+    Reth: RethNodeComponents + Clone + Send + 'static,
+{
+    /// Launches the installed ExExes, each with a task forwarding it the canonical state
+    /// notifications and persisting its checkpoints.
+    ///
+    /// This must be called before the node commits any chain, so no notification is missed.
+    pub(crate) fn launch<DB>(
+        self,
+        components: &Reth,
+        factory: Arc<ProviderFactory<DB>>,
+    ) -> eyre::Result<()>
+    where
+        DB: Database + 'static,
+    {
+        let mut ids = self.ids().collect::<Vec<_>>();
+        ids.sort_unstable();
+        if let Some(id) = ids.windows(2).find(|ids| ids[0] == ids[1]).map(|ids| ids[0]) {
+            eyre::bail!("The ExEx {id} is installed more than once.")
+        }
+
+        for InstalledExEx { id, capacity, launch } in self.exexs {
+            let head = factory
+                .provider()?
+                .get_stage_checkpoint(StageId::Other(id))?
+                .map(|checkpoint| checkpoint.block_number);
+            info!(target: "reth::cli", exex = id, ?head, "Launching ExEx");
+
+            let (notifications_tx, notifications) = mpsc::channel(capacity);
+            let (events, events_rx) = mpsc::unbounded_channel();
+            let (height_tx, height_rx) = watch::channel(head);
+            let canon_state = components.events().subscribe_to_canonical_state();
+
+            let task_executor = components.task_executor();
+            task_executor.spawn_critical(
+                "exex checkpoints",
+                Box::pin(persist_checkpoints(id, Arc::clone(&factory), height_rx)),
+            );
+            task_executor.spawn_critical(
+                "exex notifications",
+                Box::pin(forward_notifications(
+                    id,
+                    canon_state,
+                    capacity,
+                    notifications_tx,
+                    events_rx,
+                    height_tx,
+                )),
+            );
+
+            let exex =
+                launch(ExExContext { components: components.clone(), head, notifications, events });
+            task_executor.spawn_critical(
+                "exex",
+                Box::pin(async move {
+                    match exex.await {
+                        Ok(()) => info!(target: "reth::cli", exex = id, "ExEx finished"),
+                        Err(err) => error!(target: "reth::cli", exex = id, %err, "ExEx failed"),
+                    }
+                }),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Forwards the canonical state notifications to an ExEx as fast as it takes them, holding back
+/// up to `max_held_back` of the ones it hasn't room for, and tracks the heights it reports.
+///
+/// Once that many are held back, the subscription isn't read until the ExEx takes one, so it may
+/// lag behind and skip notifications.
+///
+/// An ExEx may drop its events sender if it never reports heights, so only the closing of its
+/// notifications, e.g. once it exits, ends the forwarding.
+async fn forward_notifications(
+    id: &'static str,
+    mut canon_state: CanonStateNotifications,
+    max_held_back: usize,
+    notifications: mpsc::Sender<CanonStateNotification>,
+    mut events: mpsc::UnboundedReceiver<ExExEvent>,
+    height: watch::Sender<Option<BlockNumber>>,
+) {
+    let mut held_back = VecDeque::with_capacity(max_held_back);
+    let mut canon_state_closed = false;
+    let mut events_closed = false;
+    loop {
+        let stalled = held_back.len() >= max_held_back;
+        tokio::select! {
+            notification = canon_state.recv(), if !canon_state_closed && !stalled => match notification {
+                Ok(notification) => held_back.push_back(notification),
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(target: "reth::cli", exex = id, skipped, "Missed canonical state notifications of ExEx");
+                }
+                Err(RecvError::Closed) => canon_state_closed = true,
+            },
+            permit = notifications.reserve(), if !held_back.is_empty() => match permit {
+                Ok(permit) => permit.send(held_back.pop_front().expect("is not empty")),
+                Err(_) => return,
+            },
+            _ = notifications.closed() => return,
+            event = events.recv(), if !events_closed => match event {
+                Some(ExExEvent::FinishedHeight(finished)) => {
+                    trace!(target: "reth::cli", exex = id, finished, held_back = held_back.len(), "ExEx finished height");
+                    height.send_replace(Some(finished));
+                }
+                None => events_closed = true,
+            },
+        }
+    }
+}
+
+/// Persists the latest height an ExEx reports as its checkpoint, until it exits.
+///
+/// The database writes happen on their own, so they never hold back the notifications while the
+/// node writes to the database.
+async fn persist_checkpoints<DB: Database + 'static>(
+    id: &'static str,
+    factory: Arc<ProviderFactory<DB>>,
+    mut height: watch::Receiver<Option<BlockNumber>>,
+) {
+    while height.changed().await.is_ok() {
+        let Some(finished) = *height.borrow_and_update() else { continue };
+        let factory = Arc::clone(&factory);
+        let saved = tokio::task::spawn_blocking(move || {
+            let provider = factory.provider_rw()?;
+            provider.save_stage_checkpoint(StageId::Other(id), StageCheckpoint::new(finished))?;
+            provider.commit()?;
+            Ok::<_, eyre::Report>(())
+        })
+        .await;
+        match saved {
+            Ok(Ok(())) => {
+                debug!(target: "reth::cli", exex = id, finished, "Saved ExEx checkpoint")
+            }
+            Ok(Err(err)) => {
+                warn!(target: "reth::cli", exex = id, %err, "Could not save ExEx checkpoint")
+            }
+            Err(err) => {
+                warn!(target: "reth::cli", exex = id, %err, "Could not save ExEx checkpoint")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::SealedBlockWithSenders;
+    use reth_provider::Chain;
+    use tokio::sync::broadcast;
+
+    #[tokio::test]
+    async fn forwards_held_back_notifications() {
+        let (canon_state_tx, canon_state) = broadcast::channel(16);
+        let (notifications_tx, mut notifications) = mpsc::channel(1);
+        let (events, events_rx) = mpsc::unbounded_channel();
+        let (height_tx, mut height) = watch::channel(None);
+        let forward = tokio::spawn(forward_notifications(
+            "test",
+            canon_state,
+            1,
+            notifications_tx,
+            events_rx,
+            height_tx,
+        ));
+
+        let commits = (1..=3)
+            .map(|number| {
+                let mut block = SealedBlockWithSenders::default();
+                block.block.header.header.number = number;
+                CanonStateNotification::Commit {
+                    new: Arc::new(Chain::new(vec![block], Default::default())),
+                }
+            })
+            .collect::<Vec<_>>();
+        for commit in &commits {
+            canon_state_tx.send(commit.clone()).unwrap();
+        }
+
+        // The notifications which didn't fit in the capacity of the ExEx are held back for it, and
+        // the ones past what's held back wait in the subscription.
+        for commit in &commits {
+            assert_eq!(&notifications.recv().await.unwrap(), commit);
+        }
+
+        events.send(ExExEvent::FinishedHeight(3)).unwrap();
+        height.changed().await.unwrap();
+        assert_eq!(*height.borrow(), Some(3));
+
+        drop(notifications);
+        forward.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn forwards_without_events() {
+        let (canon_state_tx, canon_state) = broadcast::channel(16);
+        let (notifications_tx, mut notifications) = mpsc::channel(1);
+        let (events, events_rx) = mpsc::unbounded_channel();
+        let (height_tx, _height) = watch::channel(None);
+        let forward = tokio::spawn(forward_notifications(
+            "test",
+            canon_state,
+            1,
+            notifications_tx,
+            events_rx,
+            height_tx,
+        ));
+
+        // An ExEx which never reports heights still gets the notifications sent after it dropped
+        // its events sender.
+        drop(events);
+        tokio::task::yield_now().await;
+        let commit = CanonStateNotification::Commit {
+            new: Arc::new(Chain::new(vec![SealedBlockWithSenders::default()], Default::default())),
+        };
+        canon_state_tx.send(commit.clone()).unwrap();
+        assert_eq!(notifications.recv().await.unwrap(), commit);
+        assert!(!forward.is_finished());
+
+        drop(notifications);
+        forward.await.unwrap();
+    }
+}
@@ -3,6 +3,7 @@
 use crate::cli::{
     components::{RethNodeComponents, RethRpcComponents},
     config::{PayloadBuilderConfig, RethRpcConfig},
+    exex::ExExRegistry,
 };
 use clap::Args;
 use reth_basic_payload_builder::{BasicPayloadJobGenerator, BasicPayloadJobGeneratorConfig};
@@ -48,6 +49,18 @@ pub trait RethNodeCommandConfig: fmt::Debug {
         Ok(())
     }
 
+    /// Installs execution extensions, which are launched once all components have been
+    /// initialized and notified of the chains committed by the node.
+    ///
+    /// See [ExExRegistry::install].
+    fn install_exexs<Reth>(&mut self, exexs: &mut ExExRegistry<Reth>) -> eyre::Result<()>
+    where
+        Reth: RethNodeComponents + Clone + Send + 'static,
+    {
+        let _ = exexs;
+        Ok(())
+    }
+
     /// Event hook called once the rpc servers has been started.
     fn on_rpc_server_started<Conf, Reth>(
         &mut self,
@@ -110,7 +123,10 @@ pub trait RethNodeCommandConfig: fmt::Debug {
                 .deadline(conf.deadline())
                 .max_payload_tasks(conf.max_payload_tasks())
                 .extradata(conf.extradata_rlp_bytes())
-                .max_gas_limit(conf.max_gas_limit()),
+                .max_gas_limit(conf.max_gas_limit())
+                .fee_recipient(conf.fee_recipient())
+                .min_tip(conf.min_tip())
+                .ordering(conf.ordering()),
             components.chain_spec(),
         );
         let (payload_service, payload_builder) = PayloadBuilderService::new(payload_generator);
@@ -211,6 +227,17 @@ impl<T: RethNodeCommandConfig> RethNodeCommandConfig for NoArgs<T> {
         }
     }
 
+    fn install_exexs<Reth>(&mut self, exexs: &mut ExExRegistry<Reth>) -> eyre::Result<()>
+    where
+        Reth: RethNodeComponents + Clone + Send + 'static,
+    {
+        if let Some(conf) = self.inner_mut() {
+            conf.install_exexs(exexs)
+        } else {
+            Ok(())
+        }
+    }
+
     fn on_rpc_server_started<Conf, Reth>(
         &mut self,
         config: &Conf,
@@ -11,16 +11,19 @@ use crate::{
     version::{LONG_VERSION, SHORT_VERSION},
 };
 use clap::{value_parser, ArgAction, Args, Parser, Subcommand, ValueEnum};
+use eyre::WrapErr;
+use reth_config::{config::LogsConfig, Config};
 use reth_primitives::ChainSpec;
 use reth_tracing::{
     tracing::{metadata::LevelFilter, Level, Subscriber},
     tracing_subscriber::{filter::Directive, registry::LookupSpan, EnvFilter},
     BoxedLayer, FileWorkerGuard,
 };
-use std::{fmt, fmt::Display, sync::Arc};
+use std::{fmt, fmt::Display, path::PathBuf, sync::Arc};
 
 pub mod components;
 pub mod config;
+pub mod exex;
 pub mod ext;
 
 /// The main reth cli interface.
@@ -35,7 +38,8 @@ pub struct Cli<Ext: RethCliExt = ()> {
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -88,6 +92,7 @@ impl<Ext: RethCliExt> Cli<Ext> {
             Commands::Node(command) => runner.run_command_until_exit(|ctx| command.execute(ctx)),
             Commands::Init(command) => runner.run_blocking_until_ctrl_c(command.execute()),
             Commands::Import(command) => runner.run_blocking_until_ctrl_c(command.execute()),
+            Commands::Export(command) => runner.run_blocking_until_ctrl_c(command.execute()),
             Commands::Db(command) => runner.run_blocking_until_ctrl_c(command.execute()),
             Commands::Stage(command) => runner.run_blocking_until_ctrl_c(command.execute()),
             Commands::P2P(command) => runner.run_until_ctrl_c(command.execute()),
@@ -102,10 +107,25 @@ impl<Ext: RethCliExt> Cli<Ext> {
     ///
     /// If file logging is enabled, this function returns a guard that must be kept alive to ensure
     /// that all logs are flushed to disk.
+    ///
+    /// Commands printing machine readable output to stdout only log errors, to stderr.
+    ///
+    /// The levels of targets set in the `[logs]` section of the config file of the node command
+    /// override the levels of stdout and the log file.
     pub fn init_tracing(&self) -> eyre::Result<Option<FileWorkerGuard>> {
-        let mut layers =
-            vec![reth_tracing::stdout(self.verbosity.directive(), &self.logs.color.to_string())];
-        let guard = self.logs.layer()?.map(|(layer, guard)| {
+        let color = self.logs.color.to_string();
+        let format = self.logs.format.into();
+        let directives = reth_tracing::parse_directives(&self.logs_config()?.directives())
+            .wrap_err("Invalid log levels in the [logs] section of the config")?;
+
+        let mut layers = if self.command.is_machine_output() {
+            let filter = reth_tracing::env_filter(LevelFilter::ERROR, Vec::new());
+            vec![reth_tracing::stderr(filter, &color, format)]
+        } else {
+            let filter = reth_tracing::env_filter(self.verbosity.directive(), directives.clone());
+            vec![reth_tracing::stdout(filter, &color, format)]
+        };
+        let guard = self.logs.layer(directives)?.map(|(layer, guard)| {
             layers.push(layer);
             guard
         });
@@ -114,6 +134,21 @@ impl<Ext: RethCliExt> Cli<Ext> {
         Ok(guard.flatten())
     }
 
+    /// Loads the logging configuration from the config file of the command, if it has one and it
+    /// exists.
+    fn logs_config(&self) -> eyre::Result<LogsConfig> {
+        let Some(config_path) = self.command.config_path() else {
+            return Ok(LogsConfig::default())
+        };
+        if !config_path.exists() {
+            return Ok(LogsConfig::default())
+        }
+
+        let config = confy::load_path::<Config>(&config_path)
+            .wrap_err_with(|| format!("Could not load config file {}", config_path.display()))?;
+        Ok(config.logs)
+    }
+
     /// Configures the given node extension.
     pub fn with_node_extension<C>(mut self, conf: C) -> Self
     where
@@ -139,9 +174,12 @@ pub enum Commands<Ext: RethCliExt = ()> {
     /// Initialize the database from a genesis file.
     #[command(name = "init")]
     Init(chain::InitCommand),
-    /// This syncs RLP encoded blocks from a file.
+    /// This syncs RLP encoded blocks or era1 archives from a file.
     #[command(name = "import")]
     Import(chain::ImportCommand),
+    /// Exports blocks from the database to files of other clients.
+    #[command(name = "export")]
+    Export(chain::ExportCommand),
     /// Database debugging utilities
     #[command(name = "db")]
     Db(db::Command),
@@ -174,6 +212,30 @@ impl<Ext: RethCliExt> Commands<Ext> {
             command.ext = ext
         }
     }
+
+    /// Whether the command prints machine readable output to stdout, which the logs must not
+    /// interleave with.
+    pub fn is_machine_output(&self) -> bool {
+        match self {
+            Commands::Stage(command) => command.is_machine_output(),
+            Commands::Db(command) => command.is_machine_output(),
+            Commands::Debug(command) => command.is_machine_output(),
+            Commands::Node(command) => command.is_machine_output(),
+            _ => false,
+        }
+    }
+
+    /// The config file the command loads, if it loads one.
+    fn config_path(&self) -> Option<PathBuf> {
+        match self {
+            Commands::Node(command) => Some(command.config.clone().unwrap_or_else(|| {
+                // the data dir of the node is named after the chain id with its overrides
+                let chain = command.chain_overrides.apply(command.chain.clone());
+                command.datadir.unwrap_or_chain_default(chain.chain).config_path()
+            })),
+            _ => None,
+        }
+    }
 }
 
 /// The log configuration.
@@ -190,13 +252,25 @@ pub struct Logs {
     )]
     log_directory: PlatformPath<LogsDir>,
 
-    /// The maximum size (in MB) of log files.
-    #[arg(long = "log.max-size", value_name = "SIZE", global = true, default_value_t = 200)]
+    /// The maximum size (in MB) of a log file, after which it's rotated.
+    #[arg(
+        long = "log.file.max-size",
+        alias = "log.max-size",
+        value_name = "SIZE",
+        global = true,
+        default_value_t = 200
+    )]
     log_max_size: u64,
 
-    /// The maximum amount of log files that will be stored. If set to 0, background file logging
-    /// is disabled.
-    #[arg(long = "log.max-files", value_name = "COUNT", global = true, default_value_t = 5)]
+    /// The maximum amount of log files that will be stored, the oldest one being deleted on
+    /// rotation. If set to 0, background file logging is disabled.
+    #[arg(
+        long = "log.file.max-files",
+        alias = "log.max-files",
+        value_name = "COUNT",
+        global = true,
+        default_value_t = 5
+    )]
     log_max_files: usize,
 
     /// Log events to journald.
@@ -207,6 +281,10 @@ pub struct Logs {
     #[arg(long = "log.filter", value_name = "FILTER", global = true, default_value = "error")]
     filter: String,
 
+    /// The format of the logs written to stdout and the log file.
+    #[arg(long = "log.format", value_name = "FORMAT", global = true, default_value_t = LogFormat::Terminal)]
+    format: LogFormat,
+
     /// Sets whether or not the formatter emits ANSI terminal escape codes for colors and other
     /// text formatting.
     #[arg(
@@ -222,13 +300,21 @@ pub struct Logs {
 const MB_TO_BYTES: u64 = 1024 * 1024;
 
 impl Logs {
-    /// Builds a tracing layer from the current log options.
-    pub fn layer<S>(&self) -> eyre::Result<Option<(BoxedLayer<S>, Option<FileWorkerGuard>)>>
+    /// Builds a tracing layer from the current log options, with `directives` overriding the
+    /// levels of `--log.filter`.
+    pub fn layer<S>(
+        &self,
+        directives: impl IntoIterator<Item = Directive>,
+    ) -> eyre::Result<Option<(BoxedLayer<S>, Option<FileWorkerGuard>)>>
     where
         S: Subscriber,
         for<'a> S: LookupSpan<'a>,
     {
-        let filter = EnvFilter::builder().parse(&self.filter)?;
+        let filter = directives
+            .into_iter()
+            .fold(EnvFilter::builder().parse(&self.filter)?, |filter, directive| {
+                filter.add_directive(directive)
+            });
 
         if self.journald {
             Ok(Some((reth_tracing::journald(filter).expect("Could not connect to journald"), None)))
@@ -239,6 +325,7 @@ impl Logs {
                 "reth.log",
                 self.log_max_size * MB_TO_BYTES,
                 self.log_max_files,
+                self.format.into(),
             );
             Ok(Some((layer, Some(guard))))
         } else {
@@ -307,11 +394,73 @@ impl Display for ColorMode {
     }
 }
 
+/// The format of the logs.
+#[derive(Debug, Copy, Clone, ValueEnum, Eq, PartialEq)]
+pub enum LogFormat {
+    /// Human readable lines
+    Terminal,
+    /// A JSON object per line
+    Json,
+}
+
+impl Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogFormat::Terminal => write!(f, "terminal"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl From<LogFormat> for reth_tracing::LogFormat {
+    fn from(format: LogFormat) -> Self {
+        match format {
+            LogFormat::Terminal => reth_tracing::LogFormat::Terminal,
+            LogFormat::Json => reth_tracing::LogFormat::Json,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use clap::CommandFactory;
 
+    #[test]
+    fn parse_log_file_args() {
+        let reth = Cli::<()>::try_parse_from([
+            "reth",
+            "node",
+            "--log.format",
+            "json",
+            "--log.file.max-size",
+            "10",
+            "--log.max-files",
+            "2",
+        ])
+        .unwrap();
+        assert_eq!(reth.logs.format, LogFormat::Json);
+        assert_eq!(reth.logs.log_max_size, 10);
+        assert_eq!(reth.logs.log_max_files, 2);
+
+        let reth = Cli::<()>::try_parse_from(["reth", "node"]).unwrap();
+        assert_eq!(reth.logs.format, LogFormat::Terminal);
+    }
+
+    #[test]
+    fn load_logs_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("reth.toml");
+        let args = ["reth", "node", "--config", config_path.to_str().unwrap()];
+
+        let reth = Cli::<()>::try_parse_from(args).unwrap();
+        assert_eq!(reth.logs_config().unwrap(), LogsConfig::default());
+
+        std::fs::write(&config_path, "[logs.targets]\n\"reth::stages\" = \"debug\"\n").unwrap();
+        let reth = Cli::<()>::try_parse_from(args).unwrap();
+        assert_eq!(reth.logs_config().unwrap().directives(), "reth::stages=debug");
+    }
+
     #[test]
     fn parse_color_mode() {
         let reth = Cli::<()>::try_parse_from(["reth", "node", "--color", "always"]).unwrap();
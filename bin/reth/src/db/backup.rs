@@ -0,0 +1,88 @@
+//! `reth db backup`: a consistent copy of the database, made while a node keeps using it.
+use clap::Parser;
+use human_bytes::human_bytes;
+use reth_db::{version::db_version_file_path, DatabaseEnvRO};
+use std::{
+    path::{Path, PathBuf},
+    time::Instant,
+};
+use tracing::info;
+
+/// Name of the MDBX data file of an environment.
+const DATA_FILE: &str = "mdbx.dat";
+
+/// The arguments for the `reth db backup` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The directory the backup is written to, which can be used as the database directory of a
+    /// datadir as is. It must not exist yet.
+    #[arg(value_name = "PATH")]
+    output: PathBuf,
+
+    /// Leaves the free pages out of the backup and renumbers the others, which makes it smaller
+    /// at the cost of some CPU.
+    #[arg(long)]
+    compact: bool,
+}
+
+impl Command {
+    /// Execute `db backup` command
+    ///
+    /// The copy is made in a single read transaction, so a syncing node keeps writing meanwhile,
+    /// but can't reuse the pages freed since the copy started: the database may grow until it's
+    /// done.
+    pub fn execute(self, db: &DatabaseEnvRO, db_path: &Path) -> eyre::Result<()> {
+        if self.output.exists() {
+            eyre::bail!("The backup directory {:?} already exists.", self.output)
+        }
+        std::fs::create_dir_all(&self.output)?;
+
+        info!(target: "reth::cli", path = ?self.output, compact = self.compact, "Backing up database");
+        let started = Instant::now();
+        let data_file = self.output.join(DATA_FILE);
+        db.inner.copy(&data_file, self.compact)?;
+        std::fs::copy(db_version_file_path(db_path), db_version_file_path(&self.output))?;
+
+        let size = std::fs::metadata(&data_file)?.len();
+        info!(
+            target: "reth::cli",
+            path = ?self.output,
+            size = human_bytes(size as f64),
+            elapsed = ?started.elapsed(),
+            "Backed up database"
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        database::Database,
+        init_db, open_db_read_only, tables,
+        transaction::{DbTx, DbTxMut},
+        version::get_db_version,
+    };
+
+    #[test]
+    fn backup_while_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("db");
+        let db = init_db(&db_path, None).unwrap();
+        db.update(|tx| tx.put::<tables::CanonicalHeaders>(1, Default::default())).unwrap().unwrap();
+
+        let reader = open_db_read_only(&db_path, None).unwrap();
+        let output = dir.path().join("backup");
+        Command { output: output.clone(), compact: true }.execute(&reader, &db_path).unwrap();
+        assert!(Command { output: output.clone(), compact: false }
+            .execute(&reader, &db_path)
+            .is_err());
+
+        assert!(get_db_version(&output).is_ok());
+        let backup = open_db_read_only(&output, None).unwrap();
+        let hash = backup.view(|tx| tx.get::<tables::CanonicalHeaders>(1)).unwrap().unwrap();
+        assert_eq!(hash, Some(Default::default()));
+    }
+}
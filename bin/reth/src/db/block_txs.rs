@@ -0,0 +1,86 @@
+use crate::utils::DbTool;
+use clap::Parser;
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use reth_db::{database::Database, tables, transaction::DbTx};
+use reth_primitives::{Address, BlockNumber, Signature, Transaction, TxHash, TxNumber, U256};
+use serde::Serialize;
+
+/// The arguments for the `reth db block-txs` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The block to print the transactions of.
+    #[arg(long)]
+    block: BlockNumber,
+
+    /// Prints the transactions as a JSON array instead of a table.
+    #[arg(long)]
+    json: bool,
+}
+
+/// A transaction of the block, along with its sender if [`tables::TxSenders`] holds it.
+#[derive(Debug, Serialize)]
+struct BlockTransaction {
+    tx_number: TxNumber,
+    hash: TxHash,
+    from: Option<Address>,
+    signature: Signature,
+    transaction: Transaction,
+}
+
+impl Command {
+    /// Execute `db block-txs` command
+    pub fn execute<DB: Database>(self, tool: &DbTool<'_, DB>) -> eyre::Result<()> {
+        let block = self.block;
+        let indices = tool
+            .get::<tables::BlockBodyIndices>(block)?
+            .ok_or_else(|| eyre::eyre!("Block body {block} does not exist."))?;
+
+        let transactions = tool.db.view(|tx| {
+            indices
+                .tx_num_range()
+                .map(|tx_number| {
+                    let transaction =
+                        tx.get::<tables::Transactions>(tx_number)?.ok_or_else(|| {
+                            eyre::eyre!("Transaction {tx_number} of block {block} does not exist.")
+                        })?;
+                    Ok(BlockTransaction {
+                        tx_number,
+                        hash: transaction.hash(),
+                        from: tx.get::<tables::TxSenders>(tx_number)?,
+                        signature: transaction.signature,
+                        transaction: transaction.transaction,
+                    })
+                })
+                .collect::<eyre::Result<Vec<_>>>()
+        })??;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&transactions)?);
+            return Ok(())
+        }
+
+        let mut txs_table = ComfyTable::new();
+        txs_table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+        txs_table.set_header(["Tx Number", "Hash", "From", "To", "Value"]);
+        for transaction in &transactions {
+            let mut row = Row::new();
+            row.add_cell(Cell::new(transaction.tx_number))
+                .add_cell(Cell::new(transaction.hash))
+                .add_cell(Cell::new(
+                    transaction.from.map_or_else(|| "unknown".to_string(), |from| from.to_string()),
+                ))
+                .add_cell(Cell::new(
+                    transaction
+                        .transaction
+                        .to()
+                        .map_or_else(|| "create".to_string(), |to| to.to_string()),
+                ))
+                .add_cell(Cell::new(U256::from(transaction.transaction.value())));
+            txs_table.add_row(row);
+        }
+
+        println!("{txs_table}");
+
+        Ok(())
+    }
+}
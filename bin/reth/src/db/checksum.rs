@@ -0,0 +1,241 @@
+//! `reth db checksum`: digests of the raw rows of tables, to check that the databases of two nodes
+//! are identical without shipping them.
+use super::range::TableRanges;
+use crate::utils::DbTool;
+use clap::Parser;
+use reth_db::{
+    cursor::DbCursorRO, database::Database, table::Table, transaction::DbTx, AccountChangeSet,
+    AccountHistory, AccountsTrie, BlockBodyIndices, BlockOmmers, BlockWithdrawals, Bytecodes,
+    CanonicalHeaders, HashedAccount, HashedStorage, HeaderNumbers, HeaderTD, Headers,
+    PlainAccountState, PlainStorageState, PruneCheckpoints, RawKey, RawTable, Receipts,
+    StorageChangeSet, StorageHistory, StoragesTrie, SyncStage, SyncStageProgress, Tables,
+    TransactionBlock, Transactions, TxHashNumber, TxSenders,
+};
+use reth_primitives::{BlockNumber, B256};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::ops::{Bound, RangeBounds};
+use tracing::info;
+
+/// The arguments for the `reth db checksum` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The tables to checksum, all of them but the node-local `SyncStage`, `SyncStageProgress` and
+    /// `PruneCheckpoints` if not passed. Can be passed multiple times, or as a comma-separated
+    /// list.
+    #[arg(long, value_delimiter = ',', verbatim_doc_comment)]
+    table: Vec<Tables>,
+
+    /// The first block of the range to checksum.
+    ///
+    /// Only the rows of the tables keyed by block or transaction number are limited to the range.
+    /// The other tables are checksummed whole.
+    #[arg(long, value_name = "BLOCK", requires = "to", verbatim_doc_comment)]
+    from: Option<BlockNumber>,
+
+    /// The last block of the range to checksum.
+    #[arg(long, value_name = "BLOCK", requires = "from")]
+    to: Option<BlockNumber>,
+
+    /// Prints the checksums as JSON instead of a line per table.
+    #[arg(long)]
+    json: bool,
+}
+
+/// The tables holding the progress of the stages and the pruner of the node, which differ between
+/// nodes with the same chain data and are left out of the checksums unless passed with `--table`.
+const NODE_LOCAL_TABLES: [Tables; 3] =
+    [Tables::SyncStage, Tables::SyncStageProgress, Tables::PruneCheckpoints];
+
+/// The checksum of the rows of a table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct TableChecksum {
+    pub(crate) table: &'static str,
+    pub(crate) rows: usize,
+    /// The SHA-256 of the length-prefixed raw key and value of every row, in key order.
+    pub(crate) sha256: B256,
+}
+
+/// The checksums of the tables, along with the one of all of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Checksums {
+    pub(crate) from: Option<BlockNumber>,
+    pub(crate) to: Option<BlockNumber>,
+    pub(crate) tables: Vec<TableChecksum>,
+    /// The SHA-256 of the checksums of the tables, in order.
+    pub(crate) sha256: B256,
+}
+
+impl Command {
+    /// Execute `db checksum` command
+    pub fn execute<DB: Database>(self, tool: &DbTool<'_, DB>) -> eyre::Result<()> {
+        let range = self.from.zip(self.to);
+        let tables = if self.table.is_empty() { default_tables() } else { self.table };
+        let checksums = checksums(tool, &tables, range)?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&checksums)?);
+        } else {
+            for table in &checksums.tables {
+                println!("{}  {} ({} rows)", table.sha256, table.table, table.rows);
+            }
+            println!("{}  total", checksums.sha256);
+        }
+
+        Ok(())
+    }
+
+    /// Whether the command prints its checksums as JSON to stdout.
+    pub(crate) fn is_machine_output(&self) -> bool {
+        self.json
+    }
+}
+
+/// The tables checksummed if none are passed: all of them but the [`NODE_LOCAL_TABLES`].
+fn default_tables() -> Vec<Tables> {
+    Tables::ALL.iter().copied().filter(|table| !NODE_LOCAL_TABLES.contains(table)).collect()
+}
+
+/// Checksums `tables` in a single transaction, limited to the blocks `from..=to` if `range` is
+/// passed.
+pub(crate) fn checksums<DB: Database>(
+    tool: &DbTool<'_, DB>,
+    tables: &[Tables],
+    range: Option<(BlockNumber, BlockNumber)>,
+) -> eyre::Result<Checksums> {
+    let ranges = TableRanges::new(tool, range)?;
+    let tables = tool.db.view(|tx| {
+        tables
+            .iter()
+            .map(|table| {
+                let checksum = checksum_table(tx, *table, &ranges)?;
+                info!(target: "reth::cli", table = checksum.table, rows = checksum.rows, sha256 = %checksum.sha256, "Checksummed table");
+                Ok(checksum)
+            })
+            .collect::<eyre::Result<Vec<_>>>()
+    })??;
+
+    let mut hasher = Sha256::new();
+    for table in &tables {
+        hasher.update(table.sha256);
+    }
+    let (from, to) = range.unzip();
+    Ok(Checksums { from, to, tables, sha256: B256::from_slice(&hasher.finalize()) })
+}
+
+/// Checksums the rows of `table` within `ranges`.
+fn checksum_table(
+    tx: &impl DbTx,
+    table: Tables,
+    ranges: &TableRanges,
+) -> eyre::Result<TableChecksum> {
+    match table {
+        Tables::CanonicalHeaders => checksum_rows::<CanonicalHeaders>(tx, ranges.blocks),
+        Tables::HeaderTD => checksum_rows::<HeaderTD>(tx, ranges.blocks),
+        Tables::HeaderNumbers => checksum_rows::<HeaderNumbers>(tx, ..),
+        Tables::Headers => checksum_rows::<Headers>(tx, ranges.blocks),
+        Tables::BlockBodyIndices => checksum_rows::<BlockBodyIndices>(tx, ranges.blocks),
+        Tables::BlockOmmers => checksum_rows::<BlockOmmers>(tx, ranges.blocks),
+        Tables::BlockWithdrawals => checksum_rows::<BlockWithdrawals>(tx, ranges.blocks),
+        Tables::TransactionBlock => checksum_rows::<TransactionBlock>(tx, ranges.transactions),
+        Tables::Transactions => checksum_rows::<Transactions>(tx, ranges.transactions),
+        Tables::TxHashNumber => checksum_rows::<TxHashNumber>(tx, ..),
+        Tables::Receipts => checksum_rows::<Receipts>(tx, ranges.transactions),
+        Tables::PlainAccountState => checksum_rows::<PlainAccountState>(tx, ..),
+        Tables::PlainStorageState => checksum_rows::<PlainStorageState>(tx, ..),
+        Tables::Bytecodes => checksum_rows::<Bytecodes>(tx, ..),
+        Tables::AccountHistory => checksum_rows::<AccountHistory>(tx, ..),
+        Tables::StorageHistory => checksum_rows::<StorageHistory>(tx, ..),
+        Tables::AccountChangeSet => checksum_rows::<AccountChangeSet>(tx, ranges.blocks),
+        Tables::StorageChangeSet => checksum_rows::<StorageChangeSet>(tx, ranges.storage_changes),
+        Tables::HashedAccount => checksum_rows::<HashedAccount>(tx, ..),
+        Tables::HashedStorage => checksum_rows::<HashedStorage>(tx, ..),
+        Tables::AccountsTrie => checksum_rows::<AccountsTrie>(tx, ..),
+        Tables::StoragesTrie => checksum_rows::<StoragesTrie>(tx, ..),
+        Tables::TxSenders => checksum_rows::<TxSenders>(tx, ranges.transactions),
+        Tables::SyncStage => checksum_rows::<SyncStage>(tx, ..),
+        Tables::SyncStageProgress => checksum_rows::<SyncStageProgress>(tx, ..),
+        Tables::PruneCheckpoints => checksum_rows::<PruneCheckpoints>(tx, ..),
+    }
+}
+
+/// Hashes the raw key and value of every row of `T` in `range`, including every value of the keys
+/// of dupsort tables.
+///
+/// The rows are hashed as stored, so the checksums only match between databases written with the
+/// same encoding of the tables.
+fn checksum_rows<T: Table>(
+    tx: &impl DbTx,
+    range: impl RangeBounds<T::Key>,
+) -> eyre::Result<TableChecksum> {
+    let raw = |bound: Bound<&T::Key>| match bound {
+        Bound::Included(key) => Bound::Included(RawKey::new(key.clone())),
+        Bound::Excluded(key) => Bound::Excluded(RawKey::new(key.clone())),
+        Bound::Unbounded => Bound::Unbounded,
+    };
+    let range = (raw(range.start_bound()), raw(range.end_bound()));
+
+    let mut hasher = Sha256::new();
+    let mut rows = 0;
+    for entry in tx.cursor_read::<RawTable<T>>()?.walk_range(range)? {
+        let (key, value) = entry?;
+        for field in [key.raw_key().as_slice(), value.raw_value()] {
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field);
+        }
+        rows += 1;
+    }
+
+    Ok(TableChecksum { table: T::NAME, rows, sha256: B256::from_slice(&hasher.finalize()) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        test_utils::create_test_rw_db, transaction::DbTxMut, DatabaseEnv, DatabaseError,
+    };
+    use reth_primitives::{Header, MAINNET};
+    use std::sync::Arc;
+
+    #[test]
+    fn checksum_headers() {
+        let put_headers = |gas_limit: u64| {
+            let db = create_test_rw_db();
+            db.update(|tx| {
+                for number in 0..5 {
+                    tx.put::<Headers>(number, Header { number, gas_limit, ..Default::default() })?;
+                    tx.put::<BlockBodyIndices>(number, Default::default())?;
+                }
+                Ok::<_, DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+            db
+        };
+        let (a, b, other) = (put_headers(1), put_headers(1), put_headers(2));
+        let checksum = |db: &Arc<DatabaseEnv>, range| {
+            let tool = DbTool::new(&**db, MAINNET.clone()).unwrap();
+            checksums(&tool, &[Tables::Headers, Tables::PlainAccountState], range).unwrap()
+        };
+
+        let all = checksum(&a, None);
+        assert_eq!(all, checksum(&b, None));
+        assert_ne!(all.sha256, checksum(&other, None).sha256);
+        assert_eq!(all.tables[0].rows, 5);
+        assert_eq!(all.tables[1], checksum(&other, None).tables[1]);
+
+        let range = checksum(&a, Some((1, 2)));
+        assert_eq!(range.tables[0].rows, 2);
+        assert_eq!((range.from, range.to), (Some(1), Some(2)));
+        assert_ne!(range.sha256, all.sha256);
+    }
+
+    #[test]
+    fn skip_node_local_tables() {
+        let tables = default_tables();
+        assert_eq!(tables.len(), Tables::ALL.len() - NODE_LOCAL_TABLES.len());
+        assert!(tables.contains(&Tables::Headers));
+        assert!(!tables.iter().any(|table| NODE_LOCAL_TABLES.contains(table)));
+    }
+}
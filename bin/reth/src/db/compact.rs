@@ -0,0 +1,272 @@
+//! `reth db compact`: rewrites the database without its free pages, to give the space that
+//! pruning or an unwind freed back to the filesystem.
+use clap::{builder::RangedU64ValueParser, Parser};
+use human_bytes::human_bytes;
+use reth_db::{init_db, mdbx::WriteFlags, open_db_exclusive, DatabaseEnv, TableType, Tables};
+use reth_interfaces::db::LogLevel;
+use std::{
+    borrow::Cow,
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+use tracing::{info, warn};
+
+/// Name of the MDBX data file of an environment.
+const DATA_FILE: &str = "mdbx.dat";
+
+/// Name of the MDBX lock file of an environment.
+const LOCK_FILE: &str = "mdbx.lck";
+
+/// Name of the directory in the database directory the compacted database is written to.
+const COMPACT_DIR: &str = "compact";
+
+/// The arguments for the `reth db compact` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The number of rows copied in a single write transaction.
+    #[arg(long, value_name = "ROWS", default_value_t = 1_000_000, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    batch_size: usize,
+
+    /// Keeps the data file from before the compaction as `mdbx.dat.old` in the database
+    /// directory. It takes no space until the compacted one replaces it.
+    #[arg(long)]
+    keep_old: bool,
+
+    /// Skips the check that the filesystem has room for the compacted copy.
+    #[arg(long)]
+    skip_space_check: bool,
+}
+
+impl Command {
+    /// Execute `db compact` command
+    ///
+    /// The database is opened exclusively, so the command fails if a node is running on it. The
+    /// tables are copied in key order into a fresh environment next to the database, whose data
+    /// file then atomically replaces the one of the database.
+    pub fn execute(self, db_path: &Path, log_level: Option<LogLevel>) -> eyre::Result<()> {
+        let db = open_db_exclusive(db_path, log_level)?;
+        let data_file = db_path.join(DATA_FILE);
+        let size = fs::metadata(&data_file)?.len();
+        let used = used_size(&db)?;
+        info!(
+            target: "reth::cli",
+            size = human_bytes(size as f64),
+            used = human_bytes(used as f64),
+            "Compacting database"
+        );
+
+        if self.skip_space_check {
+            warn!(target: "reth::cli", "Skipping the free space check");
+        } else if let Some(available) = available_space(db_path)? {
+            if available < used {
+                eyre::bail!(
+                    "The compacted copy takes up to {}, but only {} are available on the filesystem of {db_path:?}.",
+                    human_bytes(used as f64),
+                    human_bytes(available as f64)
+                )
+            }
+        }
+
+        let compact_path = db_path.join(COMPACT_DIR);
+        if compact_path.exists() {
+            // left over by an interrupted compaction
+            fs::remove_dir_all(&compact_path)?;
+        }
+
+        let started = Instant::now();
+        {
+            let compacted = init_db(&compact_path, log_level)?;
+            for table in Tables::ALL {
+                copy_table(&db, &compacted, table, self.batch_size)?;
+            }
+        }
+        drop(db);
+
+        let compacted_size = fs::metadata(compact_path.join(DATA_FILE))?.len();
+        swap_data_file(db_path, &compact_path, self.keep_old)?;
+        info!(
+            target: "reth::cli",
+            size = human_bytes(compacted_size as f64),
+            reclaimed = human_bytes(size.saturating_sub(compacted_size) as f64),
+            elapsed = ?started.elapsed(),
+            "Compacted database"
+        );
+
+        Ok(())
+    }
+}
+
+/// Returns the size of the pages of the database which aren't free, which bounds the size of its
+/// compacted copy.
+fn used_size(db: &DatabaseEnv) -> eyre::Result<u64> {
+    let info = db.inner.info()?;
+    let pages = (info.last_pgno() + 1).saturating_sub(db.inner.freelist()?);
+    Ok((pages * info.page_size()) as u64)
+}
+
+/// Returns the space available to unprivileged users on the filesystem of `path`, if the platform
+/// tells.
+#[cfg(unix)]
+fn available_space(path: &Path) -> eyre::Result<Option<u64>> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is a valid C string, and `stat` is only read once `statvfs` filled it.
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return Err(std::io::Error::last_os_error().into())
+    }
+    let stat = unsafe { stat.assume_init() };
+    Ok(Some(stat.f_bavail as u64 * stat.f_frsize as u64))
+}
+
+#[cfg(not(unix))]
+fn available_space(_path: &Path) -> eyre::Result<Option<u64>> {
+    warn!(target: "reth::cli", "Can't check the free space on this platform");
+    Ok(None)
+}
+
+/// Copies the raw rows of `table` from `db` to the empty `compacted`, in key order.
+///
+/// The rows are appended, which fills the pages of the copy up instead of splitting them.
+fn copy_table(
+    db: &DatabaseEnv,
+    compacted: &DatabaseEnv,
+    table: Tables,
+    batch_size: usize,
+) -> eyre::Result<()> {
+    let flags = match table.table_type() {
+        TableType::Table => WriteFlags::APPEND,
+        TableType::DupSort => WriteFlags::APPEND_DUP,
+    };
+
+    let tx = db.inner.begin_ro_txn()?;
+    let table_db = tx.open_db(Some(table.name()))?;
+    let entries = tx.db_stat(&table_db)?.entries();
+    info!(target: "reth::cli", table = table.name(), entries, "Compacting table");
+
+    let mut cursor = tx.cursor(&table_db)?;
+    let mut rows = cursor.iter_start::<Cow<'_, [u8]>, Cow<'_, [u8]>>().peekable();
+    let mut copied = 0;
+    while rows.peek().is_some() {
+        let compacted_tx = compacted.inner.begin_rw_txn()?;
+        let compacted_db = compacted_tx.open_db(Some(table.name()))?;
+        let mut compacted_cursor = compacted_tx.cursor(&compacted_db)?;
+        for row in rows.by_ref().take(batch_size) {
+            let (key, value) = row?;
+            compacted_cursor.put(&key, &value, flags)?;
+            copied += 1;
+        }
+        drop(compacted_cursor);
+        compacted_tx.commit()?;
+
+        info!(
+            target: "reth::cli",
+            table = table.name(),
+            copied,
+            progress = %format!("{:.2}%", copied as f64 * 100.0 / entries.max(1) as f64),
+            "Compacting table"
+        );
+    }
+
+    Ok(())
+}
+
+/// Replaces the data file of the database at `db_path` with the compacted one, and removes the
+/// rest of the compacted environment.
+///
+/// The rename is atomic, so the database is either the old or the compacted one if the process is
+/// interrupted.
+fn swap_data_file(db_path: &Path, compact_path: &Path, keep_old: bool) -> eyre::Result<()> {
+    let data_file = db_path.join(DATA_FILE);
+    if keep_old {
+        let old_file = old_data_file(db_path);
+        if old_file.exists() {
+            fs::remove_file(&old_file)?;
+        }
+        fs::hard_link(&data_file, &old_file)?;
+        info!(target: "reth::cli", path = ?old_file, "Kept the data file from before the compaction");
+    }
+
+    fs::rename(compact_path.join(DATA_FILE), &data_file)?;
+    // The lock file describes the replaced data file, MDBX recreates it on the next open.
+    let lock_file = db_path.join(LOCK_FILE);
+    if lock_file.exists() {
+        fs::remove_file(lock_file)?;
+    }
+    #[cfg(unix)]
+    fs::File::open(db_path)?.sync_all()?;
+
+    fs::remove_dir_all(compact_path)?;
+    Ok(())
+}
+
+/// Returns the path the data file from before the compaction is kept at.
+fn old_data_file(db_path: &Path) -> PathBuf {
+    db_path.join(format!("{DATA_FILE}.old"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        database::Database,
+        open_db_read_only, tables,
+        transaction::{DbTx, DbTxMut},
+        DatabaseError,
+    };
+    use reth_primitives::{StorageEntry, B256, U256};
+
+    #[test]
+    fn compact_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("db");
+        {
+            let db = init_db(&db_path, None).unwrap();
+            db.update(|tx| {
+                for number in 0..1000u64 {
+                    tx.put::<tables::CanonicalHeaders>(number, B256::with_last_byte(number as u8))?;
+                }
+                for slot in 0..100u8 {
+                    tx.put::<tables::PlainStorageState>(
+                        Default::default(),
+                        StorageEntry { key: B256::with_last_byte(slot), value: U256::from(slot) },
+                    )?;
+                }
+                Ok::<_, DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+            db.update(|tx| {
+                for number in 10..1000u64 {
+                    tx.delete::<tables::CanonicalHeaders>(number, None)?;
+                }
+                Ok::<_, DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+        }
+
+        Command { batch_size: 3, keep_old: true, skip_space_check: false }
+            .execute(&db_path, None)
+            .unwrap();
+        assert!(!db_path.join(COMPACT_DIR).exists());
+        assert!(old_data_file(&db_path).exists());
+
+        let db = open_db_read_only(&db_path, None).unwrap();
+        let (headers, storage) = db
+            .view(|tx| {
+                Ok::<_, DatabaseError>((
+                    tx.entries::<tables::CanonicalHeaders>()?,
+                    tx.entries::<tables::PlainStorageState>()?,
+                ))
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(headers, 10);
+        assert_eq!(storage, 100);
+        let hash = db.view(|tx| tx.get::<tables::CanonicalHeaders>(9)).unwrap().unwrap();
+        assert_eq!(hash, Some(B256::with_last_byte(9)));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::utils::DbTool;
+use clap::Parser;
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use reth_db::{
+    cursor::DbCursorRO, database::Database, models::BlockNumberAddress, tables, transaction::DbTx,
+};
+use reth_primitives::{keccak256, stage::StageId, BlockNumber, B256, U256};
+use reth_trie::{
+    hashed_cursor::{HashedPostState, HashedPostStateCursorFactory, HashedStorage},
+    StateRoot,
+};
+use std::collections::HashMap;
+
+/// The arguments for the `reth db compare-roots` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The blocks to recompute the state root at.
+    #[arg(required = true)]
+    blocks: Vec<BlockNumber>,
+}
+
+impl Command {
+    /// Execute `db compare-roots` command
+    pub fn execute<DB: Database>(mut self, tool: &DbTool<'_, DB>) -> eyre::Result<()> {
+        // The hashed state and the trie are only available at the `MerkleExecute` checkpoint, so
+        // every earlier state is reverted from there.
+        let tip = tool
+            .get::<tables::SyncStage>(StageId::MerkleExecute.to_string())?
+            .unwrap_or_default()
+            .block_number;
+
+        let mut roots_table = ComfyTable::new();
+        roots_table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+        roots_table.set_header(["Block", "Header State Root", "Computed State Root", "Result"]);
+
+        self.blocks.sort_unstable();
+        self.blocks.dedup();
+
+        let mut failed = 0;
+        for block in self.blocks {
+            if block > tip {
+                eyre::bail!(
+                    "Block {block} is past the {} checkpoint {tip}.",
+                    StageId::MerkleExecute
+                )
+            }
+
+            let expected = tool
+                .get::<tables::Headers>(block)?
+                .ok_or_else(|| eyre::eyre!("Header {block} does not exist."))?
+                .state_root;
+            let computed = tool.db.view(|tx| state_root_at(tx, block, tip))??;
+
+            let result = if computed == expected {
+                "pass"
+            } else {
+                failed += 1;
+                "fail"
+            };
+
+            let mut row = Row::new();
+            row.add_cell(Cell::new(block))
+                .add_cell(Cell::new(expected))
+                .add_cell(Cell::new(computed))
+                .add_cell(Cell::new(result));
+            roots_table.add_row(row);
+        }
+
+        println!("{roots_table}");
+
+        if failed > 0 {
+            eyre::bail!("{failed} state roots differ from their headers.")
+        }
+
+        Ok(())
+    }
+}
+
+/// Computes the state root at `block`, by reverting the hashed state of `tip` with the changesets
+/// of the blocks in between and updating the trie with it, like the Merkle stage does.
+fn state_root_at<TX: DbTx>(tx: &TX, block: BlockNumber, tip: BlockNumber) -> eyre::Result<B256> {
+    // The changesets hold the values before each block, so the first change after `block` holds
+    // the value at `block`.
+    let mut accounts = HashMap::new();
+    for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk_range(block + 1..=tip)? {
+        let (_, before) = entry?;
+        accounts.entry(before.address).or_insert(before.info);
+    }
+
+    let mut storages = HashMap::<_, HashMap<_, _>>::new();
+    for entry in tx
+        .cursor_read::<tables::StorageChangeSet>()?
+        .walk_range(BlockNumberAddress::range(block + 1..=tip))?
+    {
+        let (key, before) = entry?;
+        storages.entry(key.address()).or_default().entry(before.key).or_insert(before.value);
+    }
+
+    let mut post_state = HashedPostState::default();
+    for (address, account) in accounts {
+        match account {
+            Some(account) => post_state.insert_account(keccak256(address), account),
+            None => post_state.insert_cleared_account(keccak256(address)),
+        }
+    }
+    for (address, slots) in storages {
+        let mut storage = HashedStorage::new(false);
+        for (slot, value) in slots {
+            if value == U256::ZERO {
+                storage.insert_zero_valued_slot(keccak256(slot));
+            } else {
+                storage.insert_non_zero_valued_storage(keccak256(slot), value);
+            }
+        }
+        post_state.insert_hashed_storage(keccak256(address), storage);
+    }
+    let post_state = post_state.sorted();
+
+    let (account_prefix_set, storage_prefix_set) = post_state.construct_prefix_sets();
+    Ok(StateRoot::new(tx)
+        .with_hashed_cursor_factory(HashedPostStateCursorFactory::new(tx, &post_state))
+        .with_changed_account_prefixes(account_prefix_set)
+        .with_changed_storage_prefixes(storage_prefix_set)
+        .root()?)
+}
@@ -0,0 +1,110 @@
+use crate::stage::dump::DumpReport;
+use clap::Parser;
+use std::{collections::BTreeSet, path::PathBuf};
+
+/// The arguments for the `reth db compare-summaries` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The first dump report, written by `reth stage dump --report`.
+    #[arg(long, value_name = "REPORT_PATH")]
+    a: PathBuf,
+
+    /// The second dump report, written by `reth stage dump --report`.
+    #[arg(long, value_name = "REPORT_PATH")]
+    b: PathBuf,
+}
+
+impl Command {
+    /// Execute `db compare-summaries` command
+    pub fn execute(self) -> eyre::Result<()> {
+        let a = DumpReport::read(&self.a)?;
+        let b = DumpReport::read(&self.b)?;
+
+        if (&a.stage, a.from, a.to) != (&b.stage, b.from, b.to) {
+            eyre::bail!(
+                "The reports are of different dumps: {} {}..={} and {} {}..={}.",
+                a.stage,
+                a.from,
+                a.to,
+                b.stage,
+                b.from,
+                b.to
+            )
+        }
+
+        let mut differences = 0;
+        let mut difference = |message: String| {
+            println!("{message}");
+            differences += 1;
+        };
+
+        if a.partial != b.partial {
+            difference(format!(
+                "Only {} is a partial dump",
+                if a.partial { self.a.display() } else { self.b.display() }
+            ));
+        }
+
+        if let (Some(verification_a), Some(verification_b)) =
+            (&a.deep_verification, &b.deep_verification)
+        {
+            if verification_a != verification_b {
+                difference(format!(
+                    "Deep verifications differ: {} rows with hash {} != {} rows with hash {}",
+                    verification_a.rows,
+                    verification_a.hash,
+                    verification_b.rows,
+                    verification_b.hash
+                ));
+            }
+        }
+
+        let tables = a.rows.keys().chain(b.rows.keys()).collect::<BTreeSet<_>>();
+        for table in tables {
+            let (rows_a, rows_b) = (a.rows.get(table), b.rows.get(table));
+            if rows_a != rows_b {
+                difference(format!(
+                    "Table {table} rows differ: {} != {}",
+                    rows_a.copied().unwrap_or_default(),
+                    rows_b.copied().unwrap_or_default()
+                ));
+            }
+        }
+
+        match (&a.dry_run, &b.dry_run) {
+            (Some(dry_run_a), Some(dry_run_b)) => {
+                if dry_run_a.state_root != dry_run_b.state_root {
+                    difference(format!(
+                        "Dry-run state roots differ: {:?} != {:?}",
+                        dry_run_a.state_root, dry_run_b.state_root
+                    ));
+                }
+                if dry_run_a.error != dry_run_b.error {
+                    difference(format!(
+                        "Dry-run errors differ: {:?} != {:?}",
+                        dry_run_a.error, dry_run_b.error
+                    ));
+                }
+
+                let mismatches_a = dry_run_a.mismatches.iter().collect::<BTreeSet<_>>();
+                let mismatches_b = dry_run_b.mismatches.iter().collect::<BTreeSet<_>>();
+                for key in mismatches_a.difference(&mismatches_b) {
+                    difference(format!("Mismatch {key} is only in {}", self.a.display()));
+                }
+                for key in mismatches_b.difference(&mismatches_a) {
+                    difference(format!("Mismatch {key} is only in {}", self.b.display()));
+                }
+            }
+            (None, None) => {}
+            _ => difference("Only one of the reports has a dry-run".to_string()),
+        }
+
+        if differences > 0 {
+            eyre::bail!("The reports have {differences} differences.")
+        }
+
+        println!("The reports agree.");
+
+        Ok(())
+    }
+}
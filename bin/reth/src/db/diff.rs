@@ -4,15 +4,19 @@ use std::{
     fs::{self, File},
     hash::Hash,
     io::Write,
+    ops::RangeBounds,
     path::{Path, PathBuf},
 };
 
+use super::range::TableRanges;
 use crate::{
     args::DatabaseArgs,
     dirs::{DataDirPath, PlatformPath},
+    stage::dump::ExtractManifest,
     utils::DbTool,
 };
 use clap::Parser;
+use itertools::{EitherOrBoth, Itertools};
 
 use reth_db::{
     cursor::DbCursorRO, database::Database, open_db_read_only, table::Table, transaction::DbTx,
@@ -22,12 +26,16 @@ use reth_db::{
     Receipts, StorageChangeSet, StorageHistory, StoragesTrie, SyncStage, SyncStageProgress, Tables,
     TransactionBlock, Transactions, TxHashNumber, TxSenders,
 };
+use reth_primitives::BlockNumber;
 use tracing::info;
 
 #[derive(Parser, Debug)]
 /// The arguments for the `reth db diff` command
 pub struct Command {
     /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// It can also be the output database of `reth stage dump`, which holds the database files
+    /// itself instead of a `db/` folder.
     #[arg(long, verbatim_doc_comment)]
     secondary_datadir: PlatformPath<DataDirPath>,
 
@@ -35,9 +43,22 @@ pub struct Command {
     #[clap(flatten)]
     second_db: DatabaseArgs,
 
-    /// The table name to diff. If not specified, all tables are diffed.
-    #[arg(long, verbatim_doc_comment)]
-    table: Option<Tables>,
+    /// The table name to diff. Can be passed multiple times, or as a comma-separated list. If not
+    /// specified, all tables are diffed.
+    #[arg(long, value_delimiter = ',', verbatim_doc_comment)]
+    table: Vec<Tables>,
+
+    /// The first block of the range to diff.
+    ///
+    /// Only the rows of the tables keyed by block or transaction number are limited to the range,
+    /// the transactions of the range being looked up in the primary database. The other tables
+    /// are diffed whole.
+    #[arg(long, value_name = "BLOCK", requires = "to", verbatim_doc_comment)]
+    from: Option<BlockNumber>,
+
+    /// The last block of the range to diff.
+    #[arg(long, value_name = "BLOCK", requires = "from")]
+    to: Option<BlockNumber>,
 
     /// The output directory for the diff report.
     #[arg(long, verbatim_doc_comment)]
@@ -47,8 +68,8 @@ pub struct Command {
 impl Command {
     /// Execute the `db diff` command.
     ///
-    /// This first opens the `db/` folder from the secondary datadir, where the second database is
-    /// opened read-only.
+    /// This first opens the `db/` folder from the secondary datadir, or the secondary datadir
+    /// itself if it holds a database, where the second database is opened read-only.
     ///
     /// The tool will then iterate through all key-value pairs for the primary and secondary
     /// databases. The value for each key will be compared with its corresponding value in the
@@ -60,13 +81,17 @@ impl Command {
     /// then written to a file in the output directory.
     pub fn execute(self, tool: &DbTool<'_, DatabaseEnvRO>) -> eyre::Result<()> {
         // open second db
-        let second_db_path: PathBuf = self.secondary_datadir.join("db").into();
+        let second_db_path: PathBuf = if self.secondary_datadir.as_ref().join("mdbx.dat").exists() {
+            ExtractManifest::check(self.secondary_datadir.as_ref())?;
+            self.secondary_datadir.clone().into()
+        } else {
+            self.secondary_datadir.join("db").into()
+        };
         let second_db = open_db_read_only(&second_db_path, self.second_db.log_level)?;
 
-        let tables = match self.table {
-            Some(table) => vec![table],
-            None => Tables::ALL.to_vec(),
-        };
+        let tables = if self.table.is_empty() { Tables::ALL.to_vec() } else { self.table.clone() };
+        let TableRanges { blocks, transactions, storage_changes } =
+            TableRanges::new(tool, self.from.zip(self.to))?;
 
         for table in tables {
             let primary_tx = tool.db.tx()?;
@@ -75,70 +100,88 @@ impl Command {
             let output_dir = self.output.clone();
             match table {
                 Tables::CanonicalHeaders => {
-                    find_diffs::<CanonicalHeaders>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<CanonicalHeaders>(primary_tx, secondary_tx, blocks, output_dir)?
+                }
+                Tables::HeaderTD => {
+                    find_diffs::<HeaderTD>(primary_tx, secondary_tx, blocks, output_dir)?
                 }
-                Tables::HeaderTD => find_diffs::<HeaderTD>(primary_tx, secondary_tx, output_dir)?,
                 Tables::HeaderNumbers => {
-                    find_diffs::<HeaderNumbers>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<HeaderNumbers>(primary_tx, secondary_tx, .., output_dir)?
+                }
+                Tables::Headers => {
+                    find_diffs::<Headers>(primary_tx, secondary_tx, blocks, output_dir)?
                 }
-                Tables::Headers => find_diffs::<Headers>(primary_tx, secondary_tx, output_dir)?,
                 Tables::BlockBodyIndices => {
-                    find_diffs::<BlockBodyIndices>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<BlockBodyIndices>(primary_tx, secondary_tx, blocks, output_dir)?
                 }
                 Tables::BlockOmmers => {
-                    find_diffs::<BlockOmmers>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<BlockOmmers>(primary_tx, secondary_tx, blocks, output_dir)?
                 }
                 Tables::BlockWithdrawals => {
-                    find_diffs::<BlockWithdrawals>(primary_tx, secondary_tx, output_dir)?
-                }
-                Tables::TransactionBlock => {
-                    find_diffs::<TransactionBlock>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<BlockWithdrawals>(primary_tx, secondary_tx, blocks, output_dir)?
                 }
+                Tables::TransactionBlock => find_diffs::<TransactionBlock>(
+                    primary_tx,
+                    secondary_tx,
+                    transactions,
+                    output_dir,
+                )?,
                 Tables::Transactions => {
-                    find_diffs::<Transactions>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<Transactions>(primary_tx, secondary_tx, transactions, output_dir)?
                 }
                 Tables::TxHashNumber => {
-                    find_diffs::<TxHashNumber>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<TxHashNumber>(primary_tx, secondary_tx, .., output_dir)?
+                }
+                Tables::Receipts => {
+                    find_diffs::<Receipts>(primary_tx, secondary_tx, transactions, output_dir)?
                 }
-                Tables::Receipts => find_diffs::<Receipts>(primary_tx, secondary_tx, output_dir)?,
                 Tables::PlainAccountState => {
-                    find_diffs::<PlainAccountState>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<PlainAccountState>(primary_tx, secondary_tx, .., output_dir)?
                 }
                 Tables::PlainStorageState => {
-                    find_diffs::<PlainStorageState>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<PlainStorageState>(primary_tx, secondary_tx, .., output_dir)?
+                }
+                Tables::Bytecodes => {
+                    find_diffs::<Bytecodes>(primary_tx, secondary_tx, .., output_dir)?
                 }
-                Tables::Bytecodes => find_diffs::<Bytecodes>(primary_tx, secondary_tx, output_dir)?,
                 Tables::AccountHistory => {
-                    find_diffs::<AccountHistory>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<AccountHistory>(primary_tx, secondary_tx, .., output_dir)?
                 }
                 Tables::StorageHistory => {
-                    find_diffs::<StorageHistory>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<StorageHistory>(primary_tx, secondary_tx, .., output_dir)?
                 }
                 Tables::AccountChangeSet => {
-                    find_diffs::<AccountChangeSet>(primary_tx, secondary_tx, output_dir)?
-                }
-                Tables::StorageChangeSet => {
-                    find_diffs::<StorageChangeSet>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<AccountChangeSet>(primary_tx, secondary_tx, blocks, output_dir)?
                 }
+                Tables::StorageChangeSet => find_diffs::<StorageChangeSet>(
+                    primary_tx,
+                    secondary_tx,
+                    storage_changes,
+                    output_dir,
+                )?,
                 Tables::HashedAccount => {
-                    find_diffs::<HashedAccount>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<HashedAccount>(primary_tx, secondary_tx, .., output_dir)?
                 }
                 Tables::HashedStorage => {
-                    find_diffs::<HashedStorage>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<HashedStorage>(primary_tx, secondary_tx, .., output_dir)?
                 }
                 Tables::AccountsTrie => {
-                    find_diffs::<AccountsTrie>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<AccountsTrie>(primary_tx, secondary_tx, .., output_dir)?
                 }
                 Tables::StoragesTrie => {
-                    find_diffs::<StoragesTrie>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<StoragesTrie>(primary_tx, secondary_tx, .., output_dir)?
+                }
+                Tables::TxSenders => {
+                    find_diffs::<TxSenders>(primary_tx, secondary_tx, transactions, output_dir)?
+                }
+                Tables::SyncStage => {
+                    find_diffs::<SyncStage>(primary_tx, secondary_tx, .., output_dir)?
                 }
-                Tables::TxSenders => find_diffs::<TxSenders>(primary_tx, secondary_tx, output_dir)?,
-                Tables::SyncStage => find_diffs::<SyncStage>(primary_tx, secondary_tx, output_dir)?,
                 Tables::SyncStageProgress => {
-                    find_diffs::<SyncStageProgress>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<SyncStageProgress>(primary_tx, secondary_tx, .., output_dir)?
                 }
                 Tables::PruneCheckpoints => {
-                    find_diffs::<PruneCheckpoints>(primary_tx, secondary_tx, output_dir)?
+                    find_diffs::<PruneCheckpoints>(primary_tx, secondary_tx, .., output_dir)?
                 }
             };
         }
@@ -151,6 +194,7 @@ impl Command {
 fn find_diffs<T: Table>(
     primary_tx: impl DbTx,
     secondary_tx: impl DbTx,
+    range: impl RangeBounds<T::Key> + Clone,
     output_dir: impl AsRef<Path>,
 ) -> eyre::Result<()>
 where
@@ -160,7 +204,7 @@ where
     let table_name = T::NAME;
 
     info!("Analyzing table {table_name}...");
-    let result = find_diffs_advanced::<T>(&primary_tx, &secondary_tx)?;
+    let result = find_diffs_advanced::<T>(&primary_tx, &secondary_tx, range)?;
     info!("Done analyzing table {table_name}!");
 
     // Pretty info summary header: newline then header
@@ -229,11 +273,12 @@ where
     Ok(())
 }
 
-/// This diff algorithm is slightly different, it will walk _each_ table, cross-checking for the
-/// element in the other table.
+/// This diff algorithm is slightly different, it will walk _each_ table within `range`,
+/// cross-checking for the element in the other table.
 fn find_diffs_advanced<T: Table>(
     primary_tx: &impl DbTx,
     secondary_tx: &impl DbTx,
+    range: impl RangeBounds<T::Key> + Clone,
 ) -> eyre::Result<TableDiffResult<T>>
 where
     T::Value: PartialEq,
@@ -242,12 +287,13 @@ where
     // initialize the zipped walker
     let mut primary_zip_cursor =
         primary_tx.cursor_read::<T>().expect("Was not able to obtain a cursor.");
-    let primary_walker = primary_zip_cursor.walk(None)?;
+    let primary_walker = primary_zip_cursor.walk_range(range.clone())?;
 
     let mut secondary_zip_cursor =
         secondary_tx.cursor_read::<T>().expect("Was not able to obtain a cursor.");
-    let secondary_walker = secondary_zip_cursor.walk(None)?;
-    let zipped_cursor = primary_walker.zip(secondary_walker);
+    let secondary_walker = secondary_zip_cursor.walk_range(range)?;
+    // The walk goes on past the end of the shorter table, whose remaining elements are extra.
+    let zipped_cursor = primary_walker.zip_longest(secondary_walker);
 
     // initialize the cursors for seeking when we are cross checking elements
     let mut primary_cursor =
@@ -262,7 +308,28 @@ where
     // it basically just loops through both tables at the same time. if the keys are different, it
     // will check each key in the other table. if the keys are the same, it will compare the
     // values
-    for (primary_entry, secondary_entry) in zipped_cursor {
+    for entries in zipped_cursor {
+        let (primary_entry, secondary_entry) = match entries {
+            EitherOrBoth::Both(primary_entry, secondary_entry) => (primary_entry, secondary_entry),
+            EitherOrBoth::Left(primary_entry) => {
+                let (key, value) = primary_entry?;
+                result.try_push_discrepancy(
+                    key.clone(),
+                    Some(value),
+                    secondary_cursor.seek_exact(key)?.map(|(_, value)| value),
+                );
+                continue
+            }
+            EitherOrBoth::Right(secondary_entry) => {
+                let (key, value) = secondary_entry?;
+                result.try_push_discrepancy(
+                    key.clone(),
+                    primary_cursor.seek_exact(key)?.map(|(_, value)| value),
+                    Some(value),
+                );
+                continue
+            }
+        };
         let (primary_key, primary_value) = primary_entry?;
         let (secondary_key, secondary_value) = secondary_entry?;
 
@@ -408,3 +475,42 @@ impl<T: Table> ExtraTableElement<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{test_utils::create_test_rw_db, transaction::DbTxMut};
+    use reth_primitives::B256;
+
+    #[test]
+    fn diff_within_range() {
+        let (primary, secondary) = (create_test_rw_db(), create_test_rw_db());
+        for (db, blocks) in [(&primary, 0..10u64), (&secondary, 0..6)] {
+            db.update(|tx| {
+                for number in blocks {
+                    tx.put::<CanonicalHeaders>(number, B256::with_last_byte(number as u8))?;
+                }
+                Ok::<_, reth_db::DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+        }
+        secondary
+            .update(|tx| tx.put::<CanonicalHeaders>(3, B256::repeat_byte(0xff)))
+            .unwrap()
+            .unwrap();
+        let (primary_tx, secondary_tx) = (primary.tx().unwrap(), secondary.tx().unwrap());
+
+        let result =
+            find_diffs_advanced::<CanonicalHeaders>(&primary_tx, &secondary_tx, ..).unwrap();
+        assert_eq!(result.discrepancies.keys().collect::<Vec<_>>(), [&3]);
+        let mut extra = result.extra_elements.keys().copied().collect::<Vec<_>>();
+        extra.sort();
+        assert_eq!(extra, [6, 7, 8, 9]);
+
+        let result =
+            find_diffs_advanced::<CanonicalHeaders>(&primary_tx, &secondary_tx, 4..=6).unwrap();
+        assert!(result.discrepancies.is_empty());
+        assert_eq!(result.extra_elements.keys().collect::<Vec<_>>(), [&6]);
+    }
+}
@@ -0,0 +1,365 @@
+use super::range::TableRanges;
+use crate::utils::DbTool;
+use clap::Parser;
+use reth_db::{
+    cursor::DbCursorRO, database::Database, table::Table, transaction::DbTx, AccountChangeSet,
+    AccountHistory, AccountsTrie, BlockBodyIndices, BlockOmmers, BlockWithdrawals, Bytecodes,
+    CanonicalHeaders, HashedAccount, HashedStorage, HeaderNumbers, HeaderTD, Headers,
+    PlainAccountState, PlainStorageState, PruneCheckpoints, Receipts, StorageChangeSet,
+    StorageHistory, StoragesTrie, SyncStage, SyncStageProgress, Tables, TransactionBlock,
+    Transactions, TxHashNumber, TxSenders,
+};
+use reth_primitives::BlockNumber;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    ops::{RangeBounds, RangeInclusive},
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db export` command
+pub struct Command {
+    /// The table name to export. Can be passed multiple times, or as a comma-separated list.
+    #[arg(long, value_delimiter = ',', required = true, verbatim_doc_comment)]
+    table: Vec<Tables>,
+
+    /// The first block of the range to export.
+    ///
+    /// Only the rows of the tables keyed by block or transaction number, and the blocks of the
+    /// history shards, are limited to the range. The other tables are exported whole.
+    #[arg(long, value_name = "BLOCK", requires = "to", verbatim_doc_comment)]
+    from: Option<BlockNumber>,
+
+    /// The last block of the range to export.
+    #[arg(long, value_name = "BLOCK", requires = "from")]
+    to: Option<BlockNumber>,
+
+    /// The directory the tables are exported to, each as a `<TABLE>.csv` file with a column per
+    /// field of its keys and values, and a `<TABLE>.schema.json` file with the types of the
+    /// columns.
+    #[arg(long, value_name = "PATH", verbatim_doc_comment)]
+    output_dir: PathBuf,
+}
+
+impl Command {
+    /// Execute `db export` command
+    pub fn execute<DB: Database>(self, tool: &DbTool<'_, DB>) -> eyre::Result<()> {
+        fs::create_dir_all(&self.output_dir)?;
+        let ranges = TableRanges::new(tool, self.from.zip(self.to))?;
+        let history = self.from.zip(self.to).map(|(from, to)| from..=to);
+        let history = history.as_ref();
+        let dir = self.output_dir.as_path();
+
+        for table in &self.table {
+            let rows = match table {
+                Tables::CanonicalHeaders => {
+                    export_table::<_, CanonicalHeaders>(tool, ranges.blocks, None, dir)?
+                }
+                Tables::HeaderTD => export_table::<_, HeaderTD>(tool, ranges.blocks, None, dir)?,
+                Tables::HeaderNumbers => export_table::<_, HeaderNumbers>(tool, .., None, dir)?,
+                Tables::Headers => export_table::<_, Headers>(tool, ranges.blocks, None, dir)?,
+                Tables::BlockBodyIndices => {
+                    export_table::<_, BlockBodyIndices>(tool, ranges.blocks, None, dir)?
+                }
+                Tables::BlockOmmers => {
+                    export_table::<_, BlockOmmers>(tool, ranges.blocks, None, dir)?
+                }
+                Tables::BlockWithdrawals => {
+                    export_table::<_, BlockWithdrawals>(tool, ranges.blocks, None, dir)?
+                }
+                Tables::TransactionBlock => {
+                    export_table::<_, TransactionBlock>(tool, ranges.transactions, None, dir)?
+                }
+                Tables::Transactions => {
+                    export_table::<_, Transactions>(tool, ranges.transactions, None, dir)?
+                }
+                Tables::TxHashNumber => export_table::<_, TxHashNumber>(tool, .., None, dir)?,
+                Tables::Receipts => {
+                    export_table::<_, Receipts>(tool, ranges.transactions, None, dir)?
+                }
+                Tables::PlainAccountState => {
+                    export_table::<_, PlainAccountState>(tool, .., None, dir)?
+                }
+                Tables::PlainStorageState => {
+                    export_table::<_, PlainStorageState>(tool, .., None, dir)?
+                }
+                Tables::Bytecodes => export_table::<_, Bytecodes>(tool, .., None, dir)?,
+                Tables::AccountHistory => {
+                    export_table::<_, AccountHistory>(tool, .., history, dir)?
+                }
+                Tables::StorageHistory => {
+                    export_table::<_, StorageHistory>(tool, .., history, dir)?
+                }
+                Tables::AccountChangeSet => {
+                    export_table::<_, AccountChangeSet>(tool, ranges.blocks, None, dir)?
+                }
+                Tables::StorageChangeSet => {
+                    export_table::<_, StorageChangeSet>(tool, ranges.storage_changes, None, dir)?
+                }
+                Tables::HashedAccount => export_table::<_, HashedAccount>(tool, .., None, dir)?,
+                Tables::HashedStorage => export_table::<_, HashedStorage>(tool, .., None, dir)?,
+                Tables::AccountsTrie => export_table::<_, AccountsTrie>(tool, .., None, dir)?,
+                Tables::StoragesTrie => export_table::<_, StoragesTrie>(tool, .., None, dir)?,
+                Tables::TxSenders => {
+                    export_table::<_, TxSenders>(tool, ranges.transactions, None, dir)?
+                }
+                Tables::SyncStage => export_table::<_, SyncStage>(tool, .., None, dir)?,
+                Tables::SyncStageProgress => {
+                    export_table::<_, SyncStageProgress>(tool, .., None, dir)?
+                }
+                Tables::PruneCheckpoints => {
+                    export_table::<_, PruneCheckpoints>(tool, .., None, dir)?
+                }
+            };
+            info!(target: "reth::cli", table = table.name(), rows, ?dir, "Exported table");
+        }
+
+        Ok(())
+    }
+}
+
+/// The type of a column of an exported table, for loading the CSV file with typed columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ColumnType {
+    Integer,
+    Boolean,
+    String,
+    /// A list, written as JSON.
+    Json,
+}
+
+impl ColumnType {
+    fn of(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => None,
+            Value::Bool(_) => Some(Self::Boolean),
+            Value::Number(_) => Some(Self::Integer),
+            Value::String(_) => Some(Self::String),
+            Value::Array(_) | Value::Object(_) => Some(Self::Json),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Column {
+    name: String,
+    #[serde(rename = "type")]
+    kind: Option<ColumnType>,
+}
+
+/// The schema of an exported table, written next to its CSV file.
+#[derive(Debug, Serialize)]
+struct Schema {
+    table: &'static str,
+    rows: usize,
+    columns: Vec<Column>,
+}
+
+/// The columns of an exported table, in the order their fields first show up in the rows.
+#[derive(Debug, Default)]
+struct Columns {
+    columns: Vec<Column>,
+    index: HashMap<String, usize>,
+}
+
+impl Columns {
+    /// Adds the fields of `row` to the columns.
+    ///
+    /// A column holding values of different types, e.g. a number that's a string in other rows,
+    /// is a string column.
+    fn add(&mut self, row: &[(String, Value)]) {
+        for (name, value) in row {
+            let index = *self.index.entry(name.clone()).or_insert_with(|| {
+                self.columns.push(Column { name: name.clone(), kind: None });
+                self.columns.len() - 1
+            });
+            let column = &mut self.columns[index];
+            column.kind = match (column.kind, ColumnType::of(value)) {
+                (kind, None) | (None, kind) => kind,
+                (Some(kind), Some(other)) if kind == other => Some(kind),
+                _ => Some(ColumnType::String),
+            };
+        }
+    }
+
+    /// The cells of `row`, with an empty cell for each column it doesn't have.
+    fn cells(&self, row: Vec<(String, Value)>) -> Vec<String> {
+        let mut cells = vec![String::new(); self.columns.len()];
+        for (name, value) in row {
+            cells[self.index[&name]] = match value {
+                Value::Null => String::new(),
+                Value::String(value) => value,
+                value => value.to_string(),
+            };
+        }
+        cells
+    }
+}
+
+/// Writes the rows of `T` in `range` to `<T>.csv` in `dir`, and the types of its columns to
+/// `<T>.schema.json`. Returns the number of written rows.
+///
+/// The rows are read twice in the same transaction, first to find the columns, since the fields
+/// of enum variants and of optional values only show up in some of the rows.
+///
+/// The blocks of history shards are limited to `history`, and shards without any such blocks are
+/// skipped.
+fn export_table<DB: Database, T: Table>(
+    tool: &DbTool<'_, DB>,
+    range: impl RangeBounds<T::Key> + Clone,
+    history: Option<&RangeInclusive<BlockNumber>>,
+    dir: &Path,
+) -> eyre::Result<usize> {
+    tool.db.view(|tx| {
+        let mut columns = Columns::default();
+        walk_rows::<T>(tx, range.clone(), history, |row| {
+            columns.add(&row);
+            Ok(())
+        })?;
+
+        let mut file = BufWriter::new(File::create(dir.join(format!("{}.csv", T::NAME)))?);
+        let names = columns.columns.iter().map(|column| column.name.clone()).collect::<Vec<_>>();
+        write_record(&mut file, &names)?;
+
+        let mut rows = 0;
+        walk_rows::<T>(tx, range, history, |row| {
+            write_record(&mut file, &columns.cells(row))?;
+            rows += 1;
+            Ok(())
+        })?;
+        file.flush()?;
+
+        let schema = Schema { table: T::NAME, rows, columns: columns.columns };
+        fs::write(
+            dir.join(format!("{}.schema.json", T::NAME)),
+            serde_json::to_string_pretty(&schema)?,
+        )?;
+
+        Ok(rows)
+    })?
+}
+
+/// Calls `f` with the fields of every row of `T` in `range`, the fields of its key named `key`
+/// or prefixed with `key.`, and the ones of its value `value` or `value.`.
+fn walk_rows<T: Table>(
+    tx: &impl DbTx,
+    range: impl RangeBounds<T::Key>,
+    history: Option<&RangeInclusive<BlockNumber>>,
+    mut f: impl FnMut(Vec<(String, Value)>) -> eyre::Result<()>,
+) -> eyre::Result<()> {
+    for entry in tx.cursor_read::<T>()?.walk_range(range)? {
+        let (key, value) = entry?;
+        let mut value = serde_json::to_value(value)?;
+        if let Some(history) = history {
+            let Value::Array(blocks) = &mut value else {
+                eyre::bail!("Value of {} isn't a list of blocks.", T::NAME)
+            };
+            blocks.retain(|block| block.as_u64().is_some_and(|block| history.contains(&block)));
+            if blocks.is_empty() {
+                continue
+            }
+        }
+
+        let mut row = vec![];
+        flatten("key".to_string(), serde_json::to_value(key)?, &mut row);
+        flatten("value".to_string(), value, &mut row);
+        f(row)?;
+    }
+    Ok(())
+}
+
+/// Splits the fields of nested objects into their own columns, named by their path.
+fn flatten(name: String, value: Value, row: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(fields) => {
+            for (field, value) in fields {
+                flatten(format!("{name}.{field}"), value, row);
+            }
+        }
+        value => row.push((name, value)),
+    }
+}
+
+/// Writes a CSV record, quoting the cells that need it.
+fn write_record(out: &mut impl Write, cells: &[String]) -> io::Result<()> {
+    for (i, cell) in cells.iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        if cell.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+            write!(out, "\"{}\"", cell.replace('"', "\"\""))?;
+        } else {
+            out.write_all(cell.as_bytes())?;
+        }
+    }
+    out.write_all(b"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        models::ShardedKey, test_utils::create_test_rw_db, transaction::DbTxMut, DatabaseError,
+    };
+    use reth_primitives::{Address, Header, IntegerList, MAINNET};
+
+    #[test]
+    fn export_headers_and_history() {
+        let db = create_test_rw_db();
+        db.update(|tx| {
+            for number in 0..5 {
+                tx.put::<Headers>(
+                    number,
+                    Header { number, gas_limit: 30_000_000, ..Default::default() },
+                )?;
+            }
+            for (highest, blocks) in [(3, vec![1usize, 3]), (u64::MAX, vec![7])] {
+                tx.put::<AccountHistory>(
+                    ShardedKey::new(Address::with_last_byte(1), highest),
+                    IntegerList::new(blocks).unwrap(),
+                )?;
+            }
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+        let tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        assert_eq!(export_table::<_, Headers>(&tool, 1..=3, None, dir.path()).unwrap(), 3);
+        let csv = fs::read_to_string(dir.path().join("Headers.csv")).unwrap();
+        let mut lines = csv.lines();
+        let names = lines.next().unwrap().split(',').collect::<Vec<_>>();
+        assert_eq!(names[0], "key");
+        assert!(names.contains(&"value.gas_limit"));
+        assert_eq!(lines.count(), 3);
+
+        let schema: Value = serde_json::from_str(
+            &fs::read_to_string(dir.path().join("Headers.schema.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(schema["rows"], 3);
+        assert_eq!(schema["columns"][0]["type"], "integer");
+
+        // Only the first shard indexes blocks of the range.
+        assert_eq!(
+            export_table::<_, AccountHistory>(&tool, .., Some(&(2..=5)), dir.path()).unwrap(),
+            1
+        );
+        let csv = fs::read_to_string(dir.path().join("AccountHistory.csv")).unwrap();
+        assert_eq!(csv.lines().nth(1).unwrap().split(',').last(), Some("[3]"));
+    }
+
+    #[test]
+    fn quote_cells() {
+        let mut out = vec![];
+        write_record(&mut out, &["1".to_string(), "a,b".to_string(), "\"x\"".to_string()]).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "1,\"a,b\",\"\"\"x\"\"\"\n");
+    }
+}
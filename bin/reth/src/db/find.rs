@@ -0,0 +1,76 @@
+use crate::utils::DbTool;
+use boyer_moore_magiclen::BMByte;
+use clap::Parser;
+use reth_db::{
+    cursor::DbCursorRO, database::Database, table::Table, transaction::DbTx, RawTable, TableViewer,
+    Tables,
+};
+use reth_primitives::Bytes;
+use tracing::info;
+
+/// The arguments for the `reth db find` command
+///
+/// Every scanned entry is read, so this is O(n) in the number of entries: bound the scan with
+/// `--skip` and `--len` on large tables. Matching keys are printed as soon as they're found, and
+/// the scan can be stopped at any time with Ctrl-C.
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The table to scan
+    #[arg(long)]
+    table: Tables,
+    /// The bytes to look for in the values, hex encoded with an optional `0x` prefix, e.g. an
+    /// address or a log topic.
+    ///
+    /// ATTENTION! For compressed tables (`Transactions` and `Receipts`), there might be
+    /// missing results since the search uses the raw value from the database.
+    #[arg(long, value_name = "HEX")]
+    value_contains: Bytes,
+    /// Skip first N entries
+    #[arg(long, short, default_value_t = 0)]
+    skip: usize,
+    /// How many entries to scan. All the remaining ones if not passed.
+    #[arg(long, short)]
+    len: Option<usize>,
+}
+
+impl Command {
+    /// Execute `db find` command
+    pub fn execute<DB: Database>(self, tool: &DbTool<'_, DB>) -> eyre::Result<()> {
+        self.table.view(&FindViewer { tool, args: &self })
+    }
+}
+
+struct FindViewer<'a, DB: Database> {
+    tool: &'a DbTool<'a, DB>,
+    args: &'a Command,
+}
+
+impl<DB: Database> TableViewer<()> for FindViewer<'_, DB> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<(), Self::Error> {
+        let searcher = BMByte::from(&self.args.value_contains.to_vec())
+            .ok_or_else(|| eyre::eyre!("Invalid search pattern."))?;
+
+        let (scanned, found) = self.tool.db.view(|tx| {
+            let mut cursor = tx.cursor_read::<RawTable<T>>()?;
+            let (mut scanned, mut found) = (0, 0);
+            for row in
+                cursor.walk(None)?.skip(self.args.skip).take(self.args.len.unwrap_or(usize::MAX))
+            {
+                let (key, value) = row?;
+                scanned += 1;
+                if searcher.find_first_in(value.raw_value()).is_some() {
+                    found += 1;
+                    println!("{}", serde_json::to_string(&key.key()?)?);
+                }
+            }
+
+            Ok::<_, eyre::Report>((scanned, found))
+        })??;
+
+        info!(target: "reth::cli", table = T::NAME, scanned, found, "Scanned table");
+
+        Ok(())
+    }
+}
@@ -1,7 +1,10 @@
 use crate::utils::DbTool;
 use clap::Parser;
 
-use reth_db::{database::Database, table::Table, TableType, TableViewer, Tables};
+use reth_db::{
+    database::Database, table::Table, HeaderNumbers, TableType, TableViewer, Tables, TxHashNumber,
+};
+use reth_primitives::{keccak256, Address, B256};
 use tracing::error;
 
 /// The arguments for the `reth db get` command
@@ -12,8 +15,11 @@ pub struct Command {
     /// NOTE: The dupsort tables are not supported now.
     pub table: Tables,
 
-    /// The key to get content for   
-    #[arg(value_parser = maybe_json_value_parser)]
+    /// The key to get content for, as JSON
+    ///
+    /// The tables keyed by block or transaction number also take the hash of the block or
+    /// transaction, and the tables keyed by hashed address take the address.
+    #[arg(value_parser = maybe_json_value_parser, verbatim_doc_comment)]
     pub key: String,
 }
 
@@ -49,7 +55,7 @@ impl<DB: Database> TableViewer<()> for GetValueViewer<'_, DB> {
 
     fn view<T: Table>(&self) -> Result<(), Self::Error> {
         // get a key for given table
-        let key = self.args.table_key::<T>()?;
+        let key = resolve_key::<T, _>(self.tool, self.args.table, &self.args.key)?;
 
         match self.tool.get::<T>(key)? {
             Some(content) => {
@@ -64,8 +70,57 @@ impl<DB: Database> TableViewer<()> for GetValueViewer<'_, DB> {
     }
 }
 
+/// Parses the JSON `key` of `table`, or one of the readable forms of its keys:
+///
+/// - the hash of a block, for the tables keyed by block number, through [`HeaderNumbers`]
+/// - the hash of a transaction, for the tables keyed by transaction number, through
+///   [`TxHashNumber`]
+/// - an address, for the tables keyed by hashed address
+pub(crate) fn resolve_key<T: Table, DB: Database>(
+    tool: &DbTool<'_, DB>,
+    table: Tables,
+    key: &str,
+) -> eyre::Result<T::Key> {
+    assert_eq!(T::NAME, table.name());
+
+    let error = match serde_json::from_str::<T::Key>(key) {
+        Ok(key) => return Ok(key),
+        Err(error) => error,
+    };
+
+    let resolved = match table {
+        Tables::CanonicalHeaders |
+        Tables::HeaderTD |
+        Tables::Headers |
+        Tables::BlockBodyIndices |
+        Tables::BlockOmmers |
+        Tables::BlockWithdrawals |
+        Tables::AccountChangeSet => {
+            let Ok(hash) = serde_json::from_str::<B256>(key) else { eyre::bail!(error) };
+            let number = tool
+                .get::<HeaderNumbers>(hash)?
+                .ok_or_else(|| eyre::eyre!("Block {hash} not found in the database."))?;
+            serde_json::to_value(number)?
+        }
+        Tables::Transactions | Tables::Receipts | Tables::TxSenders => {
+            let Ok(hash) = serde_json::from_str::<B256>(key) else { eyre::bail!(error) };
+            let number = tool
+                .get::<TxHashNumber>(hash)?
+                .ok_or_else(|| eyre::eyre!("Transaction {hash} not found in the database."))?;
+            serde_json::to_value(number)?
+        }
+        Tables::HashedAccount | Tables::HashedStorage => {
+            let Ok(address) = serde_json::from_str::<Address>(key) else { eyre::bail!(error) };
+            serde_json::to_value(keccak256(address))?
+        }
+        _ => eyre::bail!(error),
+    };
+
+    Ok(serde_json::from_value(resolved)?)
+}
+
 /// Map the user input value to json
-fn maybe_json_value_parser(value: &str) -> Result<String, eyre::Error> {
+pub(crate) fn maybe_json_value_parser(value: &str) -> Result<String, eyre::Error> {
     if serde_json::from_str::<serde::de::IgnoredAny>(value).is_ok() {
         Ok(value.to_string())
     } else {
@@ -79,9 +134,11 @@ mod tests {
     use clap::{Args, Parser};
     use reth_db::{
         models::{storage_sharded_key::StorageShardedKey, ShardedKey},
-        AccountHistory, HashedAccount, Headers, StorageHistory, SyncStage,
+        test_utils::create_test_rw_db,
+        transaction::DbTxMut,
+        AccountHistory, HashedAccount, Headers, StorageHistory, SyncStage, Transactions,
     };
-    use reth_primitives::{Address, B256};
+    use reth_primitives::MAINNET;
     use std::str::FromStr;
 
     /// A helper type to parse Args more easily
@@ -109,6 +166,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn resolve_readable_keys() {
+        let db = create_test_rw_db();
+        let (block_hash, tx_hash) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        db.update(|tx| {
+            tx.put::<HeaderNumbers>(block_hash, 7)?;
+            tx.put::<TxHashNumber>(tx_hash, 42)
+        })
+        .unwrap()
+        .unwrap();
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+
+        let key = maybe_json_value_parser(&block_hash.to_string()).unwrap();
+        assert_eq!(resolve_key::<Headers, _>(&tool, Tables::Headers, &key).unwrap(), 7);
+        let key = maybe_json_value_parser(&tx_hash.to_string()).unwrap();
+        assert_eq!(resolve_key::<Transactions, _>(&tool, Tables::Transactions, &key).unwrap(), 42);
+
+        let address = Address::with_last_byte(3);
+        let key = maybe_json_value_parser(&address.to_string()).unwrap();
+        assert_eq!(
+            resolve_key::<HashedAccount, _>(&tool, Tables::HashedAccount, &key).unwrap(),
+            keccak256(address)
+        );
+
+        let key = maybe_json_value_parser(&B256::ZERO.to_string()).unwrap();
+        assert!(resolve_key::<Headers, _>(&tool, Tables::Headers, &key).is_err());
+    }
+
     #[test]
     fn parse_string_key_args() {
         let args =
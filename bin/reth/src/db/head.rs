@@ -0,0 +1,33 @@
+use crate::utils::DbTool;
+use clap::Parser;
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use reth_db::database::Database;
+
+/// The arguments for the `reth db head` command
+#[derive(Parser, Debug)]
+pub struct Command;
+
+impl Command {
+    /// Execute `db head` command
+    pub fn execute<DB: Database>(self, tool: &DbTool<'_, DB>) -> eyre::Result<()> {
+        let tip = tool.tip()?;
+        let checkpoints = tool.stage_checkpoints()?;
+
+        println!("Chain: {} (id {})", tool.chain.chain, tool.chain.chain.id());
+        println!("Tip: {tip}");
+
+        let mut table = ComfyTable::new();
+        table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+        table.set_header(["Stage", "Checkpoint", "Behind tip"]);
+        for (stage, checkpoint) in checkpoints {
+            let mut row = Row::new();
+            row.add_cell(Cell::new(stage))
+                .add_cell(Cell::new(checkpoint.block_number))
+                .add_cell(Cell::new(tip.saturating_sub(checkpoint.block_number)));
+            table.add_row(row);
+        }
+        println!("{table}");
+
+        Ok(())
+    }
+}
@@ -1,8 +1,15 @@
-use super::tui::DbListTUI;
+use super::{
+    get::{maybe_json_value_parser, resolve_key},
+    tui::DbListTUI,
+};
 use crate::utils::{DbTool, ListFilter};
 use clap::Parser;
 use eyre::WrapErr;
-use reth_db::{database::Database, table::Table, DatabaseEnvRO, TableViewer, Tables};
+use reth_db::{
+    database::Database,
+    table::{Encode, Table},
+    DatabaseEnvRO, TableViewer, Tables,
+};
 use reth_primitives::hex;
 use std::cell::RefCell;
 use tracing::error;
@@ -19,8 +26,14 @@ pub struct Command {
     #[arg(long, short, default_value_t = false)]
     reverse: bool,
     /// How many items to take from the walker
-    #[arg(long, short, default_value_t = 5)]
+    #[arg(long, short, visible_alias = "limit", default_value_t = 5)]
     len: usize,
+    /// The key of the first entry to list, in the forms `reth db get` takes
+    #[arg(long, value_parser = maybe_json_value_parser)]
+    start: Option<String>,
+    /// The key of the last entry to list, in the forms `reth db get` takes
+    #[arg(long, value_parser = maybe_json_value_parser)]
+    end: Option<String>,
     /// Search parameter for both keys and values. Prefix it with `0x` to search for binary data,
     /// and text otherwise.
     ///
@@ -61,8 +74,23 @@ impl Command {
             search,
             reverse: self.reverse,
             only_count: self.count,
+            start: None,
+            end: None,
         }
     }
+
+    /// Resolves a bound of the range of the table, and encodes it as the database sorts it.
+    fn encoded_key<T: Table>(
+        &self,
+        tool: &DbTool<'_, DatabaseEnvRO>,
+        key: Option<&str>,
+    ) -> eyre::Result<Option<Vec<u8>>> {
+        key.map(|key| {
+            let key = resolve_key::<T, _>(tool, self.table, key)?;
+            Ok(key.encode().as_ref().to_vec())
+        })
+        .transpose()
+    }
 }
 
 struct ListTableViewer<'a> {
@@ -74,6 +102,9 @@ impl TableViewer<()> for ListTableViewer<'_> {
     type Error = eyre::Report;
 
     fn view<T: Table>(&self) -> Result<(), Self::Error> {
+        let start = self.args.encoded_key::<T>(self.tool, self.args.start.as_deref())?;
+        let end = self.args.encoded_key::<T>(self.tool, self.args.end.as_deref())?;
+
         self.tool.db.view(|tx| {
             let table_db = tx.inner.open_db(Some(self.args.table.name())).wrap_err("Could not open db.")?;
             let stats = tx.inner.db_stat(&table_db).wrap_err(format!("Could not find table: {}", stringify!($table)))?;
@@ -90,7 +121,7 @@ impl TableViewer<()> for ListTableViewer<'_> {
             }
 
 
-            let list_filter = self.args.list_filter();
+            let list_filter = self.args.list_filter().with_range(start, end);
 
             if self.args.json || self.args.count {
                 let (list, count) = self.tool.list::<T>(&list_filter)?;
@@ -1,18 +1,14 @@
 //! Database debugging tool
 use crate::{
-    args::{utils::genesis_value_parser, DatabaseArgs},
+    args::{utils::genesis_value_parser, ChainOverrideArgs, DatabaseArgs},
     dirs::{DataDirPath, MaybePlatformPath},
+    stage::dump::ExtractManifest,
     utils::DbTool,
 };
 use clap::{Parser, Subcommand};
-use comfy_table::{Cell, Row, Table as ComfyTable};
-use eyre::WrapErr;
-use human_bytes::human_bytes;
 use reth_db::{
-    database::Database,
     open_db, open_db_read_only,
     version::{get_db_version, DatabaseVersionError, DB_VERSION},
-    Tables,
 };
 use reth_primitives::ChainSpec;
 use std::{
@@ -20,13 +16,28 @@ use std::{
     sync::Arc,
 };
 
+mod backup;
+mod block_txs;
+mod checksum;
 mod clear;
+mod compact;
+mod compare_roots;
+mod compare_summaries;
 mod diff;
+mod export;
+mod find;
 mod get;
+mod head;
 mod list;
+mod range;
+#[cfg(feature = "remote-db")]
+mod remote;
 mod snapshots;
+mod stats;
 /// DB List TUI
 mod tui;
+mod verify;
+mod watch;
 
 /// `reth db` command
 #[derive(Debug, Parser)]
@@ -43,7 +54,8 @@ pub struct Command {
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -60,6 +72,10 @@ pub struct Command {
     )]
     chain: Arc<ChainSpec>,
 
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    chain_overrides: ChainOverrideArgs,
+
     #[clap(flatten)]
     db: DatabaseArgs,
 
@@ -71,13 +87,34 @@ pub struct Command {
 /// `reth db` subcommands
 pub enum Subcommands {
     /// Lists all the tables, their entry count and their size
-    Stats,
+    Stats(stats::Command),
+    /// Prints the chain, the tip block and the checkpoint of every stage
+    Head(head::Command),
+    /// Periodically prints the tip and the checkpoint of every stage, with their progress
+    Watch(watch::Command),
     /// Lists the contents of a table
     List(list::Command),
     /// Create a diff between two database tables or two entire databases.
     Diff(diff::Command),
+    /// Exports tables, or the rows of a block range, to CSV files with typed columns, e.g. for
+    /// analytics
+    Export(export::Command),
     /// Gets the content of a table for the given key
     Get(get::Command),
+    /// Prints the keys of a table whose value contains a byte pattern
+    Find(find::Command),
+    /// Prints the transactions of a block, along with their senders
+    BlockTxs(block_txs::Command),
+    /// Recomputes the state root at several blocks and compares it against their headers
+    CompareRoots(compare_roots::Command),
+    /// Checks the invariants between tables read-only, e.g. that every transaction has a lookup
+    /// entry, and reports their violations
+    Verify(verify::Command),
+    /// Compares the reports of two dumps, e.g. made by different reth versions
+    CompareSummaries(compare_summaries::Command),
+    /// Prints a checksum of the rows of every chain data table, e.g. to check that the databases
+    /// of two nodes synced to the same block are identical
+    Checksum(checksum::Command),
     /// Deletes all database entries
     Drop {
         /// Bypasses the interactive confirmation and drops the database directly
@@ -86,8 +123,19 @@ pub enum Subcommands {
     },
     /// Deletes all table entries
     Clear(clear::Command),
+    /// Rewrites the database without its free pages, giving the space freed by pruning or an
+    /// unwind back to the filesystem. The node must be stopped
+    Compact(compact::Command),
     /// Snapshots tables from database
     Snapshot(snapshots::Command),
+    /// Copies the database to a directory while it's in use, e.g. by a syncing node
+    Backup(backup::Command),
+    /// Serves the tables of the database read-only over TCP, without authentication
+    #[cfg(feature = "remote-db")]
+    ServeTables(remote::ServeCommand),
+    /// Copies the rows of tables of a `serve-tables` endpoint into a local database
+    #[cfg(feature = "remote-db")]
+    PullTables(remote::PullCommand),
     /// Lists current and local database versions
     Version,
     /// Returns the full database path
@@ -95,84 +143,40 @@ pub enum Subcommands {
 }
 
 impl Command {
+    /// Whether the command prints machine readable output to stdout.
+    pub fn is_machine_output(&self) -> bool {
+        match &self.command {
+            Subcommands::Stats(command) => command.is_machine_output(),
+            Subcommands::Verify(command) => command.is_machine_output(),
+            Subcommands::Checksum(command) => command.is_machine_output(),
+            _ => false,
+        }
+    }
+
     /// Execute `db` command
-    pub async fn execute(self) -> eyre::Result<()> {
+    pub async fn execute(mut self) -> eyre::Result<()> {
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
         // add network name to data dir
         let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
         let db_path = data_dir.db_path();
+        ExtractManifest::check(&db_path)?;
 
         match self.command {
-            // TODO: We'll need to add this on the DB trait.
-            Subcommands::Stats { .. } => {
+            Subcommands::Stats(command) => {
                 let db = open_db_read_only(&db_path, self.db.log_level)?;
                 let tool = DbTool::new(&db, self.chain.clone())?;
-                let mut stats_table = ComfyTable::new();
-                stats_table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
-                stats_table.set_header([
-                    "Table Name",
-                    "# Entries",
-                    "Branch Pages",
-                    "Leaf Pages",
-                    "Overflow Pages",
-                    "Total Size",
-                ]);
-
-                tool.db.view(|tx| {
-                    let mut tables =
-                        Tables::ALL.iter().map(|table| table.name()).collect::<Vec<_>>();
-                    tables.sort();
-                    let mut total_size = 0;
-                    for table in tables {
-                        let table_db =
-                            tx.inner.open_db(Some(table)).wrap_err("Could not open db.")?;
-
-                        let stats = tx
-                            .inner
-                            .db_stat(&table_db)
-                            .wrap_err(format!("Could not find table: {table}"))?;
-
-                        // Defaults to 16KB right now but we should
-                        // re-evaluate depending on the DB we end up using
-                        // (e.g. REDB does not have these options as configurable intentionally)
-                        let page_size = stats.page_size() as usize;
-                        let leaf_pages = stats.leaf_pages();
-                        let branch_pages = stats.branch_pages();
-                        let overflow_pages = stats.overflow_pages();
-                        let num_pages = leaf_pages + branch_pages + overflow_pages;
-                        let table_size = page_size * num_pages;
-
-                        total_size += table_size;
-                        let mut row = Row::new();
-                        row.add_cell(Cell::new(table))
-                            .add_cell(Cell::new(stats.entries()))
-                            .add_cell(Cell::new(branch_pages))
-                            .add_cell(Cell::new(leaf_pages))
-                            .add_cell(Cell::new(overflow_pages))
-                            .add_cell(Cell::new(human_bytes(table_size as f64)));
-                        stats_table.add_row(row);
-                    }
-
-                    let max_widths = stats_table.column_max_content_widths();
-
-                    let mut seperator = Row::new();
-                    for width in max_widths {
-                        seperator.add_cell(Cell::new("-".repeat(width as usize)));
-                    }
-                    stats_table.add_row(seperator);
-
-                    let mut row = Row::new();
-                    row.add_cell(Cell::new("Total DB size"))
-                        .add_cell(Cell::new(""))
-                        .add_cell(Cell::new(""))
-                        .add_cell(Cell::new(""))
-                        .add_cell(Cell::new(""))
-                        .add_cell(Cell::new(human_bytes(total_size as f64)));
-                    stats_table.add_row(row);
-
-                    Ok::<(), eyre::Report>(())
-                })??;
-
-                println!("{stats_table}");
+                command.execute(&tool)?;
+            }
+            Subcommands::Head(command) => {
+                let db = open_db_read_only(&db_path, self.db.log_level)?;
+                let tool = DbTool::new(&db, self.chain.clone())?;
+                command.execute(&tool)?;
+            }
+            Subcommands::Watch(command) => {
+                let db = open_db_read_only(&db_path, self.db.log_level)?;
+                let tool = DbTool::new(&db, self.chain.clone())?;
+                command.execute(&tool).await?;
             }
             Subcommands::List(command) => {
                 let db = open_db_read_only(&db_path, self.db.log_level)?;
@@ -184,12 +188,46 @@ impl Command {
                 let tool = DbTool::new(&db, self.chain.clone())?;
                 command.execute(&tool)?;
             }
+            Subcommands::Export(command) => {
+                let db = open_db_read_only(&db_path, self.db.log_level)?;
+                let tool = DbTool::new(&db, self.chain.clone())?;
+                command.execute(&tool)?;
+            }
             Subcommands::Get(command) => {
                 let db = open_db_read_only(&db_path, self.db.log_level)?;
                 let tool = DbTool::new(&db, self.chain.clone())?;
                 command.execute(&tool)?;
             }
+            Subcommands::Find(command) => {
+                let db = open_db_read_only(&db_path, self.db.log_level)?;
+                let tool = DbTool::new(&db, self.chain.clone())?;
+                command.execute(&tool)?;
+            }
+            Subcommands::BlockTxs(command) => {
+                let db = open_db_read_only(&db_path, self.db.log_level)?;
+                let tool = DbTool::new(&db, self.chain.clone())?;
+                command.execute(&tool)?;
+            }
+            Subcommands::CompareRoots(command) => {
+                let db = open_db_read_only(&db_path, self.db.log_level)?;
+                let tool = DbTool::new(&db, self.chain.clone())?;
+                command.execute(&tool)?;
+            }
+            Subcommands::Verify(command) => {
+                let db = open_db_read_only(&db_path, self.db.log_level)?;
+                let tool = DbTool::new(&db, self.chain.clone())?;
+                command.execute(&tool)?;
+            }
+            Subcommands::CompareSummaries(command) => {
+                command.execute()?;
+            }
+            Subcommands::Checksum(command) => {
+                let db = open_db_read_only(&db_path, self.db.log_level)?;
+                let tool = DbTool::new(&db, self.chain.clone())?;
+                command.execute(&tool)?;
+            }
             Subcommands::Drop { force } => {
+                self.db.ensure_writable("db drop")?;
                 if !force {
                     // Ask for confirmation
                     print!("Are you sure you want to drop the database at {db_path:?}? This cannot be undone. (y/N): ");
@@ -210,12 +248,30 @@ impl Command {
                 tool.drop(db_path)?;
             }
             Subcommands::Clear(command) => {
+                self.db.ensure_writable("db clear")?;
                 let db = open_db(&db_path, self.db.log_level)?;
                 command.execute(&db)?;
             }
+            Subcommands::Compact(command) => {
+                self.db.ensure_writable("db compact")?;
+                command.execute(&db_path, self.db.log_level)?;
+            }
             Subcommands::Snapshot(command) => {
                 command.execute(&db_path, self.db.log_level, self.chain.clone())?;
             }
+            #[cfg(feature = "remote-db")]
+            Subcommands::ServeTables(command) => {
+                let db = open_db_read_only(&db_path, self.db.log_level)?;
+                command.execute(Arc::new(db))?;
+            }
+            #[cfg(feature = "remote-db")]
+            Subcommands::PullTables(command) => {
+                command.execute()?;
+            }
+            Subcommands::Backup(command) => {
+                let db = open_db_read_only(&db_path, self.db.log_level)?;
+                command.execute(&db, &db_path)?;
+            }
             Subcommands::Version => {
                 let local_db_version = match get_db_version(&db_path) {
                     Ok(version) => Some(version),
@@ -250,4 +306,38 @@ mod tests {
         let cmd = Command::try_parse_from(["reth", "stats", "--datadir", "../mainnet"]).unwrap();
         assert_eq!(cmd.datadir.as_ref(), Some(Path::new("../mainnet")));
     }
+
+    #[test]
+    fn parse_stats_json_sorted() {
+        let cmd =
+            Command::try_parse_from(["reth", "stats", "--json", "--sort", "size", "--reverse"])
+                .unwrap();
+        assert!(cmd.is_machine_output());
+    }
+
+    #[test]
+    fn parse_checksum_range() {
+        let cmd = Command::try_parse_from([
+            "reth",
+            "checksum",
+            "--table",
+            "Headers,Transactions",
+            "--from",
+            "1",
+            "--to",
+            "10",
+            "--json",
+        ])
+        .unwrap();
+        assert!(cmd.is_machine_output());
+        assert!(Command::try_parse_from(["reth", "checksum", "--from", "1"]).is_err());
+    }
+
+    #[test]
+    fn parse_chain_overrides() {
+        let cmd = Command::try_parse_from(["reth", "--chain.override.chain-id", "1337", "stats"])
+            .unwrap();
+        let chain = cmd.chain_overrides.apply(cmd.chain.clone());
+        assert_eq!(chain.chain, reth_primitives::Chain::from(1337));
+    }
 }
@@ -0,0 +1,58 @@
+use crate::utils::DbTool;
+use reth_db::{
+    database::Database, models::BlockNumberAddress, transaction::DbTx, BlockBodyIndices,
+};
+use reth_primitives::{BlockNumber, TxNumber};
+use std::ops::Bound;
+
+/// The key ranges of the tables keyed by block or transaction number that hold the rows of a
+/// block range, e.g. of `--from` and `--to`.
+///
+/// All the ranges are unbounded if there is no block range.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TableRanges {
+    /// The blocks of the range.
+    pub(crate) blocks: (Bound<BlockNumber>, Bound<BlockNumber>),
+    /// The transactions of the blocks of the range, according to the database of the tool.
+    pub(crate) transactions: (Bound<TxNumber>, Bound<TxNumber>),
+    /// The keys of the storage changes of the blocks of the range.
+    pub(crate) storage_changes: (Bound<BlockNumberAddress>, Bound<BlockNumberAddress>),
+}
+
+impl TableRanges {
+    /// The ranges of the blocks `from..=to` in the database of `tool`, or of all rows if `None`.
+    pub(crate) fn new<DB: Database>(
+        tool: &DbTool<'_, DB>,
+        range: Option<(BlockNumber, BlockNumber)>,
+    ) -> eyre::Result<Self> {
+        let Some((from, to)) = range else {
+            return Ok(Self {
+                blocks: (Bound::Unbounded, Bound::Unbounded),
+                transactions: (Bound::Unbounded, Bound::Unbounded),
+                storage_changes: (Bound::Unbounded, Bound::Unbounded),
+            })
+        };
+        if from > to {
+            eyre::bail!("--from {from} is after --to {to}.")
+        }
+
+        let indices = |block| {
+            tool.db.view(|tx| tx.get::<BlockBodyIndices>(block))??.ok_or_else(|| {
+                eyre::eyre!("Block body indices of block {block} not found in the database.")
+            })
+        };
+        let storage_changes = BlockNumberAddress::range(from..to + 1);
+
+        Ok(Self {
+            blocks: (Bound::Included(from), Bound::Included(to)),
+            transactions: (
+                Bound::Included(indices(from)?.first_tx_num()),
+                Bound::Excluded(indices(to)?.next_tx_num()),
+            ),
+            storage_changes: (
+                Bound::Included(storage_changes.start),
+                Bound::Excluded(storage_changes.end),
+            ),
+        })
+    }
+}
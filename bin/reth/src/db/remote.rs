@@ -0,0 +1,605 @@
+//! Experimental reads of the tables of a remote node, e.g. a shared archive node, to build a local
+//! source database for a dump without copying the whole database of the node.
+//!
+//! `reth db serve-tables` answers requests read-only over TCP, and `reth db pull-tables` copies
+//! the rows of a table over a key range into a local database. Every message is a sequence of
+//! length-prefixed records:
+//!
+//! ```text
+//! record   = length (u32, little endian) | bytes
+//! request  = table name | start key | end key | batch size (u32, little endian)
+//! response = batch* | end
+//! batch    = row count (u32, little endian, > 0) | (key | value){row count}
+//! end      = 0 (u32, little endian) | error message, empty on success
+//! ```
+//!
+//! Keys and values are the raw bytes of the database. Empty start and end keys leave the range
+//! unbounded, the end key being inclusive otherwise. Records longer than the largest key, value
+//! or message are rejected before being read.
+//!
+//! The rows of every batch are read in a transaction of their own, so a table pulled from a node
+//! that is writing to it isn't a consistent snapshot of the table.
+//!
+//! The endpoint has no authentication nor encryption, and serves every table of the database,
+//! so anyone who can connect can read the whole node. It should only be bound to a private
+//! interface or reached through an SSH tunnel. A pulled table is only as trustworthy as the node
+//! it was pulled from.
+use crate::stage::dump::{read_record, write_record};
+use clap::Parser;
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW},
+    database::Database,
+    init_db,
+    table::{Decode, Decompress, Table},
+    transaction::{DbTx, DbTxMut},
+    DatabaseEnv, RawKey, RawTable, RawValue, TableViewer, Tables,
+};
+use reth_primitives::hex;
+use std::{
+    cell::RefCell,
+    io::{BufReader, BufWriter, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, Condvar, Mutex},
+    time::Duration,
+};
+use tracing::{info, warn};
+
+/// The longest table name or raw key of a message.
+const MAX_KEY_LEN: usize = 256;
+
+/// The longest raw value of a response.
+const MAX_VALUE_LEN: usize = 64 * 1024 * 1024;
+
+/// The longest error message of a response.
+const MAX_ERROR_LEN: usize = 64 * 1024;
+
+/// How long a served connection may wait for its next request or block on a write, so that idle
+/// clients and clients that stopped reading don't hold on to their slot.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The arguments for the `reth db serve-tables` command
+#[derive(Parser, Debug)]
+pub struct ServeCommand {
+    /// The address the table-read endpoint listens on.
+    ///
+    /// The endpoint isn't authenticated, so only bind it to an interface reachable by trusted
+    /// machines.
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:9545", verbatim_doc_comment)]
+    addr: SocketAddr,
+    /// The maximum number of connections served at once.
+    ///
+    /// Further connections are only accepted once one of them is closed.
+    #[arg(
+        long,
+        value_name = "CONNECTIONS",
+        default_value_t = 16,
+        value_parser = clap::value_parser!(u32).range(1..),
+        verbatim_doc_comment
+    )]
+    max_connections: u32,
+}
+
+impl ServeCommand {
+    /// Execute `db serve-tables` command
+    pub fn execute<DB: Database + 'static>(self, db: Arc<DB>) -> eyre::Result<()> {
+        let listener = TcpListener::bind(self.addr)?;
+        warn!(target: "reth::cli", addr = %self.addr, "Serving the tables of the database without authentication");
+
+        let limit = Arc::new(ConnectionLimit::new(self.max_connections as usize));
+        loop {
+            let slot = limit.acquire();
+            let (stream, peer) = listener.accept()?;
+            let db = db.clone();
+            std::thread::spawn(move || {
+                let _slot = slot;
+                if let Err(err) = serve_connection(&*db, stream, CONNECTION_TIMEOUT) {
+                    warn!(target: "reth::cli", %peer, %err, "Table-read connection failed");
+                }
+            });
+        }
+    }
+}
+
+/// Bounds the number of connections served at once.
+#[derive(Debug)]
+struct ConnectionLimit {
+    max: usize,
+    open: Mutex<usize>,
+    closed: Condvar,
+}
+
+impl ConnectionLimit {
+    fn new(max: usize) -> Self {
+        Self { max, open: Mutex::new(0), closed: Condvar::new() }
+    }
+
+    /// Waits for fewer than `max` connections to be open, returning the slot of a new one.
+    fn acquire(self: &Arc<Self>) -> ConnectionSlot {
+        let mut open = self.open.lock().expect("not poisoned");
+        while *open >= self.max {
+            open = self.closed.wait(open).expect("not poisoned");
+        }
+        *open += 1;
+        ConnectionSlot(self.clone())
+    }
+}
+
+/// The slot of an open connection, freed on drop.
+#[derive(Debug)]
+struct ConnectionSlot(Arc<ConnectionLimit>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        *self.0.open.lock().expect("not poisoned") -= 1;
+        self.0.closed.notify_one();
+    }
+}
+
+/// The arguments for the `reth db pull-tables` command
+#[derive(Parser, Debug)]
+pub struct PullCommand {
+    /// The address of the `reth db serve-tables` endpoint to read from.
+    #[arg(long, value_name = "ADDR")]
+    remote: SocketAddr,
+    /// The tables to pull.
+    #[arg(long = "table", value_name = "TABLE", required = true)]
+    tables: Vec<Tables>,
+    /// The first key to pull, as raw hex. Defaults to the first row of the tables.
+    #[arg(long, value_name = "HEX", value_parser = parse_raw_key)]
+    start_key: Option<Vec<u8>>,
+    /// The last key to pull, as raw hex. Defaults to the last row of the tables.
+    #[arg(long, value_name = "HEX", value_parser = parse_raw_key)]
+    end_key: Option<Vec<u8>>,
+    /// The number of rows the remote sends at once.
+    #[arg(
+        long,
+        value_name = "ROWS",
+        default_value_t = 10_000,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    batch_size: u32,
+    /// The local database the rows are written to, created if missing.
+    #[arg(long, value_name = "PATH")]
+    output_db: PathBuf,
+}
+
+impl PullCommand {
+    /// Execute `db pull-tables` command
+    pub fn execute(self) -> eyre::Result<()> {
+        let db = init_db(&self.output_db, None)?;
+        let request = Request {
+            start: self.start_key.clone().unwrap_or_default(),
+            end: self.end_key.clone().unwrap_or_default(),
+            batch_size: self.batch_size,
+        };
+
+        for table in &self.tables {
+            let stream = TcpStream::connect(self.remote)?;
+            let rows = table.view(&PullViewer {
+                db: &db,
+                table: *table,
+                request: &request,
+                stream: RefCell::new(stream),
+            })?;
+            info!(target: "reth::cli", %table, rows, remote = %self.remote, "Pulled table");
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_raw_key(value: &str) -> eyre::Result<Vec<u8>> {
+    Ok(hex::decode(value)?)
+}
+
+/// A request for the rows of a table over a key range.
+#[derive(Debug, PartialEq, Eq)]
+struct Request {
+    /// The first raw key, empty to start at the first row.
+    start: Vec<u8>,
+    /// The last raw key, empty to end at the last row.
+    end: Vec<u8>,
+    batch_size: u32,
+}
+
+impl Request {
+    fn write(&self, table: Tables, writer: &mut impl Write) -> eyre::Result<()> {
+        write_record(writer, table.name().as_bytes())?;
+        write_record(writer, &self.start)?;
+        write_record(writer, &self.end)?;
+        write_record(writer, &self.batch_size.to_le_bytes())?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads the next request of a connection, or `None` once it's closed.
+    fn read(reader: &mut impl Read) -> eyre::Result<Option<(String, Self)>> {
+        let Some(table) = read_record(reader, MAX_KEY_LEN)? else { return Ok(None) };
+        let (start, end) =
+            (expect_record(reader, MAX_KEY_LEN)?, expect_record(reader, MAX_KEY_LEN)?);
+        let batch_size = read_u32(reader)?;
+        Ok(Some((String::from_utf8(table)?, Self { start, end, batch_size: batch_size.max(1) })))
+    }
+}
+
+fn expect_record(reader: &mut impl Read, max_len: usize) -> eyre::Result<Vec<u8>> {
+    read_record(reader, max_len)?.ok_or_else(|| eyre::eyre!("Table-read message is truncated."))
+}
+
+fn read_u32(reader: &mut impl Read) -> eyre::Result<u32> {
+    let record = expect_record(reader, 4)?;
+    Ok(u32::from_le_bytes(
+        record
+            .try_into()
+            .map_err(|_| eyre::eyre!("Table-read message holds a malformed number."))?,
+    ))
+}
+
+/// Answers the requests of a connection until it's closed, failing if it has to wait longer than
+/// `timeout` for a request or for a write.
+fn serve_connection<DB: Database>(
+    db: &DB,
+    stream: TcpStream,
+    timeout: Duration,
+) -> eyre::Result<()> {
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let writer = RefCell::new(BufWriter::new(stream));
+    while let Some((name, request)) = Request::read(&mut reader)? {
+        let result = Tables::from_str(&name)
+            .map_err(|err| eyre::eyre!("{err}: {name}"))
+            .and_then(|table| table.view(&ServeViewer { db, request: &request, writer: &writer }));
+
+        let mut writer = writer.borrow_mut();
+        write_record(&mut *writer, &0u32.to_le_bytes())?;
+        match result {
+            Ok(rows) => {
+                write_record(&mut *writer, &[])?;
+                info!(target: "reth::cli", table = name, rows, "Served table rows");
+            }
+            Err(err) => write_record(&mut *writer, err.to_string().as_bytes())?,
+        }
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+struct ServeViewer<'a, DB: Database, W: Write> {
+    db: &'a DB,
+    request: &'a Request,
+    writer: &'a RefCell<W>,
+}
+
+impl<DB: Database, W: Write> TableViewer<usize> for ServeViewer<'_, DB, W> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<usize, Self::Error> {
+        let (end, batch_size) = (&self.request.end, self.request.batch_size as usize);
+        // The last key written and how many of its rows were, to resume the walk after them. Rows
+        // of a dupsort table share their key, so the walk can't just resume after the key.
+        let mut resume: Option<(Vec<u8>, usize)> = None;
+        let mut rows = 0;
+        loop {
+            // Every batch is read in a transaction of its own, closed before the batch is written,
+            // so that a client reading slowly doesn't keep a read transaction of the node open.
+            let (mut batch, done) = self.db.view(|tx| -> eyre::Result<_> {
+                let mut cursor = tx.cursor_read::<RawTable<T>>()?;
+                let (start, mut skip) = match &resume {
+                    Some((key, written)) => (Some(key), *written),
+                    None => ((!self.request.start.is_empty()).then_some(&self.request.start), 0),
+                };
+                let resumed = resume.as_ref().map(|(key, _)| key);
+
+                let mut batch = Vec::new();
+                for row in cursor.walk(start.map(RawKey::<T::Key>::decode).transpose()?)? {
+                    let (key, value) = row?;
+                    if skip > 0 && Some(key.raw_key()) == resumed {
+                        skip -= 1;
+                        continue
+                    }
+                    if !end.is_empty() && key.raw_key().as_slice() > end.as_slice() {
+                        return Ok((batch, true))
+                    }
+                    if batch.len() == batch_size {
+                        return Ok((batch, false))
+                    }
+                    batch.push((key, value));
+                }
+                Ok((batch, true))
+            })??;
+
+            if let Some((last, _)) = batch.last() {
+                let last = last.raw_key().clone();
+                let trailing =
+                    batch.iter().rev().take_while(|(key, _)| *key.raw_key() == last).count();
+                let written = match resume {
+                    Some((key, written)) if key == last && trailing == batch.len() => {
+                        written + trailing
+                    }
+                    _ => trailing,
+                };
+                resume = Some((last, written));
+            }
+            rows += write_batch(&mut *self.writer.borrow_mut(), &mut batch)?;
+            if done {
+                return Ok(rows)
+            }
+        }
+    }
+}
+
+/// Writes and clears the rows of `batch`, if any, returning their number.
+fn write_batch<T: Table>(
+    writer: &mut impl Write,
+    batch: &mut Vec<(RawKey<T::Key>, RawValue<T::Value>)>,
+) -> eyre::Result<usize> {
+    let rows = batch.len();
+    if rows == 0 {
+        return Ok(0)
+    }
+
+    write_record(writer, &u32::try_from(rows)?.to_le_bytes())?;
+    for (key, value) in batch.drain(..) {
+        write_record(writer, key.raw_key())?;
+        write_record(writer, value.raw_value())?;
+    }
+    writer.flush()?;
+
+    Ok(rows)
+}
+
+struct PullViewer<'a> {
+    db: &'a DatabaseEnv,
+    table: Tables,
+    request: &'a Request,
+    stream: RefCell<TcpStream>,
+}
+
+impl TableViewer<usize> for PullViewer<'_> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<usize, Self::Error> {
+        let mut stream = self.stream.borrow_mut();
+        self.request.write(self.table, &mut BufWriter::new(&mut *stream))?;
+        let mut reader = BufReader::new(&mut *stream);
+
+        let tx = self.db.tx_mut()?;
+        let mut cursor = tx.cursor_write::<RawTable<T>>()?;
+        let mut rows = 0;
+        loop {
+            let count = read_u32(&mut reader)?;
+            if count == 0 {
+                let error = expect_record(&mut reader, MAX_ERROR_LEN)?;
+                if !error.is_empty() {
+                    eyre::bail!(
+                        "Remote failed to serve {}: {}",
+                        self.table,
+                        String::from_utf8_lossy(&error)
+                    )
+                }
+                break
+            }
+
+            for _ in 0..count {
+                let (key, value) = (
+                    expect_record(&mut reader, MAX_KEY_LEN)?,
+                    expect_record(&mut reader, MAX_VALUE_LEN)?,
+                );
+                let (key, value) =
+                    (RawKey::<T::Key>::decode(key)?, RawValue::decompress_owned(value)?);
+                // The rows may land between existing ones of the local table, so they are
+                // inserted rather than appended.
+                cursor.upsert(key, value)?;
+            }
+            rows += count as usize;
+        }
+        drop(cursor);
+        tx.commit()?;
+
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{tables, test_utils::create_test_rw_db};
+    use reth_primitives::{Address, Bytecode, Bytes, StorageEntry, B256, U256};
+
+    /// Serves `db` on a local port until the test ends.
+    fn serve(db: Arc<DatabaseEnv>) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let remote = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                serve_connection(&*db, stream.unwrap(), CONNECTION_TIMEOUT).unwrap();
+            }
+        });
+        remote
+    }
+
+    #[test]
+    fn pull_table_range() {
+        let source = create_test_rw_db();
+        source
+            .update(|tx| {
+                for number in 0..10u64 {
+                    tx.put::<tables::CanonicalHeaders>(number, B256::with_last_byte(number as u8))?;
+                }
+                Ok::<_, reth_db::DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+
+        let remote = serve(source);
+
+        let dir = tempfile::tempdir().unwrap();
+        PullCommand::try_parse_from([
+            "reth",
+            "--remote",
+            &remote.to_string(),
+            "--table",
+            "CanonicalHeaders",
+            "--start-key",
+            &hex::encode(3u64.to_be_bytes()),
+            "--end-key",
+            &hex::encode(7u64.to_be_bytes()),
+            "--batch-size",
+            "2",
+            "--output-db",
+            dir.path().to_str().unwrap(),
+        ])
+        .unwrap()
+        .execute()
+        .unwrap();
+
+        let pulled = reth_db::open_db_read_only(dir.path(), None).unwrap();
+        let rows = pulled
+            .view(|tx| {
+                tx.cursor_read::<tables::CanonicalHeaders>()?
+                    .walk(None)?
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rows,
+            (3..=7u64)
+                .map(|number| (number, B256::with_last_byte(number as u8)))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn pull_dupsort_table() {
+        let source = create_test_rw_db();
+        let entries = [Address::with_last_byte(1), Address::with_last_byte(2)]
+            .into_iter()
+            .flat_map(|address| {
+                (1..=3u8).map(move |slot| {
+                    let entry =
+                        StorageEntry { key: B256::with_last_byte(slot), value: U256::from(slot) };
+                    (address, entry)
+                })
+            })
+            .collect::<Vec<_>>();
+        source
+            .update(|tx| {
+                for (address, entry) in &entries {
+                    tx.put::<tables::PlainStorageState>(*address, *entry)?;
+                }
+                Ok::<_, reth_db::DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+        let remote = serve(source);
+
+        // The batches split the slots of an account, which must all be pulled once
+        let dir = tempfile::tempdir().unwrap();
+        PullCommand::try_parse_from([
+            "reth",
+            "--remote",
+            &remote.to_string(),
+            "--table",
+            "PlainStorageState",
+            "--batch-size",
+            "2",
+            "--output-db",
+            dir.path().to_str().unwrap(),
+        ])
+        .unwrap()
+        .execute()
+        .unwrap();
+
+        let pulled = reth_db::open_db_read_only(dir.path(), None).unwrap();
+        let rows = pulled
+            .view(|tx| {
+                tx.cursor_read::<tables::PlainStorageState>()?
+                    .walk(None)?
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(rows, entries);
+    }
+
+    #[test]
+    fn drop_stalled_connection() {
+        // Far more than the socket buffers hold, so that the writes of the server block
+        let source = create_test_rw_db();
+        source
+            .update(|tx| {
+                for byte in 0..32u8 {
+                    let code = Bytecode::new_raw(Bytes::from(vec![byte; 1024 * 1024]));
+                    tx.put::<tables::Bytecodes>(B256::with_last_byte(byte), code)?;
+                }
+                Ok::<_, reth_db::DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let remote = listener.local_addr().unwrap();
+        let limit = Arc::new(ConnectionLimit::new(1));
+        let (served, result) = std::sync::mpsc::channel();
+        std::thread::spawn({
+            let limit = limit.clone();
+            move || {
+                let stalled = {
+                    let _slot = limit.acquire();
+                    let (stream, _) = listener.accept().unwrap();
+                    serve_connection(&*source, stream, Duration::from_millis(200)).is_err()
+                };
+                served.send(stalled).unwrap();
+            }
+        });
+
+        // The client requests the table but never reads the response
+        let mut client = TcpStream::connect(remote).unwrap();
+        Request { start: vec![], end: vec![], batch_size: 1 }
+            .write(Tables::Bytecodes, &mut client)
+            .unwrap();
+
+        assert!(result.recv_timeout(Duration::from_secs(10)).unwrap());
+        assert_eq!(*limit.open.lock().unwrap(), 0);
+        drop(client);
+    }
+
+    #[test]
+    fn reject_long_records() {
+        let mut message = Vec::new();
+        let request = Request { start: vec![0; MAX_KEY_LEN + 1], end: vec![], batch_size: 1 };
+        request.write(Tables::CanonicalHeaders, &mut message).unwrap();
+        assert!(Request::read(&mut message.as_slice()).is_err());
+
+        // A length past the end of the message is read as a truncated record, not allocated
+        let mut message = Vec::new();
+        write_record(&mut message, Tables::CanonicalHeaders.name().as_bytes()).unwrap();
+        message.extend_from_slice(&(MAX_KEY_LEN as u32).to_le_bytes());
+        assert!(Request::read(&mut message.as_slice()).is_err());
+    }
+
+    #[test]
+    fn limit_connections() {
+        let limit = Arc::new(ConnectionLimit::new(1));
+        let slot = limit.acquire();
+
+        let (opened, waiting) = std::sync::mpsc::channel();
+        let thread = std::thread::spawn({
+            let limit = limit.clone();
+            move || {
+                let _slot = limit.acquire();
+                opened.send(()).unwrap();
+            }
+        });
+        assert!(waiting.recv_timeout(std::time::Duration::from_millis(100)).is_err());
+
+        drop(slot);
+        waiting.recv().unwrap();
+        thread.join().unwrap();
+    }
+}
@@ -1,17 +1,18 @@
 use crate::{db::genesis_value_parser, utils::DbTool};
 use clap::Parser;
 use itertools::Itertools;
-use reth_db::open_db_read_only;
+use reth_db::{database::Database, open_db_read_only, DatabaseEnvRO};
 use reth_interfaces::db::LogLevel;
 use reth_nippy_jar::{
     compression::{DecoderDictionary, Decompressor},
     NippyJar,
 };
 use reth_primitives::{
-    snapshot::{Compression, InclusionFilter, PerfectHashingFunction},
+    snapshot::{Compression, Filters, InclusionFilter, PerfectHashingFunction},
     BlockNumber, ChainSpec, SnapshotSegment,
 };
 use reth_provider::providers::SnapshotProvider;
+use reth_snapshot::segments::{Receipts, Segment, Transactions};
 use std::{path::Path, sync::Arc};
 
 mod bench;
@@ -22,7 +23,8 @@ mod headers;
 pub struct Command {
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -98,8 +100,14 @@ impl Command {
                             InclusionFilter::Cuckoo,
                             *phf,
                         )?,
-                        SnapshotSegment::Transactions => todo!(),
-                        SnapshotSegment::Receipts => todo!(),
+                        SnapshotSegment::Transactions => self.generate_snapshot(
+                            &tool,
+                            Transactions::new(*compression, self.filters(*phf)),
+                        )?,
+                        SnapshotSegment::Receipts => self.generate_snapshot(
+                            &tool,
+                            Receipts::new(*compression, self.filters(*phf)),
+                        )?,
                     }
                 }
             }
@@ -125,6 +133,25 @@ impl Command {
         Ok(())
     }
 
+    /// Generates the snapshot of `segment` for the blocks of the command.
+    fn generate_snapshot(
+        &self,
+        tool: &DbTool<'_, DatabaseEnvRO>,
+        segment: impl Segment,
+    ) -> eyre::Result<()> {
+        segment.snapshot(&tool.db.tx()?, self.from..=(self.from + self.block_interval - 1))?;
+        Ok(())
+    }
+
+    /// Returns the filters of the snapshots, using the provided [`PerfectHashingFunction`].
+    fn filters(&self, phf: PerfectHashingFunction) -> Filters {
+        if self.with_filters {
+            Filters::WithFilters(InclusionFilter::Cuckoo, phf)
+        } else {
+            Filters::WithoutFilters
+        }
+    }
+
     /// Returns a [`SnapshotProvider`] of the provided [`NippyJar`], alongside a list of
     /// [`DecoderDictionary`] and [`Decompressor`] if necessary.
     fn prepare_jar_provider<'a>(
@@ -140,6 +167,9 @@ impl Command {
             }
         }
 
-        Ok((SnapshotProvider { jar: &*jar, jar_start_block: self.from }, decompressors))
+        Ok((
+            SnapshotProvider { jar: &*jar, jar_start_block: self.from, jar_start_tx: 0 },
+            decompressors,
+        ))
     }
 }
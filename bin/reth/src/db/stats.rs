@@ -0,0 +1,230 @@
+//! `reth db stats`: the entries, pages and size of every table, as MDBX reports them.
+use crate::utils::DbTool;
+use clap::{Parser, ValueEnum};
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use eyre::WrapErr;
+use human_bytes::human_bytes;
+use reth_db::{database::Database, DatabaseEnvRO, Tables};
+use serde::Serialize;
+
+/// The arguments for the `reth db stats` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// Prints the stats as JSON instead of a table.
+    #[arg(long)]
+    json: bool,
+
+    /// The column the tables are sorted by.
+    #[arg(long, value_enum, default_value_t = SortBy::Name)]
+    sort: SortBy,
+
+    /// Sorts the tables in descending order.
+    #[arg(long)]
+    reverse: bool,
+}
+
+/// A column of `reth db stats` to sort the tables by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortBy {
+    /// The name of the table.
+    Name,
+    /// The number of entries of the table.
+    Entries,
+    /// The size of the pages of the table.
+    Size,
+    /// The number of pages of the table.
+    Pages,
+}
+
+/// The stats of a single table.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct TableStats {
+    pub(crate) name: &'static str,
+    pub(crate) entries: usize,
+    pub(crate) branch_pages: usize,
+    pub(crate) leaf_pages: usize,
+    pub(crate) overflow_pages: usize,
+    pub(crate) size_bytes: usize,
+    /// The share of all the pages of the tables which belong to this table, in percent.
+    pub(crate) share_percent: f64,
+}
+
+impl TableStats {
+    fn pages(&self) -> usize {
+        self.branch_pages + self.leaf_pages + self.overflow_pages
+    }
+}
+
+/// The stats of all tables, along with their total size.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct DbStats {
+    pub(crate) tables: Vec<TableStats>,
+    pub(crate) page_size: usize,
+    pub(crate) total_size_bytes: usize,
+}
+
+impl Command {
+    /// Execute `db stats` command
+    pub fn execute(self, tool: &DbTool<'_, DatabaseEnvRO>) -> eyre::Result<()> {
+        let mut stats = tool.db.view(|tx| {
+            let mut tables = Vec::with_capacity(Tables::ALL.len());
+            let mut page_size = 0;
+            for table in Tables::ALL.iter().map(|table| table.name()) {
+                let table_db = tx.inner.open_db(Some(table)).wrap_err("Could not open db.")?;
+                let stats = tx
+                    .inner
+                    .db_stat(&table_db)
+                    .wrap_err(format!("Could not find table: {table}"))?;
+
+                // Defaults to 16KB right now but we should
+                // re-evaluate depending on the DB we end up using
+                // (e.g. REDB does not have these options as configurable intentionally)
+                page_size = stats.page_size() as usize;
+                let (branch_pages, leaf_pages, overflow_pages) =
+                    (stats.branch_pages(), stats.leaf_pages(), stats.overflow_pages());
+                tables.push(TableStats {
+                    name: table,
+                    entries: stats.entries(),
+                    branch_pages,
+                    leaf_pages,
+                    overflow_pages,
+                    size_bytes: page_size * (branch_pages + leaf_pages + overflow_pages),
+                    share_percent: 0.0,
+                });
+            }
+
+            let total_size_bytes = tables.iter().map(|table| table.size_bytes).sum();
+            Ok::<_, eyre::Report>(DbStats { tables, page_size, total_size_bytes })
+        })??;
+
+        stats.fill_shares();
+        stats.sort(self.sort, self.reverse);
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+        } else {
+            println!("{}", stats_table(&stats));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the command prints its stats as JSON to stdout.
+    pub(crate) fn is_machine_output(&self) -> bool {
+        self.json
+    }
+}
+
+impl DbStats {
+    /// Sets the share of the total size of every table.
+    fn fill_shares(&mut self) {
+        for table in &mut self.tables {
+            table.share_percent = if self.total_size_bytes == 0 {
+                0.0
+            } else {
+                table.size_bytes as f64 * 100.0 / self.total_size_bytes as f64
+            };
+        }
+    }
+
+    /// Sorts the tables by `by`, and by name between equal ones.
+    fn sort(&mut self, by: SortBy, reverse: bool) {
+        self.tables.sort_by(|a, b| {
+            let ordering = match by {
+                SortBy::Name => a.name.cmp(b.name),
+                SortBy::Entries => a.entries.cmp(&b.entries),
+                SortBy::Size => a.size_bytes.cmp(&b.size_bytes),
+                SortBy::Pages => a.pages().cmp(&b.pages()),
+            };
+            ordering.then_with(|| a.name.cmp(b.name))
+        });
+        if reverse {
+            self.tables.reverse();
+        }
+    }
+}
+
+/// Renders the stats as a table, with a row per table and a row with the total size.
+fn stats_table(stats: &DbStats) -> ComfyTable {
+    let mut table = ComfyTable::new();
+    table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+    table.set_header([
+        "Table Name",
+        "# Entries",
+        "Branch Pages",
+        "Leaf Pages",
+        "Overflow Pages",
+        "Total Size",
+        "% of DB",
+    ]);
+
+    for stats in &stats.tables {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(stats.name))
+            .add_cell(Cell::new(stats.entries))
+            .add_cell(Cell::new(stats.branch_pages))
+            .add_cell(Cell::new(stats.leaf_pages))
+            .add_cell(Cell::new(stats.overflow_pages))
+            .add_cell(Cell::new(human_bytes(stats.size_bytes as f64)))
+            .add_cell(Cell::new(format!("{:.2}%", stats.share_percent)));
+        table.add_row(row);
+    }
+
+    let mut separator = Row::new();
+    for width in table.column_max_content_widths() {
+        separator.add_cell(Cell::new("-".repeat(width as usize)));
+    }
+    table.add_row(separator);
+
+    let mut row = Row::new();
+    row.add_cell(Cell::new("Total DB size"))
+        .add_cell(Cell::new(""))
+        .add_cell(Cell::new(""))
+        .add_cell(Cell::new(""))
+        .add_cell(Cell::new(""))
+        .add_cell(Cell::new(human_bytes(stats.total_size_bytes as f64)))
+        .add_cell(Cell::new(""));
+    table.add_row(row);
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(name: &'static str, entries: usize, size_bytes: usize) -> TableStats {
+        TableStats {
+            name,
+            entries,
+            branch_pages: 0,
+            leaf_pages: size_bytes / 4096,
+            overflow_pages: 0,
+            size_bytes,
+            share_percent: 0.0,
+        }
+    }
+
+    #[test]
+    fn sort_and_share() {
+        let mut stats = DbStats {
+            tables: vec![
+                table("Headers", 10, 4096),
+                table("Receipts", 5, 3 * 4096),
+                table("Bytecodes", 10, 0),
+            ],
+            page_size: 4096,
+            total_size_bytes: 4 * 4096,
+        };
+        stats.fill_shares();
+        assert_eq!(stats.tables[1].share_percent, 75.0);
+
+        stats.sort(SortBy::Size, true);
+        let names = stats.tables.iter().map(|table| table.name).collect::<Vec<_>>();
+        assert_eq!(names, ["Receipts", "Headers", "Bytecodes"]);
+
+        stats.sort(SortBy::Entries, false);
+        let names = stats.tables.iter().map(|table| table.name).collect::<Vec<_>>();
+        assert_eq!(names, ["Receipts", "Bytecodes", "Headers"]);
+    }
+}
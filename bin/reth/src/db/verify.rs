@@ -0,0 +1,469 @@
+//! `reth db verify`: read-only checks of the invariants between tables, to detect corruption
+//! before the stages build on it.
+use crate::utils::DbTool;
+use clap::{Parser, ValueEnum};
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    models::StoredBlockBodyIndices,
+    table::Table,
+    tables,
+    transaction::DbTx,
+};
+use reth_primitives::{keccak256, stage::StageId, BlockNumber, PruneSegment};
+use serde::Serialize;
+use std::{
+    fmt,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use tracing::info;
+
+/// The number of rows a check reads between two progress logs.
+const PROGRESS_INTERVAL: usize = 1_000_000;
+
+/// The arguments for the `reth db verify` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The checks to run, as a comma-separated list. Every check is run if not passed.
+    #[arg(long, value_enum, value_delimiter = ',')]
+    checks: Vec<Check>,
+
+    /// The maximum number of violations listed per check. The others are only counted.
+    #[arg(long, value_name = "COUNT", default_value_t = 100)]
+    max_violations: usize,
+
+    /// Prints the report as JSON instead of a table. The logs only go to stderr then.
+    #[arg(long)]
+    json: bool,
+
+    /// Also writes the JSON report to this file.
+    #[arg(long, value_name = "PATH")]
+    report: Option<PathBuf>,
+}
+
+/// An invariant between tables checked by `reth db verify`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Check {
+    /// The blocks of `BlockBodyIndices` are contiguous, and the transactions of every block follow
+    /// on from the ones of the previous block.
+    BodyIndices,
+    /// The last transaction of every block maps to the block in `TransactionBlock`.
+    TransactionBlock,
+    /// Every transaction up to the TransactionLookup checkpoint has a `TxHashNumber` entry
+    /// pointing at its number, unless pruned, and every entry points at a transaction with its
+    /// hash.
+    TxLookup,
+    /// `HashedAccount` holds the hashed `PlainAccountState`, and nothing else.
+    AccountHashing,
+    /// `HashedStorage` holds the hashed `PlainStorageState`, and nothing else.
+    StorageHashing,
+}
+
+impl Check {
+    const ALL: [Self; 5] = [
+        Self::BodyIndices,
+        Self::TransactionBlock,
+        Self::TxLookup,
+        Self::AccountHashing,
+        Self::StorageHashing,
+    ];
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.to_possible_value().expect("no skipped variants");
+        f.write_str(value.get_name())
+    }
+}
+
+/// The outcome of every check, as written by `--json` and `--report`.
+#[derive(Debug, Serialize)]
+struct VerifyReport {
+    tip: BlockNumber,
+    checks: Vec<CheckReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct CheckReport {
+    check: Check,
+    status: CheckStatus,
+    /// Why the check was skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    /// The number of rows read by the check.
+    rows: usize,
+    /// The number of violations found, of which at most `--max-violations` are listed.
+    violations: usize,
+    listed: Vec<Violation>,
+    elapsed: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum CheckStatus {
+    Pass,
+    Fail,
+    Skipped,
+}
+
+/// A row breaking the invariant of a check.
+#[derive(Debug, Serialize)]
+struct Violation {
+    key: String,
+    message: String,
+}
+
+/// The violations found by a check, and the rows it read so far.
+struct Violations {
+    check: Check,
+    max: usize,
+    total: usize,
+    listed: Vec<Violation>,
+    rows: usize,
+}
+
+impl Violations {
+    fn new(check: Check, max: usize) -> Self {
+        Self { check, max, total: 0, listed: vec![], rows: 0 }
+    }
+
+    /// Records a violation at `key`, listing it while under the limit.
+    fn push(&mut self, key: impl fmt::Debug, message: impl fmt::Display) {
+        self.total += 1;
+        if self.listed.len() < self.max {
+            self.listed.push(Violation { key: format!("{key:?}"), message: message.to_string() });
+        }
+    }
+
+    /// Counts a read row, logging the progress of the check every [`PROGRESS_INTERVAL`] rows.
+    fn on_row(&mut self) {
+        self.rows += 1;
+        if self.rows % PROGRESS_INTERVAL == 0 {
+            info!(target: "reth::cli", check = %self.check, rows = self.rows, violations = self.total, "Verifying");
+        }
+    }
+}
+
+impl Command {
+    /// Execute `db verify` command
+    ///
+    /// Every check reads the database in its own read transaction, so it sees a consistent
+    /// snapshot even while a node writes to it. A long check keeps the node from reusing the
+    /// pages freed in the meantime, so the database may grow until it's done.
+    pub fn execute<DB: Database>(self, tool: &DbTool<'_, DB>) -> eyre::Result<()> {
+        let checks = if self.checks.is_empty() { Check::ALL.to_vec() } else { self.checks.clone() };
+
+        let mut report = VerifyReport { tip: tool.tip()?, checks: vec![] };
+        for check in checks {
+            info!(target: "reth::cli", %check, "Running check");
+            let started = Instant::now();
+            let mut violations = Violations::new(check, self.max_violations);
+            let reason = match skip_reason(tool, check)? {
+                Some(reason) => Some(reason),
+                None => {
+                    tool.db.view(|tx| run_check(tx, check, &mut violations))??;
+                    None
+                }
+            };
+
+            let status = match (&reason, violations.total) {
+                (Some(_), _) => CheckStatus::Skipped,
+                (None, 0) => CheckStatus::Pass,
+                (None, _) => CheckStatus::Fail,
+            };
+            info!(target: "reth::cli", %check, ?status, rows = violations.rows, violations = violations.total, "Finished check");
+            report.checks.push(CheckReport {
+                check,
+                status,
+                reason,
+                rows: violations.rows,
+                violations: violations.total,
+                listed: violations.listed,
+                elapsed: started.elapsed(),
+            });
+        }
+
+        if let Some(path) = &self.report {
+            std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+            info!(target: "reth::cli", ?path, "Wrote verification report");
+        }
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            print_report(&report);
+        }
+
+        let failed = report.checks.iter().filter(|check| check.status == CheckStatus::Fail).count();
+        if failed > 0 {
+            eyre::bail!("{failed} of {} checks found violations.", report.checks.len())
+        }
+
+        Ok(())
+    }
+
+    /// Whether the command prints its report as JSON to stdout.
+    pub(crate) fn is_machine_output(&self) -> bool {
+        self.json
+    }
+}
+
+/// Why `check` can't run against the database, e.g. since the stages it relies on are behind.
+///
+/// The hashed state is only comparable to the plain state once the hashing stages caught up with
+/// the execution and finished their range.
+fn skip_reason<DB: Database>(tool: &DbTool<'_, DB>, check: Check) -> eyre::Result<Option<String>> {
+    let stage = match check {
+        Check::AccountHashing => StageId::AccountHashing,
+        Check::StorageHashing => StageId::StorageHashing,
+        Check::BodyIndices | Check::TransactionBlock | Check::TxLookup => return Ok(None),
+    };
+
+    let checkpoint = |stage: StageId| -> eyre::Result<BlockNumber> {
+        Ok(tool.get::<tables::SyncStage>(stage.to_string())?.unwrap_or_default().block_number)
+    };
+    let (hashed, executed) = (checkpoint(stage)?, checkpoint(StageId::Execution)?);
+    if hashed != executed {
+        return Ok(Some(format!(
+            "the {stage} checkpoint {hashed} differs from the {} checkpoint {executed}",
+            StageId::Execution
+        )))
+    }
+    if tool.is_stage_in_flight(stage)? {
+        return Ok(Some(format!("the {stage} stage is in the middle of its range")))
+    }
+
+    Ok(None)
+}
+
+fn run_check<TX: DbTx>(tx: &TX, check: Check, violations: &mut Violations) -> eyre::Result<()> {
+    match check {
+        Check::BodyIndices => check_body_indices(tx, violations),
+        Check::TransactionBlock => check_transaction_block(tx, violations),
+        Check::TxLookup => check_tx_lookup(tx, violations),
+        Check::AccountHashing => check_account_hashing(tx, violations),
+        Check::StorageHashing => check_storage_hashing(tx, violations),
+    }
+}
+
+fn check_body_indices<TX: DbTx>(tx: &TX, violations: &mut Violations) -> eyre::Result<()> {
+    let mut previous: Option<(BlockNumber, StoredBlockBodyIndices)> = None;
+    for entry in tx.cursor_read::<tables::BlockBodyIndices>()?.walk(None)? {
+        let (block, indices) = entry?;
+        if let Some((previous_block, previous)) = previous {
+            if block != previous_block + 1 {
+                violations.push(
+                    block,
+                    format!("Blocks {}..{block} have no body indices.", previous_block + 1),
+                );
+            } else if indices.first_tx_num() != previous.next_tx_num() {
+                violations.push(
+                    block,
+                    format!(
+                        "The first transaction {} doesn't follow on from the last transaction {} of block {previous_block}.",
+                        indices.first_tx_num(),
+                        previous.last_tx_num()
+                    ),
+                );
+            }
+        }
+        violations.on_row();
+        previous = Some((block, indices));
+    }
+
+    Ok(())
+}
+
+fn check_transaction_block<TX: DbTx>(tx: &TX, violations: &mut Violations) -> eyre::Result<()> {
+    for entry in tx.cursor_read::<tables::BlockBodyIndices>()?.walk(None)? {
+        let (block, indices) = entry?;
+        if indices.tx_count() > 0 {
+            let last = indices.last_tx_num();
+            let mapped = tx.get::<tables::TransactionBlock>(last)?;
+            if mapped != Some(block) {
+                violations.push(
+                    block,
+                    format!("The last transaction {last} of the block maps to block {mapped:?}."),
+                );
+            }
+        }
+        violations.on_row();
+    }
+
+    Ok(())
+}
+
+fn check_tx_lookup<TX: DbTx>(tx: &TX, violations: &mut Violations) -> eyre::Result<()> {
+    let checkpoint = tx
+        .get::<tables::SyncStage>(StageId::TransactionLookup.to_string())?
+        .unwrap_or_default()
+        .block_number;
+    let Some(indices) = tx.get::<tables::BlockBodyIndices>(checkpoint)? else { return Ok(()) };
+    // The entries of the pruned transactions are deleted.
+    let first = tx
+        .get::<tables::PruneCheckpoints>(PruneSegment::TransactionLookup)?
+        .and_then(|checkpoint| checkpoint.tx_number)
+        .map_or(0, |pruned| pruned + 1);
+    let end = indices.next_tx_num();
+
+    for entry in tx.cursor_read::<tables::Transactions>()?.walk_range(first..end)? {
+        let (tx_number, transaction) = entry?;
+        let hash = transaction.hash();
+        let mapped = tx.get::<tables::TxHashNumber>(hash)?;
+        if mapped != Some(tx_number) {
+            violations.push(tx_number, format!("Transaction {hash} maps to number {mapped:?}."));
+        }
+        violations.on_row();
+    }
+
+    let mut transactions = tx.cursor_read::<tables::Transactions>()?;
+    for entry in tx.cursor_read::<tables::TxHashNumber>()?.walk(None)? {
+        let (hash, tx_number) = entry?;
+        if tx_number >= end {
+            violations.push(
+                hash,
+                format!(
+                    "Maps to transaction {tx_number}, past the {} checkpoint {checkpoint}.",
+                    StageId::TransactionLookup
+                ),
+            );
+        } else if let Some((_, transaction)) = transactions.seek_exact(tx_number)? {
+            if transaction.hash() != hash {
+                violations.push(
+                    hash,
+                    format!(
+                        "Maps to transaction {tx_number}, whose hash is {}.",
+                        transaction.hash()
+                    ),
+                );
+            }
+        }
+        violations.on_row();
+    }
+
+    Ok(())
+}
+
+fn check_account_hashing<TX: DbTx>(tx: &TX, violations: &mut Violations) -> eyre::Result<()> {
+    let mut hashed = tx.cursor_read::<tables::HashedAccount>()?;
+    let mut accounts = 0;
+    for entry in tx.cursor_read::<tables::PlainAccountState>()?.walk(None)? {
+        let (address, account) = entry?;
+        let hashed_account = hashed.seek_exact(keccak256(address))?.map(|(_, account)| account);
+        if hashed_account != Some(account) {
+            violations.push(
+                address,
+                format!("The hashed account {hashed_account:?} differs from the plain account {account:?}."),
+            );
+        }
+        accounts += 1;
+        violations.on_row();
+    }
+
+    let hashed_accounts = tx.entries::<tables::HashedAccount>()?;
+    if hashed_accounts > accounts {
+        violations.push(
+            tables::HashedAccount::NAME,
+            format!("{} hashed accounts have no plain account.", hashed_accounts - accounts),
+        );
+    }
+
+    Ok(())
+}
+
+fn check_storage_hashing<TX: DbTx>(tx: &TX, violations: &mut Violations) -> eyre::Result<()> {
+    let mut hashed = tx.cursor_dup_read::<tables::HashedStorage>()?;
+    let mut slots = 0;
+    for entry in tx.cursor_read::<tables::PlainStorageState>()?.walk(None)? {
+        let (address, plain) = entry?;
+        let hashed_slot = keccak256(plain.key);
+        let hashed_value = hashed
+            .seek_by_key_subkey(keccak256(address), hashed_slot)?
+            .filter(|entry| entry.key == hashed_slot)
+            .map(|entry| entry.value);
+        if hashed_value != Some(plain.value) {
+            violations.push(
+                (address, plain.key),
+                format!(
+                    "The hashed slot value {hashed_value:?} differs from the plain value {}.",
+                    plain.value
+                ),
+            );
+        }
+        slots += 1;
+        violations.on_row();
+    }
+
+    let hashed_slots = tx.entries::<tables::HashedStorage>()?;
+    if hashed_slots > slots {
+        violations.push(
+            tables::HashedStorage::NAME,
+            format!("{} hashed slots have no plain slot.", hashed_slots - slots),
+        );
+    }
+
+    Ok(())
+}
+
+fn print_report(report: &VerifyReport) {
+    println!("Tip: {}", report.tip);
+    let mut table = ComfyTable::new();
+    table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+    table.set_header(["Check", "Result", "Rows", "Violations", "Elapsed"]);
+    for check in &report.checks {
+        let result = match (check.status, &check.reason) {
+            (CheckStatus::Skipped, Some(reason)) => format!("skipped: {reason}"),
+            (CheckStatus::Pass, _) => "pass".to_string(),
+            _ => "fail".to_string(),
+        };
+        let mut row = Row::new();
+        row.add_cell(Cell::new(check.check))
+            .add_cell(Cell::new(result))
+            .add_cell(Cell::new(check.rows))
+            .add_cell(Cell::new(check.violations))
+            .add_cell(Cell::new(format!("{:?}", check.elapsed)));
+        table.add_row(row);
+    }
+    println!("{table}");
+
+    for check in report.checks.iter().filter(|check| !check.listed.is_empty()) {
+        println!("Violations of {}:", check.check);
+        for violation in &check.listed {
+            println!("  {}: {}", violation.key, violation.message);
+        }
+        if check.violations > check.listed.len() {
+            println!("  ... and {} more", check.violations - check.listed.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{test_utils::create_test_rw_db, transaction::DbTxMut};
+
+    #[test]
+    fn gap_in_body_indices() {
+        let db = create_test_rw_db();
+        db.update(|tx| {
+            for (block, first_tx_num, tx_count) in [(0, 0, 2), (1, 2, 1), (2, 4, 1), (4, 5, 0)] {
+                tx.put::<tables::BlockBodyIndices>(
+                    block,
+                    StoredBlockBodyIndices { first_tx_num, tx_count },
+                )?;
+            }
+            Ok::<_, reth_db::DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+
+        let mut violations = Violations::new(Check::BodyIndices, 1);
+        db.view(|tx| check_body_indices(tx, &mut violations)).unwrap().unwrap();
+        assert_eq!(violations.rows, 4);
+        assert_eq!(violations.total, 2);
+        assert_eq!(violations.listed.len(), 1);
+        assert_eq!(violations.listed[0].key, "2");
+    }
+}
@@ -0,0 +1,71 @@
+use crate::utils::DbTool;
+use clap::Parser;
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use reth_db::database::Database;
+use reth_primitives::{stage::StageId, BlockNumber};
+use std::{collections::HashMap, time::Duration};
+use tokio::time::{Instant, MissedTickBehavior};
+
+/// The arguments for the `reth db watch` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The number of seconds between two samples of the stage checkpoints.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 10,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    interval: u64,
+}
+
+impl Command {
+    /// Execute `db watch` command
+    ///
+    /// Every sample reads the tip and the checkpoints in a new read transaction, so the progress
+    /// of a node syncing into the database is seen.
+    pub async fn execute<DB: Database>(self, tool: &DbTool<'_, DB>) -> eyre::Result<()> {
+        println!("Chain: {} (id {})", tool.chain.chain, tool.chain.chain.id());
+
+        let mut interval = tokio::time::interval(Duration::from_secs(self.interval));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut previous: Option<(Instant, HashMap<StageId, BlockNumber>)> = None;
+        loop {
+            interval.tick().await;
+            let now = Instant::now();
+            let tip = tool.tip()?;
+            let checkpoints = tool.stage_checkpoints()?;
+
+            println!("Tip: {tip}");
+            let mut table = ComfyTable::new();
+            table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+            table.set_header(["Stage", "Checkpoint", "Behind tip", "Blocks/s"]);
+            for (stage, checkpoint) in &checkpoints {
+                let blocks_per_second = previous.as_ref().and_then(|(at, blocks)| {
+                    let elapsed = now.duration_since(*at).as_secs_f64();
+                    let progress = checkpoint.block_number.saturating_sub(*blocks.get(stage)?);
+                    Some(progress as f64 / elapsed)
+                });
+
+                let mut row = Row::new();
+                row.add_cell(Cell::new(stage))
+                    .add_cell(Cell::new(checkpoint.block_number))
+                    .add_cell(Cell::new(tip.saturating_sub(checkpoint.block_number)))
+                    .add_cell(Cell::new(
+                        blocks_per_second
+                            .map_or_else(|| "-".to_string(), |rate| format!("{rate:.2}")),
+                    ));
+                table.add_row(row);
+            }
+            println!("{table}");
+
+            previous = Some((
+                now,
+                checkpoints
+                    .into_iter()
+                    .map(|(stage, checkpoint)| (stage, checkpoint.block_number))
+                    .collect(),
+            ));
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! Command for debugging block building.
 use crate::{
-    args::{utils::genesis_value_parser, DatabaseArgs},
+    args::{utils::genesis_value_parser, ChainOverrideArgs, DatabaseArgs},
     dirs::{DataDirPath, MaybePlatformPath},
     runner::CliContext,
 };
@@ -56,7 +56,8 @@ pub struct Command {
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -72,6 +73,10 @@ pub struct Command {
     )]
     chain: Arc<ChainSpec>,
 
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    chain_overrides: ChainOverrideArgs,
+
     /// Database arguments.
     #[clap(flatten)]
     db: DatabaseArgs,
@@ -138,13 +143,16 @@ impl Command {
     }
 
     /// Execute `debug in-memory-merkle` command
-    pub async fn execute(self, ctx: CliContext) -> eyre::Result<()> {
+    pub async fn execute(mut self, ctx: CliContext) -> eyre::Result<()> {
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
         // add network name to data dir
         let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
         let db_path = data_dir.db_path();
         fs::create_dir_all(&db_path)?;
 
         // initialize the database
+        self.db.ensure_writable("debug build-block")?;
         let db = Arc::new(init_db(db_path, self.db.log_level)?);
 
         let consensus: Arc<dyn Consensus> = Arc::new(BeaconConsensus::new(Arc::clone(&self.chain)));
@@ -1,6 +1,8 @@
 //! Command for debugging execution.
 use crate::{
-    args::{get_secret_key, utils::genesis_value_parser, DatabaseArgs, NetworkArgs},
+    args::{
+        get_secret_key, utils::genesis_value_parser, ChainOverrideArgs, DatabaseArgs, NetworkArgs,
+    },
     dirs::{DataDirPath, MaybePlatformPath},
     init::init_genesis,
     node::events,
@@ -56,7 +58,8 @@ pub struct Command {
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -72,6 +75,10 @@ pub struct Command {
     )]
     chain: Arc<ChainSpec>,
 
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    chain_overrides: ChainOverrideArgs,
+
     #[clap(flatten)]
     network: NetworkArgs,
 
@@ -130,9 +137,10 @@ impl Command {
                     TotalDifficultyStage::new(consensus)
                         .with_commit_threshold(stage_conf.total_difficulty.commit_threshold),
                 )
-                .set(SenderRecoveryStage {
-                    commit_threshold: stage_conf.sender_recovery.commit_threshold,
-                })
+                .set(
+                    SenderRecoveryStage::new(stage_conf.sender_recovery.commit_threshold)
+                        .with_workers(stage_conf.sender_recovery.workers),
+                )
                 .set(ExecutionStage::new(
                     factory,
                     ExecutionStageThresholds {
@@ -199,12 +207,15 @@ impl Command {
     }
 
     /// Execute `execution-debug` command
-    pub async fn execute(self, ctx: CliContext) -> eyre::Result<()> {
+    pub async fn execute(mut self, ctx: CliContext) -> eyre::Result<()> {
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
         let config = Config::default();
 
         let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
         let db_path = data_dir.db_path();
         fs::create_dir_all(&db_path)?;
+        self.db.ensure_writable("debug execution")?;
         let db = Arc::new(init_db(db_path, self.db.log_level)?);
 
         debug!(target: "reth::cli", chain=%self.chain.chain, genesis=?self.chain.genesis_hash(), "Initializing genesis");
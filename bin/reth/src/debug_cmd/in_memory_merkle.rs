@@ -1,6 +1,8 @@
 //! Command for debugging in-memory merkle trie calculation.
 use crate::{
-    args::{get_secret_key, utils::genesis_value_parser, DatabaseArgs, NetworkArgs},
+    args::{
+        get_secret_key, utils::genesis_value_parser, ChainOverrideArgs, DatabaseArgs, NetworkArgs,
+    },
     dirs::{DataDirPath, MaybePlatformPath},
     runner::CliContext,
     utils::{get_single_body, get_single_header},
@@ -44,7 +46,8 @@ pub struct Command {
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -60,6 +63,10 @@ pub struct Command {
     )]
     chain: Arc<ChainSpec>,
 
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    chain_overrides: ChainOverrideArgs,
+
     #[clap(flatten)]
     db: DatabaseArgs,
 
@@ -103,7 +110,9 @@ impl Command {
     }
 
     /// Execute `debug in-memory-merkle` command
-    pub async fn execute(self, ctx: CliContext) -> eyre::Result<()> {
+    pub async fn execute(mut self, ctx: CliContext) -> eyre::Result<()> {
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
         let config = Config::default();
 
         // add network name to data dir
@@ -112,6 +121,7 @@ impl Command {
         fs::create_dir_all(&db_path)?;
 
         // initialize the database
+        self.db.ensure_writable("debug in-memory-merkle")?;
         let db = Arc::new(init_db(db_path, self.db.log_level)?);
         let factory = ProviderFactory::new(&db, self.chain.clone());
         let provider = factory.provider()?;
@@ -1,6 +1,8 @@
 //! Command for debugging merkle trie calculation.
 use crate::{
-    args::{get_secret_key, utils::genesis_value_parser, DatabaseArgs, NetworkArgs},
+    args::{
+        get_secret_key, utils::genesis_value_parser, ChainOverrideArgs, DatabaseArgs, NetworkArgs,
+    },
     dirs::{DataDirPath, MaybePlatformPath},
     runner::CliContext,
     utils::get_single_header,
@@ -49,7 +51,8 @@ pub struct Command {
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -65,6 +68,10 @@ pub struct Command {
     )]
     chain: Arc<ChainSpec>,
 
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    chain_overrides: ChainOverrideArgs,
+
     #[clap(flatten)]
     db: DatabaseArgs,
 
@@ -112,7 +119,9 @@ impl Command {
     }
 
     /// Execute `merkle-debug` command
-    pub async fn execute(self, ctx: CliContext) -> eyre::Result<()> {
+    pub async fn execute(mut self, ctx: CliContext) -> eyre::Result<()> {
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
         let config = Config::default();
 
         // add network name to data dir
@@ -121,6 +130,7 @@ impl Command {
         fs::create_dir_all(&db_path)?;
 
         // initialize the database
+        self.db.ensure_writable("debug merkle")?;
         let db = Arc::new(init_db(db_path, self.db.log_level)?);
         let factory = ProviderFactory::new(&db, self.chain.clone());
         let provider_rw = factory.provider_rw().map_err(PipelineError::Interface)?;
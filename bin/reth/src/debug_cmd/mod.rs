@@ -7,6 +7,7 @@ mod build_block;
 mod execution;
 mod in_memory_merkle;
 mod merkle;
+mod replay_block;
 
 /// `reth debug` command
 #[derive(Debug, Parser)]
@@ -26,6 +27,8 @@ pub enum Subcommands {
     InMemoryMerkle(in_memory_merkle::Command),
     /// Debug block building.
     BuildBlock(build_block::Command),
+    /// Re-execute a block and report the state it reads and writes.
+    ReplayBlock(replay_block::Command),
 }
 
 impl Command {
@@ -36,6 +39,15 @@ impl Command {
             Subcommands::Merkle(command) => command.execute(ctx).await,
             Subcommands::InMemoryMerkle(command) => command.execute(ctx).await,
             Subcommands::BuildBlock(command) => command.execute(ctx).await,
+            Subcommands::ReplayBlock(command) => command.execute().await,
+        }
+    }
+
+    /// Whether the command prints machine readable output to stdout.
+    pub fn is_machine_output(&self) -> bool {
+        match &self.command {
+            Subcommands::ReplayBlock(command) => command.is_machine_output(),
+            _ => false,
         }
     }
 }
@@ -0,0 +1,568 @@
+//! Command for re-executing a single canonical block and inspecting the state it reads and writes.
+use crate::{
+    args::{
+        utils::{genesis_value_parser, hash_or_num_value_parser},
+        ChainOverrideArgs, DatabaseArgs,
+    },
+    dirs::{DataDirPath, MaybePlatformPath},
+};
+use clap::Parser;
+use eyre::WrapErr;
+use reth_db::{
+    cursor::DbCursorRO,
+    models::{AccountBeforeTx, BlockNumberAddress},
+    open_db_read_only, tables,
+    transaction::DbTx,
+};
+use reth_interfaces::RethResult;
+use reth_primitives::{
+    fs, keccak256, revm::compat::into_reth_acc, trie::AccountProof, Account, Address,
+    BlockHashOrNumber, BlockNumber, BlockWithSenders, Bytecode, Bytes, ChainSpec, StorageEntry,
+    StorageKey, StorageValue, TransactionVariant, B256, U256,
+};
+use reth_provider::{
+    AccountReader, BlockHashReader, BlockNumReader, BlockReader, BundleStateWithReceipts,
+    ExecutorFactory, HeaderProvider, ProviderFactory, StateProvider, StateRootProvider,
+};
+use reth_trie::{
+    hashed_cursor::{HashedPostState, HashedPostStateCursorFactory},
+    proof::Proof,
+    StateRoot,
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+use tracing::*;
+
+/// `reth debug replay-block` command
+/// Re-executes a canonical block on top of the state of its parent, and reports the changes it
+/// makes to the state, the accounts and storage slots it reads and the witness proving them.
+/// The database is only read, so this can run while a node uses it.
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
+    ///
+    /// Built-in chains:
+    /// - mainnet
+    /// - goerli
+    /// - sepolia
+    /// - holesky
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        verbatim_doc_comment,
+        default_value = "mainnet",
+        value_parser = genesis_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    chain_overrides: ChainOverrideArgs,
+
+    #[clap(flatten)]
+    db: DatabaseArgs,
+
+    /// The number or hash of the canonical block to re-execute.
+    #[arg(value_parser = hash_or_num_value_parser)]
+    block: BlockHashOrNumber,
+
+    /// Writes the report as JSON to this file instead of stdout.
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Compares the changes of the re-execution with the ones stored in the database, and
+    /// reports every account and storage slot where they differ.
+    #[arg(long)]
+    compare: bool,
+}
+
+/// The report of the re-execution of a block.
+#[derive(Debug, Serialize)]
+struct Report {
+    number: BlockNumber,
+    hash: B256,
+    /// The state root of the header of the block.
+    state_root: B256,
+    /// The state root after the changes of the re-execution.
+    replayed_state_root: B256,
+    /// The accounts changed by the block, ordered by address.
+    changes: Vec<AccountChange>,
+    /// The accounts read by the block, ordered by address.
+    touched: Vec<TouchedAccount>,
+    witness: Witness,
+    /// The differences with the changes stored in the database, with `--compare`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mismatches: Option<Vec<Mismatch>>,
+}
+
+/// The change of an account by the block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct AccountChange {
+    address: Address,
+    before: Option<Account>,
+    after: Option<Account>,
+    /// Whether the block destroyed the account, wiping its storage.
+    destroyed: bool,
+    /// The changed storage slots, ordered by key.
+    storage: Vec<SlotChange>,
+}
+
+/// The change of a storage slot by the block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct SlotChange {
+    slot: B256,
+    before: U256,
+    after: U256,
+}
+
+/// An account read by the block, along with the storage slots of it which were read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct TouchedAccount {
+    address: Address,
+    slots: Vec<B256>,
+}
+
+/// The state needed to execute the block without a database.
+#[derive(Debug, Default, Serialize)]
+struct Witness {
+    /// The nodes of the state trie of the parent proving the touched accounts and storage slots,
+    /// by their hashes.
+    state: BTreeMap<B256, Bytes>,
+    /// The bytecodes read by the block, by their hashes.
+    codes: BTreeMap<B256, Bytes>,
+}
+
+/// A difference between the re-execution and the database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Mismatch {
+    /// The account before the block differs.
+    AccountBefore { address: Address, replayed: Option<Account>, stored: Option<Account> },
+    /// The account after the block differs.
+    AccountAfter { address: Address, replayed: Option<Account>, stored: Option<Account> },
+    /// The storage slot before the block differs.
+    SlotBefore { address: Address, slot: B256, replayed: U256, stored: U256 },
+    /// The storage slot after the block differs.
+    SlotAfter { address: Address, slot: B256, replayed: U256, stored: U256 },
+}
+
+/// The values of accounts and storage slots before and after the block.
+#[derive(Debug, Default)]
+struct StateDiff {
+    accounts: BTreeMap<Address, (Option<Account>, Option<Account>)>,
+    slots: BTreeMap<(Address, B256), (U256, U256)>,
+}
+
+impl Command {
+    /// Execute `debug replay-block` command
+    pub async fn execute(mut self) -> eyre::Result<()> {
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
+        // add network name to data dir
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let db = open_db_read_only(&data_dir.db_path(), self.db.log_level)?;
+        let factory = ProviderFactory::new(&db, self.chain.clone());
+        let provider = factory.provider()?;
+        let tx = provider.tx_ref();
+
+        let number = provider
+            .convert_hash_or_number(self.block)?
+            .ok_or_else(|| eyre::eyre!("Block {:?} not found", self.block))?;
+        if number == 0 {
+            eyre::bail!("The genesis block has no parent to re-execute it on.")
+        }
+        let BlockWithSenders { block, senders } = provider
+            .block_with_senders(number, TransactionVariant::WithHash)?
+            .ok_or_else(|| eyre::eyre!("Block {number} not found"))?;
+        let td = provider
+            .header_td_by_number(number)?
+            .ok_or_else(|| eyre::eyre!("Total difficulty of block {number} not found"))?;
+
+        info!(target: "reth::cli", number, "Re-executing block");
+        let state = RecordingStateProvider::new(factory.history_by_block_number(number - 1)?);
+        let executor_factory = reth_revm::Factory::new(self.chain.clone());
+        let mut executor = executor_factory.with_state(&state);
+        executor
+            .execute_and_verify_receipt(&block, td, Some(senders))
+            .wrap_err_with(|| format!("Could not re-execute block {number}"))?;
+        let bundle = executor.take_output_state();
+        drop(executor);
+        let reads = state.into_reads();
+        let touched = reads
+            .accounts
+            .iter()
+            .map(|(address, slots)| TouchedAccount {
+                address: *address,
+                slots: slots.iter().copied().collect(),
+            })
+            .collect();
+
+        info!(target: "reth::cli", accounts = reads.accounts.len(), "Generating the witness");
+        let reverts = HashedPostState::from_reverts(tx, number)?;
+        let witness = witness(tx, &reverts, reads)?;
+        let replayed_state_root = replayed_state_root(tx, reverts, &bundle)?;
+
+        let changes = account_changes(&bundle);
+        let mismatches = if self.compare {
+            info!(target: "reth::cli", "Comparing with the changes stored in the database");
+            let parent = factory.history_by_block_number(number - 1)?;
+            let stored = factory.history_by_block_number(number)?;
+            Some(compare(tx, number, &changes, &parent, &stored)?)
+        } else {
+            None
+        };
+
+        let report = Report {
+            number,
+            hash: block.header.hash_slow(),
+            state_root: block.header.state_root,
+            replayed_state_root,
+            changes,
+            touched,
+            witness,
+            mismatches,
+        };
+
+        if report.replayed_state_root != report.state_root {
+            warn!(target: "reth::cli", expected = ?report.state_root, got = ?report.replayed_state_root, "State root mismatch");
+        }
+        if let Some(mismatches) = report.mismatches.as_ref().filter(|m| !m.is_empty()) {
+            warn!(target: "reth::cli", mismatches = mismatches.len(), "Re-execution differs from the database");
+        }
+
+        let json = serde_json::to_string_pretty(&report)?;
+        if let Some(output) = &self.output {
+            fs::write(output, json)?;
+            info!(target: "reth::cli", path = %output.display(), changes = report.changes.len(), nodes = report.witness.state.len(), "Wrote the report");
+        } else {
+            println!("{json}");
+        }
+
+        Ok(())
+    }
+
+    /// Whether the command prints its report to stdout.
+    pub(crate) fn is_machine_output(&self) -> bool {
+        self.output.is_none()
+    }
+}
+
+/// A [StateProvider] recording the accounts, storage slots and bytecodes read through it.
+struct RecordingStateProvider<SP> {
+    inner: SP,
+    reads: Mutex<Reads>,
+}
+
+/// The state read through a [RecordingStateProvider].
+#[derive(Debug, Default)]
+struct Reads {
+    /// The read accounts, along with their read storage slots.
+    accounts: BTreeMap<Address, BTreeSet<B256>>,
+    /// The read bytecodes, by their hashes.
+    codes: BTreeMap<B256, Bytes>,
+}
+
+impl<SP> RecordingStateProvider<SP> {
+    fn new(inner: SP) -> Self {
+        Self { inner, reads: Default::default() }
+    }
+
+    fn record(&self, f: impl FnOnce(&mut Reads)) {
+        f(&mut self.reads.lock().expect("not poisoned"))
+    }
+
+    fn into_reads(self) -> Reads {
+        self.reads.into_inner().expect("not poisoned")
+    }
+}
+
+impl<SP: StateProvider> BlockHashReader for RecordingStateProvider<SP> {
+    fn block_hash(&self, number: BlockNumber) -> RethResult<Option<B256>> {
+        self.inner.block_hash(number)
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> RethResult<Vec<B256>> {
+        self.inner.canonical_hashes_range(start, end)
+    }
+}
+
+impl<SP: StateProvider> AccountReader for RecordingStateProvider<SP> {
+    fn basic_account(&self, address: Address) -> RethResult<Option<Account>> {
+        self.record(|reads| {
+            reads.accounts.entry(address).or_default();
+        });
+        self.inner.basic_account(address)
+    }
+}
+
+impl<SP: StateProvider> StateRootProvider for RecordingStateProvider<SP> {
+    fn state_root(&self, post_state: &BundleStateWithReceipts) -> RethResult<B256> {
+        self.inner.state_root(post_state)
+    }
+}
+
+impl<SP: StateProvider> StateProvider for RecordingStateProvider<SP> {
+    fn storage(
+        &self,
+        account: Address,
+        storage_key: StorageKey,
+    ) -> RethResult<Option<StorageValue>> {
+        self.record(|reads| {
+            reads.accounts.entry(account).or_default().insert(storage_key);
+        });
+        self.inner.storage(account, storage_key)
+    }
+
+    fn bytecode_by_hash(&self, code_hash: B256) -> RethResult<Option<Bytecode>> {
+        let bytecode = self.inner.bytecode_by_hash(code_hash)?;
+        if let Some(bytecode) = &bytecode {
+            self.record(|reads| {
+                reads.codes.insert(code_hash, bytecode.original_bytes());
+            });
+        }
+        Ok(bytecode)
+    }
+
+    fn proof(&self, address: Address, keys: &[B256]) -> RethResult<AccountProof> {
+        self.inner.proof(address, keys)
+    }
+}
+
+/// Collects the trie nodes proving the read accounts and storage slots in the state of the
+/// parent, which `reverts` revert the latest state to.
+fn witness(tx: &impl DbTx, reverts: &HashedPostState, reads: Reads) -> eyre::Result<Witness> {
+    let proof = Proof::overlay(tx, reverts);
+    let mut state = BTreeMap::new();
+    for (address, slots) in &reads.accounts {
+        let slots = slots.iter().copied().collect::<Vec<_>>();
+        let AccountProof { proof, storage_proofs, .. } = proof.account_proof(*address, &slots)?;
+        for node in proof.into_iter().chain(storage_proofs.into_iter().flat_map(|p| p.proof)) {
+            state.insert(keccak256(&node), node);
+        }
+    }
+    Ok(Witness { state, codes: reads.codes })
+}
+
+/// Computes the state root of the changes of the block on top of the state of its parent.
+fn replayed_state_root(
+    tx: &impl DbTx,
+    mut post_state: HashedPostState,
+    bundle: &BundleStateWithReceipts,
+) -> eyre::Result<B256> {
+    post_state.extend(bundle.hash_state_slow());
+    post_state.sort();
+    let (account_prefix_set, storage_prefix_set) = post_state.construct_prefix_sets();
+    Ok(StateRoot::new(tx)
+        .with_hashed_cursor_factory(HashedPostStateCursorFactory::new(tx, &post_state))
+        .with_changed_account_prefixes(account_prefix_set)
+        .with_changed_storage_prefixes(storage_prefix_set)
+        .root()?)
+}
+
+/// Lists the accounts changed by the block, skipping the ones it only touched.
+fn account_changes(bundle: &BundleStateWithReceipts) -> Vec<AccountChange> {
+    let mut changes = bundle
+        .state()
+        .state()
+        .iter()
+        .filter_map(|(address, account)| {
+            let mut storage = account
+                .storage
+                .iter()
+                .filter(|(_, slot)| slot.previous_or_original_value != slot.present_value)
+                .map(|(key, slot)| SlotChange {
+                    slot: B256::new(key.to_be_bytes()),
+                    before: slot.previous_or_original_value,
+                    after: slot.present_value,
+                })
+                .collect::<Vec<_>>();
+            storage.sort_unstable_by_key(|change| change.slot);
+
+            let change = AccountChange {
+                address: *address,
+                before: account.original_info.clone().map(into_reth_acc),
+                after: account.info.clone().map(into_reth_acc),
+                destroyed: account.status.was_destroyed(),
+                storage,
+            };
+            (change.before != change.after || change.destroyed || !change.storage.is_empty())
+                .then_some(change)
+        })
+        .collect::<Vec<_>>();
+    changes.sort_unstable_by_key(|change| change.address);
+    changes
+}
+
+/// Compares the changes of the re-execution with the changesets of the block and the state after
+/// it stored in the database.
+fn compare(
+    tx: &impl DbTx,
+    number: BlockNumber,
+    changes: &[AccountChange],
+    parent: &impl StateProvider,
+    stored: &impl StateProvider,
+) -> eyre::Result<Vec<Mismatch>> {
+    let mut replayed = StateDiff::default();
+    for change in changes {
+        if change.before != change.after || change.destroyed {
+            replayed.accounts.insert(change.address, (change.before, change.after));
+        }
+        for slot in &change.storage {
+            replayed.slots.insert((change.address, slot.slot), (slot.before, slot.after));
+        }
+    }
+
+    // The changesets only hold the values before the block, the values after it are read from
+    // the state.
+    let mut stored_diff = StateDiff::default();
+    for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk_range(number..=number)? {
+        let (_, AccountBeforeTx { address, info }) = entry?;
+        stored_diff.accounts.insert(address, (info, stored.basic_account(address)?));
+    }
+    for entry in tx
+        .cursor_read::<tables::StorageChangeSet>()?
+        .walk_range(BlockNumberAddress::range(number..=number))?
+    {
+        let (BlockNumberAddress((_, address)), StorageEntry { key, value }) = entry?;
+        let after = stored.storage(address, key)?.unwrap_or_default();
+        stored_diff.slots.insert((address, key), (value, after));
+    }
+
+    // Fill in the accounts and slots changed on one side only with their unchanged values on the
+    // other.
+    let destroyed =
+        changes.iter().filter(|change| change.destroyed).map(|change| change.address).collect();
+    replayed.fill(&stored_diff, parent, &destroyed)?;
+    stored_diff.fill(&replayed, stored, &BTreeSet::new())?;
+
+    Ok(replayed.mismatches(&stored_diff))
+}
+
+impl StateDiff {
+    /// Adds the accounts and slots changed in `other` but not in this diff, as unchanged from
+    /// their values in `state`, or wiped for the slots of the `destroyed` accounts.
+    fn fill(
+        &mut self,
+        other: &Self,
+        state: &impl StateProvider,
+        destroyed: &BTreeSet<Address>,
+    ) -> RethResult<()> {
+        for address in other.accounts.keys() {
+            if !self.accounts.contains_key(address) {
+                let account = state.basic_account(*address)?;
+                self.accounts.insert(*address, (account, account));
+            }
+        }
+        for (address, slot) in other.slots.keys() {
+            if !self.slots.contains_key(&(*address, *slot)) {
+                let value = state.storage(*address, *slot)?.unwrap_or_default();
+                let after = if destroyed.contains(address) { U256::ZERO } else { value };
+                self.slots.insert((*address, *slot), (value, after));
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists the values of the accounts and slots of this diff which differ from the `stored` one,
+    /// which holds the same keys.
+    fn mismatches(&self, stored: &Self) -> Vec<Mismatch> {
+        let mut mismatches = Vec::new();
+        for (address, (before, after)) in &self.accounts {
+            let Some((stored_before, stored_after)) = stored.accounts.get(address) else {
+                continue
+            };
+            if before != stored_before {
+                mismatches.push(Mismatch::AccountBefore {
+                    address: *address,
+                    replayed: *before,
+                    stored: *stored_before,
+                });
+            }
+            if after != stored_after {
+                mismatches.push(Mismatch::AccountAfter {
+                    address: *address,
+                    replayed: *after,
+                    stored: *stored_after,
+                });
+            }
+        }
+        for ((address, slot), (before, after)) in &self.slots {
+            let Some((stored_before, stored_after)) = stored.slots.get(&(*address, *slot)) else {
+                continue
+            };
+            if before != stored_before {
+                mismatches.push(Mismatch::SlotBefore {
+                    address: *address,
+                    slot: *slot,
+                    replayed: *before,
+                    stored: *stored_before,
+                });
+            }
+            if after != stored_after {
+                mismatches.push(Mismatch::SlotAfter {
+                    address: *address,
+                    slot: *slot,
+                    replayed: *after,
+                    stored: *stored_after,
+                });
+            }
+        }
+        mismatches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_diff_mismatches() {
+        let (a, b) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let account = |nonce| Some(Account { nonce, ..Default::default() });
+        let slot = B256::with_last_byte(1);
+
+        let replayed = StateDiff {
+            accounts: BTreeMap::from([(a, (account(1), account(2))), (b, (None, account(1)))]),
+            slots: BTreeMap::from([((a, slot), (U256::ZERO, U256::from(1)))]),
+        };
+        let stored = StateDiff {
+            accounts: BTreeMap::from([(a, (account(1), account(2))), (b, (None, account(2)))]),
+            slots: BTreeMap::from([((a, slot), (U256::from(2), U256::from(1)))]),
+        };
+
+        assert_eq!(
+            replayed.mismatches(&stored),
+            [
+                Mismatch::AccountAfter { address: b, replayed: account(1), stored: account(2) },
+                Mismatch::SlotBefore {
+                    address: a,
+                    slot,
+                    replayed: U256::ZERO,
+                    stored: U256::from(2)
+                },
+            ]
+        );
+        assert!(replayed.mismatches(&replayed).is_empty());
+    }
+}
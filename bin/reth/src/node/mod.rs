@@ -5,12 +5,13 @@ use crate::{
     args::{
         get_secret_key,
         utils::{genesis_value_parser, parse_socket_address},
-        DatabaseArgs, DebugArgs, DevArgs, NetworkArgs, PayloadBuilderArgs, PruningArgs,
-        RpcServerArgs, TxPoolArgs,
+        ChainOverrideArgs, DatabaseArgs, DebugArgs, DevArgs, NetworkArgs, PayloadBuilderArgs,
+        PruningArgs, RpcServerArgs, TxPoolArgs,
     },
     cli::{
         components::RethNodeComponentsImpl,
         config::RethRpcConfig,
+        exex::ExExRegistry,
         ext::{RethCliExt, RethNodeCommandConfig},
     },
     dirs::{DataDirPath, MaybePlatformPath},
@@ -21,7 +22,7 @@ use crate::{
     utils::get_single_header,
     version::SHORT_VERSION,
 };
-use clap::{value_parser, Parser};
+use clap::{value_parser, Parser, Subcommand};
 use eyre::Context;
 use fdlimit::raise_fd_limit;
 use futures::{future::Either, pin_mut, stream, stream_select, StreamExt};
@@ -51,7 +52,10 @@ use reth_interfaces::{
 use reth_network::{error::NetworkError, NetworkConfig, NetworkHandle, NetworkManager};
 use reth_network_api::{NetworkInfo, PeersInfo};
 use reth_primitives::{
-    constants::eip4844::{LoadKzgSettingsError, MAINNET_KZG_TRUSTED_SETUP},
+    constants::{
+        eip4844::{LoadKzgSettingsError, MAINNET_KZG_TRUSTED_SETUP},
+        MAXIMUM_EXTRA_DATA_SIZE,
+    },
     kzg::KzgSettings,
     stage::StageId,
     BlockHashOrNumber, BlockNumber, ChainSpec, DisplayHardforks, Head, SealedHeader, B256,
@@ -89,9 +93,11 @@ use tracing::*;
 
 pub mod cl_events;
 pub mod events;
+pub mod status;
 
 /// Start the node
 #[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct NodeCommand<Ext: RethCliExt = ()> {
     /// The path to the data dir for all reth files and subdirectories.
     ///
@@ -109,7 +115,8 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -128,6 +135,10 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
     )]
     pub chain: Arc<ChainSpec>,
 
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    pub chain_overrides: ChainOverrideArgs,
+
     /// Enable Prometheus metrics.
     ///
     /// The metrics will be served at the given interface and port.
@@ -154,6 +165,13 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
     #[arg(long, value_name = "PATH")]
     pub trusted_setup_file: Option<PathBuf>,
 
+    /// The number of threads the sender recovery stage recovers the senders of transactions on.
+    ///
+    /// Overrides the `workers` of the sender recovery stage in reth.toml. Defaults to the number
+    /// of CPUs.
+    #[arg(long = "senders.workers", value_name = "WORKERS")]
+    pub senders_workers: Option<usize>,
+
     /// All networking related arguments
     #[clap(flatten)]
     pub network: NetworkArgs,
@@ -189,6 +207,18 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
     /// Additional cli arguments
     #[clap(flatten)]
     pub ext: Ext::Node,
+
+    /// Queries a running node instead of starting one
+    #[command(subcommand)]
+    pub command: Option<Subcommands>,
+}
+
+/// `reth node` subcommands
+#[derive(Subcommand, Debug)]
+pub enum Subcommands {
+    /// Print the sync progress of a running node
+    #[command(name = "status")]
+    Status(status::Command),
 }
 
 impl<Ext: RethCliExt> NodeCommand<Ext> {
@@ -198,8 +228,10 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             datadir,
             config,
             chain,
+            chain_overrides,
             metrics,
             trusted_setup_file,
+            senders_workers,
             instance,
             network,
             rpc,
@@ -209,15 +241,18 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             db,
             dev,
             pruning,
+            command,
             ..
         } = self;
         NodeCommand {
             datadir,
             config,
             chain,
+            chain_overrides,
             metrics,
             instance,
             trusted_setup_file,
+            senders_workers,
             network,
             rpc,
             txpool,
@@ -227,28 +262,53 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             dev,
             pruning,
             ext,
+            command,
         }
     }
 
+    /// Whether the command prints its output to stdout for other programs to read.
+    pub(crate) fn is_machine_output(&self) -> bool {
+        matches!(&self.command, Some(Subcommands::Status(command)) if command.is_machine_output())
+    }
+
     /// Execute `node` command
     pub async fn execute(mut self, ctx: CliContext) -> eyre::Result<()> {
+        if let Some(Subcommands::Status(command)) = self.command.take() {
+            return command.execute().await
+        }
+
         info!(target: "reth::cli", "reth {} starting", SHORT_VERSION);
 
         // Raise the fd limit of the process.
         // Does not do anything on windows.
         raise_fd_limit();
 
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
         // add network name to data dir
         let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
         let config_path = self.config.clone().unwrap_or(data_dir.config_path());
 
         let mut config: Config = self.load_config(config_path.clone())?;
+        if let Some(workers) = self.senders_workers {
+            config.stages.sender_recovery.workers = Some(workers);
+        }
+        self.rpc.gas_price_oracle.config = config.gas_price_oracle.clone();
+        if let Some(extradata) = &config.builder.extradata {
+            if extradata.len() > MAXIMUM_EXTRA_DATA_SIZE {
+                eyre::bail!(
+                    "The builder extradata of {config_path:?} exceeds {MAXIMUM_EXTRA_DATA_SIZE} bytes."
+                )
+            }
+        }
+        self.builder.config = config.builder.clone();
 
         // always store reth.toml in the data dir, not the chain specific data dir
         info!(target: "reth::cli", path = ?config_path, "Configuration loaded");
 
         let db_path = data_dir.db_path();
         info!(target: "reth::cli", path = ?db_path, "Opening database");
+        self.db.ensure_writable("node")?;
         let db = Arc::new(init_db(&db_path, self.db.log_level)?);
         info!(target: "reth::cli", "Database opened");
 
@@ -275,7 +335,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         ctx.task_executor.spawn_critical("metrics listener task", metrics_listener);
 
         let prune_config =
-            self.pruning.prune_config(Arc::clone(&self.chain))?.or(config.prune.clone());
+            self.pruning.prune_config(Arc::clone(&self.chain), config.prune.as_ref())?;
 
         // configure blockchain tree
         let tree_externals = TreeExternals::new(
@@ -363,6 +423,14 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         };
         self.ext.on_components_initialized(&components)?;
 
+        let mut exexs = ExExRegistry::default();
+        self.ext.install_exexs(&mut exexs)?;
+        if !exexs.is_empty() {
+            debug!(target: "reth::cli", "Launching ExExes");
+            let factory = ProviderFactory::new(Arc::clone(&db), Arc::clone(&self.chain));
+            exexs.launch(&components, Arc::new(factory))?;
+        }
+
         debug!(target: "reth::cli", "Spawning payload builder service");
         let payload_builder = self.ext.spawn_payload_builder_service(&self.builder, &components)?;
 
@@ -844,9 +912,10 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                     TotalDifficultyStage::new(consensus)
                         .with_commit_threshold(stage_config.total_difficulty.commit_threshold),
                 )
-                .set(SenderRecoveryStage {
-                    commit_threshold: stage_config.sender_recovery.commit_threshold,
-                })
+                .set(
+                    SenderRecoveryStage::new(stage_config.sender_recovery.commit_threshold)
+                        .with_workers(stage_config.sender_recovery.workers),
+                )
                 .set(
                     ExecutionStage::new(
                         factory,
@@ -998,6 +1067,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_node_status_command() {
+        let cmd = NodeCommand::<()>::try_parse_from(["reth", "status", "--json"]).unwrap();
+        assert!(matches!(cmd.command, Some(Subcommands::Status(_))));
+        assert!(cmd.is_machine_output());
+
+        assert!(NodeCommand::<()>::try_parse_from(["reth", "--http", "status"]).is_err());
+        assert!(NodeCommand::<()>::parse_from(["reth"]).command.is_none());
+    }
+
     #[test]
     fn parse_discovery_addr() {
         let cmd =
@@ -1005,6 +1084,15 @@ mod tests {
         assert_eq!(cmd.network.discovery.addr, Ipv4Addr::LOCALHOST);
     }
 
+    #[test]
+    fn parse_senders_workers() {
+        let cmd = NodeCommand::<()>::try_parse_from(["reth", "--senders.workers", "4"]).unwrap();
+        assert_eq!(cmd.senders_workers, Some(4));
+
+        let cmd = NodeCommand::<()>::try_parse_from(["reth"]).unwrap();
+        assert_eq!(cmd.senders_workers, None);
+    }
+
     #[test]
     fn parse_addr() {
         let cmd = NodeCommand::<()>::try_parse_from([
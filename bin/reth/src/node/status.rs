@@ -0,0 +1,140 @@
+//! `reth node status`: prints the sync progress of a running node, queried with `admin_nodeStatus`.
+use clap::Parser;
+use jsonrpsee::{
+    core::client::{ClientT, SubscriptionClientT},
+    http_client::HttpClientBuilder,
+    ws_client::WsClientBuilder,
+};
+use reth_rpc_api::clients::AdminApiClient;
+use reth_rpc_builder::constants;
+use reth_rpc_types::NodeStatus;
+
+/// Print the sync progress of a running node
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The IPC endpoint of the node to query.
+    #[arg(long, default_value_t = constants::DEFAULT_IPC_ENDPOINT.to_string())]
+    ipcpath: String,
+
+    /// The HTTP or WS URL of the node to query instead of its IPC endpoint.
+    ///
+    /// The node must serve the admin module on it, e.g. with `--http.api admin`.
+    #[arg(long, value_name = "URL", verbatim_doc_comment)]
+    rpc_url: Option<String>,
+
+    /// Prints the status as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+impl Command {
+    /// Execute `node status` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let status = match self.rpc_url.as_deref() {
+            Some(url) if url.starts_with("ws") => {
+                node_status(&WsClientBuilder::default().build(url).await?).await?
+            }
+            Some(url) => node_status(&HttpClientBuilder::default().build(url)?).await?,
+            None => self.ipc_node_status().await?,
+        };
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&status)?);
+        } else {
+            print!("{}", format_status(&status));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the command prints the status as JSON to stdout.
+    pub(crate) fn is_machine_output(&self) -> bool {
+        self.json
+    }
+
+    #[cfg(unix)]
+    async fn ipc_node_status(&self) -> eyre::Result<NodeStatus> {
+        let client = reth_ipc::client::IpcClientBuilder::default().build(&self.ipcpath).await?;
+        node_status(&client).await
+    }
+
+    #[cfg(not(unix))]
+    async fn ipc_node_status(&self) -> eyre::Result<NodeStatus> {
+        eyre::bail!("Querying {} isn't supported on this platform, pass --rpc-url.", self.ipcpath)
+    }
+}
+
+/// Queries `admin_nodeStatus` with `client`.
+async fn node_status<C>(client: &C) -> eyre::Result<NodeStatus>
+where
+    C: ClientT + SubscriptionClientT + Sync,
+{
+    Ok(AdminApiClient::node_status(client).await?)
+}
+
+/// Formats the status as a summary followed by a line per stage.
+fn format_status(status: &NodeStatus) -> String {
+    let mut out = format!(
+        "Latest block: {} ({})\nSync target:  {}\nSyncing:      {}\nPeers:        {}\n\n",
+        status.latest_block,
+        status.latest_block_hash,
+        status.sync_target.map_or_else(|| "none".to_string(), |target| target.to_string()),
+        if status.is_syncing { "yes" } else { "no" },
+        status.peers,
+    );
+
+    out.push_str(&format!("{:<20} {:>12}  Progress\n", "Stage", "Checkpoint"));
+    for stage in &status.stages {
+        let progress = match stage.processed.zip(stage.total) {
+            Some((processed, total)) if total > 0 => {
+                format!("{:.2}% ({processed}/{total})", processed as f64 / total as f64 * 100.0)
+            }
+            _ => "-".to_string(),
+        };
+        out.push_str(&format!("{:<20} {:>12}  {progress}\n", stage.name, stage.checkpoint));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_rpc_types::StageStatus;
+
+    #[test]
+    fn format_node_status() {
+        let status = NodeStatus {
+            latest_block: 10,
+            sync_target: Some(20),
+            is_syncing: true,
+            peers: 3,
+            stages: vec![
+                StageStatus {
+                    name: "Headers".to_string(),
+                    checkpoint: 20,
+                    processed: Some(20),
+                    total: Some(20),
+                },
+                StageStatus {
+                    name: "Execution".to_string(),
+                    checkpoint: 10,
+                    processed: Some(5),
+                    total: Some(20),
+                },
+                StageStatus {
+                    name: "Finish".to_string(),
+                    checkpoint: 10,
+                    processed: None,
+                    total: None,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let out = format_status(&status);
+        assert!(out.contains("Sync target:  20\n"));
+        assert!(out.contains("Syncing:      yes\n"));
+        assert!(out.contains("Execution                      10  25.00% (5/20)\n"));
+        assert!(out.contains("Finish                         10  -\n"));
+    }
+}
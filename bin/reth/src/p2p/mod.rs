@@ -3,20 +3,24 @@ use crate::{
     args::{
         get_secret_key,
         utils::{chain_spec_value_parser, hash_or_num_value_parser},
-        DatabaseArgs, DiscoveryArgs,
+        ChainOverrideArgs, DatabaseArgs, DiscoveryArgs,
     },
     dirs::{DataDirPath, MaybePlatformPath},
     utils::get_single_header,
 };
 use backon::{ConstantBuilder, Retryable};
 use clap::{Parser, Subcommand};
+use futures::StreamExt;
 use reth_config::Config;
 use reth_db::open_db;
 use reth_discv4::NatResolver;
+use reth_eth_wire::{GetReceipts, Receipts};
 use reth_interfaces::p2p::bodies::client::BodiesClient;
-use reth_primitives::{BlockHashOrNumber, ChainSpec, NodeRecord};
+use reth_network::{FetchClient, NetworkEvent, PeerRequest};
+use reth_primitives::{BlockHashOrNumber, ChainSpec, NodeRecord, B256};
 use reth_provider::ProviderFactory;
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::HashSet, path::PathBuf, sync::Arc};
+use tokio::sync::oneshot;
 
 /// `reth p2p` command
 #[derive(Debug, Parser)]
@@ -27,7 +31,8 @@ pub struct Command {
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -43,6 +48,10 @@ pub struct Command {
     )]
     chain: Arc<ChainSpec>,
 
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    chain_overrides: ChainOverrideArgs,
+
     /// The path to the data dir for all reth files and subdirectories.
     ///
     /// Defaults to the OS-specific data directory:
@@ -71,6 +80,10 @@ pub struct Command {
     #[arg(long)]
     trusted_only: bool,
 
+    /// Pin the requests to this peer, which is the only one connected to
+    #[arg(long, value_name = "ENODE", conflicts_with = "trusted_peer")]
+    peer: Option<NodeRecord>,
+
     /// The number of retries per request
     #[arg(long, default_value = "5")]
     retries: usize,
@@ -100,10 +113,18 @@ pub enum Subcommands {
         #[arg(value_parser = hash_or_num_value_parser)]
         id: BlockHashOrNumber,
     },
+    /// Download the receipts of a block
+    Receipts {
+        /// The block number or hash
+        #[arg(value_parser = hash_or_num_value_parser)]
+        id: BlockHashOrNumber,
+    },
 }
 impl Command {
     /// Execute `p2p` command
-    pub async fn execute(&self) -> eyre::Result<()> {
+    pub async fn execute(mut self) -> eyre::Result<()> {
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
         let tempdir = tempfile::TempDir::new()?;
         let noop_db = Arc::new(open_db(&tempdir.into_path(), self.db.log_level)?);
 
@@ -117,11 +138,16 @@ impl Command {
             config.peers.trusted_nodes.insert(peer);
         }
 
+        // The pinned peer is the only one connected to, so that it serves all requests.
+        if let Some(peer) = self.peer {
+            config.peers.trusted_nodes = HashSet::from([peer]);
+        }
+
         if config.peers.trusted_nodes.is_empty() && self.trusted_only {
             eyre::bail!("No trusted nodes. Set trusted peer with `--trusted-peer <enode record>` or set `--trusted-only` to `false`")
         }
 
-        config.peers.connect_trusted_nodes_only = self.trusted_only;
+        config.peers.connect_trusted_nodes_only = self.trusted_only || self.peer.is_some();
 
         let default_secret_key_path = data_dir.p2p_secret_path();
         let secret_key_path = self.p2p_secret_key.clone().unwrap_or(default_secret_key_path);
@@ -136,6 +162,7 @@ impl Command {
             .build(Arc::new(ProviderFactory::new(noop_db, self.chain.clone())))
             .start_network()
             .await?;
+        let mut events = network.event_listener();
 
         let fetch_client = network.fetch_client().await?;
         let retries = self.retries.max(1);
@@ -150,20 +177,7 @@ impl Command {
                 println!("Successfully downloaded header: {header:?}");
             }
             Subcommands::Body { id } => {
-                let hash = match id {
-                    BlockHashOrNumber::Hash(hash) => hash,
-                    BlockHashOrNumber::Number(number) => {
-                        println!("Block number provided. Downloading header first...");
-                        let client = fetch_client.clone();
-                        let header = (move || {
-                            get_single_header(client.clone(), BlockHashOrNumber::Number(number))
-                        })
-                        .retry(&backoff)
-                        .notify(|err, _| println!("Error requesting header: {err}. Retrying..."))
-                        .await?;
-                        header.hash()
-                    }
-                };
+                let hash = block_hash(fetch_client.clone(), id, &backoff).await?;
                 let (_, result) = (move || {
                     let client = fetch_client.clone();
                     client.get_block_bodies(vec![hash])
@@ -181,8 +195,94 @@ impl Command {
                 let body = result.into_iter().next().unwrap();
                 println!("Successfully downloaded body: {body:?}")
             }
+            Subcommands::Receipts { id } => {
+                let hash = block_hash(fetch_client, id, &backoff).await?;
+
+                // Receipts are requested from a peer directly, the pinned one if any.
+                let peer_id = loop {
+                    match events.next().await {
+                        Some(NetworkEvent::SessionEstablished { peer_id, .. })
+                            if self.peer.map_or(true, |peer| peer.id == peer_id) =>
+                        {
+                            break peer_id
+                        }
+                        Some(_) => {}
+                        None => eyre::bail!("Network stopped before a session was established"),
+                    }
+                };
+
+                let Receipts(result) = (move || {
+                    let network = network.clone();
+                    async move {
+                        let (response, rx) = oneshot::channel();
+                        network.send_request(
+                            peer_id,
+                            PeerRequest::GetReceipts { request: GetReceipts(vec![hash]), response },
+                        );
+                        Ok::<_, eyre::Report>(rx.await??)
+                    }
+                })
+                .retry(&backoff)
+                .notify(|err, _| println!("Error requesting receipts: {err}. Retrying..."))
+                .await?;
+                if result.len() != 1 {
+                    eyre::bail!(
+                        "Invalid number of receipt lists received. Expected: 1. Received: {}",
+                        result.len()
+                    )
+                }
+                let receipts = result.into_iter().next().unwrap();
+                println!("Successfully downloaded receipts from {peer_id}: {receipts:?}")
+            }
         }
 
         Ok(())
     }
 }
+
+/// Returns the hash of the block `id`, downloading its header first if it's a number.
+async fn block_hash(
+    client: FetchClient,
+    id: BlockHashOrNumber,
+    backoff: &ConstantBuilder,
+) -> eyre::Result<B256> {
+    Ok(match id {
+        BlockHashOrNumber::Hash(hash) => hash,
+        BlockHashOrNumber::Number(number) => {
+            println!("Block number provided. Downloading header first...");
+            let header =
+                (move || get_single_header(client.clone(), BlockHashOrNumber::Number(number)))
+                    .retry(backoff)
+                    .notify(|err, _| println!("Error requesting header: {err}. Retrying..."))
+                    .await?;
+            header.hash()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_receipts_with_peer() {
+        let peer = "enode://6f8a80d14311c39f35f516fa664deaaaa13e85b2f7493f37f6144d86991ec012937307647bd3b9a82abe2974e1407241d54947bbb39763a4cac9f77166ad92a0@10.3.58.6:30303";
+        let cmd = Command::parse_from(["reth", "--peer", peer, "receipts", "1000"]);
+        assert_eq!(cmd.peer, Some(peer.parse().unwrap()));
+        assert!(matches!(
+            cmd.command,
+            Subcommands::Receipts { id: BlockHashOrNumber::Number(1000) }
+        ));
+
+        assert!(Command::try_parse_from([
+            "reth",
+            "--peer",
+            peer,
+            "--trusted-peer",
+            peer,
+            "receipts",
+            "1000"
+        ])
+        .is_err());
+    }
+}
@@ -105,11 +105,24 @@ pub(crate) async fn initialize(
             Ok::<(), eyre::Report>(())
         }).map_err(|error| error!(?error, "Failed to read db table stats"));
 
-        if let Ok(freelist) =
-            db.freelist().map_err(|error| error!(?error, "Failed to read db.freelist"))
-        {
+        let freelist =
+            db.freelist().map_err(|error| error!(?error, "Failed to read db.freelist")).ok();
+        if let Some(freelist) = freelist {
             gauge!("db.freelist", freelist as f64);
         }
+
+        if let Ok(info) = db.info().map_err(|error| error!(?error, "Failed to read db.info")) {
+            let page_size = info.page_size().max(1);
+            gauge!("db.map_size", info.map_size() as f64);
+            gauge!("db.dirty_pages", (info.unsync_volume() / page_size) as f64);
+
+            if let Some(freelist) = freelist {
+                // Pages of the map that hold no data, including the pages on the freelist
+                let total_pages = info.map_size() / page_size;
+                let used_pages = (info.last_pgno() + 1).saturating_sub(freelist);
+                gauge!("db.free_pages", total_pages.saturating_sub(used_pages) as f64);
+            }
+        }
     };
 
     // Clone `process` to move it into the hook and use the original `process` for describe below.
@@ -127,6 +140,9 @@ pub(crate) async fn initialize(
     describe_gauge!("db.table_pages", "The number of database pages for a table");
     describe_gauge!("db.table_entries", "The number of entries for a table");
     describe_gauge!("db.freelist", "The number of pages on the freelist");
+    describe_gauge!("db.map_size", Unit::Bytes, "The size of the memory map of the database");
+    describe_gauge!("db.free_pages", "The number of pages of the memory map that hold no data");
+    describe_gauge!("db.dirty_pages", "The number of written pages not synchronized to disk yet");
     process.describe();
     describe_memory_stats();
 
@@ -1,6 +1,6 @@
 //! Database debugging tool
 use crate::{
-    args::{utils::genesis_value_parser, DatabaseArgs, StageEnum},
+    args::{utils::genesis_value_parser, ChainOverrideArgs, DatabaseArgs, StageEnum},
     dirs::{DataDirPath, MaybePlatformPath},
     init::{insert_genesis_header, insert_genesis_state},
     utils::DbTool,
@@ -26,7 +26,8 @@ pub struct Command {
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -42,6 +43,10 @@ pub struct Command {
     )]
     chain: Arc<ChainSpec>,
 
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    chain_overrides: ChainOverrideArgs,
+
     #[clap(flatten)]
     db: DatabaseArgs,
 
@@ -50,12 +55,15 @@ pub struct Command {
 
 impl Command {
     /// Execute `db` command
-    pub async fn execute(self) -> eyre::Result<()> {
+    pub async fn execute(mut self) -> eyre::Result<()> {
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
         // add network name to data dir
         let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
         let db_path = data_dir.db_path();
         fs::create_dir_all(&db_path)?;
 
+        self.db.ensure_writable("stage drop")?;
         let db = open_db(db_path.as_ref(), self.db.log_level)?;
 
         let tool = DbTool::new(&db, self.chain.clone())?;
@@ -0,0 +1,275 @@
+//! Export of the plain state at a block, as the `alloc` of a genesis file.
+use super::revert::StateReverts;
+use crate::utils::DbTool;
+use clap::Parser;
+use eyre::Result;
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    tables,
+    transaction::DbTx,
+};
+use reth_primitives::{
+    stage::StageId, Address, BlockNumber, GenesisAccount, B256, KECCAK_EMPTY, U256,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+use tracing::{info, warn};
+
+/// Accounts of the state at a block, written as the `alloc` map of a genesis file, e.g. to start
+/// a devnet with mainnet-like state.
+///
+/// The whole state is huge, so the accounts have to be bounded with `--address` or
+/// `--max-accounts`.
+#[derive(Debug, Clone, Parser)]
+pub struct ExportAllocCommand {
+    /// The block whose post-state is exported.
+    ///
+    /// The plain state is reverted to it with the changesets of the later blocks, if the
+    /// execution stage is past it.
+    #[arg(long, value_name = "BLOCK", verbatim_doc_comment)]
+    block: BlockNumber,
+    /// The path the allocation JSON is written to.
+    #[arg(long, value_name = "PATH")]
+    out: PathBuf,
+    /// An account to export. Can be passed multiple times.
+    #[arg(long = "address", value_name = "ADDRESS")]
+    addresses: Vec<Address>,
+    /// The maximum number of accounts exported, in address order.
+    #[arg(long, value_name = "N")]
+    max_accounts: Option<usize>,
+}
+
+pub(crate) async fn export_alloc<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &ExportAllocCommand,
+) -> Result<()> {
+    if command.addresses.is_empty() && command.max_accounts.is_none() {
+        eyre::bail!("Exporting the whole state isn't supported. Pass --address or --max-accounts.")
+    }
+
+    let block = command.block;
+    let state_block = db_tool
+        .get::<tables::SyncStage>(StageId::Execution.to_string())?
+        .unwrap_or_default()
+        .block_number;
+    if block > state_block {
+        eyre::bail!("Block {block} is past the plain state, which is at block {state_block}.")
+    }
+
+    let tx = db_tool.db.tx()?;
+    let reverts = if block < state_block {
+        info!(target: "reth::cli", from = state_block, to = block, "Reverting plain state");
+        StateReverts::after(&tx, block)?
+    } else {
+        StateReverts::default()
+    };
+    let alloc = read_alloc(&tx, &reverts, &command.addresses, command.max_accounts)?;
+
+    std::fs::write(&command.out, serde_json::to_string_pretty(&alloc)?)?;
+    info!(target: "reth::cli", block, accounts = alloc.len(), path = ?command.out, "Exported allocation");
+
+    Ok(())
+}
+
+/// Reads `addresses`, or every account if empty, up to `max_accounts` of them, with `reverts`
+/// applied to the plain state of `tx`.
+fn read_alloc<TX: DbTx>(
+    tx: &TX,
+    reverts: &StateReverts,
+    addresses: &[Address],
+    max_accounts: Option<usize>,
+) -> Result<BTreeMap<Address, GenesisAccount>> {
+    let max_accounts = max_accounts.unwrap_or(usize::MAX);
+    let accounts = if addresses.is_empty() {
+        let mut accounts = BTreeMap::new();
+        // The first address not read yet, if the walk stopped before the end of the table.
+        let mut end = None;
+        for entry in tx.cursor_read::<tables::PlainAccountState>()?.walk(None)? {
+            let (address, account) = entry?;
+            if accounts.len() == max_accounts {
+                end = Some(address);
+                break
+            }
+            if let Some(account) = reverts.account(address, Some(account)) {
+                accounts.insert(address, account);
+            }
+        }
+        // The accounts the reverts restore are missing from the table.
+        for (address, account) in &reverts.accounts {
+            if end.map_or(false, |end| *address >= end) {
+                break
+            }
+            if let Some(account) = account {
+                accounts.insert(*address, *account);
+            }
+        }
+        accounts.into_iter().take(max_accounts).collect()
+    } else {
+        let mut accounts = vec![];
+        for address in addresses.iter().take(max_accounts) {
+            let current = tx.get::<tables::PlainAccountState>(*address)?;
+            match reverts.account(*address, current) {
+                Some(account) => accounts.push((*address, account)),
+                None => warn!(target: "reth::cli", %address, "Account does not exist, skipping it"),
+            }
+        }
+        accounts
+    };
+
+    let mut storage_cursor = tx.cursor_dup_read::<tables::PlainStorageState>()?;
+    let mut alloc = BTreeMap::new();
+    for (address, account) in accounts {
+        let code = match account.bytecode_hash {
+            Some(hash) if hash != KECCAK_EMPTY => Some(
+                tx.get::<tables::Bytecodes>(hash)?
+                    .ok_or_else(|| {
+                        eyre::eyre!("Bytecode {hash} of account {address} does not exist.")
+                    })?
+                    .original_bytes(),
+            ),
+            _ => None,
+        };
+        let mut storage = storage_cursor
+            .walk_dup(Some(address), None)?
+            .map(|entry| entry.map(|(_, entry)| (entry.key, entry.value)))
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+        for (key, value) in reverts.storage.get(&address).into_iter().flatten() {
+            storage.insert(*key, *value);
+        }
+        let storage = storage
+            .into_iter()
+            .filter(|(_, value)| *value != U256::ZERO)
+            .map(|(key, value)| (key, B256::new(value.to_be_bytes())))
+            .collect::<HashMap<_, _>>();
+
+        alloc.insert(
+            address,
+            GenesisAccount {
+                nonce: Some(account.nonce),
+                balance: account.balance,
+                code,
+                storage: (!storage.is_empty()).then_some(storage),
+            },
+        );
+    }
+
+    Ok(alloc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{test_utils::create_test_rw_db, DatabaseError};
+    use reth_primitives::{keccak256, Account, Bytecode, Bytes, StorageEntry, U256};
+
+    #[test]
+    fn read_contract_and_storage() {
+        let db = create_test_rw_db();
+        let code = Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xf3]);
+        let (eoa, contract) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        db.update(|tx| {
+            tx.put::<tables::PlainAccountState>(
+                eoa,
+                Account { nonce: 1, balance: U256::from(10), bytecode_hash: None },
+            )?;
+            tx.put::<tables::PlainAccountState>(
+                contract,
+                Account { nonce: 0, balance: U256::ZERO, bytecode_hash: Some(keccak256(&code)) },
+            )?;
+            tx.put::<tables::Bytecodes>(keccak256(&code), Bytecode::new_raw(code.clone()))?;
+            tx.put::<tables::PlainStorageState>(
+                contract,
+                StorageEntry { key: B256::with_last_byte(1), value: U256::from(7) },
+            )?;
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+
+        let tx = db.tx().unwrap();
+        let alloc = read_alloc(&tx, &StateReverts::default(), &[], None).unwrap();
+        assert_eq!(alloc.len(), 2);
+        assert_eq!(alloc[&eoa].balance, U256::from(10));
+        assert_eq!(alloc[&eoa].code, None);
+        assert_eq!(alloc[&contract].code, Some(code));
+        assert_eq!(
+            alloc[&contract].storage,
+            Some(HashMap::from([(B256::with_last_byte(1), B256::with_last_byte(7))]))
+        );
+
+        assert_eq!(
+            read_alloc(&tx, &StateReverts::default(), &[], Some(1))
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>(),
+            [&eoa]
+        );
+        assert_eq!(
+            read_alloc(
+                &tx,
+                &StateReverts::default(),
+                &[contract, Address::with_last_byte(3)],
+                None
+            )
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>(),
+            [&contract]
+        );
+    }
+
+    #[test]
+    fn read_reverted_accounts() {
+        let db = create_test_rw_db();
+        let (first, second, third) =
+            (Address::with_last_byte(1), Address::with_last_byte(2), Address::with_last_byte(3));
+        db.update(|tx| {
+            tx.put::<tables::PlainAccountState>(first, Account { nonce: 2, ..Default::default() })?;
+            tx.put::<tables::PlainAccountState>(third, Account::default())?;
+            tx.put::<tables::PlainStorageState>(
+                first,
+                StorageEntry { key: B256::with_last_byte(1), value: U256::from(7) },
+            )?;
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+
+        // `first` changed, `second` was destroyed and `third` created after the block.
+        let reverts = StateReverts {
+            accounts: BTreeMap::from([
+                (first, Some(Account { nonce: 1, ..Default::default() })),
+                (second, Some(Account::default())),
+                (third, None),
+            ]),
+            storage: BTreeMap::from([(
+                first,
+                BTreeMap::from([
+                    (B256::with_last_byte(1), U256::ZERO),
+                    (B256::with_last_byte(2), U256::from(3)),
+                ]),
+            )]),
+        };
+
+        let tx = db.tx().unwrap();
+        let alloc = read_alloc(&tx, &reverts, &[], None).unwrap();
+        assert_eq!(alloc.keys().collect::<Vec<_>>(), [&first, &second]);
+        assert_eq!(alloc[&first].nonce, Some(1));
+        assert_eq!(
+            alloc[&first].storage,
+            Some(HashMap::from([(B256::with_last_byte(2), B256::with_last_byte(3))]))
+        );
+
+        assert_eq!(
+            read_alloc(&tx, &reverts, &[], Some(1)).unwrap().keys().collect::<Vec<_>>(),
+            [&first]
+        );
+        assert_eq!(
+            read_alloc(&tx, &reverts, &[third, second], None).unwrap().keys().collect::<Vec<_>>(),
+            [&second]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! The `tar-zstd` format of a dump: the table files of the `files` format, packed with the chain
+//! specification and the schema stamp of the extract into a single zstd compressed tar archive.
+//!
+//! The archive only holds regular files at its root, so it's read and written by the minimal
+//! ustar implementation below. Files too large for the octal size field use the base-256 encoding
+//! of GNU tar, which every common `tar` understands, e.g. `tar --zstd -xf tables.tar.zst`.
+use super::{
+    files, manifest::EXTRACT_MANIFEST_FILE, write_chain_spec, ExtractManifest, ScratchDirs,
+};
+use crate::args::utils::EMBEDDED_CHAIN_SPEC_FILE;
+use reth_db::{database::Database, init_db};
+use reth_primitives::ChainSpec;
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+use tracing::info;
+
+/// Name of the archive in the output folder of a dump.
+pub(crate) const ARCHIVE_FILE: &str = "tables.tar.zst";
+
+/// The zstd level of the archive. Higher levels barely shrink the raw table bytes further, but
+/// are several times slower.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Size of a tar header and of the blocks the contents are padded to.
+const BLOCK_SIZE: usize = 512;
+
+/// Writes the tables of `db` to the archive at `path`, along with the chain specification and
+/// the schema stamp of the extract.
+///
+/// The table files are first exported to a scratch directory, since a tar entry states its size
+/// before its contents.
+pub(crate) fn write_archive<DB: Database>(
+    db: &DB,
+    chain: &ChainSpec,
+    path: &Path,
+    scratch: &ScratchDirs,
+) -> eyre::Result<()> {
+    let dir = scratch.create("archive")?;
+    files::export_tables(db, dir.path())?;
+    write_chain_spec(dir.path(), chain)?;
+    ExtractManifest::write(dir.path())?;
+
+    let mut entries = std::fs::read_dir(dir.path())?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+
+    let mut encoder = zstd::Encoder::new(BufWriter::new(File::create(path)?), COMPRESSION_LEVEL)?;
+    for entry in &entries {
+        let name = entry.file_name().and_then(|name| name.to_str()).expect("named by the dump");
+        let size = entry.metadata()?.len();
+        encoder.write_all(&header(name, size)?)?;
+        io::copy(&mut File::open(entry)?, &mut encoder)?;
+        encoder.write_all(&[0; BLOCK_SIZE][..padding(size)])?;
+    }
+    // The end of the archive is marked by two empty blocks.
+    encoder.write_all(&[0; 2 * BLOCK_SIZE])?;
+    encoder.finish()?.flush()?;
+
+    info!(target: "reth::cli", ?path, files = entries.len(), size = std::fs::metadata(path)?.len(), "Wrote archive");
+
+    Ok(())
+}
+
+/// Unpacks the archive at `path` into the directory `dir`.
+pub(crate) fn unpack_archive(path: &Path, dir: &Path) -> eyre::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let mut decoder = zstd::Decoder::new(BufReader::new(File::open(path)?))?;
+
+    let mut block = [0; BLOCK_SIZE];
+    loop {
+        decoder.read_exact(&mut block)?;
+        if block.iter().all(|byte| *byte == 0) {
+            break
+        }
+        let (name, size) = parse_header(&block)?;
+
+        let mut file = BufWriter::new(File::create(dir.join(&name))?);
+        let copied = io::copy(&mut (&mut decoder).take(size), &mut file)?;
+        if copied != size {
+            eyre::bail!("Archive {path:?} is truncated in the middle of {name}.")
+        }
+        file.flush()?;
+        io::copy(&mut (&mut decoder).take(padding(size) as u64), &mut io::sink())?;
+    }
+
+    Ok(())
+}
+
+/// Unpacks the archive of a dump at `path` into a new database at `output_db`, with the chain
+/// specification and the schema stamp next to it, as if it was dumped in the `mdbx` format.
+pub(crate) fn load_archive(
+    path: &Path,
+    output_db: &Path,
+    scratch: &ScratchDirs,
+) -> eyre::Result<()> {
+    if output_db.read_dir().is_ok_and(|mut entries| entries.next().is_some()) {
+        eyre::bail!("The output database {output_db:?} already exists and isn't empty.")
+    }
+
+    let dir = scratch.create("load")?;
+    unpack_archive(path, dir.path())?;
+    ExtractManifest::check(dir.path())?;
+
+    let db = init_db(output_db, None)?;
+    files::import_tables(dir.path(), &db)?;
+    for file in [EMBEDDED_CHAIN_SPEC_FILE, EXTRACT_MANIFEST_FILE] {
+        std::fs::copy(dir.path().join(file), output_db.join(file))?;
+    }
+    info!(target: "reth::cli", archive = ?path, path = ?output_db, "Loaded archive");
+
+    Ok(())
+}
+
+/// The ustar header of the regular file `name` holding `size` bytes.
+fn header(name: &str, size: u64) -> eyre::Result<[u8; BLOCK_SIZE]> {
+    if name.len() > 100 {
+        eyre::bail!("File name {name} is too long for the archive.")
+    }
+
+    let mut header = [0; BLOCK_SIZE];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[100..108].copy_from_slice(b"0000644\0");
+    header[108..116].copy_from_slice(b"0000000\0");
+    header[116..124].copy_from_slice(b"0000000\0");
+    if size < 8u64.pow(11) {
+        header[124..136].copy_from_slice(format!("{size:011o}\0").as_bytes());
+    } else {
+        header[124] = 0x80;
+        header[128..136].copy_from_slice(&size.to_be_bytes());
+    }
+    header[136..148].copy_from_slice(b"00000000000\0");
+    header[156] = b'0';
+    header[257..265].copy_from_slice(b"ustar\x0000");
+
+    header[148..156].copy_from_slice(b"        ");
+    let checksum = header.iter().map(|byte| *byte as u32).sum::<u32>();
+    header[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+
+    Ok(header)
+}
+
+/// Returns the name and size of the regular file described by `header`.
+fn parse_header(header: &[u8; BLOCK_SIZE]) -> eyre::Result<(String, u64)> {
+    let mut blank = *header;
+    blank[148..156].copy_from_slice(b"        ");
+    let checksum = blank.iter().map(|byte| *byte as u32).sum::<u32>();
+    if octal(&header[148..156])? != checksum as u64 {
+        eyre::bail!("Archive header has an invalid checksum.")
+    }
+
+    let name = std::str::from_utf8(&header[..100])?.trim_end_matches('\0').to_string();
+    if !matches!(header[156], b'0' | b'\0') {
+        eyre::bail!("Archive entry {name} isn't a regular file.")
+    }
+    if name.is_empty() || name.contains('/') || name.contains('\\') || name.starts_with('.') {
+        eyre::bail!("Archive entry {name:?} isn't a file of a dump.")
+    }
+
+    let size = if header[124] & 0x80 != 0 {
+        u64::from_be_bytes(header[128..136].try_into()?)
+    } else {
+        octal(&header[124..136])?
+    };
+
+    Ok((name, size))
+}
+
+/// Parses a NUL or space terminated octal field.
+fn octal(field: &[u8]) -> eyre::Result<u64> {
+    let digits = std::str::from_utf8(field)?.trim_matches(|c| c == '\0' || c == ' ');
+    Ok(u64::from_str_radix(digits, 8)?)
+}
+
+/// The number of zero bytes padding contents of `size` bytes to a whole block.
+fn padding(size: u64) -> usize {
+    (BLOCK_SIZE - (size % BLOCK_SIZE as u64) as usize) % BLOCK_SIZE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        tables,
+        test_utils::create_test_rw_db,
+        transaction::{DbTx, DbTxMut},
+        DatabaseEnv,
+    };
+    use reth_primitives::{B256, MAINNET};
+
+    #[test]
+    fn header_roundtrip() {
+        for size in [0, 1, 511, 512, 8u64.pow(11) - 1, 8u64.pow(11), 64 << 30] {
+            assert_eq!(
+                parse_header(&header("Headers.bin", size).unwrap()).unwrap(),
+                ("Headers.bin".to_string(), size)
+            );
+        }
+        assert!(parse_header(&header("../Headers.bin", 1).unwrap()).is_err());
+
+        let mut corrupted = header("Headers.bin", 1).unwrap();
+        corrupted[0] = b'X';
+        assert!(parse_header(&corrupted).is_err());
+    }
+
+    #[test]
+    fn load_written_archive() {
+        let source = create_test_rw_db();
+        source
+            .update(|tx| {
+                for number in 0..600u64 {
+                    tx.put::<tables::CanonicalHeaders>(number, B256::with_last_byte(number as u8))?;
+                }
+                Ok::<_, reth_db::DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+
+        let root = tempfile::tempdir().unwrap();
+        let scratch = ScratchDirs::new(Some(root.path().join("scratch")));
+        let archive = root.path().join(ARCHIVE_FILE);
+        write_archive(&*source, &MAINNET, &archive, &scratch).unwrap();
+
+        let output = root.path().join("loaded");
+        load_archive(&archive, &output, &scratch).unwrap();
+        assert!(output.join(EMBEDDED_CHAIN_SPEC_FILE).exists());
+        ExtractManifest::check(&output).unwrap();
+
+        let loaded = reth_db::open_db_read_only(&output, None).unwrap();
+        let rows = |db: &DatabaseEnv| db.view(|tx| tx.entries::<tables::CanonicalHeaders>());
+        assert_eq!(rows(&loaded).unwrap().unwrap(), 600);
+        assert_eq!(std::fs::read_dir(scratch.root()).unwrap().count(), 0);
+
+        assert!(load_archive(&archive, &output, &scratch).is_err());
+    }
+}
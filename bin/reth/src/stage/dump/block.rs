@@ -0,0 +1,416 @@
+//! Dump of a single block, with only the state its execution reads.
+use super::{
+    clear_output_db, execution::validate_receipts, log_imported_rows, write_chain_spec,
+    DumpedStage, ExtractManifest, Mismatches,
+};
+use crate::utils::DbTool;
+use clap::Parser;
+use eyre::Result;
+use reth_db::{
+    cursor::DbDupCursorRO,
+    database::Database,
+    init_db,
+    table::Table,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    DatabaseEnv,
+};
+use reth_interfaces::RethResult;
+use reth_primitives::{
+    stage::{StageCheckpoint, StageId},
+    trie::AccountProof,
+    Account, Address, BlockNumber, Bytecode, StorageEntry, B256, KECCAK_EMPTY, U256,
+};
+use reth_provider::{
+    AccountReader, BlockExecutor, BlockHashReader, BlockReader, BundleStateWithReceipts,
+    ExecutorFactory, HeaderProvider, ProviderFactory, StateProvider, StateRootProvider,
+    TransactionVariant,
+};
+use reth_revm::Factory;
+use reth_stages::{stages::ExecutionStage, ExecInput, Stage};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    sync::Mutex,
+};
+use tracing::info;
+
+/// Dumps everything needed to execute a single block: its header and body, and only the accounts,
+/// storage slots, bytecodes and block hashes its execution reads.
+///
+/// The smallest possible extract to reproduce a bad block.
+#[derive(Debug, Clone, Parser)]
+pub struct BlockCommand {
+    /// The block to dump.
+    #[arg(long, value_name = "BLOCK")]
+    number: BlockNumber,
+    /// The path to the new database folder. A relative path is resolved against `--output-base`.
+    #[arg(long, value_name = "OUTPUT_PATH", verbatim_doc_comment)]
+    pub(crate) output_db: PathBuf,
+    /// Executes the block in the new database, and compares its receipts and the state it read
+    /// and wrote against the source database.
+    ///
+    /// The extract doesn't hold the whole state, so the state root can't be computed. The state
+    /// the block touched is compared instead, which is what the root commits to.
+    #[arg(long, verbatim_doc_comment)]
+    dry_run: bool,
+    /// The maximum number of mismatched receipts and state entries logged by `--dry-run`.
+    ///
+    /// All of them are still counted.
+    #[arg(long, value_name = "MAX_MISMATCHES", default_value_t = 10, verbatim_doc_comment)]
+    max_mismatches: usize,
+    /// If passed, a non-empty `--output-db` holding an earlier dump is removed before dumping.
+    #[arg(long)]
+    force: bool,
+}
+
+/// The stage recorded in the manifest of a block dump, which `--append` never extends.
+const BLOCK_DUMP: &str = "Block";
+
+pub(crate) async fn dump_block<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &BlockCommand,
+) -> Result<()> {
+    let number = command.number;
+    if number == 0 {
+        eyre::bail!("The genesis block isn't executed, so it can't be dumped on its own.")
+    }
+    clear_output_db(&command.output_db, command.force)?;
+
+    let factory = ProviderFactory::new(db_tool.db, db_tool.chain.clone());
+    let provider = factory.provider()?;
+    let block = provider
+        .block_with_senders(number, TransactionVariant::NoHash)?
+        .ok_or_else(|| eyre::eyre!("Block {number} does not exist."))?;
+    let total_difficulty = provider
+        .header_td_by_number(number)?
+        .ok_or_else(|| eyre::eyre!("Total difficulty of block {number} does not exist."))?;
+
+    // The stored block may be the bad one, so its receipts are only checked by the dry-run.
+    info!(target: "reth::cli", block = number, "Executing block to record the state it reads");
+    let state = RecordingStateProvider::new(factory.history_by_block_number(number - 1)?);
+    Factory::new(db_tool.chain.clone()).with_state(&state).execute(
+        &block.block,
+        total_difficulty,
+        Some(block.senders),
+    )?;
+    let read = state.into_read()?;
+    info!(
+        target: "reth::cli",
+        block = number,
+        accounts = read.accounts.len(),
+        slots = read.storage.len(),
+        bytecodes = read.bytecodes.len(),
+        block_hashes = read.block_hashes.len(),
+        "Recorded state read by the block"
+    );
+
+    info!(target: "reth::cli", output_path = ?command.output_db, "Creating separate db");
+    let output_db = init_db(&command.output_db, None)?;
+    write_chain_spec(&command.output_db, &db_tool.chain)?;
+    ExtractManifest::write_stage(
+        &command.output_db,
+        DumpedStage::new(&db_tool.chain, StageId::Other(BLOCK_DUMP), number - 1, number),
+    )?;
+    output_db
+        .update(|tx| db_tool.db.view(|source| write_block(source, tx, number, &read)))???;
+    log_imported_rows(&output_db, StageId::Execution)?;
+
+    if command.dry_run {
+        dry_run(db_tool, &output_db, number, &read, command.max_mismatches).await?;
+    }
+
+    Ok(())
+}
+
+/// Copies the rows block `number` is executed from to `tx`, and writes the state of the block
+/// before it that was read.
+fn write_block<STX: DbTx, TX: DbTxMut>(
+    source: &STX,
+    tx: &TX,
+    number: BlockNumber,
+    read: &ReadState,
+) -> Result<()> {
+    let parent = number - 1;
+    copy_rows::<tables::Headers, _, _>(source, tx, [parent, number])?;
+    copy_rows::<tables::HeaderTD, _, _>(source, tx, [parent, number])?;
+    copy_rows::<tables::CanonicalHeaders, _, _>(source, tx, [parent, number])?;
+    copy_rows::<tables::BlockBodyIndices, _, _>(source, tx, [number])?;
+    copy_rows::<tables::BlockOmmers, _, _>(source, tx, [number])?;
+    copy_rows::<tables::BlockWithdrawals, _, _>(source, tx, [number])?;
+
+    let transactions = source
+        .get::<tables::BlockBodyIndices>(number)?
+        .ok_or_else(|| eyre::eyre!("Block body indices {number} do not exist."))?
+        .tx_num_range();
+    copy_rows::<tables::Transactions, _, _>(source, tx, transactions.clone())?;
+    copy_rows::<tables::TxSenders, _, _>(source, tx, transactions)?;
+
+    for (block, hash) in &read.block_hashes {
+        tx.put::<tables::CanonicalHeaders>(*block, *hash)?;
+    }
+    for (address, account) in &read.accounts {
+        if let Some(account) = account {
+            tx.put::<tables::PlainAccountState>(*address, *account)?;
+        }
+    }
+    for ((address, key), value) in &read.storage {
+        if *value != U256::ZERO {
+            tx.put::<tables::PlainStorageState>(
+                *address,
+                StorageEntry { key: *key, value: *value },
+            )?;
+        }
+    }
+    for (hash, bytecode) in &read.bytecodes {
+        tx.put::<tables::Bytecodes>(*hash, bytecode.clone())?;
+    }
+
+    Ok(())
+}
+
+/// Copies the rows of `keys` that `source` has to `tx`.
+fn copy_rows<T: Table, STX: DbTx, TX: DbTxMut>(
+    source: &STX,
+    tx: &TX,
+    keys: impl IntoIterator<Item = T::Key>,
+) -> Result<()> {
+    for key in keys {
+        if let Some(value) = source.get::<T>(key.clone())? {
+            tx.put::<T>(key, value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Executes block `number` in the output database without committing, then compares the
+/// receipts and the state it read against the source database at `number`.
+async fn dry_run<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    output_db: &DatabaseEnv,
+    number: BlockNumber,
+    read: &ReadState,
+    max_mismatches: usize,
+) -> Result<()> {
+    info!(target: "reth::cli", stage = %StageId::Execution, block = number, "Executing block. [dry-run]");
+    let factory = ProviderFactory::new(output_db, db_tool.chain.clone());
+    let provider = factory.provider_rw()?;
+    ExecutionStage::new_with_factory(Factory::new(db_tool.chain.clone()))
+        .execute(
+            &provider,
+            ExecInput { target: Some(number), checkpoint: Some(StageCheckpoint::new(number - 1)) },
+        )
+        .await?;
+
+    db_tool.db.view(|source| {
+        validate_receipts(provider.tx_ref(), source, number..=number, max_mismatches)
+    })??;
+
+    let source =
+        ProviderFactory::new(db_tool.db, db_tool.chain.clone()).history_by_block_number(number)?;
+    compare_post_state(provider.tx_ref(), &source, number, read, max_mismatches)?;
+
+    info!(target: "reth::cli", stage = %StageId::Execution, block = number, "Success.");
+
+    Ok(())
+}
+
+/// Compares the accounts and storage slots read by block `number`, as left by its execution in
+/// `tx`, against `source` at the same block.
+fn compare_post_state<TX: DbTx>(
+    tx: &TX,
+    source: &impl StateProvider,
+    number: BlockNumber,
+    read: &ReadState,
+    max_mismatches: usize,
+) -> Result<()> {
+    let mut mismatches = Mismatches::new(StageId::Execution, max_mismatches);
+    let mut mismatched = 0;
+    for address in read.accounts.keys() {
+        let produced = tx.get::<tables::PlainAccountState>(*address)?;
+        let expected = source.basic_account(*address)?;
+        if produced != expected {
+            mismatches.insert(
+                (*address, None),
+                format!("Account {address} is {produced:?} after the block, but {expected:?} in the source database."),
+            );
+            mismatched += 1;
+        }
+    }
+
+    let mut storage_cursor = tx.cursor_dup_read::<tables::PlainStorageState>()?;
+    for (address, key) in read.storage.keys() {
+        let produced = storage_cursor
+            .seek_by_key_subkey(*address, *key)?
+            .filter(|entry| entry.key == *key)
+            .map_or(U256::ZERO, |entry| entry.value);
+        let expected = source.storage(*address, *key)?.unwrap_or_default();
+        if produced != expected {
+            mismatches.insert(
+                (*address, Some(*key)),
+                format!("Storage slot {key} of account {address} is {produced} after the block, but {expected} in the source database."),
+            );
+            mismatched += 1;
+        }
+    }
+
+    let blocks =
+        if mismatched > 0 { BTreeMap::from([(number, mismatched)]) } else { BTreeMap::new() };
+    mismatches.finish(blocks)?;
+
+    info!(target: "reth::cli", stage = %StageId::Execution, accounts = read.accounts.len(), slots = read.storage.len(), "Validated post-state.");
+
+    Ok(())
+}
+
+/// The state read through a [`RecordingStateProvider`], as it was before the executed block.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ReadState {
+    /// Every account read, `None` if it didn't exist.
+    accounts: BTreeMap<Address, Option<Account>>,
+    /// Every storage slot read, zero if it wasn't set.
+    storage: BTreeMap<(Address, B256), U256>,
+    bytecodes: BTreeMap<B256, Bytecode>,
+    block_hashes: BTreeMap<BlockNumber, B256>,
+}
+
+/// A [`StateProvider`] recording everything read from it.
+struct RecordingStateProvider<SP> {
+    inner: SP,
+    read: Mutex<ReadState>,
+}
+
+impl<SP: StateProvider> RecordingStateProvider<SP> {
+    fn new(inner: SP) -> Self {
+        Self { inner, read: Mutex::default() }
+    }
+
+    fn read(&self) -> std::sync::MutexGuard<'_, ReadState> {
+        self.read.lock().expect("not poisoned")
+    }
+
+    /// Returns the recorded state, along with the bytecodes of the read accounts that weren't
+    /// executed, so that every account of the extract has its bytecode.
+    fn into_read(self) -> RethResult<ReadState> {
+        let mut read = self.read.into_inner().expect("not poisoned");
+        let code_hashes = read
+            .accounts
+            .values()
+            .flatten()
+            .filter_map(|account| account.bytecode_hash)
+            .filter(|hash| *hash != KECCAK_EMPTY && !read.bytecodes.contains_key(hash))
+            .collect::<BTreeSet<_>>();
+        for hash in code_hashes {
+            if let Some(bytecode) = self.inner.bytecode_by_hash(hash)? {
+                read.bytecodes.insert(hash, bytecode);
+            }
+        }
+        Ok(read)
+    }
+}
+
+impl<SP: StateProvider> BlockHashReader for RecordingStateProvider<SP> {
+    fn block_hash(&self, number: BlockNumber) -> RethResult<Option<B256>> {
+        let hash = self.inner.block_hash(number)?;
+        if let Some(hash) = hash {
+            self.read().block_hashes.insert(number, hash);
+        }
+        Ok(hash)
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> RethResult<Vec<B256>> {
+        let hashes = self.inner.canonical_hashes_range(start, end)?;
+        self.read().block_hashes.extend((start..).zip(hashes.iter().copied()));
+        Ok(hashes)
+    }
+}
+
+impl<SP: StateProvider> AccountReader for RecordingStateProvider<SP> {
+    fn basic_account(&self, address: Address) -> RethResult<Option<Account>> {
+        let account = self.inner.basic_account(address)?;
+        self.read().accounts.insert(address, account);
+        Ok(account)
+    }
+}
+
+impl<SP: StateProvider> StateRootProvider for RecordingStateProvider<SP> {
+    fn state_root(&self, post_state: &BundleStateWithReceipts) -> RethResult<B256> {
+        self.inner.state_root(post_state)
+    }
+}
+
+impl<SP: StateProvider> StateProvider for RecordingStateProvider<SP> {
+    fn storage(&self, account: Address, storage_key: B256) -> RethResult<Option<U256>> {
+        let value = self.inner.storage(account, storage_key)?;
+        self.read().storage.insert((account, storage_key), value.unwrap_or_default());
+        Ok(value)
+    }
+
+    fn bytecode_by_hash(&self, code_hash: B256) -> RethResult<Option<Bytecode>> {
+        let bytecode = self.inner.bytecode_by_hash(code_hash)?;
+        if let Some(bytecode) = &bytecode {
+            self.read().bytecodes.insert(code_hash, bytecode.clone());
+        }
+        Ok(bytecode)
+    }
+
+    fn proof(&self, address: Address, keys: &[B256]) -> RethResult<AccountProof> {
+        self.inner.proof(address, keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{test_utils::create_test_rw_db, DatabaseError};
+    use reth_primitives::{keccak256, Bytes, MAINNET};
+
+    #[test]
+    fn record_read_state() {
+        let db = create_test_rw_db();
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xf3]));
+        let code_hash = keccak256(code.original_bytes());
+        let contract = Account { nonce: 1, balance: U256::ZERO, bytecode_hash: Some(code_hash) };
+        let (contract_address, missing) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let slot = B256::with_last_byte(1);
+        db.update(|tx| {
+            tx.put::<tables::PlainAccountState>(contract_address, contract)?;
+            tx.put::<tables::Bytecodes>(code_hash, code.clone())?;
+            tx.put::<tables::PlainStorageState>(
+                contract_address,
+                StorageEntry { key: slot, value: U256::from(7) },
+            )?;
+            tx.put::<tables::CanonicalHeaders>(0, B256::with_last_byte(9))?;
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+
+        let factory = ProviderFactory::new(&db, MAINNET.clone());
+        let state = RecordingStateProvider::new(factory.latest().unwrap());
+        assert_eq!(state.basic_account(contract_address).unwrap(), Some(contract));
+        assert_eq!(state.basic_account(missing).unwrap(), None);
+        assert_eq!(state.storage(contract_address, slot).unwrap(), Some(U256::from(7)));
+        assert_eq!(state.storage(missing, slot).unwrap(), None);
+        assert_eq!(state.block_hash(0).unwrap(), Some(B256::with_last_byte(9)));
+        assert_eq!(state.block_hash(1).unwrap(), None);
+
+        // The bytecode of the contract is added though it wasn't read.
+        assert_eq!(
+            state.into_read().unwrap(),
+            ReadState {
+                accounts: BTreeMap::from([(contract_address, Some(contract)), (missing, None)]),
+                storage: BTreeMap::from([
+                    ((contract_address, slot), U256::from(7)),
+                    ((missing, slot), U256::ZERO),
+                ]),
+                bytecodes: BTreeMap::from([(code_hash, code)]),
+                block_hashes: BTreeMap::from([(0, B256::with_last_byte(9))]),
+            }
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! Export of the net state changes of a range of blocks.
+use crate::utils::DbTool;
+use clap::Parser;
+use eyre::Result;
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    models::BlockNumberAddress,
+    tables,
+    transaction::DbTx,
+};
+use reth_primitives::{stage::StageId, Account, Address, BlockNumber, B256, U256};
+use serde::Serialize;
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    path::PathBuf,
+};
+use tracing::info;
+
+/// The net changes of the state between two blocks, written as JSON, e.g. to see what a range of
+/// blocks changed without dumping the whole state.
+#[derive(Debug, Clone, Parser)]
+pub struct StateDiffCommand {
+    /// The block whose post-state the diff starts from.
+    #[arg(long, value_name = "BLOCK")]
+    from: BlockNumber,
+    /// The block whose post-state the diff ends at.
+    #[arg(long, value_name = "BLOCK")]
+    to: BlockNumber,
+    /// The path the diff JSON is written to.
+    #[arg(long, value_name = "PATH")]
+    out: PathBuf,
+}
+
+/// The accounts whose state differs between the post-states of `from` and `to`.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct StateDiff {
+    from: BlockNumber,
+    to: BlockNumber,
+    accounts: BTreeMap<Address, AccountDiff>,
+}
+
+/// An account at `from` and `to`, `None` if it doesn't exist, and its changed storage slots.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct AccountDiff {
+    before: Option<Account>,
+    after: Option<Account>,
+    storage: BTreeMap<B256, SlotDiff>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct SlotDiff {
+    before: U256,
+    after: U256,
+}
+
+pub(crate) fn export_state_diff<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &StateDiffCommand,
+) -> Result<()> {
+    let (from, to) = (command.from, command.to);
+    if from >= to {
+        eyre::bail!("--to must be greater than --from.")
+    }
+
+    // The values at `to` are reverted from the plain state with the later changesets.
+    let tip = db_tool
+        .get::<tables::SyncStage>(StageId::Execution.to_string())?
+        .unwrap_or_default()
+        .block_number;
+    if to > tip {
+        eyre::bail!("Block {to} is past the plain state, which is at block {tip}.")
+    }
+
+    let diff = db_tool.db.view(|tx| state_diff(tx, from, to, tip))??;
+    let slots = diff.accounts.values().map(|account| account.storage.len()).sum::<usize>();
+
+    std::fs::write(&command.out, serde_json::to_string_pretty(&diff)?)?;
+    info!(target: "reth::cli", from, to, accounts = diff.accounts.len(), slots, path = ?command.out, "Exported state diff");
+
+    Ok(())
+}
+
+/// Merges the changesets of the blocks after `from` up to `to` into the net changes of the state,
+/// given that the plain state is at `tip`.
+///
+/// Keys changed back to their value at `from` aren't part of the diff, so accounts created and
+/// destroyed within the range are left out.
+fn state_diff<TX: DbTx>(
+    tx: &TX,
+    from: BlockNumber,
+    to: BlockNumber,
+    tip: BlockNumber,
+) -> Result<StateDiff> {
+    // The changesets hold the values before each block, so the first change after a block holds
+    // the value at that block. Keys that didn't change after `to` still hold their value at `to` in
+    // the plain state.
+    let mut accounts = BTreeMap::new();
+    let mut later_accounts = BTreeMap::new();
+    for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk_range(from + 1..=tip)? {
+        let (block, before) = entry?;
+        let changes = if block <= to { &mut accounts } else { &mut later_accounts };
+        changes.entry(before.address).or_insert(before.info);
+    }
+    let account_at_to = |address| match later_accounts.get(&address) {
+        Some(account) => Ok(*account),
+        None => tx.get::<tables::PlainAccountState>(address),
+    };
+
+    let mut storages = BTreeMap::new();
+    let mut later_storages = BTreeMap::new();
+    for entry in tx
+        .cursor_read::<tables::StorageChangeSet>()?
+        .walk_range(BlockNumberAddress::range(from + 1..=tip))?
+    {
+        let (key, before) = entry?;
+        let changes = if key.block_number() <= to { &mut storages } else { &mut later_storages };
+        changes.entry((key.address(), before.key)).or_insert(before.value);
+    }
+
+    let mut diff = BTreeMap::new();
+    for (address, before) in accounts {
+        let after = account_at_to(address)?;
+        diff.insert(address, AccountDiff { before, after, storage: BTreeMap::new() });
+    }
+
+    let mut storage_cursor = tx.cursor_dup_read::<tables::PlainStorageState>()?;
+    for ((address, slot), before) in storages {
+        let after = match later_storages.get(&(address, slot)) {
+            Some(value) => *value,
+            None => storage_cursor
+                .seek_by_key_subkey(address, slot)?
+                .filter(|entry| entry.key == slot)
+                .map_or(U256::ZERO, |entry| entry.value),
+        };
+        if before == after {
+            continue
+        }
+
+        let account = match diff.entry(address) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            // Only the storage of the account changed.
+            Entry::Vacant(entry) => {
+                let account = account_at_to(address)?;
+                entry.insert(AccountDiff {
+                    before: account,
+                    after: account,
+                    storage: BTreeMap::new(),
+                })
+            }
+        };
+        account.storage.insert(slot, SlotDiff { before, after });
+    }
+
+    diff.retain(|_, account| account.before != account.after || !account.storage.is_empty());
+
+    Ok(StateDiff { from, to, accounts: diff })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        models::AccountBeforeTx, test_utils::create_test_rw_db, transaction::DbTxMut, DatabaseError,
+    };
+    use reth_primitives::StorageEntry;
+
+    #[test]
+    fn merge_changesets() {
+        let db = create_test_rw_db();
+        let account = |nonce| Account { nonce, balance: U256::ZERO, bytecode_hash: None };
+        let (changed, created, ephemeral, reverted, storage_only) = (
+            Address::with_last_byte(1),
+            Address::with_last_byte(2),
+            Address::with_last_byte(3),
+            Address::with_last_byte(4),
+            Address::with_last_byte(5),
+        );
+        let slot = B256::with_last_byte(1);
+        db.update(|tx| {
+            // Block 2 changes `changed` and `reverted`, creates `created` and `ephemeral`, and
+            // only writes the storage of `storage_only`.
+            for (address, info) in [
+                (changed, Some(account(1))),
+                (created, None),
+                (ephemeral, None),
+                (reverted, Some(account(1))),
+            ] {
+                tx.put::<tables::AccountChangeSet>(2, AccountBeforeTx { address, info })?;
+            }
+            tx.put::<tables::StorageChangeSet>(
+                (2, changed).into(),
+                StorageEntry { key: slot, value: U256::from(1) },
+            )?;
+            tx.put::<tables::StorageChangeSet>(
+                (2, storage_only).into(),
+                StorageEntry { key: slot, value: U256::ZERO },
+            )?;
+            tx.put::<tables::StorageChangeSet>(
+                (2, ephemeral).into(),
+                StorageEntry { key: slot, value: U256::ZERO },
+            )?;
+
+            // Block 3 destroys `ephemeral` and restores `reverted`.
+            for (address, info) in [(ephemeral, Some(account(1))), (reverted, Some(account(2)))] {
+                tx.put::<tables::AccountChangeSet>(3, AccountBeforeTx { address, info })?;
+            }
+            tx.put::<tables::StorageChangeSet>(
+                (3, ephemeral).into(),
+                StorageEntry { key: slot, value: U256::from(5) },
+            )?;
+
+            // Block 4, after the range, changes `changed` again.
+            tx.put::<tables::AccountChangeSet>(
+                4,
+                AccountBeforeTx { address: changed, info: Some(account(2)) },
+            )?;
+            tx.put::<tables::StorageChangeSet>(
+                (4, changed).into(),
+                StorageEntry { key: slot, value: U256::from(2) },
+            )?;
+
+            tx.put::<tables::PlainAccountState>(changed, account(3))?;
+            tx.put::<tables::PlainAccountState>(created, account(1))?;
+            tx.put::<tables::PlainAccountState>(reverted, account(1))?;
+            tx.put::<tables::PlainAccountState>(storage_only, account(7))?;
+            tx.put::<tables::PlainStorageState>(
+                storage_only,
+                StorageEntry { key: slot, value: U256::from(9) },
+            )?;
+            tx.put::<tables::PlainStorageState>(
+                changed,
+                StorageEntry { key: slot, value: U256::from(3) },
+            )?;
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+
+        let diff = state_diff(&db.tx().unwrap(), 1, 3, 4).unwrap();
+        assert_eq!(
+            diff.accounts,
+            BTreeMap::from([
+                (
+                    changed,
+                    AccountDiff {
+                        before: Some(account(1)),
+                        after: Some(account(2)),
+                        storage: BTreeMap::from([(
+                            slot,
+                            SlotDiff { before: U256::from(1), after: U256::from(2) }
+                        )]),
+                    }
+                ),
+                (
+                    created,
+                    AccountDiff { before: None, after: Some(account(1)), storage: BTreeMap::new() }
+                ),
+                (
+                    storage_only,
+                    AccountDiff {
+                        before: Some(account(7)),
+                        after: Some(account(7)),
+                        storage: BTreeMap::from([(
+                            slot,
+                            SlotDiff { before: U256::ZERO, after: U256::from(9) }
+                        )]),
+                    }
+                ),
+            ])
+        );
+    }
+}
@@ -1,150 +1,592 @@
-use super::setup;
+use super::{
+    forks::skipped_fork_tables,
+    import_dupsort, import_reachable_bytecodes, import_table, import_table_with_range,
+    log_imported_rows, profile_single, prune_unmatched_blocks, repeat_dry_run,
+    revert::StateReverts,
+    setup,
+    source::{import_headers_with_range, ReadSource},
+    trace::write_traces,
+    transaction_range, DumpProgress, DumpReport, ExtractManifest, Mismatches, ReferenceDb,
+    StageCommand,
+};
 use crate::utils::DbTool;
 use eyre::Result;
 use reth_db::{
-    cursor::DbCursorRO, database::Database, table::TableImporter, tables, transaction::DbTx,
-    DatabaseEnv,
+    cursor::DbCursorRO, database::Database, init_db, open_db_read_only, table::Table, tables,
+    transaction::DbTx, DatabaseEnv,
+};
+use reth_primitives::{
+    stage::{StageCheckpoint, StageId},
+    BlockNumber, ChainSpec, PruneModes, Receipt, TxNumber, KECCAK_EMPTY,
 };
-use reth_primitives::{stage::StageCheckpoint, ChainSpec};
-use reth_provider::ProviderFactory;
+use reth_provider::{ProviderFactory, StageCheckpointWriter};
 use reth_revm::Factory;
-use reth_stages::{stages::ExecutionStage, Stage, UnwindInput};
-use std::{path::PathBuf, sync::Arc};
-use tracing::info;
+use reth_stages::{
+    stages::{ExecutionStage, ExecutionStageThresholds, MERKLE_STAGE_DEFAULT_CLEAN_THRESHOLD},
+    Stage,
+};
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+use tracing::{info, warn};
 
 pub(crate) async fn dump_execution_stage<DB: Database>(
     db_tool: &DbTool<'_, DB>,
-    from: u64,
-    to: u64,
-    output_db: &PathBuf,
-    should_run: bool,
+    command: &StageCommand,
+    progress: Option<&dyn DumpProgress>,
 ) -> Result<()> {
-    let (output_db, tip_block_number) = setup(from, to, output_db, db_tool)?;
+    let (from, to) = (command.from, command.to);
+    if command.resumable {
+        if let Some(checkpoint) = dry_run_checkpoint(&command.output_db)? {
+            if !command.restart {
+                return resume_dry_run(db_tool, command, checkpoint, progress).await
+            }
+            warn!(target: "reth::cli", stage = %StageId::Execution, block = checkpoint.block_number, path = ?command.output_db, "Discarding the interrupted dry-run, dumping again");
+            std::fs::remove_dir_all(&command.output_db)?;
+        }
+    }
 
-    import_tables_with_range(&output_db, db_tool, from, to)?;
+    let (output_db, _) = setup(StageId::Execution, command, db_tool)?;
 
-    unwind_and_copy(db_tool, from, tip_block_number, &output_db).await?;
+    let headers_source = import_tables_with_range(&output_db, db_tool, command, progress)?;
 
-    if should_run {
-        dry_run(db_tool.chain.clone(), output_db, to, from).await?;
-    }
+    command.check_deadline()?;
+    copy_state_at_from(db_tool, command, &output_db, progress)?;
 
-    Ok(())
+    prune_unmatched_blocks(&output_db, db_tool, command)?;
+    let rows = log_imported_rows(&output_db, StageId::Execution)?;
+
+    let dry_run = if command.should_run() {
+        command.check_deadline()?;
+        let dry_run_from = command.dry_run_from()?;
+        output_db.view(|tx| check_references(tx, from, to))??;
+        if let Some(dir) = &command.trace_out {
+            write_traces(db_tool, dry_run_from + 1..=to, command.trace_level, dir)?;
+        }
+        let compare_receipts =
+            command.compare_receipts.then_some((db_tool.db, command.max_mismatches));
+        let outcome = if command.resumable {
+            profile_single(resumable_dry_run(
+                db_tool.chain.clone(),
+                &output_db,
+                to,
+                from,
+                dry_run_from,
+                compare_receipts,
+                command.reference(),
+                progress,
+            ))
+            .await
+        } else {
+            repeat_dry_run(StageId::Execution, command, || {
+                dry_run(
+                    db_tool.chain.clone(),
+                    &output_db,
+                    to,
+                    from,
+                    dry_run_from,
+                    compare_receipts,
+                    command.reference(),
+                    progress,
+                )
+            })
+            .await
+        };
+        Some(outcome.map(|run| run.map(|_| None)))
+    } else {
+        None
+    };
+
+    DumpReport::new(StageId::Execution, command, rows)
+        .headers_source(headers_source)
+        .finish(command, dry_run, progress)
 }
 
-/// Imports all the tables that can be copied over a range.
+/// Imports all the tables that can be copied over a range, returning where the headers were read
+/// from.
 fn import_tables_with_range<DB: Database>(
     output_db: &DatabaseEnv,
     db_tool: &DbTool<'_, DB>,
-    from: u64,
-    to: u64,
-) -> eyre::Result<()> {
+    command: &StageCommand,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<ReadSource> {
     //  We're not sharing the transaction in case the memory grows too much.
+    let (from, to) = (command.from, command.to);
 
-    output_db.update(|tx| {
-        tx.import_table_with_range::<tables::CanonicalHeaders, _>(&db_tool.db.tx()?, Some(from), to)
-    })??;
-    output_db.update(|tx| {
-        tx.import_table_with_range::<tables::HeaderTD, _>(&db_tool.db.tx()?, Some(from), to)
-    })??;
-    output_db.update(|tx| {
-        tx.import_table_with_range::<tables::Headers, _>(&db_tool.db.tx()?, Some(from), to)
-    })??;
-    output_db.update(|tx| {
-        tx.import_table_with_range::<tables::BlockBodyIndices, _>(&db_tool.db.tx()?, Some(from), to)
-    })??;
-    output_db.update(|tx| {
-        tx.import_table_with_range::<tables::BlockOmmers, _>(&db_tool.db.tx()?, Some(from), to)
-    })??;
+    let headers_source = import_headers_with_range(db_tool, command, output_db, progress)?;
+    import_table_with_range::<tables::BlockBodyIndices, _>(
+        output_db, db_tool, command, from, to, progress,
+    )?;
+    let skipped = skipped_fork_tables(
+        db_tool,
+        &[tables::BlockOmmers::NAME, tables::BlockWithdrawals::NAME],
+        from,
+        to,
+    )?;
+    if !skipped.contains(&tables::BlockOmmers::NAME) {
+        import_table_with_range::<tables::BlockOmmers, _>(
+            output_db, db_tool, command, from, to, progress,
+        )?;
+    }
+    if !skipped.contains(&tables::BlockWithdrawals::NAME) {
+        import_table_with_range::<tables::BlockWithdrawals, _>(
+            output_db, db_tool, command, from, to, progress,
+        )?;
+    }
 
     // Find range of transactions that need to be copied over
-    let (from_tx, to_tx) = db_tool.db.view(|read_tx| {
-        let mut read_cursor = read_tx.cursor_read::<tables::BlockBodyIndices>()?;
-        let (_, from_block) =
-            read_cursor.seek(from)?.ok_or(eyre::eyre!("BlockBody {from} does not exist."))?;
-        let (_, to_block) =
-            read_cursor.seek(to)?.ok_or(eyre::eyre!("BlockBody {to} does not exist."))?;
-
-        Ok::<(u64, u64), eyre::ErrReport>((
-            from_block.first_tx_num,
-            to_block.first_tx_num + to_block.tx_count,
-        ))
-    })??;
-
-    output_db.update(|tx| {
-        tx.import_table_with_range::<tables::Transactions, _>(
-            &db_tool.db.tx()?,
-            Some(from_tx),
-            to_tx,
-        )
-    })??;
+    let (from_tx, to_tx) = transaction_range(db_tool.db, from, to)?;
 
-    output_db.update(|tx| {
-        tx.import_table_with_range::<tables::TxSenders, _>(&db_tool.db.tx()?, Some(from_tx), to_tx)
-    })??;
+    import_table_with_range::<tables::Transactions, _>(
+        output_db, db_tool, command, from_tx, to_tx, progress,
+    )?;
+    import_table_with_range::<tables::TxSenders, _>(
+        output_db, db_tool, command, from_tx, to_tx, progress,
+    )?;
 
-    Ok(())
+    Ok(headers_source)
 }
 
-/// Dry-run an unwind to FROM block, so we can get the PlainStorageState and
-/// PlainAccountState safely. There might be some state dependency from an address
-/// which hasn't been changed in the given range.
-async fn unwind_and_copy<DB: Database>(
+/// Copies the PlainStorageState and PlainAccountState as of FROM block, so we can get them
+/// safely. There might be some state dependency from an address which hasn't been changed in the
+/// given range.
+///
+/// The plain state of the source is copied as it is, then reverted to FROM block with the
+/// changesets of the later blocks.
+fn copy_state_at_from<DB: Database>(
     db_tool: &DbTool<'_, DB>,
-    from: u64,
-    tip_block_number: u64,
+    command: &StageCommand,
     output_db: &DatabaseEnv,
+    progress: Option<&dyn DumpProgress>,
 ) -> eyre::Result<()> {
-    let factory = ProviderFactory::new(db_tool.db, db_tool.chain.clone());
-    let provider = factory.provider_rw()?;
-
-    let mut exec_stage = ExecutionStage::new_with_factory(Factory::new(db_tool.chain.clone()));
-
-    exec_stage
-        .unwind(
-            &provider,
-            UnwindInput {
-                unwind_to: from,
-                checkpoint: StageCheckpoint::new(tip_block_number),
-                bad_block: None,
-            },
-        )
-        .await?;
+    let source_tx = db_tool.db.tx()?;
 
-    let unwind_inner_tx = provider.into_tx();
-
-    output_db
-        .update(|tx| tx.import_dupsort::<tables::PlainStorageState, _>(&unwind_inner_tx))??;
-    output_db.update(|tx| tx.import_table::<tables::PlainAccountState, _>(&unwind_inner_tx))??;
-    output_db.update(|tx| tx.import_table::<tables::Bytecodes, _>(&unwind_inner_tx))??;
+    import_dupsort::<tables::PlainStorageState, _>(output_db, &source_tx, command, progress)?;
+    import_table::<tables::PlainAccountState, _>(output_db, &source_tx, command, progress)?;
+    let reverts = StateReverts::after(&source_tx, command.from)?;
+    output_db.update(|tx| reverts.revert_plain_state(tx, command.subkey_range().as_ref()))??;
+    import_reachable_bytecodes(output_db, &source_tx, command, progress)?;
 
     Ok(())
 }
 
 /// Try to re-execute the stage without committing
-async fn dry_run<DB: Database>(
+///
+/// The blocks before `dry_run_from` are executed first, only to bring the state up to it. With
+/// `compare_receipts`, the receipts produced from `dry_run_from` are compared against the ones of
+/// the source database, logging at most the given number of mismatches.
+///
+/// Every block is executed under the rules of the hardforks `chain` schedules for it, so a range
+/// crossing an activation switches rules at the activation block.
+async fn dry_run<DB: Database, SDB: Database>(
     chain: Arc<ChainSpec>,
-    output_db: DB,
+    output_db: &DB,
     to: u64,
     from: u64,
+    dry_run_from: u64,
+    compare_receipts: Option<(&SDB, usize)>,
+    reference: Option<&ReferenceDb>,
+    progress: Option<&dyn DumpProgress>,
 ) -> eyre::Result<()> {
-    info!(target: "reth::cli", "Executing stage. [dry-run]");
-
-    let factory = ProviderFactory::new(&output_db, chain.clone());
+    let factory = ProviderFactory::new(output_db, chain.clone());
     let provider = factory.provider_rw()?;
     let mut exec_stage = ExecutionStage::new_with_factory(Factory::new(chain.clone()));
 
-    exec_stage
+    if dry_run_from > from {
+        info!(target: "reth::cli", stage = %StageId::Execution, from, to = dry_run_from, "Warming up state. [dry-run]");
+        let output = exec_stage
+            .execute(
+                &provider,
+                reth_stages::ExecInput {
+                    target: Some(dry_run_from),
+                    checkpoint: Some(StageCheckpoint::new(from)),
+                },
+            )
+            .await?;
+        if let Some(progress) = progress {
+            progress.on_dry_run_block(StageId::Execution, output.checkpoint.block_number);
+        }
+    }
+
+    info!(target: "reth::cli", stage = %StageId::Execution, from = dry_run_from, to, "Executing stage. [dry-run]");
+
+    let output = exec_stage
         .execute(
             &provider,
             reth_stages::ExecInput {
                 target: Some(to),
-                checkpoint: Some(StageCheckpoint::new(from)),
+                checkpoint: Some(StageCheckpoint::new(dry_run_from)),
             },
         )
         .await?;
+    if let Some(progress) = progress {
+        progress.on_dry_run_block(StageId::Execution, output.checkpoint.block_number);
+    }
 
-    info!(target: "reth::cli", "Success.");
+    if let Some((source_db, max_mismatches)) = compare_receipts {
+        source_db.view(|source| {
+            validate_receipts(provider.tx_ref(), source, dry_run_from + 1..=to, max_mismatches)
+        })??;
+    }
+    if let Some(reference) = reference {
+        reference.compare(StageId::Execution, provider.tx_ref())?;
+    }
+
+    info!(target: "reth::cli", stage = %StageId::Execution, from = dry_run_from, to, "Success.");
 
     Ok(())
 }
+
+/// The number of blocks executed by the resumable dry-run between two commits.
+const RESUMABLE_COMMIT_BLOCKS: u64 = 1_000;
+
+/// Returns the checkpoint committed by an interrupted `--resumable` dry-run into `output_db`,
+/// if any.
+///
+/// A dump never imports [`tables::SyncStage`], so the checkpoint can only come from a dry-run.
+pub(super) fn dry_run_checkpoint(output_db: &Path) -> eyre::Result<Option<StageCheckpoint>> {
+    if !output_db.join("mdbx.dat").exists() {
+        return Ok(None)
+    }
+    ExtractManifest::check(output_db)?;
+    let db = open_db_read_only(output_db, None)?;
+    Ok(db.view(|tx| tx.get::<tables::SyncStage>(StageId::Execution.to_string()))??)
+}
+
+/// Continues the dry-run of an existing output database from its committed `checkpoint`, without
+/// dumping again.
+async fn resume_dry_run<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &StageCommand,
+    checkpoint: StageCheckpoint,
+    progress: Option<&dyn DumpProgress>,
+) -> Result<()> {
+    let (from, to) = (command.from, command.to);
+    info!(target: "reth::cli", stage = %StageId::Execution, block = checkpoint.block_number, to, path = ?command.output_db, "Resuming dry-run from its checkpoint. Pass --restart to dump again");
+
+    let output_db = init_db(&command.output_db, None)?;
+    let rows = log_imported_rows(&output_db, StageId::Execution)?;
+    let dry_run = profile_single(resumable_dry_run(
+        db_tool.chain.clone(),
+        &output_db,
+        to,
+        checkpoint.block_number.max(from),
+        command.dry_run_from()?,
+        command.compare_receipts.then_some((db_tool.db, command.max_mismatches)),
+        command.reference(),
+        progress,
+    ))
+    .await;
+
+    DumpReport::new(StageId::Execution, command, rows).finish(
+        command,
+        Some(dry_run.map(|run| run.map(|_| None))),
+        progress,
+    )
+}
+
+/// Re-executes the stage from the committed block `start`, committing the state and the stage
+/// checkpoint every [`RESUMABLE_COMMIT_BLOCKS`] blocks, like the pipeline of the node does.
+///
+/// The output database is left at `to`, so it can't be dry-run again from `--from`. With
+/// `compare_receipts`, the receipts from `dry_run_from` are compared once all blocks are executed.
+async fn resumable_dry_run<DB: Database, SDB: Database>(
+    chain: Arc<ChainSpec>,
+    output_db: &DB,
+    to: u64,
+    start: u64,
+    dry_run_from: u64,
+    compare_receipts: Option<(&SDB, usize)>,
+    reference: Option<&ReferenceDb>,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<()> {
+    let factory = ProviderFactory::new(output_db, chain.clone());
+    let mut exec_stage = ExecutionStage::new(
+        Factory::new(chain),
+        ExecutionStageThresholds {
+            max_blocks: Some(RESUMABLE_COMMIT_BLOCKS),
+            ..Default::default()
+        },
+        MERKLE_STAGE_DEFAULT_CLEAN_THRESHOLD,
+        PruneModes::none(),
+    );
+
+    info!(target: "reth::cli", stage = %StageId::Execution, from = start, to, "Executing stage. [resumable]");
+    let mut checkpoint = StageCheckpoint::new(start);
+    while checkpoint.block_number < to {
+        let provider = factory.provider_rw()?;
+        let output = exec_stage
+            .execute(
+                &provider,
+                reth_stages::ExecInput { target: Some(to), checkpoint: Some(checkpoint) },
+            )
+            .await?;
+        provider.save_stage_checkpoint(StageId::Execution, output.checkpoint)?;
+        provider.commit()?;
+
+        checkpoint = output.checkpoint;
+        info!(target: "reth::cli", stage = %StageId::Execution, block = checkpoint.block_number, "Committed dry-run checkpoint");
+        if let Some(progress) = progress {
+            progress.on_dry_run_block(StageId::Execution, checkpoint.block_number);
+        }
+    }
+
+    if let Some((source_db, max_mismatches)) = compare_receipts {
+        let tx = output_db.tx()?;
+        source_db.view(|source| {
+            validate_receipts(&tx, source, dry_run_from + 1..=to, max_mismatches)
+        })??;
+    }
+    if let Some(reference) = reference {
+        reference.compare(StageId::Execution, &output_db.tx()?)?;
+    }
+
+    info!(target: "reth::cli", stage = %StageId::Execution, from = start, to, "Success.");
+
+    Ok(())
+}
+
+/// Compares the receipts of the transactions of `range` produced in `tx` against the ones stored
+/// in `source`, skipping the receipts `source` doesn't have anymore.
+pub(crate) fn validate_receipts<TX: DbTx, STX: DbTx>(
+    tx: &TX,
+    source: &STX,
+    range: std::ops::RangeInclusive<u64>,
+    max_mismatches: usize,
+) -> eyre::Result<()> {
+    let mut mismatches = Mismatches::<TxNumber>::new(StageId::Execution, max_mismatches);
+    let mut blocks = BTreeMap::<_, usize>::new();
+    let (mut compared, mut missing) = (0, 0);
+    let mut first = None;
+    for entry in tx.cursor_read::<tables::BlockBodyIndices>()?.walk_range(range)? {
+        let (block, indices) = entry?;
+        for tx_number in indices.tx_num_range() {
+            let Some(stored) = source.get::<tables::Receipts>(tx_number)? else {
+                missing += 1;
+                continue
+            };
+            let produced = tx.get::<tables::Receipts>(tx_number)?.ok_or_else(|| {
+                eyre::eyre!("Receipt of transaction {tx_number} was not produced.")
+            })?;
+            compared += 1;
+
+            if let Some(difference) = receipt_difference(&stored, &produced) {
+                first.get_or_insert((block, tx_number));
+                mismatches.insert(
+                    tx_number,
+                    format!("Receipt of transaction {tx_number} in block {block} does not match the source database. {difference}"),
+                );
+                *blocks.entry(block).or_default() += 1;
+            }
+        }
+    }
+
+    if missing > 0 {
+        warn!(target: "reth::cli", stage = %StageId::Execution, receipts = missing, "Skipped receipts pruned from the source database");
+    }
+    if let Some((block, tx_number)) = first {
+        warn!(target: "reth::cli", stage = %StageId::Execution, block, tx_number, "First mismatched receipt");
+    }
+    mismatches.finish(blocks)?;
+
+    info!(target: "reth::cli", stage = %StageId::Execution, receipts = compared, "Validated receipts.");
+
+    Ok(())
+}
+
+/// Makes sure the rows the execution of the blocks after `from` reads are in the extract, failing
+/// with the first dangling reference.
+///
+/// Every account must have its bytecode, and every executed block its header and the
+/// transactions and senders of its body indices.
+fn check_references<TX: DbTx>(tx: &TX, from: BlockNumber, to: BlockNumber) -> eyre::Result<()> {
+    for entry in tx.cursor_read::<tables::PlainAccountState>()?.walk(None)? {
+        let (address, account) = entry?;
+        if let Some(hash) = account.bytecode_hash.filter(|hash| *hash != KECCAK_EMPTY) {
+            if tx.get::<tables::Bytecodes>(hash)?.is_none() {
+                eyre::bail!(
+                    "Account {address} references bytecode {hash}, which is not in the extract."
+                )
+            }
+        }
+    }
+
+    for block in from + 1..=to {
+        if tx.get::<tables::Headers>(block)?.is_none() {
+            eyre::bail!("Header of block {block} is not in the extract.")
+        }
+        let indices = tx
+            .get::<tables::BlockBodyIndices>(block)?
+            .ok_or_else(|| eyre::eyre!("Body indices of block {block} are not in the extract."))?;
+        for tx_number in indices.tx_num_range() {
+            if tx.get::<tables::Transactions>(tx_number)?.is_none() {
+                eyre::bail!("Block {block} references transaction {tx_number}, which is not in the extract.")
+            }
+            if tx.get::<tables::TxSenders>(tx_number)?.is_none() {
+                eyre::bail!("Block {block} references the sender of transaction {tx_number}, which is not in the extract.")
+            }
+        }
+    }
+
+    info!(target: "reth::cli", stage = %StageId::Execution, from, to, "Checked extract references");
+
+    Ok(())
+}
+
+/// Describes the first field of `produced` that differs from `stored`, if any.
+fn receipt_difference(stored: &Receipt, produced: &Receipt) -> Option<String> {
+    if stored.success != produced.success {
+        return Some(format!(
+            "Status differs. Expected: {}. Got: {}",
+            stored.success, produced.success
+        ))
+    }
+    if stored.cumulative_gas_used != produced.cumulative_gas_used {
+        return Some(format!(
+            "Cumulative gas used differs. Expected: {}. Got: {}",
+            stored.cumulative_gas_used, produced.cumulative_gas_used
+        ))
+    }
+    if stored.logs.len() != produced.logs.len() {
+        return Some(format!(
+            "Number of logs differs. Expected: {}. Got: {}",
+            stored.logs.len(),
+            produced.logs.len()
+        ))
+    }
+    stored.logs.iter().zip(&produced.logs).position(|(stored, produced)| stored != produced).map(
+        |index| {
+            format!(
+                "Log {index} differs. Expected: {:?}. Got: {:?}",
+                stored.logs[index], produced.logs[index]
+            )
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        init::init_genesis,
+        stage::dump::{
+            run_stages,
+            self_test::{build_chain, synthetic_genesis, synthetic_keys, CONTRACT},
+            ScratchDirs, Stages,
+        },
+    };
+    use clap::Parser;
+    use reth_db::{
+        models::StoredBlockBodyIndices, test_utils::create_test_rw_db, transaction::DbTxMut,
+        DatabaseError,
+    };
+    use reth_primitives::{
+        Account, Address, Bytes, Chain, ChainSpecBuilder, ForkCondition, GenesisAccount, Hardfork,
+        Header, Log, TransactionSignedNoHash, TxType, B256,
+    };
+    use reth_provider::ReceiptProvider;
+
+    #[test]
+    fn dangling_references() {
+        let db = create_test_rw_db();
+        db.update(|tx| {
+            tx.put::<tables::PlainAccountState>(
+                Address::with_last_byte(1),
+                Account { bytecode_hash: Some(B256::with_last_byte(1)), ..Default::default() },
+            )?;
+            tx.put::<tables::Headers>(2, Header::default())?;
+            tx.put::<tables::BlockBodyIndices>(
+                2,
+                StoredBlockBodyIndices { first_tx_num: 0, tx_count: 1 },
+            )?;
+            tx.put::<tables::Transactions>(0, TransactionSignedNoHash::default())?;
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+
+        let err = db.view(|tx| check_references(tx, 1, 2)).unwrap().unwrap_err();
+        assert!(err.to_string().contains("bytecode"));
+
+        db.update(|tx| tx.put::<tables::Bytecodes>(B256::with_last_byte(1), Default::default()))
+            .unwrap()
+            .unwrap();
+        let err = db.view(|tx| check_references(tx, 1, 2)).unwrap().unwrap_err();
+        assert!(err.to_string().contains("sender of transaction 0"));
+
+        db.update(|tx| tx.put::<tables::TxSenders>(0, Address::ZERO)).unwrap().unwrap();
+        db.view(|tx| check_references(tx, 1, 2)).unwrap().unwrap();
+        let err = db.view(|tx| check_references(tx, 1, 3)).unwrap().unwrap_err();
+        assert!(err.to_string().contains("Header of block 3"));
+    }
+
+    #[test]
+    fn first_receipt_difference() {
+        let log = Log { address: Address::with_last_byte(1), topics: vec![], data: Bytes::new() };
+        let stored = Receipt {
+            tx_type: TxType::EIP1559,
+            success: true,
+            cumulative_gas_used: 21_000,
+            logs: vec![log.clone()],
+        };
+        assert_eq!(receipt_difference(&stored, &stored.clone()), None);
+
+        let produced = Receipt { success: false, cumulative_gas_used: 0, ..stored.clone() };
+        assert!(receipt_difference(&stored, &produced).unwrap().starts_with("Status"));
+
+        let produced = Receipt { cumulative_gas_used: 0, logs: vec![], ..stored.clone() };
+        assert!(receipt_difference(&stored, &produced).unwrap().starts_with("Cumulative gas"));
+
+        let produced = Receipt { logs: vec![], ..stored.clone() };
+        assert!(receipt_difference(&stored, &produced).unwrap().starts_with("Number of logs"));
+
+        let produced =
+            Receipt { logs: vec![Log { data: Bytes::from_static(&[1]), ..log }], ..stored.clone() };
+        assert!(receipt_difference(&stored, &produced).unwrap().starts_with("Log 0"));
+    }
+
+    #[tokio::test]
+    async fn dry_run_across_shanghai() {
+        // `NUMBER CALLER SSTORE PUSH0 PUSH0 RETURN`: PUSH0 is only valid from Shanghai on, so the
+        // calls fail before the activation and succeed after it.
+        let code = Bytes::from_static(&[0x43, 0x33, 0x55, 0x5f, 0x5f, 0xf3]);
+        let keys = synthetic_keys();
+        let genesis = synthetic_genesis(&keys)
+            .extend_accounts([(CONTRACT, GenesisAccount::default().with_code(Some(code)))]);
+        // The blocks are 12 seconds apart, so Shanghai activates at block 4.
+        let chain = Arc::new(
+            ChainSpecBuilder::default()
+                .chain(Chain::dev())
+                .genesis(genesis)
+                .paris_activated()
+                .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(48))
+                .build(),
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(init_db(dir.path().join("source"), None).unwrap());
+        init_genesis(db.clone(), chain.clone()).unwrap();
+        build_chain(&db, chain.clone(), &keys, 6, 2).unwrap();
+
+        let provider = ProviderFactory::new(&db, chain.clone()).provider().unwrap();
+        let calls = (1..=6)
+            .map(|block| provider.receipts_by_block(block.into()).unwrap().unwrap()[0].success)
+            .collect::<Vec<_>>();
+        assert_eq!(calls, [false, false, false, true, true, true]);
+        drop(provider);
+
+        // The dry-run checks the receipts and gas used of blocks 2 to 6 against their headers.
+        let tool = DbTool::new(&db, chain).unwrap();
+        let command = StageCommand::try_parse_from([
+            "reth",
+            "--output-db",
+            "execution",
+            "--from",
+            "1",
+            "--to",
+            "6",
+            "--dry-run",
+        ])
+        .unwrap();
+        let scratch = ScratchDirs::new(Some(dir.path().join("scratch")));
+        run_stages(&tool, Stages::Execution(command), dir.path(), &scratch).await.unwrap();
+    }
+}
@@ -0,0 +1,255 @@
+//! The `files` format of a dump, holding one file per table instead of an MDBX environment.
+//!
+//! Every non-empty table is written to `<TABLE>.bin`, as a sequence of records in table order:
+//!
+//! ```text
+//! key length (u32, little endian) | key | value length (u32, little endian) | value
+//! ```
+//!
+//! Keys and values are the raw bytes of the database, i.e. encoded and compressed. The tables
+//! and their number of records are listed in `index.json`.
+use super::ScratchDirs;
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW},
+    database::Database,
+    init_db,
+    table::{Decode, Decompress, Table},
+    transaction::{DbTx, DbTxMut},
+    DatabaseEnv, RawKey, RawTable, RawValue, TableType, TableViewer, Tables,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    path::Path,
+    str::FromStr,
+};
+use tracing::info;
+
+/// Name of the index listing the table files of a dump.
+pub(crate) const INDEX_FILE: &str = "index.json";
+
+/// The tables held by a dump in the `files` format.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FilesIndex {
+    tables: Vec<TableFile>,
+}
+
+/// A table written to its own file.
+#[derive(Debug, Serialize, Deserialize)]
+struct TableFile {
+    /// The name of the table.
+    table: String,
+    /// The name of the file, relative to the index.
+    file: String,
+    /// The number of records in the file.
+    records: usize,
+}
+
+/// Writes every non-empty table of `db` to its own file under `dir`, along with the index.
+pub(crate) fn export_tables<DB: Database>(db: &DB, dir: &Path) -> eyre::Result<()> {
+    std::fs::create_dir_all(dir)?;
+
+    let mut index = FilesIndex::default();
+    for table in Tables::ALL {
+        let file = format!("{}.bin", table.name());
+        let records = table.view(&ExportViewer { db, path: &dir.join(&file) })?;
+        if records > 0 {
+            info!(target: "reth::cli", %table, records, "Exported table");
+            index.tables.push(TableFile { table: table.name().to_string(), file, records });
+        }
+    }
+
+    std::fs::write(dir.join(INDEX_FILE), serde_json::to_string_pretty(&index)?)?;
+    info!(target: "reth::cli", path = ?dir, tables = index.tables.len(), "Exported tables to files");
+
+    Ok(())
+}
+
+/// Imports the tables listed in the index of `dir` back into `db`, e.g. a temporary environment
+/// to dry-run a stage against.
+pub(crate) fn import_tables(dir: &Path, db: &DatabaseEnv) -> eyre::Result<()> {
+    let index: FilesIndex = serde_json::from_str(&std::fs::read_to_string(dir.join(INDEX_FILE))?)?;
+
+    for TableFile { table: name, file, records } in index.tables {
+        let table = Tables::from_str(&name).map_err(|err| eyre::eyre!("{err}: {name}"))?;
+        let imported = table.view(&ImportViewer { db, table, path: &dir.join(&file) })?;
+        if imported != records {
+            eyre::bail!(
+                "Table file {file} holds {imported} records, but the index lists {records}."
+            )
+        }
+        info!(target: "reth::cli", %table, records, "Imported table");
+    }
+
+    Ok(())
+}
+
+/// Loads the files of `dir` back into a temporary environment, and checks that its tables hold the
+/// same rows as the ones of `db`.
+pub(crate) fn verify_export<DB: Database>(
+    db: &DB,
+    dir: &Path,
+    scratch: &ScratchDirs,
+) -> eyre::Result<()> {
+    let temp = scratch.create("verify")?;
+    let loaded = init_db(temp.path().join("db"), None)?;
+    import_tables(dir, &loaded)?;
+
+    let (expected, got) = (temp.path().join("expected.bin"), temp.path().join("got.bin"));
+    for table in Tables::ALL {
+        let rows = table.view(&ExportViewer { db, path: &expected })?;
+        let loaded_rows = table.view(&ExportViewer { db: &loaded, path: &got })?;
+        if rows != loaded_rows || (rows > 0 && std::fs::read(&expected)? != std::fs::read(&got)?) {
+            eyre::bail!("Table {table} doesn't read back from its file as it was exported.")
+        }
+    }
+    info!(target: "reth::cli", path = ?dir, "Verified exported tables");
+
+    Ok(())
+}
+
+struct ExportViewer<'a, DB: Database> {
+    db: &'a DB,
+    path: &'a Path,
+}
+
+impl<DB: Database> TableViewer<usize> for ExportViewer<'_, DB> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<usize, Self::Error> {
+        self.db.view(|tx| -> eyre::Result<usize> {
+            let mut cursor = tx.cursor_read::<RawTable<T>>()?;
+            let mut rows = cursor.walk(None)?.peekable();
+            if rows.peek().is_none() {
+                return Ok(0)
+            }
+
+            let mut writer = BufWriter::new(File::create(self.path)?);
+            let mut records = 0;
+            for row in rows {
+                let (key, value) = row?;
+                write_record(&mut writer, key.raw_key())?;
+                write_record(&mut writer, value.raw_value())?;
+                records += 1;
+            }
+            writer.flush()?;
+
+            Ok(records)
+        })?
+    }
+}
+
+struct ImportViewer<'a> {
+    db: &'a DatabaseEnv,
+    table: Tables,
+    path: &'a Path,
+}
+
+impl TableViewer<usize> for ImportViewer<'_> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<usize, Self::Error> {
+        let mut reader = BufReader::new(File::open(self.path)?);
+
+        let tx = self.db.tx_mut()?;
+        let mut cursor = tx.cursor_write::<RawTable<T>>()?;
+        let mut records = 0;
+        // The files are written by the dump itself, so their records aren't bounded
+        while let Some(key) = read_record(&mut reader, usize::MAX)? {
+            let value = read_record(&mut reader, usize::MAX)?.ok_or_else(|| {
+                eyre::eyre!("Table file {:?} is truncated after record {records}.", self.path)
+            })?;
+            let (key, value) = (RawKey::<T::Key>::decode(key)?, RawValue::decompress_owned(value)?);
+            // Appending a duplicate of the previous key is rejected, so the values of a dupsort
+            // table are inserted instead.
+            match self.table.table_type() {
+                TableType::Table => cursor.append(key, value)?,
+                TableType::DupSort => cursor.upsert(key, value)?,
+            }
+            records += 1;
+        }
+        drop(cursor);
+        tx.commit()?;
+
+        Ok(records)
+    }
+}
+
+/// Writes `bytes` prefixed by their length.
+pub(crate) fn write_record(writer: &mut impl Write, bytes: &[u8]) -> eyre::Result<()> {
+    writer.write_all(&u32::try_from(bytes.len())?.to_le_bytes())?;
+    writer.write_all(bytes)?;
+    Ok(())
+}
+
+/// Reads the next length-prefixed record, or `None` at the end of the file.
+///
+/// Records longer than `max_len` are rejected without being read. The buffer of a record grows
+/// with the bytes actually read, so a bogus length doesn't allocate it upfront.
+pub(crate) fn read_record(reader: &mut impl Read, max_len: usize) -> eyre::Result<Option<Vec<u8>>> {
+    let mut len = [0; 4];
+    match reader.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err.into()),
+    }
+
+    let len = u32::from_le_bytes(len) as usize;
+    if len > max_len {
+        eyre::bail!("Record of {len} bytes is longer than the limit of {max_len} bytes.")
+    }
+    let mut bytes = Vec::new();
+    reader.by_ref().take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len {
+        return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into())
+    }
+    Ok(Some(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{tables, test_utils::create_test_rw_db};
+    use reth_primitives::{Account, Address, StorageEntry, B256, U256};
+
+    #[test]
+    fn roundtrip() {
+        let source = create_test_rw_db();
+        source
+            .update(|tx| {
+                for number in 0..3u64 {
+                    tx.put::<tables::CanonicalHeaders>(number, B256::with_last_byte(number as u8))?;
+                    tx.put::<tables::PlainAccountState>(
+                        Address::with_last_byte(number as u8),
+                        Account { nonce: number, ..Default::default() },
+                    )?;
+                }
+                for slot in 0..3u8 {
+                    tx.put::<tables::PlainStorageState>(
+                        Default::default(),
+                        StorageEntry { key: B256::with_last_byte(slot), value: U256::from(slot) },
+                    )?;
+                }
+                Ok::<_, reth_db::DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        export_tables(&source, dir.path()).unwrap();
+        assert!(!dir.path().join("Headers.bin").exists());
+
+        let loaded = create_test_rw_db();
+        import_tables(dir.path(), &loaded).unwrap();
+        for table in
+            [Tables::CanonicalHeaders, Tables::PlainAccountState, Tables::PlainStorageState]
+        {
+            let dump = |db: &DatabaseEnv| {
+                table.view(&ExportViewer { db, path: &dir.path().join("check.bin") }).unwrap();
+                std::fs::read(dir.path().join("check.bin")).unwrap()
+            };
+            assert_eq!(dump(&*source), dump(&*loaded), "{table}");
+        }
+    }
+}
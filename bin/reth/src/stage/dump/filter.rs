@@ -0,0 +1,182 @@
+//! Sparse dumps of the blocks matching `--filter`.
+//!
+//! The predicate grammar is:
+//!
+//! - `non-empty`: blocks with at least one transaction.
+//! - `gas_used>N`: blocks which used more than `N` gas.
+//! - `has_withdrawals`: blocks with at least one withdrawal.
+use super::StageCommand;
+use crate::utils::DbTool;
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    models::BlockNumberAddress,
+    table::Table,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    DatabaseEnv,
+};
+use reth_primitives::{BlockNumber, TxNumber};
+use std::{fmt, ops::Range, str::FromStr};
+use tracing::info;
+
+/// Predicate selecting the blocks of the range kept by `--filter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockFilter {
+    /// Blocks with at least one transaction.
+    NonEmpty,
+    /// Blocks which used more than the given gas.
+    GasUsedAbove(u64),
+    /// Blocks with at least one withdrawal.
+    HasWithdrawals,
+}
+
+impl BlockFilter {
+    /// Whether `block` of the source database matches the predicate.
+    fn matches<TX: DbTx>(&self, tx: &TX, block: BlockNumber) -> eyre::Result<bool> {
+        Ok(match self {
+            BlockFilter::NonEmpty => tx
+                .get::<tables::BlockBodyIndices>(block)?
+                .map_or(false, |indices| !indices.is_empty()),
+            BlockFilter::GasUsedAbove(gas) => {
+                tx.get::<tables::Headers>(block)?.map_or(false, |header| header.gas_used > *gas)
+            }
+            BlockFilter::HasWithdrawals => tx
+                .get::<tables::BlockWithdrawals>(block)?
+                .map_or(false, |withdrawals| !withdrawals.withdrawals.is_empty()),
+        })
+    }
+}
+
+impl FromStr for BlockFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s {
+            "non-empty" => return Ok(BlockFilter::NonEmpty),
+            "has_withdrawals" => return Ok(BlockFilter::HasWithdrawals),
+            _ => {}
+        }
+
+        match s.strip_prefix("gas_used").map(str::trim_start).and_then(|s| s.strip_prefix('>')) {
+            Some(gas) => gas
+                .trim()
+                .parse()
+                .map(BlockFilter::GasUsedAbove)
+                .map_err(|err| format!("Invalid gas in block filter {s:?}: {err}")),
+            None => Err(format!(
+                "Unknown block filter {s:?}. Expected `non-empty`, `gas_used>N` or `has_withdrawals`."
+            )),
+        }
+    }
+}
+
+impl fmt::Display for BlockFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockFilter::NonEmpty => write!(f, "non-empty"),
+            BlockFilter::GasUsedAbove(gas) => write!(f, "gas_used>{gas}"),
+            BlockFilter::HasWithdrawals => write!(f, "has_withdrawals"),
+        }
+    }
+}
+
+/// Deletes the imported rows of the blocks which don't match `--filter`, leaving the tables which
+/// aren't keyed by block or transaction untouched.
+///
+/// The predicate is evaluated against the source database, since not every stage imports the
+/// headers.
+pub(crate) fn prune_unmatched_blocks<DB: Database>(
+    output_db: &DatabaseEnv,
+    db_tool: &DbTool<'_, DB>,
+    command: &StageCommand,
+) -> eyre::Result<()> {
+    let Some(filter) = command.filter else { return Ok(()) };
+
+    let unmatched = db_tool.db.view(|tx| {
+        let mut unmatched = vec![];
+        for block in command.from..=command.to {
+            if !filter.matches(tx, block)? {
+                let txs = tx
+                    .get::<tables::BlockBodyIndices>(block)?
+                    .map(|indices| indices.tx_num_range());
+                unmatched.push((block, txs.unwrap_or_default()));
+            }
+        }
+        Ok::<_, eyre::Report>(unmatched)
+    })??;
+
+    output_db.update(|tx| delete_blocks(tx, &unmatched))??;
+
+    let blocks = command.to - command.from + 1;
+    info!(target: "reth::cli", %filter, blocks, matched = blocks - unmatched.len() as u64, "Filtered blocks");
+
+    Ok(())
+}
+
+/// Deletes the rows of `blocks` from the tables keyed by block, and the ones of their
+/// transactions from the tables keyed by transaction.
+pub(crate) fn delete_blocks<TX: DbTxMut + DbTx>(
+    tx: &TX,
+    blocks: &[(BlockNumber, Range<TxNumber>)],
+) -> eyre::Result<()> {
+    for (block, txs) in blocks {
+        delete_block::<tables::CanonicalHeaders, _>(tx, *block)?;
+        delete_block::<tables::HeaderTD, _>(tx, *block)?;
+        delete_block::<tables::Headers, _>(tx, *block)?;
+        delete_block::<tables::BlockBodyIndices, _>(tx, *block)?;
+        delete_block::<tables::BlockOmmers, _>(tx, *block)?;
+        delete_block::<tables::BlockWithdrawals, _>(tx, *block)?;
+        // Deleting a key of a dupsort table deletes all of its values.
+        delete_block::<tables::AccountChangeSet, _>(tx, *block)?;
+
+        let storage_keys = tx
+            .cursor_read::<tables::StorageChangeSet>()?
+            .walk_range(BlockNumberAddress::range(*block..=*block))?
+            .map(|entry| entry.map(|(key, _)| key))
+            .collect::<Result<Vec<_>, _>>()?;
+        for key in storage_keys {
+            tx.delete::<tables::StorageChangeSet>(key, None)?;
+        }
+
+        for tx_number in txs.clone() {
+            tx.delete::<tables::Transactions>(tx_number, None)?;
+            tx.delete::<tables::TxSenders>(tx_number, None)?;
+            tx.delete::<tables::Receipts>(tx_number, None)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Deletes the row of `block` from `T`, if any.
+fn delete_block<T: Table<Key = BlockNumber>, TX: DbTxMut>(
+    tx: &TX,
+    block: BlockNumber,
+) -> eyre::Result<()> {
+    tx.delete::<T>(block, None)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_block_filter() {
+        assert_eq!("non-empty".parse(), Ok(BlockFilter::NonEmpty));
+        assert_eq!("has_withdrawals".parse(), Ok(BlockFilter::HasWithdrawals));
+        assert_eq!("gas_used>15000000".parse(), Ok(BlockFilter::GasUsedAbove(15_000_000)));
+        assert_eq!(" gas_used > 1 ".parse(), Ok(BlockFilter::GasUsedAbove(1)));
+        assert!("gas_used>".parse::<BlockFilter>().is_err());
+        assert!("gas_used<1".parse::<BlockFilter>().is_err());
+        assert!("empty".parse::<BlockFilter>().is_err());
+
+        for filter in
+            [BlockFilter::NonEmpty, BlockFilter::GasUsedAbove(7), BlockFilter::HasWithdrawals]
+        {
+            assert_eq!(filter.to_string().parse(), Ok(filter));
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Tables that only hold rows of the blocks of some hardforks.
+use crate::utils::DbTool;
+use reth_db::{database::Database, table::Table, tables};
+use reth_primitives::{BlockNumber, Hardfork, Head};
+use tracing::info;
+
+/// A table that can only hold rows of the blocks within a range of hardforks.
+struct ForkTable {
+    table: &'static str,
+    /// The hardfork of the first blocks with rows, if any.
+    since: Option<Hardfork>,
+    /// The hardfork from which on blocks have no rows anymore, if any.
+    until: Option<Hardfork>,
+}
+
+const FORK_TABLES: &[ForkTable] = &[
+    // Proof of stake blocks have no ommers.
+    ForkTable { table: tables::BlockOmmers::NAME, since: None, until: Some(Hardfork::Paris) },
+    ForkTable {
+        table: tables::BlockWithdrawals::NAME,
+        since: Some(Hardfork::Shanghai),
+        until: None,
+    },
+];
+
+/// Returns the tables among `tables` that can't hold rows of the blocks `from..=to`, given the
+/// hardforks of the chain active at both ends of the range.
+///
+/// Dumping such a table would only ever import an empty table, so it's skipped instead.
+pub(crate) fn skipped_fork_tables<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    tables: &[&'static str],
+    from: BlockNumber,
+    to: BlockNumber,
+) -> eyre::Result<Vec<&'static str>> {
+    let (first, last) = (head(db_tool, from)?, head(db_tool, to)?);
+
+    let mut skipped = vec![];
+    for fork_table in FORK_TABLES.iter().filter(|fork_table| tables.contains(&fork_table.table)) {
+        let table = fork_table.table;
+        if let Some(fork) = fork_table.since.filter(|fork| !is_active(db_tool, *fork, &last)) {
+            info!(target: "reth::cli", table, ?fork, from, to, "Skipping table, the range is before the hardfork its rows start at");
+            skipped.push(table);
+        } else if let Some(fork) = fork_table.until.filter(|fork| is_active(db_tool, *fork, &first))
+        {
+            info!(target: "reth::cli", table, ?fork, from, to, "Skipping table, the range is past the hardfork its rows end at");
+            skipped.push(table);
+        }
+    }
+
+    Ok(skipped)
+}
+
+fn is_active<DB: Database>(db_tool: &DbTool<'_, DB>, fork: Hardfork, head: &Head) -> bool {
+    db_tool.chain.fork(fork).active_at_head(head)
+}
+
+/// The fork relevant fields of `block`, left at their defaults if the source doesn't have them.
+///
+/// Missing headers then count as earlier than every hardfork, so their tables are never skipped.
+fn head<DB: Database>(db_tool: &DbTool<'_, DB>, block: BlockNumber) -> eyre::Result<Head> {
+    let mut head = Head { number: block, ..Default::default() };
+    if let Some(header) = db_tool.get::<tables::Headers>(block)? {
+        head.difficulty = header.difficulty;
+        head.timestamp = header.timestamp;
+    }
+    if let Some(total_difficulty) = db_tool.get::<tables::HeaderTD>(block)? {
+        head.total_difficulty = total_difficulty.0;
+    }
+
+    Ok(head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{test_utils::create_test_rw_db, transaction::DbTxMut, DatabaseError};
+    use reth_primitives::{Header, MAINNET};
+
+    #[test]
+    fn skip_tables_of_other_forks() {
+        let db = create_test_rw_db();
+        // The Paris block and the first Shanghai block of mainnet.
+        let (paris, shanghai) = (15_537_394, 17_034_870);
+        db.update(|tx| {
+            tx.put::<tables::Headers>(paris, Header { number: paris, ..Default::default() })?;
+            tx.put::<tables::Headers>(
+                shanghai,
+                Header { number: shanghai, timestamp: 1_681_338_455, ..Default::default() },
+            )?;
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+        let fork_tables = [tables::BlockOmmers::NAME, tables::BlockWithdrawals::NAME];
+        assert_eq!(
+            skipped_fork_tables(&tool, &fork_tables, 1, 2).unwrap(),
+            [tables::BlockWithdrawals::NAME]
+        );
+        assert!(skipped_fork_tables(&tool, &fork_tables, paris - 1, shanghai).unwrap().is_empty());
+        assert_eq!(
+            skipped_fork_tables(&tool, &fork_tables, paris, paris + 1).unwrap(),
+            [tables::BlockOmmers::NAME, tables::BlockWithdrawals::NAME]
+        );
+        assert_eq!(
+            skipped_fork_tables(&tool, &fork_tables, shanghai, shanghai).unwrap(),
+            [tables::BlockOmmers::NAME]
+        );
+        // Only the requested tables are skipped.
+        assert!(skipped_fork_tables(&tool, &[tables::Headers::NAME], 1, 2).unwrap().is_empty());
+    }
+}
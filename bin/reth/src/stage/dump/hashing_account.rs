@@ -1,60 +1,88 @@
-use super::setup;
+use super::{
+    import_reachable_bytecodes, import_table, import_table_with_range, log_imported_rows,
+    prune_unmatched_blocks, repeat_dry_run, setup, DumpProgress, DumpReport, Mismatches,
+    ReferenceDb, StageCommand,
+};
 use crate::utils::DbTool;
 use eyre::Result;
-use reth_db::{database::Database, table::TableImporter, tables, DatabaseEnv};
-use reth_primitives::{stage::StageCheckpoint, BlockNumber, ChainSpec};
+use reth_db::{cursor::DbCursorRO, database::Database, tables, transaction::DbTx, DatabaseEnv};
+use reth_primitives::{
+    keccak256,
+    stage::{StageCheckpoint, StageId},
+    ChainSpec,
+};
 use reth_provider::ProviderFactory;
-use reth_stages::{stages::AccountHashingStage, Stage, UnwindInput};
-use std::{path::PathBuf, sync::Arc};
+use reth_stages::{stages::AccountHashingStage, Stage};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
 use tracing::info;
 
 pub(crate) async fn dump_hashing_account_stage<DB: Database>(
     db_tool: &DbTool<'_, DB>,
-    from: BlockNumber,
-    to: BlockNumber,
-    output_db: &PathBuf,
-    should_run: bool,
+    command: &StageCommand,
+    progress: Option<&dyn DumpProgress>,
 ) -> Result<()> {
-    let (output_db, tip_block_number) = setup(from, to, output_db, db_tool)?;
+    let (from, to) = (command.from, command.to);
+    if command.dry_run_from()? != from {
+        // The dry-run hashes the whole state from scratch, so it can't start from a later block.
+        eyre::bail!("The account hashing stage can only be dry-run from --from.")
+    }
+    let (output_db, _) = setup(StageId::AccountHashing, command, db_tool)?;
 
     // Import relevant AccountChangeSets
-    output_db.update(|tx| {
-        tx.import_table_with_range::<tables::AccountChangeSet, _>(&db_tool.db.tx()?, Some(from), to)
-    })??;
+    import_table_with_range::<tables::AccountChangeSet, _>(
+        &output_db, db_tool, command, from, to, progress,
+    )?;
 
-    unwind_and_copy(db_tool, from, tip_block_number, &output_db).await?;
+    command.check_deadline()?;
+    copy_plain_state(db_tool, command, &output_db, progress)?;
 
-    if should_run {
-        dry_run(db_tool.chain.clone(), output_db, to, from).await?;
-    }
+    prune_unmatched_blocks(&output_db, db_tool, command)?;
+    let rows = log_imported_rows(&output_db, StageId::AccountHashing)?;
 
-    Ok(())
+    let dry_run = if command.should_run() {
+        command.check_deadline()?;
+        Some(
+            repeat_dry_run(StageId::AccountHashing, command, || {
+                dry_run(
+                    db_tool.chain.clone(),
+                    &output_db,
+                    to,
+                    from,
+                    command.validate_only,
+                    command.max_mismatches,
+                    command.reference(),
+                    progress,
+                )
+            })
+            .await
+            .map(|run| run.map(|_| None)),
+        )
+    } else {
+        None
+    };
+
+    DumpReport::new(StageId::AccountHashing, command, rows).finish(command, dry_run, progress)
 }
 
-/// Dry-run an unwind to FROM block and copy the necessary table data to the new database.
-async fn unwind_and_copy<DB: Database>(
+/// Copy the necessary table data to the new database.
+///
+/// An unwind of the stage only reverts the hashed state, so the plain state is copied as the
+/// source holds it.
+fn copy_plain_state<DB: Database>(
     db_tool: &DbTool<'_, DB>,
-    from: u64,
-    tip_block_number: u64,
+    command: &StageCommand,
     output_db: &DatabaseEnv,
+    progress: Option<&dyn DumpProgress>,
 ) -> eyre::Result<()> {
-    let factory = ProviderFactory::new(db_tool.db, db_tool.chain.clone());
-    let provider = factory.provider_rw()?;
-    let mut exec_stage = AccountHashingStage::default();
-
-    exec_stage
-        .unwind(
-            &provider,
-            UnwindInput {
-                unwind_to: from,
-                checkpoint: StageCheckpoint::new(tip_block_number),
-                bad_block: None,
-            },
-        )
-        .await?;
-    let unwind_inner_tx = provider.into_tx();
+    let source_tx = db_tool.db.tx()?;
 
-    output_db.update(|tx| tx.import_table::<tables::PlainAccountState, _>(&unwind_inner_tx))??;
+    import_table::<tables::PlainAccountState, _>(output_db, &source_tx, command, progress)?;
+    if command.include_bytecodes {
+        import_reachable_bytecodes(output_db, &source_tx, command, progress)?;
+    }
 
     Ok(())
 }
@@ -62,13 +90,17 @@ async fn unwind_and_copy<DB: Database>(
 /// Try to re-execute the stage straightaway
 async fn dry_run<DB: Database>(
     chain: Arc<ChainSpec>,
-    output_db: DB,
+    output_db: &DB,
     to: u64,
     from: u64,
+    validate_only: bool,
+    max_mismatches: usize,
+    reference: Option<&ReferenceDb>,
+    progress: Option<&dyn DumpProgress>,
 ) -> eyre::Result<()> {
-    info!(target: "reth::cli", "Executing stage.");
+    info!(target: "reth::cli", stage = %StageId::AccountHashing, from, to, "Executing stage.");
 
-    let factory = ProviderFactory::new(&output_db, chain);
+    let factory = ProviderFactory::new(output_db, chain);
     let provider = factory.provider_rw()?;
     let mut exec_stage = AccountHashingStage {
         clean_threshold: 1, // Forces hashing from scratch
@@ -77,7 +109,7 @@ async fn dry_run<DB: Database>(
 
     let mut exec_output = false;
     while !exec_output {
-        exec_output = exec_stage
+        let output = exec_stage
             .execute(
                 &provider,
                 reth_stages::ExecInput {
@@ -85,11 +117,78 @@ async fn dry_run<DB: Database>(
                     checkpoint: Some(StageCheckpoint::new(from)),
                 },
             )
-            .await?
-            .done;
+            .await?;
+        if let Some(progress) = progress {
+            progress.on_dry_run_block(StageId::AccountHashing, output.checkpoint.block_number);
+        }
+        exec_output = output.done;
+    }
+
+    if validate_only {
+        validate_hashed_accounts(provider.tx_ref(), max_mismatches)?;
+    }
+    if let Some(reference) = reference {
+        reference.compare(StageId::AccountHashing, provider.tx_ref())?;
+    }
+
+    info!(target: "reth::cli", stage = %StageId::AccountHashing, from, to, "Success.");
+
+    Ok(())
+}
+
+/// Checks that every [`tables::PlainAccountState`] entry has a matching
+/// [`tables::HashedAccount`] entry, and that there are no others.
+///
+/// Mismatched accounts are traced back to the blocks of [`tables::AccountChangeSet`] that changed
+/// them.
+fn validate_hashed_accounts<TX: DbTx>(tx: &TX, max_mismatches: usize) -> eyre::Result<()> {
+    let mut mismatches = Mismatches::new(StageId::AccountHashing, max_mismatches);
+
+    let mut accounts = 0;
+    for entry in tx.cursor_read::<tables::PlainAccountState>()?.walk(None)? {
+        let (address, account) = entry?;
+        let hashed_address = keccak256(address);
+        let hashed = tx.get::<tables::HashedAccount>(hashed_address)?;
+        if hashed != Some(account) {
+            mismatches.insert(
+                hashed_address,
+                format!("HashedAccount entry of {address} does not match PlainAccountState. Expected: {account:?}. Got: {hashed:?}"),
+            );
+        }
+        accounts += 1;
+    }
+
+    let hashed_accounts = tx.entries::<tables::HashedAccount>()?;
+    if hashed_accounts != accounts {
+        // Only look for the extra entries when there are some, since it requires holding every
+        // hashed address.
+        let mut expected = HashSet::with_capacity(accounts);
+        for entry in tx.cursor_read::<tables::PlainAccountState>()?.walk(None)? {
+            expected.insert(keccak256(entry?.0));
+        }
+        for entry in tx.cursor_read::<tables::HashedAccount>()?.walk(None)? {
+            let (hashed_address, account) = entry?;
+            if !expected.contains(&hashed_address) {
+                mismatches.insert(
+                    hashed_address,
+                    format!("HashedAccount entry {hashed_address} is not in PlainAccountState. Got: {account:?}"),
+                );
+            }
+        }
+    }
+
+    if !mismatches.is_empty() {
+        let mut blocks = BTreeMap::<_, usize>::new();
+        for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk(None)? {
+            let (block, before) = entry?;
+            if mismatches.contains(&keccak256(before.address)) {
+                *blocks.entry(block).or_default() += 1;
+            }
+        }
+        mismatches.finish(blocks)?;
     }
 
-    info!(target: "reth::cli", "Success.");
+    info!(target: "reth::cli", stage = %StageId::AccountHashing, accounts, "Validated hashed accounts.");
 
     Ok(())
 }
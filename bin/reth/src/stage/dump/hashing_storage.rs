@@ -1,59 +1,87 @@
-use super::setup;
+use super::{
+    import_dupsort, log_imported_rows, prune_unmatched_blocks, repeat_dry_run, setup, DumpProgress,
+    DumpReport, Mismatches, ReferenceDb, StageCommand,
+};
 use crate::utils::DbTool;
 use eyre::Result;
-use reth_db::{database::Database, table::TableImporter, tables, DatabaseEnv};
-use reth_primitives::{stage::StageCheckpoint, ChainSpec};
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    tables,
+    transaction::DbTx,
+    DatabaseEnv,
+};
+use reth_primitives::{
+    keccak256,
+    stage::{StageCheckpoint, StageId},
+    ChainSpec,
+};
 use reth_provider::ProviderFactory;
-use reth_stages::{stages::StorageHashingStage, Stage, UnwindInput};
-use std::{path::PathBuf, sync::Arc};
+use reth_stages::{stages::StorageHashingStage, Stage};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
 use tracing::info;
 
 pub(crate) async fn dump_hashing_storage_stage<DB: Database>(
     db_tool: &DbTool<'_, DB>,
-    from: u64,
-    to: u64,
-    output_db: &PathBuf,
-    should_run: bool,
+    command: &StageCommand,
+    progress: Option<&dyn DumpProgress>,
 ) -> Result<()> {
-    let (output_db, tip_block_number) = setup(from, to, output_db, db_tool)?;
+    let (from, to) = (command.from, command.to);
+    if command.dry_run_from()? != from {
+        // The dry-run hashes the whole state from scratch, so it can't start from a later block.
+        eyre::bail!("The storage hashing stage can only be dry-run from --from.")
+    }
+    let (output_db, _) = setup(StageId::StorageHashing, command, db_tool)?;
 
-    unwind_and_copy(db_tool, from, tip_block_number, &output_db).await?;
+    command.check_deadline()?;
+    copy_plain_state(db_tool, command, &output_db, progress)?;
 
-    if should_run {
-        dry_run(db_tool.chain.clone(), output_db, to, from).await?;
-    }
+    prune_unmatched_blocks(&output_db, db_tool, command)?;
+    let rows = log_imported_rows(&output_db, StageId::StorageHashing)?;
 
-    Ok(())
+    let dry_run = if command.should_run() {
+        command.check_deadline()?;
+        Some(
+            repeat_dry_run(StageId::StorageHashing, command, || {
+                dry_run(
+                    db_tool.chain.clone(),
+                    &output_db,
+                    to,
+                    from,
+                    command.validate_only,
+                    command.max_mismatches,
+                    command.reference(),
+                    progress,
+                )
+            })
+            .await
+            .map(|run| run.map(|_| None)),
+        )
+    } else {
+        None
+    };
+
+    DumpReport::new(StageId::StorageHashing, command, rows).finish(command, dry_run, progress)
 }
 
-/// Dry-run an unwind to FROM block and copy the necessary table data to the new database.
-async fn unwind_and_copy<DB: Database>(
+/// Copy the necessary table data to the new database.
+///
+/// An unwind of the stage only reverts the hashed state, so the plain state is copied as the
+/// source holds it.
+fn copy_plain_state<DB: Database>(
     db_tool: &DbTool<'_, DB>,
-    from: u64,
-    tip_block_number: u64,
+    command: &StageCommand,
     output_db: &DatabaseEnv,
+    progress: Option<&dyn DumpProgress>,
 ) -> eyre::Result<()> {
-    let factory = ProviderFactory::new(db_tool.db, db_tool.chain.clone());
-    let provider = factory.provider_rw()?;
-
-    let mut exec_stage = StorageHashingStage::default();
-
-    exec_stage
-        .unwind(
-            &provider,
-            UnwindInput {
-                unwind_to: from,
-                checkpoint: StageCheckpoint::new(tip_block_number),
-                bad_block: None,
-            },
-        )
-        .await?;
-    let unwind_inner_tx = provider.into_tx();
+    let source_tx = db_tool.db.tx()?;
 
     // TODO optimize we can actually just get the entries we need for both these tables
-    output_db
-        .update(|tx| tx.import_dupsort::<tables::PlainStorageState, _>(&unwind_inner_tx))??;
-    output_db.update(|tx| tx.import_dupsort::<tables::StorageChangeSet, _>(&unwind_inner_tx))??;
+    import_dupsort::<tables::PlainStorageState, _>(output_db, &source_tx, command, progress)?;
+    import_dupsort::<tables::StorageChangeSet, _>(output_db, &source_tx, command, progress)?;
 
     Ok(())
 }
@@ -61,13 +89,17 @@ async fn unwind_and_copy<DB: Database>(
 /// Try to re-execute the stage straightaway
 async fn dry_run<DB: Database>(
     chain: Arc<ChainSpec>,
-    output_db: DB,
+    output_db: &DB,
     to: u64,
     from: u64,
+    validate_only: bool,
+    max_mismatches: usize,
+    reference: Option<&ReferenceDb>,
+    progress: Option<&dyn DumpProgress>,
 ) -> eyre::Result<()> {
-    info!(target: "reth::cli", "Executing stage.");
+    info!(target: "reth::cli", stage = %StageId::StorageHashing, from, to, "Executing stage.");
 
-    let factory = ProviderFactory::new(&output_db, chain);
+    let factory = ProviderFactory::new(output_db, chain);
     let provider = factory.provider_rw()?;
     let mut exec_stage = StorageHashingStage {
         clean_threshold: 1, // Forces hashing from scratch
@@ -76,7 +108,7 @@ async fn dry_run<DB: Database>(
 
     let mut exec_output = false;
     while !exec_output {
-        exec_output = exec_stage
+        let output = exec_stage
             .execute(
                 &provider,
                 reth_stages::ExecInput {
@@ -84,11 +116,89 @@ async fn dry_run<DB: Database>(
                     checkpoint: Some(StageCheckpoint::new(from)),
                 },
             )
-            .await?
-            .done;
+            .await?;
+        if let Some(progress) = progress {
+            progress.on_dry_run_block(StageId::StorageHashing, output.checkpoint.block_number);
+        }
+        exec_output = output.done;
+    }
+
+    if validate_only {
+        validate_hashed_storages(provider.tx_ref(), max_mismatches)?;
+    }
+    if let Some(reference) = reference {
+        reference.compare(StageId::StorageHashing, provider.tx_ref())?;
+    }
+
+    info!(target: "reth::cli", stage = %StageId::StorageHashing, from, to, "Success.");
+
+    Ok(())
+}
+
+/// Checks that every [`tables::PlainStorageState`] entry has a matching
+/// [`tables::HashedStorage`] entry, and that there are no others.
+///
+/// Mismatched slots are traced back to the blocks of [`tables::StorageChangeSet`] that changed
+/// them.
+fn validate_hashed_storages<TX: DbTx>(tx: &TX, max_mismatches: usize) -> eyre::Result<()> {
+    let mut mismatches = Mismatches::new(StageId::StorageHashing, max_mismatches);
+    let mut hashed_cursor = tx.cursor_dup_read::<tables::HashedStorage>()?;
+
+    let mut slots = 0;
+    for entry in tx.cursor_read::<tables::PlainStorageState>()?.walk(None)? {
+        let (address, entry) = entry?;
+        let (hashed_address, hashed_slot) = (keccak256(address), keccak256(entry.key));
+        let hashed = hashed_cursor
+            .seek_by_key_subkey(hashed_address, hashed_slot)?
+            .filter(|hashed| hashed.key == hashed_slot)
+            .map(|hashed| hashed.value);
+        if hashed != Some(entry.value) {
+            mismatches.insert(
+                (hashed_address, hashed_slot),
+                format!(
+                    "HashedStorage entry of {address} at slot {} does not match PlainStorageState. Expected: {}. Got: {hashed:?}",
+                    entry.key, entry.value
+                ),
+            );
+        }
+        slots += 1;
+    }
+
+    let hashed_slots = tx.entries::<tables::HashedStorage>()?;
+    if hashed_slots != slots {
+        // Only look for the extra entries when there are some, since it requires holding every
+        // hashed slot.
+        let mut expected = HashSet::with_capacity(slots);
+        for entry in tx.cursor_read::<tables::PlainStorageState>()?.walk(None)? {
+            let (address, entry) = entry?;
+            expected.insert((keccak256(address), keccak256(entry.key)));
+        }
+        for entry in tx.cursor_read::<tables::HashedStorage>()?.walk(None)? {
+            let (hashed_address, entry) = entry?;
+            if !expected.contains(&(hashed_address, entry.key)) {
+                mismatches.insert(
+                    (hashed_address, entry.key),
+                    format!(
+                        "HashedStorage entry of {hashed_address} at slot {} is not in PlainStorageState. Got: {}",
+                        entry.key, entry.value
+                    ),
+                );
+            }
+        }
+    }
+
+    if !mismatches.is_empty() {
+        let mut blocks = BTreeMap::<_, usize>::new();
+        for entry in tx.cursor_read::<tables::StorageChangeSet>()?.walk(None)? {
+            let (key, entry) = entry?;
+            if mismatches.contains(&(keccak256(key.address()), keccak256(entry.key))) {
+                *blocks.entry(key.block_number()).or_default() += 1;
+            }
+        }
+        mismatches.finish(blocks)?;
     }
 
-    info!(target: "reth::cli", "Success.");
+    info!(target: "reth::cli", stage = %StageId::StorageHashing, slots, "Validated hashed storages.");
 
     Ok(())
 }
@@ -0,0 +1,214 @@
+use super::{
+    import_table_with_range, log_imported_rows, prune_unmatched_blocks, repeat_dry_run,
+    revert::unwind_shards, setup, DumpProgress, DumpReport, Mismatches, StageCommand,
+};
+use crate::utils::DbTool;
+use eyre::Result;
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    models::ShardedKey,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    DatabaseEnv,
+};
+use reth_primitives::{
+    stage::{StageCheckpoint, StageId},
+    Address, BlockNumber, ChainSpec,
+};
+use reth_provider::{AccountExtReader, ProviderFactory};
+use reth_stages::{stages::IndexAccountHistoryStage, Stage};
+use std::{collections::BTreeMap, sync::Arc};
+use tracing::info;
+
+pub(crate) async fn dump_index_account_history_stage<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &StageCommand,
+    progress: Option<&dyn DumpProgress>,
+) -> Result<()> {
+    let (from, to) = (command.from, command.to);
+    let (output_db, _) = setup(StageId::IndexAccountHistory, command, db_tool)?;
+
+    // Import relevant AccountChangeSets
+    import_table_with_range::<tables::AccountChangeSet, _>(
+        &output_db, db_tool, command, from, to, progress,
+    )?;
+
+    command.check_deadline()?;
+    let expected = copy_shards_at_from(db_tool, command, &output_db)?;
+
+    prune_unmatched_blocks(&output_db, db_tool, command)?;
+    let rows = log_imported_rows(&output_db, StageId::IndexAccountHistory)?;
+
+    let dry_run = if command.should_run() {
+        command.check_deadline()?;
+        let dry_run_from = command.dry_run_from()?;
+        Some(
+            repeat_dry_run(StageId::IndexAccountHistory, command, || {
+                dry_run(
+                    db_tool.chain.clone(),
+                    &output_db,
+                    to,
+                    from,
+                    dry_run_from,
+                    &expected,
+                    command.max_mismatches,
+                    progress,
+                )
+            })
+            .await
+            .map(|run| run.map(|_| None)),
+        )
+    } else {
+        None
+    };
+
+    DumpReport::new(StageId::IndexAccountHistory, command, rows).finish(command, dry_run, progress)
+}
+
+/// Copy the history shards of the accounts changed by the range to the new database, as an
+/// unwind to FROM block leaves them.
+///
+/// Returns the blocks up to `to` at which the source database indexes these accounts, which the
+/// dry-run has to derive again.
+fn copy_shards_at_from<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &StageCommand,
+    output_db: &DatabaseEnv,
+) -> eyre::Result<BTreeMap<Address, Vec<BlockNumber>>> {
+    let (from, to) = (command.from, command.to);
+    let factory = ProviderFactory::new(db_tool.db, db_tool.chain.clone());
+    let provider = factory.provider()?;
+
+    let changed = provider.changed_accounts_and_blocks_with_range(from + 1..=to)?;
+    let mut expected = BTreeMap::new();
+    for address in changed.keys() {
+        expected.insert(*address, account_history(provider.tx_ref(), *address, to)?);
+    }
+
+    // The stage only appends to the last shard of the accounts it indexes, so the others aren't
+    // needed.
+    let tx = output_db.tx_mut()?;
+    let mut shards = 0;
+    for address in changed.keys() {
+        let mut cursor = provider.tx_ref().cursor_read::<tables::AccountHistory>()?;
+        let source_shards = cursor
+            .walk(Some(ShardedKey::new(*address, 0)))?
+            .take_while(|entry| entry.as_ref().map_or(true, |(key, _)| key.key == *address))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (key, blocks) in unwind_shards(source_shards, from, ShardedKey::last(*address)) {
+            tx.put::<tables::AccountHistory>(key, blocks)?;
+            shards += 1;
+        }
+    }
+    tx.commit()?;
+    info!(target: "reth::cli", stage = %StageId::IndexAccountHistory, accounts = changed.len(), shards, "Imported history shards of the changed accounts");
+
+    Ok(expected)
+}
+
+/// Try to re-execute the stage straightaway
+///
+/// The blocks before `dry_run_from` are indexed first, only to bring the shards up to it.
+async fn dry_run<DB: Database>(
+    chain: Arc<ChainSpec>,
+    output_db: &DB,
+    to: u64,
+    from: u64,
+    dry_run_from: u64,
+    expected: &BTreeMap<Address, Vec<BlockNumber>>,
+    max_mismatches: usize,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<()> {
+    let factory = ProviderFactory::new(output_db, chain);
+    let provider = factory.provider_rw()?;
+    let mut exec_stage = IndexAccountHistoryStage::default();
+
+    for (checkpoint, target) in [(from, dry_run_from), (dry_run_from, to)] {
+        if checkpoint == target {
+            continue
+        }
+        info!(target: "reth::cli", stage = %StageId::IndexAccountHistory, from = checkpoint, to = target, "Executing stage.");
+
+        let mut exec_output = false;
+        while !exec_output {
+            let output = exec_stage
+                .execute(
+                    &provider,
+                    reth_stages::ExecInput {
+                        target: Some(target),
+                        checkpoint: Some(StageCheckpoint::new(checkpoint)),
+                    },
+                )
+                .await?;
+            if let Some(progress) = progress {
+                progress
+                    .on_dry_run_block(StageId::IndexAccountHistory, output.checkpoint.block_number);
+            }
+            exec_output = output.done;
+        }
+    }
+
+    validate_account_history(provider.tx_ref(), expected, to, max_mismatches)?;
+
+    info!(target: "reth::cli", stage = %StageId::IndexAccountHistory, from, to, "Success.");
+
+    Ok(())
+}
+
+/// Checks that the derived [`tables::AccountHistory`] of every account changed by the range
+/// indexes the same blocks up to `to` as the source database.
+///
+/// Mismatched accounts are traced back to the blocks of [`tables::AccountChangeSet`] that changed
+/// them.
+fn validate_account_history<TX: DbTx>(
+    tx: &TX,
+    expected: &BTreeMap<Address, Vec<BlockNumber>>,
+    to: u64,
+    max_mismatches: usize,
+) -> eyre::Result<()> {
+    let mut mismatches = Mismatches::new(StageId::IndexAccountHistory, max_mismatches);
+
+    for (address, blocks) in expected {
+        let derived = account_history(tx, *address, to)?;
+        if &derived != blocks {
+            mismatches.insert(
+                *address,
+                format!("AccountHistory of {address} does not match the source. Expected: {blocks:?}. Got: {derived:?}"),
+            );
+        }
+    }
+
+    if !mismatches.is_empty() {
+        let mut blocks = BTreeMap::<_, usize>::new();
+        for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk(None)? {
+            let (block, before) = entry?;
+            if mismatches.contains(&before.address) {
+                *blocks.entry(block).or_default() += 1;
+            }
+        }
+        mismatches.finish(blocks)?;
+    }
+
+    info!(target: "reth::cli", stage = %StageId::IndexAccountHistory, accounts = expected.len(), "Validated account history.");
+
+    Ok(())
+}
+
+/// Returns the blocks up to `to` at which `tx` indexes changes of `address`, across all of its
+/// shards.
+fn account_history<TX: DbTx>(tx: &TX, address: Address, to: u64) -> eyre::Result<Vec<BlockNumber>> {
+    let mut history = vec![];
+    for entry in
+        tx.cursor_read::<tables::AccountHistory>()?.walk(Some(ShardedKey::new(address, 0)))?
+    {
+        let (key, blocks) = entry?;
+        if key.key != address {
+            break
+        }
+        history.extend(
+            blocks.iter(0).map(|block| block as BlockNumber).take_while(|block| *block <= to),
+        );
+    }
+    Ok(history)
+}
@@ -0,0 +1,228 @@
+use super::{
+    import_table_with_range, log_imported_rows, prune_unmatched_blocks, repeat_dry_run,
+    revert::unwind_shards, setup, DumpProgress, DumpReport, Mismatches, StageCommand,
+};
+use crate::utils::DbTool;
+use eyre::Result;
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    models::{storage_sharded_key::StorageShardedKey, BlockNumberAddress},
+    tables,
+    transaction::{DbTx, DbTxMut},
+    DatabaseEnv,
+};
+use reth_primitives::{
+    stage::{StageCheckpoint, StageId},
+    Address, BlockNumber, ChainSpec, B256,
+};
+use reth_provider::{ProviderFactory, StorageReader};
+use reth_stages::{stages::IndexStorageHistoryStage, Stage};
+use std::{collections::BTreeMap, sync::Arc};
+use tracing::info;
+
+pub(crate) async fn dump_index_storage_history_stage<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &StageCommand,
+    progress: Option<&dyn DumpProgress>,
+) -> Result<()> {
+    let (from, to) = (command.from, command.to);
+    let (output_db, _) = setup(StageId::IndexStorageHistory, command, db_tool)?;
+
+    // Import relevant StorageChangeSets
+    import_table_with_range::<tables::StorageChangeSet, _>(
+        &output_db,
+        db_tool,
+        command,
+        BlockNumberAddress((from, Address::ZERO)),
+        BlockNumberAddress((to, Address::repeat_byte(0xff))),
+        progress,
+    )?;
+
+    command.check_deadline()?;
+    let expected = copy_shards_at_from(db_tool, command, &output_db)?;
+
+    prune_unmatched_blocks(&output_db, db_tool, command)?;
+    let rows = log_imported_rows(&output_db, StageId::IndexStorageHistory)?;
+
+    let dry_run = if command.should_run() {
+        command.check_deadline()?;
+        let dry_run_from = command.dry_run_from()?;
+        Some(
+            repeat_dry_run(StageId::IndexStorageHistory, command, || {
+                dry_run(
+                    db_tool.chain.clone(),
+                    &output_db,
+                    to,
+                    from,
+                    dry_run_from,
+                    &expected,
+                    command.max_mismatches,
+                    progress,
+                )
+            })
+            .await
+            .map(|run| run.map(|_| None)),
+        )
+    } else {
+        None
+    };
+
+    DumpReport::new(StageId::IndexStorageHistory, command, rows).finish(command, dry_run, progress)
+}
+
+/// Copy the history shards of the storage slots changed by the range to the new database, as an
+/// unwind to FROM block leaves them.
+///
+/// Returns the blocks up to `to` at which the source database indexes these slots, which the
+/// dry-run has to derive again.
+fn copy_shards_at_from<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &StageCommand,
+    output_db: &DatabaseEnv,
+) -> eyre::Result<BTreeMap<(Address, B256), Vec<BlockNumber>>> {
+    let (from, to) = (command.from, command.to);
+    let factory = ProviderFactory::new(db_tool.db, db_tool.chain.clone());
+    let provider = factory.provider()?;
+
+    let changed = provider.changed_storages_and_blocks_with_range(from + 1..=to)?;
+    let mut expected = BTreeMap::new();
+    for slot in changed.keys() {
+        expected.insert(*slot, storage_history(provider.tx_ref(), *slot, to)?);
+    }
+
+    // The stage only appends to the last shard of the slots it indexes, so the others aren't
+    // needed.
+    let tx = output_db.tx_mut()?;
+    let mut shards = 0;
+    for (address, storage_key) in changed.keys() {
+        let mut cursor = provider.tx_ref().cursor_read::<tables::StorageHistory>()?;
+        let source_shards = cursor
+            .walk(Some(StorageShardedKey::new(*address, *storage_key, 0)))?
+            .take_while(|entry| {
+                entry.as_ref().map_or(true, |(key, _)| {
+                    key.address == *address && key.sharded_key.key == *storage_key
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let last = StorageShardedKey::last(*address, *storage_key);
+        for (key, blocks) in unwind_shards(source_shards, from, last) {
+            tx.put::<tables::StorageHistory>(key, blocks)?;
+            shards += 1;
+        }
+    }
+    tx.commit()?;
+    info!(target: "reth::cli", stage = %StageId::IndexStorageHistory, slots = changed.len(), shards, "Imported history shards of the changed storage slots");
+
+    Ok(expected)
+}
+
+/// Try to re-execute the stage straightaway
+///
+/// The blocks before `dry_run_from` are indexed first, only to bring the shards up to it.
+async fn dry_run<DB: Database>(
+    chain: Arc<ChainSpec>,
+    output_db: &DB,
+    to: u64,
+    from: u64,
+    dry_run_from: u64,
+    expected: &BTreeMap<(Address, B256), Vec<BlockNumber>>,
+    max_mismatches: usize,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<()> {
+    let factory = ProviderFactory::new(output_db, chain);
+    let provider = factory.provider_rw()?;
+    let mut exec_stage = IndexStorageHistoryStage::default();
+
+    for (checkpoint, target) in [(from, dry_run_from), (dry_run_from, to)] {
+        if checkpoint == target {
+            continue
+        }
+        info!(target: "reth::cli", stage = %StageId::IndexStorageHistory, from = checkpoint, to = target, "Executing stage.");
+
+        let mut exec_output = false;
+        while !exec_output {
+            let output = exec_stage
+                .execute(
+                    &provider,
+                    reth_stages::ExecInput {
+                        target: Some(target),
+                        checkpoint: Some(StageCheckpoint::new(checkpoint)),
+                    },
+                )
+                .await?;
+            if let Some(progress) = progress {
+                progress
+                    .on_dry_run_block(StageId::IndexStorageHistory, output.checkpoint.block_number);
+            }
+            exec_output = output.done;
+        }
+    }
+
+    validate_storage_history(provider.tx_ref(), expected, to, max_mismatches)?;
+
+    info!(target: "reth::cli", stage = %StageId::IndexStorageHistory, from, to, "Success.");
+
+    Ok(())
+}
+
+/// Checks that the derived [`tables::StorageHistory`] of every storage slot changed by the range
+/// indexes the same blocks up to `to` as the source database.
+///
+/// Mismatched slots are traced back to the blocks of [`tables::StorageChangeSet`] that changed
+/// them.
+fn validate_storage_history<TX: DbTx>(
+    tx: &TX,
+    expected: &BTreeMap<(Address, B256), Vec<BlockNumber>>,
+    to: u64,
+    max_mismatches: usize,
+) -> eyre::Result<()> {
+    let mut mismatches = Mismatches::new(StageId::IndexStorageHistory, max_mismatches);
+
+    for (slot, blocks) in expected {
+        let (address, storage_key) = slot;
+        let derived = storage_history(tx, *slot, to)?;
+        if &derived != blocks {
+            mismatches.insert(
+                *slot,
+                format!("StorageHistory of slot {storage_key} of {address} does not match the source. Expected: {blocks:?}. Got: {derived:?}"),
+            );
+        }
+    }
+
+    if !mismatches.is_empty() {
+        let mut blocks = BTreeMap::<_, usize>::new();
+        for entry in tx.cursor_read::<tables::StorageChangeSet>()?.walk(None)? {
+            let (key, entry) = entry?;
+            if mismatches.contains(&(key.address(), entry.key)) {
+                *blocks.entry(key.block_number()).or_default() += 1;
+            }
+        }
+        mismatches.finish(blocks)?;
+    }
+
+    info!(target: "reth::cli", stage = %StageId::IndexStorageHistory, slots = expected.len(), "Validated storage history.");
+
+    Ok(())
+}
+
+/// Returns the blocks up to `to` at which `tx` indexes changes of the storage `slot`, across all
+/// of its shards.
+fn storage_history<TX: DbTx>(
+    tx: &TX,
+    (address, storage_key): (Address, B256),
+    to: u64,
+) -> eyre::Result<Vec<BlockNumber>> {
+    let mut history = vec![];
+    let mut cursor = tx.cursor_read::<tables::StorageHistory>()?;
+    for entry in cursor.walk(Some(StorageShardedKey::new(address, storage_key, 0)))? {
+        let (key, blocks) = entry?;
+        if key.address != address || key.sharded_key.key != storage_key {
+            break
+        }
+        history.extend(
+            blocks.iter(0).map(|block| block as BlockNumber).take_while(|block| *block <= to),
+        );
+    }
+    Ok(history)
+}
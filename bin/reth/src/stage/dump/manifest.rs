@@ -0,0 +1,275 @@
+//! Stamp of the database schema an extract was dumped with, checked whenever it is opened.
+use crate::version::SHORT_VERSION;
+use reth_db::{version::DB_VERSION, TableType, Tables};
+use reth_primitives::{stage::StageId, BlockNumber, ChainSpec, B256};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::debug;
+
+/// Name of the file holding the [`ExtractManifest`] of an output database.
+pub(crate) const EXTRACT_MANIFEST_FILE: &str = "extract.json";
+
+/// The schema of an output database, written next to it by every dump.
+///
+/// Every table is stored in the MDBX sub-database named after it, so an extract of a reth build
+/// with other tables or another database version would be misread.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ExtractManifest {
+    /// The version of the reth build that dumped the extract.
+    reth_version: String,
+    /// The version of the database format, see [`DB_VERSION`].
+    db_version: u64,
+    tables: Vec<ManifestTable>,
+    /// The stage and range of a stage extract, unset for the other dumps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stage: Option<DumpedStage>,
+}
+
+/// The chain, stage and range a stage extract was dumped for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct DumpedStage {
+    /// The name of the chain of the source database.
+    chain: String,
+    /// The genesis hash of the chain, which tells apart the chains of the same name.
+    genesis_hash: B256,
+    /// The dumped stage.
+    stage: String,
+    /// The first block of the range.
+    pub(crate) from: BlockNumber,
+    /// The last block of the range.
+    pub(crate) to: BlockNumber,
+}
+
+impl DumpedStage {
+    /// The dump of `stage` over `from..=to` on `chain`.
+    pub(crate) fn new(
+        chain: &ChainSpec,
+        stage: StageId,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Self {
+        Self {
+            chain: chain.chain.to_string(),
+            genesis_hash: chain.genesis_hash(),
+            stage: stage.to_string(),
+            from,
+            to,
+        }
+    }
+
+    /// Fails unless the dump of `stage` over `from..=to` on `chain` can extend this one, i.e. it
+    /// is of the same chain and stage, and its range overlaps or adjoins this range.
+    fn check_extended_by(
+        &self,
+        chain: &ChainSpec,
+        stage: StageId,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> eyre::Result<()> {
+        if self.genesis_hash != chain.genesis_hash() {
+            eyre::bail!(
+                "The extract was dumped from chain {} with genesis {}, not from {} with genesis {}.",
+                self.chain,
+                self.genesis_hash,
+                chain.chain,
+                chain.genesis_hash()
+            )
+        }
+        if self.stage != stage.to_string() {
+            eyre::bail!("The extract was dumped for the {} stage, not for {stage}.", self.stage)
+        }
+        if from > self.to || to < self.from {
+            eyre::bail!(
+                "The range {from}..={to} doesn't overlap or adjoin the range {}..={} of the extract, so it can't extend it.",
+                self.from,
+                self.to
+            )
+        }
+        if from >= self.from && to <= self.to {
+            eyre::bail!(
+                "The range {from}..={to} is already covered by the range {}..={} of the extract.",
+                self.from,
+                self.to
+            )
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct ManifestTable {
+    name: String,
+    dupsort: bool,
+}
+
+impl ExtractManifest {
+    /// The schema of this build.
+    fn current() -> Self {
+        Self {
+            reth_version: SHORT_VERSION.to_string(),
+            db_version: DB_VERSION,
+            tables: Tables::ALL
+                .iter()
+                .map(|table| ManifestTable {
+                    name: table.name().to_string(),
+                    dupsort: matches!(table.table_type(), TableType::DupSort),
+                })
+                .collect(),
+            stage: None,
+        }
+    }
+
+    /// Writes the schema of this build next to the output database at `path`.
+    pub(crate) fn write(path: &Path) -> eyre::Result<()> {
+        Self::current().write_to(path)
+    }
+
+    /// Writes the schema of this build and the dumped `stage` next to the stage extract at
+    /// `path`.
+    pub(crate) fn write_stage(path: &Path, stage: DumpedStage) -> eyre::Result<()> {
+        Self { stage: Some(stage), ..Self::current() }.write_to(path)
+    }
+
+    fn write_to(&self, path: &Path) -> eyre::Result<()> {
+        std::fs::write(path.join(EXTRACT_MANIFEST_FILE), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Reads the manifest of the database at `path`, if it has one.
+    fn read(path: &Path) -> eyre::Result<Option<Self>> {
+        let manifest_path = path.join(EXTRACT_MANIFEST_FILE);
+        if !manifest_path.exists() {
+            return Ok(None)
+        }
+        Ok(Some(serde_json::from_str(&std::fs::read_to_string(&manifest_path)?)?))
+    }
+
+    /// Fails if the database at `path` is an extract dumped with another schema than the one of
+    /// this build.
+    ///
+    /// Databases without a manifest, e.g. the one of a node, aren't checked.
+    pub(crate) fn check(path: &Path) -> eyre::Result<()> {
+        let Some(manifest) = Self::read(path)? else { return Ok(()) };
+        manifest.check_compatible(&Self::current())?;
+        debug!(target: "reth::cli", ?path, reth_version = manifest.reth_version, "Checked extract schema");
+        Ok(())
+    }
+
+    /// Whether the folder at `path` holds a manifest recording a dumped stage.
+    pub(crate) fn is_stage_extract(path: &Path) -> eyre::Result<bool> {
+        Ok(Self::read(path)?.map_or(false, |manifest| manifest.stage.is_some()))
+    }
+
+    /// Returns the dumped stage of the extract at `path`, failing unless the dump of `stage` over
+    /// `from..=to` on `chain` can extend it, see `--append`.
+    pub(crate) fn check_append(
+        path: &Path,
+        chain: &ChainSpec,
+        stage: StageId,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> eyre::Result<DumpedStage> {
+        let Some(manifest) = Self::read(path)? else {
+            eyre::bail!(
+                "{path:?} has no {EXTRACT_MANIFEST_FILE}, so it isn't an extract to append to."
+            )
+        };
+        manifest.check_compatible(&Self::current())?;
+        let Some(dumped) = manifest.stage else {
+            eyre::bail!(
+                "The extract at {path:?} was dumped by reth {} without its stage and range, so it can't be appended to.",
+                manifest.reth_version
+            )
+        };
+        dumped.check_extended_by(chain, stage, from, to)?;
+        Ok(dumped)
+    }
+
+    fn check_compatible(&self, current: &Self) -> eyre::Result<()> {
+        if self.db_version != current.db_version {
+            eyre::bail!(
+                "The extract was dumped by reth {} with database version v{}, which is incompatible with v{} of this build.",
+                self.reth_version,
+                self.db_version,
+                current.db_version
+            )
+        }
+
+        let differing = self
+            .tables
+            .iter()
+            .filter(|table| !current.tables.contains(table))
+            .chain(current.tables.iter().filter(|table| !self.tables.contains(table)))
+            .map(|table| table.name.as_str())
+            .collect::<Vec<_>>();
+        if !differing.is_empty() {
+            eyre::bail!(
+                "The extract was dumped by reth {} with other tables than this build: {}. Its rows would be misread.",
+                self.reth_version,
+                differing.join(", ")
+            )
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{GOERLI, MAINNET};
+
+    #[test]
+    fn check_extract_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        ExtractManifest::check(dir.path()).unwrap();
+        ExtractManifest::write(dir.path()).unwrap();
+        ExtractManifest::check(dir.path()).unwrap();
+
+        let current = ExtractManifest::current();
+        let older = ExtractManifest { db_version: DB_VERSION - 1, ..ExtractManifest::current() };
+        assert!(older.check_compatible(&current).is_err());
+
+        let mut renamed = ExtractManifest::current();
+        renamed.tables[0].name = "OldTable".to_string();
+        assert!(renamed.check_compatible(&current).is_err());
+
+        let mut resorted = ExtractManifest::current();
+        resorted.tables[0].dupsort = !resorted.tables[0].dupsort;
+        assert!(resorted.check_compatible(&current).is_err());
+
+        let other_build = ExtractManifest { reth_version: "0.0.0".to_string(), ..current };
+        other_build.check_compatible(&ExtractManifest::current()).unwrap();
+    }
+
+    #[test]
+    fn check_append_to_extract() {
+        let dir = tempfile::tempdir().unwrap();
+        let check = |stage, from, to| {
+            ExtractManifest::check_append(dir.path(), &MAINNET, stage, from, to).map(|_| ())
+        };
+        assert!(check(StageId::Execution, 20, 30).is_err());
+        ExtractManifest::write(dir.path()).unwrap();
+        assert!(check(StageId::Execution, 20, 30).is_err());
+
+        ExtractManifest::write_stage(
+            dir.path(),
+            DumpedStage::new(&MAINNET, StageId::Execution, 10, 20),
+        )
+        .unwrap();
+        let dumped =
+            ExtractManifest::check_append(dir.path(), &MAINNET, StageId::Execution, 20, 30)
+                .unwrap();
+        assert_eq!((dumped.from, dumped.to), (10, 20));
+        check(StageId::Execution, 5, 15).unwrap();
+        check(StageId::Execution, 0, 40).unwrap();
+
+        // A gap between the ranges, a range already dumped, another stage or another chain.
+        assert!(check(StageId::Execution, 21, 30).is_err());
+        assert!(check(StageId::Execution, 12, 18).is_err());
+        assert!(check(StageId::AccountHashing, 20, 30).is_err());
+        assert!(
+            ExtractManifest::check_append(dir.path(), &GOERLI, StageId::Execution, 20, 30).is_err()
+        );
+    }
+}
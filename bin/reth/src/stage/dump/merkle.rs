@@ -1,8 +1,24 @@
-use super::setup;
+use super::{
+    import_dupsort, import_table, import_table_with_range, log_imported_rows,
+    prune_unmatched_blocks, repeat_dry_run, revert::StateReverts, setup,
+    source::import_headers_with_range, DumpProgress, DumpReport, ReferenceDb, StageCommand,
+};
 use crate::utils::DbTool;
-use eyre::Result;
-use reth_db::{database::Database, table::TableImporter, tables, DatabaseEnv};
-use reth_primitives::{stage::StageCheckpoint, BlockNumber, ChainSpec, PruneModes};
+use eyre::{Result, WrapErr};
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    models::BlockNumberAddress,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    DatabaseEnv,
+};
+use reth_primitives::{
+    keccak256,
+    stage::{StageCheckpoint, StageId},
+    trie::{Nibbles, StoredNibbles, StoredNibblesSubKey},
+    Account, BlockNumber, ChainSpec, PruneModes, B256, U256,
+};
 use reth_provider::ProviderFactory;
 use reth_stages::{
     stages::{
@@ -11,45 +27,120 @@ use reth_stages::{
     },
     Stage, UnwindInput,
 };
-use std::{path::PathBuf, sync::Arc};
-use tracing::info;
+use reth_trie::{
+    hashed_cursor::{HashedPostState, HashedPostStateCursorFactory, HashedStorage},
+    prefix_set::PrefixSetMut,
+    StateRoot,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::Arc,
+};
+use tracing::{info, warn};
 
 pub(crate) async fn dump_merkle_stage<DB: Database>(
     db_tool: &DbTool<'_, DB>,
-    from: BlockNumber,
-    to: BlockNumber,
-    output_db: &PathBuf,
-    should_run: bool,
+    command: &StageCommand,
+    progress: Option<&dyn DumpProgress>,
 ) -> Result<()> {
-    let (output_db, tip_block_number) = setup(from, to, output_db, db_tool)?;
-
-    output_db.update(|tx| {
-        tx.import_table_with_range::<tables::Headers, _>(&db_tool.db.tx()?, Some(from), to)
-    })??;
+    let (from, to) = (command.from, command.to);
+    if command.dry_run_from()? != from {
+        // The trie is imported at `from`, so it can only be updated from there.
+        eyre::bail!("The merkle stage can only be dry-run from --from.")
+    }
+    let (output_db, tip_block_number) = setup(StageId::MerkleExecute, command, db_tool)?;
 
-    output_db.update(|tx| {
-        tx.import_table_with_range::<tables::AccountChangeSet, _>(&db_tool.db.tx()?, Some(from), to)
-    })??;
+    let headers_source = import_headers_with_range(db_tool, command, &output_db, progress)?;
 
-    unwind_and_copy(db_tool, (from, to), tip_block_number, &output_db).await?;
+    import_table_with_range::<tables::AccountChangeSet, _>(
+        &output_db, db_tool, command, from, to, progress,
+    )?;
 
-    if should_run {
-        dry_run(db_tool.chain.clone(), output_db, to, from).await?;
+    command.check_deadline()?;
+    if command.with_trie {
+        unwind_and_copy(db_tool, command, tip_block_number, &output_db, progress).await?;
+    } else {
+        copy_hashed_state_at_to(db_tool, command, &output_db, progress)?;
     }
 
+    prune_unmatched_blocks(&output_db, db_tool, command)?;
+    let rows = log_imported_rows(&output_db, StageId::MerkleExecute)?;
+
+    let dry_run = if command.should_run() {
+        command.check_deadline()?;
+        if command.with_trie {
+            output_db.view(|tx| check_trie_boundary(tx, from))??;
+        }
+        if let Some(path) = &command.roots_csv {
+            output_db.view(|tx| write_roots_csv(tx, from, to, path))??;
+        }
+        Some(
+            repeat_dry_run(StageId::MerkleExecute, command, || {
+                dry_run(
+                    db_tool.chain.clone(),
+                    &output_db,
+                    to,
+                    from,
+                    command.with_trie,
+                    command.reference(),
+                    progress,
+                )
+            })
+            .await
+            .map(|run| run.map(Some)),
+        )
+    } else {
+        None
+    };
+
+    DumpReport::new(StageId::MerkleExecute, command, rows)
+        .headers_source(headers_source)
+        .finish(command, dry_run, progress)
+}
+
+/// Copy the necessary table data to the new database, with the hashed state as of TO block.
+///
+/// The hashed state of the source is copied as it is, then reverted to TO block with the
+/// changesets of the later blocks.
+fn copy_hashed_state_at_to<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &StageCommand,
+    output_db: &DatabaseEnv,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<()> {
+    let source_tx = db_tool.db.tx()?;
+
+    // TODO optimize we can actually just get the entries we need
+    import_dupsort::<tables::StorageChangeSet, _>(output_db, &source_tx, command, progress)?;
+
+    import_table::<tables::HashedAccount, _>(output_db, &source_tx, command, progress)?;
+    import_dupsort::<tables::HashedStorage, _>(output_db, &source_tx, command, progress)?;
+    let reverts = StateReverts::after(&source_tx, command.to)?;
+    output_db.update(|tx| reverts.revert_hashed_state(tx, command.subkey_range().as_ref()))??;
+
     Ok(())
 }
 
-/// Dry-run an unwind to FROM block and copy the necessary table data to the new database.
+/// Dry-run an unwind to FROM block and copy the necessary table data to the new database, along
+/// with the trie as of FROM block.
+///
+/// Only the stages can unwind the trie, so this unwinds the source database in a transaction which
+/// is never committed, and doesn't work with `--db.read-only`.
 async fn unwind_and_copy<DB: Database>(
     db_tool: &DbTool<'_, DB>,
-    range: (u64, u64),
+    command: &StageCommand,
     tip_block_number: u64,
     output_db: &DatabaseEnv,
+    progress: Option<&dyn DumpProgress>,
 ) -> eyre::Result<()> {
-    let (from, to) = range;
+    let (from, to) = (command.from, command.to);
     let factory = ProviderFactory::new(db_tool.db, db_tool.chain.clone());
-    let provider = factory.provider_rw()?;
+    let provider = factory.provider_rw().wrap_err(
+        "--with-trie unwinds the trie in the source database, which --db.read-only doesn't allow",
+    )?;
 
     let unwind = UnwindInput {
         unwind_to: from,
@@ -103,45 +194,414 @@ async fn unwind_and_copy<DB: Database>(
     let unwind_inner_tx = provider.into_tx();
 
     // TODO optimize we can actually just get the entries we need
-    output_db.update(|tx| tx.import_dupsort::<tables::StorageChangeSet, _>(&unwind_inner_tx))??;
+    import_dupsort::<tables::StorageChangeSet, _>(output_db, &unwind_inner_tx, command, progress)?;
+
+    import_table::<tables::HashedAccount, _>(output_db, &unwind_inner_tx, command, progress)?;
+    import_dupsort::<tables::HashedStorage, _>(output_db, &unwind_inner_tx, command, progress)?;
+    import_range_subtries(output_db, &unwind_inner_tx, from, to)?;
+
+    Ok(())
+}
+
+/// Imports the nodes of the tries of `tx` on the paths of the keys changed after block `from` up
+/// to `to`, which are the nodes an incremental update of the range reads.
+///
+/// The other subtries are skipped by the update using the hashes of their parents, or rebuilt from
+/// the hashed state if their parents don't store them.
+fn import_range_subtries<TX: DbTx>(
+    output_db: &DatabaseEnv,
+    tx: &TX,
+    from: BlockNumber,
+    to: BlockNumber,
+) -> Result<()> {
+    let mut accounts = BTreeSet::new();
+    let mut storages = BTreeMap::<B256, BTreeSet<B256>>::new();
+    for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk_range(from + 1..=to)? {
+        accounts.insert(keccak256(entry?.1.address));
+    }
+    for entry in tx
+        .cursor_read::<tables::StorageChangeSet>()?
+        .walk_range(BlockNumberAddress::range(from + 1..=to))?
+    {
+        let (key, before) = entry?;
+        let address = keccak256(key.address());
+        accounts.insert(address);
+        storages.entry(address).or_default().insert(keccak256(before.key));
+    }
+
+    let (mut account_nodes, mut storage_nodes) = (0, 0);
+    output_db.update(|output| {
+        for_each_trie_path(&accounts, |path| {
+            let path = StoredNibbles::from(path.to_vec());
+            if let Some(node) = tx.get::<tables::AccountsTrie>(path.clone())? {
+                output.put::<tables::AccountsTrie>(path, node)?;
+                account_nodes += 1;
+            }
+            Ok(())
+        })?;
+
+        let mut cursor = tx.cursor_dup_read::<tables::StoragesTrie>()?;
+        for (address, slots) in &storages {
+            for_each_trie_path(slots, |path| {
+                let path = StoredNibblesSubKey::from(path.to_vec());
+                if let Some(entry) = cursor
+                    .seek_by_key_subkey(*address, path.clone())?
+                    .filter(|entry| entry.nibbles == path)
+                {
+                    output.put::<tables::StoragesTrie>(*address, entry)?;
+                    storage_nodes += 1;
+                }
+                Ok(())
+            })?;
+        }
+        Ok::<_, eyre::Report>(())
+    })??;
+
+    info!(target: "reth::cli", stage = %StageId::MerkleExecute, accounts = accounts.len(), account_nodes, storage_nodes, "Imported the subtries of the range");
+
+    Ok(())
+}
+
+/// Calls `f` once with the path of every trie node which may be on the way to one of the sorted
+/// `keys`. The leaves themselves aren't stored in the trie tables.
+///
+/// The paths shared with the previous key were already visited, so no set of them is kept.
+fn for_each_trie_path<'a>(
+    keys: impl IntoIterator<Item = &'a B256>,
+    mut f: impl FnMut(&[u8]) -> Result<()>,
+) -> Result<()> {
+    let mut previous: Option<Nibbles> = None;
+    for key in keys {
+        let nibbles = Nibbles::unpack(key);
+        let start =
+            previous.as_ref().map_or(0, |previous| nibbles.common_prefix_length(previous) + 1);
+        for len in start..nibbles.len() {
+            f(&nibbles.hex_data[..len])?;
+        }
+        previous = Some(nibbles);
+    }
+    Ok(())
+}
+
+/// Makes sure the imported trie is the one of block `from`, which the dry-run updates
+/// incrementally instead of rebuilding it.
+///
+/// A stale trie would make the dry-run compute a misleading root. Small tries have no stored root
+/// node, in which case the dry-run rebuilds them from the hashed state.
+fn check_trie_boundary<TX: DbTx>(tx: &TX, from: BlockNumber) -> eyre::Result<()> {
+    let expected = tx
+        .get::<tables::Headers>(from)?
+        .ok_or_else(|| eyre::eyre!("Header {from} does not exist."))?
+        .state_root;
+
+    match tx.get::<tables::AccountsTrie>(StoredNibbles::from(vec![]))?.and_then(|node| node.root_hash) {
+        Some(root) if root == expected => {
+            info!(target: "reth::cli", stage = %StageId::MerkleExecute, block = from, %root, "Verified imported trie");
+        }
+        Some(root) => eyre::bail!(
+            "The imported accounts trie has root {root}, but the state root of block {from} is {expected}. The trie wasn't unwound to --from, so the incremental dry-run can't be trusted."
+        ),
+        None => {
+            warn!(target: "reth::cli", stage = %StageId::MerkleExecute, block = from, trie_nodes = tx.entries::<tables::AccountsTrie>()?, "Imported accounts trie has no root node, so its state at --from can't be verified");
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the state root of every block after `from` up to `to` to a CSV file at `path`, along
+/// with the one of its header, one row at a time.
+///
+/// The imported trie is at `from` and the hashed state at `to`, so the hashed state of every block
+/// is overlaid with the values its keys had before their next change. All the keys changed over
+/// the range are recomputed for every block, unchanged ones keeping their value of `from`.
+fn write_roots_csv<TX: DbTx>(
+    tx: &TX,
+    from: BlockNumber,
+    to: BlockNumber,
+    path: &Path,
+) -> Result<()> {
+    let mut accounts = BTreeMap::<B256, VecDeque<(BlockNumber, Option<Account>)>>::new();
+    let mut storages = BTreeMap::<(B256, B256), VecDeque<(BlockNumber, U256)>>::new();
+    let mut changed = BTreeMap::<BlockNumber, (Vec<B256>, Vec<(B256, B256)>)>::new();
+    for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk_range(from + 1..=to)? {
+        let (block, before) = entry?;
+        let address = keccak256(before.address);
+        accounts.entry(address).or_default().push_back((block, before.info));
+        changed.entry(block).or_default().0.push(address);
+    }
+    for entry in tx
+        .cursor_read::<tables::StorageChangeSet>()?
+        .walk_range(BlockNumberAddress::range(from + 1..=to))?
+    {
+        let (key, before) = entry?;
+        let slot = (keccak256(key.address()), keccak256(before.key));
+        storages.entry(slot).or_default().push_back((key.block_number(), before.value));
+        changed.entry(key.block_number()).or_default().1.push(slot);
+    }
+
+    let mut account_prefixes = PrefixSetMut::default();
+    let mut storage_prefixes = HashMap::<B256, PrefixSetMut>::new();
+    for address in accounts.keys() {
+        account_prefixes.insert(Nibbles::unpack(address));
+    }
+    for (address, slot) in storages.keys() {
+        account_prefixes.insert(Nibbles::unpack(address));
+        storage_prefixes.entry(*address).or_default().insert(Nibbles::unpack(slot));
+    }
+    let account_prefixes = account_prefixes.freeze();
+    let storage_prefixes = storage_prefixes
+        .into_iter()
+        .map(|(address, prefixes)| (address, prefixes.freeze()))
+        .collect::<HashMap<_, _>>();
+
+    let mut csv = BufWriter::new(File::create(path)?);
+    writeln!(csv, "block_number,computed_root,stored_root,matches")?;
+    let mut diverging = vec![];
+    for block in from + 1..=to {
+        // The values at `block` are the ones before the first change after it.
+        let (changed_accounts, changed_slots) = changed.remove(&block).unwrap_or_default();
+        for address in changed_accounts {
+            accounts.get_mut(&address).and_then(VecDeque::pop_front);
+        }
+        for slot in changed_slots {
+            storages.get_mut(&slot).and_then(VecDeque::pop_front);
+        }
+
+        let mut state = HashedPostState::default();
+        for (address, changes) in &accounts {
+            match changes.front() {
+                Some((_, Some(account))) => state.insert_account(*address, *account),
+                Some((_, None)) => state.insert_cleared_account(*address),
+                None => {}
+            }
+        }
+        let mut storage = BTreeMap::<B256, HashedStorage>::new();
+        for ((address, slot), changes) in &storages {
+            if let Some((_, value)) = changes.front() {
+                let storage = storage.entry(*address).or_insert_with(|| HashedStorage::new(false));
+                if *value == U256::ZERO {
+                    storage.insert_zero_valued_slot(*slot);
+                } else {
+                    storage.insert_non_zero_valued_storage(*slot, *value);
+                }
+            }
+        }
+        for (address, storage) in storage {
+            state.insert_hashed_storage(address, storage);
+        }
+        let state = state.sorted();
+
+        let computed = StateRoot::new(tx)
+            .with_hashed_cursor_factory(HashedPostStateCursorFactory::new(tx, &state))
+            .with_changed_account_prefixes(account_prefixes.clone())
+            .with_changed_storage_prefixes(storage_prefixes.clone())
+            .root()?;
+        let stored = tx
+            .get::<tables::Headers>(block)?
+            .ok_or_else(|| eyre::eyre!("Header {block} does not exist."))?
+            .state_root;
+        if computed != stored {
+            diverging.push(block);
+        }
+        writeln!(csv, "{block},{computed:?},{stored:?},{}", computed == stored)?;
+    }
+    csv.flush()?;
 
-    output_db.update(|tx| tx.import_table::<tables::HashedAccount, _>(&unwind_inner_tx))??;
-    output_db.update(|tx| tx.import_dupsort::<tables::HashedStorage, _>(&unwind_inner_tx))??;
-    output_db.update(|tx| tx.import_table::<tables::AccountsTrie, _>(&unwind_inner_tx))??;
-    output_db.update(|tx| tx.import_dupsort::<tables::StoragesTrie, _>(&unwind_inner_tx))??;
+    info!(target: "reth::cli", stage = %StageId::MerkleExecute, from, to, diverging = diverging.len(), first_diverging = ?diverging.first(), ?path, "Wrote state roots");
 
     Ok(())
 }
 
-/// Try to re-execute the stage straightaway
+/// Try to re-execute the stage straightaway, returning the verified state root of TO block.
 async fn dry_run<DB: Database>(
     chain: Arc<ChainSpec>,
-    output_db: DB,
+    output_db: &DB,
     to: u64,
     from: u64,
-) -> eyre::Result<()> {
-    info!(target: "reth::cli", "Executing stage.");
-    let factory = ProviderFactory::new(&output_db, chain);
+    incremental: bool,
+    reference: Option<&ReferenceDb>,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<B256> {
+    info!(target: "reth::cli", stage = %StageId::MerkleExecute, from, to, "Executing stage.");
+    let factory = ProviderFactory::new(output_db, chain);
     let provider = factory.provider_rw()?;
     let mut exec_output = false;
     while !exec_output {
-        exec_output = MerkleStage::Execution {
-            clean_threshold: u64::MAX, /* Forces updating the root instead of calculating
-                                        * from
-                                        * scratch */
+        // The largest threshold forces updating the imported trie, and the smallest rebuilding
+        // it from the hashed state.
+        let clean_threshold = if incremental { u64::MAX } else { 0 };
+        let output = MerkleStage::Execution { clean_threshold }
+            .execute(
+                &provider,
+                reth_stages::ExecInput {
+                    target: Some(to),
+                    checkpoint: Some(StageCheckpoint::new(from)),
+                },
+            )
+            .await?;
+        if let Some(progress) = progress {
+            progress.on_dry_run_block(StageId::MerkleExecute, output.checkpoint.block_number);
         }
-        .execute(
-            &provider,
-            reth_stages::ExecInput {
-                target: Some(to),
-                checkpoint: Some(StageCheckpoint::new(from)),
-            },
-        )
-        .await?
-        .done;
+        exec_output = output.done;
+    }
+
+    // The stage only succeeds if the computed root matches the header.
+    let state_root = provider
+        .tx_ref()
+        .get::<tables::Headers>(to)?
+        .ok_or_else(|| eyre::eyre!("Header {to} does not exist."))?
+        .state_root;
+    if let Some(reference) = reference {
+        reference.compare(StageId::MerkleExecute, provider.tx_ref())?;
     }
 
-    info!(target: "reth::cli", "Success.");
+    info!(target: "reth::cli", stage = %StageId::MerkleExecute, from, to, %state_root, "Success.");
 
-    Ok(())
+    Ok(state_root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{models::AccountBeforeTx, test_utils::create_test_rw_db, transaction::DbTxMut};
+    use reth_primitives::{Address, Header, StorageEntry};
+
+    #[test]
+    fn trie_boundary() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        for i in 0..256u64 {
+            tx.put::<tables::HashedAccount>(
+                keccak256(i.to_be_bytes()),
+                Account { nonce: i, balance: U256::from(i), bytecode_hash: None },
+            )
+            .unwrap();
+        }
+        let (root, updates) = StateRoot::new(&tx).root_with_updates().unwrap();
+        updates.flush(&tx).unwrap();
+        tx.put::<tables::Headers>(1, Header { state_root: root, ..Default::default() }).unwrap();
+        tx.put::<tables::Headers>(2, Header { state_root: B256::ZERO, ..Default::default() })
+            .unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.tx().unwrap();
+        check_trie_boundary(&tx, 1).unwrap();
+        // The trie of block 1 is stale at block 2.
+        assert!(check_trie_boundary(&tx, 2).is_err());
+        assert!(check_trie_boundary(&tx, 3).is_err());
+    }
+
+    #[test]
+    fn range_subtries() {
+        let account = |nonce| Account { nonce, balance: U256::ZERO, bytecode_hash: None };
+        let changed = [Address::with_last_byte(3), Address::with_last_byte(200)];
+
+        let source = create_test_rw_db();
+        let tx = source.tx_mut().unwrap();
+        for i in 0..=255 {
+            tx.put::<tables::HashedAccount>(keccak256(Address::with_last_byte(i)), account(1))
+                .unwrap();
+        }
+        let (_, updates) = StateRoot::new(&tx).root_with_updates().unwrap();
+        updates.flush(&tx).unwrap();
+        for address in changed {
+            tx.put::<tables::AccountChangeSet>(
+                1,
+                AccountBeforeTx { address, info: Some(account(1)) },
+            )
+            .unwrap();
+        }
+        tx.commit().unwrap();
+
+        let output = create_test_rw_db();
+        import_range_subtries(&output, &source.tx().unwrap(), 0, 1).unwrap();
+        let tx = output.tx_mut().unwrap();
+        let imported = tx.entries::<tables::AccountsTrie>().unwrap();
+        assert!(imported > 0);
+        assert!(imported < source.tx().unwrap().entries::<tables::AccountsTrie>().unwrap());
+
+        // The imported nodes are enough to update the trie to the state after the range.
+        let mut prefixes = PrefixSetMut::default();
+        for i in 0..=255 {
+            let address = Address::with_last_byte(i);
+            let nonce = if changed.contains(&address) { 2 } else { 1 };
+            tx.put::<tables::HashedAccount>(keccak256(address), account(nonce)).unwrap();
+        }
+        for address in changed {
+            prefixes.insert(Nibbles::unpack(keccak256(address)));
+        }
+        let updated =
+            StateRoot::new(&tx).with_changed_account_prefixes(prefixes.freeze()).root().unwrap();
+        tx.clear::<tables::AccountsTrie>().unwrap();
+        assert_eq!(updated, StateRoot::new(&tx).root().unwrap());
+    }
+
+    /// Writes the hashed state of a block, with an optional storage slot of `storage.0`,
+    /// returning its root.
+    fn put_state<TX: DbTxMut + DbTx>(
+        tx: &TX,
+        accounts: &[(Address, u64)],
+        storage: Option<(Address, B256, u64)>,
+    ) -> B256 {
+        tx.clear::<tables::HashedAccount>().unwrap();
+        tx.clear::<tables::HashedStorage>().unwrap();
+        for (address, nonce) in accounts {
+            tx.put::<tables::HashedAccount>(
+                keccak256(address),
+                Account { nonce: *nonce, balance: U256::ZERO, bytecode_hash: None },
+            )
+            .unwrap();
+        }
+        if let Some((address, slot, value)) = storage {
+            tx.put::<tables::HashedStorage>(
+                keccak256(address),
+                StorageEntry { key: keccak256(slot), value: U256::from(value) },
+            )
+            .unwrap();
+        }
+        StateRoot::new(tx).root().unwrap()
+    }
+
+    #[test]
+    fn roots_of_every_block() {
+        let account = |nonce| Account { nonce, balance: U256::ZERO, bytecode_hash: None };
+        let (a, b) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let slot = B256::with_last_byte(1);
+
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        // Block 1 bumps `a` and creates `b`, block 2 bumps `a` and writes the storage of `b`.
+        let root_1 = put_state(&tx, &[(a, 2), (b, 1)], None);
+        put_state(&tx, &[(a, 1)], None);
+        let (_, updates) = StateRoot::new(&tx).root_with_updates().unwrap();
+        updates.flush(&tx).unwrap();
+        let root_2 = put_state(&tx, &[(a, 3), (b, 1)], Some((b, slot, 5)));
+        for (block, address, info) in
+            [(1, a, Some(account(1))), (1, b, None), (2, a, Some(account(2)))]
+        {
+            tx.put::<tables::AccountChangeSet>(block, AccountBeforeTx { address, info }).unwrap();
+        }
+        tx.put::<tables::StorageChangeSet>(
+            (2, b).into(),
+            StorageEntry { key: slot, value: U256::ZERO },
+        )
+        .unwrap();
+        tx.put::<tables::Headers>(1, Header { state_root: root_1, ..Default::default() }).unwrap();
+        // The stored root of block 2 diverges.
+        tx.put::<tables::Headers>(2, Header { state_root: B256::ZERO, ..Default::default() })
+            .unwrap();
+        tx.commit().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("roots.csv");
+        write_roots_csv(&db.tx().unwrap(), 0, 2, &path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!(
+                "block_number,computed_root,stored_root,matches\n1,{root_1:?},{root_1:?},true\n2,{root_2:?},{:?},false\n",
+                B256::ZERO
+            )
+        );
+    }
 }
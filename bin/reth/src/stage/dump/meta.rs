@@ -0,0 +1,98 @@
+//! Dump of the node metadata: where every stage stopped and how the node prunes.
+use super::{log_imported_rows, write_chain_spec, ExtractManifest};
+use crate::utils::DbTool;
+use clap::Parser;
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use eyre::Result;
+use reth_db::{
+    cursor::DbCursorRO, database::Database, init_db, table::TableImporter, tables,
+    transaction::DbTx, DatabaseEnv,
+};
+use reth_primitives::stage::StageId;
+use std::path::PathBuf;
+use tracing::info;
+
+/// Dumps the stage checkpoints and the prune checkpoints, regardless of the sync height.
+#[derive(Debug, Clone, Parser)]
+pub struct MetaCommand {
+    /// The path to the new database folder. A relative path is resolved against `--output-base`.
+    #[arg(long, value_name = "OUTPUT_PATH", verbatim_doc_comment)]
+    pub(crate) output_db: PathBuf,
+}
+
+pub(crate) fn dump_meta<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &MetaCommand,
+) -> Result<()> {
+    info!(
+        target: "reth::cli",
+        output_path = ?command.output_db,
+        chain = %db_tool.chain.chain,
+        "Creating separate db"
+    );
+
+    let output_db = init_db(&command.output_db, None)?;
+    write_chain_spec(&command.output_db, &db_tool.chain)?;
+    ExtractManifest::write(&command.output_db)?;
+
+    output_db.update(|tx| tx.import_table::<tables::SyncStage, _>(&db_tool.db.tx()?))??;
+    output_db.update(|tx| tx.import_table::<tables::SyncStageProgress, _>(&db_tool.db.tx()?))??;
+    output_db.update(|tx| tx.import_table::<tables::PruneCheckpoints, _>(&db_tool.db.tx()?))??;
+
+    log_imported_rows(&output_db, StageId::Other("Meta"))?;
+
+    print_summary(&output_db)
+}
+
+/// Prints the imported checkpoints.
+fn print_summary(output_db: &DatabaseEnv) -> Result<()> {
+    output_db.view(|tx| {
+        let mut stages_table = ComfyTable::new();
+        stages_table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+        stages_table.set_header(["Stage", "Checkpoint", "Entities", "Intermediate Progress"]);
+
+        for entry in tx.cursor_read::<tables::SyncStage>()?.walk(None)? {
+            let (stage, checkpoint) = entry?;
+            let progress = tx.get::<tables::SyncStageProgress>(stage.clone())?;
+
+            let mut row = Row::new();
+            row.add_cell(Cell::new(&stage))
+                .add_cell(Cell::new(checkpoint.block_number))
+                .add_cell(Cell::new(
+                    checkpoint.entities().map(|entities| entities.to_string()).unwrap_or_default(),
+                ))
+                .add_cell(Cell::new(match progress {
+                    Some(progress) if !progress.is_empty() => "yes",
+                    _ => "no",
+                }));
+            stages_table.add_row(row);
+        }
+
+        let mut prune_table = ComfyTable::new();
+        prune_table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+        prune_table.set_header(["Segment", "Mode", "Pruned Block", "Pruned Transaction"]);
+
+        for entry in tx.cursor_read::<tables::PruneCheckpoints>()?.walk(None)? {
+            let (segment, checkpoint) = entry?;
+
+            let mut row = Row::new();
+            row.add_cell(Cell::new(segment))
+                .add_cell(Cell::new(format!("{:?}", checkpoint.prune_mode)))
+                .add_cell(Cell::new(
+                    checkpoint.block_number.map(|block| block.to_string()).unwrap_or_default(),
+                ))
+                .add_cell(Cell::new(
+                    checkpoint.tx_number.map(|tx| tx.to_string()).unwrap_or_default(),
+                ));
+            prune_table.add_row(row);
+        }
+
+        println!("{stages_table}");
+        println!();
+        println!("{prune_table}");
+
+        Ok::<(), eyre::Report>(())
+    })??;
+
+    Ok(())
+}
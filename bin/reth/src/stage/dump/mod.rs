@@ -1,16 +1,36 @@
 //! Database debugging tool
 use crate::{
     dirs::{DataDirPath, MaybePlatformPath},
-    utils::DbTool,
+    utils::{open_db_with_timeout, DbTool},
 };
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use comfy_table::Table as ComfyTable;
 use reth_db::{
-    cursor::DbCursorRO, database::Database, init_db, table::TableImporter, tables,
-    transaction::DbTx, DatabaseEnv,
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW},
+    database::Database,
+    init_db_with_sync_mode,
+    mdbx::SyncMode,
+    open_db_read_only,
+    table::{Decode, DupSort, Table, TableImporter},
+    tables,
+    transaction::{DbTx, DbTxMut},
+    DatabaseEnv, DatabaseError, RawDupSort, RawKey, TableViewer, Tables,
 };
-use reth_primitives::ChainSpec;
-use std::{path::PathBuf, sync::Arc};
-use tracing::info;
+use reth_primitives::{
+    stage::StageId, BlockNumber, ChainSpec, TxNumber, B256, DEV, GOERLI, HOLESKY, KECCAK_EMPTY,
+    MAINNET, SEPOLIA,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt,
+    future::Future,
+    hash::Hash,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::{info, warn};
 
 mod hashing_storage;
 use hashing_storage::dump_hashing_storage_stage;
@@ -22,9 +42,88 @@ mod execution;
 use execution::dump_execution_stage;
 
 mod merkle;
-use crate::args::{utils::genesis_value_parser, DatabaseArgs};
+use crate::args::{
+    utils::{genesis_value_parser, EMBEDDED_CHAIN_SPEC_FILE},
+    ChainOverrideArgs, DatabaseArgs,
+};
 use merkle::dump_merkle_stage;
 
+mod senders;
+use senders::dump_senders_stage;
+
+mod tx_lookup;
+use tx_lookup::dump_tx_lookup_stage;
+
+mod index_account_history;
+use index_account_history::dump_index_account_history_stage;
+
+mod index_storage_history;
+use index_storage_history::dump_index_storage_history_stage;
+
+mod meta;
+use meta::{dump_meta, MetaCommand};
+
+mod alloc;
+use alloc::{export_alloc, ExportAllocCommand};
+
+mod archive;
+pub(crate) use archive::load_archive;
+use archive::{unpack_archive, write_archive, ARCHIVE_FILE};
+
+mod diff;
+use diff::{export_state_diff, StateDiffCommand};
+
+mod block;
+use block::{dump_block, BlockCommand};
+
+mod report;
+pub(crate) use report::DumpReport;
+use report::MismatchError;
+
+mod progress;
+use progress::{DumpProgress, LogProgress};
+
+mod profile;
+use profile::{profile_dry_run, profile_single, DryRunProfile, Profiled};
+
+mod files;
+#[cfg(feature = "remote-db")]
+pub(crate) use files::{read_record, write_record};
+
+mod filter;
+use filter::prune_unmatched_blocks;
+pub use filter::BlockFilter;
+
+mod forks;
+
+mod reference;
+use reference::ReferenceDb;
+
+mod revert;
+
+mod manifest;
+pub(crate) use manifest::{DumpedStage, ExtractManifest};
+
+mod prune;
+use prune::prune_output;
+
+mod scratch;
+pub(crate) use scratch::ScratchDirs;
+
+mod source;
+
+mod self_test;
+use self_test::{run_self_test, SelfTestCommand};
+
+mod split;
+use split::dump_split;
+
+mod trace;
+use trace::{write_traces, TraceLevel};
+
+mod verify;
+use verify::{verify_keys, verify_table, DeepVerification};
+
 /// `reth dump-stage` command
 #[derive(Debug, Parser)]
 pub struct Command {
@@ -40,7 +139,8 @@ pub struct Command {
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -56,15 +156,139 @@ pub struct Command {
     )]
     chain: Arc<ChainSpec>,
 
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    chain_overrides: ChainOverrideArgs,
+
+    /// Dumps even if the genesis of the database doesn't match `--chain`, e.g. to inspect a
+    /// foreign database.
+    ///
+    /// The tables are then interpreted with the rules of another chain, so the results may be
+    /// nonsensical.
+    #[arg(long)]
+    force_chain: bool,
+
+    /// The maximum number of seconds to wait for the source database to open, e.g. while a
+    /// running node holds its lock. Waits indefinitely if not passed.
+    ///
+    /// The dump only reads the source database, so it can also be opened with `--db.read-only`,
+    /// except for a merkle dump with `--with-trie`.
+    #[arg(long, value_name = "SECONDS", verbatim_doc_comment)]
+    open_timeout: Option<u64>,
+
+    /// The directory a relative `--output-db` path is resolved against.
+    ///
+    /// Defaults to the data dir of the chain, e.g. `$HOME/.local/share/reth/mainnet/`.
+    #[arg(long, value_name = "PATH")]
+    output_base: Option<PathBuf>,
+
+    /// The directory the temporary databases and files of the dump are created in. They are
+    /// removed once the dump finishes, even if it fails.
+    ///
+    /// Defaults to the system temp dir.
+    #[arg(long, value_name = "PATH", verbatim_doc_comment)]
+    scratch_dir: Option<PathBuf>,
+
     #[clap(flatten)]
     db: DatabaseArgs,
 
     #[clap(subcommand)]
-    command: Stages,
+    command: Subcommands,
+}
+
+/// How the writes to the output database of a dump are flushed to disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputDurability {
+    /// Every commit is flushed to disk before it returns.
+    #[default]
+    Safe,
+    /// Commits are never flushed, the OS writes them back whenever it wants. A system crash may
+    /// corrupt the output database.
+    #[value(name = "nosync")]
+    NoSync,
+    /// Commits are flushed asynchronously. A system crash may only lose the last commits.
+    Async,
+}
+
+/// How the output of a dump is stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum DumpFormat {
+    /// An MDBX environment, which can be opened like any reth database.
+    #[default]
+    Mdbx,
+    /// One file per table, holding its raw key/value records, and an `index.json` listing them.
+    Files,
+    /// The files of the `files` format, packed into a single zstd compressed tar archive. It's
+    /// loaded back into a database with `reth stage load`.
+    TarZstd,
+}
+
+impl From<OutputDurability> for SyncMode {
+    fn from(durability: OutputDurability) -> Self {
+        match durability {
+            OutputDurability::Safe => SyncMode::Durable,
+            OutputDurability::NoSync => SyncMode::UtterlyNoSync,
+            OutputDurability::Async => SyncMode::SafeNoSync,
+        }
+    }
+}
+
+/// `reth stage dump` subcommands
+#[derive(Debug, Clone, Subcommand)]
+pub enum Subcommands {
+    /// Stage over a range of blocks.
+    #[command(flatten)]
+    Stage(Stages),
+    /// Stage checkpoints and prune checkpoints, to see how the node was configured and where
+    /// every stage stopped.
+    Meta(MetaCommand),
+    /// Accounts of the state at a block, as the `alloc` of a genesis file.
+    ExportAlloc(ExportAllocCommand),
+    /// Net changes of the state between two blocks, merged from the changesets.
+    StateDiff(StateDiffCommand),
+    /// A single block, with only the state its execution reads.
+    Block(BlockCommand),
+    /// Every dumper with its dry-run, against a synthetic chain built in a scratch database, to
+    /// check the build end-to-end without a node.
+    SelfTest(SelfTestCommand),
 }
 
 /// Supported stages to be dumped
-#[derive(Debug, Clone, Parser)]
+///
+/// Every stage supports `--validate-only`: the dry-run executes inside a write transaction that is
+/// never committed, so the output database only ever holds the imported source tables. It can't be
+/// combined with `--resumable`. On top of the checks each stage performs by itself, the derived
+/// output of every dry-run is compared against the imported source of truth:
+///
+/// - Execution: receipts root and gas used are checked against the imported headers by the stage.
+/// - StorageHashing: `HashedStorage` is compared against the imported `PlainStorageState`.
+/// - AccountHashing: `HashedAccount` is compared against the imported `PlainAccountState`.
+/// - Merkle: the computed state root is checked against the imported `Headers` by the stage.
+/// - Senders: the senders are recovered again and compared against the imported `TxSenders`.
+/// - TransactionLookup: `TxHashNumber` is checked against the imported `Transactions` and the
+///   source lookup.
+/// - IndexAccountHistory: the `AccountHistory` of the changed accounts is compared against the
+///   source history.
+/// - IndexStorageHistory: the `StorageHistory` of the changed slots is compared against the source
+///   history.
+///
+/// The hashing stages report every differing entry, up to `--max-mismatches`, and the blocks of the
+/// imported changesets that touched them.
+///
+/// A dry-run is never committed, except the `--resumable` one of the execution stage, which commits
+/// its state and checkpoint every 1000 blocks and leaves the output database at `--to`. Otherwise
+/// the tables a dry-run writes are always rolled back, leaving a source-only extract:
+///
+/// - Execution: `PlainAccountState`, `PlainStorageState`, `Bytecodes`, `AccountChangeSet`,
+///   `StorageChangeSet` and `Receipts`.
+/// - StorageHashing: `HashedStorage`.
+/// - AccountHashing: `HashedAccount`.
+/// - Merkle: `AccountsTrie`, `StoragesTrie` and `SyncStageProgress`.
+/// - Senders: none, the senders are only compared in memory.
+/// - TransactionLookup: `TxHashNumber`.
+/// - IndexAccountHistory: `AccountHistory`.
+/// - IndexStorageHistory: `StorageHistory`.
+#[derive(Debug, Clone, Subcommand)]
 pub enum Stages {
     /// Execution stage.
     Execution(StageCommand),
@@ -74,82 +298,2143 @@ pub enum Stages {
     AccountHashing(StageCommand),
     /// Merkle stage.
     Merkle(StageCommand),
+    /// Senders of the transactions, as recovered by the SenderRecovery stage.
+    Senders(StageCommand),
+    /// TransactionLookup stage.
+    TransactionLookup(StageCommand),
+    /// IndexAccountHistory stage.
+    IndexAccountHistory(StageCommand),
+    /// IndexStorageHistory stage.
+    IndexStorageHistory(StageCommand),
+}
+
+impl Stages {
+    /// The name of the stage, as used on the command line.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Stages::Execution(_) => "execution",
+            Stages::StorageHashing(_) => "storage-hashing",
+            Stages::AccountHashing(_) => "account-hashing",
+            Stages::Merkle(_) => "merkle",
+            Stages::Senders(_) => "senders",
+            Stages::TransactionLookup(_) => "transaction-lookup",
+            Stages::IndexAccountHistory(_) => "index-account-history",
+            Stages::IndexStorageHistory(_) => "index-storage-history",
+        }
+    }
+
+    /// The tables imported from the source database to dump the stage, e.g. the ones holding the
+    /// input of its dry-run.
+    ///
+    /// Every stage imports the `BlockBodyIndices` of its range, and the stages which check their
+    /// output against the headers import them too.
+    pub fn required_tables(&self) -> &[&str] {
+        match self {
+            Stages::Execution(_) => &[
+                tables::CanonicalHeaders::NAME,
+                tables::HeaderTD::NAME,
+                tables::Headers::NAME,
+                tables::BlockBodyIndices::NAME,
+                tables::BlockOmmers::NAME,
+                tables::BlockWithdrawals::NAME,
+                tables::Transactions::NAME,
+                tables::TxSenders::NAME,
+                tables::PlainAccountState::NAME,
+                tables::PlainStorageState::NAME,
+                tables::Bytecodes::NAME,
+            ],
+            Stages::StorageHashing(_) => &[
+                tables::BlockBodyIndices::NAME,
+                tables::PlainStorageState::NAME,
+                tables::StorageChangeSet::NAME,
+            ],
+            Stages::AccountHashing(_) => &[
+                tables::BlockBodyIndices::NAME,
+                tables::PlainAccountState::NAME,
+                tables::AccountChangeSet::NAME,
+            ],
+            // The trie is updated from the hashed state, so the plain state isn't needed.
+            Stages::Merkle(_) => &[
+                tables::CanonicalHeaders::NAME,
+                tables::HeaderTD::NAME,
+                tables::Headers::NAME,
+                tables::BlockBodyIndices::NAME,
+                tables::AccountChangeSet::NAME,
+                tables::StorageChangeSet::NAME,
+                tables::HashedAccount::NAME,
+                tables::HashedStorage::NAME,
+                tables::AccountsTrie::NAME,
+                tables::StoragesTrie::NAME,
+            ],
+            Stages::Senders(_) => &[
+                tables::BlockBodyIndices::NAME,
+                tables::Transactions::NAME,
+                tables::TxSenders::NAME,
+            ],
+            Stages::TransactionLookup(_) => {
+                &[tables::BlockBodyIndices::NAME, tables::Transactions::NAME]
+            }
+            // Only the last shards of the changed keys are imported, see the dumpers.
+            Stages::IndexAccountHistory(_) => &[
+                tables::BlockBodyIndices::NAME,
+                tables::AccountChangeSet::NAME,
+                tables::AccountHistory::NAME,
+            ],
+            Stages::IndexStorageHistory(_) => &[
+                tables::BlockBodyIndices::NAME,
+                tables::StorageChangeSet::NAME,
+                tables::StorageHistory::NAME,
+            ],
+        }
+    }
+
+    /// The [`StageId`] whose checkpoint covers the dumped tables.
+    pub(crate) fn id(&self) -> StageId {
+        match self {
+            Stages::Execution(_) => StageId::Execution,
+            Stages::StorageHashing(_) => StageId::StorageHashing,
+            Stages::AccountHashing(_) => StageId::AccountHashing,
+            Stages::Merkle(_) => StageId::MerkleExecute,
+            Stages::Senders(_) => StageId::SenderRecovery,
+            Stages::TransactionLookup(_) => StageId::TransactionLookup,
+            Stages::IndexAccountHistory(_) => StageId::IndexAccountHistory,
+            Stages::IndexStorageHistory(_) => StageId::IndexStorageHistory,
+        }
+    }
+
+    /// Returns the [`StageCommand`] of the stage.
+    pub(crate) fn command(&self) -> &StageCommand {
+        match self {
+            Stages::Execution(command) |
+            Stages::StorageHashing(command) |
+            Stages::AccountHashing(command) |
+            Stages::Merkle(command) |
+            Stages::Senders(command) |
+            Stages::TransactionLookup(command) |
+            Stages::IndexAccountHistory(command) |
+            Stages::IndexStorageHistory(command) => command,
+        }
+    }
+
+    /// Returns the mutable [`StageCommand`] of the stage.
+    pub(crate) fn command_mut(&mut self) -> &mut StageCommand {
+        match self {
+            Stages::Execution(command) |
+            Stages::StorageHashing(command) |
+            Stages::AccountHashing(command) |
+            Stages::Merkle(command) |
+            Stages::Senders(command) |
+            Stages::TransactionLookup(command) |
+            Stages::IndexAccountHistory(command) |
+            Stages::IndexStorageHistory(command) => command,
+        }
+    }
 }
 
 /// Stage command that takes a range
 #[derive(Debug, Clone, Parser)]
 pub struct StageCommand {
-    /// The path to the new database folder.
+    /// The path to the new database folder. A relative path is resolved against `--output-base`.
+    ///
+    /// If `--output-db-name-template` is passed, this is the base directory the folder is
+    /// created in.
     #[arg(long, value_name = "OUTPUT_PATH", verbatim_doc_comment)]
     output_db: PathBuf,
 
+    /// Template of the new database folder name, created under `--output-db`.
+    ///
+    /// The placeholders `{stage}`, `{from}` and `{to}` are replaced by the dumped stage and
+    /// range. All of them are required, so that every dump gets its own folder.
+    /// e.g. `{stage}_{from}_{to}`
+    #[arg(long, value_name = "TEMPLATE", verbatim_doc_comment)]
+    output_db_name_template: Option<String>,
+
+    /// How the writes to the output database are flushed to disk.
+    ///
+    /// The extract can always be rebuilt from the source database, so bulk imports can trade
+    /// durability for speed. The output database is flushed once more when the dump closes it.
+    #[arg(long, value_enum, value_name = "DURABILITY", default_value_t = OutputDurability::Safe)]
+    output_durability: OutputDurability,
+
+    /// How the output is stored.
+    ///
+    /// With `files`, the dump and its dry-run go through a temporary MDBX environment inside the
+    /// output folder, whose tables are then written to their own files. Each file is a sequence of
+    /// `<u32 LE key length><key><u32 LE value length><value>` records, holding the raw database
+    /// bytes.
+    ///
+    /// With `tar-zstd`, these files are packed into `tables.tar.zst` inside the output folder,
+    /// along with the chain specification, instead. Load it into a new database with
+    /// `reth stage load` to dry-run it again.
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        default_value_t = DumpFormat::Mdbx,
+        verbatim_doc_comment
+    )]
+    format: DumpFormat,
+
+    /// If passed, the range is split into numbered output databases under `--output-db`, a new
+    /// one being started once the blocks of the current one reach this size in bytes.
+    ///
+    /// The size is estimated from the source rows of the blocks and transactions, so the state
+    /// imported at the start of every chunk comes on top of it. The chunks and their ranges are
+    /// listed in `manifest.json`. Every chunk is a complete extract, dry-run over its whole
+    /// range.
+    #[arg(
+        long,
+        value_name = "BYTES",
+        conflicts_with_all = ["format", "dry_run_from", "limit_duration"],
+        verbatim_doc_comment
+    )]
+    split_size: Option<u64>,
+
     /// From which block.
-    #[arg(long, short)]
-    from: u64,
+    #[arg(
+        id = "from",
+        long = "from",
+        short = 'f',
+        value_name = "FROM",
+        required_unless_present = "time_from"
+    )]
+    from_block: Option<u64>,
     /// To which block.
-    #[arg(long, short)]
+    #[arg(
+        id = "to",
+        long = "to",
+        short = 't',
+        value_name = "TO",
+        required_unless_present = "time_to"
+    )]
+    to_block: Option<u64>,
+    /// From the first block produced at or after this time, instead of `--from`.
+    ///
+    /// An RFC3339 timestamp in UTC, e.g. `2023-10-01T14:00:00Z`.
+    #[arg(long, value_name = "TIME", conflicts_with = "from", value_parser = humantime::parse_rfc3339_weak, verbatim_doc_comment)]
+    time_from: Option<SystemTime>,
+    /// To the last block produced at or before this time, instead of `--to`.
+    ///
+    /// An RFC3339 timestamp in UTC, e.g. `2023-10-01T15:00:00Z`.
+    #[arg(long, value_name = "TIME", conflicts_with = "to", value_parser = humantime::parse_rfc3339_weak, verbatim_doc_comment)]
+    time_to: Option<SystemTime>,
+    /// If passed, the range ends at the first block whose execution brings the gas used by the
+    /// blocks after `--from` to this cap, if that's before `--to`.
+    ///
+    /// This bounds the work of an execution dry-run by gas rather than by blocks, however dense
+    /// the blocks are. Only applies to the execution stage.
+    #[arg(long, value_name = "GAS", verbatim_doc_comment)]
+    max_gas: Option<u64>,
+    /// The first block of the range, resolved from `--from` or `--time-from`.
+    #[arg(skip)]
+    from: u64,
+    /// The last block of the range, resolved from `--to` or `--time-to`.
+    #[arg(skip)]
     to: u64,
     /// If passed, it will dry-run a stage execution from the newly created database right after
     /// dumping.
     #[arg(long, short, default_value = "false")]
     dry_run: bool,
+    /// The block the dry-run starts executing from, instead of `--from`.
+    ///
+    /// The blocks before it are still imported, so that a wider range provides context while only
+    /// the suspicious blocks are tested. Must lie within the imported range.
+    ///
+    /// The hashing and merkle stages derive their output from the state at `--from`, so they
+    /// reject any other start.
+    #[arg(long, value_name = "BLOCK", verbatim_doc_comment)]
+    dry_run_from: Option<u64>,
+    /// The directory holding the headers snapshots generated by `reth db snapshot`.
+    ///
+    /// Headers which are no longer in the source database are read from here.
+    #[arg(long, value_name = "SNAPSHOTS_DIR", default_value = ".")]
+    snapshots_dir: PathBuf,
+    /// If passed, it will dry-run the stage without keeping any of its derived output and compare
+    /// the result against the imported source tables. Implies `--dry-run`.
+    #[arg(long, default_value = "false")]
+    validate_only: bool,
+    /// The maximum number of differing entries logged by the hashing stages with
+    /// `--validate-only` and by `--compare-receipts`.
+    ///
+    /// All of them are still counted.
+    #[arg(long, value_name = "MAX_MISMATCHES", default_value_t = 10)]
+    max_mismatches: usize,
+    /// If passed, the receipts produced by the execution dry-run are compared against the
+    /// receipts of the source database, reporting the first difference of every transaction.
+    ///
+    /// Receipts pruned from the source database are skipped. Only supported by the execution
+    /// stage.
+    #[arg(long, default_value = "false", verbatim_doc_comment)]
+    compare_receipts: bool,
+    /// A reference database, e.g. an extract of another client or reth version, the tables
+    /// derived by the dry-run are compared against, on top of the source comparisons.
+    ///
+    /// Every row of the derived tables must match the reference. It must hold the same canonical
+    /// blocks as the source database at `--from` and `--to`. Not supported by the senders stage,
+    /// whose dry-run derives no tables.
+    #[arg(long, value_name = "PATH", verbatim_doc_comment)]
+    compare_against: Option<PathBuf>,
+    /// The database opened from `--compare-against`.
+    #[arg(skip)]
+    reference: Option<Arc<ReferenceDb>>,
+    /// If passed, the merkle dry-run also writes the state root of every block of the range to
+    /// this CSV file, as `block_number,computed_root,stored_root,matches`.
+    ///
+    /// The roots are computed from the trie imported by `--with-trie`, or else from the hashed
+    /// state, so every block is listed even if an earlier one diverges. Only supported by the
+    /// merkle stage.
+    #[arg(long, value_name = "PATH", verbatim_doc_comment)]
+    roots_csv: Option<PathBuf>,
+    /// If passed, the merkle dump also imports the nodes of `AccountsTrie` and `StoragesTrie` at
+    /// `--from` on the paths of the keys the range changes, and the dry-run updates that trie
+    /// incrementally, like a synced node does.
+    ///
+    /// Otherwise, the dry-run rebuilds the trie from the hashed state. Only supported by the
+    /// merkle stage. The trie is unwound in the source database, in a transaction which is never
+    /// committed, so the source can't be opened with `--db.read-only`.
+    #[arg(long, verbatim_doc_comment)]
+    with_trie: bool,
+    /// If passed, the execution dry-run first re-executes every block of the range on the
+    /// history of the source database and writes the traces of its transactions to this
+    /// directory, as JSON in `<block>/<index>-<hash>.json`.
+    ///
+    /// Opcode traces record every step and can take gigabytes per block, so keep the range small.
+    /// Only supported by the execution stage.
+    #[arg(long, value_name = "DIR", verbatim_doc_comment)]
+    trace_out: Option<PathBuf>,
+    /// The detail of the traces written to `--trace-out`.
+    #[arg(
+        long,
+        value_enum,
+        value_name = "LEVEL",
+        default_value_t = TraceLevel::Call,
+        requires = "trace_out"
+    )]
+    trace_level: TraceLevel,
+    /// If passed, the execution dry-run commits its state and checkpoint to the output database
+    /// every 1000 blocks, so that an interrupted dry-run continues from its last checkpoint when
+    /// the same command is run again, without dumping again.
+    ///
+    /// The output database is then left at `--to`, instead of holding the extract at `--from`.
+    /// Only supported by the execution stage.
+    #[arg(
+        long,
+        requires = "dry_run",
+        conflicts_with_all = ["validate_only", "dry_run_repeat", "format"],
+        verbatim_doc_comment
+    )]
+    resumable: bool,
+    /// If passed with `--resumable`, the checkpoint of an interrupted dry-run is discarded, and
+    /// the output database is dumped again from scratch.
+    #[arg(long, requires = "resumable")]
+    restart: bool,
+    /// The number of threads the dry-run is split across.
+    ///
+    /// Only supported by the stages whose blocks are validated independently of each other, i.e.
+    /// senders.
+    #[arg(
+        long,
+        value_name = "JOBS",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    dry_run_jobs: u64,
+    /// The number of times the dry-run is repeated against the output database, to measure the
+    /// variance of its duration.
+    ///
+    /// The dry-run never commits, so every iteration starts from the imported tables. The min,
+    /// median and max durations are printed once all of them are done.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u64).range(1..),
+        verbatim_doc_comment
+    )]
+    dry_run_repeat: u64,
+    /// The number of rows skipped in every table imported over the range, after seeking to the
+    /// start of the range.
+    ///
+    /// This produces an intentionally incomplete extract, so it disables the dry-run.
+    #[arg(long, value_name = "ROWS", default_value_t = 0, verbatim_doc_comment)]
+    row_offset: usize,
+    /// The maximum number of rows imported in every table imported over the range.
+    ///
+    /// This produces an intentionally incomplete extract, so it disables the dry-run.
+    #[arg(long, value_name = "ROWS", verbatim_doc_comment)]
+    row_limit: Option<usize>,
+    /// If passed, the bytecodes of the dumped accounts are imported too, making the state
+    /// extract self-contained.
+    ///
+    /// Only the code hashes reachable from the accounts are copied, not the whole code table.
+    /// Only applies to the account hashing stage: the execution stage always imports the
+    /// bytecodes of its accounts.
+    #[arg(long, verbatim_doc_comment)]
+    include_bytecodes: bool,
+    /// The first storage slot imported by key of the dupsort tables, e.g. to isolate some slots
+    /// of a large contract. Defaults to the lowest slot if only `--subkey-to` is passed.
+    ///
+    /// Only applies to the dupsort tables holding storage slots, i.e. `PlainStorageState`,
+    /// `StorageChangeSet` and `HashedStorage`, whose slots are hashed. Requires a single block
+    /// range, with `--to` being `--from` + 1.
+    ///
+    /// This produces an intentionally incomplete extract, so it disables the dry-run.
+    #[arg(long, value_name = "SLOT", verbatim_doc_comment)]
+    subkey_from: Option<B256>,
+    /// The last storage slot imported by key of the dupsort tables. Defaults to the highest slot
+    /// if only `--subkey-from` is passed.
+    ///
+    /// See `--subkey-from`.
+    #[arg(long, value_name = "SLOT", verbatim_doc_comment)]
+    subkey_to: Option<B256>,
+    /// If passed, only the blocks of the range matching this predicate are kept, e.g. to collect
+    /// interesting fixtures.
+    ///
+    /// Possible predicates:
+    /// - non-empty: blocks with at least one transaction
+    /// - gas_used>N: blocks which used more than N gas
+    /// - has_withdrawals: blocks with at least one withdrawal
+    ///
+    /// The rows of the other blocks are deleted from the tables keyed by block or transaction, so
+    /// the extract isn't contiguous and the dry-run is disabled. The state tables are untouched.
+    #[arg(long, value_name = "PREDICATE", verbatim_doc_comment)]
+    filter: Option<BlockFilter>,
+    /// If passed, a JSON summary of the dump and its dry-run is written to this path.
+    #[arg(long, value_name = "REPORT_PATH")]
+    report: Option<PathBuf>,
+    /// If passed, only errors are logged, to stderr, and the report of the dump is printed to
+    /// stdout as a single JSON line, e.g. to pipe it into another tool.
+    ///
+    /// Overrides the verbosity flags. The report is still written to `--report` if passed.
+    #[arg(long, verbatim_doc_comment)]
+    machine: bool,
+    /// If passed, the canonical hash of every dumped block is written to this path, as one
+    /// `<number> <hash>` line per block.
+    #[arg(long, value_name = "PATH")]
+    hashes_out: Option<PathBuf>,
+    /// The wall-clock budget of the whole dump, in seconds.
+    ///
+    /// Once exceeded, the table being imported is rolled back, leaving the tables imported so
+    /// far, and a partial report is written. The budget is checked before the dry-run starts, but
+    /// a running dry-run is not interrupted.
+    #[arg(long, value_name = "SECONDS", verbatim_doc_comment)]
+    limit_duration: Option<u64>,
+    /// Instant at which `--limit-duration` runs out, set once the dump starts.
+    #[arg(skip)]
+    deadline: Option<Instant>,
+    /// If passed, it will fail instead of warning when the stage has not committed a clean
+    /// checkpoint covering the range in the source database, e.g. because a node is running it.
+    #[arg(long, default_value = "false")]
+    strict: bool,
+    /// If passed, every imported row is read back from the output database and compared byte for
+    /// byte against its source, to catch silent write corruption or truncation.
+    ///
+    /// This reads every row twice, so it's slow. The total number of verified rows and their
+    /// combined hash are logged and added to the report.
+    #[arg(long)]
+    deep_verify: bool,
+    /// The rows verified so far by `--deep-verify`.
+    #[arg(skip)]
+    verification: Arc<Mutex<DeepVerification>>,
+    /// Shell command run once the dump succeeded, e.g. to hash or upload the extract.
+    ///
+    /// The environment of the command holds `RETH_DUMP_OUTPUT`, `RETH_DUMP_STAGE`,
+    /// `RETH_DUMP_FROM` and `RETH_DUMP_TO`. Its output is logged, and the dump fails if it exits
+    /// with a non-zero status. Only available in builds with the `dump-post-cmd` feature.
+    #[cfg(feature = "dump-post-cmd")]
+    #[arg(long, value_name = "CMD", verbatim_doc_comment)]
+    post_cmd: Option<String>,
+    /// If passed, the files of the output are made read-only once the dump succeeded, so that a
+    /// finished extract can't be modified by accident.
+    ///
+    /// The extract can still be read by `reth db` and dry-runs of `reth stage`, but opening it
+    /// for writing fails.
+    #[arg(long, verbatim_doc_comment)]
+    output_db_readonly_after: bool,
+    /// If passed, the rows of the blocks outside of `--keep-from` and `--keep-to` are deleted
+    /// from the tables keyed by block or transaction once the dump and its dry-run are done, and
+    /// the output database is compacted.
+    ///
+    /// This turns a wide working extract into a small one to share. The state is left as dumped,
+    /// at `--from`, so the pruned extract can't be dry-run from `--keep-from`. The report still
+    /// describes the whole dumped range.
+    #[arg(
+        long,
+        requires_all = ["keep_from", "keep_to"],
+        conflicts_with = "split_size",
+        verbatim_doc_comment
+    )]
+    prune_output: bool,
+    /// The first block kept by `--prune-output`.
+    #[arg(long, value_name = "BLOCK", requires = "prune_output")]
+    keep_from: Option<u64>,
+    /// The last block kept by `--prune-output`.
+    #[arg(long, value_name = "BLOCK", requires = "prune_output")]
+    keep_to: Option<u64>,
+    /// If passed, a non-empty `--output-db` holding an earlier dump is removed before dumping.
+    ///
+    /// Otherwise the dump refuses to write into a folder which isn't empty, so that the rows of
+    /// several dumps are never mixed. Only a folder whose `extract.json` records a dumped stage
+    /// is removed, never e.g. the database of a node.
+    #[arg(long, conflicts_with = "append", verbatim_doc_comment)]
+    force: bool,
+    /// If passed, the extract in `--output-db` is extended to also cover the range of `--from`
+    /// and `--to`.
+    ///
+    /// Its manifest must be of the same chain, stage and database version, and its range must
+    /// overlap or adjoin the requested one. The state of an extract is taken at the start of its
+    /// range, so the whole extended range is dumped again, into `<OUTPUT_PATH>.append`. That
+    /// folder only replaces the extract once the dump succeeded.
+    #[arg(
+        long,
+        conflicts_with_all = ["output_db_name_template", "split_size", "format", "max_gas", "resumable"],
+        verbatim_doc_comment
+    )]
+    append: bool,
+}
+
+impl StageCommand {
+    /// Whether the stage should be executed against the output database after dumping.
+    ///
+    /// Incomplete extracts are never executed.
+    pub(crate) fn should_run(&self) -> bool {
+        (self.dry_run || self.validate_only) && !self.is_incomplete()
+    }
+
+    /// Whether `--row-offset`, `--row-limit`, `--filter` or the subkey range are leaving rows of
+    /// the range out.
+    pub(crate) fn is_incomplete(&self) -> bool {
+        self.row_offset > 0 ||
+            self.row_limit.is_some() ||
+            self.filter.is_some() ||
+            self.subkey_range().is_some()
+    }
+
+    /// The storage slots imported by key from `--subkey-from` and `--subkey-to`, if any of them
+    /// was passed.
+    pub(crate) fn subkey_range(&self) -> Option<RangeInclusive<B256>> {
+        (self.subkey_from.is_some() || self.subkey_to.is_some()).then(|| {
+            self.subkey_from.unwrap_or(B256::ZERO)..=
+                self.subkey_to.unwrap_or(B256::repeat_byte(0xff))
+        })
+    }
+
+    /// Resolves the block range from `--from` and `--to`, or from the headers timestamps.
+    fn resolve_range<DB: Database>(&mut self, db_tool: &DbTool<'_, DB>) -> eyre::Result<()> {
+        self.from = match (self.from_block, self.time_from) {
+            (Some(block), _) => block,
+            (None, Some(time)) => block_by_time(db_tool, time, TimeBound::AtOrAfter)?,
+            (None, None) => eyre::bail!("Either --from or --time-from is required."),
+        };
+        self.to = match (self.to_block, self.time_to) {
+            (Some(block), _) => block,
+            (None, Some(time)) => block_by_time(db_tool, time, TimeBound::AtOrBefore)?,
+            (None, None) => eyre::bail!("Either --to or --time-to is required."),
+        };
+
+        if self.time_from.is_some() || self.time_to.is_some() {
+            info!(target: "reth::cli", from = self.from, to = self.to, "Resolved block range from timestamps");
+        }
+
+        Ok(())
+    }
+
+    /// Moves the end of the range back to the first block at which the gas used by the blocks
+    /// after `from` reaches `max_gas`, summing up the gas used of their headers.
+    ///
+    /// The range always keeps at least one block to execute.
+    fn cap_range_by_gas<DB: Database>(
+        &mut self,
+        db_tool: &DbTool<'_, DB>,
+        max_gas: u64,
+    ) -> eyre::Result<()> {
+        if self.from >= self.to {
+            return Ok(())
+        }
+
+        let (from, to) = (self.from, self.to);
+        let mut gas_used = 0u64;
+        self.to = db_tool.db.view(|tx| {
+            for entry in tx.cursor_read::<tables::Headers>()?.walk_range(from + 1..=to)? {
+                let (block, header) = entry?;
+                gas_used = gas_used.saturating_add(header.gas_used);
+                if gas_used >= max_gas {
+                    return Ok(block)
+                }
+            }
+            Ok::<_, eyre::Report>(to)
+        })??;
+
+        info!(target: "reth::cli", from, to = self.to, requested_to = to, gas_used, max_gas, "Resolved block range from --max-gas");
+        Ok(())
+    }
+
+    /// Returns the block the dry-run starts executing from, making sure it lies within the
+    /// imported range.
+    pub(crate) fn dry_run_from(&self) -> eyre::Result<u64> {
+        let dry_run_from = self.dry_run_from.unwrap_or(self.from);
+        if !(self.from..self.to).contains(&dry_run_from) {
+            eyre::bail!(
+                "Dry-run start {dry_run_from} is outside of the imported range {}..{}.",
+                self.from,
+                self.to
+            )
+        }
+        Ok(dry_run_from)
+    }
+
+    /// The blocks kept by `--prune-output`, if passed.
+    fn keep_range(&self) -> Option<RangeInclusive<BlockNumber>> {
+        match (self.prune_output, self.keep_from, self.keep_to) {
+            (true, Some(from), Some(to)) => Some(from..=to),
+            _ => None,
+        }
+    }
+
+    /// The database of `--compare-against`, if passed.
+    pub(crate) fn reference(&self) -> Option<&ReferenceDb> {
+        self.reference.as_deref()
+    }
+
+    /// The rows verified so far by `--deep-verify`, if passed.
+    pub(crate) fn deep_verification(&self) -> Option<DeepVerification> {
+        self.deep_verify.then(|| *self.verification.lock().expect("not poisoned"))
+    }
+
+    /// Fails with [`DeadlineExceeded`] once `--limit-duration` ran out.
+    pub(crate) fn check_deadline(&self) -> eyre::Result<()> {
+        match (self.deadline, self.limit_duration) {
+            (Some(deadline), Some(seconds)) if Instant::now() >= deadline => {
+                Err(DeadlineExceeded(Duration::from_secs(seconds)).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Renders `--output-db-name-template`, if any, into the final output database path.
+    fn resolve_output_db(&mut self, stage: &str) -> eyre::Result<()> {
+        if let Some(template) = &self.output_db_name_template {
+            let name = render_output_db_name(template, stage, self.from, self.to)?;
+            self.output_db = self.output_db.join(name);
+        }
+        Ok(())
+    }
+}
+
+/// Which block [`block_by_time`] looks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimeBound {
+    /// The first block produced at or after the time.
+    AtOrAfter,
+    /// The last block produced at or before the time.
+    AtOrBefore,
+}
+
+/// Finds the block produced closest to `time` with a binary search over [`tables::Headers`],
+/// relying on timestamps strictly increasing with the block number.
+fn block_by_time<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    time: SystemTime,
+    bound: TimeBound,
+) -> eyre::Result<u64> {
+    let timestamp = time.duration_since(UNIX_EPOCH)?.as_secs();
+
+    db_tool.db.view(|tx| {
+        let mut cursor = tx.cursor_read::<tables::Headers>()?;
+        let (Some((first, first_header)), Some((last, last_header))) =
+            (cursor.first()?, cursor.last()?)
+        else {
+            eyre::bail!("No headers in database.")
+        };
+        if timestamp < first_header.timestamp || timestamp > last_header.timestamp {
+            eyre::bail!(
+                "Timestamp {timestamp} is outside of the synced range of blocks {first}..={last}, produced at {}..={}.",
+                first_header.timestamp,
+                last_header.timestamp
+            )
+        }
+
+        // Lowest block produced at or after the timestamp.
+        let (mut low, mut high) = (first, last);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let header = tx
+                .get::<tables::Headers>(middle)?
+                .ok_or_else(|| eyre::eyre!("Header {middle} does not exist."))?;
+            if header.timestamp < timestamp {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+
+        let at_or_after = tx
+            .get::<tables::Headers>(low)?
+            .ok_or_else(|| eyre::eyre!("Header {low} does not exist."))?;
+        Ok(match bound {
+            TimeBound::AtOrAfter => low,
+            TimeBound::AtOrBefore if at_or_after.timestamp == timestamp => low,
+            TimeBound::AtOrBefore => low - 1,
+        })
+    })?
+}
+
+/// Renders an output database folder name template, making sure the result is unique to the
+/// stage and range, and safe to use as a folder name.
+fn render_output_db_name(template: &str, stage: &str, from: u64, to: u64) -> eyre::Result<String> {
+    for placeholder in ["{stage}", "{from}", "{to}"] {
+        if !template.contains(placeholder) {
+            eyre::bail!(
+                "Output db name template {template:?} is missing {placeholder}, so it is not unique."
+            )
+        }
+    }
+
+    let name = template
+        .replace("{stage}", stage)
+        .replace("{from}", &from.to_string())
+        .replace("{to}", &to.to_string());
+
+    if name.contains(['{', '}']) {
+        eyre::bail!("Output db name template {template:?} has an unknown placeholder.")
+    }
+    if name == "." ||
+        name == ".." ||
+        !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        eyre::bail!(
+            "Output db name {name:?} is not a safe folder name. Only ASCII letters, digits, `-`, `_` and `.` are allowed."
+        )
+    }
+
+    Ok(name)
+}
+
+/// Resolves a relative `path` against `base`, leaving absolute paths unchanged.
+fn resolve_relative_path(path: &Path, base: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base.join(path)
+    }
 }
 
 impl Command {
+    /// Whether `--machine` was passed to a stage dump, which prints its report to stdout.
+    pub fn is_machine_output(&self) -> bool {
+        matches!(&self.command, Subcommands::Stage(stages) if stages.command().machine)
+    }
+
     /// Execute `dump-stage` command
-    pub async fn execute(self) -> eyre::Result<()> {
+    pub async fn execute(mut self) -> eyre::Result<()> {
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
+        if let Subcommands::SelfTest(command) = &self.command {
+            return run_self_test(command, &ScratchDirs::new(self.scratch_dir)).await
+        }
+
         // add network name to data dir
         let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
         let db_path = data_dir.db_path();
         info!(target: "reth::cli", path = ?db_path, "Opening database");
-        let db = Arc::new(init_db(db_path, self.db.log_level)?);
+        let db_args = self.db;
+        let db = Arc::new(open_db_with_timeout(
+            &db_path,
+            self.open_timeout.map(Duration::from_secs),
+            move |path| db_args.init_db(path),
+        )?);
         info!(target: "reth::cli", "Database opened");
 
         let tool = DbTool::new(&db, self.chain.clone())?;
+        check_chain(&tool, self.force_chain)?;
 
-        match &self.command {
-            Stages::Execution(StageCommand { output_db, from, to, dry_run, .. }) => {
-                dump_execution_stage(&tool, *from, *to, output_db, *dry_run).await?
-            }
-            Stages::StorageHashing(StageCommand { output_db, from, to, dry_run, .. }) => {
-                dump_hashing_storage_stage(&tool, *from, *to, output_db, *dry_run).await?
+        let output_base = self.output_base.unwrap_or_else(|| data_dir.as_ref().to_path_buf());
+        let scratch = ScratchDirs::new(self.scratch_dir);
+        let stages = match self.command {
+            Subcommands::Stage(stages) => stages,
+            Subcommands::ExportAlloc(command) => return export_alloc(&tool, &command).await,
+            Subcommands::StateDiff(command) => return export_state_diff(&tool, &command),
+            Subcommands::Block(mut command) => {
+                command.output_db = resolve_relative_path(&command.output_db, &output_base);
+                info!(target: "reth::cli", path = ?command.output_db, "Resolved output database path");
+                return dump_block(&tool, &command).await
             }
-            Stages::AccountHashing(StageCommand { output_db, from, to, dry_run, .. }) => {
-                dump_hashing_account_stage(&tool, *from, *to, output_db, *dry_run).await?
+            Subcommands::Meta(mut command) => {
+                command.output_db = resolve_relative_path(&command.output_db, &output_base);
+                info!(target: "reth::cli", path = ?command.output_db, "Resolved output database path");
+                return dump_meta(&tool, &command)
             }
-            Stages::Merkle(StageCommand { output_db, from, to, dry_run, .. }) => {
-                dump_merkle_stage(&tool, *from, *to, output_db, *dry_run).await?
+            Subcommands::SelfTest(_) => unreachable!("the self-test doesn't read the database"),
+        };
+        run_stages(&tool, stages, &output_base, &scratch).await
+    }
+}
+
+/// Dumps the stage of `stages` from the source database of `tool`, with its dry-run, checks and
+/// exports.
+pub(crate) async fn run_stages<DB: Database>(
+    tool: &DbTool<'_, DB>,
+    mut stages: Stages,
+    output_base: &Path,
+    scratch: &ScratchDirs,
+) -> eyre::Result<()> {
+    let name = stages.name();
+    let stage = stages.id();
+    let is_execution = matches!(stages, Stages::Execution(_));
+    let command = stages.command_mut();
+    command.resolve_range(tool)?;
+    if let Some(max_gas) = command.max_gas {
+        if !is_execution {
+            eyre::bail!("Only the execution stage can be capped with --max-gas.")
+        }
+        command.cap_range_by_gas(tool, max_gas)?;
+    }
+    command.output_db = resolve_relative_path(&command.output_db, output_base);
+    command.resolve_output_db(name)?;
+    info!(target: "reth::cli", path = ?command.output_db, "Resolved output database path");
+    let extended = prepare_output_db(tool, stage, command)?;
+    // The files are exported from a temporary environment, removed once the dump is done.
+    let mut output = command.output_db.clone();
+    let files_env = match command.format {
+        DumpFormat::Mdbx => None,
+        DumpFormat::Files | DumpFormat::TarZstd => {
+            std::fs::create_dir_all(&output)?;
+            let env = scratch.create("mdbx")?;
+            command.output_db = env.path().to_path_buf();
+            Some(env)
+        }
+    };
+    command.deadline =
+        command.limit_duration.map(|seconds| Instant::now() + Duration::from_secs(seconds));
+    if let Some(path) = &command.compare_against {
+        if !command.should_run() {
+            eyre::bail!("--compare-against requires a dry-run, with --dry-run or --validate-only.")
+        }
+        let reference =
+            ReferenceDb::open(path, tool, command.from, command.to, command.max_mismatches)?;
+        command.reference = Some(Arc::new(reference));
+    }
+
+    let command = stages.command();
+    command.dry_run_from()?;
+    if command.is_incomplete() && (command.dry_run || command.validate_only) {
+        warn!(target: "reth::cli", row_offset = command.row_offset, row_limit = ?command.row_limit, filter = ?command.filter, "Skipping the dry-run, since the extract is incomplete");
+    }
+    if command.subkey_range().is_some() && command.to != command.from + 1 {
+        eyre::bail!("--subkey-from and --subkey-to require a single block range, with --to being --from + 1.")
+    }
+    if command.resumable && !matches!(stages, Stages::Execution(_)) {
+        eyre::bail!("Only the execution dry-run can be resumed with --resumable.")
+    }
+    if command.compare_receipts && !matches!(stages, Stages::Execution(_)) {
+        eyre::bail!("Only the execution stage produces receipts to --compare-receipts.")
+    }
+    if command.roots_csv.is_some() && !matches!(stages, Stages::Merkle(_)) {
+        eyre::bail!("Only the merkle stage computes state roots to --roots-csv.")
+    }
+    if command.with_trie && !matches!(stages, Stages::Merkle(_)) {
+        eyre::bail!("Only the merkle stage imports the trie of --with-trie.")
+    }
+    if command.trace_out.is_some() && !matches!(stages, Stages::Execution(_)) {
+        eyre::bail!("Only the execution stage executes transactions to --trace-out.")
+    }
+    if command.compare_against.is_some() && matches!(stages, Stages::Senders(_)) {
+        eyre::bail!("The senders dry-run derives no tables to --compare-against.")
+    }
+    if command.compare_against.is_some() &&
+        matches!(stages, Stages::IndexAccountHistory(_) | Stages::IndexStorageHistory(_))
+    {
+        eyre::bail!(
+            "The {name} dry-run only derives the shards of the range, which can't be compared row by row with --compare-against."
+        )
+    }
+    if let Some(keep) = command.keep_range() {
+        if keep.is_empty() || *keep.start() < command.from || *keep.end() > command.to {
+            eyre::bail!(
+                "The range kept by --prune-output {}..={} must lie within the imported range {}..={}.",
+                keep.start(),
+                keep.end(),
+                command.from,
+                command.to
+            )
+        }
+    }
+    if command.dry_run_jobs > 1 && !matches!(stages, Stages::Senders(_)) {
+        eyre::bail!(
+            "The {name} stage depends on the state of previous blocks, so its dry-run can't be split with --dry-run-jobs."
+        )
+    }
+    check_stage_checkpoint(tool, stages.id(), command)?;
+    check_stale_blocks(tool, command)?;
+
+    let progress = Some(&LogProgress as &dyn DumpProgress);
+    let result = match command.split_size {
+        Some(split_size) => dump_split(tool, &stages, split_size, progress).await,
+        None => dump_stage(tool, &stages, progress).await,
+    };
+
+    let deadline_exceeded =
+        result.as_ref().err().and_then(|err| err.downcast_ref::<DeadlineExceeded>());
+    if let Some(DeadlineExceeded(budget)) = deadline_exceeded {
+        warn!(target: "reth::cli", stage = %stages.id(), ?budget, "Dump ran out of time, keeping the tables imported so far");
+        let output_db = open_db_read_only(&command.output_db, None)?;
+        let rows = log_imported_rows(&output_db, stages.id())?;
+        DumpReport::new(stages.id(), command, rows).partial().finish(command, None, progress)?;
+    } else if let Some(path) = &command.hashes_out {
+        write_block_hashes(tool, command, path)?;
+    }
+
+    if let Some(DeepVerification { rows, hash }) = command.deep_verification() {
+        info!(target: "reth::cli", stage = %stages.id(), rows, %hash, "Verified imported rows");
+    }
+
+    if let (Ok(()), Some(keep)) = (&result, command.keep_range()) {
+        prune_output(&command.output_db, command, keep)?;
+    }
+
+    if let Some(path) = extended {
+        if result.is_ok() {
+            replace_extract(&path, &command.output_db)?;
+            info!(target: "reth::cli", path = ?path, from = command.from, to = command.to, "Extended extract");
+            output = path;
+        } else {
+            warn!(target: "reth::cli", path = ?path, "Keeping the extract as it was, since the extended dump failed");
+            std::fs::remove_dir_all(&command.output_db)?;
+        }
+    }
+
+    if let Some(env) = files_env {
+        if result.is_ok() || deadline_exceeded.is_some() {
+            let output_db = open_db_read_only(&command.output_db, None)?;
+            if command.format == DumpFormat::TarZstd {
+                let archive = output.join(ARCHIVE_FILE);
+                write_archive(&output_db, &tool.chain, &archive, scratch)?;
+                if command.deep_verify {
+                    let unpacked = scratch.create("unpacked")?;
+                    unpack_archive(&archive, unpacked.path())?;
+                    files::verify_export(&output_db, unpacked.path(), scratch)?;
+                }
+            } else {
+                files::export_tables(&output_db, &output)?;
+                if command.deep_verify {
+                    files::verify_export(&output_db, &output, scratch)?;
+                }
+                write_chain_spec(&output, &tool.chain)?;
             }
+            ExtractManifest::write_stage(
+                &output,
+                DumpedStage::new(&tool.chain, stage, command.from, command.to),
+            )?;
         }
+        env.close()?;
+    }
 
-        Ok(())
+    #[cfg(feature = "dump-post-cmd")]
+    if let (Ok(()), Some(cmd)) = (&result, &command.post_cmd) {
+        run_post_cmd(cmd, name, command.from, command.to, &output)?;
     }
+
+    if result.is_ok() && command.output_db_readonly_after {
+        lock_output(&output)?;
+        info!(target: "reth::cli", path = ?output, "Locked output database. Make its files writable again to unlock it, e.g. with `chmod -R u+w`");
+    }
+
+    result
 }
 
-/// Sets up the database and initial state on [`tables::BlockBodyIndices`]. Also returns the tip
-/// block number.
+/// Runs `--post-cmd` through the shell, describing the dump of the blocks `from..=to` of `stage`
+/// into `output` in its environment.
+#[cfg(feature = "dump-post-cmd")]
+fn run_post_cmd(
+    cmd: &str,
+    stage: &str,
+    from: BlockNumber,
+    to: BlockNumber,
+    output: &Path,
+) -> eyre::Result<()> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = std::process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = std::process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+
+    info!(target: "reth::cli", cmd, "Running post-dump command");
+    let result = shell
+        .arg(cmd)
+        .env("RETH_DUMP_OUTPUT", output)
+        .env("RETH_DUMP_STAGE", stage)
+        .env("RETH_DUMP_FROM", from.to_string())
+        .env("RETH_DUMP_TO", to.to_string())
+        .output()?;
+    for line in String::from_utf8_lossy(&result.stdout).lines() {
+        info!(target: "reth::cli", "[post-cmd] {line}");
+    }
+    for line in String::from_utf8_lossy(&result.stderr).lines() {
+        warn!(target: "reth::cli", "[post-cmd] {line}");
+    }
+
+    if !result.status.success() {
+        eyre::bail!("Post-dump command `{cmd}` failed with {}.", result.status)
+    }
+
+    Ok(())
+}
+
+/// Makes every file under `path` read-only, except the MDBX lock files which readers still
+/// write to.
+fn lock_output(path: &Path) -> eyre::Result<()> {
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            lock_output(&entry.path())?;
+        } else if entry.file_name() != "mdbx.lck" {
+            let mut permissions = entry.metadata()?.permissions();
+            permissions.set_readonly(true);
+            std::fs::set_permissions(entry.path(), permissions)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the [`tables::CanonicalHeaders`] of the range to `path`, one `<number> <hash>` line per
+/// block.
+fn write_block_hashes<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &StageCommand,
+    path: &Path,
+) -> eyre::Result<()> {
+    let hashes = db_tool.db.view(|tx| {
+        let mut hashes = String::new();
+        for entry in
+            tx.cursor_read::<tables::CanonicalHeaders>()?.walk_range(command.from..=command.to)?
+        {
+            let (number, hash) = entry?;
+            hashes.push_str(&format!("{number} {hash}\n"));
+        }
+        Ok::<_, DatabaseError>(hashes)
+    })??;
+
+    std::fs::write(path, hashes)?;
+    info!(target: "reth::cli", from = command.from, to = command.to, path = ?path, "Wrote block hashes");
+
+    Ok(())
+}
+
+/// Makes sure the genesis of the source database is the one of `--chain`, unless `force` is set.
+fn check_chain<DB: Database>(db_tool: &DbTool<'_, DB>, force: bool) -> eyre::Result<()> {
+    let Some(stored) = db_tool.get::<tables::CanonicalHeaders>(0)? else { return Ok(()) };
+    let requested = db_tool.chain.genesis_hash();
+    if stored == requested {
+        return Ok(())
+    }
+
+    // The database doesn't hold its chain ID, so it's only known for the built-in chains.
+    let stored_chain = [&MAINNET, &GOERLI, &SEPOLIA, &HOLESKY, &DEV]
+        .into_iter()
+        .find(|spec| spec.genesis_hash() == stored)
+        .map_or_else(|| "unknown".to_string(), |spec| spec.chain.to_string());
+
+    if !force {
+        eyre::bail!(
+            "The database belongs to chain {stored_chain} with genesis {stored}, not to chain {} with genesis {requested}. Pass --force-chain to dump it anyway.",
+            db_tool.chain.chain
+        )
+    }
+
+    warn!(
+        target: "reth::cli",
+        %stored_chain,
+        %stored,
+        requested_chain = %db_tool.chain.chain,
+        %requested,
+        "Dumping a database of another chain than --chain, the results may be nonsensical"
+    );
+
+    Ok(())
+}
+
+/// Makes sure the source database holds a clean checkpoint of `stage` covering the range, since
+/// otherwise a running node may still be writing its tables.
+///
+/// Warns, or fails with `--strict`, naming the stage the pipeline is currently running.
+fn check_stage_checkpoint<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    stage: StageId,
+    command: &StageCommand,
+) -> eyre::Result<()> {
+    let checkpoints = db_tool.stage_checkpoints()?;
+    let checkpoint = checkpoints
+        .iter()
+        .find_map(|(id, checkpoint)| (*id == stage).then_some(checkpoint.block_number))
+        .unwrap_or_default();
+    let in_flight = db_tool.is_stage_in_flight(stage)?;
+
+    if checkpoint >= command.to && !in_flight {
+        return Ok(())
+    }
+
+    // Stages run in order, so the running one is the first that is behind its predecessor.
+    let running = checkpoints
+        .windows(2)
+        .find(|pair| pair[1].1.block_number < pair[0].1.block_number)
+        .map_or_else(|| "none".to_string(), |pair| pair[1].0.to_string());
+
+    if command.strict {
+        eyre::bail!(
+            "The {stage} stage has not committed a clean checkpoint up to block {}: it is at block {checkpoint}{}. The pipeline is currently running stage {running}.",
+            command.to,
+            if in_flight { " and in the middle of its range" } else { "" }
+        )
+    }
+
+    warn!(
+        target: "reth::cli",
+        %stage,
+        checkpoint,
+        to = command.to,
+        in_flight,
+        running_stage = %running,
+        "Stage has not committed a clean checkpoint covering the range, its tables may be in a transient state. Pass --strict to fail instead."
+    );
+
+    Ok(())
+}
+
+/// Warns if a header of the range predates the genesis of the chain, since it was then left behind
+/// by a previous incarnation of a network which periodically resets, and no longer matches it.
+fn check_stale_blocks<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &StageCommand,
+) -> eyre::Result<()> {
+    let genesis_timestamp = db_tool.chain.genesis.timestamp;
+    let stale = db_tool.db.view(|tx| {
+        tx.cursor_read::<tables::Headers>()?
+            .walk_range(command.from..=command.to)?
+            .find_map(|entry| match entry {
+                Ok((number, header)) if header.timestamp < genesis_timestamp => {
+                    Some(Ok((number, header.timestamp)))
+                }
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .transpose()
+    })??;
+
+    if let Some((block, timestamp)) = stale {
+        warn!(
+            target: "reth::cli",
+            block,
+            timestamp,
+            genesis_timestamp,
+            chain = %db_tool.chain.chain,
+            "Block predates the genesis of the chain, the source database holds stale data from before a network reset"
+        );
+    }
+
+    Ok(())
+}
+
+/// Makes sure the dump doesn't mix its rows with the ones already in `--output-db`.
+///
+/// A non-empty output database is only dumped into by a `--resumable` dry-run continuing from its
+/// checkpoint without `--force`, or once [`clear_output_db`] removed it. With `--append`, the
+/// range of the dump is widened to also cover the range of the extract, and the dump goes into a
+/// folder next to it. That folder replaces the extract with [`replace_extract`] once the dump
+/// succeeded, so a failed dump leaves the extract as it was. The path of the extract is returned
+/// then.
+fn prepare_output_db<DB: Database>(
+    tool: &DbTool<'_, DB>,
+    stage: StageId,
+    command: &mut StageCommand,
+) -> eyre::Result<Option<PathBuf>> {
+    let path = command.output_db.clone();
+    if command.append {
+        if is_empty_output(&path)? || !path.join("mdbx.dat").exists() {
+            eyre::bail!("There is no MDBX extract at {path:?} to append to.")
+        }
+        let dumped =
+            ExtractManifest::check_append(&path, &tool.chain, stage, command.from, command.to)?;
+        command.from = command.from.min(dumped.from);
+        command.to = command.to.max(dumped.to);
+
+        let staging = sibling_path(&path, "append")?;
+        if staging.exists() {
+            // left by a failed append
+            std::fs::remove_dir_all(&staging)?;
+        }
+        info!(target: "reth::cli", %stage, from = command.from, to = command.to, dumped_from = dumped.from, dumped_to = dumped.to, path = ?staging, "Extending extract, dumping the whole range again");
+        command.output_db = staging;
+        return Ok(Some(path))
+    }
+
+    if command.resumable &&
+        !command.force &&
+        !is_empty_output(&path)? &&
+        execution::dry_run_checkpoint(&path)?.is_some()
+    {
+        return Ok(None)
+    }
+    clear_output_db(&path, command.force)?;
+    Ok(None)
+}
+
+/// Fails unless the folder at `path` is missing or empty, or `force` is passed and it holds an
+/// earlier dump, which is then removed.
+///
+/// Only a folder whose [`ExtractManifest`] records a dumped stage counts as an earlier dump, so
+/// that e.g. the database of a node is never removed.
+pub(crate) fn clear_output_db(path: &Path, force: bool) -> eyre::Result<()> {
+    if is_empty_output(path)? {
+        return Ok(())
+    }
+    if !force {
+        eyre::bail!("The output database {path:?} is not empty. Pass --force to replace the earlier dump in it.")
+    }
+    if !ExtractManifest::is_stage_extract(path)? {
+        eyre::bail!(
+            "{path:?} doesn't hold an earlier dump with a stage in its {}, refusing to remove it with --force.",
+            manifest::EXTRACT_MANIFEST_FILE
+        )
+    }
+    warn!(target: "reth::cli", path = ?path, "Removing the earlier dump in the output database");
+    std::fs::remove_dir_all(path)?;
+    Ok(())
+}
+
+/// Whether there is nothing at `path` a dump could mix its rows with.
+fn is_empty_output(path: &Path) -> eyre::Result<bool> {
+    Ok(!path.is_dir() || std::fs::read_dir(path)?.next().is_none())
+}
+
+/// Returns the path next to `path`, with `extension` appended to its name.
+fn sibling_path(path: &Path, extension: &str) -> eyre::Result<PathBuf> {
+    let mut name = path
+        .file_name()
+        .ok_or_else(|| eyre::eyre!("The output database {path:?} has no folder name."))?
+        .to_os_string();
+    name.push(".");
+    name.push(extension);
+    Ok(path.with_file_name(name))
+}
+
+/// Replaces the extract at `path` with the extended one `--append` dumped into `staging`.
+fn replace_extract(path: &Path, staging: &Path) -> eyre::Result<()> {
+    let old = sibling_path(path, "old")?;
+    if old.exists() {
+        std::fs::remove_dir_all(&old)?;
+    }
+    std::fs::rename(path, &old)?;
+    std::fs::rename(staging, path)?;
+    std::fs::remove_dir_all(&old)?;
+    Ok(())
+}
+
+/// Opens the output database with `--output-durability`, and sets up the initial state on
+/// [`tables::BlockBodyIndices`], from the block given by [`body_indices_start`]. Also returns the
+/// tip block number.
 pub(crate) fn setup<DB: Database>(
-    from: u64,
-    to: u64,
-    output_db: &PathBuf,
+    stage: StageId,
+    command: &StageCommand,
     db_tool: &DbTool<'_, DB>,
 ) -> eyre::Result<(DatabaseEnv, u64)> {
+    let (from, to) = (command.from, command.to);
     assert!(from < to, "FROM block should be bigger than TO block.");
 
-    info!(target: "reth::cli", ?output_db, "Creating separate db");
+    info!(
+        target: "reth::cli",
+        %stage,
+        from,
+        to,
+        output_path = ?command.output_db,
+        durability = ?command.output_durability,
+        chain = %db_tool.chain.chain,
+        "Creating separate db"
+    );
 
-    let output_db = init_db(output_db, None)?;
+    let output_db =
+        init_db_with_sync_mode(&command.output_db, None, command.output_durability.into())?;
+    write_chain_spec(&command.output_db, &db_tool.chain)?;
+    ExtractManifest::write_stage(
+        &command.output_db,
+        DumpedStage::new(&db_tool.chain, stage, from, to),
+    )?;
 
     output_db.update(|tx| {
         tx.import_table_with_range::<tables::BlockBodyIndices, _>(
             &db_tool.db.tx()?,
-            Some(from - 1),
+            Some(body_indices_start(stage, from)),
             to + 1,
         )
     })??;
 
-    let (tip_block_number, _) =
-        db_tool.db.view(|tx| tx.cursor_read::<tables::BlockBodyIndices>()?.last())??.expect("some");
+    if reads_transactions(stage) {
+        if let Some(gap) = output_db.view(|tx| check_body_indices_boundary(tx, from))?? {
+            warn!(target: "reth::cli", %stage, from, %gap, "Imported block body indices are inconsistent at the start of the range, the extract will likely fail to dry-run");
+        }
+    }
+
+    Ok((output_db, db_tool.tip()?))
+}
+
+/// Whether `stage` reads the transactions of the blocks it executes, whose numbering has to follow
+/// on from the block before the range.
+fn reads_transactions(stage: StageId) -> bool {
+    matches!(stage, StageId::Execution | StageId::SenderRecovery | StageId::TransactionLookup)
+}
+
+/// The first block whose [`tables::BlockBodyIndices`] are imported for `stage`.
+///
+/// The stages reading transactions also import the indices of `from - 1`, to check where the
+/// transactions of the range start. The others only read the blocks after `from`, so they don't
+/// fail on a source pruned right before it.
+fn body_indices_start(stage: StageId, from: BlockNumber) -> BlockNumber {
+    if reads_transactions(stage) {
+        from.saturating_sub(1)
+    } else {
+        from
+    }
+}
+
+/// Dumps the range of `stages` into its output database.
+pub(crate) async fn dump_stage<DB: Database>(
+    tool: &DbTool<'_, DB>,
+    stages: &Stages,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<()> {
+    match stages {
+        Stages::Execution(command) => dump_execution_stage(tool, command, progress).await,
+        Stages::StorageHashing(command) => {
+            dump_hashing_storage_stage(tool, command, progress).await
+        }
+        Stages::AccountHashing(command) => {
+            dump_hashing_account_stage(tool, command, progress).await
+        }
+        Stages::Merkle(command) => dump_merkle_stage(tool, command, progress).await,
+        Stages::Senders(command) => dump_senders_stage(tool, command, progress).await,
+        Stages::TransactionLookup(command) => dump_tx_lookup_stage(tool, command, progress).await,
+        Stages::IndexAccountHistory(command) => {
+            dump_index_account_history_stage(tool, command, progress).await
+        }
+        Stages::IndexStorageHistory(command) => {
+            dump_index_storage_history_stage(tool, command, progress).await
+        }
+    }
+}
+
+/// Inconsistency between the [`tables::BlockBodyIndices`] of `from - 1` and `from`.
+#[derive(Debug, PartialEq, Eq)]
+enum BodyIndicesGap {
+    /// The indices of `from - 1` are missing, usually because the source database was pruned.
+    MissingParent,
+    /// The transactions of `from` don't start right after the ones of `from - 1`.
+    Offset { expected: TxNumber, got: TxNumber },
+}
+
+impl fmt::Display for BodyIndicesGap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BodyIndicesGap::MissingParent => write!(f, "missing indices of the parent block"),
+            BodyIndicesGap::Offset { expected, got } => {
+                write!(f, "first transaction is {got}, but the parent block ends at {expected}")
+            }
+        }
+    }
+}
+
+/// Checks that the [`tables::BlockBodyIndices`] of block `from` follow the ones of `from - 1`.
+fn check_body_indices_boundary<TX: DbTx>(
+    tx: &TX,
+    from: BlockNumber,
+) -> eyre::Result<Option<BodyIndicesGap>> {
+    let Some(parent) = from.checked_sub(1) else { return Ok(None) };
+    let Some(indices) = tx.get::<tables::BlockBodyIndices>(from)? else { return Ok(None) };
+
+    Ok(match tx.get::<tables::BlockBodyIndices>(parent)? {
+        None => Some(BodyIndicesGap::MissingParent),
+        Some(parent) if parent.next_tx_num() != indices.first_tx_num => {
+            Some(BodyIndicesGap::Offset {
+                expected: parent.next_tx_num(),
+                got: indices.first_tx_num,
+            })
+        }
+        Some(_) => None,
+    })
+}
+
+/// Writes `chain` into the output database folder, so that the extract can be loaded with
+/// `--chain <OUTPUT_PATH>` on machines which don't have its chain specification file.
+pub(crate) fn write_chain_spec(output_db: &Path, chain: &ChainSpec) -> eyre::Result<()> {
+    let path = output_db.join(EMBEDDED_CHAIN_SPEC_FILE);
+    std::fs::write(&path, serde_json::to_string_pretty(chain)?)?;
+    info!(target: "reth::cli", chain = %chain.chain, path = ?path, "Wrote chain specification");
+    Ok(())
+}
+
+/// Imports the rows of `T` within `from..=to`, skipping and capping them by `--row-offset` and
+/// `--row-limit`.
+///
+/// The import is rolled back if `--limit-duration` runs out in the middle of it. With
+/// `--deep-verify`, the imported rows are read back once committed.
+pub(crate) fn import_table_with_range<T: Table, DB: Database>(
+    output_db: &DatabaseEnv,
+    db_tool: &DbTool<'_, DB>,
+    command: &StageCommand,
+    from: T::Key,
+    to: T::Key,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<()> {
+    command.check_deadline()?;
+    if let Some(progress) = progress {
+        progress.on_table_start(T::NAME);
+    }
+
+    let tx = output_db.tx_mut()?;
+    let source_tx = db_tool.db.tx()?;
+    let mut source_cursor = source_tx.cursor_read::<T>()?;
+    let mut destination_cursor = tx.cursor_write::<T>()?;
+
+    let rows = source_cursor
+        .walk_range(from.clone()..=to.clone())?
+        .skip(command.row_offset)
+        .take(command.row_limit.unwrap_or(usize::MAX));
+    let mut imported = 0;
+    for row in rows {
+        // Dropping the transaction without committing aborts it.
+        if imported % DEADLINE_CHECK_INTERVAL == 0 {
+            command.check_deadline()?;
+            if let Some(progress) = progress.filter(|_| imported > 0) {
+                progress.on_rows(T::NAME, imported);
+            }
+        }
+
+        let (key, value) = row?;
+        destination_cursor.append(key, value)?;
+        imported += 1;
+    }
+
+    drop(destination_cursor);
+    tx.commit()?;
+
+    if let Some(progress) = progress {
+        progress.on_rows(T::NAME, imported);
+    }
+
+    if command.deep_verify {
+        verify_table::<T, _>(
+            output_db,
+            &source_tx,
+            RawKey::new(from)..=RawKey::new(to),
+            command.row_offset,
+            command.row_limit.unwrap_or(usize::MAX),
+            |_| true,
+            &mut command.verification.lock().expect("not poisoned"),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Imports every row of `T` from `source_tx`, reporting it to `progress`, and reads it back with
+/// `--deep-verify`.
+pub(crate) fn import_table<T: Table, TX: DbTx>(
+    output_db: &DatabaseEnv,
+    source_tx: &TX,
+    command: &StageCommand,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<()> {
+    if let Some(progress) = progress {
+        progress.on_table_start(T::NAME);
+    }
+    output_db.update(|tx| tx.import_table::<T, _>(source_tx))??;
+    if let Some(progress) = progress {
+        progress.on_rows(T::NAME, output_db.view(|tx| tx.entries::<T>())??);
+    }
+    if command.deep_verify {
+        verify_table::<T, _>(
+            output_db,
+            source_tx,
+            ..,
+            0,
+            usize::MAX,
+            |_| true,
+            &mut command.verification.lock().expect("not poisoned"),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Imports every row of the [`DupSort`] table `T` from `source_tx`, reporting it to `progress`,
+/// and reads it back with `--deep-verify`.
+///
+/// Only the storage slots of `--subkey-from` and `--subkey-to` are imported, if `T` holds
+/// storage slots.
+pub(crate) fn import_dupsort<T: DupSort, TX: DbTx>(
+    output_db: &DatabaseEnv,
+    source_tx: &TX,
+    command: &StageCommand,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<()> {
+    if let Some(progress) = progress {
+        progress.on_table_start(T::NAME);
+    }
+    let slots = command.subkey_range().filter(|_| STORAGE_SLOT_TABLES.contains(&T::NAME));
+    match &slots {
+        Some(slots) => {
+            let rows =
+                output_db.update(|tx| import_storage_slots::<T, _, _>(tx, source_tx, slots))??;
+            info!(target: "reth::cli", table = T::NAME, from = %slots.start(), to = %slots.end(), rows, "Imported storage slots");
+        }
+        None => output_db.update(|tx| tx.import_dupsort::<T, _>(source_tx))??,
+    }
+    if let Some(progress) = progress {
+        progress.on_rows(T::NAME, output_db.view(|tx| tx.entries::<T>())??);
+    }
+    if command.deep_verify {
+        verify_table::<T, _>(
+            output_db,
+            source_tx,
+            ..,
+            0,
+            usize::MAX,
+            |value| slots.as_ref().map_or(true, |slots| is_storage_slot_in(value, slots)),
+            &mut command.verification.lock().expect("not poisoned"),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Imports the [`tables::Bytecodes`] of the accounts already imported into
+/// [`tables::PlainAccountState`], instead of the whole code table, and reads them back with
+/// `--deep-verify`.
+pub(crate) fn import_reachable_bytecodes<TX: DbTx>(
+    output_db: &DatabaseEnv,
+    source_tx: &TX,
+    command: &StageCommand,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<()> {
+    if let Some(progress) = progress {
+        progress.on_table_start(tables::Bytecodes::NAME);
+    }
+
+    let code_hashes = output_db.view(|tx| {
+        tx.cursor_read::<tables::PlainAccountState>()?
+            .walk(None)?
+            .filter_map(|entry| {
+                entry
+                    .map(|(_, account)| account.bytecode_hash.filter(|hash| *hash != KECCAK_EMPTY))
+                    .transpose()
+            })
+            .collect::<Result<BTreeSet<_>, _>>()
+    })??;
+
+    // The hashes are sorted, so the codes can be appended.
+    let mut imported = vec![];
+    output_db.update(|tx| {
+        let mut cursor = tx.cursor_write::<tables::Bytecodes>()?;
+        for hash in &code_hashes {
+            if let Some(code) = source_tx.get::<tables::Bytecodes>(*hash)? {
+                cursor.append(*hash, code)?;
+                imported.push(*hash);
+            }
+        }
+        Ok::<_, DatabaseError>(())
+    })??;
+
+    info!(target: "reth::cli", table = tables::Bytecodes::NAME, code_hashes = imported.len(), "Imported bytecodes of the imported accounts");
+    if imported.len() < code_hashes.len() {
+        warn!(target: "reth::cli", table = tables::Bytecodes::NAME, missing = code_hashes.len() - imported.len(), "Bytecodes of imported accounts are missing from the source database");
+    }
+    if let Some(progress) = progress {
+        progress.on_rows(tables::Bytecodes::NAME, imported.len());
+    }
+    if command.deep_verify {
+        verify_keys::<tables::Bytecodes, _>(
+            output_db,
+            source_tx,
+            imported,
+            &mut command.verification.lock().expect("not poisoned"),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The dupsort tables whose subkey is a storage slot, sliced by `--subkey-from` and
+/// `--subkey-to`.
+const STORAGE_SLOT_TABLES: [&str; 3] =
+    [tables::PlainStorageState::NAME, tables::StorageChangeSet::NAME, tables::HashedStorage::NAME];
+
+/// Imports the values of every key of `T` whose storage slot lies within `slots`, returning how
+/// many were imported.
+///
+/// The values of a key are sorted by slot, so the cursor seeks to the first slot of every key and
+/// moves to the next key once past the last one.
+fn import_storage_slots<T: DupSort, TX: DbTx, W: DbTxMut>(
+    tx: &W,
+    source_tx: &TX,
+    slots: &RangeInclusive<B256>,
+) -> eyre::Result<usize> {
+    let mut destination_cursor = tx.cursor_dup_write::<RawDupSort<T>>()?;
+    // A failed seek leaves a cursor unpositioned, so the keys are walked by a cursor of their own.
+    let mut keys_cursor = source_tx.cursor_dup_read::<RawDupSort<T>>()?;
+    let mut values_cursor = source_tx.cursor_dup_read::<RawDupSort<T>>()?;
+    let first = RawKey::<T::SubKey>::decode(slots.start().as_slice())?;
+
+    let mut imported = 0;
+    while let Some((key, _)) = keys_cursor.next_no_dup()? {
+        for row in values_cursor.walk_dup(Some(key), Some(first.clone()))? {
+            let (key, value) = row?;
+            if !is_storage_slot_in(value.raw_value(), slots) {
+                break
+            }
+            destination_cursor.append_dup(key, value)?;
+            imported += 1;
+        }
+    }
+
+    Ok(imported)
+}
+
+/// Whether the raw value of a storage slot table starts with a slot within `slots`.
+fn is_storage_slot_in(value: &[u8], slots: &RangeInclusive<B256>) -> bool {
+    value.get(..32).map_or(false, |slot| slots.contains(&B256::from_slice(slot)))
+}
+
+/// Number of rows imported between checks of `--limit-duration`.
+const DEADLINE_CHECK_INTERVAL: usize = 10_000;
 
-    Ok((output_db, tip_block_number))
+/// Error of a dump which ran out of its `--limit-duration` budget.
+#[derive(Debug, thiserror::Error)]
+#[error("The dump exceeded its time budget of {0:?}.")]
+pub(crate) struct DeadlineExceeded(Duration);
+
+/// Returns the range of transactions of the blocks `from..=to`, as `(first, end)` with `end`
+/// exclusive.
+pub(crate) fn transaction_range<DB: Database>(
+    db: &DB,
+    from: u64,
+    to: u64,
+) -> eyre::Result<(TxNumber, TxNumber)> {
+    db.view(|read_tx| {
+        let mut read_cursor = read_tx.cursor_read::<tables::BlockBodyIndices>()?;
+        let (_, from_block) =
+            read_cursor.seek(from)?.ok_or(eyre::eyre!("BlockBody {from} does not exist."))?;
+        let (_, to_block) =
+            read_cursor.seek(to)?.ok_or(eyre::eyre!("BlockBody {to} does not exist."))?;
+
+        Ok::<(u64, u64), eyre::ErrReport>((
+            from_block.first_tx_num,
+            to_block.first_tx_num + to_block.tx_count,
+        ))
+    })?
+}
+
+/// Runs the dry-run of `stage` `--dry-run-repeat` times, returning the outcome of the last one
+/// along with the profile of every iteration.
+///
+/// Fails on the first failing iteration. When repeated, the min, median and max durations of the
+/// iterations are printed.
+pub(crate) async fn repeat_dry_run<T, F, Fut>(
+    stage: StageId,
+    command: &StageCommand,
+    mut dry_run: F,
+) -> eyre::Result<Profiled<T>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = eyre::Result<T>>,
+{
+    let mut durations = Vec::with_capacity(command.dry_run_repeat as usize);
+    let mut profile = DryRunProfile::default();
+    let mut outcome = None;
+    for iteration in 1..=command.dry_run_repeat {
+        let started = Instant::now();
+        let (iteration_outcome, iteration_profile) = profile_dry_run(dry_run()).await?;
+        let elapsed = started.elapsed();
+        outcome = Some(iteration_outcome);
+        profile.iterations.push(iteration_profile);
+        if command.dry_run_repeat > 1 {
+            info!(target: "reth::cli", %stage, iteration, ?elapsed, "Dry-run iteration done");
+        }
+        durations.push(elapsed);
+    }
+
+    // The statistics would break the JSON output.
+    if durations.len() > 1 && !command.machine {
+        println!("{}", dry_run_stats(stage, durations));
+    }
+
+    Ok(Profiled { outcome: outcome.expect("at least one iteration"), profile })
+}
+
+/// Renders the min, median and max of the dry-run `durations` of `stage` as a table.
+fn dry_run_stats(stage: StageId, mut durations: Vec<Duration>) -> ComfyTable {
+    durations.sort_unstable();
+    let middle = durations.len() / 2;
+    let median = if durations.len() % 2 == 0 {
+        (durations[middle - 1] + durations[middle]) / 2
+    } else {
+        durations[middle]
+    };
+
+    let mut table = ComfyTable::new();
+    table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+    table.set_header(["Stage", "Iterations", "Min", "Median", "Max"]);
+    table.add_row([
+        stage.to_string(),
+        durations.len().to_string(),
+        format!("{:?}", durations[0]),
+        format!("{median:?}"),
+        format!("{:?}", durations[durations.len() - 1]),
+    ]);
+    table
+}
+
+/// Logs the number of rows of every non-empty table in the output database, and returns them.
+pub(crate) fn log_imported_rows<DB: Database>(
+    output_db: &DB,
+    stage: StageId,
+) -> eyre::Result<BTreeMap<String, usize>> {
+    let mut imported = BTreeMap::new();
+    for table in Tables::ALL {
+        let rows = table.view(&RowCountViewer { db: output_db })?;
+        if rows > 0 {
+            info!(target: "reth::cli", %stage, table = table.name(), rows, "Imported table");
+            imported.insert(table.name().to_string(), rows);
+        }
+    }
+
+    Ok(imported)
+}
+
+/// Entries derived by a dry-run that don't match the imported source tables.
+pub(crate) struct Mismatches<K> {
+    stage: StageId,
+    max: usize,
+    keys: HashSet<K>,
+}
+
+impl<K: Eq + Hash + fmt::Debug> Mismatches<K> {
+    /// Creates an empty set which logs at most `max` mismatches.
+    pub(crate) fn new(stage: StageId, max: usize) -> Self {
+        Self { stage, max, keys: HashSet::new() }
+    }
+
+    /// Records the mismatch of the entry at `key`, logging it while under the limit.
+    pub(crate) fn insert(&mut self, key: K, mismatch: impl fmt::Display) {
+        if self.keys.len() < self.max {
+            warn!(target: "reth::cli", stage = %self.stage, %mismatch, "Mismatch");
+        }
+        self.keys.insert(key);
+    }
+
+    /// Whether the entry at `key` is mismatched.
+    pub(crate) fn contains(&self, key: &K) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// Whether no mismatch was recorded.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Reports the mismatches, and fails if there are any.
+    ///
+    /// `blocks` holds, for every block of the imported changesets, how many mismatched entries it
+    /// changed.
+    pub(crate) fn finish(self, blocks: BTreeMap<BlockNumber, usize>) -> eyre::Result<()> {
+        if self.keys.is_empty() {
+            return Ok(())
+        }
+
+        let mismatches = self.keys.len();
+        if mismatches > self.max {
+            warn!(target: "reth::cli", stage = %self.stage, hidden = mismatches - self.max, "More mismatches not logged");
+        }
+        for (block, entries) in blocks.iter().take(self.max) {
+            warn!(target: "reth::cli", stage = %self.stage, block, entries, "Block changed mismatched entries");
+        }
+
+        let message = match blocks.keys().next() {
+            Some(block) => format!(
+                "{mismatches} entries derived by the {} stage differ from the source tables. First divergence at block {block}.",
+                self.stage
+            ),
+            None => format!(
+                "{mismatches} entries derived by the {} stage differ from the source tables. None of them were changed in the imported range.",
+                self.stage
+            ),
+        };
+        let mut keys = self.keys.iter().map(|key| format!("{key:?}")).collect::<Vec<_>>();
+        keys.sort_unstable();
+
+        Err(MismatchError { message, keys }.into())
+    }
+}
+
+struct RowCountViewer<'a, DB: Database> {
+    db: &'a DB,
+}
+
+impl<DB: Database> TableViewer<usize> for RowCountViewer<'_, DB> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<usize, Self::Error> {
+        Ok(self.db.view(|tx| tx.entries::<T>())??)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{init_db, models::StoredBlockBodyIndices, test_utils::create_test_rw_db};
+    use reth_primitives::{
+        keccak256, Account, Address, Bytecode, Bytes, Header, StorageEntry, U256,
+    };
+
+    #[test]
+    fn resolve_relative_output_db() {
+        let base = Path::new("/data/reth/mainnet");
+        assert_eq!(
+            resolve_relative_path(Path::new("dumps/exec1"), base),
+            PathBuf::from("/data/reth/mainnet/dumps/exec1")
+        );
+        assert_eq!(
+            resolve_relative_path(Path::new("/tmp/exec1"), base),
+            PathBuf::from("/tmp/exec1")
+        );
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "dump-post-cmd"))]
+    fn post_cmd_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = dir.path().join("env");
+        let cmd = format!(
+            "echo \"$RETH_DUMP_OUTPUT $RETH_DUMP_STAGE $RETH_DUMP_FROM $RETH_DUMP_TO\" > {}",
+            env.display()
+        );
+        run_post_cmd(&cmd, "execution", 10, 20, Path::new("/tmp/extract")).unwrap();
+        assert_eq!(std::fs::read_to_string(&env).unwrap(), "/tmp/extract execution 10 20\n");
+
+        let err = run_post_cmd("echo failing >&2; exit 3", "execution", 10, 20, dir.path())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`echo failing >&2; exit 3` failed"), "{err}");
+    }
+
+    #[test]
+    fn import_storage_slot_range() {
+        let (source, output) = (create_test_rw_db(), create_test_rw_db());
+        let (first, second) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        source
+            .update(|tx| {
+                for address in [first, second] {
+                    for slot in 1..=4 {
+                        tx.put::<tables::PlainStorageState>(
+                            address,
+                            StorageEntry {
+                                key: B256::with_last_byte(slot),
+                                value: U256::from(slot),
+                            },
+                        )?;
+                    }
+                }
+                Ok::<_, DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+
+        let slots = B256::with_last_byte(2)..=B256::with_last_byte(3);
+        let imported = output
+            .update(|tx| {
+                import_storage_slots::<tables::PlainStorageState, _, _>(
+                    tx,
+                    &source.tx().unwrap(),
+                    &slots,
+                )
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(imported, 4);
+
+        let tx = output.tx().unwrap();
+        let rows = tx
+            .cursor_read::<tables::PlainStorageState>()
+            .unwrap()
+            .walk(None)
+            .unwrap()
+            .map(|row| row.map(|(address, entry)| (address, entry.key)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            [
+                (first, B256::with_last_byte(2)),
+                (first, B256::with_last_byte(3)),
+                (second, B256::with_last_byte(2)),
+                (second, B256::with_last_byte(3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn import_only_reachable_bytecodes() {
+        let (source, output) = (create_test_rw_db(), create_test_rw_db());
+        let codes = [[0x60, 0x00], [0x60, 0x01], [0x60, 0x02]].map(|code| {
+            let code = Bytes::copy_from_slice(&code);
+            (keccak256(&code), Bytecode::new_raw(code))
+        });
+        source
+            .update(|tx| {
+                for (hash, code) in &codes {
+                    tx.put::<tables::Bytecodes>(*hash, code.clone())?;
+                }
+                Ok::<_, DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+        output
+            .update(|tx| {
+                for (i, bytecode_hash) in
+                    [Some(codes[1].0), Some(codes[1].0), Some(KECCAK_EMPTY), None]
+                        .into_iter()
+                        .enumerate()
+                {
+                    tx.put::<tables::PlainAccountState>(
+                        Address::with_last_byte(i as u8),
+                        Account { bytecode_hash, ..Default::default() },
+                    )?;
+                }
+                Ok::<_, DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+
+        let command = StageCommand::try_parse_from([
+            "reth",
+            "--output-db",
+            "out",
+            "--from",
+            "1",
+            "--to",
+            "2",
+            "--deep-verify",
+        ])
+        .unwrap();
+        import_reachable_bytecodes(&output, &source.tx().unwrap(), &command, None).unwrap();
+
+        let tx = output.tx().unwrap();
+        assert_eq!(tx.entries::<tables::Bytecodes>().unwrap(), 1);
+        assert_eq!(tx.get::<tables::Bytecodes>(codes[1].0).unwrap(), Some(codes[1].1.clone()));
+        assert_eq!(command.deep_verification().map(|verification| verification.rows), Some(1));
+    }
+
+    #[test]
+    fn lock_output_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("000");
+        init_db(&db, None).unwrap();
+        write_chain_spec(dir.path(), &MAINNET).unwrap();
+
+        lock_output(dir.path()).unwrap();
+        let readonly = |path: PathBuf| std::fs::metadata(path).unwrap().permissions().readonly();
+        assert!(readonly(db.join("mdbx.dat")));
+        assert!(!readonly(db.join("mdbx.lck")));
+        assert!(readonly(dir.path().join(EMBEDDED_CHAIN_SPEC_FILE)));
+        // Readers still open the locked database.
+        open_db_read_only(&db, None).unwrap();
+    }
+
+    #[test]
+    fn render_output_db_name_template() {
+        assert_eq!(
+            render_output_db_name("{stage}_{from}_{to}", "execution", 12000000, 12001000).unwrap(),
+            "execution_12000000_12001000"
+        );
+        assert!(render_output_db_name("{stage}_{from}", "execution", 1, 2).is_err());
+        assert!(render_output_db_name("{stage}_{from}_{to}_{chain}", "execution", 1, 2).is_err());
+        assert!(render_output_db_name("{stage}/{from}_{to}", "execution", 1, 2).is_err());
+    }
+
+    #[test]
+    fn parse_time_range() {
+        let command = StageCommand::try_parse_from([
+            "reth",
+            "--output-db",
+            "out",
+            "--time-from",
+            "2023-10-01T14:00:00Z",
+            "--to",
+            "100",
+        ])
+        .unwrap();
+        assert!(command.time_from.is_some());
+        assert_eq!(command.to_block, Some(100));
+
+        assert!(
+            StageCommand::try_parse_from(["reth", "--output-db", "out", "--to", "100"]).is_err()
+        );
+        assert!(StageCommand::try_parse_from([
+            "reth",
+            "--output-db",
+            "out",
+            "--from",
+            "1",
+            "--time-from",
+            "2023-10-01T14:00:00Z",
+            "--to",
+            "100",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn cap_range_by_gas() {
+        let db = create_test_rw_db();
+        db.update(|tx| {
+            for block in 0..=10 {
+                tx.put::<tables::Headers>(
+                    block,
+                    Header { number: block, gas_used: 100, ..Default::default() },
+                )?;
+            }
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+        let mut command = StageCommand::try_parse_from([
+            "reth",
+            "--output-db",
+            "out",
+            "--from",
+            "2",
+            "--to",
+            "9",
+        ])
+        .unwrap();
+        command.resolve_range(&tool).unwrap();
+
+        // The gas of `from` itself isn't counted, since it's not executed.
+        command.cap_range_by_gas(&tool, 250).unwrap();
+        assert_eq!((command.from, command.to), (2, 5));
+        command.cap_range_by_gas(&tool, 1).unwrap();
+        assert_eq!(command.to, 3);
+        command.to = 9;
+        command.cap_range_by_gas(&tool, 10_000).unwrap();
+        assert_eq!(command.to, 9);
+    }
+
+    #[test]
+    fn body_indices_boundary() {
+        let db = create_test_rw_db();
+        let put = |block, first_tx_num, tx_count| {
+            db.update(|tx| {
+                tx.put::<tables::BlockBodyIndices>(
+                    block,
+                    StoredBlockBodyIndices { first_tx_num, tx_count },
+                )
+            })
+            .unwrap()
+            .unwrap();
+        };
+        let check = |from| db.view(|tx| check_body_indices_boundary(tx, from)).unwrap().unwrap();
+
+        // Nothing to check without the indices of `from`.
+        assert_eq!(check(0), None);
+        assert_eq!(check(11), None);
+
+        put(10, 100, 5);
+        assert_eq!(check(10), Some(BodyIndicesGap::MissingParent));
+
+        put(11, 105, 2);
+        assert_eq!(check(11), None);
+
+        put(12, 108, 1);
+        assert_eq!(check(12), Some(BodyIndicesGap::Offset { expected: 107, got: 108 }));
+
+        put(20, 200, 1);
+        assert_eq!(check(20), Some(BodyIndicesGap::MissingParent));
+    }
+
+    #[test]
+    fn prepare_non_empty_output_db() {
+        let db = create_test_rw_db();
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("out");
+        let command = |range: &[&str]| {
+            let mut args = vec!["reth", "--output-db", output.to_str().unwrap()];
+            args.extend(range);
+            let mut command = StageCommand::try_parse_from(args).unwrap();
+            command.resolve_range(&tool).unwrap();
+            command
+        };
+        let prepare = |mut command: StageCommand| {
+            prepare_output_db(&tool, StageId::Execution, &mut command)
+                .map(|extended| (command, extended))
+        };
+        let range = ["--from", "10", "--to", "20"];
+
+        // Missing and empty folders are dumped into, but hold nothing to append to.
+        prepare(command(&range)).unwrap();
+        assert!(prepare(command(&["--from", "20", "--to", "30", "--append"])).is_err());
+        std::fs::create_dir_all(&output).unwrap();
+        prepare(command(&range)).unwrap();
+
+        // A database without the manifest of a dump, e.g. the one of a node, is never removed.
+        drop(init_db(&output, None).unwrap());
+        assert!(prepare(command(&range)).is_err());
+        assert!(prepare(command(&["--from", "10", "--to", "20", "--force"])).is_err());
+        assert!(output.join("mdbx.dat").exists());
+
+        ExtractManifest::write_stage(
+            &output,
+            DumpedStage::new(&MAINNET, StageId::Execution, 10, 20),
+        )
+        .unwrap();
+        assert!(prepare(command(&range)).is_err());
+
+        // The extended dump goes next to the extract, which is only replaced once it succeeded.
+        let (appended, extended) =
+            prepare(command(&["--from", "20", "--to", "30", "--append"])).unwrap();
+        assert_eq!((appended.from, appended.to), (10, 30));
+        assert_eq!(extended.as_deref(), Some(output.as_path()));
+        assert_eq!(appended.output_db, dir.path().join("out.append"));
+        assert!(output.join("mdbx.dat").exists());
+
+        std::fs::create_dir_all(&appended.output_db).unwrap();
+        std::fs::write(appended.output_db.join("marker"), "extended").unwrap();
+        replace_extract(&output, &appended.output_db).unwrap();
+        assert!(output.join("marker").exists());
+        assert!(!appended.output_db.exists());
+        assert!(!dir.path().join("out.old").exists());
+
+        ExtractManifest::write_stage(
+            &output,
+            DumpedStage::new(&MAINNET, StageId::Execution, 10, 20),
+        )
+        .unwrap();
+        assert!(prepare(command(&["--from", "25", "--to", "30", "--append"])).is_err());
+        let (_, extended) = prepare(command(&["--from", "25", "--to", "30", "--force"])).unwrap();
+        assert_eq!(extended, None);
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn body_indices_start_per_stage() {
+        assert_eq!(body_indices_start(StageId::Execution, 10), 9);
+        assert_eq!(body_indices_start(StageId::SenderRecovery, 10), 9);
+        assert_eq!(body_indices_start(StageId::TransactionLookup, 10), 9);
+        assert_eq!(body_indices_start(StageId::IndexAccountHistory, 10), 10);
+        assert_eq!(body_indices_start(StageId::Execution, 0), 0);
+        assert_eq!(body_indices_start(StageId::AccountHashing, 10), 10);
+        assert_eq!(body_indices_start(StageId::MerkleExecute, 0), 0);
+    }
 }
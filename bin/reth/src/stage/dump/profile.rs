@@ -0,0 +1,192 @@
+//! Resource usage of dry-runs, so that dumps can double as benchmarks of their stage.
+//!
+//! The storage I/O and memory of the process are read from procfs, so they're only measured on
+//! Linux. The entries a dry-run processes are the `rows` of its report, since a dump imports just
+//! the rows its stage reads.
+use comfy_table::Table as ComfyTable;
+use human_bytes::human_bytes;
+use serde::{Deserialize, Serialize};
+use std::{future::Future, time::Instant};
+
+/// The outcome of a dry-run, along with its profile.
+#[derive(Debug)]
+pub(crate) struct Profiled<T> {
+    pub(crate) outcome: T,
+    pub(crate) profile: DryRunProfile,
+}
+
+impl<T> Profiled<T> {
+    /// Maps the outcome, keeping the profile.
+    pub(crate) fn map<U>(self, f: impl FnOnce(T) -> U) -> Profiled<U> {
+        Profiled { outcome: f(self.outcome), profile: self.profile }
+    }
+}
+
+/// Profile of a dry-run, with an entry per iteration of `--dry-run-repeat`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct DryRunProfile {
+    pub(crate) iterations: Vec<IterationProfile>,
+}
+
+/// Resource usage of a single execution of a dry-run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct IterationProfile {
+    /// The wall time, in milliseconds.
+    pub(crate) wall_time_ms: u64,
+    /// The storage I/O of the process, if measured.
+    pub(crate) io: Option<IoCounters>,
+    /// The peak resident memory of the process, in bytes, if measured.
+    ///
+    /// The peak is reset before the dry-run where the kernel allows it. Otherwise, it's the peak
+    /// since the process started.
+    pub(crate) peak_memory_bytes: Option<u64>,
+}
+
+/// Storage I/O counters of the process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct IoCounters {
+    /// Bytes read from storage, including the pages of the memory mapped database.
+    pub(crate) read_bytes: u64,
+    /// Bytes written to storage.
+    pub(crate) write_bytes: u64,
+    /// Read system calls.
+    pub(crate) read_calls: u64,
+    /// Write system calls.
+    pub(crate) write_calls: u64,
+    /// Page faults which had to read from storage, i.e. pages of the database which weren't
+    /// cached.
+    pub(crate) major_faults: u64,
+}
+
+impl IoCounters {
+    /// The counters of the process so far, or `None` if procfs isn't available.
+    fn read() -> Option<Self> {
+        let io = std::fs::read_to_string("/proc/self/io").ok()?;
+        let field = |name: &str| {
+            io.lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(':')?.trim().parse().ok())
+        };
+
+        // The fields after the executable name, which may contain spaces, start with the third.
+        let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+        let major_faults = stat.rsplit_once(')')?.1.split_whitespace().nth(9)?.parse().ok()?;
+
+        Some(Self {
+            read_bytes: field("read_bytes")?,
+            write_bytes: field("write_bytes")?,
+            read_calls: field("syscr")?,
+            write_calls: field("syscw")?,
+            major_faults,
+        })
+    }
+
+    /// The counters accumulated since `earlier`.
+    fn since(self, earlier: Self) -> Self {
+        Self {
+            read_bytes: self.read_bytes.saturating_sub(earlier.read_bytes),
+            write_bytes: self.write_bytes.saturating_sub(earlier.write_bytes),
+            read_calls: self.read_calls.saturating_sub(earlier.read_calls),
+            write_calls: self.write_calls.saturating_sub(earlier.write_calls),
+            major_faults: self.major_faults.saturating_sub(earlier.major_faults),
+        }
+    }
+}
+
+/// Resets the peak resident memory of the process, if the kernel allows it.
+fn reset_peak_memory() {
+    let _ = std::fs::write("/proc/self/clear_refs", "5");
+}
+
+/// The peak resident memory of the process, in bytes, or `None` if procfs isn't available.
+fn peak_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kilobytes: u64 = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// Executes `dry_run` and measures its resource usage.
+pub(crate) async fn profile_dry_run<T>(
+    dry_run: impl Future<Output = eyre::Result<T>>,
+) -> eyre::Result<(T, IterationProfile)> {
+    reset_peak_memory();
+    let io = IoCounters::read();
+    let started = Instant::now();
+
+    let outcome = dry_run.await?;
+
+    let profile = IterationProfile {
+        wall_time_ms: started.elapsed().as_millis() as u64,
+        io: io.zip(IoCounters::read()).map(|(before, after)| after.since(before)),
+        peak_memory_bytes: peak_memory(),
+    };
+    Ok((outcome, profile))
+}
+
+/// Executes `dry_run` once and profiles it, for the dry-runs which aren't repeated.
+pub(crate) async fn profile_single<T>(
+    dry_run: impl Future<Output = eyre::Result<T>>,
+) -> eyre::Result<Profiled<T>> {
+    let (outcome, iteration) = profile_dry_run(dry_run).await?;
+    Ok(Profiled { outcome, profile: DryRunProfile { iterations: vec![iteration] } })
+}
+
+/// Renders the profile of the dry-run of `stage` as a table, with a row per iteration.
+pub(crate) fn profile_table(stage: &str, profile: &DryRunProfile) -> ComfyTable {
+    let mut table = ComfyTable::new();
+    table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+    table.set_header([
+        "Stage",
+        "Iteration",
+        "Wall Time",
+        "Read",
+        "Written",
+        "Read Calls",
+        "Write Calls",
+        "Major Faults",
+        "Peak Memory",
+    ]);
+
+    let bytes = |bytes: Option<u64>| bytes.map_or("-".to_string(), |b| human_bytes(b as f64));
+    let count = |count: Option<u64>| count.map_or("-".to_string(), |count| count.to_string());
+    for (iteration, run) in profile.iterations.iter().enumerate() {
+        table.add_row([
+            stage.to_string(),
+            (iteration + 1).to_string(),
+            format!("{}ms", run.wall_time_ms),
+            bytes(run.io.map(|io| io.read_bytes)),
+            bytes(run.io.map(|io| io.write_bytes)),
+            count(run.io.map(|io| io.read_calls)),
+            count(run.io.map(|io| io.write_calls)),
+            count(run.io.map(|io| io.major_faults)),
+            bytes(run.peak_memory_bytes),
+        ]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn profile_iteration() {
+        let (outcome, profile) =
+            profile_dry_run(async { Ok::<_, eyre::Report>(vec![0u8; 1 << 20].len()) })
+                .await
+                .unwrap();
+        assert_eq!(outcome, 1 << 20);
+
+        if let Some(peak) = profile.peak_memory_bytes {
+            assert!(peak >= 1 << 20);
+        }
+        let rendered = profile_table("Execution", &DryRunProfile { iterations: vec![profile] });
+        assert_eq!(rendered.row_iter().count(), 1);
+    }
+}
@@ -0,0 +1,55 @@
+//! Progress callbacks of a dump.
+use super::DumpReport;
+use reth_primitives::{stage::StageId, BlockNumber};
+use tracing::{debug, info};
+
+/// Receives the progress of a dump.
+///
+/// The dump functions take an `Option<&dyn DumpProgress>`, and skip every callback when it's
+/// `None`.
+pub(crate) trait DumpProgress {
+    /// Called before the rows of `table` are imported.
+    fn on_table_start(&self, table: &'static str);
+
+    /// Called with the number of rows of `table` imported so far.
+    fn on_rows(&self, table: &'static str, rows: usize);
+
+    /// Called once the dry-run of `stage` has executed up to `block`.
+    fn on_dry_run_block(&self, stage: StageId, block: BlockNumber);
+
+    /// Called once the dump is done, with its report.
+    fn on_complete(&self, report: &DumpReport);
+}
+
+/// [`DumpProgress`] which logs every callback, used by the CLI.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct LogProgress;
+
+impl DumpProgress for LogProgress {
+    fn on_table_start(&self, table: &'static str) {
+        info!(target: "reth::cli", table, "Importing table");
+    }
+
+    fn on_rows(&self, table: &'static str, rows: usize) {
+        debug!(target: "reth::cli", table, rows, "Imported rows");
+    }
+
+    fn on_dry_run_block(&self, stage: StageId, block: BlockNumber) {
+        info!(target: "reth::cli", %stage, block, "Dry-run reached block");
+    }
+
+    fn on_complete(&self, report: &DumpReport) {
+        info!(
+            target: "reth::cli",
+            stage = report.stage,
+            from = report.from,
+            to = report.to,
+            rows = report.rows.values().sum::<usize>(),
+            partial = report.partial,
+            "Dump complete"
+        );
+        for (table, source) in &report.sources {
+            info!(target: "reth::cli", table, source = %source.source, snapshots = ?source.snapshots, "Table source");
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Pruning of a finished extract down to the blocks of `--keep-from` and `--keep-to`.
+use super::{filter::delete_blocks, StageCommand};
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW},
+    database::Database,
+    init_db, open_db,
+    table::Table,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    DatabaseEnv, RawTable, TableType, TableViewer, Tables,
+};
+use reth_primitives::BlockNumber;
+use std::{ops::RangeInclusive, path::Path};
+use tracing::info;
+
+/// Name of the MDBX data file of an environment.
+const DATA_FILE: &str = "mdbx.dat";
+/// Name of the MDBX lock file of an environment.
+const LOCK_FILE: &str = "mdbx.lck";
+
+/// Deletes the rows of the blocks outside of `keep` from the tables of the output database keyed
+/// by block or transaction, then compacts it.
+///
+/// The other tables, e.g. the state, are left as dumped.
+pub(crate) fn prune_output(
+    output_db: &Path,
+    command: &StageCommand,
+    keep: RangeInclusive<BlockNumber>,
+) -> eyre::Result<()> {
+    let db = open_db(output_db, None)?;
+    // Every stage imports the block body indices, which hold the transactions of the blocks.
+    let pruned = db.view(|tx| {
+        let mut pruned = vec![];
+        for block in command.from.saturating_sub(1)..=command.to + 1 {
+            if !keep.contains(&block) {
+                let txs = tx
+                    .get::<tables::BlockBodyIndices>(block)?
+                    .map(|indices| indices.tx_num_range());
+                pruned.push((block, txs.unwrap_or_default()));
+            }
+        }
+        Ok::<_, eyre::Report>(pruned)
+    })??;
+    db.update(|tx| delete_blocks(tx, &pruned))??;
+    drop(db);
+
+    info!(target: "reth::cli", keep_from = keep.start(), keep_to = keep.end(), blocks = pruned.len(), "Pruned output database");
+    compact(output_db)
+}
+
+/// Rewrites the MDBX environment at `path` into a fresh one holding the same rows, so that the
+/// pages freed by deletions are given back.
+///
+/// The fresh environment is written inside `path`, so that it can replace the data file with a
+/// rename.
+fn compact(path: &Path) -> eyre::Result<()> {
+    let size = std::fs::metadata(path.join(DATA_FILE))?.len();
+
+    let dir = tempfile::Builder::new().prefix("compact-").tempdir_in(path)?;
+    {
+        let source = open_db(path, None)?;
+        let compacted = init_db(dir.path(), None)?;
+        for table in Tables::ALL {
+            table.view(&CopyViewer { source: &source, target: &compacted, table })?;
+        }
+    }
+    std::fs::rename(dir.path().join(DATA_FILE), path.join(DATA_FILE))?;
+    // The lock file describes the replaced data file, so it's recreated on the next open.
+    if path.join(LOCK_FILE).exists() {
+        std::fs::remove_file(path.join(LOCK_FILE))?;
+    }
+
+    let compacted = std::fs::metadata(path.join(DATA_FILE))?.len();
+    info!(target: "reth::cli", ?path, size, compacted, "Compacted output database");
+
+    Ok(())
+}
+
+struct CopyViewer<'a> {
+    source: &'a DatabaseEnv,
+    target: &'a DatabaseEnv,
+    table: Tables,
+}
+
+impl TableViewer<()> for CopyViewer<'_> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<(), Self::Error> {
+        let source = self.source.tx()?;
+        let tx = self.target.tx_mut()?;
+        let mut cursor = tx.cursor_write::<RawTable<T>>()?;
+        for row in source.cursor_read::<RawTable<T>>()?.walk(None)? {
+            let (key, value) = row?;
+            // Appending a duplicate of the previous key is rejected, so the values of a dupsort
+            // table are inserted instead.
+            match self.table.table_type() {
+                TableType::Table => cursor.append(key, value)?,
+                TableType::DupSort => cursor.upsert(key, value)?,
+            }
+        }
+        drop(cursor);
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use reth_db::{models::StoredBlockBodyIndices, open_db_read_only, DatabaseError};
+    use reth_primitives::{Address, Header};
+
+    #[test]
+    fn prune_and_compact() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = init_db(dir.path(), None).unwrap();
+        db.update(|tx| {
+            for block in 0..=10u64 {
+                tx.put::<tables::Headers>(block, Header { number: block, ..Default::default() })?;
+                tx.put::<tables::BlockBodyIndices>(
+                    block,
+                    StoredBlockBodyIndices { first_tx_num: block, tx_count: 1 },
+                )?;
+                tx.put::<tables::TxSenders>(block, Address::with_last_byte(block as u8))?;
+            }
+            tx.put::<tables::PlainAccountState>(Address::ZERO, Default::default())?;
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+        drop(db);
+
+        let mut command = StageCommand::try_parse_from([
+            "reth",
+            "--output-db",
+            "out",
+            "--from",
+            "1",
+            "--to",
+            "9",
+        ])
+        .unwrap();
+        (command.from, command.to) = (1, 9);
+        prune_output(dir.path(), &command, 3..=5).unwrap();
+
+        let db = open_db_read_only(dir.path(), None).unwrap();
+        let tx = db.tx().unwrap();
+        let blocks = tx
+            .cursor_read::<tables::Headers>()
+            .unwrap()
+            .walk(None)
+            .unwrap()
+            .map(|row| row.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(blocks, [3, 4, 5]);
+        assert_eq!(tx.entries::<tables::BlockBodyIndices>().unwrap(), 3);
+        assert_eq!(tx.entries::<tables::TxSenders>().unwrap(), 3);
+        // Tables which aren't keyed by block are kept.
+        assert_eq!(tx.entries::<tables::PlainAccountState>().unwrap(), 1);
+        assert_eq!(tx.get::<tables::TxSenders>(4).unwrap(), Some(Address::with_last_byte(4)));
+    }
+}
@@ -0,0 +1,221 @@
+//! Comparison of the tables derived by a dry-run against a reference database, for
+//! `--compare-against`.
+use super::{ExtractManifest, Mismatches};
+use crate::utils::DbTool;
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    open_db_read_only,
+    table::{Decode, Table},
+    tables,
+    transaction::DbTx,
+    DatabaseEnvRO, RawKey, RawTable,
+};
+use reth_primitives::{hex, stage::StageId, BlockNumber};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+/// A database holding known-good tables of the dumped range, e.g. an extract of another client or
+/// reth version.
+pub(crate) struct ReferenceDb {
+    path: PathBuf,
+    db: DatabaseEnvRO,
+    max_mismatches: usize,
+}
+
+impl fmt::Debug for ReferenceDb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReferenceDb").field("path", &self.path).finish_non_exhaustive()
+    }
+}
+
+impl ReferenceDb {
+    /// Opens the reference database at `path` read-only, making sure it holds the same canonical
+    /// blocks as the source database at both ends of the range.
+    ///
+    /// At most `max_mismatches` differing rows are logged by every comparison.
+    pub(crate) fn open<DB: Database>(
+        path: &Path,
+        db_tool: &DbTool<'_, DB>,
+        from: BlockNumber,
+        to: BlockNumber,
+        max_mismatches: usize,
+    ) -> eyre::Result<Self> {
+        ExtractManifest::check(path)?;
+        let db = open_db_read_only(path, None)?;
+        db.view(|tx| {
+            for block in [from, to] {
+                let source = db_tool
+                    .get::<tables::CanonicalHeaders>(block)?
+                    .ok_or_else(|| eyre::eyre!("Canonical header {block} does not exist."))?;
+                match tx.get::<tables::CanonicalHeaders>(block)? {
+                    Some(reference) if reference == source => {}
+                    Some(reference) => eyre::bail!(
+                        "Block {block} of the reference database is {reference}, but {source} in the source database. It belongs to another chain or fork."
+                    ),
+                    None => eyre::bail!(
+                        "The reference database has no canonical header {block}, so it doesn't cover the range {from}..={to}."
+                    ),
+                }
+            }
+            Ok(())
+        })??;
+
+        info!(target: "reth::cli", ?path, from, to, "Opened reference database");
+        Ok(Self { path: path.to_path_buf(), db, max_mismatches })
+    }
+
+    /// Compares the tables derived by the dry-run of `stage` in `tx` against the reference.
+    ///
+    /// Every row of the derived tables must be in the reference. Rows only the reference has
+    /// aren't reported, since it may cover a larger range or more of the state.
+    pub(crate) fn compare<TX: DbTx>(&self, stage: StageId, tx: &TX) -> eyre::Result<()> {
+        let mut mismatches = Mismatches::new(stage, self.max_mismatches);
+        let reference = self.db.tx()?;
+        let rows = match stage {
+            StageId::Execution => {
+                compare_table::<tables::PlainAccountState, _, _>(tx, &reference, &mut mismatches)? +
+                    compare_table::<tables::PlainStorageState, _, _>(
+                        tx,
+                        &reference,
+                        &mut mismatches,
+                    )? +
+                    compare_table::<tables::AccountChangeSet, _, _>(
+                        tx,
+                        &reference,
+                        &mut mismatches,
+                    )? +
+                    compare_table::<tables::StorageChangeSet, _, _>(
+                        tx,
+                        &reference,
+                        &mut mismatches,
+                    )? +
+                    compare_table::<tables::Receipts, _, _>(tx, &reference, &mut mismatches)?
+            }
+            StageId::StorageHashing => {
+                compare_table::<tables::HashedStorage, _, _>(tx, &reference, &mut mismatches)?
+            }
+            StageId::AccountHashing => {
+                compare_table::<tables::HashedAccount, _, _>(tx, &reference, &mut mismatches)?
+            }
+            StageId::MerkleExecute => {
+                compare_table::<tables::AccountsTrie, _, _>(tx, &reference, &mut mismatches)? +
+                    compare_table::<tables::StoragesTrie, _, _>(tx, &reference, &mut mismatches)?
+            }
+            StageId::TransactionLookup => {
+                compare_table::<tables::TxHashNumber, _, _>(tx, &reference, &mut mismatches)?
+            }
+            _ => eyre::bail!("The {stage} dry-run derives no tables to --compare-against."),
+        };
+        mismatches.finish(Default::default())?;
+
+        info!(target: "reth::cli", %stage, rows, path = ?self.path, "Validated against reference database.");
+
+        Ok(())
+    }
+}
+
+/// Compares every row of `T` in `tx` against `reference`, returning the number of compared rows.
+///
+/// The rows are compared raw, and all the values of a key at once for dupsort tables.
+fn compare_table<T: Table, TX: DbTx, RTX: DbTx>(
+    tx: &TX,
+    reference: &RTX,
+    mismatches: &mut Mismatches<(&'static str, Vec<u8>)>,
+) -> eyre::Result<usize> {
+    let mut reference_cursor = reference.cursor_read::<RawTable<T>>()?;
+    let mut compare = |key: Vec<u8>, values: Vec<Vec<u8>>| -> eyre::Result<()> {
+        let raw_key = RawKey::<T::Key>::decode(&key)?;
+        let expected = reference_cursor
+            .walk_range(raw_key.clone()..=raw_key)?
+            .map(|entry| entry.map(|(_, value)| value.into_value()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if values != expected {
+            mismatches.insert(
+                (T::NAME, key.clone()),
+                format!(
+                    "Row {} of table {} differs from the reference database: {} values, {} in the reference.",
+                    hex::encode(&key),
+                    T::NAME,
+                    values.len(),
+                    expected.len()
+                ),
+            );
+        }
+        Ok(())
+    };
+
+    let mut rows = 0;
+    let mut current: Option<(Vec<u8>, Vec<Vec<u8>>)> = None;
+    for entry in tx.cursor_read::<RawTable<T>>()?.walk(None)? {
+        let (key, value) = entry?;
+        let (key, value) = (key.into_key(), value.into_value());
+        rows += 1;
+        match &mut current {
+            Some((current_key, values)) if *current_key == key => values.push(value),
+            _ => {
+                if let Some((key, values)) = current.replace((key, vec![value])) {
+                    compare(key, values)?;
+                }
+            }
+        }
+    }
+    if let Some((key, values)) = current {
+        compare(key, values)?;
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{test_utils::create_test_rw_db, transaction::DbTxMut, DatabaseError};
+    use reth_primitives::{Address, StorageEntry, B256, U256};
+
+    #[test]
+    fn compare_dupsort_rows() {
+        let (produced, reference) = (create_test_rw_db(), create_test_rw_db());
+        let slot = |key, value: u64| StorageEntry {
+            key: B256::with_last_byte(key),
+            value: U256::from(value),
+        };
+        let (same, changed, extra) =
+            (Address::with_last_byte(1), Address::with_last_byte(2), Address::with_last_byte(3));
+        produced
+            .update(|tx| {
+                tx.put::<tables::PlainStorageState>(same, slot(1, 1))?;
+                tx.put::<tables::PlainStorageState>(same, slot(2, 2))?;
+                tx.put::<tables::PlainStorageState>(changed, slot(1, 1))?;
+                Ok::<_, DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+        reference
+            .update(|tx| {
+                tx.put::<tables::PlainStorageState>(same, slot(1, 1))?;
+                tx.put::<tables::PlainStorageState>(same, slot(2, 2))?;
+                tx.put::<tables::PlainStorageState>(changed, slot(1, 2))?;
+                // Rows only the reference has aren't compared.
+                tx.put::<tables::PlainStorageState>(extra, slot(1, 1))?;
+                Ok::<_, DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+
+        let mut mismatches = Mismatches::new(StageId::Execution, 10);
+        let rows = compare_table::<tables::PlainStorageState, _, _>(
+            &produced.tx().unwrap(),
+            &reference.tx().unwrap(),
+            &mut mismatches,
+        )
+        .unwrap();
+        assert_eq!(rows, 3);
+        assert!(!mismatches.contains(&(tables::PlainStorageState::NAME, same.to_vec())));
+        assert!(mismatches.contains(&(tables::PlainStorageState::NAME, changed.to_vec())));
+        assert!(!mismatches.contains(&(tables::PlainStorageState::NAME, extra.to_vec())));
+    }
+}
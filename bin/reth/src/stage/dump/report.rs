@@ -0,0 +1,162 @@
+//! Summary of a dump, written with `--report`.
+use super::{
+    profile::{profile_table, DryRunProfile, Profiled},
+    source::ReadSource,
+    DeepVerification, DumpProgress, StageCommand,
+};
+use reth_db::{table::Table, tables};
+use reth_primitives::{stage::StageId, B256};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+use tracing::info;
+
+/// Summary of a dump.
+///
+/// The schema is kept stable, so that the reports of different reth versions can be compared with
+/// `reth db compare-summaries`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct DumpReport {
+    /// The dumped stage.
+    pub(crate) stage: String,
+    /// The first dumped block.
+    pub(crate) from: u64,
+    /// The last dumped block.
+    pub(crate) to: u64,
+    /// The number of rows of every non-empty table of the output database.
+    pub(crate) rows: BTreeMap<String, usize>,
+    /// The outcome of the dry-run, if one was executed.
+    pub(crate) dry_run: Option<DryRunReport>,
+    /// Whether the dump stopped before importing every table, because it ran out of time.
+    #[serde(default)]
+    pub(crate) partial: bool,
+    /// The rows read back with `--deep-verify`, if passed.
+    #[serde(default)]
+    pub(crate) deep_verification: Option<DeepVerification>,
+    /// Where the rows of every non-empty table of the output database were read from.
+    #[serde(default)]
+    pub(crate) sources: BTreeMap<String, ReadSource>,
+}
+
+/// Outcome of a dump dry-run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct DryRunReport {
+    /// The state root computed by the dry-run, for the stages which compute one.
+    pub(crate) state_root: Option<B256>,
+    /// The keys of the derived entries which don't match the imported source tables.
+    pub(crate) mismatches: Vec<String>,
+    /// The error the dry-run failed with, if any.
+    pub(crate) error: Option<String>,
+    /// The resource usage of the dry-run, if it succeeded.
+    #[serde(default)]
+    pub(crate) profile: Option<DryRunProfile>,
+}
+
+/// Error of a dry-run whose derived entries don't match the imported source tables.
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub(crate) struct MismatchError {
+    pub(crate) message: String,
+    pub(crate) keys: Vec<String>,
+}
+
+impl DumpReport {
+    /// Creates the report of a dump without dry-run.
+    pub(crate) fn new(
+        stage: StageId,
+        command: &StageCommand,
+        rows: BTreeMap<String, usize>,
+    ) -> Self {
+        Self {
+            stage: stage.to_string(),
+            from: command.from,
+            to: command.to,
+            sources: rows.keys().map(|table| (table.clone(), ReadSource::database())).collect(),
+            rows,
+            dry_run: None,
+            partial: false,
+            deep_verification: command.deep_verification(),
+        }
+    }
+
+    /// Records where the headers tables were read from. Every other table is read from the
+    /// database.
+    pub(crate) fn headers_source(mut self, source: ReadSource) -> Self {
+        for table in [tables::CanonicalHeaders::NAME, tables::HeaderTD::NAME, tables::Headers::NAME]
+        {
+            if let Some(table_source) = self.sources.get_mut(table) {
+                *table_source = source.clone();
+            }
+        }
+        self
+    }
+
+    /// Marks the dump as stopped before importing every table.
+    pub(crate) fn partial(mut self) -> Self {
+        self.partial = true;
+        self
+    }
+
+    /// Records the outcome of the dry-run, writes the report to `--report` if passed, and returns
+    /// the dry-run error, if any.
+    ///
+    /// The profile of a successful dry-run is printed as a table, unless `--machine` is passed.
+    pub(crate) fn finish(
+        mut self,
+        command: &StageCommand,
+        dry_run: Option<eyre::Result<Profiled<Option<B256>>>>,
+        progress: Option<&dyn DumpProgress>,
+    ) -> eyre::Result<()> {
+        let mut result = Ok(());
+        if let Some(dry_run) = dry_run {
+            self.dry_run = Some(match &dry_run {
+                Ok(run) => {
+                    if !command.machine {
+                        println!("{}", profile_table(&self.stage, &run.profile));
+                    }
+                    DryRunReport {
+                        state_root: run.outcome,
+                        mismatches: vec![],
+                        error: None,
+                        profile: Some(run.profile.clone()),
+                    }
+                }
+                Err(err) => DryRunReport {
+                    state_root: None,
+                    mismatches: err
+                        .downcast_ref::<MismatchError>()
+                        .map(|err| err.keys.clone())
+                        .unwrap_or_default(),
+                    error: Some(format!("{err:#}")),
+                    profile: None,
+                },
+            });
+            result = dry_run.map(drop);
+        }
+
+        if let Some(path) = &command.report {
+            self.write(path)?;
+        }
+        if command.machine {
+            println!("{}", serde_json::to_string(&self)?);
+        }
+        if let Some(progress) = progress {
+            progress.on_complete(&self);
+        }
+
+        result
+    }
+
+    /// Writes the report as JSON.
+    fn write(&self, path: &Path) -> eyre::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        info!(target: "reth::cli", stage = self.stage, path = ?path, "Wrote report");
+        Ok(())
+    }
+
+    /// Reads a report written by [`DumpReport::finish`].
+    pub(crate) fn read(path: &Path) -> eyre::Result<Self> {
+        let report = std::fs::read_to_string(path)?;
+        serde_json::from_str(&report)
+            .map_err(|err| eyre::eyre!("Invalid report at {}: {err}", path.display()))
+    }
+}
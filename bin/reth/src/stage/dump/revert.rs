@@ -0,0 +1,225 @@
+//! The state of the source database at an earlier block, from its changesets.
+//!
+//! The dumpers read the source through a read-only transaction and apply these reverts to what
+//! they copied, instead of unwinding the source in a transaction they never commit, so that they
+//! work with `--db.read-only`.
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
+    models::BlockNumberAddress,
+    table::{DupSort, Table},
+    tables,
+    transaction::{DbTx, DbTxMut},
+    BlockNumberList, DatabaseError,
+};
+use reth_primitives::{keccak256, Account, Address, BlockNumber, StorageEntry, B256, U256};
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+/// The accounts and storage slots changed after a block, with their values at that block.
+#[derive(Debug, Default)]
+pub(crate) struct StateReverts {
+    /// The changed accounts, `None` if they didn't exist at the block.
+    pub(crate) accounts: BTreeMap<Address, Option<Account>>,
+    /// The changed storage slots of every account, zero if they were unset at the block.
+    pub(crate) storage: BTreeMap<Address, BTreeMap<B256, U256>>,
+}
+
+impl StateReverts {
+    /// Reads the reverts of every block after `block` from the changesets of `tx`.
+    pub(crate) fn after<TX: DbTx>(tx: &TX, block: BlockNumber) -> Result<Self, DatabaseError> {
+        let mut reverts = Self::default();
+        // The changesets are walked in block order, so the first one of a key holds its value at
+        // `block`.
+        for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk(Some(block + 1))? {
+            let (_, before) = entry?;
+            reverts.accounts.entry(before.address).or_insert(before.info);
+        }
+        for entry in tx
+            .cursor_read::<tables::StorageChangeSet>()?
+            .walk(Some(BlockNumberAddress((block + 1, Address::ZERO))))?
+        {
+            let (key, before) = entry?;
+            reverts
+                .storage
+                .entry(key.address())
+                .or_default()
+                .entry(before.key)
+                .or_insert(before.value);
+        }
+        Ok(reverts)
+    }
+
+    /// The account at `address` at the block, given the one of the source.
+    pub(crate) fn account(&self, address: Address, current: Option<Account>) -> Option<Account> {
+        self.accounts.get(&address).copied().unwrap_or(current)
+    }
+
+    /// Reverts the [`tables::PlainAccountState`] and [`tables::PlainStorageState`] of `tx`, only
+    /// touching the storage slots within `slots` if given.
+    pub(crate) fn revert_plain_state<TX: DbTxMut + DbTx>(
+        &self,
+        tx: &TX,
+        slots: Option<&RangeInclusive<B256>>,
+    ) -> Result<(), DatabaseError> {
+        self.revert::<tables::PlainAccountState, tables::PlainStorageState, _>(
+            tx,
+            |address| address,
+            |slot| slot,
+            slots,
+        )
+    }
+
+    /// Reverts the [`tables::HashedAccount`] and [`tables::HashedStorage`] of `tx`, only
+    /// touching the hashed storage slots within `slots` if given.
+    pub(crate) fn revert_hashed_state<TX: DbTxMut + DbTx>(
+        &self,
+        tx: &TX,
+        slots: Option<&RangeInclusive<B256>>,
+    ) -> Result<(), DatabaseError> {
+        self.revert::<tables::HashedAccount, tables::HashedStorage, _>(
+            tx, keccak256, keccak256, slots,
+        )
+    }
+
+    fn revert<A, S, TX>(
+        &self,
+        tx: &TX,
+        account_key: impl Fn(Address) -> A::Key,
+        slot_key: impl Fn(B256) -> B256,
+        slots: Option<&RangeInclusive<B256>>,
+    ) -> Result<(), DatabaseError>
+    where
+        A: Table<Value = Account>,
+        S: DupSort<Key = A::Key, SubKey = B256, Value = StorageEntry>,
+        TX: DbTxMut + DbTx,
+    {
+        let mut accounts = tx.cursor_write::<A>()?;
+        for (address, account) in &self.accounts {
+            let key = account_key(*address);
+            match account {
+                Some(account) => accounts.upsert(key, *account)?,
+                None => {
+                    if accounts.seek_exact(key)?.is_some() {
+                        accounts.delete_current()?;
+                    }
+                }
+            }
+        }
+
+        let mut storage = tx.cursor_dup_write::<S>()?;
+        for (address, changed) in &self.storage {
+            let key = account_key(*address);
+            for (slot, value) in changed {
+                let slot = slot_key(*slot);
+                if slots.map_or(false, |slots| !slots.contains(&slot)) {
+                    continue
+                }
+                if storage
+                    .seek_by_key_subkey(key.clone(), slot)?
+                    .filter(|entry| entry.key == slot)
+                    .is_some()
+                {
+                    storage.delete_current()?;
+                }
+                if *value != U256::ZERO {
+                    storage.upsert(key.clone(), StorageEntry { key: slot, value: *value })?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The shards of a key of a history index, in key order, as an unwind to `block` leaves them:
+/// the blocks after it are dropped along with the shards left empty, and the last shard left is
+/// keyed by `last` again.
+pub(crate) fn unwind_shards<K>(
+    shards: impl IntoIterator<Item = (K, BlockNumberList)>,
+    block: BlockNumber,
+    last: K,
+) -> Vec<(K, BlockNumberList)> {
+    let mut unwound = vec![];
+    for (key, list) in shards {
+        let blocks = list.iter(0).collect::<Vec<_>>();
+        let kept = blocks.iter().take_while(|number| **number as u64 <= block).count();
+        if kept > 0 {
+            unwound.push((key, BlockNumberList::new_pre_sorted(&blocks[..kept])));
+        }
+        if kept < blocks.len() {
+            break
+        }
+    }
+    if let Some((key, _)) = unwound.last_mut() {
+        *key = last;
+    }
+    unwound
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{database::Database, models::AccountBeforeTx, test_utils::create_test_rw_db};
+
+    #[test]
+    fn revert_to_block() {
+        let db = create_test_rw_db();
+        let (kept, created, destroyed) =
+            (Address::with_last_byte(1), Address::with_last_byte(2), Address::with_last_byte(3));
+        let account = |nonce| Account { nonce, ..Default::default() };
+        let slot =
+            |value: u64| StorageEntry { key: B256::with_last_byte(1), value: U256::from(value) };
+        let tx = db.tx_mut().unwrap();
+        // `kept` changed at blocks 2 and 3, `created` was created at block 3 and `destroyed`
+        // destroyed at block 3.
+        tx.put::<tables::PlainAccountState>(kept, account(3)).unwrap();
+        tx.put::<tables::PlainAccountState>(created, account(0)).unwrap();
+        tx.put::<tables::PlainStorageState>(kept, slot(3)).unwrap();
+        tx.put::<tables::PlainStorageState>(created, slot(3)).unwrap();
+        for (block, address, info) in [
+            (1, kept, None),
+            (2, kept, Some(account(1))),
+            (3, kept, Some(account(2))),
+            (3, created, None),
+            (3, destroyed, Some(account(1))),
+        ] {
+            tx.put::<tables::AccountChangeSet>(block, AccountBeforeTx { address, info }).unwrap();
+        }
+        for (block, address, value) in
+            [(2, kept, 1), (3, kept, 2), (3, created, 0), (3, destroyed, 5)]
+        {
+            tx.put::<tables::StorageChangeSet>(BlockNumberAddress((block, address)), slot(value))
+                .unwrap();
+        }
+        tx.commit().unwrap();
+
+        let reverts = db.view(|tx| StateReverts::after(tx, 1)).unwrap().unwrap();
+        assert_eq!(reverts.account(kept, Some(account(3))), Some(account(1)));
+        db.update(|tx| reverts.revert_plain_state(tx, None)).unwrap().unwrap();
+
+        let tx = db.tx().unwrap();
+        assert_eq!(tx.get::<tables::PlainAccountState>(kept).unwrap(), Some(account(1)));
+        assert_eq!(tx.get::<tables::PlainAccountState>(created).unwrap(), None);
+        assert_eq!(tx.get::<tables::PlainAccountState>(destroyed).unwrap(), Some(account(1)));
+        let mut storage = tx.cursor_dup_read::<tables::PlainStorageState>().unwrap();
+        let slots = storage.walk(None).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(slots, [(kept, slot(1)), (destroyed, slot(5))]);
+    }
+
+    #[test]
+    fn unwind_history_shards() {
+        let list = |blocks: &[usize]| BlockNumberList::new_pre_sorted(blocks);
+        let shards =
+            || vec![(10, list(&[1, 5, 10])), (20, list(&[15, 20])), (u64::MAX, list(&[25]))];
+
+        assert_eq!(
+            unwind_shards(shards(), 30, u64::MAX),
+            [(10, list(&[1, 5, 10])), (20, list(&[15, 20])), (u64::MAX, list(&[25]))]
+        );
+        assert_eq!(
+            unwind_shards(shards(), 16, u64::MAX),
+            [(10, list(&[1, 5, 10])), (u64::MAX, list(&[15]))]
+        );
+        assert_eq!(unwind_shards(shards(), 12, u64::MAX), [(u64::MAX, list(&[1, 5, 10]))]);
+        assert_eq!(unwind_shards(shards(), 0, u64::MAX), []);
+    }
+}
@@ -0,0 +1,82 @@
+//! Temporary directories of a dump, e.g. the environment the `files` format is exported from.
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use tracing::debug;
+
+/// Allocates the temporary directories of a dump under `--scratch-dir`.
+#[derive(Debug, Clone)]
+pub(crate) struct ScratchDirs {
+    root: PathBuf,
+}
+
+impl ScratchDirs {
+    /// Allocates under `root`, or the system temp dir if not set.
+    pub(crate) fn new(root: Option<PathBuf>) -> Self {
+        Self { root: root.unwrap_or_else(std::env::temp_dir) }
+    }
+
+    /// The directory all scratch directories are created in.
+    pub(crate) fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Creates an empty directory named after `purpose`.
+    ///
+    /// The directory and everything in it is removed once the returned guard is dropped, be it
+    /// after success, on an early error return or while unwinding from a panic.
+    pub(crate) fn create(&self, purpose: &str) -> eyre::Result<TempDir> {
+        std::fs::create_dir_all(&self.root)?;
+        let dir = tempfile::Builder::new()
+            .prefix(&format!("reth-dump-{purpose}-"))
+            .tempdir_in(&self.root)?;
+        debug!(target: "reth::cli", path = ?dir.path(), "Created scratch directory");
+        Ok(dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::init_db;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    #[test]
+    fn removed_on_drop() {
+        let root = tempfile::tempdir().unwrap();
+        let scratch = ScratchDirs::new(Some(root.path().join("scratch")));
+
+        let path = {
+            let dir = scratch.create("mdbx").unwrap();
+            init_db(dir.path(), None).unwrap();
+            assert!(dir.path().starts_with(scratch.root()));
+            assert!(dir
+                .path()
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("reth-dump-mdbx-"));
+            dir.path().to_path_buf()
+        };
+        assert!(!path.exists());
+
+        let mut path = None;
+        let result = (|| -> eyre::Result<()> {
+            let dir = scratch.create("verify")?;
+            path = Some(dir.path().to_path_buf());
+            std::fs::write(dir.path().join("expected.bin"), [1])?;
+            eyre::bail!("export failed")
+        })();
+        assert!(result.is_err());
+        assert!(!path.take().unwrap().exists());
+
+        let panicked = catch_unwind(AssertUnwindSafe(|| {
+            let dir = scratch.create("verify").unwrap();
+            path = Some(dir.path().to_path_buf());
+            panic!("dry-run panicked");
+        }));
+        assert!(panicked.is_err());
+        assert!(!path.unwrap().exists());
+
+        assert_eq!(std::fs::read_dir(scratch.root()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,345 @@
+//! `dump-stage self-test`: every dumper run with its dry-run against a synthetic chain, to check a
+//! build end-to-end without a node.
+use super::{dump_block, run_stages, BlockCommand, ScratchDirs, StageCommand, Stages};
+use crate::{init::init_genesis, utils::DbTool};
+use clap::Parser;
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use reth_db::{database::Database, init_db};
+use reth_primitives::{
+    proofs, public_key_to_address, sign_message, AccessList, Address, Block, Bloom, Bytes, Chain,
+    ChainSpec, ChainSpecBuilder, Genesis, GenesisAccount, Header, ReceiptWithBloom, Receipts,
+    SealedBlockWithSenders, Transaction, TransactionKind, TransactionSigned, TxEip1559, B256,
+    EMPTY_OMMER_ROOT, U256,
+};
+use reth_provider::{
+    BlockExecutor, BlockWriter, BundleStateWithReceipts, ProviderFactory, StateProviderFactory,
+};
+use reth_revm::{
+    database::StateProviderDatabase, db::states::bundle_state::BundleRetention,
+    processor::EVMProcessor,
+};
+use secp256k1::{SecretKey, SECP256K1};
+use std::sync::Arc;
+use tracing::info;
+
+/// The gas limit of every synthetic block.
+const GAS_LIMIT: u64 = 30_000_000;
+/// The contract every other synthetic transaction calls.
+pub(super) const CONTRACT: Address = Address::new([0xcc; 20]);
+/// `NUMBER CALLER SSTORE STOP`: stores the block number in the slot of the caller, so that the
+/// blocks change the storage too.
+const CONTRACT_CODE: [u8; 4] = [0x43, 0x33, 0x55, 0x00];
+/// The number of funded senders of the synthetic transactions.
+const SENDERS: u8 = 4;
+
+/// The arguments for the `reth dump-stage self-test` command
+#[derive(Debug, Clone, Parser)]
+pub struct SelfTestCommand {
+    /// The number of synthetic blocks after the genesis block.
+    #[arg(
+        long,
+        value_name = "BLOCKS",
+        default_value_t = 8,
+        value_parser = clap::value_parser!(u64).range(2..=64)
+    )]
+    blocks: u64,
+    /// The number of transactions of every synthetic block.
+    #[arg(long, value_name = "TRANSACTIONS", default_value_t = 4)]
+    transactions: usize,
+}
+
+/// Builds a synthetic chain in a scratch database, then dumps every stage with `--dry-run` and a
+/// block with `--dry-run` from it, exactly as `dump-stage` does from a node.
+///
+/// The dry-runs check their output by themselves: the receipts and gas used of the execution,
+/// the hashed state, the state root of the merkle stage, the recovered senders, the transaction
+/// lookup and the history indices. The outcome
+/// of every dumper is printed, and the command fails if any of them did.
+pub(crate) async fn run_self_test(
+    command: &SelfTestCommand,
+    scratch: &ScratchDirs,
+) -> eyre::Result<()> {
+    let dir = scratch.create("self-test")?;
+    let keys = synthetic_keys();
+    let chain = Arc::new(
+        ChainSpecBuilder::default()
+            .chain(Chain::dev())
+            .genesis(synthetic_genesis(&keys))
+            .shanghai_activated()
+            .build(),
+    );
+
+    let db = Arc::new(init_db(dir.path().join("source"), None)?);
+    init_genesis(db.clone(), chain.clone())?;
+    build_chain(&db, chain.clone(), &keys, command.blocks, command.transactions)?;
+    info!(target: "reth::cli", blocks = command.blocks, transactions = command.transactions, path = ?dir.path(), "Built synthetic chain");
+
+    let tool = DbTool::new(&db, chain)?;
+    let stage_command = |name: &str| {
+        StageCommand::try_parse_from([
+            "reth",
+            "--output-db",
+            name,
+            "--from",
+            "1",
+            "--to",
+            &command.blocks.to_string(),
+            "--dry-run",
+        ])
+    };
+    let stages = [
+        Stages::Execution(stage_command("execution")?),
+        Stages::StorageHashing(stage_command("storage-hashing")?),
+        Stages::AccountHashing(stage_command("account-hashing")?),
+        Stages::Merkle(stage_command("merkle")?),
+        Stages::Senders(stage_command("senders")?),
+        Stages::TransactionLookup(stage_command("transaction-lookup")?),
+        Stages::IndexAccountHistory(stage_command("index-account-history")?),
+        Stages::IndexStorageHistory(stage_command("index-storage-history")?),
+    ];
+
+    let mut outcomes = vec![];
+    for stages in stages {
+        let name = stages.name();
+        outcomes.push((name, run_stages(&tool, stages, dir.path(), scratch).await));
+    }
+    let block_command = BlockCommand::try_parse_from([
+        "reth",
+        "--number",
+        &command.blocks.to_string(),
+        "--output-db",
+        &dir.path().join("block").to_string_lossy(),
+        "--dry-run",
+    ])?;
+    outcomes.push(("block", dump_block(&tool, &block_command).await));
+
+    let mut table = ComfyTable::new();
+    table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+    table.set_header(["Dumper", "Result"]);
+    for (name, outcome) in &outcomes {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(name)).add_cell(Cell::new(match outcome {
+            Ok(()) => "pass".to_string(),
+            Err(err) => format!("fail: {err:#}"),
+        }));
+        table.add_row(row);
+    }
+    println!("{table}");
+
+    let failed = outcomes.iter().filter(|(_, outcome)| outcome.is_err()).count();
+    if failed > 0 {
+        eyre::bail!("{failed} of {} dumpers failed the self-test.", outcomes.len())
+    }
+
+    Ok(())
+}
+
+/// The keys of the senders of the synthetic transactions.
+pub(super) fn synthetic_keys() -> Vec<SecretKey> {
+    (1..=SENDERS)
+        .map(|byte| SecretKey::from_slice(&[byte; 32]).expect("a valid secret key"))
+        .collect()
+}
+
+/// A genesis funding the senders of `keys` and deploying [`CONTRACT`].
+pub(super) fn synthetic_genesis(keys: &[SecretKey]) -> Genesis {
+    let balance = U256::from(10).pow(U256::from(24));
+    Genesis::default()
+        .with_gas_limit(GAS_LIMIT)
+        .extend_accounts(keys.iter().map(|key| {
+            (
+                public_key_to_address(key.public_key(SECP256K1)),
+                GenesisAccount::default().with_balance(balance),
+            )
+        }))
+        .extend_accounts([(
+            CONTRACT,
+            GenesisAccount::default().with_code(Some(Bytes::from_static(&CONTRACT_CODE))),
+        )])
+}
+
+/// Appends `blocks` blocks of `transactions` transactions each on top of the genesis block of
+/// `db`, with their state, hashed state, trie and history. The blocks are 12 seconds apart and
+/// follow the hardforks of `chain`.
+///
+/// The gas used, receipts root, logs bloom and state root of a block are only known once it's
+/// executed, so every block is executed once to fill its header. The whole chain is then executed
+/// again with the receipts verified, producing the state written to the database along with it.
+pub(super) fn build_chain<DB: Database>(
+    db: &DB,
+    chain: Arc<ChainSpec>,
+    keys: &[SecretKey],
+    blocks: u64,
+    transactions: usize,
+) -> eyre::Result<()> {
+    let factory = ProviderFactory::new(db, chain.clone());
+    let senders =
+        keys.iter().map(|key| public_key_to_address(key.public_key(SECP256K1))).collect::<Vec<_>>();
+
+    let provider = factory.provider()?;
+    let mut executor =
+        EVMProcessor::new_with_db(chain.clone(), StateProviderDatabase::new(factory.latest()?));
+    let mut parent = chain.sealed_genesis_header();
+    let mut nonces = vec![0; keys.len()];
+    let mut built = vec![];
+    for number in 1..=blocks {
+        let base_fee =
+            parent.next_block_base_fee(chain.base_fee_params).expect("london is active at genesis");
+        let mut body = vec![];
+        let mut block_senders = vec![];
+        for index in 0..transactions {
+            let sender = index % keys.len();
+            // Every other transaction is a transfer to an account created by the block.
+            let to = if index % 2 == 0 { CONTRACT } else { Address::repeat_byte(number as u8) };
+            let transaction = Transaction::Eip1559(TxEip1559 {
+                chain_id: chain.chain.id(),
+                nonce: nonces[sender],
+                gas_limit: 100_000,
+                max_fee_per_gas: 2 * base_fee as u128,
+                max_priority_fee_per_gas: 1,
+                to: TransactionKind::Call(to),
+                value: U256::from(number).into(),
+                access_list: AccessList::default(),
+                input: Bytes::default(),
+            });
+            let signature = sign_message(
+                B256::from_slice(&keys[sender].secret_bytes()),
+                transaction.signature_hash(),
+            )?;
+            body.push(TransactionSigned::from_transaction_and_signature(transaction, signature));
+            block_senders.push(senders[sender]);
+            nonces[sender] += 1;
+        }
+
+        let timestamp = parent.timestamp + 12;
+        // Blocks before Shanghai have no withdrawals.
+        let withdrawals = chain.is_shanghai_active_at_timestamp(timestamp).then(Vec::new);
+        let mut block = Block {
+            header: Header {
+                parent_hash: parent.hash(),
+                ommers_hash: EMPTY_OMMER_ROOT,
+                beneficiary: Address::with_last_byte(0xfe),
+                transactions_root: proofs::calculate_transaction_root(&body),
+                withdrawals_root: withdrawals.as_deref().map(proofs::calculate_withdrawals_root),
+                number,
+                gas_limit: GAS_LIMIT,
+                timestamp,
+                base_fee_per_gas: Some(base_fee),
+                ..Default::default()
+            },
+            body,
+            ommers: vec![],
+            withdrawals,
+        };
+        let (receipts, gas_used) =
+            executor.execute_transactions(&block, U256::ZERO, Some(block_senders.clone()))?;
+        executor.apply_post_execution_state_change(&block, U256::ZERO)?;
+        executor.db_mut().merge_transitions(BundleRetention::Reverts);
+
+        let receipts = receipts.into_iter().map(ReceiptWithBloom::from).collect::<Vec<_>>();
+        block.header.gas_used = gas_used;
+        block.header.receipts_root = proofs::calculate_receipt_root(&receipts);
+        block.header.logs_bloom =
+            receipts.iter().fold(Bloom::ZERO, |bloom, receipt| bloom | receipt.bloom);
+        block.header.state_root = BundleStateWithReceipts::new(
+            executor.db_mut().bundle_state.clone(),
+            Receipts::new(),
+            number,
+        )
+        .state_root_slow(provider.tx_ref())?;
+
+        let block = block.seal_slow();
+        parent = block.header.clone();
+        built.push(
+            SealedBlockWithSenders::new(block, block_senders).expect("a sender per transaction"),
+        );
+    }
+    drop(executor);
+    drop(provider);
+
+    let mut executor =
+        EVMProcessor::new_with_db(chain.clone(), StateProviderDatabase::new(factory.latest()?));
+    for block in &built {
+        executor.execute_and_verify_receipt(
+            &block.block.clone().unseal(),
+            U256::ZERO,
+            Some(block.senders.clone()),
+        )?;
+    }
+    let state = executor.take_output_state();
+    drop(executor);
+
+    let provider = factory.provider_rw()?;
+    provider.append_blocks_with_bundle_state(built, state, None)?;
+    provider.commit()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::DatabaseArgs;
+
+    #[tokio::test]
+    async fn every_dumper_passes_on_synthetic_chain() {
+        let root = tempfile::tempdir().unwrap();
+        let command = SelfTestCommand::try_parse_from(["reth", "--blocks", "3"]).unwrap();
+        run_self_test(&command, &ScratchDirs::new(Some(root.path().to_path_buf()))).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn dumpers_read_read_only_source() {
+        let root = tempfile::tempdir().unwrap();
+        let keys = synthetic_keys();
+        let chain = Arc::new(
+            ChainSpecBuilder::default()
+                .chain(Chain::dev())
+                .genesis(synthetic_genesis(&keys))
+                .shanghai_activated()
+                .build(),
+        );
+        let path = root.path().join("source");
+        {
+            let db = Arc::new(init_db(&path, None).unwrap());
+            init_genesis(db.clone(), chain.clone()).unwrap();
+            build_chain(&db, chain.clone(), &keys, 6, 4).unwrap();
+        }
+
+        let db = DatabaseArgs { read_only: true, ..Default::default() }.init_db(&path).unwrap();
+        let tool = DbTool::new(&db, chain).unwrap();
+        let scratch = ScratchDirs::new(Some(root.path().join("scratch")));
+        // The range ends before the tip, so the dumpers have to revert the state of the source.
+        let command = |name: &str, args: &[&str]| {
+            StageCommand::try_parse_from(
+                ["reth", "--output-db", name, "--from", "2", "--to", "4", "--dry-run"]
+                    .into_iter()
+                    .chain(args.iter().copied()),
+            )
+            .unwrap()
+        };
+        for stages in [
+            Stages::Execution(command("execution", &[])),
+            Stages::StorageHashing(command("storage-hashing", &[])),
+            Stages::AccountHashing(command("account-hashing", &[])),
+            Stages::Merkle(command("merkle", &[])),
+            Stages::IndexAccountHistory(command("index-account-history", &[])),
+            Stages::IndexStorageHistory(command("index-storage-history", &[])),
+        ] {
+            let name = stages.name();
+            if let Err(err) = run_stages(&tool, stages, root.path(), &scratch).await {
+                panic!("The {name} dump failed: {err:#}");
+            }
+        }
+
+        let err = run_stages(
+            &tool,
+            Stages::Merkle(command("merkle-trie", &["--with-trie"])),
+            root.path(),
+            &scratch,
+        )
+        .await
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("--db.read-only"));
+    }
+}
@@ -0,0 +1,170 @@
+use super::{
+    import_table_with_range, log_imported_rows, prune_unmatched_blocks, repeat_dry_run, setup,
+    transaction_range, DumpProgress, DumpReport, Mismatches, StageCommand,
+};
+use crate::utils::DbTool;
+use eyre::Result;
+use reth_db::{cursor::DbCursorRO, database::Database, tables, transaction::DbTx, DatabaseEnv};
+use reth_primitives::{stage::StageId, Address, TxHash, TxNumber};
+use std::{collections::BTreeMap, ops::Range, time::Instant};
+use tracing::{info, warn};
+
+pub(crate) async fn dump_senders_stage<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &StageCommand,
+    progress: Option<&dyn DumpProgress>,
+) -> Result<()> {
+    let (from, to) = (command.from, command.to);
+    let (output_db, _) = setup(StageId::SenderRecovery, command, db_tool)?;
+
+    let (from_tx, to_tx) = transaction_range(db_tool.db, from, to)?;
+
+    import_table_with_range::<tables::Transactions, _>(
+        &output_db, db_tool, command, from_tx, to_tx, progress,
+    )?;
+    import_table_with_range::<tables::TxSenders, _>(
+        &output_db, db_tool, command, from_tx, to_tx, progress,
+    )?;
+
+    prune_unmatched_blocks(&output_db, db_tool, command)?;
+    let rows = log_imported_rows(&output_db, StageId::SenderRecovery)?;
+
+    let dry_run = if command.should_run() {
+        command.check_deadline()?;
+        let dry_run_from = command.dry_run_from()?;
+        Some(
+            repeat_dry_run(StageId::SenderRecovery, command, || {
+                std::future::ready(dry_run(
+                    &output_db,
+                    to,
+                    dry_run_from,
+                    command.max_mismatches,
+                    command.dry_run_jobs,
+                    progress,
+                ))
+            })
+            .await
+            .map(|run| run.map(|_| None)),
+        )
+    } else {
+        None
+    };
+
+    DumpReport::new(StageId::SenderRecovery, command, rows).finish(command, dry_run, progress)
+}
+
+/// Recovers the senders again and compares them against the imported [`tables::TxSenders`].
+///
+/// Every transaction is recovered independently, so the range is split evenly across `jobs`
+/// threads.
+fn dry_run(
+    output_db: &DatabaseEnv,
+    to: u64,
+    from: u64,
+    max_mismatches: usize,
+    jobs: u64,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<()> {
+    info!(target: "reth::cli", stage = %StageId::SenderRecovery, from, to, jobs, "Recovering senders.");
+    let started = Instant::now();
+
+    let (from_tx, to_tx) = transaction_range(output_db, from, to)?;
+    let chunk_size = ((to_tx - from_tx + jobs - 1) / jobs).max(1);
+    let chunks = (from_tx..to_tx)
+        .step_by(chunk_size as usize)
+        .map(|start| start..(start + chunk_size).min(to_tx))
+        .collect::<Vec<_>>();
+
+    let results = std::thread::scope(|scope| {
+        let handles = chunks
+            .into_iter()
+            .map(|chunk| scope.spawn(move || recover_senders(output_db, chunk)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("sender recovery job panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    // Chunks are merged in order, so the mismatches are logged by increasing transaction number.
+    let mut mismatches = Mismatches::new(StageId::SenderRecovery, max_mismatches);
+    let mut first = None;
+    let mut transactions = 0;
+    for result in results {
+        let (recovered, chunk_mismatches) = result?;
+        transactions += recovered;
+        for SenderMismatch { tx_number, hash, recovered, stored } in chunk_mismatches {
+            first.get_or_insert((tx_number, hash));
+            mismatches.insert(
+                tx_number,
+                format!("TxSenders entry of transaction {tx_number} ({hash}) does not match the recovered sender. Expected: {recovered:?}. Got: {stored:?}"),
+            );
+        }
+    }
+
+    if let Some((tx_number, hash)) = first {
+        warn!(target: "reth::cli", stage = %StageId::SenderRecovery, tx_number, %hash, "First mismatched transaction");
+
+        let blocks = output_db.view(|tx| {
+            let mut blocks = BTreeMap::<_, usize>::new();
+            for entry in tx.cursor_read::<tables::BlockBodyIndices>()?.walk_range(from..=to)? {
+                let (block, indices) = entry?;
+                let entries = indices.tx_num_range().filter(|tx| mismatches.contains(tx)).count();
+                if entries > 0 {
+                    blocks.insert(block, entries);
+                }
+            }
+            Ok::<_, eyre::Report>(blocks)
+        })??;
+        mismatches.finish(blocks)?;
+    }
+
+    let elapsed = started.elapsed();
+    let throughput = transactions as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    info!(target: "reth::cli", stage = %StageId::SenderRecovery, transactions, ?elapsed, tx_per_second = throughput as u64, "Validated senders.");
+
+    // The senders of the whole range are recovered in one pass.
+    if let Some(progress) = progress {
+        progress.on_dry_run_block(StageId::SenderRecovery, to);
+    }
+
+    info!(target: "reth::cli", stage = %StageId::SenderRecovery, from, to, "Success.");
+
+    Ok(())
+}
+
+/// Transaction whose recovered sender doesn't match the imported [`tables::TxSenders`] entry.
+struct SenderMismatch {
+    tx_number: TxNumber,
+    hash: TxHash,
+    recovered: Option<Address>,
+    stored: Option<Address>,
+}
+
+/// Recovers the senders of the transactions in `range`, returning how many were recovered and
+/// the mismatched ones.
+fn recover_senders(
+    output_db: &DatabaseEnv,
+    range: Range<TxNumber>,
+) -> eyre::Result<(usize, Vec<SenderMismatch>)> {
+    output_db.view(|tx| {
+        let mut mismatches = vec![];
+        let mut transactions = 0;
+        for entry in tx.cursor_read::<tables::Transactions>()?.walk_range(range)? {
+            let (tx_number, transaction) = entry?;
+            let recovered = transaction.recover_signer();
+            let stored = tx.get::<tables::TxSenders>(tx_number)?;
+            if recovered.is_none() || recovered != stored {
+                mismatches.push(SenderMismatch {
+                    tx_number,
+                    hash: transaction.hash(),
+                    recovered,
+                    stored,
+                });
+            }
+            transactions += 1;
+        }
+
+        Ok::<_, eyre::Report>((transactions, mismatches))
+    })?
+}
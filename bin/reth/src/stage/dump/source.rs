@@ -0,0 +1,222 @@
+//! Snapshot aware reads of the tables that can be moved out of the database.
+use super::{import_table_with_range, DumpProgress, StageCommand};
+use crate::utils::DbTool;
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW},
+    database::Database,
+    table::{Decompress, Table},
+    tables,
+    transaction::{DbTx, DbTxMut},
+    DatabaseEnv,
+};
+use reth_nippy_jar::{compression::Compressors, NippyJar, NippyJarCursor};
+use reth_primitives::BlockNumber;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+/// Backend a table range was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TableSource {
+    /// The whole range was read from the database.
+    Database,
+    /// The whole range was read from snapshot files.
+    Snapshot,
+    /// The lower part of the range was read from snapshot files, and the rest from the database.
+    Mixed,
+}
+
+impl fmt::Display for TableSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableSource::Database => write!(f, "database"),
+            TableSource::Snapshot => write!(f, "snapshot"),
+            TableSource::Mixed => write!(f, "mixed"),
+        }
+    }
+}
+
+/// Where the range of a table was read from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ReadSource {
+    /// The backend the range was read from.
+    pub(crate) source: TableSource,
+    /// The file names of the snapshots the range was partly or fully read from.
+    #[serde(default)]
+    pub(crate) snapshots: Vec<String>,
+}
+
+impl ReadSource {
+    /// A range read from the database only.
+    pub(crate) fn database() -> Self {
+        Self { source: TableSource::Database, snapshots: vec![] }
+    }
+}
+
+/// A headers snapshot file generated by `reth db snapshot headers`.
+#[derive(Debug)]
+struct HeadersSnapshot {
+    range: RangeInclusive<BlockNumber>,
+    path: PathBuf,
+}
+
+/// Imports [`tables::CanonicalHeaders`], [`tables::HeaderTD`] and [`tables::Headers`] over a
+/// range.
+///
+/// Blocks which are no longer in the source database are read from the headers snapshots in
+/// `--snapshots-dir`. `reth db snapshot` also generates transactions and receipts snapshots, but
+/// only the headers ones are read here: every other table is always read from the database.
+///
+/// Only the canonical chain can be imported: [`tables::Headers`] is keyed by block number, so it
+/// never holds side chains, which only live in the memory of the blockchain tree of a node.
+pub(crate) fn import_headers_with_range<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &StageCommand,
+    output_db: &DatabaseEnv,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<ReadSource> {
+    let (from, to) = (command.from, command.to);
+    let snapshots_dir = command.snapshots_dir.as_path();
+
+    // Snapshots only ever hold the oldest blocks, so everything below the first header still in
+    // the database has to come from them.
+    let first_in_db = db_tool
+        .db
+        .view(|tx| tx.cursor_read::<tables::Headers>()?.seek(from))??
+        .map(|(number, _)| number)
+        .filter(|number| *number <= to);
+
+    let source = match first_in_db {
+        Some(number) if number == from => ReadSource::database(),
+        Some(number) => ReadSource {
+            source: TableSource::Mixed,
+            snapshots: import_headers_from_snapshots(snapshots_dir, output_db, from..=number - 1)?,
+        },
+        None => ReadSource {
+            source: TableSource::Snapshot,
+            snapshots: import_headers_from_snapshots(snapshots_dir, output_db, from..=to)?,
+        },
+    };
+
+    if let Some(db_from) = first_in_db {
+        import_table_with_range::<tables::CanonicalHeaders, _>(
+            output_db, db_tool, command, db_from, to, progress,
+        )?;
+        import_table_with_range::<tables::HeaderTD, _>(
+            output_db, db_tool, command, db_from, to, progress,
+        )?;
+        import_table_with_range::<tables::Headers, _>(
+            output_db, db_tool, command, db_from, to, progress,
+        )?;
+    }
+
+    for table in [tables::CanonicalHeaders::NAME, tables::HeaderTD::NAME, tables::Headers::NAME] {
+        info!(target: "reth::cli", table, from, to, source = %source.source, snapshots = ?source.snapshots, "Read table");
+    }
+
+    Ok(source)
+}
+
+/// Copies the headers of `range` from the snapshots in `snapshots_dir` into the output database,
+/// returning the file names of the snapshots they were read from.
+fn import_headers_from_snapshots(
+    snapshots_dir: &Path,
+    output_db: &DatabaseEnv,
+    range: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Vec<String>> {
+    let mut snapshots = find_headers_snapshots(snapshots_dir)?;
+    snapshots.sort_by_key(|snapshot| *snapshot.range.start());
+
+    let mut next = *range.start();
+    let mut read = vec![];
+    for snapshot in snapshots {
+        if next > *range.end() {
+            break
+        }
+        if !snapshot.range.contains(&next) {
+            continue
+        }
+
+        let end = (*snapshot.range.end()).min(*range.end());
+        info!(target: "reth::cli", path = ?snapshot.path, from = next, to = end, "Reading headers snapshot");
+
+        let mut jar = NippyJar::load_without_header(&snapshot.path)?;
+        let mut dictionaries = None;
+        let mut decompressors = vec![];
+        if let Some(Compressors::Zstd(zstd)) = jar.compressor_mut() {
+            if zstd.use_dict {
+                dictionaries = zstd.generate_decompress_dictionaries();
+                decompressors = zstd.generate_decompressors(dictionaries.as_ref().expect("qed"))?;
+            }
+        }
+        let mut cursor =
+            NippyJarCursor::new(&jar, (!decompressors.is_empty()).then_some(decompressors))?;
+
+        output_db.update(|tx| {
+            let mut canonical_cursor = tx.cursor_write::<tables::CanonicalHeaders>()?;
+            let mut td_cursor = tx.cursor_write::<tables::HeaderTD>()?;
+            let mut headers_cursor = tx.cursor_write::<tables::Headers>()?;
+
+            for number in next..=end {
+                let row =
+                    cursor.row_by_number((number - snapshot.range.start()) as usize)?.ok_or_else(
+                        || eyre::eyre!("Header {number} is missing from {:?}", snapshot.path),
+                    )?;
+
+                // Columns are laid out as `Headers`, `HeaderTD` and `CanonicalHeaders`.
+                headers_cursor
+                    .append(number, <tables::Headers as Table>::Value::decompress(row[0])?)?;
+                td_cursor
+                    .append(number, <tables::HeaderTD as Table>::Value::decompress(row[1])?)?;
+                canonical_cursor.append(
+                    number,
+                    <tables::CanonicalHeaders as Table>::Value::decompress(row[2])?,
+                )?;
+            }
+
+            Ok::<(), eyre::Report>(())
+        })??;
+
+        next = end + 1;
+        read.extend(snapshot.path.file_name().map(|name| name.to_string_lossy().into_owned()));
+    }
+
+    if next <= *range.end() {
+        eyre::bail!(
+            "Headers {next}..={} are neither in the database nor in any snapshot at {}.",
+            range.end(),
+            snapshots_dir.display()
+        )
+    }
+
+    Ok(read)
+}
+
+/// Finds all headers snapshots in `dir` by their file name.
+fn find_headers_snapshots(dir: &Path) -> eyre::Result<Vec<HeadersSnapshot>> {
+    let mut snapshots = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else { continue };
+        if name.ends_with(".idx") {
+            continue
+        }
+
+        // `snapshot_headers_{start}_{end}_{filters}_{compression}`, see
+        // `reth_snapshot::segments::get_snapshot_segment_file_name`.
+        let parts = name.split('_').collect::<Vec<_>>();
+        if parts.len() != 6 || parts[0] != "snapshot" || parts[1] != "headers" {
+            continue
+        }
+        let (Ok(start), Ok(end)) = (parts[2].parse(), parts[3].parse()) else { continue };
+
+        snapshots.push(HeadersSnapshot { range: start..=end, path });
+    }
+
+    Ok(snapshots)
+}
@@ -0,0 +1,187 @@
+//! Dumps of a range split into several output databases by `--split-size`.
+use super::{dump_stage, DumpProgress, DumpedStage, ExtractManifest, Stages};
+use crate::utils::DbTool;
+use reth_db::{
+    cursor::DbCursorRO, database::Database, models::BlockNumberAddress, table::Table, tables,
+    transaction::DbTx, RawKey, RawTable,
+};
+use reth_primitives::BlockNumber;
+use serde::{Deserialize, Serialize};
+use std::ops::{Range, RangeInclusive};
+use tracing::info;
+
+/// Name of the manifest listing the chunks of a split dump.
+pub(crate) const MANIFEST_FILE: &str = "manifest.json";
+
+/// Chunks of a split dump, written to [`MANIFEST_FILE`] in `--output-db`.
+#[derive(Debug, Serialize, Deserialize)]
+struct SplitManifest {
+    stage: String,
+    split_size: u64,
+    chunks: Vec<Chunk>,
+}
+
+/// An output database holding a contiguous part of the range.
+#[derive(Debug, Serialize, Deserialize)]
+struct Chunk {
+    /// The folder of the output database, relative to `--output-db`.
+    path: String,
+    from: BlockNumber,
+    to: BlockNumber,
+    /// The estimated size of the rows of the range, without the state at `from`.
+    estimated_size: u64,
+}
+
+/// Dumps the range of `stages` into numbered output databases under `--output-db`, each holding
+/// the rows of about `split_size` bytes of blocks.
+///
+/// Every chunk is a complete extract of its range, so each of them is dry-run on its own.
+pub(crate) async fn dump_split<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    stages: &Stages,
+    split_size: u64,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<()> {
+    let command = stages.command();
+    let (from, to) = (command.from, command.to);
+    let sizes = db_tool.db.view(|tx| {
+        (from..=to)
+            .map(|block| Ok((block, block_size(tx, block, stages.required_tables())?)))
+            .collect::<eyre::Result<Vec<_>>>()
+    })??;
+    let chunks = split_ranges(&sizes, split_size);
+    info!(target: "reth::cli", stage = %stages.id(), from, to, split_size, chunks = chunks.len(), "Splitting dump");
+
+    std::fs::create_dir_all(&command.output_db)?;
+    // Marks the folder as a dump, which `--force` may replace.
+    ExtractManifest::write_stage(
+        &command.output_db,
+        DumpedStage::new(&db_tool.chain, stages.id(), from, to),
+    )?;
+    let mut manifest =
+        SplitManifest { stage: stages.name().to_string(), split_size, chunks: vec![] };
+    for (index, range) in chunks.into_iter().enumerate() {
+        let path = format!("{index:03}");
+        let mut chunk = stages.clone();
+        let chunk_command = chunk.command_mut();
+        chunk_command.from = *range.start();
+        chunk_command.to = *range.end();
+        chunk_command.output_db = command.output_db.join(&path);
+        chunk_command.report =
+            command.report.as_ref().map(|report| report.with_extension(format!("{path}.json")));
+
+        dump_stage(db_tool, &chunk, progress).await?;
+
+        let estimated_size =
+            sizes.iter().filter(|(block, _)| range.contains(block)).map(|(_, size)| size).sum();
+        info!(target: "reth::cli", stage = %stages.id(), chunk = path, from = range.start(), to = range.end(), estimated_size, "Dumped chunk");
+        manifest.chunks.push(Chunk {
+            path,
+            from: *range.start(),
+            to: *range.end(),
+            estimated_size,
+        });
+
+        // Written after every chunk, so that the chunks of a failed dump are still listed.
+        std::fs::write(
+            command.output_db.join(MANIFEST_FILE),
+            serde_json::to_string_pretty(&manifest)?,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Splits the blocks into contiguous ranges whose sizes stay under `split_size`, unless a range
+/// would otherwise hold a single block.
+///
+/// A dump needs at least two blocks, so a last single block is added to the previous range.
+fn split_ranges(sizes: &[(BlockNumber, u64)], split_size: u64) -> Vec<RangeInclusive<BlockNumber>> {
+    let mut ranges: Vec<RangeInclusive<BlockNumber>> = vec![];
+    let mut current: Option<(BlockNumber, BlockNumber, u64)> = None;
+    for &(block, size) in sizes {
+        current = match current {
+            Some((start, end, total)) if end > start && total + size > split_size => {
+                ranges.push(start..=end);
+                Some((block, block, size))
+            }
+            Some((start, _, total)) => Some((start, block, total + size)),
+            None => Some((block, block, size)),
+        };
+    }
+
+    match (current, ranges.last_mut()) {
+        (Some((start, end, _)), Some(last)) if start == end => *last = *last.start()..=end,
+        (Some((start, end, _)), _) => ranges.push(start..=end),
+        (None, _) => {}
+    }
+
+    ranges
+}
+
+/// The raw size of the rows of `block` in the block and transaction keyed tables among
+/// `required_tables`.
+fn block_size<TX: DbTx>(
+    tx: &TX,
+    block: BlockNumber,
+    required_tables: &[&str],
+) -> eyre::Result<u64> {
+    let blocks = block..block + 1;
+    let txs = tx
+        .get::<tables::BlockBodyIndices>(block)?
+        .map(|indices| indices.tx_num_range())
+        .unwrap_or_default();
+    let storage_changes = BlockNumberAddress::range(block..=block);
+
+    Ok(raw_size::<tables::CanonicalHeaders, _>(tx, required_tables, blocks.clone())? +
+        raw_size::<tables::HeaderTD, _>(tx, required_tables, blocks.clone())? +
+        raw_size::<tables::Headers, _>(tx, required_tables, blocks.clone())? +
+        raw_size::<tables::BlockBodyIndices, _>(tx, required_tables, blocks.clone())? +
+        raw_size::<tables::BlockOmmers, _>(tx, required_tables, blocks.clone())? +
+        raw_size::<tables::BlockWithdrawals, _>(tx, required_tables, blocks.clone())? +
+        raw_size::<tables::AccountChangeSet, _>(tx, required_tables, blocks)? +
+        raw_size::<tables::StorageChangeSet, _>(tx, required_tables, storage_changes)? +
+        raw_size::<tables::Transactions, _>(tx, required_tables, txs.clone())? +
+        raw_size::<tables::TxSenders, _>(tx, required_tables, txs)?)
+}
+
+/// The raw size of the keys and values of `T` within `range`, or zero if `T` isn't among
+/// `required_tables`.
+fn raw_size<T: Table, TX: DbTx>(
+    tx: &TX,
+    required_tables: &[&str],
+    range: Range<T::Key>,
+) -> eyre::Result<u64> {
+    if !required_tables.contains(&T::NAME) {
+        return Ok(0)
+    }
+
+    let mut size = 0;
+    let range = RawKey::new(range.start)..RawKey::new(range.end);
+    for entry in tx.cursor_read::<RawTable<T>>()?.walk_range(range)? {
+        let (key, value) = entry?;
+        size += (key.raw_key().len() + value.raw_value().len()) as u64;
+    }
+
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_by_size() {
+        let sizes = (1..=6).map(|block| (block, 10)).collect::<Vec<_>>();
+        assert_eq!(split_ranges(&sizes, 20), [1..=2, 3..=4, 5..=6]);
+        assert_eq!(split_ranges(&sizes, 30), [1..=3, 4..=6]);
+        assert_eq!(split_ranges(&sizes, 1_000), [1..=6]);
+
+        // Blocks are never dumped alone, even if they are bigger than the cap.
+        assert_eq!(split_ranges(&sizes, 5), [1..=2, 3..=4, 5..=6]);
+        assert_eq!(split_ranges(&sizes[..5], 20), [1..=2, 3..=5]);
+
+        let sizes = [(1, 10), (2, 100), (3, 10), (4, 10), (5, 10)];
+        assert_eq!(split_ranges(&sizes, 30), [1..=2, 3..=5]);
+    }
+}
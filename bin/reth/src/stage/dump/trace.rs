@@ -0,0 +1,114 @@
+//! EVM traces of the transactions of the execution dry-run, for `--trace-out`.
+use crate::utils::DbTool;
+use clap::ValueEnum;
+use eyre::Result;
+use reth_db::database::Database;
+use reth_primitives::{
+    revm::env::{fill_cfg_and_block_env, fill_tx_env},
+    stage::StageId,
+    BlockNumber,
+};
+use reth_provider::{BlockReader, HeaderProvider, ProviderFactory, TransactionVariant};
+use reth_revm::{
+    database::StateProviderDatabase,
+    db::CacheDB,
+    primitives::{Env, ResultAndState},
+    state_change::apply_beacon_root_contract_call,
+    tracing::{TracingInspector, TracingInspectorConfig},
+    DatabaseCommit, EVM,
+};
+use reth_rpc_types::trace::geth::{CallConfig, GethDefaultTracingOptions};
+use std::{ops::RangeInclusive, path::Path};
+use tracing::{info, warn};
+
+/// The detail of the traces written by `--trace-out`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TraceLevel {
+    /// The calls of every transaction, as by geth's `callTracer`, with their logs.
+    #[default]
+    Call,
+    /// Every executed opcode, as by geth's default struct logger.
+    Opcode,
+}
+
+/// Re-executes every transaction of `blocks` on the state of the source database before its
+/// block, writing its trace to `<dir>/<block>/<index>-<hash>.json`.
+///
+/// The transactions of a block are executed on top of each other, so every trace sees the state
+/// the transactions before it left.
+pub(crate) fn write_traces<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    blocks: RangeInclusive<BlockNumber>,
+    level: TraceLevel,
+    dir: &Path,
+) -> Result<()> {
+    if level == TraceLevel::Opcode {
+        warn!(target: "reth::cli", stage = %StageId::Execution, from = blocks.start(), to = blocks.end(), "Writing opcode traces, which can take gigabytes per block");
+    }
+
+    let factory = ProviderFactory::new(db_tool.db, db_tool.chain.clone());
+    let provider = factory.provider()?;
+    let mut traces = 0;
+    for number in blocks.clone() {
+        let block = provider
+            .block_with_senders(number, TransactionVariant::WithHash)?
+            .ok_or_else(|| eyre::eyre!("Block {number} does not exist."))?;
+        let total_difficulty = provider
+            .header_td_by_number(number)?
+            .ok_or_else(|| eyre::eyre!("Total difficulty of block {number} does not exist."))?;
+
+        let state = factory.history_by_block_number(number - 1)?;
+        let mut env = Env::default();
+        fill_cfg_and_block_env(
+            &mut env.cfg,
+            &mut env.block,
+            &db_tool.chain,
+            &block.header,
+            total_difficulty,
+        );
+        let mut evm = EVM::with_env(env);
+        evm.database(CacheDB::new(StateProviderDatabase::new(state)));
+        apply_beacon_root_contract_call(
+            &db_tool.chain,
+            block.timestamp,
+            number,
+            block.parent_beacon_block_root,
+            &mut evm,
+        )?;
+
+        let block_dir = dir.join(number.to_string());
+        std::fs::create_dir_all(&block_dir)?;
+        for (index, (transaction, sender)) in block.body.iter().zip(&block.senders).enumerate() {
+            fill_tx_env(&mut evm.env.tx, transaction, *sender);
+            let mut inspector = TracingInspector::new(match level {
+                TraceLevel::Call => TracingInspectorConfig::default_parity().set_record_logs(true),
+                TraceLevel::Opcode => {
+                    TracingInspectorConfig::from_geth_config(&GethDefaultTracingOptions::default())
+                }
+            });
+            let ResultAndState { result, state } = evm.inspect(&mut inspector).map_err(|err| {
+                eyre::eyre!("Transaction {} of block {number} failed: {err:?}", transaction.hash())
+            })?;
+            evm.db().expect("database is set").commit(state);
+
+            let builder = inspector.into_geth_builder();
+            let trace = match level {
+                TraceLevel::Call => serde_json::to_vec_pretty(&builder.geth_call_traces(
+                    CallConfig { with_log: Some(true), ..Default::default() },
+                    result.gas_used(),
+                ))?,
+                TraceLevel::Opcode => serde_json::to_vec_pretty(&builder.geth_traces(
+                    result.gas_used(),
+                    result.into_output().unwrap_or_default(),
+                    GethDefaultTracingOptions::default(),
+                ))?,
+            };
+            std::fs::write(block_dir.join(format!("{index}-{}.json", transaction.hash())), trace)?;
+            traces += 1;
+        }
+    }
+
+    info!(target: "reth::cli", stage = %StageId::Execution, from = blocks.start(), to = blocks.end(), traces, path = ?dir, "Wrote transaction traces");
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use super::{
+    import_table_with_range, log_imported_rows, prune_unmatched_blocks, repeat_dry_run, setup,
+    transaction_range, DumpProgress, DumpReport, Mismatches, ReferenceDb, StageCommand,
+};
+use crate::utils::DbTool;
+use eyre::Result;
+use reth_db::{cursor::DbCursorRO, database::Database, tables, transaction::DbTx, DatabaseEnv};
+use reth_primitives::stage::{StageCheckpoint, StageId};
+use reth_provider::ProviderFactory;
+use reth_stages::{stages::TransactionLookupStage, Stage};
+use std::collections::BTreeMap;
+use tracing::info;
+
+pub(crate) async fn dump_tx_lookup_stage<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    command: &StageCommand,
+    progress: Option<&dyn DumpProgress>,
+) -> Result<()> {
+    let (from, to) = (command.from, command.to);
+    let (output_db, _) = setup(StageId::TransactionLookup, command, db_tool)?;
+
+    let (from_tx, to_tx) = transaction_range(db_tool.db, from, to)?;
+
+    import_table_with_range::<tables::Transactions, _>(
+        &output_db, db_tool, command, from_tx, to_tx, progress,
+    )?;
+
+    prune_unmatched_blocks(&output_db, db_tool, command)?;
+    let rows = log_imported_rows(&output_db, StageId::TransactionLookup)?;
+
+    let dry_run = if command.should_run() {
+        command.check_deadline()?;
+        let dry_run_from = command.dry_run_from()?;
+        Some(
+            repeat_dry_run(StageId::TransactionLookup, command, || {
+                dry_run(
+                    db_tool,
+                    &output_db,
+                    to,
+                    dry_run_from,
+                    command.max_mismatches,
+                    command.reference(),
+                    progress,
+                )
+            })
+            .await
+            .map(|run| run.map(|_| None)),
+        )
+    } else {
+        None
+    };
+
+    DumpReport::new(StageId::TransactionLookup, command, rows).finish(command, dry_run, progress)
+}
+
+/// Try to re-execute the stage straightaway
+async fn dry_run<DB: Database>(
+    db_tool: &DbTool<'_, DB>,
+    output_db: &DatabaseEnv,
+    to: u64,
+    from: u64,
+    max_mismatches: usize,
+    reference: Option<&ReferenceDb>,
+    progress: Option<&dyn DumpProgress>,
+) -> eyre::Result<()> {
+    info!(target: "reth::cli", stage = %StageId::TransactionLookup, from, to, "Executing stage.");
+
+    let factory = ProviderFactory::new(output_db, db_tool.chain.clone());
+    let provider = factory.provider_rw()?;
+    let mut exec_stage = TransactionLookupStage::default();
+
+    let mut exec_output = false;
+    while !exec_output {
+        let output = exec_stage
+            .execute(
+                &provider,
+                reth_stages::ExecInput {
+                    target: Some(to),
+                    checkpoint: Some(StageCheckpoint::new(from)),
+                },
+            )
+            .await?;
+        if let Some(progress) = progress {
+            progress.on_dry_run_block(StageId::TransactionLookup, output.checkpoint.block_number);
+        }
+        exec_output = output.done;
+    }
+
+    db_tool
+        .db
+        .view(|source| validate_tx_lookup(provider.tx_ref(), source, from, to, max_mismatches))??;
+    if let Some(reference) = reference {
+        reference.compare(StageId::TransactionLookup, provider.tx_ref())?;
+    }
+
+    info!(target: "reth::cli", stage = %StageId::TransactionLookup, from, to, "Success.");
+
+    Ok(())
+}
+
+/// Checks that every transaction of the blocks after `from` has a [`tables::TxHashNumber`] entry
+/// pointing at its number, and that there are no others.
+///
+/// The derived entries are also compared against the ones of the source database, unless the
+/// source pruned them.
+fn validate_tx_lookup<TX: DbTx, STX: DbTx>(
+    tx: &TX,
+    source: &STX,
+    from: u64,
+    to: u64,
+    max_mismatches: usize,
+) -> eyre::Result<()> {
+    let mut mismatches = Mismatches::new(StageId::TransactionLookup, max_mismatches);
+    let mut blocks = BTreeMap::<_, usize>::new();
+
+    let mut transactions = 0;
+    for entry in tx.cursor_read::<tables::BlockBodyIndices>()?.walk_range(from + 1..=to)? {
+        let (block, indices) = entry?;
+        for tx_number in indices.tx_num_range() {
+            let hash = tx
+                .get::<tables::Transactions>(tx_number)?
+                .ok_or_else(|| eyre::eyre!("Transaction {tx_number} does not exist."))?
+                .hash();
+            let derived = tx.get::<tables::TxHashNumber>(hash)?;
+            let stored = source.get::<tables::TxHashNumber>(hash)?;
+            if derived != Some(tx_number) || stored.is_some_and(|stored| stored != tx_number) {
+                mismatches.insert(
+                    tx_number,
+                    format!("TxHashNumber entry of transaction {tx_number} ({hash}) does not match its number. Got: {derived:?}. Source: {stored:?}"),
+                );
+                *blocks.entry(block).or_default() += 1;
+            }
+            transactions += 1;
+        }
+    }
+
+    let entries = tx.entries::<tables::TxHashNumber>()?;
+    if entries != transactions {
+        eyre::bail!(
+            "The dry-run derived {entries} TxHashNumber entries for the {transactions} transactions of blocks {}..={to}.",
+            from + 1
+        )
+    }
+    mismatches.finish(blocks)?;
+
+    info!(target: "reth::cli", stage = %StageId::TransactionLookup, transactions, "Validated transaction lookup.");
+
+    Ok(())
+}
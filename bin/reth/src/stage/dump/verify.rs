@@ -0,0 +1,117 @@
+//! Read back of the imported rows, for `--deep-verify`.
+use reth_db::{
+    cursor::DbCursorRO, database::Database, table::Table, transaction::DbTx, DatabaseEnv, RawKey,
+    RawTable,
+};
+use reth_primitives::{hex, keccak256, B256};
+use serde::{Deserialize, Serialize};
+use std::ops::RangeBounds;
+use tracing::info;
+
+/// Number of rows read back between progress logs.
+const PROGRESS_INTERVAL: usize = 100_000;
+
+/// Rows read back from the output database by `--deep-verify`, and their combined hash.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct DeepVerification {
+    /// The number of verified rows.
+    pub(crate) rows: usize,
+    /// The hash chained over the raw key and value of every verified row, in import order.
+    pub(crate) hash: B256,
+}
+
+impl DeepVerification {
+    fn add(&mut self, key: &[u8], value: &[u8]) {
+        self.rows += 1;
+        self.hash = keccak256([self.hash.as_slice(), key, value].concat());
+    }
+}
+
+/// Reads back the rows of `T` within `range` from the output database, and checks that they
+/// byte-match the ones of `source_tx`, after skipping `skip` of them and taking at most `take`.
+///
+/// Only the source rows whose raw value passes `keep` are expected to have been imported.
+pub(crate) fn verify_table<T: Table, TX: DbTx>(
+    output_db: &DatabaseEnv,
+    source_tx: &TX,
+    range: impl RangeBounds<RawKey<T::Key>> + Clone,
+    skip: usize,
+    take: usize,
+    keep: impl Fn(&[u8]) -> bool,
+    verification: &mut DeepVerification,
+) -> eyre::Result<()> {
+    let rows = output_db.view(|tx| {
+        let mut output_cursor = tx.cursor_read::<RawTable<T>>()?;
+        let mut source_cursor = source_tx.cursor_read::<RawTable<T>>()?;
+        let mut output_rows = output_cursor.walk_range(range.clone())?;
+        let mut source_rows = source_cursor
+            .walk_range(range)?
+            .filter(|row| row.as_ref().map_or(true, |(_, value)| keep(value.raw_value())))
+            .skip(skip)
+            .take(take);
+
+        let mut rows = 0;
+        loop {
+            match (source_rows.next().transpose()?, output_rows.next().transpose()?) {
+                (None, None) => break,
+                (Some((source_key, source_value)), Some((key, value)))
+                    if source_key.raw_key() == key.raw_key() &&
+                        source_value.raw_value() == value.raw_value() =>
+                {
+                    verification.add(key.raw_key(), value.raw_value());
+                }
+                (source, output) => eyre::bail!(
+                    "Row {rows} of table {} doesn't match the source. Expected key: {:?}. Got key: {:?}",
+                    T::NAME,
+                    source.map(|(key, _)| hex::encode(key.raw_key())),
+                    output.map(|(key, _)| hex::encode(key.raw_key())),
+                ),
+            }
+
+            rows += 1;
+            if rows % PROGRESS_INTERVAL == 0 {
+                info!(target: "reth::cli", table = T::NAME, rows, "Verifying table");
+            }
+        }
+
+        Ok(rows)
+    })??;
+
+    info!(target: "reth::cli", table = T::NAME, rows, "Verified table");
+
+    Ok(())
+}
+
+/// Reads back the rows of `T` at `keys` from the output database, and checks that they
+/// byte-match the ones of `source_tx`.
+pub(crate) fn verify_keys<T: Table, TX: DbTx>(
+    output_db: &DatabaseEnv,
+    source_tx: &TX,
+    keys: impl IntoIterator<Item = T::Key>,
+    verification: &mut DeepVerification,
+) -> eyre::Result<()> {
+    let rows = output_db.view(|tx| {
+        let mut rows = 0;
+        for key in keys {
+            let key = RawKey::new(key);
+            let (source, output) =
+                (source_tx.get::<RawTable<T>>(key.clone())?, tx.get::<RawTable<T>>(key.clone())?);
+            match (source, output) {
+                (Some(source), Some(value)) if source.raw_value() == value.raw_value() => {
+                    verification.add(key.raw_key(), value.raw_value());
+                }
+                _ => eyre::bail!(
+                    "Row {} of table {} doesn't match the source.",
+                    hex::encode(key.raw_key()),
+                    T::NAME
+                ),
+            }
+            rows += 1;
+        }
+        Ok(rows)
+    })??;
+
+    info!(target: "reth::cli", table = T::NAME, rows, "Verified table");
+
+    Ok(())
+}
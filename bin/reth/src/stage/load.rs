@@ -0,0 +1,34 @@
+//! `reth stage load` command
+use crate::stage::dump::{load_archive, ScratchDirs};
+use clap::Parser;
+use std::path::PathBuf;
+
+/// `reth stage load` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The archive written by `reth stage dump` with `--format tar-zstd`, i.e. the
+    /// `tables.tar.zst` file inside its output folder.
+    #[arg(value_name = "ARCHIVE")]
+    archive: PathBuf,
+
+    /// The path to the new database folder, which must not exist yet or be empty.
+    ///
+    /// It's laid out like the output of a dump in the `mdbx` format, with the chain specification
+    /// of the dump next to the database.
+    #[arg(long, value_name = "OUTPUT_PATH", verbatim_doc_comment)]
+    output_db: PathBuf,
+
+    /// The directory the archive is unpacked in before its tables are loaded. It's removed once
+    /// the command finishes, even if it fails.
+    ///
+    /// Defaults to the system temp dir.
+    #[arg(long, value_name = "PATH", verbatim_doc_comment)]
+    scratch_dir: Option<PathBuf>,
+}
+
+impl Command {
+    /// Execute `stage load` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        load_archive(&self.archive, &self.output_db, &ScratchDirs::new(self.scratch_dir))
+    }
+}
@@ -3,6 +3,7 @@ use clap::{Parser, Subcommand};
 
 pub mod drop;
 pub mod dump;
+pub mod load;
 pub mod run;
 pub mod unwind;
 
@@ -27,6 +28,8 @@ pub enum Subcommands {
     Drop(drop::Command),
     /// Dumps a stage from a range into a new database.
     Dump(dump::Command),
+    /// Loads an archive written by `dump --format tar-zstd` into a new database.
+    Load(load::Command),
     /// Unwinds a certain block range, deleting it from the database.
     Unwind(unwind::Command),
 }
@@ -38,7 +41,16 @@ impl Command {
             Subcommands::Run(command) => command.execute().await,
             Subcommands::Drop(command) => command.execute().await,
             Subcommands::Dump(command) => command.execute().await,
+            Subcommands::Load(command) => command.execute().await,
             Subcommands::Unwind(command) => command.execute().await,
         }
     }
+
+    /// Whether the command prints machine readable output to stdout.
+    pub fn is_machine_output(&self) -> bool {
+        match &self.command {
+            Subcommands::Dump(command) => command.is_machine_output(),
+            _ => false,
+        }
+    }
 }
@@ -2,7 +2,10 @@
 //!
 //! Stage debugging tool
 use crate::{
-    args::{get_secret_key, utils::chain_spec_value_parser, DatabaseArgs, NetworkArgs, StageEnum},
+    args::{
+        get_secret_key, utils::chain_spec_value_parser, ChainOverrideArgs, DatabaseArgs,
+        NetworkArgs, StageEnum,
+    },
     dirs::{DataDirPath, MaybePlatformPath},
     prometheus_exporter,
     version::SHORT_VERSION,
@@ -13,12 +16,12 @@ use reth_config::Config;
 use reth_db::init_db;
 use reth_downloaders::bodies::bodies::BodiesDownloaderBuilder;
 use reth_primitives::ChainSpec;
-use reth_provider::{ProviderFactory, StageCheckpointReader};
+use reth_provider::{ProviderFactory, StageCheckpointReader, StageCheckpointWriter};
 use reth_stages::{
     stages::{
         AccountHashingStage, BodyStage, ExecutionStage, ExecutionStageThresholds,
         IndexAccountHistoryStage, IndexStorageHistoryStage, MerkleStage, SenderRecoveryStage,
-        StorageHashingStage, TransactionLookupStage,
+        StorageHashingStage, TotalDifficultyStage, TransactionLookupStage,
     },
     ExecInput, ExecOutput, PipelineError, Stage, UnwindInput,
 };
@@ -44,7 +47,8 @@ pub struct Command {
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -60,6 +64,10 @@ pub struct Command {
     )]
     chain: Arc<ChainSpec>,
 
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    chain_overrides: ChainOverrideArgs,
+
     /// Enable Prometheus metrics.
     ///
     /// The metrics will be served at the given interface and port.
@@ -70,11 +78,11 @@ pub struct Command {
     #[arg(value_enum)]
     stage: StageEnum,
 
-    /// The height to start at
+    /// The height to start at, i.e. the checkpoint the stage is unwound to and executed from.
     #[arg(long)]
     from: u64,
 
-    /// The end of the stage
+    /// The end of the stage, i.e. the block it's executed to.
     #[arg(long, short)]
     to: u64,
 
@@ -96,9 +104,11 @@ pub struct Command {
     #[clap(flatten)]
     db: DatabaseArgs,
 
-    /// Commits the changes in the database. WARNING: potentially destructive.
+    /// Commits the changes in the database, along with the checkpoint of the stage. WARNING:
+    /// potentially destructive.
     ///
-    /// Useful when you want to run diagnostics on the database.
+    /// Without it, the changes are discarded once the stage ran, which is useful when you want to
+    /// run diagnostics on the database.
     // TODO: We should consider allowing to run hooks at the end of the stage run,
     // e.g. query the DB size, or any table data.
     #[arg(long, short)]
@@ -107,7 +117,13 @@ pub struct Command {
 
 impl Command {
     /// Execute `stage` command
-    pub async fn execute(self) -> eyre::Result<()> {
+    pub async fn execute(mut self) -> eyre::Result<()> {
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
+        if self.from > self.to {
+            eyre::bail!("--from {} is after --to {}.", self.from, self.to)
+        }
+
         // Raise the fd limit of the process.
         // Does not do anything on windows.
         fdlimit::raise_fd_limit();
@@ -123,6 +139,7 @@ impl Command {
         let db_path = data_dir.db_path();
 
         info!(target: "reth::cli", path = ?db_path, "Opening database");
+        self.db.ensure_writable("stage run")?;
         let db = Arc::new(init_db(db_path, self.db.log_level)?);
         info!(target: "reth::cli", "Database opened");
 
@@ -194,6 +211,15 @@ impl Command {
                     (Box::new(stage), None)
                 }
                 StageEnum::Senders => (Box::new(SenderRecoveryStage::new(batch_size)), None),
+                StageEnum::TotalDifficulty => {
+                    let consensus = Arc::new(BeaconConsensus::new(self.chain.clone()));
+                    (
+                        Box::new(
+                            TotalDifficultyStage::new(consensus).with_commit_threshold(batch_size),
+                        ),
+                        None,
+                    )
+                }
                 StageEnum::Execution => {
                     let factory = reth_revm::Factory::new(self.chain.clone());
                     (
@@ -225,26 +251,44 @@ impl Command {
                 ),
                 StageEnum::AccountHistory => (Box::<IndexAccountHistoryStage>::default(), None),
                 StageEnum::StorageHistory => (Box::<IndexStorageHistoryStage>::default(), None),
-                _ => return Ok(()),
+                StageEnum::Headers => {
+                    eyre::bail!(
+                        "The headers stage syncs to a tip from the network, run the node instead."
+                    )
+                }
+                StageEnum::Hashing => {
+                    eyre::bail!("Run the account-hashing and storage-hashing stages one after another instead.")
+                }
             };
         if let Some(unwind_stage) = &unwind_stage {
             assert!(exec_stage.type_id() == unwind_stage.type_id());
         }
 
-        let checkpoint = provider_rw.get_stage_checkpoint(exec_stage.id())?.unwrap_or_default();
+        let stage_id = exec_stage.id();
+        let checkpoint = provider_rw.get_stage_checkpoint(stage_id)?.unwrap_or_default();
+        info!(target: "reth::cli", stage = %stage_id, checkpoint = checkpoint.block_number, from = self.from, to = self.to, "Running stage");
+
+        if self.skip_unwind && checkpoint.block_number > self.from {
+            warn!(target: "reth::cli", stage = %stage_id, checkpoint = checkpoint.block_number, "The stage already ran past --from, its changes of the range are written again");
+        } else if checkpoint.block_number < self.from {
+            eyre::bail!(
+                "The {stage_id} stage is at block {}, running it from {} would skip the blocks in between.",
+                checkpoint.block_number,
+                self.from
+            )
+        }
 
         let unwind_stage = unwind_stage.as_mut().unwrap_or(&mut exec_stage);
 
-        let mut unwind = UnwindInput {
-            checkpoint: checkpoint.with_block_number(self.to),
-            unwind_to: self.from,
-            bad_block: None,
-        };
+        // Everything the stage wrote after `--from` is unwound, even past `--to`, so that its
+        // checkpoint matches its tables afterwards.
+        let mut unwind = UnwindInput { checkpoint, unwind_to: self.from, bad_block: None };
 
         if !self.skip_unwind {
             while unwind.checkpoint.block_number > self.from {
                 let unwind_output = unwind_stage.unwind(&provider_rw, unwind).await?;
                 unwind.checkpoint = unwind_output.checkpoint;
+                provider_rw.save_stage_checkpoint(stage_id, unwind.checkpoint)?;
 
                 if self.commit {
                     provider_rw.commit()?;
@@ -258,19 +302,26 @@ impl Command {
             checkpoint: Some(checkpoint.with_block_number(self.from)),
         };
 
-        while let ExecOutput { checkpoint: stage_progress, done: false } =
-            exec_stage.execute(&provider_rw, input).await?
-        {
+        loop {
+            let ExecOutput { checkpoint: stage_progress, done } =
+                exec_stage.execute(&provider_rw, input).await?;
             input.checkpoint = Some(stage_progress);
+            provider_rw.save_stage_checkpoint(stage_id, stage_progress)?;
+            info!(target: "reth::cli", stage = %stage_id, checkpoint = stage_progress.block_number, done, "Executed stage");
 
             if self.commit {
                 provider_rw.commit()?;
                 provider_rw = factory.provider_rw().map_err(PipelineError::Interface)?;
             }
+            if done {
+                break
+            }
         }
 
         if self.commit {
-            provider_rw.commit()?;
+            info!(target: "reth::cli", stage = %stage_id, to = self.to, "Committed the stage and its checkpoint");
+        } else {
+            info!(target: "reth::cli", stage = %stage_id, "Discarded the changes, pass --commit to keep them");
         }
 
         Ok(())
@@ -1,14 +1,42 @@
 //! Unwinding a certain block range
 
 use crate::{
-    args::{utils::genesis_value_parser, DatabaseArgs},
+    args::{utils::genesis_value_parser, ChainOverrideArgs, DatabaseArgs, StageEnum},
     dirs::{DataDirPath, MaybePlatformPath},
 };
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use reth_beacon_consensus::BeaconConsensus;
 use reth_db::{cursor::DbCursorRO, database::Database, open_db, tables, transaction::DbTx};
-use reth_primitives::{BlockHashOrNumber, ChainSpec};
-use reth_provider::{BlockExecutionWriter, ProviderFactory};
+use reth_primitives::{stage::StageId, BlockHashOrNumber, BlockNumber, ChainSpec};
+use reth_provider::{
+    BlockExecutionWriter, ProviderFactory, StageCheckpointReader, StageCheckpointWriter,
+};
+use reth_stages::{
+    stages::{
+        AccountHashingStage, ExecutionStage, IndexAccountHistoryStage, IndexStorageHistoryStage,
+        MerkleStage, SenderRecoveryStage, StorageHashingStage, TotalDifficultyStage,
+        TransactionLookupStage,
+    },
+    Stage, UnwindInput,
+};
 use std::{ops::RangeInclusive, sync::Arc};
+use tracing::info;
+
+/// The stages which can be unwound on their own with `--stage`, in the order they're unwound.
+///
+/// It's the reverse of the order the pipeline executes them in, so that a stage is unwound before
+/// the tables it reads are.
+const UNWIND_ORDER: [StageEnum; 9] = [
+    StageEnum::StorageHistory,
+    StageEnum::AccountHistory,
+    StageEnum::TxLookup,
+    StageEnum::StorageHashing,
+    StageEnum::AccountHashing,
+    StageEnum::Merkle,
+    StageEnum::Execution,
+    StageEnum::Senders,
+    StageEnum::TotalDifficulty,
+];
 
 /// `reth stage unwind` command
 #[derive(Debug, Parser)]
@@ -25,7 +53,8 @@ pub struct Command {
 
     /// The chain this node is running.
     ///
-    /// Possible values are either a built-in chain or the path to a chain specification file.
+    /// Possible values are either a built-in chain, the path to a chain specification file, or an
+    /// `https://` URL of one, optionally pinned with `#sha256=<CHECKSUM>`.
     ///
     /// Built-in chains:
     /// - mainnet
@@ -42,16 +71,29 @@ pub struct Command {
     )]
     chain: Arc<ChainSpec>,
 
+    /// Overrides of fields of the chain specification
+    #[clap(flatten)]
+    chain_overrides: ChainOverrideArgs,
+
     #[clap(flatten)]
     db: DatabaseArgs,
 
+    /// Only unwinds the tables and checkpoint of the given stage, instead of the whole pipeline.
+    ///
+    /// Can be passed multiple times, e.g. to unwind the merkle and hashing stages so that they are
+    /// rebuilt from the plain state with `reth stage run`.
+    #[arg(long = "stage", value_enum, global = true)]
+    stages: Vec<StageEnum>,
+
     #[clap(subcommand)]
     command: Subcommands,
 }
 
 impl Command {
     /// Execute `db stage unwind` command
-    pub async fn execute(self) -> eyre::Result<()> {
+    pub async fn execute(mut self) -> eyre::Result<()> {
+        self.chain = self.chain_overrides.apply(self.chain.clone());
+
         // add network name to data dir
         let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
         let db_path = data_dir.db_path();
@@ -59,6 +101,7 @@ impl Command {
             eyre::bail!("Database {db_path:?} does not exist.")
         }
 
+        self.db.ensure_writable("stage unwind")?;
         let db = open_db(db_path.as_ref(), self.db.log_level)?;
 
         let range = self.command.unwind_range(&db)?;
@@ -68,6 +111,9 @@ impl Command {
         }
 
         let factory = ProviderFactory::new(&db, self.chain.clone());
+        if !self.stages.is_empty() {
+            return self.unwind_stages(&factory, range.start() - 1).await
+        }
         let provider = factory.provider_rw()?;
 
         let blocks_and_execution = provider
@@ -80,6 +126,144 @@ impl Command {
 
         Ok(())
     }
+
+    /// Unwinds the stages of `--stage` to the block `target`, leaving the tables of every other
+    /// stage untouched.
+    async fn unwind_stages<DB: Database>(
+        &self,
+        factory: &ProviderFactory<&DB>,
+        target: BlockNumber,
+    ) -> eyre::Result<()> {
+        let stages = self.selected_stages()?;
+        let provider = factory.provider_rw()?;
+
+        for stage in &stages {
+            for other in unwound_with(*stage) {
+                let block = provider
+                    .get_stage_checkpoint(stage_id(*other))?
+                    .unwrap_or_default()
+                    .block_number;
+                if !stages.contains(other) && block > target {
+                    eyre::bail!(
+                        "The {} stage is at block {block} and has to be unwound along with the {} stage, pass --stage {} too.",
+                        stage_id(*other),
+                        stage_id(*stage),
+                        stage_name(*other),
+                    )
+                }
+            }
+        }
+
+        for &stage in &stages {
+            let mut unwind = UnwindInput {
+                checkpoint: provider.get_stage_checkpoint(stage_id(stage))?.unwrap_or_default(),
+                unwind_to: target,
+                bad_block: None,
+            };
+            if unwind.checkpoint.block_number <= target {
+                info!(target: "reth::cli", stage = %stage_id(stage), checkpoint = unwind.checkpoint.block_number, "The stage is already at or below the target");
+                continue
+            }
+
+            let from = unwind.checkpoint.block_number;
+            let mut unwind_stage = build_stage(stage, self.chain.clone());
+            while unwind.checkpoint.block_number > target {
+                unwind.checkpoint = unwind_stage.unwind(&provider, unwind).await?.checkpoint;
+                provider.save_stage_checkpoint(stage_id(stage), unwind.checkpoint)?;
+                // The pipeline keeps a checkpoint for both merkle stages.
+                if stage == StageEnum::Merkle {
+                    provider.save_stage_checkpoint(StageId::MerkleUnwind, unwind.checkpoint)?;
+                }
+            }
+            info!(target: "reth::cli", stage = %stage_id(stage), from, to = target, "Unwound stage");
+        }
+
+        provider.commit()?;
+        println!("Unwound the stages {stages:?} to block {target}");
+
+        Ok(())
+    }
+
+    /// The stages of `--stage`, in the order they're unwound.
+    fn selected_stages(&self) -> eyre::Result<Vec<StageEnum>> {
+        let mut selected = Vec::new();
+        for stage in &self.stages {
+            match stage {
+                StageEnum::Headers | StageEnum::Bodies => eyre::bail!(
+                    "The {stage:?} stage holds the canonical chain, unwind the whole pipeline without --stage instead."
+                ),
+                StageEnum::Hashing => {
+                    selected.extend([StageEnum::AccountHashing, StageEnum::StorageHashing])
+                }
+                stage => selected.push(*stage),
+            }
+        }
+        Ok(UNWIND_ORDER.into_iter().filter(|stage| selected.contains(stage)).collect())
+    }
+}
+
+/// The ID of the checkpoint of `stage`.
+fn stage_id(stage: StageEnum) -> StageId {
+    match stage {
+        StageEnum::Headers => StageId::Headers,
+        StageEnum::Bodies => StageId::Bodies,
+        StageEnum::Senders => StageId::SenderRecovery,
+        StageEnum::Execution => StageId::Execution,
+        StageEnum::AccountHashing | StageEnum::Hashing => StageId::AccountHashing,
+        StageEnum::StorageHashing => StageId::StorageHashing,
+        StageEnum::Merkle => StageId::MerkleExecute,
+        StageEnum::TxLookup => StageId::TransactionLookup,
+        StageEnum::AccountHistory => StageId::IndexAccountHistory,
+        StageEnum::StorageHistory => StageId::IndexStorageHistory,
+        StageEnum::TotalDifficulty => StageId::TotalDifficulty,
+    }
+}
+
+/// The name `stage` is passed to `--stage` with.
+fn stage_name(stage: StageEnum) -> String {
+    stage.to_possible_value().map(|value| value.get_name().to_string()).unwrap_or_default()
+}
+
+/// The stages which have to be unwound along with `stage`, so that the database stays consistent.
+///
+/// These are the stages which read the tables of `stage` and, for the merkle stage, the hashing
+/// stages whose unwound state the trie is unwound with.
+fn unwound_with(stage: StageEnum) -> &'static [StageEnum] {
+    match stage {
+        StageEnum::Senders | StageEnum::TotalDifficulty => &[StageEnum::Execution],
+        StageEnum::Execution => &[
+            StageEnum::AccountHashing,
+            StageEnum::StorageHashing,
+            StageEnum::Merkle,
+            StageEnum::AccountHistory,
+            StageEnum::StorageHistory,
+        ],
+        StageEnum::AccountHashing | StageEnum::StorageHashing => &[StageEnum::Merkle],
+        StageEnum::Merkle => &[StageEnum::AccountHashing, StageEnum::StorageHashing],
+        _ => &[],
+    }
+}
+
+/// Builds `stage` to unwind it. Only the stages of [`UNWIND_ORDER`] are supported.
+fn build_stage<DB: Database>(stage: StageEnum, chain: Arc<ChainSpec>) -> Box<dyn Stage<DB>> {
+    match stage {
+        StageEnum::Senders => Box::<SenderRecoveryStage>::default(),
+        StageEnum::TotalDifficulty => {
+            Box::new(TotalDifficultyStage::new(Arc::new(BeaconConsensus::new(chain))))
+        }
+        StageEnum::Execution => {
+            Box::new(ExecutionStage::new_with_factory(reth_revm::Factory::new(chain)))
+        }
+        StageEnum::AccountHashing => Box::<AccountHashingStage>::default(),
+        StageEnum::StorageHashing => Box::<StorageHashingStage>::default(),
+        StageEnum::Merkle => Box::new(MerkleStage::default_unwind()),
+        StageEnum::TxLookup => Box::<TransactionLookupStage>::default(),
+        StageEnum::AccountHistory => Box::<IndexAccountHistoryStage>::default(),
+        StageEnum::StorageHistory => Box::<IndexStorageHistoryStage>::default(),
+        StageEnum::Headers | StageEnum::Bodies | StageEnum::Hashing => {
+            unreachable!("the {stage:?} stage isn't unwound on its own")
+        }
+    }
 }
 
 /// `reth stage unwind` subcommand
@@ -127,4 +311,18 @@ mod tests {
         let cmd = Command::parse_from(["reth", "--datadir", "dir", "num-blocks", "100"]);
         assert_eq!(cmd.command, Subcommands::NumBlocks { amount: 100 });
     }
+
+    #[test]
+    fn parse_unwind_stages() {
+        let cmd = Command::parse_from([
+            "reth", "to-block", "100", "--stage", "merkle", "--stage", "hashing",
+        ]);
+        assert_eq!(
+            cmd.selected_stages().unwrap(),
+            vec![StageEnum::StorageHashing, StageEnum::AccountHashing, StageEnum::Merkle]
+        );
+
+        let cmd = Command::parse_from(["reth", "--stage", "bodies", "num-blocks", "1"]);
+        assert!(cmd.selected_stages().is_err());
+    }
 }
@@ -7,8 +7,9 @@ use reth_db::{
     cursor::DbCursorRO,
     database::Database,
     table::{Decode, Decompress, Table, TableRow},
+    tables,
     transaction::{DbTx, DbTxMut},
-    DatabaseError, RawTable, TableRawRow,
+    DatabaseError, RawKey, RawTable, TableRawRow,
 };
 use reth_interfaces::p2p::{
     bodies::client::BodiesClient,
@@ -16,13 +17,16 @@ use reth_interfaces::p2p::{
     priority::Priority,
 };
 use reth_primitives::{
-    fs, BlockHashOrNumber, ChainSpec, HeadersDirection, SealedBlock, SealedHeader,
+    fs,
+    stage::{StageCheckpoint, StageId},
+    BlockHashOrNumber, ChainSpec, HeadersDirection, SealedBlock, SealedHeader,
 };
 use std::{
     env::VarError,
     path::{Path, PathBuf},
     rc::Rc,
-    sync::Arc,
+    sync::{mpsc, Arc},
+    time::Duration,
 };
 use tracing::info;
 
@@ -126,6 +130,9 @@ impl<'a, DB: Database> DbTool<'a, DB> {
             let mut cursor =
                 tx.cursor_read::<RawTable<T>>().expect("Was not able to obtain a cursor.");
 
+            let start = filter.start.as_ref().map(RawKey::<T::Key>::decode).transpose()?;
+            let end = filter.end.as_ref().map(RawKey::<T::Key>::decode).transpose()?;
+
             let map_filter = |row: Result<TableRawRow<T>, _>| {
                 if let Ok((k, v)) = row {
                     let (key, value) = (k.into_key(), v.into_value());
@@ -158,16 +165,29 @@ impl<'a, DB: Database> DbTool<'a, DB> {
                 None
             };
 
+            let key_of = |row: &Result<TableRawRow<T>, DatabaseError>| {
+                row.as_ref().ok().map(|(key, _)| key.raw_key().clone())
+            };
+
             if filter.reverse {
+                // The reverse walk starts at the first key from `end` on, which is past the range
+                // if `end` isn't in the table. There's no such key if `end` is past the last one.
+                let start_back = match end {
+                    Some(end) if cursor.seek(end.clone())?.is_some() => Some(end),
+                    _ => None,
+                };
                 Ok(cursor
-                    .walk_back(None)?
+                    .walk_back(start_back)?
+                    .skip_while(|row| key_of(row).is_some_and(|key| filter.is_after_end(&key)))
+                    .take_while(|row| key_of(row).map_or(true, |key| !filter.is_before_start(&key)))
                     .skip(filter.skip)
                     .filter_map(map_filter)
                     .take(filter.len)
                     .collect::<Vec<(_, _)>>())
             } else {
                 Ok(cursor
-                    .walk(None)?
+                    .walk(start)?
+                    .take_while(|row| key_of(row).map_or(true, |key| !filter.is_after_end(&key)))
                     .skip(filter.skip)
                     .filter_map(map_filter)
                     .take(filter.len)
@@ -183,6 +203,41 @@ impl<'a, DB: Database> DbTool<'a, DB> {
         self.db.view(|tx| tx.get::<T>(key))?.map_err(|e| eyre::eyre!(e))
     }
 
+    /// Returns the highest block in [`tables::BlockBodyIndices`].
+    pub fn tip(&self) -> Result<u64> {
+        let (tip, _) = self
+            .db
+            .view(|tx| tx.cursor_read::<tables::BlockBodyIndices>()?.last())??
+            .ok_or_else(|| eyre::eyre!("No block bodies in database"))?;
+        Ok(tip)
+    }
+
+    /// Returns the checkpoint of every stage in [`StageId::ALL`], in pipeline order.
+    pub fn stage_checkpoints(&self) -> Result<Vec<(StageId, StageCheckpoint)>> {
+        self.db
+            .view(|tx| {
+                StageId::ALL
+                    .into_iter()
+                    .map(|id| {
+                        Ok((id, tx.get::<tables::SyncStage>(id.to_string())?.unwrap_or_default()))
+                    })
+                    .collect::<Result<Vec<_>, DatabaseError>>()
+            })?
+            .map_err(|e| eyre::eyre!(e))
+    }
+
+    /// Returns whether `stage` stopped in the middle of its range, either by saving intermediate
+    /// progress or by having processed only part of its entities.
+    pub fn is_stage_in_flight(&self, stage: StageId) -> Result<bool> {
+        let checkpoint = self.get::<tables::SyncStage>(stage.to_string())?.unwrap_or_default();
+        if checkpoint.entities().is_some_and(|entities| entities.processed < entities.total) {
+            return Ok(true)
+        }
+
+        let progress = self.get::<tables::SyncStageProgress>(stage.to_string())?;
+        Ok(progress.is_some_and(|progress| !progress.is_empty()))
+    }
+
     /// Drops the database at the given path.
     pub fn drop(&mut self, path: impl AsRef<Path>) -> Result<()> {
         let path = path.as_ref();
@@ -198,6 +253,37 @@ impl<'a, DB: Database> DbTool<'a, DB> {
     }
 }
 
+/// Opens the database at `path` with `open` on a separate thread, failing if it takes longer than
+/// `timeout`.
+///
+/// Opening can block on the lock of a database a running node holds, so this turns an indefinite
+/// wait into an error. Without `timeout`, the database is opened on the current thread.
+pub fn open_db_with_timeout<T, F>(path: &Path, timeout: Option<Duration>, open: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(PathBuf) -> Result<T> + Send + 'static,
+{
+    let Some(timeout) = timeout else { return open(path.to_path_buf()) };
+
+    let (sender, receiver) = mpsc::channel();
+    let thread_path = path.to_path_buf();
+    std::thread::spawn(move || {
+        // The receiver is gone once the timeout ran out, so the result is dropped.
+        let _ = sender.send(open(thread_path));
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(mpsc::RecvTimeoutError::Timeout) => eyre::bail!(
+            "Database at {} is locked by a running node: it couldn't be opened within {timeout:?}.",
+            path.display()
+        ),
+        Err(mpsc::RecvTimeoutError::Disconnected) => {
+            eyre::bail!("Opening the database at {} panicked.", path.display())
+        }
+    }
+}
+
 /// Parses a user-specified path with support for environment variables and common shorthands (e.g.
 /// ~ for the user's home directory).
 pub fn parse_path(value: &str) -> Result<PathBuf, shellexpand::LookupError<VarError>> {
@@ -217,12 +303,33 @@ pub struct ListFilter {
     pub reverse: bool,
     /// Only counts the number of filtered entries without decoding and returning them.
     pub only_count: bool,
+    /// The encoded key of the first entry of the range, if bounded.
+    pub start: Option<Vec<u8>>,
+    /// The encoded key of the last entry of the range, if bounded.
+    pub end: Option<Vec<u8>>,
 }
 
 impl ListFilter {
     /// Creates a new [`ListFilter`].
     pub fn new(skip: usize, len: usize, search: Vec<u8>, reverse: bool, only_count: bool) -> Self {
-        ListFilter { skip, len, search, reverse, only_count }
+        ListFilter { skip, len, search, reverse, only_count, start: None, end: None }
+    }
+
+    /// Only lists the entries from the encoded key `start` up to `end`, both inclusive.
+    pub fn with_range(mut self, start: Option<Vec<u8>>, end: Option<Vec<u8>>) -> Self {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
+    /// Whether the encoded `key` comes before the start of the range.
+    pub fn is_before_start(&self, key: &[u8]) -> bool {
+        self.start.as_ref().is_some_and(|start| key < start.as_slice())
+    }
+
+    /// Whether the encoded `key` comes after the end of the range.
+    pub fn is_after_end(&self, key: &[u8]) -> bool {
+        self.end.as_ref().is_some_and(|end| key > end.as_slice())
     }
 
     /// If `search` has a list of bytes, then filter for rows that have this sequence.
@@ -236,3 +343,58 @@ impl ListFilter {
         self.len = len;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{models::StoredBlockBodyIndices, table::Encode, test_utils::create_test_rw_db};
+    use reth_primitives::MAINNET;
+
+    #[test]
+    fn tip_of_empty_db() {
+        let db = create_test_rw_db();
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+        assert!(tool.tip().is_err());
+    }
+
+    #[test]
+    fn tip_is_highest_body() {
+        let db = create_test_rw_db();
+        db.update(|tx| {
+            for block in 0..3 {
+                tx.put::<tables::BlockBodyIndices>(block, StoredBlockBodyIndices::default())?;
+            }
+            Ok::<(), DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+        assert_eq!(tool.tip().unwrap(), 2);
+    }
+
+    #[test]
+    fn list_key_range() {
+        let db = create_test_rw_db();
+        db.update(|tx| {
+            for block in 0..10 {
+                tx.put::<tables::BlockBodyIndices>(block, StoredBlockBodyIndices::default())?;
+            }
+            Ok::<(), DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+
+        let keys = |reverse, start: u64, end: u64| {
+            let filter = ListFilter::new(0, 100, Vec::new(), reverse, false)
+                .with_range(Some(start.encode().to_vec()), Some(end.encode().to_vec()));
+            let (rows, _) = tool.list::<tables::BlockBodyIndices>(&filter).unwrap();
+            rows.into_iter().map(|(key, _)| key).collect::<Vec<_>>()
+        };
+        assert_eq!(keys(false, 3, 6), [3, 4, 5, 6]);
+        assert_eq!(keys(true, 3, 6), [6, 5, 4, 3]);
+        // The reverse walk starts at the last key if the range ends past it.
+        assert_eq!(keys(true, 7, 20), [9, 8, 7]);
+    }
+}
@@ -5,10 +5,11 @@ use reth_downloaders::{
     headers::reverse_headers::ReverseHeadersDownloaderBuilder,
 };
 use reth_network::{NetworkConfigBuilder, PeersConfig, SessionsConfig};
-use reth_primitives::PruneModes;
+use reth_primitives::{Address, PayloadOrdering, PruneModes};
+use reth_rpc_types::gas_oracle::GasPriceOracleConfig;
 use secp256k1::SecretKey;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 
 /// Configuration for the reth node.
 #[derive(Debug, Clone, Default, Deserialize, PartialEq, Serialize)]
@@ -24,6 +25,13 @@ pub struct Config {
     pub peers: PeersConfig,
     /// Configuration for peer sessions.
     pub sessions: SessionsConfig,
+    /// Configuration for the gas price oracle of the RPC server, which the `--gpo.*` arguments
+    /// override.
+    pub gas_price_oracle: GasPriceOracleConfig,
+    /// Configuration for logging.
+    pub logs: LogsConfig,
+    /// Configuration for the payload builder, which the `--builder.*` arguments override.
+    pub builder: BuilderConfig,
 }
 
 impl Config {
@@ -194,11 +202,14 @@ impl From<BodiesConfig> for BodiesDownloaderBuilder {
 pub struct SenderRecoveryConfig {
     /// The maximum number of transactions to process before committing progress to the database.
     pub commit_threshold: u64,
+    /// The number of threads to recover senders on. If not set, the threads of the global rayon
+    /// thread pool are used.
+    pub workers: Option<usize>,
 }
 
 impl Default for SenderRecoveryConfig {
     fn default() -> Self {
-        Self { commit_threshold: 5_000_000 }
+        Self { commit_threshold: 5_000_000, workers: None }
     }
 }
 
@@ -302,6 +313,42 @@ impl Default for PruneConfig {
     }
 }
 
+/// Logging configuration.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct LogsConfig {
+    /// Levels of targets, e.g. `"reth::stages" = "debug"`, overriding the log levels of stdout
+    /// and the log file for these targets.
+    pub targets: BTreeMap<String, String>,
+}
+
+impl LogsConfig {
+    /// Returns the level overrides as comma-separated filter directives.
+    pub fn directives(&self) -> String {
+        self.targets
+            .iter()
+            .map(|(target, level)| format!("{target}={level}"))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Payload builder configuration.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct BuilderConfig {
+    /// The extra data of the built blocks, the client version if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extradata: Option<String>,
+    /// The fee recipient of the payloads requested with the zero address as their fee recipient.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_recipient: Option<Address>,
+    /// The minimum effective tip per gas, in wei, of the included transactions.
+    pub min_tip: u64,
+    /// The order in which the transactions of the pool are included.
+    pub ordering: PayloadOrdering,
+}
+
 #[cfg(test)]
 mod tests {
     use super::Config;
@@ -336,6 +383,57 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_gas_price_oracle_config() {
+        let config: Config = toml::from_str(
+            r"
+[gas_price_oracle]
+blocks = 10
+defaultRewardPercentiles = [10, 50, 90]
+",
+        )
+        .unwrap();
+        assert_eq!(config.gas_price_oracle.blocks, 10);
+        assert_eq!(config.gas_price_oracle.default_reward_percentiles, [10, 50, 90]);
+        assert_eq!(config.gas_price_oracle.percentile, 60);
+    }
+
+    #[test]
+    fn test_logs_config() {
+        let config: Config = toml::from_str(
+            r#"
+[logs.targets]
+"reth::stages" = "debug"
+"reth::network" = "warn"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.logs.directives(), "reth::network=warn,reth::stages=debug");
+        assert_eq!(Config::default().logs.directives(), "");
+    }
+
+    #[test]
+    fn test_builder_config() {
+        let config: Config = toml::from_str(
+            r#"
+[builder]
+extradata = "my builder"
+fee_recipient = "0x000000000000000000000000000000000000dead"
+min_tip = 1000000000
+ordering = "profit"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.builder.extradata.as_deref(), Some("my builder"));
+        assert_eq!(
+            config.builder.fee_recipient,
+            Some("0x000000000000000000000000000000000000dead".parse().unwrap())
+        );
+        assert_eq!(config.builder.min_tip, 1_000_000_000);
+        assert_eq!(config.builder.ordering, super::PayloadOrdering::Profit);
+        assert_eq!(Config::default().builder.ordering, super::PayloadOrdering::Greedy);
+    }
+
     // ensures config deserialization is backwards compatible
     #[test]
     fn test_backwards_compatibility() {
@@ -94,6 +94,15 @@ pub enum ProviderError {
         /// Block hash
         block_hash: BlockHash,
     },
-    #[error("State at block #{0} is pruned")]
-    StateAtBlockPruned(BlockNumber),
+    /// The history the state at a block is read from was pruned.
+    #[error("State at block #{block_number} is pruned, the history is available from block #{lowest_available}")]
+    StateAtBlockPruned {
+        /// The block at the start of which the state was requested.
+        block_number: BlockNumber,
+        /// The lowest block at the start of which the state is available.
+        lowest_available: BlockNumber,
+    },
+    /// The provider doesn't hold the data required to answer the request.
+    #[error("Request is not supported by this provider")]
+    UnsupportedProvider,
 }
@@ -82,18 +82,33 @@ impl FileClient {
         let file_len = metadata.len();
 
         // read the entire file into memory
-        let mut reader = vec![];
+        let mut reader = Vec::with_capacity(file_len as usize);
         file.read_to_end(&mut reader).await.unwrap();
 
+        Ok(Self::from_blocks(Self::decode_blocks(&reader).await?))
+    }
+
+    /// Decodes the RLP encoded blocks written one after another in `bytes`, e.g. the contents of
+    /// a file exported by geth.
+    pub async fn decode_blocks(bytes: &[u8]) -> Result<Vec<Block>, FileClientError> {
+        // use with_capacity to make sure the internal buffer contains all the bytes
+        let mut stream = FramedRead::with_capacity(bytes, BlockFileCodec, bytes.len());
+
+        let mut blocks = vec![];
+        while let Some(block_res) = stream.next().await {
+            blocks.push(block_res?);
+        }
+        Ok(blocks)
+    }
+
+    /// Create a new file client from blocks that were already decoded, e.g. from an archive in
+    /// a different format.
+    pub fn from_blocks(blocks: impl IntoIterator<Item = Block>) -> Self {
         let mut headers = HashMap::new();
         let mut hash_to_number = HashMap::new();
         let mut bodies = HashMap::new();
 
-        // use with_capacity to make sure the internal buffer contains the entire file
-        let mut stream = FramedRead::with_capacity(&reader[..], BlockFileCodec, file_len as usize);
-
-        while let Some(block_res) = stream.next().await {
-            let block = block_res?;
+        for block in blocks {
             let block_hash = block.header.hash_slow();
 
             // add to the internal maps
@@ -111,12 +126,12 @@ impl FileClient {
 
         trace!(blocks = headers.len(), "Initialized file client");
 
-        Ok(Self { headers, hash_to_number, bodies })
+        Self { headers, hash_to_number, bodies }
     }
 
     /// Get the tip hash of the chain.
     pub fn tip(&self) -> Option<B256> {
-        self.headers.get(&(self.headers.len() as u64)).map(|h| h.hash_slow())
+        self.max_block().and_then(|number| self.headers.get(&number)).map(|h| h.hash_slow())
     }
 
     /// Returns the highest block number of this client has or `None` if empty
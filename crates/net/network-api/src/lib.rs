@@ -96,6 +96,9 @@ pub trait Peers: PeersInfo {
 
     /// Get the reputation of a peer.
     async fn reputation_by_id(&self, peer_id: PeerId) -> Result<Option<Reputation>, NetworkError>;
+
+    /// Get the kind of a peer, if it's in the peer set.
+    async fn kind_by_id(&self, peer_id: PeerId) -> Result<Option<PeerKind>, NetworkError>;
 }
 
 /// Represents the kind of peer
@@ -127,6 +130,8 @@ pub struct PeerInfo {
     pub eth_version: EthVersion,
     /// The Status message the peer sent for the `eth` handshake
     pub status: Status,
+    /// The kind of the peer in the peer set.
+    pub kind: PeerKind,
 }
 
 /// The direction of the connection.
@@ -82,4 +82,8 @@ impl Peers for NoopNetwork {
     async fn reputation_by_id(&self, _peer_id: PeerId) -> Result<Option<Reputation>, NetworkError> {
         Ok(None)
     }
+
+    async fn kind_by_id(&self, _peer_id: PeerId) -> Result<Option<PeerKind>, NetworkError> {
+        Ok(None)
+    }
 }
@@ -40,7 +40,7 @@ use reth_eth_wire::{
 };
 use reth_metrics::common::mpsc::UnboundedMeteredSender;
 use reth_net_common::bandwidth_meter::BandwidthMeter;
-use reth_network_api::ReputationChangeKind;
+use reth_network_api::{PeerInfo, ReputationChangeKind};
 use reth_primitives::{ForkId, NodeRecord, PeerId, B256};
 use reth_provider::{BlockNumReader, BlockReader};
 use reth_rpc_types::{EthProtocolInfo, NetworkStatus};
@@ -511,6 +511,14 @@ where
         }
     }
 
+    /// Sets the kind of the peer of a session from the peer set.
+    fn with_peer_kind(&self, mut peer: PeerInfo) -> PeerInfo {
+        if let Some(kind) = self.swarm.state().peers().peer_kind(&peer.remote_id) {
+            peer.kind = kind;
+        }
+        peer
+    }
+
     /// Handler for received messages from a handle
     fn on_handle_message(&mut self, msg: NetworkHandleMessage) {
         match msg {
@@ -568,6 +576,9 @@ where
             NetworkHandleMessage::GetReputationById(peer_id, tx) => {
                 let _ = tx.send(self.swarm.state_mut().peers().get_reputation(&peer_id));
             }
+            NetworkHandleMessage::GetPeerKindById(peer_id, tx) => {
+                let _ = tx.send(self.swarm.state().peers().peer_kind(&peer_id));
+            }
             NetworkHandleMessage::FetchClient(tx) => {
                 let _ = tx.send(self.fetch_client());
             }
@@ -580,10 +591,12 @@ where
                 }
             }
             NetworkHandleMessage::GetPeerInfo(tx) => {
-                let _ = tx.send(self.swarm.sessions_mut().get_peer_info());
+                let peers = self.swarm.sessions_mut().get_peer_info();
+                let _ = tx.send(peers.into_iter().map(|peer| self.with_peer_kind(peer)).collect());
             }
             NetworkHandleMessage::GetPeerInfoById(peer_id, tx) => {
-                let _ = tx.send(self.swarm.sessions_mut().get_peer_info_by_id(peer_id));
+                let peer = self.swarm.sessions_mut().get_peer_info_by_id(peer_id);
+                let _ = tx.send(peer.map(|peer| self.with_peer_kind(peer)));
             }
         }
     }
@@ -239,6 +239,12 @@ impl Peers for NetworkHandle {
         let _ = self.manager().send(NetworkHandleMessage::GetReputationById(peer_id, tx));
         Ok(rx.await?)
     }
+
+    async fn kind_by_id(&self, peer_id: PeerId) -> Result<Option<PeerKind>, NetworkError> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.manager().send(NetworkHandleMessage::GetPeerKindById(peer_id, tx));
+        Ok(rx.await?)
+    }
 }
 
 #[async_trait]
@@ -358,6 +364,8 @@ pub(crate) enum NetworkHandleMessage {
     GetPeerInfoById(PeerId, oneshot::Sender<Option<PeerInfo>>),
     /// Get the reputation for a specific peer
     GetReputationById(PeerId, oneshot::Sender<Option<Reputation>>),
+    /// Get the kind of a specific peer in the peer set
+    GetPeerKindById(PeerId, oneshot::Sender<Option<PeerKind>>),
     /// Gracefully shutdown network
     Shutdown(oneshot::Sender<()>),
     /// Add a new listener for `DiscoveryEvent`.
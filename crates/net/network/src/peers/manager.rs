@@ -180,6 +180,11 @@ impl PeersManager {
         self.peers.len()
     }
 
+    /// Returns the kind of the peer, if it's in the peer set.
+    pub(crate) fn peer_kind(&self, peer_id: &PeerId) -> Option<PeerKind> {
+        self.peers.get(peer_id).map(|peer| peer.kind)
+    }
+
     /// Returns an iterator over all peers
     pub(crate) fn iter_peers(&self) -> impl Iterator<Item = NodeRecord> + '_ {
         self.peers.iter().map(|(peer_id, v)| NodeRecord::new(v.addr, *peer_id))
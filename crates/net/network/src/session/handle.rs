@@ -10,7 +10,7 @@ use reth_eth_wire::{
     DisconnectReason, EthStream, EthVersion, P2PStream, Status,
 };
 use reth_net_common::bandwidth_meter::MeteredStream;
-use reth_network_api::PeerInfo;
+use reth_network_api::{PeerInfo, PeerKind};
 use reth_primitives::PeerId;
 use std::{io, net::SocketAddr, sync::Arc, time::Instant};
 use tokio::{
@@ -148,6 +148,8 @@ impl ActiveSessionHandle {
             client_version: self.client_version.clone(),
             eth_version: self.version,
             status: self.status,
+            // the session doesn't know the kind, the network fills it in from the peer set
+            kind: PeerKind::Basic,
         }
     }
 }
@@ -14,7 +14,7 @@ use reth_network::{
     test_utils::{enr_to_peer_id, NetworkEventStream, PeerConfig, Testnet, GETH_TIMEOUT},
     NetworkConfigBuilder, NetworkEvent, NetworkManager, PeersConfig,
 };
-use reth_network_api::{NetworkInfo, Peers, PeersInfo};
+use reth_network_api::{NetworkInfo, PeerKind, Peers, PeersInfo};
 use reth_primitives::{mainnet_nodes, HeadersDirection, NodeRecord, PeerId};
 use reth_provider::test_utils::NoopProvider;
 use reth_transaction_pool::test_utils::testing_pool;
@@ -194,6 +194,37 @@ async fn test_get_peer_by_id() {
     assert!(peer.is_none());
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_trusted_peer_info() {
+    reth_tracing::init_test_tracing();
+    let mut net = Testnet::create(2).await;
+
+    let mut handles = net.handles();
+    let handle0 = handles.next().unwrap();
+    let handle1 = handles.next().unwrap();
+
+    drop(handles);
+    let _handle = net.spawn();
+
+    let mut listener0 = NetworkEventStream::new(handle0.event_listener());
+
+    handle0.add_trusted_peer(*handle1.peer_id(), handle1.local_addr());
+    let _ = listener0.next_session_established().await.unwrap();
+
+    let peer = handle0.get_peer_by_id(*handle1.peer_id()).await.unwrap().unwrap();
+    assert_eq!(peer.kind, PeerKind::Trusted);
+    assert!(!peer.direction.is_incoming());
+    assert_eq!(handle0.kind_by_id(*handle1.peer_id()).await.unwrap(), Some(PeerKind::Trusted));
+
+    // a trusted peer is demoted before it can be removed, like admin_removePeer does
+    handle0.remove_peer(*handle1.peer_id(), PeerKind::Trusted);
+    assert_eq!(handle0.kind_by_id(*handle1.peer_id()).await.unwrap(), Some(PeerKind::Basic));
+    handle0.remove_peer(*handle1.peer_id(), PeerKind::Basic);
+    let (peer_id, _) = listener0.next_session_closed().await.unwrap();
+    assert_eq!(peer_id, *handle1.peer_id());
+    assert_eq!(handle0.kind_by_id(*handle1.peer_id()).await.unwrap(), None);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
 async fn test_connect_with_boot_nodes() {
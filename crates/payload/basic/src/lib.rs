@@ -9,7 +9,7 @@
 #![deny(unused_must_use, rust_2018_idioms)]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
-use crate::metrics::PayloadBuilderMetrics;
+use crate::{metrics::PayloadBuilderMetrics, ordering::OrderedTransactions};
 use alloy_rlp::Encodable;
 use futures_core::ready;
 use futures_util::FutureExt;
@@ -27,8 +27,8 @@ use reth_primitives::{
     },
     proofs,
     revm::{compat::into_reth_log, env::tx_env_with_recovered},
-    Block, BlockNumberOrTag, Bytes, ChainSpec, Header, IntoRecoveredTransaction, Receipt, Receipts,
-    SealedBlock, Withdrawal, B256, EMPTY_OMMER_ROOT, U256,
+    Address, Block, BlockNumberOrTag, Bytes, ChainSpec, Header, IntoRecoveredTransaction, Receipt,
+    Receipts, SealedBlock, Withdrawal, B256, EMPTY_OMMER_ROOT, U256,
 };
 use reth_provider::{BlockReaderIdExt, BlockSource, BundleStateWithReceipts, StateProviderFactory};
 use reth_revm::{
@@ -57,6 +57,9 @@ use tokio::{
 use tracing::{debug, trace};
 
 mod metrics;
+mod ordering;
+
+pub use reth_primitives::PayloadOrdering;
 
 /// The [`PayloadJobGenerator`] that creates [`BasicPayloadJob`]s.
 #[derive(Debug)]
@@ -130,7 +133,7 @@ where
 
     fn new_payload_job(
         &self,
-        attributes: PayloadBuilderAttributes,
+        mut attributes: PayloadBuilderAttributes,
     ) -> Result<Self::Job, PayloadBuilderError> {
         let parent_block = if attributes.parent.is_zero() {
             // use latest block if parent is zero: genesis block
@@ -148,12 +151,20 @@ where
             block.seal(attributes.parent)
         };
 
+        if attributes.suggested_fee_recipient.is_zero() {
+            if let Some(fee_recipient) = self.config.fee_recipient {
+                attributes.suggested_fee_recipient = fee_recipient;
+            }
+        }
+
         let config = PayloadConfig::new(
             Arc::new(parent_block),
             self.config.extradata.clone(),
             attributes,
             Arc::clone(&self.chain_spec),
-        );
+        )
+        .with_min_tip(self.config.min_tip)
+        .with_ordering(self.config.ordering);
 
         let until = tokio::time::Instant::now() + self.config.deadline;
         let deadline = Box::pin(tokio::time::sleep_until(until));
@@ -200,6 +211,12 @@ pub struct BasicPayloadJobGeneratorConfig {
     deadline: Duration,
     /// Maximum number of tasks to spawn for building a payload.
     max_payload_tasks: usize,
+    /// The fee recipient of the payloads requested without one.
+    fee_recipient: Option<Address>,
+    /// The minimum effective tip per gas of the included transactions, in wei.
+    min_tip: u128,
+    /// The order in which the transactions of the pool are included.
+    ordering: PayloadOrdering,
 }
 
 // === impl BasicPayloadJobGeneratorConfig ===
@@ -243,6 +260,31 @@ impl BasicPayloadJobGeneratorConfig {
         self.max_gas_limit = max_gas_limit;
         self
     }
+
+    /// Sets the fee recipient of the payloads requested with the zero address as their suggested
+    /// fee recipient.
+    ///
+    /// Defaults to none, which keeps the zero address.
+    pub fn fee_recipient(mut self, fee_recipient: Option<Address>) -> Self {
+        self.fee_recipient = fee_recipient;
+        self
+    }
+
+    /// Sets the minimum effective tip per gas, in wei, of the transactions included in payloads.
+    ///
+    /// Defaults to 0.
+    pub fn min_tip(mut self, min_tip: u128) -> Self {
+        self.min_tip = min_tip;
+        self
+    }
+
+    /// Sets the order in which the transactions of the pool are included in payloads.
+    ///
+    /// Defaults to [PayloadOrdering::Greedy].
+    pub fn ordering(mut self, ordering: PayloadOrdering) -> Self {
+        self.ordering = ordering;
+        self
+    }
 }
 
 impl Default for BasicPayloadJobGeneratorConfig {
@@ -256,6 +298,9 @@ impl Default for BasicPayloadJobGeneratorConfig {
             // 12s slot time
             deadline: SLOT_DURATION,
             max_payload_tasks: 3,
+            fee_recipient: None,
+            min_tip: 0,
+            ordering: PayloadOrdering::Greedy,
         }
     }
 }
@@ -355,6 +400,7 @@ where
                         BuildOutcome::Better { payload, cached_reads } => {
                             this.cached_reads = Some(cached_reads);
                             trace!(target: "payload_builder", value = %payload.fees(), "built better payload");
+                            this.metrics.record_better_payload(payload.fees());
                             let payload = Arc::new(payload);
                             this.best_payload = Some(payload);
                         }
@@ -540,6 +586,10 @@ pub struct PayloadConfig {
     attributes: PayloadBuilderAttributes,
     /// The chain spec.
     chain_spec: Arc<ChainSpec>,
+    /// The minimum effective tip per gas of the included transactions, in wei.
+    min_tip: u128,
+    /// The order in which the transactions of the pool are included.
+    ordering: PayloadOrdering,
 }
 
 impl PayloadConfig {
@@ -561,8 +611,22 @@ impl PayloadConfig {
             extra_data,
             attributes,
             chain_spec,
+            min_tip: 0,
+            ordering: PayloadOrdering::Greedy,
         }
     }
+
+    /// Sets the minimum effective tip per gas, in wei, of the included transactions.
+    pub fn with_min_tip(mut self, min_tip: u128) -> Self {
+        self.min_tip = min_tip;
+        self
+    }
+
+    /// Sets the order in which the transactions of the pool are included.
+    pub fn with_ordering(mut self, ordering: PayloadOrdering) -> Self {
+        self.ordering = ordering;
+        self
+    }
 }
 
 /// The possible outcomes of a payload building attempt.
@@ -682,6 +746,8 @@ where
         extra_data,
         attributes,
         chain_spec,
+        min_tip,
+        ordering,
     } = config;
 
     debug!(target: "payload_builder", parent_hash = ?parent_block.hash, parent_number = parent_block.number, "building new payload");
@@ -691,7 +757,12 @@ where
     let base_fee = initialized_block_env.basefee.to::<u64>();
 
     let mut executed_txs = Vec::new();
-    let mut best_txs = pool.best_transactions_with_base_fee(base_fee);
+    let mut best_txs = OrderedTransactions::new(
+        pool.best_transactions_with_base_fee(base_fee),
+        ordering,
+        base_fee,
+        min_tip,
+    );
 
     let mut total_fees = U256::ZERO;
 
@@ -915,6 +986,7 @@ where
         attributes,
         chain_spec,
         initialized_cfg,
+        ..
     } = config;
 
     debug!(target: "payload_builder", parent_hash = ?parent_block.hash, parent_number = parent_block.number, "building empty payload");
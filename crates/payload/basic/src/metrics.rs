@@ -1,6 +1,10 @@
 //! Metrics for the payload builder impl
 
-use reth_metrics::{metrics::Counter, Metrics};
+use reth_metrics::{
+    metrics::{Counter, Gauge, Histogram},
+    Metrics,
+};
+use reth_primitives::U256;
 
 /// Transaction pool metrics
 #[derive(Metrics)]
@@ -12,6 +16,10 @@ pub(crate) struct PayloadBuilderMetrics {
     pub(crate) initiated_payload_builds: Counter,
     /// Total number of failed payload build attempts
     pub(crate) failed_payload_builds: Counter,
+    /// The fees of the best payload of the latest job, in ether
+    pub(crate) best_payload_value: Gauge,
+    /// The fees of the better payloads built, in ether
+    pub(crate) payload_value: Histogram,
 }
 
 impl PayloadBuilderMetrics {
@@ -26,4 +34,11 @@ impl PayloadBuilderMetrics {
     pub(crate) fn inc_failed_payload_builds(&self) {
         self.failed_payload_builds.increment(1);
     }
+
+    /// Records the fees of a payload better than the earlier ones of its job.
+    pub(crate) fn record_better_payload(&self, fees: U256) {
+        let value = u128::try_from(fees).unwrap_or(u128::MAX) as f64 / 1e18;
+        self.best_payload_value.set(value);
+        self.payload_value.record(value);
+    }
 }
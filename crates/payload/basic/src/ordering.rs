@@ -0,0 +1,278 @@
+//! The orders in which payloads include the transactions of the pool.
+
+use reth_primitives::PayloadOrdering;
+use reth_transaction_pool::{BestTransactions, PoolTransaction, ValidPoolTransaction};
+use std::{fmt, sync::Arc};
+
+/// How many transactions of the pool the [PayloadOrdering::Profit] ordering picks the next one
+/// from.
+const PROFIT_ORDERING_WINDOW: usize = 64;
+
+/// The best transactions of the pool tipping at least a minimum, in a [PayloadOrdering].
+pub(crate) struct OrderedTransactions<T: PoolTransaction> {
+    /// The best transactions of the pool, in its order.
+    inner: Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T>>>>,
+    /// The order to yield the transactions in.
+    ordering: PayloadOrdering,
+    /// The base fee of the payload.
+    base_fee: u64,
+    /// The minimum effective tip per gas of the yielded transactions.
+    min_tip: u128,
+    /// The transactions taken from `inner` which haven't been yielded yet, in the order of the
+    /// pool.
+    buffered: Vec<Arc<ValidPoolTransaction<T>>>,
+}
+
+impl<T: PoolTransaction> OrderedTransactions<T> {
+    /// Orders the transactions of `inner` in `ordering`, for a payload with `base_fee`.
+    ///
+    /// The transactions tipping less than `min_tip` are skipped along with their descendants, as
+    /// if they were marked invalid.
+    pub(crate) fn new(
+        inner: Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T>>>>,
+        ordering: PayloadOrdering,
+        base_fee: u64,
+        min_tip: u128,
+    ) -> Self {
+        Self { inner, ordering, base_fee, min_tip, buffered: Vec::new() }
+    }
+
+    /// The effective tip per gas of `tx`.
+    fn tip(&self, tx: &ValidPoolTransaction<T>) -> u128 {
+        tx.effective_tip_per_gas(self.base_fee).unwrap_or_default()
+    }
+
+    /// The fees `tx` pays to the fee recipient if it uses all of its gas.
+    fn fees(&self, tx: &ValidPoolTransaction<T>) -> u128 {
+        self.tip(tx).saturating_mul(tx.gas_limit() as u128)
+    }
+
+    /// Drops the buffered blob transactions, and the ones depending on them.
+    fn drop_buffered_blobs(&mut self) {
+        let blobs = self
+            .buffered
+            .iter()
+            .filter(|tx| tx.is_eip4844())
+            .map(|tx| (tx.sender(), tx.nonce()))
+            .collect::<Vec<_>>();
+        self.buffered.retain(|tx| {
+            !blobs.iter().any(|(sender, nonce)| tx.sender() == *sender && tx.nonce() >= *nonce)
+        });
+    }
+
+    /// Returns the next transaction in the ordering, regardless of its tip.
+    fn next_ordered(&mut self) -> Option<Arc<ValidPoolTransaction<T>>> {
+        if self.ordering == PayloadOrdering::Greedy {
+            return self.inner.next()
+        }
+
+        while self.buffered.len() < PROFIT_ORDERING_WINDOW {
+            let Some(tx) = self.inner.next() else { break };
+            self.buffered.push(tx);
+        }
+
+        // Only the first buffered transaction of a sender can be included, since the pool yields
+        // the later ones after the transactions they depend on.
+        let mut best: Option<(usize, u128)> = None;
+        for (idx, tx) in self.buffered.iter().enumerate() {
+            if self.buffered[..idx].iter().any(|earlier| earlier.sender() == tx.sender()) {
+                continue
+            }
+            let fees = self.fees(tx);
+            if best.map_or(true, |(_, best_fees)| fees > best_fees) {
+                best = Some((idx, fees));
+            }
+        }
+
+        best.map(|(idx, _)| self.buffered.remove(idx))
+    }
+}
+
+impl<T: PoolTransaction> fmt::Debug for OrderedTransactions<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OrderedTransactions")
+            .field("ordering", &self.ordering)
+            .field("base_fee", &self.base_fee)
+            .field("min_tip", &self.min_tip)
+            .field("buffered", &self.buffered.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: PoolTransaction> Iterator for OrderedTransactions<T> {
+    type Item = Arc<ValidPoolTransaction<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let tx = self.next_ordered()?;
+            if self.tip(&tx) >= self.min_tip {
+                return Some(tx)
+            }
+            self.mark_invalid(&tx);
+        }
+    }
+}
+
+impl<T: PoolTransaction> BestTransactions for OrderedTransactions<T> {
+    fn mark_invalid(&mut self, tx: &Self::Item) {
+        self.inner.mark_invalid(tx);
+        self.buffered
+            .retain(|buffered| buffered.sender() != tx.sender() || buffered.nonce() < tx.nonce());
+    }
+
+    fn no_updates(&mut self) {
+        self.inner.no_updates()
+    }
+
+    fn skip_blobs(&mut self) {
+        self.set_skip_blobs(true)
+    }
+
+    fn set_skip_blobs(&mut self, skip_blobs: bool) {
+        self.inner.set_skip_blobs(skip_blobs);
+        if skip_blobs {
+            self.drop_buffered_blobs();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::TxHash;
+    use reth_transaction_pool::test_utils::{MockTransaction, MockTransactionFactory};
+    use std::collections::VecDeque;
+
+    type MockPoolTransaction = Arc<ValidPoolTransaction<MockTransaction>>;
+
+    /// Yields the given transactions in their order, like the pool yields its best ones.
+    struct PoolOrder {
+        txs: VecDeque<MockPoolTransaction>,
+        skip_blobs: bool,
+    }
+
+    impl Iterator for PoolOrder {
+        type Item = MockPoolTransaction;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            loop {
+                let tx = self.txs.pop_front()?;
+                if !(self.skip_blobs && tx.is_eip4844()) {
+                    return Some(tx)
+                }
+            }
+        }
+    }
+
+    impl BestTransactions for PoolOrder {
+        fn mark_invalid(&mut self, tx: &Self::Item) {
+            self.txs.retain(|other| other.sender() != tx.sender() || other.nonce() < tx.nonce());
+        }
+
+        fn no_updates(&mut self) {}
+
+        fn skip_blobs(&mut self) {
+            self.set_skip_blobs(true)
+        }
+
+        fn set_skip_blobs(&mut self, skip_blobs: bool) {
+            self.skip_blobs = skip_blobs
+        }
+    }
+
+    /// A transaction paying `tip` per gas for `gas_limit` gas, with a base fee of zero.
+    fn tx(tip: u128, gas_limit: u64) -> MockTransaction {
+        MockTransaction::eip1559().with_gas_price(tip).with_gas_limit(gas_limit)
+    }
+
+    fn ordered(
+        txs: &[&MockTransaction],
+        ordering: PayloadOrdering,
+        min_tip: u128,
+    ) -> OrderedTransactions<MockTransaction> {
+        let mut factory = MockTransactionFactory::default();
+        let txs = txs.iter().map(|tx| factory.validated_arc((*tx).clone())).collect();
+        OrderedTransactions::new(
+            Box::new(PoolOrder { txs, skip_blobs: false }),
+            ordering,
+            0,
+            min_tip,
+        )
+    }
+
+    fn hashes(txs: impl Iterator<Item = MockPoolTransaction>) -> Vec<TxHash> {
+        txs.map(|tx| *tx.hash()).collect()
+    }
+
+    #[test]
+    fn greedy_keeps_pool_order() {
+        let (a, b) = (tx(10, 21_000), tx(5, 100_000));
+        let txs = ordered(&[&a, &b], PayloadOrdering::Greedy, 0);
+        assert_eq!(hashes(txs), [*a.hash(), *b.hash()]);
+    }
+
+    #[test]
+    fn profit_orders_by_fees() {
+        let (a, b) = (tx(10, 21_000), tx(5, 100_000));
+        let txs = ordered(&[&a, &b], PayloadOrdering::Profit, 0);
+        assert_eq!(hashes(txs), [*b.hash(), *a.hash()]);
+    }
+
+    #[test]
+    fn profit_keeps_nonce_order() {
+        let first = tx(1, 21_000);
+        let second = first.next().with_gas_limit(1_000_000);
+        let other = tx(2, 100_000);
+        let txs = ordered(&[&other, &first, &second], PayloadOrdering::Profit, 0);
+        assert_eq!(hashes(txs), [*other.hash(), *first.hash(), *second.hash()]);
+    }
+
+    #[test]
+    fn profit_picks_within_window() {
+        let mut txs = (0..PROFIT_ORDERING_WINDOW).map(|_| tx(1, 21_000)).collect::<Vec<_>>();
+        let outside = tx(100, 1_000_000);
+        txs.push(outside.clone());
+
+        let mut ordered = ordered(&txs.iter().collect::<Vec<_>>(), PayloadOrdering::Profit, 0);
+        assert_ne!(*ordered.next().unwrap().hash(), *outside.hash());
+        // once a transaction of the window was yielded, the next one of the pool is in it
+        assert_eq!(*ordered.next().unwrap().hash(), *outside.hash());
+    }
+
+    #[test]
+    fn mark_invalid_drops_buffered_descendants() {
+        let first = tx(1, 21_000);
+        let second = first.next();
+        let other = tx(2, 100_000);
+        let mut txs = ordered(&[&first, &second, &other], PayloadOrdering::Profit, 0);
+
+        assert_eq!(*txs.next().unwrap().hash(), *other.hash());
+        let buffered = txs.buffered[0].clone();
+        assert_eq!(*buffered.hash(), *first.hash());
+        txs.mark_invalid(&buffered);
+        assert!(txs.next().is_none());
+    }
+
+    #[test]
+    fn skip_blobs_drops_buffered_blobs() {
+        let blob = MockTransaction::eip4844().with_gas_price(1).with_gas_limit(21_000);
+        let after_blob = blob.next();
+        let other = tx(2, 100_000);
+        let mut txs = ordered(&[&blob, &after_blob, &other], PayloadOrdering::Profit, 0);
+
+        assert_eq!(*txs.next().unwrap().hash(), *other.hash());
+        txs.skip_blobs();
+        assert!(txs.next().is_none());
+    }
+
+    #[test]
+    fn min_tip_skips_descendants() {
+        let low = tx(1, 21_000);
+        let after_low = low.next().with_gas_price(20);
+        let high = tx(10, 21_000);
+        for ordering in [PayloadOrdering::Greedy, PayloadOrdering::Profit] {
+            let txs = ordered(&[&high, &low, &after_low], ordering, 5);
+            assert_eq!(hashes(txs), [*high.hash()], "{ordering}");
+        }
+    }
+}
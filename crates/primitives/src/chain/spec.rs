@@ -452,6 +452,19 @@ impl ChainSpec {
         self.hardforks.get(&fork).copied().unwrap_or(ForkCondition::Never)
     }
 
+    /// Sets the activation condition of `fork`, e.g. to override a fork of a known chain.
+    ///
+    /// Clears the cached values which depend on the activation of the forks.
+    pub fn set_fork(&mut self, fork: Hardfork, condition: ForkCondition) {
+        self.hardforks.insert(fork, condition);
+        self.fork_timestamps = ForkTimestamps::from_hardforks(&self.hardforks);
+        if fork == Hardfork::Paris {
+            self.paris_block_and_final_difficulty = None;
+        }
+        // The genesis header has the fields of the forks active at genesis.
+        self.genesis_hash = None;
+    }
+
     /// Get an iterator of all hardforks with their respective activation conditions.
     pub fn forks_iter(&self) -> impl Iterator<Item = (Hardfork, ForkCondition)> + '_ {
         self.hardforks.iter().map(|(f, b)| (*f, *b))
@@ -1194,6 +1207,18 @@ mod tests {
     use bytes::BytesMut;
     use std::str::FromStr;
 
+    #[test]
+    fn set_fork_clears_caches() {
+        let mut spec = (*MAINNET).clone();
+        spec.set_fork(Hardfork::Cancun, ForkCondition::Timestamp(1_800_000_000));
+        assert_eq!(spec.fork_timestamps.cancun, Some(1_800_000_000));
+        assert!(spec.is_cancun_active_at_timestamp(1_800_000_000));
+        assert_eq!(spec.genesis_hash(), MAINNET.genesis_hash());
+
+        spec.set_fork(Hardfork::Shanghai, ForkCondition::Timestamp(0));
+        assert_ne!(spec.genesis_hash(), MAINNET.genesis_hash());
+    }
+
     fn test_fork_ids(spec: &ChainSpec, cases: &[(Head, ForkId)]) {
         for (block, expected_id) in cases {
             let computed_id = spec.fork_id(block);
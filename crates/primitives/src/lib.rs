@@ -32,6 +32,7 @@ mod header;
 mod integer_list;
 mod log;
 mod net;
+mod payload;
 mod peer;
 mod precaution;
 pub mod proofs;
@@ -75,6 +76,7 @@ pub use net::{
     goerli_nodes, holesky_nodes, mainnet_nodes, sepolia_nodes, NodeRecord, GOERLI_BOOTNODES,
     HOLESKY_BOOTNODES, MAINNET_BOOTNODES, SEPOLIA_BOOTNODES,
 };
+pub use payload::PayloadOrdering;
 pub use peer::{PeerId, WithPeerId};
 pub use prune::{
     PruneCheckpoint, PruneMode, PruneModes, PruneProgress, PruneSegment, PruneSegmentError,
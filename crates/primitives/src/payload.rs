@@ -0,0 +1,40 @@
+//! Settings of the payloads the node builds.
+
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// The order in which a payload includes the transactions of the pool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadOrdering {
+    /// The order of the pool: by highest effective tip per gas.
+    #[default]
+    Greedy,
+    /// By the highest fees paid to the fee recipient, the effective tip times the gas limit,
+    /// among the next transactions of the pool.
+    ///
+    /// This favors transactions paying more in total over the ones paying more per gas, and the
+    /// transactions of a sender are still included in nonce order.
+    Profit,
+}
+
+impl fmt::Display for PayloadOrdering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayloadOrdering::Greedy => f.write_str("greedy"),
+            PayloadOrdering::Profit => f.write_str("profit"),
+        }
+    }
+}
+
+impl FromStr for PayloadOrdering {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "greedy" => Ok(PayloadOrdering::Greedy),
+            "profit" => Ok(PayloadOrdering::Profit),
+            _ => Err(format!("Unknown payload ordering {s}, expected greedy or profit")),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use reth_primitives::NodeRecord;
-use reth_rpc_types::{NodeInfo, PeerInfo};
+use reth_rpc_types::{NodeInfo, NodeStatus, PeerInfo};
 
 /// Admin namespace rpc interface that gives access to several non-standard RPC methods.
 #[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
@@ -15,7 +15,7 @@ pub trait AdminApi {
     ///
     /// Returns true if the peer was successfully removed.
     #[method(name = "removePeer")]
-    fn remove_peer(&self, record: NodeRecord) -> RpcResult<bool>;
+    async fn remove_peer(&self, record: NodeRecord) -> RpcResult<bool>;
 
     /// Adds the given node record to the trusted peerset.
     #[method(name = "addTrustedPeer")]
@@ -46,4 +46,9 @@ pub trait AdminApi {
     /// Returns the ENR of the node.
     #[method(name = "nodeInfo")]
     async fn node_info(&self) -> RpcResult<NodeInfo>;
+
+    /// Returns the progress of the sync of the node: the checkpoints of the stages of the
+    /// pipeline, the sync target, the number of connected peers and the latest canonical block.
+    #[method(name = "nodeStatus")]
+    fn node_status(&self) -> RpcResult<NodeStatus>;
 }
@@ -42,6 +42,9 @@ pub struct EthConfig {
     ///
     /// Sets TTL for stale filters
     pub stale_filter_ttl: std::time::Duration,
+    /// Whether `newPendingTransactions` subscriptions can stream full transactions instead of
+    /// their hashes.
+    pub full_pending_transactions: bool,
 }
 
 /// Default value for stale filter ttl
@@ -56,6 +59,7 @@ impl Default for EthConfig {
             max_logs_per_response: DEFAULT_MAX_LOGS_PER_RESPONSE,
             rpc_gas_cap: RPC_DEFAULT_GAS_CAP.into(),
             stale_filter_ttl: DEFAULT_STALE_FILTER_TTL,
+            full_pending_transactions: false,
         }
     }
 }
@@ -90,4 +94,10 @@ impl EthConfig {
         self.rpc_gas_cap = rpc_gas_cap;
         self
     }
+
+    /// Configures whether `newPendingTransactions` subscriptions can stream full transactions
+    pub fn full_pending_transactions(mut self, enabled: bool) -> Self {
+        self.full_pending_transactions = enabled;
+        self
+    }
 }
@@ -13,7 +13,7 @@ use reth_primitives::{
     U256,
 };
 use reth_rpc_api::{
-    clients::{AdminApiClient, EthApiClient},
+    clients::{AdminApiClient, EthApiClient, TxPoolApiClient},
     DebugApiClient, EthFilterApiClient, NetApiClient, OtterscanClient, TraceApiClient,
     Web3ApiClient,
 };
@@ -57,6 +57,7 @@ where
     AdminApiClient::add_trusted_peer(client, node).await.unwrap();
     AdminApiClient::remove_trusted_peer(client, node).await.unwrap();
     AdminApiClient::node_info(client).await.unwrap();
+    AdminApiClient::node_status(client).await.unwrap();
 }
 
 async fn test_basic_eth_calls<C>(client: &C)
@@ -184,6 +185,17 @@ where
     TraceApiClient::trace_filter(client, trace_filter).await.unwrap();
 }
 
+async fn test_basic_txpool_calls<C>(client: &C)
+where
+    C: ClientT + SubscriptionClientT + Sync,
+{
+    let status = TxPoolApiClient::txpool_status(client).await.unwrap();
+    assert_eq!((status.pending, status.queued), (Default::default(), Default::default()));
+    TxPoolApiClient::txpool_inspect(client).await.unwrap();
+    TxPoolApiClient::txpool_content(client).await.unwrap();
+    TxPoolApiClient::txpool_content_from(client, Address::default()).await.unwrap();
+}
+
 async fn test_basic_web3_calls<C>(client: &C)
 where
     C: ClientT + SubscriptionClientT + Sync,
@@ -396,6 +408,33 @@ async fn test_call_trace_functions_http_and_ws() {
     test_basic_trace_calls(&client).await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_txpool_functions_http() {
+    reth_tracing::init_test_tracing();
+
+    let handle = launch_http(vec![RethRpcModule::Txpool]).await;
+    let client = handle.http_client().unwrap();
+    test_basic_txpool_calls(&client).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_txpool_functions_ws() {
+    reth_tracing::init_test_tracing();
+
+    let handle = launch_ws(vec![RethRpcModule::Txpool]).await;
+    let client = handle.ws_client().await.unwrap();
+    test_basic_txpool_calls(&client).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_txpool_functions_http_and_ws() {
+    reth_tracing::init_test_tracing();
+
+    let handle = launch_http_ws(vec![RethRpcModule::Txpool]).await;
+    let client = handle.http_client().unwrap();
+    test_basic_txpool_calls(&client).await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_call_web3_functions_http() {
     reth_tracing::init_test_tracing();
@@ -89,6 +89,42 @@ pub struct EthProtocolInfo {
     pub genesis: B256,
 }
 
+/// Represents the `admin_nodeStatus` response: the progress of the sync of the node.
+///
+/// Note: this is specific to reth and not part of Geth's admin namespace.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStatus {
+    /// Number of the latest block of the canonical chain.
+    pub latest_block: u64,
+    /// Hash of the latest block of the canonical chain.
+    pub latest_block_hash: B256,
+    /// The block the pipeline syncs to, if it has received one.
+    pub sync_target: Option<u64>,
+    /// Whether the node is syncing.
+    pub is_syncing: bool,
+    /// Number of connected peers.
+    pub peers: usize,
+    /// The checkpoints of the stages of the pipeline, in the order they run.
+    pub stages: Vec<StageStatus>,
+}
+
+/// The checkpoint of a stage of the pipeline.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageStatus {
+    /// Name of the stage.
+    pub name: String,
+    /// The block the stage has processed up to.
+    pub checkpoint: u64,
+    /// Number of entities the stage processed in its current range, if it tracks them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processed: Option<u64>,
+    /// Total number of entities of the current range of the stage, if it tracks them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,83 @@
+//! Settings of the gas price oracle.
+
+use alloy_primitives::U256;
+use serde::{Deserialize, Serialize};
+
+/// The default number of transactions sampled in a block
+pub const SAMPLE_NUMBER: usize = 3_usize;
+
+/// The default maximum gas price to use for the estimate
+pub const DEFAULT_MAX_PRICE: U256 = U256::from_limbs([500_000_000_000u64, 0, 0, 0]);
+
+/// The default minimum gas price, under which the sample will be ignored
+pub const DEFAULT_IGNORE_PRICE: U256 = U256::from_limbs([2u64, 0, 0, 0]);
+
+/// Settings for the gas price oracle of the `eth` namespace, which also caches the rewards of
+/// `eth_feeHistory`
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GasPriceOracleConfig {
+    /// The number of populated blocks to produce the gas price estimate
+    pub blocks: u32,
+
+    /// The number of transactions with the lowest tips sampled in every block
+    pub transactions: usize,
+
+    /// The percentile of gas prices to use for the estimate
+    pub percentile: u32,
+
+    /// The maximum number of headers to keep in the cache
+    pub max_header_history: u64,
+
+    /// The maximum number of blocks for estimating gas price, and of blocks the rewards of
+    /// `eth_feeHistory` are cached for
+    pub max_block_history: u64,
+
+    /// The reward percentiles `eth_feeHistory` reports when the request doesn't pass any.
+    ///
+    /// If empty, such requests get no rewards, as the spec describes.
+    pub default_reward_percentiles: Vec<u32>,
+
+    /// The default gas price to use if there are no blocks to use
+    pub default: Option<U256>,
+
+    /// The maximum gas price to use for the estimate
+    pub max_price: Option<U256>,
+
+    /// The minimum gas price, under which the sample will be ignored
+    pub ignore_price: Option<U256>,
+}
+
+impl Default for GasPriceOracleConfig {
+    fn default() -> Self {
+        GasPriceOracleConfig {
+            blocks: 20,
+            transactions: SAMPLE_NUMBER,
+            percentile: 60,
+            max_header_history: 1024,
+            max_block_history: 1024,
+            default_reward_percentiles: Vec::new(),
+            default: None,
+            max_price: Some(DEFAULT_MAX_PRICE),
+            ignore_price: Some(DEFAULT_IGNORE_PRICE),
+        }
+    }
+}
+
+impl GasPriceOracleConfig {
+    /// Creating a new gpo config with blocks, ignoreprice, maxprice and percentile
+    pub fn new(
+        blocks: Option<u32>,
+        ignore_price: Option<u64>,
+        max_price: Option<u64>,
+        percentile: Option<u32>,
+    ) -> Self {
+        Self {
+            blocks: blocks.unwrap_or(20),
+            percentile: percentile.unwrap_or(60),
+            max_price: max_price.map(U256::from).or(Some(DEFAULT_MAX_PRICE)),
+            ignore_price: ignore_price.map(U256::from).or(Some(DEFAULT_IGNORE_PRICE)),
+            ..Default::default()
+        }
+    }
+}
@@ -7,6 +7,7 @@ pub mod engine;
 pub mod error;
 mod fee;
 mod filter;
+pub mod gas_oracle;
 mod index;
 mod log;
 pub mod pubsub;
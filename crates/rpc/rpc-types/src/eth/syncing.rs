@@ -64,6 +64,10 @@ pub struct PeerNetworkInfo {
     pub remote_address: String,
     /// Local endpoint address
     pub local_address: String,
+    /// Whether the remote peer opened the connection
+    pub inbound: bool,
+    /// Whether the remote peer is a trusted peer
+    pub trusted: bool,
 }
 
 /// Peer protocols information
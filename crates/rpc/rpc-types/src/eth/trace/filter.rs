@@ -1,4 +1,5 @@
 //! `trace_filter` types and support
+use crate::trace::parity::{Action, TraceOutput, TransactionTrace};
 use alloy_primitives::Address;
 use reth_primitives::serde_helper::num::u64_hex_or_decimal_opt;
 use serde::{Deserialize, Serialize};
@@ -62,15 +63,47 @@ pub struct TraceFilterMatcher {
 
 impl TraceFilterMatcher {
     /// Returns `true` if the given `from` and `to` addresses match this filter.
+    ///
+    /// An empty list of `from` or `to` addresses matches any address.
     pub fn matches(&self, from: Address, to: Option<Address>) -> bool {
+        self.matches_addresses(Some(from), to)
+    }
+
+    /// Returns `true` if the addresses of the action of the trace match this filter.
+    ///
+    /// The `from` address of a selfdestruct is the destroyed contract, and its `to` address the
+    /// refund address. The `to` address of a create is the created contract and of a reward its
+    /// author.
+    pub fn matches_trace(&self, trace: &TransactionTrace) -> bool {
+        let (from, to) = match &trace.action {
+            Action::Call(call) => (Some(call.from), Some(call.to)),
+            Action::Create(create) => {
+                let created = match &trace.result {
+                    Some(TraceOutput::Create(output)) => Some(output.address),
+                    _ => None,
+                };
+                (Some(create.from), created)
+            }
+            Action::Selfdestruct(selfdestruct) => {
+                (Some(selfdestruct.address), Some(selfdestruct.refund_address))
+            }
+            Action::Reward(reward) => (None, Some(reward.author)),
+        };
+        self.matches_addresses(from, to)
+    }
+
+    fn matches_addresses(&self, from: Option<Address>, to: Option<Address>) -> bool {
+        let from_matches = from.map_or(false, |from| self.from_addresses.contains(&from));
+        let to_matches = to.map_or(false, |to| self.to_addresses.contains(&to));
         match self.mode {
             TraceFilterMode::Union => {
-                self.from_addresses.contains(&from) ||
-                    to.map_or(false, |to| self.to_addresses.contains(&to))
+                (self.from_addresses.is_empty() && self.to_addresses.is_empty()) ||
+                    from_matches ||
+                    to_matches
             }
             TraceFilterMode::Intersection => {
-                self.from_addresses.contains(&from) &&
-                    to.map_or(false, |to| self.to_addresses.contains(&to))
+                (self.from_addresses.is_empty() || from_matches) &&
+                    (self.to_addresses.is_empty() || to_matches)
             }
         }
     }
@@ -87,4 +120,27 @@ mod tests {
         assert_eq!(filter.from_block, Some(3));
         assert_eq!(filter.to_block, Some(5));
     }
+
+    #[test]
+    fn test_filter_matcher() {
+        let (a, b, c) = (Address::with_last_byte(1), Address::with_last_byte(2), Address::ZERO);
+
+        let s = r#"{"fromBlock": "0x3", "toBlock": "0x5"}"#;
+        let filter: TraceFilter = serde_json::from_str(s).unwrap();
+        assert!(filter.matcher().matches(a, None));
+
+        let s = format!(r#"{{"fromAddress": ["{a}"], "toAddress": ["{b}"]}}"#);
+        let filter: TraceFilter = serde_json::from_str(&s).unwrap();
+        let matcher = filter.matcher();
+        assert!(matcher.matches(a, Some(c)));
+        assert!(matcher.matches(c, Some(b)));
+        assert!(!matcher.matches(c, Some(c)));
+
+        let s = format!(r#"{{"fromAddress": ["{a}"], "mode": "intersection"}}"#);
+        let filter: TraceFilter = serde_json::from_str(&s).unwrap();
+        let matcher = filter.matcher();
+        assert!(matcher.matches(a, Some(c)));
+        assert!(matcher.matches(a, None));
+        assert!(!matcher.matches(b, Some(c)));
+    }
 }
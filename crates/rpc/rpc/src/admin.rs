@@ -1,30 +1,75 @@
 use crate::result::ToRpcResult;
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
+use reth_interfaces::RethResult;
 use reth_network_api::{NetworkInfo, PeerKind, Peers};
-use reth_primitives::NodeRecord;
+use reth_primitives::{stage::StageId, NodeRecord};
+use reth_provider::{BlockNumReader, StageCheckpointReader};
 use reth_rpc_api::AdminApiServer;
-use reth_rpc_types::{NodeInfo, PeerEthProtocolInfo, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo};
+use reth_rpc_types::{
+    NodeInfo, NodeStatus, PeerEthProtocolInfo, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
+    StageStatus,
+};
 
 /// `admin` API implementation.
 ///
 /// This type provides the functionality for handling `admin` related requests.
-pub struct AdminApi<N> {
+pub struct AdminApi<N, Provider> {
     /// An interface to interact with the network
     network: N,
+    /// The provider to read the sync progress from
+    provider: Provider,
 }
 
-impl<N> AdminApi<N> {
+impl<N, Provider> AdminApi<N, Provider> {
     /// Creates a new instance of `AdminApi`.
-    pub fn new(network: N) -> Self {
-        AdminApi { network }
+    pub fn new(network: N, provider: Provider) -> Self {
+        AdminApi { network, provider }
+    }
+}
+
+impl<N, Provider> AdminApi<N, Provider>
+where
+    N: NetworkInfo,
+    Provider: BlockNumReader + StageCheckpointReader,
+{
+    /// Returns the progress of the sync of the node.
+    fn try_node_status(&self) -> RethResult<NodeStatus> {
+        let chain_info = self.provider.chain_info()?;
+
+        let mut sync_target = None;
+        let mut stages = Vec::with_capacity(StageId::ALL.len());
+        for stage in StageId::ALL {
+            let checkpoint = self.provider.get_stage_checkpoint(stage)?.unwrap_or_default();
+            if stage == StageId::Headers {
+                sync_target =
+                    checkpoint.headers_stage_checkpoint().map(|headers| headers.block_range.to);
+            }
+            let entities = checkpoint.entities();
+            stages.push(StageStatus {
+                name: stage.to_string(),
+                checkpoint: checkpoint.block_number,
+                processed: entities.map(|entities| entities.processed),
+                total: entities.map(|entities| entities.total),
+            });
+        }
+
+        Ok(NodeStatus {
+            latest_block: chain_info.best_number,
+            latest_block_hash: chain_info.best_hash,
+            sync_target,
+            is_syncing: self.network.is_syncing(),
+            peers: self.network.num_connected_peers(),
+            stages,
+        })
     }
 }
 
 #[async_trait]
-impl<N> AdminApiServer for AdminApi<N>
+impl<N, Provider> AdminApiServer for AdminApi<N, Provider>
 where
     N: NetworkInfo + Peers + 'static,
+    Provider: BlockNumReader + StageCheckpointReader + 'static,
 {
     /// Handler for `admin_addPeer`
     fn add_peer(&self, record: NodeRecord) -> RpcResult<bool> {
@@ -33,7 +78,12 @@ where
     }
 
     /// Handler for `admin_removePeer`
-    fn remove_peer(&self, record: NodeRecord) -> RpcResult<bool> {
+    async fn remove_peer(&self, record: NodeRecord) -> RpcResult<bool> {
+        if self.network.kind_by_id(record.id).await.to_rpc_result()? == Some(PeerKind::Trusted) {
+            // this only demotes the peer, the peer set ignores the removal of trusted peers
+            self.network.remove_peer(record.id, PeerKind::Trusted);
+        }
+        // removes the peer from the peer set and disconnects its session
         self.network.remove_peer(record.id, PeerKind::Basic);
         Ok(true)
     }
@@ -64,6 +114,8 @@ where
                         .local_addr
                         .unwrap_or_else(|| self.network.local_addr())
                         .to_string(),
+                    inbound: peer.direction.is_incoming(),
+                    trusted: peer.kind == PeerKind::Trusted,
                 },
                 protocols: PeerProtocolsInfo {
                     eth: Some(PeerEthProtocolInfo {
@@ -87,6 +139,11 @@ where
         Ok(NodeInfo::new(enr, status))
     }
 
+    /// Handler for `admin_nodeStatus`
+    fn node_status(&self) -> RpcResult<NodeStatus> {
+        self.try_node_status().to_rpc_result()
+    }
+
     /// Handler for `admin_peerEvents`
     async fn subscribe_peer_events(
         &self,
@@ -96,7 +153,7 @@ where
     }
 }
 
-impl<N> std::fmt::Debug for AdminApi<N> {
+impl<N, Provider> std::fmt::Debug for AdminApi<N, Provider> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("AdminApi").finish_non_exhaustive()
     }
@@ -9,7 +9,7 @@ use reth_primitives::{
     basefee::calculate_next_block_base_fee, BlockNumberOrTag, SealedHeader, U256,
 };
 use reth_provider::{BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider, StateProviderFactory};
-use reth_rpc_types::{FeeHistory, TxGasAndReward};
+use reth_rpc_types::FeeHistory;
 use reth_transaction_pool::TransactionPool;
 use tracing::debug;
 
@@ -48,6 +48,13 @@ where
             return Ok(FeeHistory::default())
         }
 
+        // Requests without percentiles get the configured default ones, if any
+        let default_percentiles = &self.gas_oracle().config().default_reward_percentiles;
+        let reward_percentiles = reward_percentiles.or_else(|| {
+            (!default_percentiles.is_empty())
+                .then(|| default_percentiles.iter().map(|percentile| *percentile as f64).collect())
+        });
+
         // See https://github.com/ethereum/go-ethereum/blob/2754b197c935ee63101cbbca2752338246384fec/eth/gasprice/feehistory.go#L218C8-L225
         let max_fee_history = if reward_percentiles.is_none() {
             self.gas_oracle().config().max_header_history
@@ -142,34 +149,8 @@ where
         percentiles: &[f64],
         header: &SealedHeader,
     ) -> Result<Vec<U256>, EthApiError> {
-        let (transactions, receipts) = self
-            .cache()
-            .get_transactions_and_receipts(header.hash)
-            .await?
-            .ok_or(EthApiError::InvalidBlockRange)?;
-
-        let mut transactions = transactions
-            .into_iter()
-            .zip(receipts)
-            .scan(0, |previous_gas, (tx, receipt)| {
-                // Convert the cumulative gas used in the receipts
-                // to the gas usage by the transaction
-                //
-                // While we will sum up the gas again later, it is worth
-                // noting that the order of the transactions will be different,
-                // so the sum will also be different for each receipt.
-                let gas_used = receipt.cumulative_gas_used - *previous_gas;
-                *previous_gas = receipt.cumulative_gas_used;
-
-                Some(TxGasAndReward {
-                    gas_used,
-                    reward: tx.effective_gas_tip(header.base_fee_per_gas).unwrap_or_default(),
-                })
-            })
-            .collect::<Vec<_>>();
-
-        // Sort the transactions by their rewards in ascending order
-        transactions.sort_by_key(|tx| tx.reward);
+        // The transactions sorted by their rewards in ascending order
+        let transactions = self.gas_oracle().block_rewards(header).await?;
 
         // Find the transaction that corresponds to the given percentile
         //
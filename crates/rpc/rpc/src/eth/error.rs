@@ -43,6 +43,13 @@ pub enum EthApiError {
     UnknownBlockOrTxIndex,
     #[error("Invalid block range")]
     InvalidBlockRange,
+    /// Thrown when the state at a block is requested, but its history was pruned, e.g. because
+    /// the block is outside of the `--history.state` window.
+    #[error("state pruned, earliest available block {earliest_available}")]
+    StatePruned {
+        /// The earliest block the state after which is available.
+        earliest_available: u64,
+    },
     /// An internal error where prevrandao is not set in the evm's environment
     #[error("Prevrandao not in th EVM's environment after merge")]
     PrevrandaoNotSet,
@@ -120,7 +127,9 @@ impl From<EthApiError> for ErrorObject<'static> {
             EthApiError::InvalidBlockData(_) |
             EthApiError::Internal(_) |
             EthApiError::TransactionNotFound => internal_rpc_err(error.to_string()),
-            EthApiError::UnknownBlockNumber | EthApiError::UnknownBlockOrTxIndex => {
+            EthApiError::UnknownBlockNumber |
+            EthApiError::UnknownBlockOrTxIndex |
+            EthApiError::StatePruned { .. } => {
                 rpc_error_with_code(EthRpcErrorCode::ResourceNotFound.code(), error.to_string())
             }
             EthApiError::UnknownSafeOrFinalizedBlock => {
@@ -178,6 +187,10 @@ impl From<reth_interfaces::provider::ProviderError> for EthApiError {
             ProviderError::FinalizedBlockNotFound | ProviderError::SafeBlockNotFound => {
                 EthApiError::UnknownSafeOrFinalizedBlock
             }
+            // the state after a block is the one at the start of the next
+            ProviderError::StateAtBlockPruned { lowest_available, .. } => {
+                EthApiError::StatePruned { earliest_available: lowest_available.saturating_sub(1) }
+            }
             err => EthApiError::Internal(err.into()),
         }
     }
@@ -639,4 +652,18 @@ mod tests {
         let err = EthApiError::ExecutionTimedOut(Duration::from_secs(10));
         assert_eq!(err.to_string(), "execution aborted (timeout = 10s)");
     }
+
+    #[test]
+    fn state_pruned_error() {
+        let err: EthApiError = reth_interfaces::provider::ProviderError::StateAtBlockPruned {
+            block_number: 51,
+            lowest_available: 101,
+        }
+        .into();
+        assert_eq!(err.to_string(), "state pruned, earliest available block 100");
+
+        let err = ErrorObject::from(err);
+        assert_eq!(err.code(), EthRpcErrorCode::ResourceNotFound.code());
+        assert_eq!(err.message(), "state pruned, earliest available block 100");
+    }
 }
@@ -5,82 +5,20 @@ use crate::eth::{
     error::{EthApiError, EthResult, RpcInvalidTransactionError},
 };
 use derive_more::{Deref, DerefMut};
-use reth_primitives::{constants::GWEI_TO_WEI, BlockNumberOrTag, B256, U256};
+use reth_primitives::{constants::GWEI_TO_WEI, BlockNumberOrTag, SealedHeader, B256, U256};
 use reth_provider::BlockReaderIdExt;
+use reth_rpc_types::TxGasAndReward;
 use schnellru::{ByLength, LruMap};
-use serde::{Deserialize, Serialize};
-use std::fmt::{self, Debug, Formatter};
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+};
 use tokio::sync::Mutex;
 use tracing::warn;
 
-/// The number of transactions sampled in a block
-pub const SAMPLE_NUMBER: usize = 3_usize;
-
-/// The default maximum gas price to use for the estimate
-pub const DEFAULT_MAX_PRICE: U256 = U256::from_limbs([500_000_000_000u64, 0, 0, 0]);
-
-/// The default minimum gas price, under which the sample will be ignored
-pub const DEFAULT_IGNORE_PRICE: U256 = U256::from_limbs([2u64, 0, 0, 0]);
-
-/// Settings for the [GasPriceOracle]
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct GasPriceOracleConfig {
-    /// The number of populated blocks to produce the gas price estimate
-    pub blocks: u32,
-
-    /// The percentile of gas prices to use for the estimate
-    pub percentile: u32,
-
-    /// The maximum number of headers to keep in the cache
-    pub max_header_history: u64,
-
-    /// The maximum number of blocks for estimating gas price
-    pub max_block_history: u64,
-
-    /// The default gas price to use if there are no blocks to use
-    pub default: Option<U256>,
-
-    /// The maximum gas price to use for the estimate
-    pub max_price: Option<U256>,
-
-    /// The minimum gas price, under which the sample will be ignored
-    pub ignore_price: Option<U256>,
-}
-
-impl Default for GasPriceOracleConfig {
-    fn default() -> Self {
-        GasPriceOracleConfig {
-            blocks: 20,
-            percentile: 60,
-            max_header_history: 1024,
-            max_block_history: 1024,
-            default: None,
-            max_price: Some(DEFAULT_MAX_PRICE),
-            ignore_price: Some(DEFAULT_IGNORE_PRICE),
-        }
-    }
-}
-
-impl GasPriceOracleConfig {
-    /// Creating a new gpo config with blocks, ignoreprice, maxprice and percentile
-    pub fn new(
-        blocks: Option<u32>,
-        ignore_price: Option<u64>,
-        max_price: Option<u64>,
-        percentile: Option<u32>,
-    ) -> Self {
-        Self {
-            blocks: blocks.unwrap_or(20),
-            percentile: percentile.unwrap_or(60),
-            max_header_history: 1024,
-            max_block_history: 1024,
-            default: None,
-            max_price: max_price.map(U256::from).or(Some(DEFAULT_MAX_PRICE)),
-            ignore_price: ignore_price.map(U256::from).or(Some(DEFAULT_IGNORE_PRICE)),
-        }
-    }
-}
+pub use reth_rpc_types::gas_oracle::{
+    GasPriceOracleConfig, DEFAULT_IGNORE_PRICE, DEFAULT_MAX_PRICE, SAMPLE_NUMBER,
+};
 
 /// Calculates a gas price depending on recent blocks.
 #[derive(Debug)]
@@ -96,6 +34,8 @@ pub struct GasPriceOracle<Provider> {
     /// Stores the latest calculated price and its block hash and Cache stores the lowest effective
     /// tip values of recent blocks
     inner: Mutex<GasPriceOracleInner>,
+    /// Caches the rewards of the transactions of recent blocks, for `eth_feeHistory`
+    reward_cache: Mutex<RewardLruCache>,
 }
 
 impl<Provider> GasPriceOracle<Provider>
@@ -113,6 +53,13 @@ where
             warn!(prev_percentile = ?oracle_config.percentile, "Invalid configured gas price percentile, assuming 100.");
             oracle_config.percentile = 100;
         }
+        if oracle_config.default_reward_percentiles.iter().any(|percentile| *percentile > 100) {
+            warn!(prev_percentiles = ?oracle_config.default_reward_percentiles, "Invalid configured default reward percentiles, assuming 100 for the ones above it.");
+        }
+        for percentile in &mut oracle_config.default_reward_percentiles {
+            *percentile = (*percentile).min(100);
+        }
+        oracle_config.default_reward_percentiles.sort_unstable();
         let ignore_price = oracle_config.ignore_price.map(|price| price.saturating_to());
 
         // this is the number of blocks that we will cache the values for
@@ -124,7 +71,11 @@ where
             ))),
         });
 
-        Self { provider, oracle_config, cache, ignore_price, inner }
+        let reward_cache = Mutex::new(RewardLruCache(LruMap::new(ByLength::new(
+            oracle_config.max_block_history as u32,
+        ))));
+
+        Self { provider, oracle_config, cache, ignore_price, inner, reward_cache }
     }
 
     /// Returns the configuration of the gas price oracle.
@@ -170,7 +121,7 @@ where
                 } else {
                     // Otherwise we fetch it using get_block_values
                     let (parent_hash, block_values) = self
-                        .get_block_values(current_hash, SAMPLE_NUMBER)
+                        .get_block_values(current_hash, self.oracle_config.transactions)
                         .await?
                         .ok_or(EthApiError::UnknownBlockNumber)?;
                     inner
@@ -215,6 +166,49 @@ where
         Ok(price)
     }
 
+    /// Returns the gas used and the effective tip of every transaction of the block, sorted by
+    /// ascending tip, which the rewards of `eth_feeHistory` are percentiles of.
+    ///
+    /// The rewards of the last `max_block_history` blocks are cached.
+    pub async fn block_rewards(&self, header: &SealedHeader) -> EthResult<Arc<[TxGasAndReward]>> {
+        if let Some(rewards) = self.reward_cache.lock().await.get(&header.hash) {
+            return Ok(Arc::clone(rewards))
+        }
+
+        let (transactions, receipts) = self
+            .cache
+            .get_transactions_and_receipts(header.hash)
+            .await?
+            .ok_or(EthApiError::InvalidBlockRange)?;
+
+        let mut rewards = transactions
+            .into_iter()
+            .zip(receipts)
+            .scan(0, |previous_gas, (tx, receipt)| {
+                // Convert the cumulative gas used in the receipts
+                // to the gas usage by the transaction
+                //
+                // While we will sum up the gas again later, it is worth
+                // noting that the order of the transactions will be different,
+                // so the sum will also be different for each receipt.
+                let gas_used = receipt.cumulative_gas_used - *previous_gas;
+                *previous_gas = receipt.cumulative_gas_used;
+
+                Some(TxGasAndReward {
+                    gas_used,
+                    reward: tx.effective_gas_tip(header.base_fee_per_gas).unwrap_or_default(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Sort the transactions by their rewards in ascending order
+        rewards.sort_by_key(|tx| tx.reward);
+
+        let rewards: Arc<[TxGasAndReward]> = rewards.into();
+        self.reward_cache.lock().await.insert(header.hash, Arc::clone(&rewards));
+        Ok(rewards)
+    }
+
     /// Get the `limit` lowest effective tip values for the given block. If the oracle has a
     /// configured `ignore_price` threshold, then tip values under that threshold will be ignored
     /// before returning a result.
@@ -297,6 +291,19 @@ impl Debug for EffectiveTipLruCache {
     }
 }
 
+/// Wrapper struct for the LruMap of the rewards of blocks
+#[derive(Deref, DerefMut)]
+pub struct RewardLruCache(LruMap<B256, Arc<[TxGasAndReward]>, ByLength>);
+
+impl Debug for RewardLruCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RewardLruCache")
+            .field("cache_length", &self.len())
+            .field("cache_memory_usage", &self.memory_usage())
+            .finish()
+    }
+}
+
 /// Stores the last result that the oracle returned
 #[derive(Debug, Clone)]
 pub struct GasPriceOracleResult {
@@ -328,4 +335,18 @@ mod tests {
     fn ignore_price_sanity() {
         assert_eq!(DEFAULT_IGNORE_PRICE, U256::from(2u64));
     }
+
+    #[test]
+    fn partial_config() {
+        let config: GasPriceOracleConfig =
+            serde_json::from_str(r#"{"blocks":10,"defaultRewardPercentiles":[25,75]}"#).unwrap();
+        assert_eq!(
+            config,
+            GasPriceOracleConfig {
+                blocks: 10,
+                default_reward_percentiles: vec![25, 75],
+                ..Default::default()
+            }
+        );
+    }
 }
@@ -31,6 +31,8 @@ pub struct EthPubSub<Provider, Pool, Events, Network> {
     inner: Arc<EthPubSubInner<Provider, Pool, Events, Network>>,
     /// The type that's used to spawn subscription tasks.
     subscription_task_spawner: Box<dyn TaskSpawner>,
+    /// Whether `newPendingTransactions` subscriptions can stream full transactions.
+    full_pending_transactions: bool,
 }
 
 // === impl EthPubSub ===
@@ -58,7 +60,17 @@ impl<Provider, Pool, Events, Network> EthPubSub<Provider, Pool, Events, Network>
         subscription_task_spawner: Box<dyn TaskSpawner>,
     ) -> Self {
         let inner = EthPubSubInner { provider, pool, chain_events, network };
-        Self { inner: Arc::new(inner), subscription_task_spawner }
+        Self { inner: Arc::new(inner), subscription_task_spawner, full_pending_transactions: false }
+    }
+
+    /// Allows `newPendingTransactions` subscriptions to stream full transaction objects instead of
+    /// their hashes.
+    ///
+    /// This is disabled by default, since every subscriber then receives every transaction of the
+    /// pool.
+    pub fn with_full_pending_transactions(mut self, enabled: bool) -> Self {
+        self.full_pending_transactions = enabled;
+        self
     }
 }
 
@@ -80,8 +92,9 @@ where
     ) -> jsonrpsee::core::SubscriptionResult {
         let sink = pending.accept().await?;
         let pubsub = self.inner.clone();
+        let full_pending_transactions = self.full_pending_transactions;
         self.subscription_task_spawner.spawn(Box::pin(async move {
-            let _ = handle_accepted(pubsub, sink, kind, params).await;
+            let _ = handle_accepted(pubsub, sink, kind, params, full_pending_transactions).await;
         }));
 
         Ok(())
@@ -94,6 +107,7 @@ async fn handle_accepted<Provider, Pool, Events, Network>(
     accepted_sink: SubscriptionSink,
     kind: SubscriptionKind,
     params: Option<Params>,
+    full_pending_transactions: bool,
 ) -> Result<(), jsonrpsee::core::Error>
 where
     Provider: BlockReader + EvmEnvProvider + Clone + 'static,
@@ -124,6 +138,12 @@ where
         SubscriptionKind::NewPendingTransactions => {
             if let Some(params) = params {
                 match params {
+                    Params::Bool(true) if !full_pending_transactions => {
+                        return Err(invalid_params_rpc_err(
+                            "Full pending transactions are disabled on this node",
+                        )
+                        .into())
+                    }
                     Params::Bool(true) => {
                         // full transaction objects requested
                         let stream = pubsub.full_pending_transaction_stream().map(|tx| {
@@ -42,13 +42,15 @@ impl Headers {
 impl Segment for Headers {
     fn snapshot(&self, tx: &impl DbTx, range: RangeInclusive<BlockNumber>) -> RethResult<()> {
         let range_len = range.clone().count();
-        let mut jar = prepare_jar::<3, tables::Headers>(
-            tx,
+        // The range may start past the headers of the database
+        let total_rows =
+            tx.entries::<tables::Headers>()?.saturating_sub(*range.start() as usize).min(range_len);
+        let mut jar = prepare_jar::<3>(
             SnapshotSegment::Headers,
             self.filters,
             self.compression,
             range.clone(),
-            range_len,
+            total_rows,
             || {
                 Ok([
                     self.dataset_for_compression::<tables::Headers>(tx, &range, range_len)?,
@@ -1,15 +1,19 @@
 //! Snapshot segment implementations and utilities.
 
 mod headers;
+mod receipts;
+mod transactions;
 
 pub use headers::Headers;
+pub use receipts::Receipts;
+pub use transactions::Transactions;
 
-use reth_db::{table::Table, transaction::DbTx};
-use reth_interfaces::RethResult;
-use reth_nippy_jar::NippyJar;
+use reth_db::{cursor::DbCursorRO, tables, transaction::DbTx};
+use reth_interfaces::{provider::ProviderError, RethResult};
+use reth_nippy_jar::{ColumnResult, NippyJar};
 use reth_primitives::{
     snapshot::{Compression, Filters, InclusionFilter, PerfectHashingFunction},
-    BlockNumber, SnapshotSegment,
+    BlockNumber, SnapshotSegment, TxHash, TxNumber,
 };
 use std::{ops::RangeInclusive, path::PathBuf};
 
@@ -21,14 +25,14 @@ pub trait Segment {
     fn snapshot(&self, tx: &impl DbTx, range: RangeInclusive<BlockNumber>) -> RethResult<()>;
 }
 
-/// Returns a [`NippyJar`] according to the desired configuration.
-pub(crate) fn prepare_jar<const COLUMNS: usize, T: Table>(
-    tx: &impl DbTx,
+/// Returns a [`NippyJar`] of `total_rows` rows according to the desired configuration, for the
+/// blocks of `range`.
+pub(crate) fn prepare_jar<const COLUMNS: usize>(
     segment: SnapshotSegment,
     filters: Filters,
     compression: Compression,
     range: RangeInclusive<BlockNumber>,
-    range_len: usize,
+    total_rows: usize,
     prepare_compression: impl Fn() -> RethResult<Rows<COLUMNS>>,
 ) -> RethResult<NippyJar> {
     let mut nippy_jar = NippyJar::new_without_header(
@@ -50,7 +54,6 @@ pub(crate) fn prepare_jar<const COLUMNS: usize, T: Table>(
     };
 
     if let Filters::WithFilters(inclusion_filter, phf) = filters {
-        let total_rows = (tx.entries::<T>()? - *range.start() as usize).min(range_len);
         nippy_jar = match inclusion_filter {
             InclusionFilter::Cuckoo => nippy_jar.with_cuckoo_filter(total_rows),
         };
@@ -63,6 +66,29 @@ pub(crate) fn prepare_jar<const COLUMNS: usize, T: Table>(
     Ok(nippy_jar)
 }
 
+/// Returns the range of the transactions of the blocks of `range`.
+pub(crate) fn block_range_to_tx_range(
+    tx: &impl DbTx,
+    range: &RangeInclusive<BlockNumber>,
+) -> RethResult<RangeInclusive<TxNumber>> {
+    let indices = |block| {
+        tx.get::<tables::BlockBodyIndices>(block)?
+            .ok_or(ProviderError::BlockBodyIndicesNotFound(block))
+    };
+    Ok(indices(*range.start())?.first_tx_num()..=indices(*range.end())?.last_tx_num())
+}
+
+/// Returns the hashes of the transactions of `range`, the keys of the filters and PHF of the
+/// segments keyed by transaction number.
+pub(crate) fn transaction_hashes<'a>(
+    cursor: &'a mut impl DbCursorRO<tables::Transactions>,
+    range: &RangeInclusive<TxNumber>,
+) -> RethResult<impl Iterator<Item = ColumnResult<TxHash>> + 'a> {
+    Ok(cursor
+        .walk_range(range.clone())?
+        .map(|row| row.map(|(_key, tx)| tx.hash()).map_err(|e| e.into())))
+}
+
 /// Returns file name for the provided segment, filters, compression and range.
 pub fn get_snapshot_segment_file_name(
     segment: SnapshotSegment,
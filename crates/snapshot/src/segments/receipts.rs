@@ -0,0 +1,83 @@
+use crate::segments::{block_range_to_tx_range, prepare_jar, transaction_hashes, Segment};
+use reth_db::{
+    cursor::DbCursorRO, snapshot::create_snapshot_T1, table::Table, tables, transaction::DbTx,
+    RawKey, RawTable,
+};
+use reth_interfaces::RethResult;
+use reth_primitives::{
+    snapshot::{Compression, Filters},
+    BlockNumber, SnapshotSegment, TxNumber,
+};
+use std::ops::RangeInclusive;
+
+/// Snapshot segment responsible for [SnapshotSegment::Receipts] part of data.
+#[derive(Debug)]
+pub struct Receipts {
+    compression: Compression,
+    filters: Filters,
+}
+
+impl Receipts {
+    /// Creates new instance of [Receipts] snapshot segment.
+    pub fn new(compression: Compression, filters: Filters) -> Self {
+        Self { compression, filters }
+    }
+
+    // Generates the dataset to train a zstd dictionary with the most recent rows (at most 1000).
+    fn dataset_for_compression<T: Table<Key = TxNumber>>(
+        &self,
+        tx: &impl DbTx,
+        range: &RangeInclusive<TxNumber>,
+        range_len: usize,
+    ) -> RethResult<Vec<Vec<u8>>> {
+        let mut cursor = tx.cursor_read::<RawTable<T>>()?;
+        Ok(cursor
+            .walk_back(Some(RawKey::from(*range.end())))?
+            .take(range_len.min(1000))
+            .map(|row| row.map(|(_key, value)| value.into_value()).expect("should exist"))
+            .collect::<Vec<_>>())
+    }
+}
+
+impl Segment for Receipts {
+    fn snapshot(&self, tx: &impl DbTx, range: RangeInclusive<BlockNumber>) -> RethResult<()> {
+        let tx_range = block_range_to_tx_range(tx, &range)?;
+        let tx_range_len = tx_range.clone().count();
+
+        let mut jar = prepare_jar::<1>(
+            SnapshotSegment::Receipts,
+            self.filters,
+            self.compression,
+            range,
+            tx_range_len,
+            || {
+                Ok([self.dataset_for_compression::<tables::Receipts>(
+                    tx,
+                    &tx_range,
+                    tx_range_len,
+                )?])
+            },
+        )?;
+
+        // Generate list of transaction hashes for filters & PHF, so that receipts can be found by
+        // the hash of their transaction
+        let mut cursor = tx.cursor_read::<tables::Transactions>()?;
+        let mut hashes = None;
+        if self.filters.has_filters() {
+            hashes = Some(transaction_hashes(&mut cursor, &tx_range)?);
+        }
+
+        create_snapshot_T1::<tables::Receipts, TxNumber>(
+            tx,
+            tx_range,
+            None,
+            // We already prepared the dictionary beforehand
+            None::<Vec<std::vec::IntoIter<Vec<u8>>>>,
+            hashes,
+            tx_range_len,
+            &mut jar,
+        )?;
+
+        Ok(())
+    }
+}
@@ -8,6 +8,7 @@ use std::{
     future::Future,
     pin::Pin,
     task::{ready, Context, Poll},
+    time::Duration,
 };
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use tracing::trace;
@@ -38,6 +39,19 @@ pub enum MetricEvent {
         /// Gas processed.
         gas: u64,
     },
+    /// Stage executed a batch and committed it.
+    StageExecuted {
+        /// Stage ID.
+        stage_id: StageId,
+        /// The number of blocks the batch advanced the checkpoint by.
+        blocks: u64,
+        /// The number of entities processed in the batch, if the stage reports entities.
+        entities: Option<u64>,
+        /// The time it took to execute the batch.
+        elapsed: Duration,
+        /// The time it took to commit the batch.
+        commit_elapsed: Duration,
+    },
 }
 
 /// Metrics routine that listens to new metric events on the `events_rx` receiver.
@@ -90,6 +104,18 @@ impl MetricsListener {
                 .execution_stage
                 .mgas_processed_total
                 .increment(gas as f64 / MGAS_TO_GAS as f64),
+            MetricEvent::StageExecuted { stage_id, blocks, entities, elapsed, commit_elapsed } => {
+                let stage_metrics = self.sync_metrics.get_stage_metrics(stage_id);
+
+                let seconds = elapsed.as_secs_f64();
+                if seconds > 0.0 {
+                    stage_metrics.blocks_per_second.record(blocks as f64 / seconds);
+                    if let Some(entities) = entities {
+                        stage_metrics.entities_per_second.record(entities as f64 / seconds);
+                    }
+                }
+                stage_metrics.commit_duration_seconds.record(commit_elapsed.as_secs_f64());
+            }
         }
     }
 }
@@ -1,4 +1,7 @@
-use reth_metrics::{metrics::Gauge, Metrics};
+use reth_metrics::{
+    metrics::{Gauge, Histogram},
+    Metrics,
+};
 use reth_primitives::stage::StageId;
 use std::collections::HashMap;
 
@@ -26,6 +29,12 @@ pub(crate) struct StageMetrics {
     pub(crate) entities_processed: Gauge,
     /// The number of total entities of the last commit for a stage, if applicable.
     pub(crate) entities_total: Gauge,
+    /// The number of blocks per second a stage processed in each batch.
+    pub(crate) blocks_per_second: Histogram,
+    /// The number of entities per second a stage processed in each batch, if applicable.
+    pub(crate) entities_per_second: Histogram,
+    /// The time it took to commit each batch of a stage, in seconds.
+    pub(crate) commit_duration_seconds: Histogram,
 }
 
 /// Execution stage metrics.
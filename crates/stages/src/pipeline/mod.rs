@@ -10,7 +10,7 @@ use reth_primitives::{
 };
 use reth_provider::{ProviderFactory, StageCheckpointReader, StageCheckpointWriter};
 use reth_tokio_util::EventListeners;
-use std::{pin::Pin, sync::Arc};
+use std::{pin::Pin, sync::Arc, time::Instant};
 use tokio::sync::watch;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::*;
@@ -367,11 +367,13 @@ where
                 checkpoint: prev_checkpoint,
             });
 
+            let started_at = Instant::now();
             match stage
                 .execute(&provider_rw, ExecInput { target, checkpoint: prev_checkpoint })
                 .await
             {
                 Ok(out @ ExecOutput { checkpoint, done }) => {
+                    let elapsed = started_at.elapsed();
                     made_progress |=
                         checkpoint.block_number != prev_checkpoint.unwrap_or_default().block_number;
                     debug!(
@@ -399,9 +401,32 @@ where
                     });
 
                     // TODO: Make the commit interval configurable
+                    let commit_started_at = Instant::now();
                     provider_rw.commit()?;
+                    let commit_elapsed = commit_started_at.elapsed();
                     provider_rw = factory.provider_rw().map_err(PipelineError::Interface)?;
 
+                    if let Some(metrics_tx) = &mut self.metrics_tx {
+                        // The entities of the batch are only known if both checkpoints have them,
+                        // or if the stage started from scratch.
+                        let entities =
+                            checkpoint.entities().and_then(|entities| match prev_checkpoint {
+                                Some(prev_checkpoint) => prev_checkpoint
+                                    .entities()
+                                    .map(|prev| entities.processed.saturating_sub(prev.processed)),
+                                None => Some(entities.processed),
+                            });
+                        let _ = metrics_tx.send(MetricEvent::StageExecuted {
+                            stage_id,
+                            blocks: checkpoint
+                                .block_number
+                                .saturating_sub(prev_checkpoint.unwrap_or_default().block_number),
+                            entities,
+                            elapsed,
+                            commit_elapsed,
+                        });
+                    }
+
                     if done {
                         let block_number = checkpoint.block_number;
                         return Ok(if made_progress {
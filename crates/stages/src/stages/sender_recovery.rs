@@ -1,5 +1,6 @@
 use crate::{ExecInput, ExecOutput, Stage, StageError, UnwindInput, UnwindOutput};
 use itertools::Itertools;
+use rayon::ThreadPool;
 use reth_db::{
     cursor::{DbCursorRO, DbCursorRW},
     database::Database,
@@ -16,7 +17,7 @@ use reth_primitives::{
 use reth_provider::{
     BlockReader, DatabaseProviderRW, HeaderProvider, ProviderError, PruneCheckpointReader,
 };
-use std::fmt::Debug;
+use std::{fmt::Debug, sync::Arc};
 use thiserror::Error;
 use tokio::sync::mpsc;
 use tracing::*;
@@ -29,18 +30,46 @@ pub struct SenderRecoveryStage {
     /// The size of inserted items after which the control
     /// flow will be returned to the pipeline for commit
     pub commit_threshold: u64,
+    /// The number of threads senders are recovered on. If `None`, senders are recovered on the
+    /// global rayon thread pool.
+    pub workers: Option<usize>,
+    /// The thread pool of the `workers`, built on the first execution.
+    pool: Option<Arc<ThreadPool>>,
 }
 
 impl SenderRecoveryStage {
     /// Create new instance of [SenderRecoveryStage].
     pub fn new(commit_threshold: u64) -> Self {
-        Self { commit_threshold }
+        Self { commit_threshold, workers: None, pool: None }
+    }
+
+    /// Set the number of threads senders are recovered on, or `None` to recover them on the
+    /// global rayon thread pool.
+    pub fn with_workers(mut self, workers: Option<usize>) -> Self {
+        self.workers = workers;
+        self.pool = None;
+        self
+    }
+
+    /// Returns the thread pool of the `workers`, or `None` if senders are recovered on the global
+    /// rayon thread pool.
+    fn thread_pool(&mut self) -> Result<Option<Arc<ThreadPool>>, StageError> {
+        let Some(workers) = self.workers else { return Ok(None) };
+        if self.pool.is_none() {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(workers)
+                .thread_name(|i| format!("sender-recovery-{i}"))
+                .build()
+                .map_err(|err| StageError::Fatal(Box::new(err)))?;
+            self.pool = Some(Arc::new(pool));
+        }
+        Ok(self.pool.clone())
     }
 }
 
 impl Default for SenderRecoveryStage {
     fn default() -> Self {
-        Self { commit_threshold: 5_000_000 }
+        Self::new(5_000_000)
     }
 }
 
@@ -96,13 +125,17 @@ impl<DB: Database> Stage<DB> for SenderRecoveryStage {
         // channels used to return result of sender recovery.
         let mut channels = Vec::new();
 
-        // Spawn recovery jobs onto the default rayon threadpool and send the result through the
-        // channel.
+        // Spawn recovery jobs onto the threadpool of the workers, or the default rayon threadpool,
+        // and send the result through the channel.
         //
         // We try to evenly divide the transactions to recover across all threads in the threadpool.
         // Chunks are submitted instead of individual transactions to reduce the overhead of work
         // stealing in the threadpool workers.
-        let chunk_size = self.commit_threshold as usize / rayon::current_num_threads();
+        let pool = self.thread_pool()?;
+        let num_threads = pool
+            .as_ref()
+            .map_or_else(rayon::current_num_threads, |pool| pool.current_num_threads());
+        let chunk_size = self.commit_threshold as usize / num_threads;
         // prevents an edge case
         // where the chunk size is either 0 or too small
         // to gain anything from using more than 1 thread
@@ -115,16 +148,20 @@ impl<DB: Database> Stage<DB> for SenderRecoveryStage {
             // Note: Unfortunate side-effect of how chunk is designed in itertools (it is not Send)
             let chunk: Vec<_> = chunk.collect();
 
-            // Spawn the sender recovery task onto the global rayon pool
+            // Spawn the sender recovery task onto the rayon pool
             // This task will send the results through the channel after it recovered the senders.
-            rayon::spawn(move || {
+            let recover = move || {
                 let mut rlp_buf = Vec::with_capacity(128);
                 for entry in chunk {
                     rlp_buf.clear();
                     let recovery_result = recover_sender(entry, &mut rlp_buf);
                     let _ = recovered_senders_tx.send(recovery_result);
                 }
-            });
+            };
+            match &pool {
+                Some(pool) => pool.spawn(recover),
+                None => rayon::spawn(recover),
+            }
         }
 
         // Iterate over channels and append the sender in the order that they are received.
@@ -308,6 +345,34 @@ mod tests {
         assert!(runner.validate_execution(input, result.ok()).is_ok(), "execution validation");
     }
 
+    /// Execute the stage on a thread pool of its own
+    #[tokio::test]
+    async fn execute_with_workers() {
+        let (previous_stage, stage_progress) = (200, 100);
+
+        let mut runner = SenderRecoveryTestRunner::default();
+        runner.set_workers(Some(2));
+        let input = ExecInput {
+            target: Some(previous_stage),
+            checkpoint: Some(StageCheckpoint::new(stage_progress)),
+        };
+        runner.seed_execution(input).expect("failed to seed execution");
+
+        let mut stage = runner.stage();
+        assert_matches!(stage.thread_pool(), Ok(Some(pool)) if pool.current_num_threads() == 2);
+
+        let rx = runner.execute(input);
+        let result = rx.await.unwrap();
+        assert_matches!(
+            result,
+            Ok(ExecOutput { checkpoint: StageCheckpoint { block_number, .. }, done: true })
+                if block_number == previous_stage
+        );
+
+        // Validate the stage execution
+        assert!(runner.validate_execution(input, result.ok()).is_ok(), "execution validation");
+    }
+
     /// Execute the stage twice with input range that exceeds the commit threshold
     #[tokio::test]
     async fn execute_intermediate_commit() {
@@ -437,11 +502,12 @@ mod tests {
     struct SenderRecoveryTestRunner {
         tx: TestTransaction,
         threshold: u64,
+        workers: Option<usize>,
     }
 
     impl Default for SenderRecoveryTestRunner {
         fn default() -> Self {
-            Self { threshold: 1000, tx: TestTransaction::default() }
+            Self { threshold: 1000, workers: None, tx: TestTransaction::default() }
         }
     }
 
@@ -450,6 +516,10 @@ mod tests {
             self.threshold = threshold;
         }
 
+        fn set_workers(&mut self, workers: Option<usize>) {
+            self.workers = workers;
+        }
+
         /// # Panics
         ///
         /// 1. If there are any entries in the [tables::TxSenders] table above a given block number.
@@ -483,7 +553,7 @@ mod tests {
         }
 
         fn stage(&self) -> Self::S {
-            SenderRecoveryStage { commit_threshold: self.threshold }
+            SenderRecoveryStage::new(self.threshold).with_workers(self.workers)
         }
     }
 
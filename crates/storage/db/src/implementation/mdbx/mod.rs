@@ -66,10 +66,41 @@ impl<E: EnvironmentKind> Env<E> {
         path: &Path,
         kind: EnvKind,
         log_level: Option<LogLevel>,
+    ) -> Result<Env<E>, DatabaseError> {
+        Self::open_with_sync_mode(path, kind, log_level, SyncMode::Durable)
+    }
+
+    /// Opens the database like [`Env::open`], flushing the commits of read-write transactions to
+    /// disk according to `sync_mode`. It's ignored for [`EnvKind::RO`].
+    pub fn open_with_sync_mode(
+        path: &Path,
+        kind: EnvKind,
+        log_level: Option<LogLevel>,
+        sync_mode: SyncMode,
+    ) -> Result<Env<E>, DatabaseError> {
+        Self::open_with_flags(path, kind, log_level, sync_mode, false)
+    }
+
+    /// Opens the database like [`Env::open`], failing if another process has it open, and keeping
+    /// the other processes from opening it until it's closed.
+    pub fn open_exclusive(
+        path: &Path,
+        kind: EnvKind,
+        log_level: Option<LogLevel>,
+    ) -> Result<Env<E>, DatabaseError> {
+        Self::open_with_flags(path, kind, log_level, SyncMode::Durable, true)
+    }
+
+    fn open_with_flags(
+        path: &Path,
+        kind: EnvKind,
+        log_level: Option<LogLevel>,
+        sync_mode: SyncMode,
+        exclusive: bool,
     ) -> Result<Env<E>, DatabaseError> {
         let mode = match kind {
             EnvKind::RO => Mode::ReadOnly,
-            EnvKind::RW => Mode::ReadWrite { sync_mode: SyncMode::Durable },
+            EnvKind::RW => Mode::ReadWrite { sync_mode },
         };
 
         let mut inner_env = Environment::new();
@@ -85,6 +116,7 @@ impl<E: EnvironmentKind> Env<E> {
         });
         inner_env.set_flags(EnvironmentFlags {
             mode,
+            exclusive,
             // We disable readahead because it improves performance for linear scans, but
             // worsens it for random access (which is our access pattern outside of sync)
             no_rdahead: true,
@@ -103,9 +103,40 @@ use std::path::Path;
 /// Opens up an existing database or creates a new one at the specified path. Creates tables if
 /// necessary. Read/Write mode.
 pub fn init_db<P: AsRef<Path>>(path: P, log_level: Option<LogLevel>) -> eyre::Result<DatabaseEnv> {
-    use crate::version::{check_db_version_file, create_db_version_file, DatabaseVersionError};
+    let rpath = path.as_ref();
+    init_db_dir(rpath)?;
+    #[cfg(feature = "mdbx")]
+    {
+        let db = DatabaseEnv::open(rpath, EnvKind::RW, log_level)?;
+        db.create_tables()?;
+        Ok(db)
+    }
+    #[cfg(not(feature = "mdbx"))]
+    {
+        unimplemented!();
+    }
+}
 
+/// Like [`init_db`], but flushes the commits of read/write transactions to disk according to
+/// `sync_mode`, instead of always durably.
+#[cfg(feature = "mdbx")]
+pub fn init_db_with_sync_mode<P: AsRef<Path>>(
+    path: P,
+    log_level: Option<LogLevel>,
+    sync_mode: mdbx::SyncMode,
+) -> eyre::Result<DatabaseEnv> {
     let rpath = path.as_ref();
+    init_db_dir(rpath)?;
+    let db = DatabaseEnv::open_with_sync_mode(rpath, EnvKind::RW, log_level, sync_mode)?;
+    db.create_tables()?;
+    Ok(db)
+}
+
+/// Creates the database directory and its version file if missing, or checks the version file
+/// otherwise.
+fn init_db_dir(rpath: &Path) -> eyre::Result<()> {
+    use crate::version::{check_db_version_file, create_db_version_file, DatabaseVersionError};
+
     if is_database_empty(rpath) {
         std::fs::create_dir_all(rpath)
             .wrap_err_with(|| format!("Could not create database directory {}", rpath.display()))?;
@@ -117,11 +148,15 @@ pub fn init_db<P: AsRef<Path>>(path: P, log_level: Option<LogLevel>) -> eyre::Re
             Err(err) => return Err(err.into()),
         }
     }
+    Ok(())
+}
+
+/// Opens up an existing database. Read only mode. It doesn't create it or create tables if missing.
+pub fn open_db_read_only(path: &Path, log_level: Option<LogLevel>) -> eyre::Result<DatabaseEnvRO> {
     #[cfg(feature = "mdbx")]
     {
-        let db = DatabaseEnv::open(rpath, EnvKind::RW, log_level)?;
-        db.create_tables()?;
-        Ok(db)
+        Env::<NoWriteMap>::open(path, EnvKind::RO, log_level)
+            .with_context(|| format!("Could not open database at path: {}", path.display()))
     }
     #[cfg(not(feature = "mdbx"))]
     {
@@ -129,12 +164,17 @@ pub fn init_db<P: AsRef<Path>>(path: P, log_level: Option<LogLevel>) -> eyre::Re
     }
 }
 
-/// Opens up an existing database. Read only mode. It doesn't create it or create tables if missing.
-pub fn open_db_read_only(path: &Path, log_level: Option<LogLevel>) -> eyre::Result<DatabaseEnvRO> {
+/// Opens up an existing database like [`open_db`], failing if another process, e.g. a running
+/// node, has it open.
+pub fn open_db_exclusive(path: &Path, log_level: Option<LogLevel>) -> eyre::Result<DatabaseEnv> {
     #[cfg(feature = "mdbx")]
     {
-        Env::<NoWriteMap>::open(path, EnvKind::RO, log_level)
-            .with_context(|| format!("Could not open database at path: {}", path.display()))
+        Env::<WriteMap>::open_exclusive(path, EnvKind::RW, log_level).with_context(|| {
+            format!(
+                "Could not open database at path {} exclusively, is another process using it?",
+                path.display()
+            )
+        })
     }
     #[cfg(not(feature = "mdbx"))]
     {
@@ -168,6 +168,19 @@ where
         }
     }
 
+    /// Copies the environment to the file at `dest`, e.g. to back it up while it's in use.
+    ///
+    /// The copy is made in a read transaction, so it's consistent even with concurrent writes. If
+    /// `compact`, the free pages are omitted and the others renumbered, making the copy smaller.
+    ///
+    /// `dest` must not exist yet, but its parent directory must.
+    pub fn copy(&self, dest: &Path, compact: bool) -> Result<()> {
+        let dest = CString::new(path_to_bytes(dest)).map_err(|_| Error::Invalid)?;
+        let flags = if compact { ffi::MDBX_CP_COMPACT } else { ffi::MDBX_CP_DEFAULTS };
+        mdbx_result(unsafe { ffi::mdbx_env_copy(self.env(), dest.as_ptr(), flags) })?;
+        Ok(())
+    }
+
     /// Retrieves the total number of pages on the freelist.
     ///
     /// Along with [Environment::info()], this can be used to calculate the exact number
@@ -214,6 +227,20 @@ where
     }
 }
 
+#[cfg(unix)]
+fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_ref().as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(windows)]
+fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
+    // On Windows, could use std::os::windows::ffi::OsStrExt to encode_wide(),
+    // but we end up with a Vec<u16> instead of a Vec<u8>, so that doesn't
+    // really help.
+    path.as_ref().to_string_lossy().to_string().into_bytes()
+}
+
 /// Environment statistics.
 ///
 /// Contains information about the size and layout of an MDBX environment or database.
@@ -319,6 +346,18 @@ impl Info {
     pub fn num_readers(&self) -> usize {
         self.0.mi_numreaders as usize
     }
+
+    /// Database page size
+    #[inline]
+    pub fn page_size(&self) -> usize {
+        self.0.mi_dxb_pagesize as usize
+    }
+
+    /// Bytes written to the pages of the database but not synchronized to disk yet
+    #[inline]
+    pub fn unsync_volume(&self) -> usize {
+        self.0.mi_unsync_volume as usize
+    }
 }
 
 unsafe impl<E> Send for Environment<E> where E: EnvironmentKind {}
@@ -469,20 +508,6 @@ where
                     ))?;
                 }
 
-                #[cfg(unix)]
-                fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
-                    use std::os::unix::ffi::OsStrExt;
-                    path.as_ref().as_os_str().as_bytes().to_vec()
-                }
-
-                #[cfg(windows)]
-                fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
-                    // On Windows, could use std::os::windows::ffi::OsStrExt to encode_wide(),
-                    // but we end up with a Vec<u16> instead of a Vec<u8>, so that doesn't
-                    // really help.
-                    path.as_ref().to_string_lossy().to_string().into_bytes()
-                }
-
                 let path = match CString::new(path_to_bytes(path)) {
                     Ok(path) => path,
                     Err(_) => return Err(Error::Invalid),
@@ -156,3 +156,26 @@ fn test_freelist() {
     freelist = env.freelist().unwrap();
     assert!(freelist > 0);
 }
+
+#[test]
+fn test_copy() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let tx = env.begin_rw_txn().expect("begin_rw_txn");
+    tx.put(tx.open_db(None).unwrap().dbi(), b"key", b"val", WriteFlags::default()).expect("tx.put");
+    tx.commit().expect("tx.commit");
+
+    // A read transaction doesn't hold the copy back.
+    let _reader = env.begin_ro_txn().unwrap();
+    for compact in [false, true] {
+        let copy_dir = tempdir().unwrap();
+        env.copy(&copy_dir.path().join("mdbx.dat"), compact).unwrap();
+        // The destination must not exist yet.
+        assert!(env.copy(&copy_dir.path().join("mdbx.dat"), compact).is_err());
+
+        let copy = Environment::new().open(copy_dir.path()).unwrap();
+        let tx = copy.begin_ro_txn().unwrap();
+        assert_eq!(tx.get(tx.open_db(None).unwrap().dbi(), b"key").unwrap(), Some(*b"val"));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     bundle_state::{BundleStateInit, BundleStateWithReceipts, RevertsInit},
+    providers::{SnapshotProvider, Snapshots},
     traits::{
         AccountExtReader, BlockSource, ChangeSetReader, ReceiptProvider, StageCheckpointWriter,
     },
@@ -36,16 +37,16 @@ use reth_primitives::{
     trie::Nibbles,
     Account, Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders,
     ChainInfo, ChainSpec, Hardfork, Head, Header, PruneCheckpoint, PruneModes, PruneSegment,
-    Receipt, SealedBlock, SealedBlockWithSenders, SealedHeader, StorageEntry, TransactionMeta,
-    TransactionSigned, TransactionSignedEcRecovered, TransactionSignedNoHash, TxHash, TxNumber,
-    Withdrawal, B256, U256,
+    Receipt, SealedBlock, SealedBlockWithSenders, SealedHeader, SnapshotSegment, StorageEntry,
+    TransactionMeta, TransactionSigned, TransactionSignedEcRecovered, TransactionSignedNoHash,
+    TxHash, TxNumber, Withdrawal, B256, U256,
 };
 use reth_trie::{prefix_set::PrefixSetMut, StateRoot};
 use revm::primitives::{BlockEnv, CfgEnv, SpecId};
 use std::{
     collections::{hash_map, BTreeMap, BTreeSet, HashMap, HashSet},
     fmt::Debug,
-    ops::{Deref, DerefMut, Range, RangeBounds, RangeInclusive},
+    ops::{Bound, Deref, DerefMut, Range, RangeBounds, RangeInclusive},
     sync::Arc,
 };
 
@@ -95,12 +96,14 @@ pub struct DatabaseProvider<TX> {
     tx: TX,
     /// Chain spec
     chain_spec: Arc<ChainSpec>,
+    /// Snapshots the rows they hold are read from, instead of the database
+    snapshots: Option<Arc<Snapshots>>,
 }
 
 impl<TX: DbTxMut> DatabaseProvider<TX> {
     /// Creates a provider with an inner read-write transaction.
     pub fn new_rw(tx: TX, chain_spec: Arc<ChainSpec>) -> Self {
-        Self { tx, chain_spec }
+        Self { tx, chain_spec, snapshots: None }
     }
 }
 
@@ -155,7 +158,13 @@ where
 impl<TX: DbTx> DatabaseProvider<TX> {
     /// Creates a provider with an inner read-only transaction.
     pub fn new(tx: TX, chain_spec: Arc<ChainSpec>) -> Self {
-        Self { tx, chain_spec }
+        Self { tx, chain_spec, snapshots: None }
+    }
+
+    /// Reads the rows held by `snapshots` from them, instead of the database.
+    pub fn with_snapshots(mut self, snapshots: Option<Arc<Snapshots>>) -> Self {
+        self.snapshots = snapshots;
+        self
     }
 
     /// Consume `DbTx` or `DbTxMut`.
@@ -183,6 +192,64 @@ impl<TX: DbTx> DatabaseProvider<TX> {
             .walk(Some(T::Key::default()))?
             .collect::<Result<Vec<_>, DatabaseError>>()
     }
+
+    /// Reads the row `number` of `segment` with `snapshot` from the snapshot holding it, or with
+    /// `database` if there's none.
+    fn get_with_snapshot<T>(
+        &self,
+        segment: SnapshotSegment,
+        number: u64,
+        snapshot: impl FnOnce(SnapshotProvider<'_>) -> RethResult<Option<T>>,
+        database: impl FnOnce() -> RethResult<Option<T>>,
+    ) -> RethResult<Option<T>> {
+        match self.snapshots.as_ref().and_then(|snapshots| snapshots.provider(segment, number)) {
+            Some((provider, _)) => snapshot(provider),
+            None => database(),
+        }
+    }
+
+    /// Reads the rows of `range` of `segment` with `snapshot` from the snapshots holding them, and
+    /// the rows after the last of those snapshots with `database`.
+    fn get_range_with_snapshot<T>(
+        &self,
+        segment: SnapshotSegment,
+        range: impl RangeBounds<u64>,
+        mut snapshot: impl FnMut(SnapshotProvider<'_>, RangeInclusive<u64>) -> RethResult<Vec<T>>,
+        database: impl FnOnce((Bound<u64>, Bound<u64>)) -> RethResult<Vec<T>>,
+    ) -> RethResult<Vec<T>> {
+        let mut start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => match start.checked_add(1) {
+                Some(start) => start,
+                None => return Ok(Vec::new()),
+            },
+            Bound::Unbounded => 0,
+        };
+
+        let mut values = Vec::new();
+        while range.contains(&start) {
+            let Some((provider, rows)) =
+                self.snapshots.as_ref().and_then(|snapshots| snapshots.provider(segment, start))
+            else {
+                break
+            };
+            let end = match range.end_bound() {
+                Bound::Included(&end) => end.min(*rows.end()),
+                Bound::Excluded(&end) => (end - 1).min(*rows.end()),
+                Bound::Unbounded => *rows.end(),
+            };
+            values.extend(snapshot(provider, start..=end)?);
+            match end.checked_add(1) {
+                Some(next) => start = next,
+                None => return Ok(values),
+            }
+        }
+
+        if range.contains(&start) {
+            values.extend(database((Bound::Included(start), range.end_bound().cloned()))?);
+        }
+        Ok(values)
+    }
 }
 
 impl<TX: DbTxMut + DbTx> DatabaseProvider<TX> {
@@ -868,7 +935,12 @@ impl<TX: DbTx> HeaderProvider for DatabaseProvider<TX> {
     }
 
     fn header_by_number(&self, num: BlockNumber) -> RethResult<Option<Header>> {
-        Ok(self.tx.get::<tables::Headers>(num)?)
+        self.get_with_snapshot(
+            SnapshotSegment::Headers,
+            num,
+            |snapshot| snapshot.header_by_number(num),
+            || Ok(self.tx.get::<tables::Headers>(num)?),
+        )
     }
 
     fn header_td(&self, block_hash: &BlockHash) -> RethResult<Option<U256>> {
@@ -886,30 +958,49 @@ impl<TX: DbTx> HeaderProvider for DatabaseProvider<TX> {
             return Ok(Some(td))
         }
 
-        Ok(self.tx.get::<tables::HeaderTD>(number)?.map(|td| td.0))
+        self.get_with_snapshot(
+            SnapshotSegment::Headers,
+            number,
+            |snapshot| snapshot.header_td_by_number(number),
+            || Ok(self.tx.get::<tables::HeaderTD>(number)?.map(|td| td.0)),
+        )
     }
 
     fn headers_range(&self, range: impl RangeBounds<BlockNumber>) -> RethResult<Vec<Header>> {
-        let mut cursor = self.tx.cursor_read::<tables::Headers>()?;
-        cursor
-            .walk_range(range)?
-            .map(|result| result.map(|(_, header)| header).map_err(Into::into))
-            .collect::<RethResult<Vec<_>>>()
+        self.get_range_with_snapshot(
+            SnapshotSegment::Headers,
+            range,
+            |snapshot, range| snapshot.headers_range(range),
+            |range| {
+                let mut cursor = self.tx.cursor_read::<tables::Headers>()?;
+                cursor
+                    .walk_range(range)?
+                    .map(|result| result.map(|(_, header)| header).map_err(Into::into))
+                    .collect::<RethResult<Vec<_>>>()
+            },
+        )
     }
 
     fn sealed_headers_range(
         &self,
         range: impl RangeBounds<BlockNumber>,
     ) -> RethResult<Vec<SealedHeader>> {
-        let mut headers = vec![];
-        for entry in self.tx.cursor_read::<tables::Headers>()?.walk_range(range)? {
-            let (number, header) = entry?;
-            let hash = self
-                .block_hash(number)?
-                .ok_or_else(|| ProviderError::HeaderNotFound(number.into()))?;
-            headers.push(header.seal(hash));
-        }
-        Ok(headers)
+        self.get_range_with_snapshot(
+            SnapshotSegment::Headers,
+            range,
+            |snapshot, range| snapshot.sealed_headers_range(range),
+            |range| {
+                let mut headers = vec![];
+                for entry in self.tx.cursor_read::<tables::Headers>()?.walk_range(range)? {
+                    let (number, header) = entry?;
+                    let hash = self
+                        .block_hash(number)?
+                        .ok_or_else(|| ProviderError::HeaderNotFound(number.into()))?;
+                    headers.push(header.seal(hash));
+                }
+                Ok(headers)
+            },
+        )
     }
 
     fn sealed_header(&self, number: BlockNumber) -> RethResult<Option<SealedHeader>> {
@@ -926,7 +1017,12 @@ impl<TX: DbTx> HeaderProvider for DatabaseProvider<TX> {
 
 impl<TX: DbTx> BlockHashReader for DatabaseProvider<TX> {
     fn block_hash(&self, number: u64) -> RethResult<Option<B256>> {
-        Ok(self.tx.get::<tables::CanonicalHeaders>(number)?)
+        self.get_with_snapshot(
+            SnapshotSegment::Headers,
+            number,
+            |snapshot| snapshot.block_hash(number),
+            || Ok(self.tx.get::<tables::CanonicalHeaders>(number)?),
+        )
     }
 
     fn canonical_hashes_range(
@@ -934,12 +1030,18 @@ impl<TX: DbTx> BlockHashReader for DatabaseProvider<TX> {
         start: BlockNumber,
         end: BlockNumber,
     ) -> RethResult<Vec<B256>> {
-        let range = start..end;
-        let mut cursor = self.tx.cursor_read::<tables::CanonicalHeaders>()?;
-        cursor
-            .walk_range(range)?
-            .map(|result| result.map(|(_, hash)| hash).map_err(Into::into))
-            .collect::<RethResult<Vec<_>>>()
+        self.get_range_with_snapshot(
+            SnapshotSegment::Headers,
+            start..end,
+            |snapshot, range| snapshot.canonical_hashes_range(*range.start(), *range.end() + 1),
+            |range| {
+                let mut cursor = self.tx.cursor_read::<tables::CanonicalHeaders>()?;
+                cursor
+                    .walk_range(range)?
+                    .map(|result| result.map(|(_, hash)| hash).map_err(Into::into))
+                    .collect::<RethResult<Vec<_>>>()
+            },
+        )
     }
 }
 
@@ -1089,14 +1191,12 @@ impl<TX: DbTx> BlockReader for DatabaseProvider<TX> {
         let len = range.end().saturating_sub(*range.start()) as usize;
         let mut blocks = Vec::with_capacity(len);
 
-        let mut headers_cursor = self.tx.cursor_read::<tables::Headers>()?;
         let mut ommers_cursor = self.tx.cursor_read::<tables::BlockOmmers>()?;
         let mut withdrawals_cursor = self.tx.cursor_read::<tables::BlockWithdrawals>()?;
         let mut block_body_cursor = self.tx.cursor_read::<tables::BlockBodyIndices>()?;
-        let mut tx_cursor = self.tx.cursor_read::<tables::Transactions>()?;
 
         for num in range {
-            if let Some((_, header)) = headers_cursor.seek_exact(num)? {
+            if let Some(header) = self.header_by_number(num)? {
                 // If the body indices are not found, this means that the transactions either do
                 // not exist in the database yet, or they do exit but are
                 // not indexed. If they exist but are not indexed, we don't
@@ -1107,10 +1207,10 @@ impl<TX: DbTx> BlockReader for DatabaseProvider<TX> {
                     let body = if tx_range.is_empty() {
                         Vec::new()
                     } else {
-                        tx_cursor
-                            .walk_range(tx_range)?
-                            .map(|result| result.map(|(_, tx)| tx.into()))
-                            .collect::<Result<Vec<_>, _>>()?
+                        self.transactions_by_tx_range(tx_range)?
+                            .into_iter()
+                            .map(Into::into)
+                            .collect()
                     };
 
                     // If we are past shanghai, then all blocks should have a withdrawal list,
@@ -1146,14 +1246,19 @@ impl<TX: DbTx> TransactionsProvider for DatabaseProvider<TX> {
     }
 
     fn transaction_by_id(&self, id: TxNumber) -> RethResult<Option<TransactionSigned>> {
-        Ok(self.tx.get::<tables::Transactions>(id)?.map(Into::into))
+        Ok(self.transaction_by_id_no_hash(id)?.map(Into::into))
     }
 
     fn transaction_by_id_no_hash(
         &self,
         id: TxNumber,
     ) -> RethResult<Option<TransactionSignedNoHash>> {
-        Ok(self.tx.get::<tables::Transactions>(id)?)
+        self.get_with_snapshot(
+            SnapshotSegment::Transactions,
+            id,
+            |snapshot| snapshot.transaction_by_id_no_hash(id),
+            || Ok(self.tx.get::<tables::Transactions>(id)?),
+        )
     }
 
     fn transaction_by_hash(&self, hash: TxHash) -> RethResult<Option<TransactionSigned>> {
@@ -1221,17 +1326,17 @@ impl<TX: DbTx> TransactionsProvider for DatabaseProvider<TX> {
         &self,
         id: BlockHashOrNumber,
     ) -> RethResult<Option<Vec<TransactionSigned>>> {
-        let mut tx_cursor = self.tx.cursor_read::<tables::Transactions>()?;
         if let Some(block_number) = self.convert_hash_or_number(id)? {
             if let Some(body) = self.block_body_indices(block_number)? {
                 let tx_range = body.tx_num_range();
                 return if tx_range.is_empty() {
                     Ok(Some(Vec::new()))
                 } else {
-                    let transactions = tx_cursor
-                        .walk_range(tx_range)?
-                        .map(|result| result.map(|(_, tx)| tx.into()))
-                        .collect::<Result<Vec<_>, _>>()?;
+                    let transactions = self
+                        .transactions_by_tx_range(tx_range)?
+                        .into_iter()
+                        .map(Into::into)
+                        .collect();
                     Ok(Some(transactions))
                 }
             }
@@ -1245,7 +1350,6 @@ impl<TX: DbTx> TransactionsProvider for DatabaseProvider<TX> {
     ) -> RethResult<Vec<Vec<TransactionSigned>>> {
         let mut results = Vec::new();
         let mut body_cursor = self.tx.cursor_read::<tables::BlockBodyIndices>()?;
-        let mut tx_cursor = self.tx.cursor_read::<tables::Transactions>()?;
         for entry in body_cursor.walk_range(range)? {
             let (_, body) = entry?;
             let tx_num_range = body.tx_num_range();
@@ -1253,10 +1357,10 @@ impl<TX: DbTx> TransactionsProvider for DatabaseProvider<TX> {
                 results.push(Vec::new());
             } else {
                 results.push(
-                    tx_cursor
-                        .walk_range(tx_num_range)?
-                        .map(|result| result.map(|(_, tx)| tx.into()))
-                        .collect::<Result<Vec<_>, _>>()?,
+                    self.transactions_by_tx_range(tx_num_range)?
+                        .into_iter()
+                        .map(Into::into)
+                        .collect(),
                 );
             }
         }
@@ -1267,12 +1371,19 @@ impl<TX: DbTx> TransactionsProvider for DatabaseProvider<TX> {
         &self,
         range: impl RangeBounds<TxNumber>,
     ) -> RethResult<Vec<TransactionSignedNoHash>> {
-        Ok(self
-            .tx
-            .cursor_read::<tables::Transactions>()?
-            .walk_range(range)?
-            .map(|entry| entry.map(|tx| tx.1))
-            .collect::<Result<Vec<_>, _>>()?)
+        self.get_range_with_snapshot(
+            SnapshotSegment::Transactions,
+            range,
+            |snapshot, range| snapshot.transactions_by_tx_range(range),
+            |range| {
+                Ok(self
+                    .tx
+                    .cursor_read::<tables::Transactions>()?
+                    .walk_range(range)?
+                    .map(|entry| entry.map(|tx| tx.1))
+                    .collect::<Result<Vec<_>, _>>()?)
+            },
+        )
     }
 
     fn senders_by_tx_range(&self, range: impl RangeBounds<TxNumber>) -> RethResult<Vec<Address>> {
@@ -1291,7 +1402,12 @@ impl<TX: DbTx> TransactionsProvider for DatabaseProvider<TX> {
 
 impl<TX: DbTx> ReceiptProvider for DatabaseProvider<TX> {
     fn receipt(&self, id: TxNumber) -> RethResult<Option<Receipt>> {
-        Ok(self.tx.get::<tables::Receipts>(id)?)
+        self.get_with_snapshot(
+            SnapshotSegment::Receipts,
+            id,
+            |snapshot| snapshot.receipt(id),
+            || Ok(self.tx.get::<tables::Receipts>(id)?),
+        )
     }
 
     fn receipt_by_hash(&self, hash: TxHash) -> RethResult<Option<Receipt>> {
@@ -1309,11 +1425,18 @@ impl<TX: DbTx> ReceiptProvider for DatabaseProvider<TX> {
                 return if tx_range.is_empty() {
                     Ok(Some(Vec::new()))
                 } else {
-                    let mut receipts_cursor = self.tx.cursor_read::<tables::Receipts>()?;
-                    let receipts = receipts_cursor
-                        .walk_range(tx_range)?
-                        .map(|result| result.map(|(_, receipt)| receipt))
-                        .collect::<Result<Vec<_>, _>>()?;
+                    let receipts = self.get_range_with_snapshot(
+                        SnapshotSegment::Receipts,
+                        tx_range,
+                        |snapshot, range| snapshot.receipts_by_tx_range(range),
+                        |range| {
+                            let mut receipts_cursor = self.tx.cursor_read::<tables::Receipts>()?;
+                            Ok(receipts_cursor
+                                .walk_range(range)?
+                                .map(|result| result.map(|(_, receipt)| receipt))
+                                .collect::<Result<Vec<_>, _>>()?)
+                        },
+                    )?;
                     Ok(Some(receipts))
                 }
             }
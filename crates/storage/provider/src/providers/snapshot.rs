@@ -1,17 +1,85 @@
-use crate::HeaderProvider;
+use crate::{
+    BlockHashReader, BlockNumReader, HeaderProvider, ReceiptProvider, TransactionsProvider,
+};
 use reth_db::{
     table::{Decompress, Table},
-    HeaderTD,
+    CanonicalHeaders, HeaderTD,
+};
+use reth_interfaces::{provider::ProviderError, RethError, RethResult};
+use reth_nippy_jar::{
+    compression::{Compressors, Decompressor},
+    NippyJar, NippyJarCursor,
+};
+use reth_primitives::{
+    Address, BlockHash, BlockHashOrNumber, BlockNumber, ChainInfo, Header, Receipt, SealedHeader,
+    SnapshotSegment, TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber,
+    B256, U256,
 };
-use reth_interfaces::{provider::ProviderError, RethResult};
-use reth_nippy_jar::{compression::Decompressor, NippyJar, NippyJarCursor};
-use reth_primitives::{BlockHash, BlockNumber, Header, SealedHeader, U256};
-use std::ops::RangeBounds;
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::{Bound, RangeBounds, RangeInclusive},
+    path::Path,
+};
+
+/// The snapshots a [`DatabaseProvider`](crate::DatabaseProvider) reads the rows they hold from,
+/// instead of the database.
+#[derive(Debug, Default)]
+pub struct Snapshots {
+    /// The snapshots of every segment, by their first row, with their last row. Rows are block
+    /// numbers for [`SnapshotSegment::Headers`], and transaction numbers for the other segments.
+    jars: HashMap<SnapshotSegment, BTreeMap<u64, (u64, NippyJar)>>,
+}
+
+impl Snapshots {
+    /// Loads the snapshot of `segment` at `path`, which holds the rows of `rows`.
+    ///
+    /// Snapshots compressed with dictionaries need decompressors that borrow them, so they can't
+    /// be read from here.
+    pub fn insert(
+        &mut self,
+        segment: SnapshotSegment,
+        rows: RangeInclusive<u64>,
+        path: &Path,
+    ) -> RethResult<()> {
+        let jar = NippyJar::load_without_header(path)?;
+        if let Some(Compressors::Zstd(zstd)) = jar.compressor() {
+            if zstd.use_dict {
+                return Err(RethError::Custom(format!(
+                    "snapshot {} is compressed with dictionaries",
+                    path.display()
+                )))
+            }
+        }
+        self.jars.entry(segment).or_default().insert(*rows.start(), (*rows.end(), jar));
+        Ok(())
+    }
+
+    /// Returns a provider of the snapshot of `segment` holding the row `number`, along with the
+    /// rows of the snapshot.
+    pub fn provider(
+        &self,
+        segment: SnapshotSegment,
+        number: u64,
+    ) -> Option<(SnapshotProvider<'_>, RangeInclusive<u64>)> {
+        let (&start, (end, jar)) = self.jars.get(&segment)?.range(..=number).next_back()?;
+        if number > *end {
+            return None
+        }
+        let provider = match segment {
+            SnapshotSegment::Headers => {
+                SnapshotProvider { jar, jar_start_block: start, jar_start_tx: 0 }
+            }
+            SnapshotSegment::Transactions | SnapshotSegment::Receipts => {
+                SnapshotProvider { jar, jar_start_block: 0, jar_start_tx: start }
+            }
+        };
+        Some((provider, start..=*end))
+    }
+}
 
 /// SnapshotProvider
 ///
 ///  WIP Rudimentary impl just for tests
-/// TODO: should be able to walk through snapshot files/block_ranges
 /// TODO: Arc over NippyJars and/or NippyJarCursors (LRU)
 #[derive(Debug)]
 pub struct SnapshotProvider<'a> {
@@ -19,6 +87,8 @@ pub struct SnapshotProvider<'a> {
     pub jar: &'a NippyJar,
     /// Starting snapshot block
     pub jar_start_block: u64,
+    /// Starting snapshot transaction, for the segments keyed by transaction number
+    pub jar_start_tx: u64,
 }
 
 impl<'a> SnapshotProvider<'a> {
@@ -34,70 +104,291 @@ impl<'a> SnapshotProvider<'a> {
     ) -> NippyJarCursor<'a> {
         NippyJarCursor::new(self.jar, Some(decompressors)).unwrap()
     }
-}
 
-impl<'a> HeaderProvider for SnapshotProvider<'a> {
-    fn header(&self, block_hash: &BlockHash) -> RethResult<Option<Header>> {
-        // WIP
-        let mut cursor = self.cursor();
+    /// Creates cursor, failing if the data file of the snapshot can't be opened.
+    fn try_cursor(&self) -> RethResult<NippyJarCursor<'a>> {
+        Ok(NippyJarCursor::new(self.jar, None)?)
+    }
 
-        let header = Header::decompress(
-            cursor.row_by_key_with_cols::<0b01, 2>(&block_hash.0).unwrap().unwrap()[0],
-        )
-        .unwrap();
+    /// Returns the value of the row `number` of the snapshot, whose first row has the number
+    /// `jar_start`, read with `read` from the index of the row.
+    fn value<T>(
+        &self,
+        number: u64,
+        jar_start: u64,
+        read: impl FnOnce(&mut NippyJarCursor<'a>, usize) -> RethResult<Option<T>>,
+    ) -> RethResult<Option<T>> {
+        // Rows before the snapshot aren't in it
+        let Some(row) = number.checked_sub(jar_start) else { return Ok(None) };
+        read(&mut self.try_cursor()?, row as usize)
+    }
 
-        if &header.hash_slow() == block_hash {
-            return Ok(Some(header))
-        } else {
-            // check next snapshot
+    /// Returns the values of the rows of `range` which are in the snapshot, whose first row has the
+    /// number `jar_start`. Every value is read with `read`, from the index of its row.
+    fn range_values<T>(
+        &self,
+        range: impl RangeBounds<u64>,
+        jar_start: u64,
+        mut read: impl FnMut(&mut NippyJarCursor<'a>, usize) -> RethResult<Option<T>>,
+    ) -> RethResult<Vec<T>> {
+        let mut number = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => jar_start,
         }
-        Ok(None)
+        .max(jar_start);
+        let end = match range.end_bound() {
+            Bound::Included(&end) => Some(end.saturating_add(1)),
+            Bound::Excluded(&end) => Some(end),
+            Bound::Unbounded => None,
+        };
+
+        let mut cursor = self.try_cursor()?;
+        let mut values = Vec::new();
+        while end.map_or(true, |end| number < end) {
+            let Some(value) = read(&mut cursor, (number - jar_start) as usize)? else { break };
+            values.push(value);
+            number += 1;
+        }
+        Ok(values)
+    }
+
+    /// Returns the receipts of the transactions of `range` which are in the snapshot.
+    pub fn receipts_by_tx_range(
+        &self,
+        range: impl RangeBounds<TxNumber>,
+    ) -> RethResult<Vec<Receipt>> {
+        self.range_values(range, self.jar_start_tx, |cursor, row| {
+            Ok(cursor
+                .row_by_number_with_cols::<0b1, 1>(row)?
+                .map(|row| Receipt::decompress(row[0]))
+                .transpose()?)
+        })
+    }
+}
+
+impl<'a> HeaderProvider for SnapshotProvider<'a> {
+    fn header(&self, block_hash: &BlockHash) -> RethResult<Option<Header>> {
+        let mut cursor = self.try_cursor()?;
+        let Some(row) = cursor.row_by_key_with_cols::<0b01, 2>(&block_hash.0)? else {
+            return Ok(None)
+        };
+        let header = Header::decompress(row[0])?;
+
+        // Might be a false positive, or in another snapshot
+        Ok((&header.hash_slow() == block_hash).then_some(header))
     }
 
     fn header_by_number(&self, num: BlockNumber) -> RethResult<Option<Header>> {
-        Header::decompress(
-            self.cursor()
-                .row_by_number_with_cols::<0b01, 2>((num - self.jar_start_block) as usize)?
-                .ok_or(ProviderError::HeaderNotFound(num.into()))?[0],
-        )
-        .map(Some)
-        .map_err(Into::into)
+        self.value(num, self.jar_start_block, |cursor, row| {
+            Ok(cursor
+                .row_by_number_with_cols::<0b01, 2>(row)?
+                .map(|row| Header::decompress(row[0]))
+                .transpose()?)
+        })
     }
 
     fn header_td(&self, block_hash: &BlockHash) -> RethResult<Option<U256>> {
-        // WIP
-        let mut cursor = self.cursor();
+        let mut cursor = self.try_cursor()?;
+        let Some(row) = cursor.row_by_key_with_cols::<0b11, 2>(&block_hash.0)? else {
+            return Ok(None)
+        };
+        let header = Header::decompress(row[0])?;
+        let td = <HeaderTD as Table>::Value::decompress(row[1])?;
+
+        // Might be a false positive, or in another snapshot
+        Ok((&header.hash_slow() == block_hash).then_some(td.0))
+    }
 
-        let row = cursor.row_by_key_with_cols::<0b11, 2>(&block_hash.0).unwrap().unwrap();
+    fn header_td_by_number(&self, number: BlockNumber) -> RethResult<Option<U256>> {
+        self.value(number, self.jar_start_block, |cursor, row| {
+            Ok(cursor
+                .row_by_number_with_cols::<0b010, 3>(row)?
+                .map(|row| <HeaderTD as Table>::Value::decompress(row[0]))
+                .transpose()?
+                .map(|td| td.0))
+        })
+    }
 
-        let header = Header::decompress(row[0]).unwrap();
-        let td = <HeaderTD as Table>::Value::decompress(row[1]).unwrap();
+    fn headers_range(&self, range: impl RangeBounds<BlockNumber>) -> RethResult<Vec<Header>> {
+        self.range_values(range, self.jar_start_block, |cursor, row| {
+            Ok(cursor
+                .row_by_number_with_cols::<0b001, 3>(row)?
+                .map(|row| Header::decompress(row[0]))
+                .transpose()?)
+        })
+    }
 
-        if &header.hash_slow() == block_hash {
-            return Ok(Some(td.0))
-        } else {
-            // check next snapshot
-        }
-        Ok(None)
+    fn sealed_headers_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> RethResult<Vec<SealedHeader>> {
+        self.range_values(range, self.jar_start_block, |cursor, row| {
+            cursor
+                .row_by_number_with_cols::<0b101, 3>(row)?
+                .map(|row| -> RethResult<_> {
+                    let hash = <CanonicalHeaders as Table>::Value::decompress(row[1])?;
+                    Ok(Header::decompress(row[0])?.seal(hash))
+                })
+                .transpose()
+        })
     }
 
-    fn header_td_by_number(&self, _number: BlockNumber) -> RethResult<Option<U256>> {
-        unimplemented!();
+    fn sealed_header(&self, number: BlockNumber) -> RethResult<Option<SealedHeader>> {
+        Ok(self.sealed_headers_range(number..=number)?.pop())
     }
+}
 
-    fn headers_range(&self, _range: impl RangeBounds<BlockNumber>) -> RethResult<Vec<Header>> {
-        unimplemented!();
+impl<'a> BlockHashReader for SnapshotProvider<'a> {
+    fn block_hash(&self, number: u64) -> RethResult<Option<B256>> {
+        self.value(number, self.jar_start_block, |cursor, row| {
+            Ok(cursor
+                .row_by_number_with_cols::<0b100, 3>(row)?
+                .map(|row| <CanonicalHeaders as Table>::Value::decompress(row[0]))
+                .transpose()?)
+        })
     }
 
-    fn sealed_headers_range(
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> RethResult<Vec<B256>> {
+        self.range_values(start..end, self.jar_start_block, |cursor, row| {
+            Ok(cursor
+                .row_by_number_with_cols::<0b100, 3>(row)?
+                .map(|row| <CanonicalHeaders as Table>::Value::decompress(row[0]))
+                .transpose()?)
+        })
+    }
+}
+
+impl<'a> BlockNumReader for SnapshotProvider<'a> {
+    fn chain_info(&self) -> RethResult<ChainInfo> {
+        // Information on live database
+        Err(ProviderError::UnsupportedProvider.into())
+    }
+
+    fn best_block_number(&self) -> RethResult<BlockNumber> {
+        // Information on live database
+        Err(ProviderError::UnsupportedProvider.into())
+    }
+
+    fn last_block_number(&self) -> RethResult<BlockNumber> {
+        // Information on live database
+        Err(ProviderError::UnsupportedProvider.into())
+    }
+
+    fn block_number(&self, _hash: B256) -> RethResult<Option<BlockNumber>> {
+        // Information on indexing table [`tables::HeaderNumbers`]
+        Err(ProviderError::UnsupportedProvider.into())
+    }
+}
+
+impl<'a> TransactionsProvider for SnapshotProvider<'a> {
+    fn transaction_id(&self, _tx_hash: TxHash) -> RethResult<Option<TxNumber>> {
+        // Information on indexing table [`tables::TxHashNumber`]
+        Err(ProviderError::UnsupportedProvider.into())
+    }
+
+    fn transaction_by_id(&self, id: TxNumber) -> RethResult<Option<TransactionSigned>> {
+        Ok(self.transaction_by_id_no_hash(id)?.map(|tx| tx.with_hash()))
+    }
+
+    fn transaction_by_id_no_hash(
+        &self,
+        id: TxNumber,
+    ) -> RethResult<Option<TransactionSignedNoHash>> {
+        self.value(id, self.jar_start_tx, |cursor, row| {
+            Ok(cursor
+                .row_by_number_with_cols::<0b1, 1>(row)?
+                .map(|row| TransactionSignedNoHash::decompress(row[0]))
+                .transpose()?)
+        })
+    }
+
+    fn transaction_by_hash(&self, hash: TxHash) -> RethResult<Option<TransactionSigned>> {
+        let mut cursor = self.try_cursor()?;
+        let Some(row) = cursor.row_by_key_with_cols::<0b1, 1>(hash.as_slice())? else {
+            return Ok(None)
+        };
+        let tx = TransactionSignedNoHash::decompress(row[0])?.with_hash();
+
+        // Might be a false positive, or in another snapshot
+        Ok((tx.hash() == hash).then_some(tx))
+    }
+
+    fn transaction_by_hash_with_meta(
+        &self,
+        _hash: TxHash,
+    ) -> RethResult<Option<(TransactionSigned, TransactionMeta)>> {
+        // Information required on indexing table [`tables::TransactionBlock`]
+        Err(ProviderError::UnsupportedProvider.into())
+    }
+
+    fn transaction_block(&self, _id: TxNumber) -> RethResult<Option<BlockNumber>> {
+        // Information on indexing table [`tables::TransactionBlock`]
+        Err(ProviderError::UnsupportedProvider.into())
+    }
+
+    fn transactions_by_block(
+        &self,
+        _block_id: BlockHashOrNumber,
+    ) -> RethResult<Option<Vec<TransactionSigned>>> {
+        // Information on indexing table [`tables::BlockBodyIndices`]
+        Err(ProviderError::UnsupportedProvider.into())
+    }
+
+    fn transactions_by_block_range(
         &self,
         _range: impl RangeBounds<BlockNumber>,
-    ) -> RethResult<Vec<SealedHeader>> {
-        unimplemented!();
+    ) -> RethResult<Vec<Vec<TransactionSigned>>> {
+        // Information on indexing table [`tables::BlockBodyIndices`]
+        Err(ProviderError::UnsupportedProvider.into())
     }
 
-    fn sealed_header(&self, _number: BlockNumber) -> RethResult<Option<SealedHeader>> {
-        unimplemented!();
+    fn transactions_by_tx_range(
+        &self,
+        range: impl RangeBounds<TxNumber>,
+    ) -> RethResult<Vec<TransactionSignedNoHash>> {
+        self.range_values(range, self.jar_start_tx, |cursor, row| {
+            Ok(cursor
+                .row_by_number_with_cols::<0b1, 1>(row)?
+                .map(|row| TransactionSignedNoHash::decompress(row[0]))
+                .transpose()?)
+        })
+    }
+
+    fn senders_by_tx_range(&self, _range: impl RangeBounds<TxNumber>) -> RethResult<Vec<Address>> {
+        // Information on table [`tables::TxSenders`]
+        Err(ProviderError::UnsupportedProvider.into())
+    }
+
+    fn transaction_sender(&self, _id: TxNumber) -> RethResult<Option<Address>> {
+        // Information on table [`tables::TxSenders`]
+        Err(ProviderError::UnsupportedProvider.into())
+    }
+}
+
+impl<'a> ReceiptProvider for SnapshotProvider<'a> {
+    fn receipt(&self, id: TxNumber) -> RethResult<Option<Receipt>> {
+        self.value(id, self.jar_start_tx, |cursor, row| {
+            Ok(cursor
+                .row_by_number_with_cols::<0b1, 1>(row)?
+                .map(|row| Receipt::decompress(row[0]))
+                .transpose()?)
+        })
+    }
+
+    fn receipt_by_hash(&self, _hash: TxHash) -> RethResult<Option<Receipt>> {
+        // Receipts don't hold the hash of their transaction, so a false positive can only be ruled
+        // out with the transactions snapshot
+        Err(ProviderError::UnsupportedProvider.into())
+    }
+
+    fn receipts_by_block(&self, _block: BlockHashOrNumber) -> RethResult<Option<Vec<Receipt>>> {
+        // Information on indexing table [`tables::BlockBodyIndices`]
+        Err(ProviderError::UnsupportedProvider.into())
     }
 }
 
@@ -109,14 +400,16 @@ mod test {
     use reth_db::{
         cursor::DbCursorRO,
         database::Database,
-        snapshot::create_snapshot_T1_T2,
+        snapshot::{create_snapshot_T1, create_snapshot_T1_T2_T3},
         test_utils::create_test_rw_db,
         transaction::{DbTx, DbTxMut},
-        CanonicalHeaders, DatabaseError, HeaderNumbers, HeaderTD, Headers, RawTable,
+        CanonicalHeaders, DatabaseError, HeaderNumbers, HeaderTD, Headers, RawKey, RawTable,
+        Transactions,
     };
-    use reth_interfaces::test_utils::generators::{self, random_header_range};
+    use reth_interfaces::test_utils::generators::{self, random_header_range, random_signed_tx};
     use reth_nippy_jar::NippyJar;
-    use reth_primitives::{B256, MAINNET};
+    use reth_primitives::MAINNET;
+    use std::sync::Arc;
 
     #[test]
     fn test_snap() {
@@ -157,7 +450,7 @@ mod test {
             let with_compression = true;
             let with_filter = true;
 
-            let mut nippy_jar = NippyJar::new_without_header(2, snap_file.path());
+            let mut nippy_jar = NippyJar::new_without_header(3, snap_file.path());
 
             if with_compression {
                 nippy_jar = nippy_jar.with_zstd(false, 0);
@@ -180,7 +473,7 @@ mod test {
                 .unwrap()
                 .map(|row| row.map(|(_key, value)| value.into_value()).map_err(|e| e.into()));
 
-            create_snapshot_T1_T2::<Headers, HeaderTD, BlockNumber>(
+            create_snapshot_T1_T2_T3::<Headers, HeaderTD, CanonicalHeaders, BlockNumber>(
                 &tx,
                 range,
                 None,
@@ -197,10 +490,21 @@ mod test {
             let jar = NippyJar::load_without_header(snap_file.path()).unwrap();
 
             let db_provider = factory.provider().unwrap();
-            let snap_provider = SnapshotProvider { jar: &jar, jar_start_block: 0 };
+            let snap_provider = SnapshotProvider { jar: &jar, jar_start_block: 0, jar_start_tx: 0 };
 
             assert!(!headers.is_empty());
 
+            // Compare the ranges, in order
+            assert_eq!(
+                db_provider.sealed_headers_range(10..20).unwrap(),
+                snap_provider.sealed_headers_range(10..20).unwrap()
+            );
+            assert_eq!(
+                db_provider.canonical_hashes_range(0, row_count).unwrap(),
+                snap_provider.canonical_hashes_range(0, row_count).unwrap()
+            );
+            assert_eq!(snap_provider.headers_range(row_count - 1..).unwrap().len(), 1);
+
             // Shuffled for chaos.
             headers.shuffle(&mut generators::rng());
 
@@ -217,7 +521,170 @@ mod test {
                     db_provider.header_td(&header_hash).unwrap().unwrap(),
                     snap_provider.header_td(&header_hash).unwrap().unwrap()
                 );
+                assert_eq!(
+                    db_provider.header_td_by_number(header.number).unwrap(),
+                    snap_provider.header_td_by_number(header.number).unwrap()
+                );
+
+                // Compare SealedHeader
+                assert_eq!(
+                    snap_provider.sealed_header(header.number).unwrap(),
+                    Some(header.seal(header_hash))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_snap_transactions() {
+        let db = create_test_rw_db();
+        let snap_file = tempfile::NamedTempFile::new().unwrap();
+
+        // Setup data, with the first transaction of the snapshot after the first of the database
+        let mut rng = generators::rng();
+        let transactions = (0..20).map(|_| random_signed_tx(&mut rng)).collect::<Vec<_>>();
+        let jar_start_tx = 5;
+        let range = jar_start_tx..=(transactions.len() as u64 - 1);
+
+        db.update(|tx| -> Result<(), DatabaseError> {
+            for (id, transaction) in transactions.iter().enumerate() {
+                tx.put::<Transactions>(
+                    id as u64,
+                    TransactionSignedNoHash {
+                        signature: transaction.signature,
+                        transaction: transaction.transaction.clone(),
+                    },
+                )?;
             }
+            Ok(())
+        })
+        .unwrap()
+        .unwrap();
+
+        // Create Snapshot
+        {
+            let row_count = range.clone().count();
+            let mut nippy_jar = NippyJar::new_without_header(1, snap_file.path())
+                .with_zstd(false, 0)
+                .with_cuckoo_filter(row_count)
+                .with_fmph();
+
+            let tx = db.tx().unwrap();
+            let mut cursor = tx.cursor_read::<Transactions>().unwrap();
+            let hashes = cursor
+                .walk_range(range.clone())
+                .unwrap()
+                .map(|row| row.map(|(_key, tx)| tx.hash()).map_err(|e| e.into()));
+
+            create_snapshot_T1::<Transactions, TxNumber>(
+                &tx,
+                range.clone(),
+                None,
+                None::<Vec<std::vec::IntoIter<Vec<u8>>>>,
+                Some(hashes),
+                row_count,
+                &mut nippy_jar,
+            )
+            .unwrap();
         }
+
+        // Query the snapshot by number and by hash
+        let jar = NippyJar::load_without_header(snap_file.path()).unwrap();
+        let snap_provider = SnapshotProvider { jar: &jar, jar_start_block: 0, jar_start_tx };
+
+        for (id, transaction) in transactions.iter().enumerate().skip(jar_start_tx as usize) {
+            assert_eq!(
+                snap_provider.transaction_by_id(id as u64).unwrap().as_ref(),
+                Some(transaction)
+            );
+            assert_eq!(
+                snap_provider.transaction_by_hash(transaction.hash()).unwrap().as_ref(),
+                Some(transaction)
+            );
+        }
+        assert_eq!(
+            snap_provider.transactions_by_tx_range(..).unwrap().len(),
+            transactions.len() - jar_start_tx as usize
+        );
+
+        // Transactions before the snapshot aren't found by their hash
+        assert_eq!(snap_provider.transaction_by_hash(transactions[0].hash()).unwrap(), None);
+        assert_eq!(snap_provider.transaction_by_id(0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_snap_routing() {
+        let db = create_test_rw_db();
+        let snap_file = tempfile::NamedTempFile::new().unwrap();
+
+        // Setup data, with the first half of the headers moved to a snapshot
+        let headers = random_header_range(&mut generators::rng(), 0..20, B256::random());
+        let snapshot_range = 0..=9;
+
+        db.update(|tx| -> Result<(), DatabaseError> {
+            for header in headers.clone() {
+                tx.put::<CanonicalHeaders>(header.number, header.hash())?;
+                tx.put::<Headers>(header.number, header.clone().unseal())?;
+                tx.put::<HeaderTD>(header.number, U256::ZERO.into())?;
+            }
+            Ok(())
+        })
+        .unwrap()
+        .unwrap();
+
+        {
+            let row_count = snapshot_range.clone().count();
+            let mut nippy_jar = NippyJar::new_without_header(3, snap_file.path())
+                .with_zstd(false, 0)
+                .with_cuckoo_filter(row_count)
+                .with_fmph();
+
+            let tx = db.tx().unwrap();
+            let mut cursor = tx.cursor_read::<RawTable<CanonicalHeaders>>().unwrap();
+            let hashes = cursor
+                .walk_range(RawKey::from(0)..=RawKey::from(*snapshot_range.end()))
+                .unwrap()
+                .map(|row| row.map(|(_key, value)| value.into_value()).map_err(|e| e.into()));
+
+            create_snapshot_T1_T2_T3::<Headers, HeaderTD, CanonicalHeaders, BlockNumber>(
+                &tx,
+                snapshot_range.clone(),
+                None,
+                None::<Vec<std::vec::IntoIter<Vec<u8>>>>,
+                Some(hashes),
+                row_count,
+                &mut nippy_jar,
+            )
+            .unwrap();
+        }
+
+        db.update(|tx| -> Result<(), DatabaseError> {
+            for number in snapshot_range.clone() {
+                tx.delete::<CanonicalHeaders>(number, None)?;
+                tx.delete::<Headers>(number, None)?;
+            }
+            Ok(())
+        })
+        .unwrap()
+        .unwrap();
+
+        let mut snapshots = Snapshots::default();
+        snapshots.insert(SnapshotSegment::Headers, snapshot_range, snap_file.path()).unwrap();
+        assert!(snapshots.provider(SnapshotSegment::Headers, 10).is_none());
+        assert!(snapshots.provider(SnapshotSegment::Transactions, 0).is_none());
+
+        let factory =
+            ProviderFactory::new(&db, MAINNET.clone()).with_snapshots(Arc::new(snapshots));
+        let provider = factory.provider().unwrap();
+
+        // Rows are read from the snapshot, then from the database past its end
+        assert_eq!(provider.sealed_header(5).unwrap().as_ref(), Some(&headers[5]));
+        assert_eq!(provider.sealed_header(15).unwrap().as_ref(), Some(&headers[15]));
+        assert_eq!(provider.sealed_headers_range(5..15).unwrap(), headers[5..15].to_vec());
+        assert_eq!(provider.headers_range(..).unwrap().len(), headers.len());
+        assert_eq!(
+            provider.canonical_hashes_range(0, 20).unwrap(),
+            headers.iter().map(|header| header.hash()).collect::<Vec<_>>()
+        );
     }
 }
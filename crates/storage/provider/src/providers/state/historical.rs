@@ -10,10 +10,11 @@ use reth_db::{
     transaction::DbTx,
     BlockNumberList,
 };
-use reth_interfaces::RethResult;
+use reth_interfaces::{RethError, RethResult};
 use reth_primitives::{
     trie::AccountProof, Account, Address, BlockNumber, Bytecode, StorageKey, StorageValue, B256,
 };
+use reth_trie::{hashed_cursor::HashedPostState, proof::Proof};
 
 /// State provider for a given block number which takes a tx reference.
 ///
@@ -63,7 +64,7 @@ impl<'b, TX: DbTx> HistoricalStateProviderRef<'b, TX> {
     /// Lookup an account in the AccountHistory table
     pub fn account_history_lookup(&self, address: Address) -> RethResult<HistoryInfo> {
         if !self.lowest_available_blocks.is_account_history_available(self.block_number) {
-            return Err(ProviderError::StateAtBlockPruned(self.block_number).into())
+            return Err(self.state_pruned_error())
         }
 
         // history key to search IntegerList of block number changesets.
@@ -82,7 +83,7 @@ impl<'b, TX: DbTx> HistoricalStateProviderRef<'b, TX> {
         storage_key: StorageKey,
     ) -> RethResult<HistoryInfo> {
         if !self.lowest_available_blocks.is_storage_history_available(self.block_number) {
-            return Err(ProviderError::StateAtBlockPruned(self.block_number).into())
+            return Err(self.state_pruned_error())
         }
 
         // history key to search IntegerList of block number changesets.
@@ -94,6 +95,15 @@ impl<'b, TX: DbTx> HistoricalStateProviderRef<'b, TX> {
         )
     }
 
+    /// The error of a lookup of the state at a block whose history was pruned.
+    fn state_pruned_error(&self) -> RethError {
+        ProviderError::StateAtBlockPruned {
+            block_number: self.block_number,
+            lowest_available: self.lowest_available_blocks.state_block_number().unwrap_or_default(),
+        }
+        .into()
+    }
+
     fn history_info<T, K>(
         &self,
         key: K,
@@ -240,8 +250,20 @@ impl<'b, TX: DbTx> StateProvider for HistoricalStateProviderRef<'b, TX> {
     }
 
     /// Get account and storage proofs.
-    fn proof(&self, _address: Address, _keys: &[B256]) -> RethResult<AccountProof> {
-        Err(ProviderError::StateRootNotAvailableForHistoricalBlock.into())
+    ///
+    /// The proofs are generated from the latest trie, with the changes of the blocks from the
+    /// block number on reverted. Fails if the changesets of these blocks were pruned.
+    fn proof(&self, address: Address, slots: &[B256]) -> RethResult<AccountProof> {
+        if !self.lowest_available_blocks.is_account_history_available(self.block_number) ||
+            !self.lowest_available_blocks.is_storage_history_available(self.block_number)
+        {
+            return Err(self.state_pruned_error())
+        }
+
+        let reverts = HashedPostState::from_reverts(self.tx, self.block_number)?;
+        Proof::overlay(self.tx, &reverts)
+            .account_proof(address, slots)
+            .map_err(|err| RethError::Database(err.into()))
     }
 }
 
@@ -321,6 +343,12 @@ impl LowestAvailableBlocks {
     pub fn is_storage_history_available(&self, at: BlockNumber) -> bool {
         self.storage_history_block_number.map(|block_number| block_number <= at).unwrap_or(true)
     }
+
+    /// Returns the lowest block number at which both the account and storage histories are
+    /// available, [Option::None] if all history is available.
+    pub fn state_block_number(&self) -> Option<BlockNumber> {
+        self.account_history_block_number.max(self.storage_history_block_number)
+    }
 }
 
 #[cfg(test)]
@@ -564,14 +592,9 @@ mod tests {
                 storage_history_block_number: Some(3),
             },
         );
-        assert_eq!(
-            provider.account_history_lookup(ADDRESS),
-            Err(ProviderError::StateAtBlockPruned(provider.block_number).into())
-        );
-        assert_eq!(
-            provider.storage_history_lookup(ADDRESS, STORAGE),
-            Err(ProviderError::StateAtBlockPruned(provider.block_number).into())
-        );
+        let pruned = ProviderError::StateAtBlockPruned { block_number: 2, lowest_available: 3 };
+        assert_eq!(provider.account_history_lookup(ADDRESS), Err(pruned.clone().into()));
+        assert_eq!(provider.storage_history_lookup(ADDRESS, STORAGE), Err(pruned.into()));
 
         // provider block_number == lowest available block number,
         // i.e. state at provider block is available
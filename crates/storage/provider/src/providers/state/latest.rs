@@ -7,11 +7,11 @@ use reth_db::{
     tables,
     transaction::DbTx,
 };
-use reth_interfaces::{provider::ProviderError, RethError, RethResult};
+use reth_interfaces::{RethError, RethResult};
 use reth_primitives::{
-    keccak256, trie::AccountProof, Account, Address, BlockNumber, Bytecode, StorageKey,
-    StorageValue, B256,
+    trie::AccountProof, Account, Address, BlockNumber, Bytecode, StorageKey, StorageValue, B256,
 };
+use reth_trie::proof::Proof;
 
 /// State provider over latest state that takes tx reference.
 #[derive(Debug)]
@@ -85,17 +85,10 @@ impl<'b, TX: DbTx> StateProvider for LatestStateProviderRef<'b, TX> {
         self.db.get::<tables::Bytecodes>(code_hash).map_err(Into::into)
     }
 
-    fn proof(&self, address: Address, _keys: &[B256]) -> RethResult<AccountProof> {
-        let _hashed_address = keccak256(address);
-        let _root = self
-            .db
-            .cursor_read::<tables::Headers>()?
-            .last()?
-            .ok_or_else(|| ProviderError::HeaderNotFound(0.into()))?
-            .1
-            .state_root;
-
-        unimplemented!()
+    fn proof(&self, address: Address, slots: &[B256]) -> RethResult<AccountProof> {
+        Proof::new(self.db)
+            .account_proof(address, slots)
+            .map_err(|err| RethError::Database(err.into()))
     }
 }
 
@@ -21,7 +21,11 @@ use rolling_file::{RollingConditionBasic, RollingFileAppender};
 use std::path::Path;
 use tracing::Subscriber;
 use tracing_subscriber::{
-    filter::Directive, prelude::*, registry::LookupSpan, EnvFilter, Layer, Registry,
+    filter::{Directive, ParseError},
+    fmt::MakeWriter,
+    prelude::*,
+    registry::LookupSpan,
+    EnvFilter, Layer, Registry,
 };
 
 // Re-export tracing crates
@@ -31,18 +35,53 @@ pub use tracing_subscriber;
 /// A boxed tracing [Layer].
 pub type BoxedLayer<S> = Box<dyn Layer<S> + Send + Sync>;
 
+/// The format of the events written by a layer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human readable lines.
+    #[default]
+    Terminal,
+    /// A JSON object per line, with the fields of the event and its current span.
+    Json,
+}
+
 /// Initializes a new [Subscriber] based on the given layers.
 pub fn init(layers: Vec<BoxedLayer<Registry>>) {
     tracing_subscriber::registry().with(layers).init();
 }
 
+/// Builds the filter of the [`stdout`] and [`stderr`] layers.
+///
+/// The events are filtered by `default_directive`, unless overridden by `RUST_LOG`, and then by
+/// `directives`, e.g. `reth::stages=debug,reth::network=warn` to change the level of some
+/// targets.
+pub fn env_filter(
+    default_directive: impl Into<Directive>,
+    directives: impl IntoIterator<Item = Directive>,
+) -> EnvFilter {
+    directives.into_iter().fold(
+        EnvFilter::builder().with_default_directive(default_directive.into()).from_env_lossy(),
+        |filter, directive| filter.add_directive(directive),
+    )
+}
+
+/// Parses comma-separated filter directives, like the ones of `RUST_LOG`.
+pub fn parse_directives(directives: &str) -> Result<Vec<Directive>, ParseError> {
+    directives
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
 /// Builds a new tracing layer that writes to stdout.
 ///
-/// The events are filtered by `default_directive`, unless overridden by `RUST_LOG`.
+/// The events are filtered by `filter`, see [`env_filter`].
 ///
 /// Colors can be disabled with `RUST_LOG_STYLE=never`, and event targets can be displayed with
-/// `RUST_LOG_TARGET=1`.
-pub fn stdout<S>(default_directive: impl Into<Directive>, color: &str) -> BoxedLayer<S>
+/// `RUST_LOG_TARGET=1`. JSON events are never colored.
+pub fn stdout<S>(filter: EnvFilter, color: &str, format: LogFormat) -> BoxedLayer<S>
 where
     S: Subscriber,
     for<'a> S: LookupSpan<'a>,
@@ -52,19 +91,59 @@ where
         std::env::var("RUST_LOG_STYLE").map(|val| val != "never").unwrap_or(color != "never");
     let with_target = std::env::var("RUST_LOG_TARGET").map(|val| val != "0").unwrap_or(true);
 
-    let filter =
-        EnvFilter::builder().with_default_directive(default_directive.into()).from_env_lossy();
+    fmt_layer(format, with_ansi, with_target, std::io::stdout, filter)
+}
+
+/// Builds a new tracing layer that writes to stderr, e.g. to keep stdout for the output of a
+/// command.
+///
+/// The events are filtered and formatted like the ones of [`stdout`].
+pub fn stderr<S>(filter: EnvFilter, color: &str, format: LogFormat) -> BoxedLayer<S>
+where
+    S: Subscriber,
+    for<'a> S: LookupSpan<'a>,
+{
+    let with_ansi =
+        std::env::var("RUST_LOG_STYLE").map(|val| val != "never").unwrap_or(color != "never");
+    let with_target = std::env::var("RUST_LOG_TARGET").map(|val| val != "0").unwrap_or(true);
+
+    fmt_layer(format, with_ansi, with_target, std::io::stderr, filter)
+}
 
-    tracing_subscriber::fmt::layer()
-        .with_ansi(with_ansi)
-        .with_target(with_target)
-        .with_filter(filter)
-        .boxed()
+/// Builds a layer writing the events in `format` to `writer`.
+fn fmt_layer<S, W>(
+    format: LogFormat,
+    with_ansi: bool,
+    with_target: bool,
+    writer: W,
+    filter: EnvFilter,
+) -> BoxedLayer<S>
+where
+    S: Subscriber,
+    for<'a> S: LookupSpan<'a>,
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Terminal => tracing_subscriber::fmt::layer()
+            .with_ansi(with_ansi)
+            .with_target(with_target)
+            .with_writer(writer)
+            .with_filter(filter)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .with_target(with_target)
+            .with_writer(writer)
+            .with_filter(filter)
+            .boxed(),
+    }
 }
 
 /// Builds a new tracing layer that appends to a log file.
 ///
-/// The events are filtered by `filter`.
+/// The events are filtered by `filter`, and written in `format`.
 ///
 /// The boxed layer and a guard is returned. When the guard is dropped the buffer for the log
 /// file is immediately flushed to disk. Any events after the guard is dropped may be missed.
@@ -75,6 +154,7 @@ pub fn file<S>(
     file_name: impl AsRef<Path>,
     max_size_bytes: u64,
     max_files: usize,
+    format: LogFormat,
 ) -> (BoxedLayer<S>, tracing_appender::non_blocking::WorkerGuard)
 where
     S: Subscriber,
@@ -95,11 +175,7 @@ where
         )
         .expect("Could not initialize file logging"),
     );
-    let layer = tracing_subscriber::fmt::layer()
-        .with_ansi(false)
-        .with_writer(writer)
-        .with_filter(filter)
-        .boxed();
+    let layer = fmt_layer(format, false, true, writer, filter);
 
     (layer, guard)
 }
@@ -136,3 +212,19 @@ pub fn init_test_tracing() {
         .with_writer(std::io::stderr)
         .try_init();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_target_directives() {
+        let directives = parse_directives("reth::stages=debug, reth::network=warn,").unwrap();
+        assert_eq!(
+            directives.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            ["reth::stages=debug", "reth::network=warn"]
+        );
+        assert!(parse_directives("").unwrap().is_empty());
+        assert!(parse_directives("reth::stages=loud").is_err());
+    }
+}
@@ -22,7 +22,7 @@ pub(crate) struct BlobTransactions<T: PoolTransaction> {
     /// This way we can determine when transactions were submitted to the pool.
     submission_id: u64,
     /// _All_ Transactions that are currently inside the pool grouped by their identifier.
-    by_id: BTreeMap<TransactionId, Arc<ValidPoolTransaction<T>>>,
+    by_id: BTreeMap<TransactionId, BlobTransaction<T>>,
     /// _All_ transactions sorted by blob priority.
     all: BTreeSet<BlobTransaction<T>>,
     /// Keeps track of the size of this pool.
@@ -53,10 +53,9 @@ impl<T: PoolTransaction> BlobTransactions<T> {
         // keep track of size
         self.size_of += tx.size();
 
-        self.by_id.insert(id, tx.clone());
-
         let ord = BlobOrd { submission_id };
         let transaction = BlobTransaction { ord, transaction: tx };
+        self.by_id.insert(id, transaction.clone());
         self.all.insert(transaction);
     }
 
@@ -67,14 +66,51 @@ impl<T: PoolTransaction> BlobTransactions<T> {
     ) -> Option<Arc<ValidPoolTransaction<T>>> {
         // remove from queues
         let tx = self.by_id.remove(id)?;
-
-        // TODO: remove from ordered set
-        // self.best.remove(&tx);
+        self.all.remove(&tx);
 
         // keep track of size
         self.size_of -= tx.transaction.size();
 
-        Some(tx)
+        Some(tx.transaction)
+    }
+
+    /// Removes all transactions that satisfy both the given base fee and blob fee, so that they can
+    /// be promoted.
+    ///
+    /// The descendants of a transaction that doesn't satisfy them are kept as well, since they
+    /// can't be executed before it.
+    ///
+    /// Note: the transactions are not returned in a particular order.
+    pub(crate) fn enforce_pending_fees(
+        &mut self,
+        base_fee: u64,
+        blob_fee: u128,
+    ) -> Vec<Arc<ValidPoolTransaction<T>>> {
+        let mut to_remove = Vec::new();
+        {
+            let mut iter = self.by_id.iter().peekable();
+            while let Some((id, tx)) = iter.next() {
+                let tx = &tx.transaction;
+                if tx.max_fee_per_gas() < base_fee as u128 ||
+                    tx.max_fee_per_blob_gas().unwrap_or_default() < blob_fee
+                {
+                    // still parked -> skip descendant transactions
+                    'this: while let Some((peek, _)) = iter.peek() {
+                        if peek.sender != id.sender {
+                            break 'this
+                        }
+                        iter.next();
+                    }
+                } else {
+                    to_remove.push(*id);
+                }
+            }
+        }
+
+        to_remove
+            .iter()
+            .map(|id| self.remove_transaction(id).expect("transaction exists"))
+            .collect()
     }
 
     /// Returns all transactions that satisfy the given basefee and blob_fee.
@@ -134,6 +170,12 @@ struct BlobTransaction<T: PoolTransaction> {
     ord: BlobOrd,
 }
 
+impl<T: PoolTransaction> Clone for BlobTransaction<T> {
+    fn clone(&self) -> Self {
+        Self { transaction: self.transaction.clone(), ord: self.ord.clone() }
+    }
+}
+
 impl<T: PoolTransaction> Eq for BlobTransaction<T> {}
 
 impl<T: PoolTransaction> PartialEq<Self> for BlobTransaction<T> {
@@ -154,7 +196,7 @@ impl<T: PoolTransaction> Ord for BlobTransaction<T> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct BlobOrd {
     /// Identifier that tags when transaction was submitted in the pool.
     pub(crate) submission_id: u64,
@@ -198,6 +198,41 @@ impl<T: TransactionOrdering> PendingPool<T> {
         removed
     }
 
+    /// Removes all blob transactions that don't satisfy the given blob fee
+    /// (`tx.max_fee_per_blob_gas < blob_fee`), along with their descendants.
+    ///
+    /// Note: the transactions are not returned in a particular order.
+    ///
+    /// # Returns
+    ///
+    /// Removed transactions that no longer satisfy the blob fee, and their descendants which may
+    /// still satisfy it.
+    pub(crate) fn update_blob_fee(
+        &mut self,
+        blob_fee: u128,
+    ) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        let mut to_remove = Vec::new();
+        {
+            let mut iter = self.by_id.iter().peekable();
+            while let Some((id, tx)) = iter.next() {
+                if tx.transaction.max_fee_per_blob_gas().is_some_and(|fee| fee < blob_fee) {
+                    to_remove.push(*id);
+
+                    // Remove all dependent transactions.
+                    'this: while let Some((next_id, _)) = iter.peek() {
+                        if next_id.sender != id.sender {
+                            break 'this
+                        }
+                        to_remove.push(**next_id);
+                        iter.next();
+                    }
+                }
+            }
+        }
+
+        to_remove.iter().filter_map(|id| self.remove_transaction(id)).collect()
+    }
+
     /// Returns the ancestor the given transaction, the transaction with `nonce - 1`.
     ///
     /// Note: for a transaction with nonce higher than the current on chain nonce this will always
@@ -146,9 +146,68 @@ impl<T: TransactionOrdering> TxPool<T> {
     }
 
     /// Updates the tracked blob fee
-    fn update_blob_fee(&mut self, _pending_blob_fee: u128) {
-        // TODO: std::mem::swap pending_blob_fee
-        // TODO(mattsse): update blob txs
+    ///
+    /// Depending on the change in direction of the blob fee, this will promote blob transactions
+    /// from the blob pool, or demote them from the pending pool.
+    fn update_blob_fee(&mut self, mut pending_blob_fee: u128) {
+        std::mem::swap(&mut self.all_transactions.pending_blob_fee, &mut pending_blob_fee);
+        match self.all_transactions.pending_blob_fee.cmp(&pending_blob_fee) {
+            Ordering::Equal => {
+                // fee unchanged, nothing to update
+            }
+            Ordering::Greater => {
+                // increased blob fee: recheck pending pool and remove all that are no longer valid
+                let blob_fee = self.all_transactions.pending_blob_fee;
+                let removed = self.pending_pool.update_blob_fee(blob_fee);
+                let removed_ids = removed.iter().map(|tx| *tx.id()).collect::<HashSet<_>>();
+                for tx in removed {
+                    let to = {
+                        let tx =
+                            self.all_transactions.txs.get_mut(tx.id()).expect("tx exists in set");
+                        // only the blob transactions can lack the blob fee, the others were
+                        // removed with their ancestor
+                        if tx.transaction.max_fee_per_blob_gas().is_some_and(|fee| fee < blob_fee) {
+                            tx.state.remove(TxState::ENOUGH_BLOB_FEE_CAP_BLOCK);
+                        }
+                        if tx
+                            .transaction
+                            .id()
+                            .unchecked_ancestor()
+                            .is_some_and(|ancestor| removed_ids.contains(&ancestor))
+                        {
+                            tx.state.remove(TxState::NO_PARKED_ANCESTORS);
+                        }
+                        tx.subpool = tx.state.into();
+                        tx.subpool
+                    };
+                    self.add_transaction_to_subpool(to, tx);
+                }
+            }
+            Ordering::Less => {
+                // decreased blob fee: recheck blob pool and promote all that are now valid
+                self.promote_blob_transactions();
+            }
+        }
+    }
+
+    /// Moves the transactions of the blob pool that satisfy both the pending base fee and blob fee
+    /// to the sub-pool their state now permits, which is the pending pool unless they're parked
+    /// for another reason.
+    fn promote_blob_transactions(&mut self) {
+        let removed = self.blob_transactions.enforce_pending_fees(
+            self.all_transactions.pending_basefee,
+            self.all_transactions.pending_blob_fee,
+        );
+        for tx in removed {
+            let to = {
+                let tx = self.all_transactions.txs.get_mut(tx.id()).expect("tx exists in set");
+                tx.state.insert(TxState::ENOUGH_FEE_CAP_BLOCK);
+                tx.state.insert(TxState::ENOUGH_BLOB_FEE_CAP_BLOCK);
+                tx.subpool = tx.state.into();
+                tx.subpool
+            };
+            self.add_transaction_to_subpool(to, tx);
+        }
     }
 
     /// Updates the tracked basefee
@@ -190,6 +249,8 @@ impl<T: TransactionOrdering> TxPool<T> {
                     };
                     self.add_transaction_to_subpool(to, tx);
                 }
+                // blob transactions parked for their fee cap are in the blob pool instead
+                self.promote_blob_transactions();
             }
         }
     }
@@ -2138,6 +2199,78 @@ mod tests {
         assert_eq!(pool.all_transactions.txs.get(&id).unwrap().subpool, SubPool::BaseFee)
     }
 
+    #[test]
+    fn update_blob_fee_subpools() {
+        let mut f = MockTransactionFactory::default();
+        let mut pool = TxPool::new(MockOrdering::default(), Default::default());
+
+        let tx = MockTransaction::eip4844().inc_price_by(10).with_blob_fee(1_000);
+        let validated = f.validated(tx);
+        let id = *validated.id();
+        pool.add_transaction(validated, U256::MAX, 0).unwrap();
+
+        assert_eq!(pool.pending_pool.len(), 1);
+
+        pool.update_blob_fee(1_001);
+
+        assert!(pool.pending_pool.is_empty());
+        assert_eq!(pool.blob_transactions.len(), 1);
+        assert_eq!(pool.all_transactions.txs.get(&id).unwrap().subpool, SubPool::Blob);
+
+        pool.update_blob_fee(1_000);
+
+        assert_eq!(pool.pending_pool.len(), 1);
+        assert_eq!(pool.blob_transactions.len(), 0);
+        assert_eq!(pool.all_transactions.txs.get(&id).unwrap().subpool, SubPool::Pending);
+        pool.assert_invariants();
+    }
+
+    #[test]
+    fn update_blob_fee_parks_descendants() {
+        let mut f = MockTransactionFactory::default();
+        let mut pool = TxPool::new(MockOrdering::default(), Default::default());
+
+        let blob = MockTransaction::eip4844().inc_price_by(10).with_blob_fee(1_000);
+        let first = MockTransaction::eip1559()
+            .inc_price_by(10)
+            .with_sender(blob.get_sender())
+            .with_nonce(1);
+        let second = first.next();
+        let mut ids = vec![];
+        for tx in [blob, first, second] {
+            let validated = f.validated(tx);
+            ids.push(*validated.id());
+            pool.add_transaction(validated, U256::MAX, 0).unwrap();
+        }
+        assert_eq!(pool.pending_pool.len(), 3);
+
+        pool.update_blob_fee(1_001);
+
+        assert!(pool.pending_pool.is_empty());
+        let blob = pool.all_transactions.txs.get(&ids[0]).unwrap();
+        assert_eq!(blob.subpool, SubPool::Blob);
+        assert!(!blob.state.contains(TxState::ENOUGH_BLOB_FEE_CAP_BLOCK));
+        for id in &ids[1..] {
+            let tx = pool.all_transactions.txs.get(id).unwrap();
+            assert_eq!(tx.subpool, SubPool::Queued);
+            assert!(tx.state.contains(TxState::ENOUGH_BLOB_FEE_CAP_BLOCK));
+            assert!(!tx.state.contains(TxState::NO_PARKED_ANCESTORS));
+        }
+        pool.assert_invariants();
+
+        pool.update_blob_fee(1_000);
+        assert_eq!(pool.pending_pool.len(), 1);
+
+        // the descendants are no longer parked once the state of the sender is updated
+        let info = SenderInfo { state_nonce: 0, balance: U256::MAX };
+        pool.update_accounts(HashMap::from([(ids[0].sender, info)]));
+        assert_eq!(pool.pending_pool.len(), 3);
+        for id in &ids {
+            assert_eq!(pool.all_transactions.txs.get(id).unwrap().subpool, SubPool::Pending);
+        }
+        pool.assert_invariants();
+    }
+
     #[test]
     fn discard_nonce_too_low() {
         let mut f = MockTransactionFactory::default();
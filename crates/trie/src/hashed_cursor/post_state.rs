@@ -2,11 +2,13 @@ use super::{HashedAccountCursor, HashedCursorFactory, HashedStorageCursor};
 use crate::prefix_set::{PrefixSet, PrefixSetMut};
 use reth_db::{
     cursor::{DbCursorRO, DbDupCursorRO},
+    models::{AccountBeforeTx, BlockNumberAddress},
     tables,
     transaction::{DbTx, DbTxGAT},
+    DatabaseError,
 };
-use reth_primitives::{trie::Nibbles, Account, StorageEntry, B256, U256};
-use std::collections::{HashMap, HashSet};
+use reth_primitives::{keccak256, trie::Nibbles, Account, BlockNumber, StorageEntry, B256, U256};
+use std::collections::{hash_map, HashMap, HashSet};
 
 /// The post state account storage with hashed slots.
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -51,6 +53,28 @@ impl HashedStorage {
     pub fn insert_zero_valued_slot(&mut self, slot: B256) {
         self.zero_valued_slots.insert(slot);
     }
+
+    /// Applies the later changes of `other` on top of these, replacing them if `other` wiped the
+    /// storage.
+    pub fn extend(&mut self, other: Self) {
+        if other.wiped {
+            *self = other;
+            return
+        }
+
+        let changed = other
+            .non_zero_valued_storage
+            .iter()
+            .map(|(slot, _)| *slot)
+            .chain(other.zero_valued_slots.iter().copied())
+            .collect::<HashSet<_>>();
+        self.non_zero_valued_storage.retain(|(slot, _)| !changed.contains(slot));
+        self.zero_valued_slots.retain(|slot| !changed.contains(slot));
+
+        self.non_zero_valued_storage.extend(other.non_zero_valued_storage);
+        self.zero_valued_slots.extend(other.zero_valued_slots);
+        self.sorted = false;
+    }
 }
 
 /// The post state with hashed addresses as keys.
@@ -78,6 +102,50 @@ impl Default for HashedPostState {
 }
 
 impl HashedPostState {
+    /// Loads the changes which revert the latest state to the state before the block `from`,
+    /// i.e. the oldest value of every account and storage slot changed in `from` or after.
+    pub fn from_reverts<TX: DbTx>(tx: &TX, from: BlockNumber) -> Result<Self, DatabaseError> {
+        let mut accounts = HashMap::new();
+        let mut account_changeset_cursor = tx.cursor_read::<tables::AccountChangeSet>()?;
+        for entry in account_changeset_cursor.walk_range(from..)? {
+            let (_, AccountBeforeTx { address, info }) = entry?;
+            accounts.entry(address).or_insert(info);
+        }
+
+        let mut storages = HashMap::<_, HashMap<_, _>>::new();
+        let mut storage_changeset_cursor = tx.cursor_dup_read::<tables::StorageChangeSet>()?;
+        for entry in
+            storage_changeset_cursor.walk_range(BlockNumberAddress((from, Default::default()))..)?
+        {
+            let (BlockNumberAddress((_, address)), StorageEntry { key, value }) = entry?;
+            if let hash_map::Entry::Vacant(slot) = storages.entry(address).or_default().entry(key) {
+                slot.insert(value);
+            }
+        }
+
+        let mut state = Self::default();
+        for (address, info) in accounts {
+            let hashed_address = keccak256(address);
+            match info {
+                Some(account) => state.insert_account(hashed_address, account),
+                None => state.insert_cleared_account(hashed_address),
+            }
+        }
+        for (address, slots) in storages {
+            let mut hashed_storage = HashedStorage::new(false);
+            for (slot, value) in slots {
+                let hashed_slot = keccak256(slot);
+                if value == U256::ZERO {
+                    hashed_storage.insert_zero_valued_slot(hashed_slot);
+                } else {
+                    hashed_storage.insert_non_zero_valued_storage(hashed_slot, value);
+                }
+            }
+            state.insert_hashed_storage(keccak256(address), hashed_storage);
+        }
+        Ok(state.sorted())
+    }
+
     /// Sort and return self.
     pub fn sorted(mut self) -> Self {
         self.sort();
@@ -113,10 +181,45 @@ impl HashedPostState {
         self.storages.insert(hashed_address, hashed_storage);
     }
 
+    /// Applies the later changes of `other` on top of these, e.g. the changes of a block on top of
+    /// the reverts to its parent.
+    pub fn extend(&mut self, other: Self) {
+        let changed = other
+            .accounts
+            .iter()
+            .map(|(hashed_address, _)| *hashed_address)
+            .chain(other.cleared_accounts.iter().copied())
+            .collect::<HashSet<_>>();
+        self.accounts.retain(|(hashed_address, _)| !changed.contains(hashed_address));
+        self.cleared_accounts.retain(|hashed_address| !changed.contains(hashed_address));
+        self.accounts.extend(other.accounts);
+        self.cleared_accounts.extend(other.cleared_accounts);
+
+        for (hashed_address, storage) in other.storages {
+            match self.storages.entry(hashed_address) {
+                hash_map::Entry::Occupied(mut entry) => entry.get_mut().extend(storage),
+                hash_map::Entry::Vacant(entry) => {
+                    entry.insert(storage);
+                }
+            }
+        }
+        self.sorted = false;
+    }
+
     /// Construct (PrefixSet)[PrefixSet] from hashed post state.
     /// The prefix sets contain the hashed account and storage keys that have been changed in the
     /// post state.
     pub fn construct_prefix_sets(&self) -> (PrefixSet, HashMap<B256, PrefixSet>) {
+        let (account_prefix_set, storage_prefix_set) = self.construct_mut_prefix_sets();
+        (
+            account_prefix_set.freeze(),
+            storage_prefix_set.into_iter().map(|(k, v)| (k, v.freeze())).collect(),
+        )
+    }
+
+    /// Same as [HashedPostState::construct_prefix_sets], but the prefix sets can still be
+    /// extended.
+    pub(crate) fn construct_mut_prefix_sets(&self) -> (PrefixSetMut, HashMap<B256, PrefixSetMut>) {
         // Initialize prefix sets.
         let mut account_prefix_set = PrefixSetMut::default();
         let mut storage_prefix_set: HashMap<B256, PrefixSetMut> = HashMap::default();
@@ -142,10 +245,7 @@ impl HashedPostState {
             }
         }
 
-        (
-            account_prefix_set.freeze(),
-            storage_prefix_set.into_iter().map(|(k, v)| (k, v.freeze())).collect(),
-        )
+        (account_prefix_set, storage_prefix_set)
     }
 }
 
@@ -976,6 +1076,48 @@ mod tests {
         assert_storage_cursor_order(&factory, expected);
     }
 
+    #[test]
+    fn extend_post_state() {
+        let (a, b, c) = (B256::with_last_byte(1), B256::with_last_byte(2), B256::with_last_byte(3));
+        let account = |nonce| Account { nonce, ..Default::default() };
+        let storage = |wiped, slots: &[(B256, u64)]| {
+            let mut storage = HashedStorage::new(wiped);
+            for (slot, value) in slots {
+                if *value == 0 {
+                    storage.insert_zero_valued_slot(*slot);
+                } else {
+                    storage.insert_non_zero_valued_storage(*slot, U256::from(*value));
+                }
+            }
+            storage
+        };
+
+        let mut state = HashedPostState::default();
+        state.insert_account(a, account(1));
+        state.insert_cleared_account(b);
+        state.insert_hashed_storage(a, storage(false, &[(a, 1), (b, 2)]));
+        state.insert_hashed_storage(b, storage(false, &[(a, 1)]));
+
+        let mut later = HashedPostState::default();
+        later.insert_account(b, account(2));
+        later.insert_cleared_account(a);
+        later.insert_account(c, account(3));
+        later.insert_hashed_storage(a, storage(false, &[(b, 0), (c, 3)]));
+        later.insert_hashed_storage(b, storage(true, &[(b, 2)]));
+
+        state.extend(later);
+        state.sort();
+        assert_eq!(state.accounts, [(b, account(2)), (c, account(3))]);
+        assert_eq!(state.cleared_accounts, HashSet::from([a]));
+        assert_eq!(
+            state.storages[&a].non_zero_valued_storage,
+            [(a, U256::from(1)), (c, U256::from(3))]
+        );
+        assert_eq!(state.storages[&a].zero_valued_slots, HashSet::from([b]));
+        assert!(state.storages[&b].wiped);
+        assert_eq!(state.storages[&b].non_zero_valued_storage, [(b, U256::from(2))]);
+    }
+
     #[test]
     fn fuzz_hashed_storage_cursor() {
         proptest!(ProptestConfig::with_cases(10),
@@ -1,6 +1,8 @@
 use crate::{
     account::EthAccount,
-    hashed_cursor::{HashedCursorFactory, HashedStorageCursor},
+    hashed_cursor::{
+        HashedCursorFactory, HashedPostState, HashedPostStateCursorFactory, HashedStorageCursor,
+    },
     node_iter::{AccountNode, AccountNodeIter, StorageNode, StorageNodeIter},
     prefix_set::PrefixSetMut,
     trie_cursor::{AccountTrieCursor, StorageTrieCursor},
@@ -15,6 +17,7 @@ use reth_primitives::{
     trie::{AccountProof, HashBuilder, Nibbles, StorageProof},
     Address, B256,
 };
+use std::collections::HashMap;
 
 /// A struct for generating merkle proofs.
 ///
@@ -27,12 +30,40 @@ pub struct Proof<'a, TX, H> {
     tx: &'a TX,
     /// The factory for hashed cursors.
     hashed_cursor_factory: H,
+    /// A set of account prefixes whose intermediate nodes in the database are out of date.
+    changed_account_prefixes: PrefixSetMut,
+    /// A map containing the storage prefixes whose intermediate nodes in the database are out of
+    /// date, with the hashed address as key.
+    changed_storage_prefixes: HashMap<B256, PrefixSetMut>,
 }
 
 impl<'a, TX> Proof<'a, TX, &'a TX> {
     /// Create a new [Proof] instance.
     pub fn new(tx: &'a TX) -> Self {
-        Self { tx, hashed_cursor_factory: tx }
+        Self {
+            tx,
+            hashed_cursor_factory: tx,
+            changed_account_prefixes: PrefixSetMut::default(),
+            changed_storage_prefixes: HashMap::default(),
+        }
+    }
+}
+
+impl<'a, 'b, TX> Proof<'a, TX, HashedPostStateCursorFactory<'a, 'b, TX>> {
+    /// Create a new [Proof] instance for the state of the database overlayed with `post_state`,
+    /// e.g. the reverts to a historical block.
+    ///
+    /// The intermediate nodes of the changed accounts and slots are recomputed from the hashed
+    /// state, since the ones in the database don't account for the post state.
+    pub fn overlay(tx: &'a TX, post_state: &'b HashedPostState) -> Self {
+        let (changed_account_prefixes, changed_storage_prefixes) =
+            post_state.construct_mut_prefix_sets();
+        Self {
+            tx,
+            hashed_cursor_factory: HashedPostStateCursorFactory::new(tx, post_state),
+            changed_account_prefixes,
+            changed_storage_prefixes,
+        }
     }
 }
 
@@ -55,7 +86,7 @@ where
         let trie_cursor = AccountTrieCursor::new(self.tx.cursor_read::<tables::AccountsTrie>()?);
 
         // Create the walker.
-        let mut prefix_set = PrefixSetMut::default();
+        let mut prefix_set = self.changed_account_prefixes.clone();
         prefix_set.insert(target_nibbles.clone());
         let walker = TrieWalker::new(trie_cursor, prefix_set.freeze());
 
@@ -119,7 +150,12 @@ where
         }
 
         let target_nibbles = proofs.iter().map(|p| p.nibbles.clone()).collect::<Vec<_>>();
-        let prefix_set = PrefixSetMut::from(target_nibbles.clone()).freeze();
+        let mut prefix_set =
+            self.changed_storage_prefixes.get(&hashed_address).cloned().unwrap_or_default();
+        for nibbles in &target_nibbles {
+            prefix_set.insert(nibbles.clone());
+        }
+        let prefix_set = prefix_set.freeze();
         let trie_cursor = StorageTrieCursor::new(
             self.tx.cursor_dup_read::<tables::StoragesTrie>()?,
             hashed_address,
@@ -166,7 +202,10 @@ mod tests {
     use super::*;
     use crate::StateRoot;
     use once_cell::sync::Lazy;
-    use reth_db::{database::Database, test_utils::create_test_rw_db};
+    use reth_db::{
+        database::Database, models::AccountBeforeTx, test_utils::create_test_rw_db,
+        transaction::DbTxMut,
+    };
     use reth_interfaces::RethResult;
     use reth_primitives::{Account, Bytes, Chain, ChainSpec, StorageEntry, HOLESKY, MAINNET, U256};
     use reth_provider::{HashingWriter, ProviderFactory};
@@ -438,4 +477,50 @@ mod tests {
         let account_proof = Proof::new(&tx).account_proof(target, &slots).unwrap();
         pretty_assertions::assert_eq!(account_proof, expected);
     }
+
+    #[test]
+    fn overlay_reverts_proof() {
+        // Create test database and insert genesis accounts.
+        let db = create_test_rw_db();
+        insert_genesis(db.clone(), TEST_SPEC.clone()).unwrap();
+
+        // The two accounts share the intermediate nodes of the prefix `0xa7`.
+        let changed = Address::from_str("0x2031f89b3ea8014eb51a78c316e42af3e0d7695f").unwrap();
+        let sibling = Address::from_str("0x33f0fc440b8477fcfbe9d0bf8649e7dea9baedb2").unwrap();
+        let tx = db.tx().unwrap();
+        let expected =
+            [changed, sibling].map(|address| Proof::new(&tx).account_proof(address, &[]).unwrap());
+        drop(tx);
+
+        // Change the balance of the account in block 1, keeping its previous state in the
+        // changeset.
+        let provider_factory = ProviderFactory::new(db.clone(), TEST_SPEC.clone());
+        let mut provider = provider_factory.provider_rw().unwrap();
+        let account = expected[0].info.unwrap();
+        provider
+            .tx_ref()
+            .put::<tables::AccountChangeSet>(
+                1,
+                AccountBeforeTx { address: changed, info: Some(account) },
+            )
+            .unwrap();
+        provider
+            .insert_account_for_hashing([(
+                changed,
+                Some(Account { balance: U256::from(1), ..account }),
+            )])
+            .unwrap();
+        let (_, updates) = StateRoot::new(provider.tx_ref()).root_with_updates().unwrap();
+        updates.flush(provider.tx_mut()).unwrap();
+        provider.commit().unwrap();
+
+        let tx = db.tx().unwrap();
+        assert_ne!(Proof::new(&tx).account_proof(sibling, &[]).unwrap(), expected[1]);
+
+        let reverts = HashedPostState::from_reverts(&tx, 1).unwrap();
+        for (address, expected) in [changed, sibling].into_iter().zip(expected) {
+            let account_proof = Proof::overlay(&tx, &reverts).account_proof(address, &[]).unwrap();
+            pretty_assertions::assert_eq!(account_proof, expected);
+        }
+    }
 }